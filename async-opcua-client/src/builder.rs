@@ -166,7 +166,8 @@ impl ClientBuilder {
         self
     }
 
-    /// Sets the session retry limit.
+    /// Sets the session retry limit, the maximum number of reconnect attempts
+    /// before giving up. Use `-1` to retry forever, and `0` to never retry.
     ///
     /// # Panics
     ///
@@ -191,6 +192,21 @@ impl ClientBuilder {
         self
     }
 
+    /// Factor the time between retries is multiplied by after each failed
+    /// session reconnect. Must be at least 1.
+    pub fn session_retry_multiplier(mut self, session_retry_multiplier: f64) -> Self {
+        self.config.session_retry_multiplier = session_retry_multiplier;
+        self
+    }
+
+    /// Fraction of random jitter applied to the time between retries on session
+    /// reconnects, between 0 and 1. A value of `0.25` means that each delay is randomly
+    /// chosen between 75% and 125% of the computed delay.
+    pub fn session_retry_jitter(mut self, session_retry_jitter: f64) -> Self {
+        self.config.session_retry_jitter = session_retry_jitter;
+        self
+    }
+
     /// Time between making simple Read requests to the server to check for liveness
     /// and avoid session timeouts.
     pub fn keep_alive_interval(mut self, keep_alive_interval: Duration) -> Self {
@@ -250,7 +266,19 @@ impl ClientBuilder {
         self
     }
 
-    /// Set the timeout on requests sent to the server.
+    /// Only send keep-alive requests when nothing has been received from the server
+    /// during the last keep-alive interval. Any response from the server shows that
+    /// the connection is alive, so this avoids extra requests on busy sessions.
+    pub fn keep_alive_only_when_idle(mut self, keep_alive_only_when_idle: bool) -> Self {
+        self.config.keep_alive_only_when_idle = keep_alive_only_when_idle;
+        self
+    }
+
+    /// Set the default timeout on requests sent to the server. This is used by all
+    /// service calls unless overridden with `timeout` on the individual request.
+    ///
+    /// If the server does not respond in time the request fails with `BadTimeout`,
+    /// even if the server has stopped reading from the connection.
     pub fn request_timeout(mut self, request_timeout: Duration) -> Self {
         self.config.request_timeout = request_timeout;
         self
@@ -269,6 +297,23 @@ impl ClientBuilder {
         self
     }
 
+    /// Set the maximum number of republish requests the client will send to recover
+    /// missed notifications when it detects a gap in the sequence numbers of a subscription.
+    /// If the gap is larger than this, the remaining notifications are lost.
+    /// Set to 0 to disable automatic recovery.
+    pub fn max_republish_attempts(mut self, max_republish_attempts: usize) -> Self {
+        self.config.max_republish_attempts = max_republish_attempts;
+        self
+    }
+
+    /// Set whether the tokio tasks spawned by the client should be given
+    /// descriptive names. Names are only visible to tools like `tokio-console`
+    /// when compiled with `--cfg tokio_unstable`.
+    pub fn named_tasks(mut self, named_tasks: bool) -> Self {
+        self.config.named_tasks = named_tasks;
+        self
+    }
+
     /// Sets whether the client should ignore clock skew so the client can make a successful
     /// connection to the server, even when the client and server clocks are out of sync.
     pub fn ignore_clock_skew(mut self, ignore_clock_skew: bool) -> Self {
@@ -284,6 +329,21 @@ impl ClientBuilder {
         self
     }
 
+    /// Maximum number of read requests to send concurrently when
+    /// [`crate::Session::read_all`] splits a read into multiple batches.
+    pub fn max_concurrent_reads(mut self, max_concurrent_reads: usize) -> Self {
+        self.config.performance.max_concurrent_reads = max_concurrent_reads;
+        self
+    }
+
+    /// Maximum number of chunk buffers kept for reuse by each connection, instead of
+    /// allocating new buffers for every chunk sent or received. This reduces allocations
+    /// under high message rates. The default is 0, which disables buffer pooling.
+    pub fn max_pooled_buffers(mut self, max_pooled_buffers: usize) -> Self {
+        self.config.performance.max_pooled_buffers = max_pooled_buffers;
+        self
+    }
+
     /// Automatically recreate subscriptions on reconnect, by first calling
     /// [`crate::Session::transfer_subscriptions`], then attempting to recreate
     /// subscriptions if that fails.
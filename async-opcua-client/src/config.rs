@@ -16,7 +16,7 @@ use chrono::TimeDelta;
 use serde::{Deserialize, Serialize};
 use tracing::warn;
 
-use opcua_core::config::Config;
+use opcua_core::{comms::buffer_pool::BufferPool, config::Config};
 use opcua_crypto::SecurityPolicy;
 use opcua_types::{
     ApplicationType, EndpointDescription, Error, MessageSecurityMode, StatusCode, UAString,
@@ -207,6 +207,14 @@ pub(crate) struct Performance {
     /// Maximum number of monitored items per request when recreating subscriptions on session recreation.
     #[serde(default = "defaults::recreate_monitored_items_chunk")]
     pub(crate) recreate_monitored_items_chunk: usize,
+    /// Maximum number of read requests to have in flight at the same time when
+    /// splitting a large read into batches.
+    #[serde(default = "defaults::max_concurrent_reads")]
+    pub(crate) max_concurrent_reads: usize,
+    /// Maximum number of chunk buffers kept for reuse by a connection, instead of
+    /// allocating new buffers for each chunk. 0 disables buffer pooling.
+    #[serde(default)]
+    pub(crate) max_pooled_buffers: usize,
 }
 
 impl Default for Performance {
@@ -214,6 +222,8 @@ impl Default for Performance {
         Self {
             ignore_clock_skew: false,
             recreate_monitored_items_chunk: defaults::recreate_monitored_items_chunk(),
+            max_concurrent_reads: defaults::max_concurrent_reads(),
+            max_pooled_buffers: 0,
         }
     }
 }
@@ -260,7 +270,7 @@ pub struct ClientConfig {
     #[serde(default)]
     pub(crate) decoding_options: DecodingOptions,
     /// Maximum number of times to attempt to reconnect to the server before giving up.
-    /// -1 retries forever
+    /// -1 retries forever, 0 never retries.
     #[serde(default = "defaults::session_retry_limit")]
     pub(crate) session_retry_limit: i32,
 
@@ -270,6 +280,13 @@ pub struct ClientConfig {
     /// Max delay between retry attempts.
     #[serde(default = "defaults::session_retry_max")]
     pub(crate) session_retry_max: Duration,
+    /// Factor the delay between retry attempts is multiplied by after each attempt.
+    #[serde(default = "defaults::session_retry_multiplier")]
+    pub(crate) session_retry_multiplier: f64,
+    /// Fraction of random jitter applied to the delay between retry attempts,
+    /// between 0 and 1.
+    #[serde(default)]
+    pub(crate) session_retry_jitter: f64,
     /// Interval between each keep-alive request sent to the server.
     #[serde(default = "defaults::keep_alive_interval")]
     pub(crate) keep_alive_interval: Duration,
@@ -279,8 +296,14 @@ pub struct ClientConfig {
     /// forcibly reset.
     #[serde(default = "defaults::max_failed_keep_alive_count")]
     pub(crate) max_failed_keep_alive_count: u64,
+    /// Only send keep-alive requests when nothing has been received from the server
+    /// during the last keep-alive interval.
+    #[serde(default)]
+    pub(crate) keep_alive_only_when_idle: bool,
 
-    /// Timeout for each request sent to the server.
+    /// Default timeout for each request sent to the server, used by all service calls
+    /// unless a timeout is set on the request itself. Requests without a response
+    /// within this time fail with `BadTimeout`, and any late response is discarded.
     #[serde(default = "defaults::request_timeout")]
     pub(crate) request_timeout: Duration,
     /// Timeout for publish requests, separate from normal timeout since
@@ -291,6 +314,16 @@ pub struct ClientConfig {
     /// publish together, which may reduce the number of publish requests if you have a lot of subscriptions.
     #[serde(default = "defaults::min_publish_interval")]
     pub(crate) min_publish_interval: Duration,
+    /// Maximum number of republish requests sent to recover notifications
+    /// when a gap in subscription sequence numbers is detected.
+    /// Set to 0 to disable automatic recovery of missing notifications.
+    #[serde(default = "defaults::max_republish_attempts")]
+    pub(crate) max_republish_attempts: usize,
+    /// Give descriptive names to the tokio tasks spawned by the client, including
+    /// the session name and endpoint. Names are visible in tools like `tokio-console`
+    /// when compiled with `--cfg tokio_unstable`.
+    #[serde(default)]
+    pub(crate) named_tasks: bool,
 
     /// Client performance settings
     #[serde(default)]
@@ -368,6 +401,18 @@ impl Config for ClientConfig {
         if self.session_retry_limit < 0 && self.session_retry_limit != -1 {
             errors.push(format!("Session retry limit of {} is invalid - must be -1 (infinite), 0 (never) or a positive value", self.session_retry_limit));
         }
+        if self.session_retry_multiplier.is_nan() || self.session_retry_multiplier < 1.0 {
+            errors.push(format!(
+                "Session retry multiplier of {} is invalid - must be at least 1",
+                self.session_retry_multiplier
+            ));
+        }
+        if !(0.0..=1.0).contains(&self.session_retry_jitter) {
+            errors.push(format!(
+                "Session retry jitter of {} is invalid - must be between 0 and 1",
+                self.session_retry_jitter
+            ));
+        }
         if errors.is_empty() {
             Ok(())
         } else {
@@ -404,6 +449,19 @@ impl ClientConfig {
             },
             self.session_retry_initial,
         )
+        .multiplier(self.session_retry_multiplier)
+        .jitter_fraction(self.session_retry_jitter)
+    }
+
+    /// Create a buffer pool for the chunk buffers of a connection, as configured
+    /// by `max_pooled_buffers`.
+    pub(crate) fn buffer_pool(&self) -> BufferPool {
+        BufferPool::new(
+            self.performance.max_pooled_buffers,
+            self.decoding_options
+                .max_chunk_size
+                .max(self.decoding_options.max_incoming_chunk_size),
+        )
     }
 
     /// Returns an identity token corresponding to the matching user in the configuration. Or None
@@ -510,6 +568,10 @@ mod defaults {
         Duration::from_secs(30)
     }
 
+    pub(super) fn session_retry_multiplier() -> f64 {
+        SessionRetryPolicy::DEFAULT_MULTIPLIER
+    }
+
     pub(super) fn keep_alive_interval() -> Duration {
         Duration::from_secs(10)
     }
@@ -558,10 +620,18 @@ mod defaults {
         Duration::from_millis(100)
     }
 
+    pub(super) fn max_republish_attempts() -> usize {
+        10
+    }
+
     pub(super) fn recreate_monitored_items_chunk() -> usize {
         1000
     }
 
+    pub(super) fn max_concurrent_reads() -> usize {
+        4
+    }
+
     pub(super) fn recreate_subscriptions() -> bool {
         true
     }
@@ -603,11 +673,16 @@ impl ClientConfig {
             session_retry_limit: defaults::session_retry_limit(),
             session_retry_initial: defaults::session_retry_initial(),
             session_retry_max: defaults::session_retry_max(),
+            session_retry_multiplier: defaults::session_retry_multiplier(),
+            session_retry_jitter: 0.0,
             keep_alive_interval: defaults::keep_alive_interval(),
             max_failed_keep_alive_count: defaults::max_failed_keep_alive_count(),
+            keep_alive_only_when_idle: false,
             request_timeout: defaults::request_timeout(),
             publish_timeout: defaults::publish_timeout(),
             min_publish_interval: defaults::min_publish_interval(),
+            max_republish_attempts: defaults::max_republish_attempts(),
+            named_tasks: false,
             performance: Performance::default(),
             recreate_subscriptions: defaults::recreate_subscriptions(),
             session_name: "Rust OPC UA Client".into(),
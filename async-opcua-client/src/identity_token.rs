@@ -7,9 +7,16 @@ use opcua_types::{ByteString, Error, StatusCode};
 #[async_trait]
 /// Source for an issued token. Since each re-authentication when using
 /// issued tokens may require a new token.
+///
+/// The session requests a token from the source every time it is activated,
+/// including when an existing session is reactivated after the connection was lost,
+/// so an expired token can be replaced by returning a new one.
 pub trait IssuedTokenSource: Send + Sync {
     /// Get a valid issued token. This may be a cached token,
     /// or a new one if the cache is empty or expired.
+    ///
+    /// The token is encrypted according to the user token policy of the endpoint
+    /// before it is sent to the server.
     async fn get_issued_token(&self) -> Result<ByteString, Error>;
 }
 
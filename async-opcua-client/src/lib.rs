@@ -124,13 +124,18 @@ pub use builder::ClientBuilder;
 pub use config::{ClientConfig, ClientEndpoint, ClientUserToken, ANONYMOUS_USER_TOKEN_ID};
 pub use retry::{ExponentialBackoff, SessionRetryPolicy};
 pub use session::{
-    Client, DataChangeCallback, DefaultRetryPolicy, EventCallback, HistoryReadAction,
-    HistoryUpdateAction, MonitoredItem, OnSubscriptionNotification, OnSubscriptionNotificationCore,
-    RequestRetryPolicy, Session, SessionActivity, SessionBuilder, SessionConnectMode,
-    SessionEventLoop, SessionPollResult, Subscription, SubscriptionActivity, SubscriptionCallbacks,
-    UARequest,
+    AuditEventSession, Client, ConnectBestError, ConnectionState, DataChangeCallback,
+    DefaultRetryPolicy, EventCallback, HistoryReadAction, HistoryUpdateAction, IntoMethodArguments,
+    MethodCallError, MonitoredItem, OnSubscriptionNotification, OnSubscriptionNotificationCore,
+    OperationLimit, OperationLimits, PublishStats, RequestRetryPolicy, ResolvedTarget, Session,
+    SessionActivity, SessionBuilder, SessionConnectMode, SessionEventLoop, SessionPollResult,
+    StreamBackpressure, Subscription, SubscriptionActivity, SubscriptionCallbacks,
+    SubscriptionNotification, SubscriptionStateSnapshot, SubscriptionStatusAction,
+    SubscriptionStream, SubscriptionStreamSender, UARequest,
+};
+pub use transport::{
+    AsyncSecureChannel, ChannelPool, PooledSessionBuilder, SecureChannelEvent, SecureChannelTokenId,
 };
-pub use transport::AsyncSecureChannel;
 
 pub mod services {
     //! This module contains request builders for most OPC-UA services.
@@ -143,9 +148,9 @@ pub mod services {
         ActivateSession, AddNodes, AddReferences, Browse, BrowseNext, Call, Cancel, CloseSession,
         CreateMonitoredItems, CreateSession, CreateSubscription, DeleteMonitoredItems, DeleteNodes,
         DeleteReferences, DeleteSubscriptions, HistoryRead, HistoryUpdate, ModifyMonitoredItems,
-        ModifySubscription, Publish, Read, RegisterNodes, Republish, SetMonitoringMode,
-        SetPublishingMode, SetTriggering, TransferSubscriptions, TranslateBrowsePaths,
-        UnregisterNodes, Write,
+        ModifySubscription, Publish, QueryFirst, QueryNext, Read, RegisterNodes, Republish,
+        SetMonitoringMode, SetPublishingMode, SetTriggering, TransferSubscriptions,
+        TranslateBrowsePaths, UnregisterNodes, Write,
     };
 }
 
@@ -1,5 +1,7 @@
 use std::time::Duration;
 
+use rand::Rng;
+
 #[derive(Debug, Clone)]
 /// A type implementing [`Iterator<Item = Option<Duration>`] with simple exponential backoff.
 pub struct ExponentialBackoff {
@@ -7,6 +9,8 @@ pub struct ExponentialBackoff {
     max_retries: Option<u32>,
     current_sleep: Duration,
     retry_count: u32,
+    multiplier: f64,
+    jitter_fraction: f64,
 }
 
 impl ExponentialBackoff {
@@ -17,7 +21,37 @@ impl ExponentialBackoff {
             max_retries,
             current_sleep: initial_sleep,
             retry_count: 0,
+            multiplier: 2.0,
+            jitter_fraction: 0.0,
+        }
+    }
+
+    /// Set the factor the delay is multiplied by after each retry. Default is 2.
+    /// Values less than 1 are treated as 1. Non-finite values are ignored.
+    pub fn with_multiplier(mut self, multiplier: f64) -> Self {
+        if multiplier.is_finite() {
+            self.multiplier = multiplier.max(1.0);
         }
+        self
+    }
+
+    /// Multiply `sleep` by `factor`, saturating at the max delay.
+    fn scale(&self, sleep: Duration, factor: f64) -> Duration {
+        Duration::try_from_secs_f64(sleep.as_secs_f64() * factor)
+            .map_or(self.max_sleep, |d| d.min(self.max_sleep))
+    }
+
+    /// Set the fraction of random jitter applied to each delay. A value of `0.25`
+    /// means that each delay is randomly chosen between 75% and 125% of the
+    /// computed delay, capped at the max delay. Default is 0, meaning no jitter.
+    /// The value is clamped to between 0 and 1.
+    pub fn with_jitter(mut self, jitter_fraction: f64) -> Self {
+        self.jitter_fraction = if jitter_fraction.is_nan() {
+            0.0
+        } else {
+            jitter_fraction.clamp(0.0, 1.0)
+        };
+        self
     }
 }
 
@@ -29,10 +63,16 @@ impl Iterator for ExponentialBackoff {
             return None;
         }
 
-        let next_sleep = self.current_sleep;
-        self.current_sleep = self.max_sleep.min(self.current_sleep * 2);
+        let mut next_sleep = self.current_sleep;
+        self.current_sleep = self.scale(self.current_sleep, self.multiplier);
         self.retry_count += 1;
 
+        if self.jitter_fraction > 0.0 {
+            let factor =
+                1.0 + rand::thread_rng().gen_range(-self.jitter_fraction..=self.jitter_fraction);
+            next_sleep = self.scale(next_sleep, factor);
+        }
+
         Some(next_sleep)
     }
 }
@@ -42,10 +82,14 @@ impl Iterator for ExponentialBackoff {
 ///
 /// Configure the maximum delay between reconnect attempts, the
 /// initial delay between reconnect attempts, and the maximum number of reconnect attempts.
+/// The delay is multiplied by [`SessionRetryPolicy::multiplier`] after each attempt, and
+/// may be randomized using [`SessionRetryPolicy::jitter_fraction`].
 pub struct SessionRetryPolicy {
     reconnect_max_sleep: Duration,
     reconnect_retry_limit: Option<u32>,
     reconnect_initial_sleep: Duration,
+    reconnect_multiplier: f64,
+    reconnect_jitter_fraction: f64,
 }
 
 impl Default for SessionRetryPolicy {
@@ -54,6 +98,8 @@ impl Default for SessionRetryPolicy {
             reconnect_max_sleep: Duration::from_millis(Self::DEFAULT_MAX_SLEEP_MS),
             reconnect_retry_limit: Some(Self::DEFAULT_RETRY_LIMIT),
             reconnect_initial_sleep: Duration::from_millis(Self::DEFAULT_INITIAL_SLEEP_MS),
+            reconnect_multiplier: Self::DEFAULT_MULTIPLIER,
+            reconnect_jitter_fraction: 0.0,
         }
     }
 }
@@ -65,22 +111,66 @@ impl SessionRetryPolicy {
     pub const DEFAULT_INITIAL_SLEEP_MS: u64 = 500;
     /// Default maximum delay between requests in milliseconds.
     pub const DEFAULT_MAX_SLEEP_MS: u64 = 30000;
+    /// Default factor the delay is multiplied by after each attempt.
+    pub const DEFAULT_MULTIPLIER: f64 = 2.0;
 
     /// Create a new session retry policy.
+    ///
+    /// `retry_limit` is the maximum number of reconnect attempts, `None` retries forever
+    /// and `Some(0)` never retries.
     pub fn new(max_sleep: Duration, retry_limit: Option<u32>, initial_sleep: Duration) -> Self {
         Self {
             reconnect_max_sleep: max_sleep,
             reconnect_retry_limit: retry_limit,
             reconnect_initial_sleep: initial_sleep,
+            ..Default::default()
         }
     }
 
+    /// Set the maximum number of reconnect attempts before giving up.
+    /// `None` retries forever, and `Some(0)` never retries.
+    pub fn retry_limit(mut self, retry_limit: Option<u32>) -> Self {
+        self.reconnect_retry_limit = retry_limit;
+        self
+    }
+
+    /// Set the delay before the first reconnect attempt.
+    pub fn initial_interval(mut self, initial_interval: Duration) -> Self {
+        self.reconnect_initial_sleep = initial_interval;
+        self
+    }
+
+    /// Set the maximum delay between reconnect attempts.
+    pub fn max_interval(mut self, max_interval: Duration) -> Self {
+        self.reconnect_max_sleep = max_interval;
+        self
+    }
+
+    /// Set the factor the delay is multiplied by after each reconnect attempt.
+    /// Values less than 1 are treated as 1, i.e. a constant delay. Non-finite values
+    /// are ignored.
+    pub fn multiplier(mut self, multiplier: f64) -> Self {
+        self.reconnect_multiplier = multiplier;
+        self
+    }
+
+    /// Set the fraction of random jitter applied to the delay between reconnect attempts.
+    /// A value of `0.25` means that each delay is randomly chosen between 75% and 125%
+    /// of the computed delay, capped at the max interval. Use this to avoid many clients
+    /// reconnecting at the same time. The value is clamped to between 0 and 1.
+    pub fn jitter_fraction(mut self, jitter_fraction: f64) -> Self {
+        self.reconnect_jitter_fraction = jitter_fraction;
+        self
+    }
+
     pub(crate) fn new_backoff(&self) -> ExponentialBackoff {
         ExponentialBackoff::new(
             self.reconnect_max_sleep,
             self.reconnect_retry_limit,
             self.reconnect_initial_sleep,
         )
+        .with_multiplier(self.reconnect_multiplier)
+        .with_jitter(self.reconnect_jitter_fraction)
     }
 
     /// Retry forever with the given max delay and initial delay.
@@ -89,6 +179,7 @@ impl SessionRetryPolicy {
             reconnect_initial_sleep: initial_sleep,
             reconnect_retry_limit: None,
             reconnect_max_sleep: max_sleep,
+            ..Default::default()
         }
     }
 
@@ -141,6 +232,89 @@ mod tests {
         assert_eq!(Some(Duration::from_millis(3000)), backoff.next());
     }
 
+    #[test]
+    fn session_retry_limit() {
+        let policy = SessionRetryPolicy::default().retry_limit(Some(0));
+        assert_eq!(None, policy.new_backoff().next());
+
+        let policy = SessionRetryPolicy::default().retry_limit(Some(2));
+        assert_eq!(2, policy.new_backoff().count());
+
+        let policy = SessionRetryPolicy::never().retry_limit(None);
+        assert_eq!(100, policy.new_backoff().take(100).count());
+    }
+
+    #[test]
+    fn session_retry_multiplier() {
+        let policy = SessionRetryPolicy::new(
+            Duration::from_millis(5000),
+            Some(5),
+            Duration::from_millis(100),
+        )
+        .multiplier(3.0);
+        let delays: Vec<_> = policy.new_backoff().collect();
+        assert_eq!(
+            delays,
+            [100, 300, 900, 2700, 5000].map(Duration::from_millis)
+        );
+
+        // Multiplier of 1 gives a constant delay.
+        let policy =
+            SessionRetryPolicy::infinity(Duration::from_millis(5000), Duration::from_millis(100))
+                .multiplier(1.0);
+        assert!(policy
+            .new_backoff()
+            .take(100)
+            .all(|d| d == Duration::from_millis(100)));
+    }
+
+    #[test]
+    fn session_retry_large_multiplier() {
+        // Delays saturate at the max interval instead of overflowing.
+        let policy =
+            SessionRetryPolicy::infinity(Duration::from_secs(60), Duration::from_millis(100))
+                .multiplier(f64::MAX);
+        let delays: Vec<_> = policy.new_backoff().take(3).collect();
+        assert_eq!(delays, [100, 60_000, 60_000].map(Duration::from_millis));
+
+        let policy =
+            SessionRetryPolicy::infinity(Duration::MAX, Duration::from_secs(1)).multiplier(1e300);
+        assert!(policy
+            .new_backoff()
+            .take(5)
+            .all(|d| d >= Duration::from_secs(1)));
+
+        // Non-finite multipliers are ignored.
+        for multiplier in [f64::INFINITY, f64::NAN] {
+            let policy = SessionRetryPolicy::new(
+                Duration::from_millis(5000),
+                Some(3),
+                Duration::from_millis(100),
+            )
+            .multiplier(multiplier);
+            let delays: Vec<_> = policy.new_backoff().collect();
+            assert_eq!(delays, [100, 200, 400].map(Duration::from_millis));
+        }
+    }
+
+    #[test]
+    fn session_retry_jitter() {
+        let policy =
+            SessionRetryPolicy::infinity(Duration::from_millis(3000), Duration::from_millis(1000))
+                .jitter_fraction(0.5);
+
+        let mut backoff = policy.new_backoff();
+        let first = backoff.next().unwrap();
+        assert!(first >= Duration::from_millis(500) && first <= Duration::from_millis(1500));
+        let second = backoff.next().unwrap();
+        assert!(second >= Duration::from_millis(1000) && second <= Duration::from_millis(3000));
+        // Jitter never exceeds the max interval.
+        for _ in 0..100 {
+            let d = backoff.next().unwrap();
+            assert!(d >= Duration::from_millis(1500) && d <= Duration::from_millis(3000));
+        }
+    }
+
     #[test]
     fn session_retry_never() {
         let policy = SessionRetryPolicy::never();
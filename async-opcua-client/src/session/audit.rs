@@ -0,0 +1,71 @@
+use opcua_types::{NodeId, ObjectTypeId, SimpleAttributeOperand, UAString, Variant};
+
+use super::Session;
+
+/// Fields of an audit event used to correlate the event with a session.
+///
+/// To use this, add the operands from [`AuditEventSession::select_clauses`] to the
+/// select clauses of an `EventFilter` on a monitored item for audit events, then
+/// parse the received event fields using [`AuditEventSession::from_event_fields`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AuditEventSession {
+    /// The `SourceNode` of the event. For audit events raised by session
+    /// services this is the ID of the session.
+    pub source_node: NodeId,
+    /// The `ClientUserId` of the event, identifying the user of the client
+    /// that caused the event.
+    pub client_user_id: UAString,
+    /// The `SessionId` of the event. This is only set for events
+    /// derived from `AuditSessionEventType`.
+    pub session_id: NodeId,
+}
+
+impl AuditEventSession {
+    /// Number of select clauses returned by [`AuditEventSession::select_clauses`].
+    pub const NUM_SELECT_CLAUSES: usize = 3;
+
+    /// Get the select clauses needed to populate this struct, in the order
+    /// expected by [`AuditEventSession::from_event_fields`].
+    pub fn select_clauses() -> Vec<SimpleAttributeOperand> {
+        vec![
+            SimpleAttributeOperand::new_value(ObjectTypeId::BaseEventType, "SourceNode"),
+            SimpleAttributeOperand::new_value(ObjectTypeId::AuditEventType, "ClientUserId"),
+            SimpleAttributeOperand::new_value(ObjectTypeId::AuditSessionEventType, "SessionId"),
+        ]
+    }
+
+    /// Read the audit event fields from a list of event fields received
+    /// in an event notification. `offset` is the index of the first of the
+    /// select clauses returned from [`AuditEventSession::select_clauses`].
+    ///
+    /// Returns `None` if there are not enough fields.
+    pub fn from_event_fields(fields: &[Variant], offset: usize) -> Option<Self> {
+        let fields = fields.get(offset..offset + Self::NUM_SELECT_CLAUSES)?;
+        Some(Self {
+            source_node: Self::node_id_field(&fields[0]),
+            client_user_id: match &fields[1] {
+                Variant::String(s) => s.clone(),
+                _ => UAString::null(),
+            },
+            session_id: Self::node_id_field(&fields[2]),
+        })
+    }
+
+    fn node_id_field(field: &Variant) -> NodeId {
+        match field {
+            Variant::NodeId(n) => (**n).clone(),
+            _ => NodeId::null(),
+        }
+    }
+
+    /// Return `true` if this audit event was caused by the given session, i.e. if
+    /// either the `SessionId` or `SourceNode` of the event is the server-assigned
+    /// ID of the session.
+    pub fn is_for_session(&self, session: &Session) -> bool {
+        let session_id = session.server_session_id();
+        if session_id.is_null() {
+            return false;
+        }
+        self.session_id == session_id || self.source_node == session_id
+    }
+}
@@ -5,10 +5,7 @@ use tokio::{pin, select};
 use tracing::{debug, error};
 
 use crate::{
-    transport::{
-        tcp::{TcpConnector, TransportConfiguration},
-        TransportPollResult,
-    },
+    transport::{connector_for_url, tcp::TransportConfiguration, TransportPollResult},
     AsyncSecureChannel, ClientConfig, ClientEndpoint, IdentityToken,
 };
 use opcua_core::{
@@ -18,14 +15,14 @@ use opcua_core::{
     },
     config::Config,
     sync::RwLock,
-    ResponseMessage,
+    RequestMessage, ResponseMessage,
 };
 use opcua_crypto::{CertificateStore, SecurityPolicy};
 use opcua_types::{
     ApplicationDescription, ContextOwned, DecodingOptions, EndpointDescription,
     FindServersOnNetworkRequest, FindServersOnNetworkResponse, FindServersRequest,
     GetEndpointsRequest, MessageSecurityMode, NamespaceMap, RegisterServerRequest,
-    RegisteredServer, StatusCode, UAString,
+    RegisteredServer, RequestHeader, StatusCode, UAString,
 };
 
 use super::{
@@ -33,6 +30,37 @@ use super::{
     Session, SessionEventLoop,
 };
 
+/// Error returned from [`Client::connect_best`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConnectBestError {
+    /// Fetching the list of endpoints from the server failed.
+    Discovery(StatusCode),
+    /// The server has no endpoint matching the requested security and identity token.
+    /// Contains the reason each endpoint returned by the server was rejected.
+    NoSuitableEndpoint(String),
+}
+
+impl ConnectBestError {
+    /// Get a status code representing this error.
+    pub fn status(&self) -> StatusCode {
+        match self {
+            Self::Discovery(s) => *s,
+            Self::NoSuitableEndpoint(_) => StatusCode::BadSecurityPolicyRejected,
+        }
+    }
+}
+
+impl std::fmt::Display for ConnectBestError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Discovery(s) => write!(f, "Failed to fetch server endpoints: {s}"),
+            Self::NoSuitableEndpoint(e) => f.write_str(e),
+        }
+    }
+}
+
+impl std::error::Error for ConnectBestError {}
+
 /// Wrapper around common data for generating sessions and performing requests
 /// with one-shot connections.
 pub struct Client {
@@ -57,7 +85,7 @@ impl Client {
             None
         };
 
-        let (mut certificate_store, client_certificate, client_pkey) =
+        let (certificate_store, client_certificate, client_pkey) =
             CertificateStore::new_with_x509_data(
                 &config.pki_dir,
                 false,
@@ -69,6 +97,20 @@ impl Client {
             error!("Client is missing its application instance certificate and/or its private key. Encrypted endpoints will not function correctly.")
         }
 
+        Self::new_with_certificate_store(config, certificate_store)
+    }
+
+    /// Create a new client from config, using the given certificate store instead of
+    /// the one in `config.pki_dir`. The certificate store must contain the application
+    /// instance certificate and private key, unless the client only uses unencrypted endpoints.
+    ///
+    /// Certificate validation settings from `config` are applied to the store.
+    ///
+    /// Note that this does not make any connection to the server.
+    pub fn new_with_certificate_store(
+        config: ClientConfig,
+        mut certificate_store: CertificateStore,
+    ) -> Self {
         // Clients may choose to skip additional server certificate validations
         certificate_store.set_skip_verify_certs(!config.verify_server_certs);
 
@@ -150,6 +192,50 @@ impl Client {
             .build(self.certificate_store.clone()))
     }
 
+    /// Fetches the endpoints of the server at `server_url`, picks the best endpoint
+    /// supporting `user_identity_token`, and creates a [`Session`] for it.
+    ///
+    /// If `secure` is `true` the endpoint with the highest `securityLevel` is chosen,
+    /// otherwise an unencrypted endpoint. See [`SessionBuilder::connect_to_best_endpoint`].
+    ///
+    /// This function returns both a reference to the session, and a `SessionEventLoop`. You must run and
+    /// poll the event loop in order to actually establish a connection. Errors connecting to
+    /// the chosen endpoint are reported by the event loop, not by this method.
+    ///
+    /// # Arguments
+    ///
+    /// * `server_url` - URL of the discovery server to get endpoints from.
+    /// * `user_identity_token` - Identity token to use for authentication.
+    /// * `secure` - Whether to pick an encrypted endpoint.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok((Arc<Session>, SessionEventLoop))` - Session and event loop.
+    /// * `Err(ConnectBestError)` - Endpoint discovery failed, or no endpoint was suitable.
+    ///
+    pub async fn connect_best(
+        &mut self,
+        server_url: impl Into<String>,
+        user_identity_token: IdentityToken,
+        secure: bool,
+    ) -> Result<(Arc<Session>, SessionEventLoop), ConnectBestError> {
+        let endpoints = self
+            .get_server_endpoints_from_url(server_url)
+            .await
+            .map_err(ConnectBestError::Discovery)?;
+
+        Ok(self
+            .session_builder()
+            .with_endpoints(endpoints)
+            .user_identity_token(user_identity_token)
+            .connect_to_best_endpoint(secure)
+            .map_err(|e| {
+                error!("{}", e);
+                ConnectBestError::NoSuitableEndpoint(e)
+            })?
+            .build(self.certificate_store.clone()))
+    }
+
     /// Connects to a server directly using provided [`EndpointDescription`].
     ///
     /// This function returns both a reference to the session, and a `SessionEventLoop`. You must run and
@@ -221,6 +307,7 @@ impl Client {
         endpoint_info: EndpointInfo,
         channel_lifetime: u32,
     ) -> AsyncSecureChannel {
+        let connector = connector_for_url(endpoint_info.endpoint.endpoint_url.as_ref());
         AsyncSecureChannel::new(
             self.certificate_store.clone(),
             endpoint_info,
@@ -228,13 +315,13 @@ impl Client {
             self.config.performance.ignore_clock_skew,
             Arc::default(),
             TransportConfiguration {
-                max_pending_incoming: 5,
                 send_buffer_size: self.config.decoding_options.max_chunk_size,
                 recv_buffer_size: self.config.decoding_options.max_incoming_chunk_size,
                 max_message_size: self.config.decoding_options.max_message_size,
                 max_chunk_count: self.config.decoding_options.max_chunk_count,
+                buffer_pool: self.config.buffer_pool(),
             },
-            Box::new(TcpConnector),
+            connector,
             channel_lifetime,
             // We should only ever need the default decoding context for temporary connections.
             Arc::new(RwLock::new(ContextOwned::new_default(
@@ -300,30 +387,54 @@ impl Client {
         }
     }
 
-    async fn get_server_endpoints_inner(
+    /// Connect to the server at `server_url` without creating a session, send a single
+    /// request created by `make_request`, and close the connection again.
+    ///
+    /// This is used for the discovery services, which do not require a session.
+    /// If `server_url` does not contain a port, the default OPC UA port 4840 is used.
+    async fn send_without_session<R: Into<RequestMessage>>(
         &self,
-        endpoint: &EndpointDescription,
-        channel: &AsyncSecureChannel,
-        locale_ids: Option<Vec<UAString>>,
-        profile_uris: Option<Vec<UAString>>,
-    ) -> Result<Vec<EndpointDescription>, StatusCode> {
-        let request = GetEndpointsRequest {
-            request_header: channel.make_request_header(self.config.request_timeout),
-            endpoint_url: endpoint.endpoint_url.clone(),
-            locale_ids,
-            profile_uris,
+        server_url: &str,
+        make_request: impl FnOnce(RequestHeader) -> R,
+    ) -> Result<ResponseMessage, StatusCode> {
+        if !is_opc_ua_binary_url(server_url) {
+            error!("Server url \"{}\" is not a valid OPC UA url", server_url);
+            return Err(StatusCode::BadTcpEndpointUrlInvalid);
+        }
+        // Most of these fields mean nothing when calling discovery services.
+        let endpoint_info = EndpointInfo {
+            endpoint: EndpointDescription::from(server_url),
+            user_identity_token: IdentityToken::Anonymous,
+            preferred_locales: Vec::new(),
         };
-        // Send the message and wait for a response.
-        let response = channel.send(request, self.config.request_timeout).await?;
-        if let ResponseMessage::GetEndpoints(response) = response {
-            process_service_result(&response.response_header)?;
-            match response.endpoints {
-                None => Ok(Vec::new()),
-                Some(endpoints) => Ok(endpoints),
+        let channel = self.channel_from_endpoint_info(endpoint_info, self.config.channel_lifetime);
+
+        let mut evt_loop = channel.connect().await?;
+
+        let request = make_request(channel.make_request_header(self.config.request_timeout));
+        let send_fut = channel.send(request, self.config.request_timeout);
+        pin!(send_fut);
+
+        let res = loop {
+            select! {
+                r = evt_loop.poll() => {
+                    if let TransportPollResult::Closed(e) = r {
+                        return Err(e);
+                    }
+                },
+                res = &mut send_fut => break res
+            }
+        };
+
+        channel.close_channel().await;
+
+        loop {
+            if matches!(evt_loop.poll().await, TransportPollResult::Closed(_)) {
+                break;
             }
-        } else {
-            Err(process_unexpected_response(response))
         }
+
+        res
     }
 
     /// Get the list of endpoints for the server at the given URL.
@@ -362,77 +473,25 @@ impl Client {
         profile_uris: &[&str],
     ) -> Result<Vec<EndpointDescription>, StatusCode> {
         let server_url = server_url.into();
-        if !is_opc_ua_binary_url(&server_url) {
-            return Err(StatusCode::BadTcpEndpointUrlInvalid);
-        }
-        let preferred_locales = Vec::new();
-        // Most of these fields mean nothing when getting endpoints
-        let endpoint = EndpointDescription::from(server_url.as_ref());
-        let endpoint_info = EndpointInfo {
-            endpoint: endpoint.clone(),
-            user_identity_token: IdentityToken::Anonymous,
-            preferred_locales,
-        };
-        let channel = self.channel_from_endpoint_info(endpoint_info, self.config.channel_lifetime);
-
-        let mut evt_loop = channel.connect().await?;
-
-        let send_fut = self.get_server_endpoints_inner(
-            &endpoint,
-            &channel,
-            if locale_ids.is_empty() {
-                None
-            } else {
-                Some(locale_ids.iter().map(|i| (*i).into()).collect())
-            },
-            if profile_uris.is_empty() {
-                None
-            } else {
-                Some(profile_uris.iter().map(|i| (*i).into()).collect())
-            },
-        );
-        pin!(send_fut);
-
-        let res = loop {
-            select! {
-                r = evt_loop.poll() => {
-                    if let TransportPollResult::Closed(e) = r {
-                        return Err(e);
-                    }
+        let response = self
+            .send_without_session(&server_url, |request_header| GetEndpointsRequest {
+                request_header,
+                endpoint_url: server_url.as_str().into(),
+                locale_ids: if locale_ids.is_empty() {
+                    None
+                } else {
+                    Some(locale_ids.iter().map(|i| (*i).into()).collect())
                 },
-                res = &mut send_fut => break res
-            }
-        };
-
-        channel.close_channel().await;
-
-        loop {
-            if matches!(evt_loop.poll().await, TransportPollResult::Closed(_)) {
-                break;
-            }
-        }
-
-        res
-    }
-
-    async fn find_servers_inner(
-        &self,
-        endpoint_url: String,
-        channel: &AsyncSecureChannel,
-        locale_ids: Option<Vec<UAString>>,
-        server_uris: Option<Vec<UAString>>,
-    ) -> Result<Vec<ApplicationDescription>, StatusCode> {
-        let request = FindServersRequest {
-            request_header: channel.make_request_header(self.config.request_timeout),
-            endpoint_url: endpoint_url.into(),
-            locale_ids,
-            server_uris,
-        };
-
-        let response = channel.send(request, self.config.request_timeout).await?;
-        if let ResponseMessage::FindServers(response) = response {
+                profile_uris: if profile_uris.is_empty() {
+                    None
+                } else {
+                    Some(profile_uris.iter().map(|i| (*i).into()).collect())
+                },
+            })
+            .await?;
+        if let ResponseMessage::GetEndpoints(response) = response {
             process_service_result(&response.response_header)?;
-            Ok(response.servers.unwrap_or_default())
+            Ok(response.endpoints.unwrap_or_default())
         } else {
             Err(process_unexpected_response(response))
         }
@@ -441,9 +500,12 @@ impl Client {
     /// Connects to a discovery server and asks the server for a list of
     /// available servers' [`ApplicationDescription`].
     ///
+    /// See OPC UA Part 4 - Services 5.5.2 for a complete description of the service.
+    ///
     /// # Arguments
     ///
-    /// * `discovery_endpoint_url` - Discovery endpoint to connect to.
+    /// * `discovery_endpoint_url` - Discovery endpoint to connect to. If the URL does not
+    ///   contain a port, the default OPC UA port 4840 is used.
     /// * `locale_ids` - List of locales to use.
     /// * `server_uris` - List of servers to return. If empty, all known servers are returned.
     ///
@@ -459,60 +521,19 @@ impl Client {
     ) -> Result<Vec<ApplicationDescription>, StatusCode> {
         let discovery_endpoint_url = discovery_endpoint_url.into();
         debug!("find_servers, {}", discovery_endpoint_url);
-        let endpoint = EndpointDescription::from(discovery_endpoint_url.as_ref());
-        let endpoint_info = EndpointInfo {
-            endpoint: endpoint.clone(),
-            user_identity_token: IdentityToken::Anonymous,
-            preferred_locales: Vec::new(),
-        };
-        let channel = self.channel_from_endpoint_info(endpoint_info, self.config.channel_lifetime);
-
-        let mut evt_loop = channel.connect().await?;
-
-        let send_fut =
-            self.find_servers_inner(discovery_endpoint_url, &channel, locale_ids, server_uris);
-        pin!(send_fut);
-
-        let res = loop {
-            select! {
-                r = evt_loop.poll() => {
-                    if let TransportPollResult::Closed(e) = r {
-                        return Err(e);
-                    }
-                },
-                res = &mut send_fut => break res
-            }
-        };
-
-        channel.close_channel().await;
-
-        loop {
-            if matches!(evt_loop.poll().await, TransportPollResult::Closed(_)) {
-                break;
-            }
-        }
-
-        res
-    }
-
-    async fn find_servers_on_network_inner(
-        &self,
-        starting_record_id: u32,
-        max_records_to_return: u32,
-        server_capability_filter: Option<Vec<UAString>>,
-        channel: &AsyncSecureChannel,
-    ) -> Result<FindServersOnNetworkResponse, StatusCode> {
-        let request = FindServersOnNetworkRequest {
-            request_header: channel.make_request_header(self.config.request_timeout),
-            starting_record_id,
-            max_records_to_return,
-            server_capability_filter,
-        };
-
-        let response = channel.send(request, self.config.request_timeout).await?;
-        if let ResponseMessage::FindServersOnNetwork(response) = response {
+        let response = self
+            .send_without_session(&discovery_endpoint_url, |request_header| {
+                FindServersRequest {
+                    request_header,
+                    endpoint_url: discovery_endpoint_url.as_str().into(),
+                    locale_ids,
+                    server_uris,
+                }
+            })
+            .await?;
+        if let ResponseMessage::FindServers(response) = response {
             process_service_result(&response.response_header)?;
-            Ok(*response)
+            Ok(response.servers.unwrap_or_default())
         } else {
             Err(process_unexpected_response(response))
         }
@@ -521,10 +542,13 @@ impl Client {
     /// Connects to a discovery server and asks for a list of available servers on the network.
     ///
     /// See OPC UA Part 4 - Services 5.5.3 for a complete description of the service.
+    /// Note that this service is only supported by discovery servers with the multicast
+    /// extension, other servers will return `BadServiceUnsupported`.
     ///
     /// # Arguments
     ///
-    /// * `discovery_endpoint_url` - Endpoint URL to connect to.
+    /// * `discovery_endpoint_url` - Endpoint URL to connect to. If the URL does not
+    ///   contain a port, the default OPC UA port 4840 is used.
     /// * `starting_record_id` - Only records with an identifier greater than this number
     ///   will be returned.
     /// * `max_records_to_return` - The maximum number of records to return in the response.
@@ -544,45 +568,23 @@ impl Client {
         server_capability_filter: Option<Vec<UAString>>,
     ) -> Result<FindServersOnNetworkResponse, StatusCode> {
         let discovery_endpoint_url = discovery_endpoint_url.into();
-        debug!("find_servers, {}", discovery_endpoint_url);
-        let endpoint = EndpointDescription::from(discovery_endpoint_url.as_ref());
-        let endpoint_info = EndpointInfo {
-            endpoint: endpoint.clone(),
-            user_identity_token: IdentityToken::Anonymous,
-            preferred_locales: Vec::new(),
-        };
-        let channel = self.channel_from_endpoint_info(endpoint_info, self.config.channel_lifetime);
-
-        let mut evt_loop = channel.connect().await?;
-
-        let send_fut = self.find_servers_on_network_inner(
-            starting_record_id,
-            max_records_to_return,
-            server_capability_filter,
-            &channel,
-        );
-        pin!(send_fut);
-
-        let res = loop {
-            select! {
-                r = evt_loop.poll() => {
-                    if let TransportPollResult::Closed(e) = r {
-                        return Err(e);
-                    }
-                },
-                res = &mut send_fut => break res
-            }
-        };
-
-        channel.close_channel().await;
-
-        loop {
-            if matches!(evt_loop.poll().await, TransportPollResult::Closed(_)) {
-                break;
-            }
+        debug!("find_servers_on_network, {}", discovery_endpoint_url);
+        let response = self
+            .send_without_session(&discovery_endpoint_url, |request_header| {
+                FindServersOnNetworkRequest {
+                    request_header,
+                    starting_record_id,
+                    max_records_to_return,
+                    server_capability_filter,
+                }
+            })
+            .await?;
+        if let ResponseMessage::FindServersOnNetwork(response) = response {
+            process_service_result(&response.response_header)?;
+            Ok(*response)
+        } else {
+            Err(process_unexpected_response(response))
         }
-
-        res
     }
 
     /// Find an endpoint supplied from the list of endpoints that matches the input criteria.
@@ -1,7 +1,7 @@
 use std::sync::Arc;
 
 use tokio::{pin, select};
-use tracing::info;
+use tracing::{info, warn};
 
 use crate::transport::{SecureChannelEventLoop, TransportPollResult};
 use opcua_types::{NodeId, StatusCode};
@@ -74,6 +74,9 @@ impl SessionConnector {
 
     async fn ensure_and_activate_session(&self) -> Result<SessionConnectMode, StatusCode> {
         let should_create_session = self.inner.session_id.load().is_null();
+        // Sessions sharing a secure channel store the server nonce on the channel,
+        // so creating and activating sessions on the same channel must not overlap.
+        let establishment_guard = self.inner.channel.lock_session_establishment().await;
 
         if should_create_session {
             self.inner.create_session().await?;
@@ -100,6 +103,13 @@ impl SessionConnector {
                 }
             }
         };
+        drop(establishment_guard);
+
+        if self.inner.sync_type_loaders {
+            if let Err(e) = self.inner.ensure_type_loaders().await {
+                warn!("Failed to fetch namespaces for type loaders: {e}");
+            }
+        }
 
         if self.inner.recreate_subscriptions {
             self.inner.transfer_subscriptions_from_old_session().await;
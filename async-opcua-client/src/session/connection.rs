@@ -1,4 +1,4 @@
-use std::{str::FromStr, sync::Arc};
+use std::{net::SocketAddr, str::FromStr, sync::Arc};
 
 use opcua_core::{comms::url::is_opc_ua_binary_url, config::Config, sync::RwLock};
 use opcua_crypto::{CertificateStore, SecurityPolicy};
@@ -10,8 +10,7 @@ use tracing::error;
 
 use crate::{
     transport::{
-        tcp::{TcpConnector, TransportConfiguration},
-        Connector,
+        connector_for_url, tcp::TransportConfiguration, ChannelPool, Connector, ReverseConnector,
     },
     AsyncSecureChannel, ClientConfig, IdentityToken,
 };
@@ -21,10 +20,25 @@ use super::{Client, EndpointInfo, Session, SessionEventLoop};
 struct SessionBuilderInner {
     session_id: Option<NodeId>,
     user_identity_token: IdentityToken,
-    connector: Box<dyn Connector>,
+    reverse_connect: Option<SocketAddr>,
     type_loaders: Vec<Arc<dyn TypeLoader>>,
 }
 
+impl SessionBuilderInner {
+    fn connector(&self, endpoint: &EndpointDescription) -> Box<dyn Connector> {
+        match self.reverse_connect {
+            Some(addr) => {
+                let mut connector = ReverseConnector::new(addr);
+                if !endpoint.server.application_uri.is_empty() {
+                    connector = connector.server_uri(endpoint.server.application_uri.as_ref());
+                }
+                Box::new(connector)
+            }
+            None => connector_for_url(endpoint.endpoint_url.as_ref()),
+        }
+    }
+}
+
 /// Type-state builder for a session and session event loop.
 /// To use, you will typically first call [SessionBuilder::with_endpoints] to set
 /// a list of available endpoints, then one of the `connect_to` methods, then finally
@@ -46,7 +60,7 @@ impl<'a> SessionBuilder<'a, (), ()> {
             inner: SessionBuilderInner {
                 session_id: None,
                 user_identity_token: IdentityToken::Anonymous,
-                connector: Box::new(TcpConnector),
+                reverse_connect: None,
                 type_loaders: Vec::new(),
             },
         }
@@ -86,13 +100,24 @@ impl<T, R> SessionBuilder<'_, T, R> {
     }
 
     /// Add an initial type loader to the session. You can add more of these later.
-    /// Note that custom type loaders will likely not work until namespaces
-    /// are fetched from the server.
+    /// If any type loaders are added here, the namespace array is fetched from the
+    /// server each time the session is activated, see [`Session::ensure_type_loaders`].
     pub fn type_loader(mut self, type_loader: Arc<dyn TypeLoader>) -> Self {
         self.inner.type_loaders.push(type_loader);
         self
     }
 
+    /// Use reverse connect, listening on `bind_addr` for the server to open the
+    /// connection instead of connecting to the server directly. The server must
+    /// be configured to connect to this address.
+    ///
+    /// Connections are only accepted if the ReverseHello message sent by the server
+    /// matches the endpoint URL and server URI of the selected endpoint.
+    pub fn reverse_connect(mut self, bind_addr: SocketAddr) -> Self {
+        self.inner.reverse_connect = Some(bind_addr);
+        self
+    }
+
     fn endpoint_supports_token(&self, endpoint: &EndpointDescription) -> bool {
         match &self.inner.user_identity_token {
             IdentityToken::Anonymous => {
@@ -116,6 +141,23 @@ impl<T, R> SessionBuilder<'_, T, R> {
                 .is_some_and(|e| e.iter().any(|p| p.token_type == UserTokenType::IssuedToken)),
         }
     }
+
+    /// Get the reason `endpoint` cannot be picked by `connect_to_best_endpoint`, if any.
+    fn endpoint_rejection(&self, endpoint: &EndpointDescription, secure: bool) -> Option<String> {
+        if !self.endpoint_supports_token(endpoint) {
+            let token = match &self.inner.user_identity_token {
+                IdentityToken::Anonymous => "anonymous",
+                IdentityToken::UserName(_, _) => "user name",
+                IdentityToken::X509(_, _) => "certificate",
+                IdentityToken::IssuedToken(_) => "issued",
+            };
+            return Some(format!("{token} identity tokens are not supported"));
+        }
+        if !secure && endpoint.security_mode != MessageSecurityMode::None {
+            return Some(format!("security mode is {}", endpoint.security_mode));
+        }
+        None
+    }
 }
 
 impl<'a> SessionBuilder<'a, (), Vec<EndpointDescription>> {
@@ -214,22 +256,40 @@ impl<'a> SessionBuilder<'a, (), Vec<EndpointDescription>> {
     /// any unencrypted endpoint that supports the configured identity token.
     /// If `secure` is `true`, the endpoint that supports the configured identity token with the highest
     /// `securityLevel`.
+    ///
+    /// If no endpoint is suitable, the error lists the reason each endpoint was rejected.
     pub fn connect_to_best_endpoint(
         self,
         secure: bool,
     ) -> Result<SessionBuilder<'a, EndpointDescription, Vec<EndpointDescription>>, String> {
+        let mut candidates = self
+            .endpoints
+            .iter()
+            .filter(|e| self.endpoint_rejection(e, secure).is_none());
         let endpoint = if secure {
-            self.endpoints
-                .iter()
-                .filter(|e| self.endpoint_supports_token(e))
-                .max_by(|a, b| a.security_level.cmp(&b.security_level))
+            candidates.max_by(|a, b| a.security_level.cmp(&b.security_level))
         } else {
-            self.endpoints.iter().find(|e| {
-                e.security_mode == MessageSecurityMode::None && self.endpoint_supports_token(e)
-            })
+            candidates.next()
         };
         let Some(endpoint) = endpoint else {
-            return Err("No suitable endpoint found".to_owned());
+            let reasons: Vec<_> = self
+                .endpoints
+                .iter()
+                .filter_map(|e| {
+                    let reason = self.endpoint_rejection(e, secure)?;
+                    Some(format!(
+                        "{} ({}, {}): {reason}",
+                        e.endpoint_url, e.security_policy_uri, e.security_mode
+                    ))
+                })
+                .collect();
+            if reasons.is_empty() {
+                return Err("No suitable endpoint found, the server has no endpoints".to_owned());
+            }
+            return Err(format!(
+                "No suitable endpoint found: {}",
+                reasons.join("; ")
+            ));
         };
         Ok(SessionBuilder {
             inner: self.inner,
@@ -271,13 +331,14 @@ impl<R> SessionBuilder<'_, EndpointDescription, R> {
         certificate_store: Arc<RwLock<CertificateStore>>,
     ) -> (Arc<Session>, SessionEventLoop) {
         let ctx = self.make_encoding_context();
+        let connector = self.inner.connector(&self.endpoint);
         Session::new(
             Self::build_channel_inner(
                 certificate_store,
                 self.inner.user_identity_token,
                 self.endpoint,
                 self.config,
-                self.inner.connector,
+                connector,
                 ctx,
             ),
             self.config.session_name.clone().into(),
@@ -286,12 +347,22 @@ impl<R> SessionBuilder<'_, EndpointDescription, R> {
             self.config.decoding_options.as_comms_decoding_options(),
             self.config,
             self.inner.session_id,
+            !self.inner.type_loaders.is_empty(),
         )
     }
 
     fn make_encoding_context(&self) -> ContextOwned {
+        // Until the namespace array is read from the server, use the union of the
+        // namespaces of the type loaders. Loaders with their own namespace array have
+        // node IDs translated to their local indices during decoding.
+        let mut namespaces = NamespaceMap::new();
+        for loader in &self.inner.type_loaders {
+            if let Some(local) = loader.local_namespaces() {
+                namespaces.merge(local);
+            }
+        }
         let mut encoding_context = ContextOwned::new_default(
-            NamespaceMap::new(),
+            namespaces,
             self.config.decoding_options.as_comms_decoding_options(),
         );
 
@@ -321,11 +392,11 @@ impl<R> SessionBuilder<'_, EndpointDescription, R> {
             config.performance.ignore_clock_skew,
             Arc::default(),
             TransportConfiguration {
-                max_pending_incoming: 5,
                 send_buffer_size: config.decoding_options.max_chunk_size,
                 recv_buffer_size: config.decoding_options.max_incoming_chunk_size,
                 max_message_size: config.decoding_options.max_message_size,
                 max_chunk_count: config.decoding_options.max_chunk_count,
+                buffer_pool: config.buffer_pool(),
             },
             connector,
             config.channel_lifetime,
@@ -340,13 +411,28 @@ impl<R> SessionBuilder<'_, EndpointDescription, R> {
         certificate_store: Arc<RwLock<CertificateStore>>,
     ) -> AsyncSecureChannel {
         let ctx = self.make_encoding_context();
+        let connector = self.inner.connector(&self.endpoint);
         Self::build_channel_inner(
             certificate_store,
             self.inner.user_identity_token,
             self.endpoint,
             self.config,
-            self.inner.connector,
+            connector,
             ctx,
         )
     }
+
+    /// Build a channel pool, which lets you create several sessions sharing
+    /// a single secure channel. Sessions created from the pool use the
+    /// endpoint of this builder, and its user identity token by default.
+    ///
+    /// Note that any type loaders added to this builder are shared
+    /// between all sessions using the pool.
+    pub fn build_channel_pool(
+        self,
+        certificate_store: Arc<RwLock<CertificateStore>>,
+    ) -> ChannelPool {
+        let config = self.config.clone();
+        ChannelPool::new(self.build_channel(certificate_store), config)
+    }
 }
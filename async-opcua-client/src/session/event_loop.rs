@@ -4,12 +4,13 @@ use std::{
 };
 
 use futures::{future::BoxFuture, stream::BoxStream, FutureExt, Stream, StreamExt, TryStreamExt};
-use tracing::warn;
+use opcua_core::task::spawn_named;
+use tracing::{info, warn};
 
 use crate::{
     retry::{ExponentialBackoff, SessionRetryPolicy},
     session::{session_error, session_warn},
-    transport::{SecureChannelEventLoop, TransportPollResult},
+    transport::{SecureChannelEvent, SecureChannelEventLoop, TransportPollResult},
 };
 use opcua_types::{
     AttributeId, QualifiedName, ReadValueId, StatusCode, TimestampsToReturn, VariableId,
@@ -39,12 +40,34 @@ pub enum SessionPollResult {
     SessionActivity(SessionActivity),
     /// The session performed some subscription-related activity.
     Subscription(SubscriptionActivity),
+    /// The secure channel was opened, or its security token was renewed or expired.
+    SecureChannel(SecureChannelEvent),
     /// The session begins (re)connecting to the server.
     BeginConnect,
     /// Disconnect due to a keep alive terminated.
     FinishedDisconnect,
 }
 
+/// The state of the connection to the server, passed to callbacks registered with
+/// [`Session::on_connection_status_change`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ConnectionState {
+    /// The session is connecting to the server for the first time.
+    Connecting,
+    /// The session is connected to the server, and has an active session.
+    Connected,
+    /// The connection was lost, and the session is trying to reconnect.
+    Reconnecting,
+    /// The server stopped answering keep-alive requests, so the connection is
+    /// considered dead and will be closed. This is followed by `Reconnecting`
+    /// if automatic reconnects are enabled.
+    Dead,
+    /// The session was closed, either manually or because it failed to reconnect.
+    /// The event loop terminates after this.
+    Closed,
+}
+
 struct ConnectedState {
     channel: SecureChannelEventLoop,
     keep_alive: BoxStream<'static, SessionActivity>,
@@ -71,6 +94,10 @@ pub struct SessionEventLoop {
     retry: SessionRetryPolicy,
     keep_alive_interval: Duration,
     max_failed_keep_alive_count: u64,
+    keep_alive_only_when_idle: bool,
+    last_received_tx: tokio::sync::watch::Sender<Instant>,
+    connected_once: bool,
+    channel_events: tokio::sync::broadcast::Receiver<SecureChannelEvent>,
 }
 
 impl SessionEventLoop {
@@ -80,13 +107,18 @@ impl SessionEventLoop {
         trigger_publish_recv: tokio::sync::watch::Receiver<Instant>,
         keep_alive_interval: Duration,
         max_failed_keep_alive_count: u64,
+        keep_alive_only_when_idle: bool,
     ) -> Self {
         Self {
+            channel_events: inner.channel.subscribe_events(),
             inner,
             retry,
             trigger_publish_recv,
             keep_alive_interval,
             max_failed_keep_alive_count,
+            keep_alive_only_when_idle,
+            last_received_tx: tokio::sync::watch::channel(Instant::now()).0,
+            connected_once: false,
         }
     }
 
@@ -119,7 +151,15 @@ impl SessionEventLoop {
     ///
     /// * `JoinHandle<StatusCode>` - Handle to a tokio task wrapping the event loop.
     pub fn spawn(self) -> tokio::task::JoinHandle<StatusCode> {
-        tokio::task::spawn(self.run())
+        let name = self.inner.named_tasks.then(|| {
+            format!(
+                "opcua-client-event-loop {} ({}, {})",
+                self.inner.session_id(),
+                self.inner.session_name,
+                self.inner.endpoint_info().endpoint.endpoint_url
+            )
+        });
+        spawn_named(name, self.run())
     }
 
     /// Start the event loop, returning a stream that must be polled until it is closed.
@@ -131,7 +171,7 @@ impl SessionEventLoop {
     pub fn enter(self) -> impl Stream<Item = Result<SessionPollResult, StatusCode>> {
         futures::stream::try_unfold(
             (self, SessionEventLoopState::Disconnected),
-            |(slf, state)| async move {
+            |(mut slf, state)| async move {
                 let (res, state) = match state {
                     SessionEventLoopState::Connected(mut state) => {
                         tokio::select! {
@@ -142,6 +182,7 @@ impl SessionEventLoop {
 
                                     let should_reconnect = slf.inner.should_reconnect.load(Ordering::Relaxed);
                                     if !should_reconnect {
+                                        slf.inner.notify_connection_state(ConnectionState::Closed);
                                         return Ok(None);
                                     }
 
@@ -150,6 +191,9 @@ impl SessionEventLoop {
                                         SessionEventLoopState::Disconnected,
                                     ))
                                 } else {
+                                    if matches!(r, TransportPollResult::IncomingMessage) {
+                                        slf.last_received_tx.send_replace(Instant::now());
+                                    }
                                     Ok((
                                         SessionPollResult::Transport(r),
                                         SessionEventLoopState::Connected(state),
@@ -173,6 +217,7 @@ impl SessionEventLoop {
                                             && slf.max_failed_keep_alive_count != 0
                                         {
                                             session_error!(slf.inner, "Maximum number of failed keep-alives exceed limit, session will be closed.");
+                                            slf.inner.notify_connection_state(ConnectionState::Dead);
                                             state.currently_closing = true;
                                             let s = slf.inner.clone();
                                             state.disconnect_fut = async move {
@@ -199,6 +244,12 @@ impl SessionEventLoop {
                                     SessionEventLoopState::Connected(state),
                                 ))
                             }
+                            r = next_channel_event(&mut slf.channel_events) => {
+                                Ok((
+                                    SessionPollResult::SecureChannel(r),
+                                    SessionEventLoopState::Connected(state),
+                                ))
+                            }
                             _ = &mut state.disconnect_fut => {
                                 // Do nothing, if this terminates we will very soon be transitioning
                                 // to a disconnected state.
@@ -213,6 +264,11 @@ impl SessionEventLoop {
                         let connector = SessionConnector::new(slf.inner.clone());
 
                         let _ = slf.inner.state_watch_tx.send(SessionState::Connecting);
+                        slf.inner.notify_connection_state(if slf.connected_once {
+                            ConnectionState::Reconnecting
+                        } else {
+                            ConnectionState::Connecting
+                        });
 
                         Ok((
                             SessionPollResult::BeginConnect,
@@ -229,6 +285,10 @@ impl SessionEventLoop {
                         match connector.try_connect().await {
                             Ok((channel, result)) => {
                                 let _ = slf.inner.state_watch_tx.send(SessionState::Connected);
+                                slf.connected_once = true;
+                                slf.last_received_tx.send_replace(Instant::now());
+                                slf.inner
+                                    .notify_connection_state(ConnectionState::Connected);
                                 Ok((
                                     SessionPollResult::Reconnected(result),
                                     SessionEventLoopState::Connected(ConnectedState {
@@ -236,6 +296,8 @@ impl SessionEventLoop {
                                         keep_alive: SessionActivityLoop::new(
                                             slf.inner.clone(),
                                             slf.keep_alive_interval,
+                                            slf.keep_alive_only_when_idle
+                                                .then(|| slf.last_received_tx.subscribe()),
                                         )
                                         .run()
                                         .boxed(),
@@ -254,15 +316,21 @@ impl SessionEventLoop {
                             Err(e) => {
                                 warn!("Failed to connect to server, status code: {e}");
                                 match backoff.next() {
-                                    Some(x) => Ok((
-                                        SessionPollResult::ReconnectFailed(e),
-                                        SessionEventLoopState::Connecting(
-                                            connector,
-                                            backoff,
-                                            Instant::now() + x,
-                                        ),
-                                    )),
-                                    None => Err(e),
+                                    Some(x) => {
+                                        info!("Retrying connection to server in {x:?}");
+                                        Ok((
+                                            SessionPollResult::ReconnectFailed(e),
+                                            SessionEventLoopState::Connecting(
+                                                connector,
+                                                backoff,
+                                                Instant::now() + x,
+                                            ),
+                                        ))
+                                    }
+                                    None => {
+                                        slf.inner.notify_connection_state(ConnectionState::Closed);
+                                        Err(e)
+                                    }
                                 }
                             }
                         }
@@ -275,6 +343,32 @@ impl SessionEventLoop {
     }
 }
 
+impl Drop for SessionEventLoop {
+    fn drop(&mut self) {
+        // Nothing polls the transport or reconnects once the event loop is gone,
+        // so fail any requests that are still waiting, instead of letting them time out.
+        self.inner.channel.close_requests();
+    }
+}
+
+/// Wait for the next event from the secure channel. Events emitted while the
+/// session is connecting are buffered, and returned once it is connected.
+async fn next_channel_event(
+    events: &mut tokio::sync::broadcast::Receiver<SecureChannelEvent>,
+) -> SecureChannelEvent {
+    loop {
+        match events.recv().await {
+            Ok(evt) => return evt,
+            Err(tokio::sync::broadcast::error::RecvError::Lagged(count)) => {
+                warn!("Session event loop skipped {count} secure channel events");
+            }
+            Err(tokio::sync::broadcast::error::RecvError::Closed) => {
+                futures::future::pending::<()>().await
+            }
+        }
+    }
+}
+
 /// Periodic activity performed by the session.
 #[derive(Debug, Clone)]
 pub enum SessionActivity {
@@ -310,19 +404,41 @@ impl SessionIntervals {
 struct SessionActivityLoop {
     inner: Arc<Session>,
     tick_gen: SessionIntervals,
+    keep_alive_interval: Duration,
+    /// Time of the last message received from the server, if keep-alive
+    /// requests should only be sent when the connection is idle.
+    last_received: Option<tokio::sync::watch::Receiver<Instant>>,
 }
 
 impl SessionActivityLoop {
-    fn new(inner: Arc<Session>, keep_alive_interval: Duration) -> Self {
+    fn new(
+        inner: Arc<Session>,
+        keep_alive_interval: Duration,
+        last_received: Option<tokio::sync::watch::Receiver<Instant>>,
+    ) -> Self {
         Self {
             inner,
             tick_gen: SessionIntervals::new(keep_alive_interval),
+            keep_alive_interval,
+            last_received,
         }
     }
 
+    /// Return `true` if a message was received from the server during the last
+    /// keep-alive interval, meaning the keep-alive request can be skipped.
+    fn had_recent_traffic(&self) -> bool {
+        self.last_received
+            .as_ref()
+            .is_some_and(|r| r.borrow().elapsed() < self.keep_alive_interval)
+    }
+
     fn run(self) -> impl Stream<Item = SessionActivity> {
         futures::stream::unfold(self, |mut slf| async move {
-            match slf.tick_gen.next().await {
+            let mut event = slf.tick_gen.next().await;
+            while matches!(event, SessionTickEvent::KeepAlive) && slf.had_recent_traffic() {
+                event = slf.tick_gen.next().await;
+            }
+            match event {
                 SessionTickEvent::KeepAlive => {
                     let now = Instant::now();
                     let res = slf
@@ -1,7 +1,9 @@
+mod audit;
 mod client;
 mod connect;
 mod connection;
 mod event_loop;
+mod operation_limits;
 mod request_builder;
 mod retry;
 mod services;
@@ -38,34 +40,56 @@ impl From<(EndpointDescription, IdentityToken)> for EndpointInfo {
     }
 }
 
+/// The target of an [`ExpandedNodeId`], as resolved by [`Session::resolve_expanded_node_id`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum ResolvedTarget {
+    /// A node on the server the session is connected to.
+    Local(NodeId),
+    /// A node on a different server.
+    Remote {
+        /// The application URI of the server containing the node, taken from
+        /// the `ServerArray` of the server the session is connected to.
+        server_uri: String,
+        /// The node ID on the remote server. The server index is always 0. If the
+        /// namespace is given as a URI, it must be resolved using the namespace array
+        /// of the remote server, for example with [`ExpandedNodeId::try_resolve`].
+        node_id: ExpandedNodeId,
+    },
+}
+
 use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 use arc_swap::ArcSwap;
-pub use client::Client;
+pub use audit::AuditEventSession;
+pub use client::{Client, ConnectBestError};
 pub use connect::SessionConnectMode;
 pub use connection::SessionBuilder;
-pub use event_loop::{SessionActivity, SessionEventLoop, SessionPollResult};
+pub use event_loop::{ConnectionState, SessionActivity, SessionEventLoop, SessionPollResult};
 use opcua_core::handle::AtomicHandle;
 use opcua_core::sync::{Mutex, RwLock};
+pub use operation_limits::{OperationLimit, OperationLimits};
 pub use request_builder::UARequest;
 pub use retry::{DefaultRetryPolicy, RequestRetryPolicy};
 pub use services::attributes::{
     HistoryRead, HistoryReadAction, HistoryUpdate, HistoryUpdateAction, Read, Write,
 };
-pub use services::method::Call;
+pub use services::method::{Call, IntoMethodArguments, MethodCallError};
 pub use services::node_management::{AddNodes, AddReferences, DeleteNodes, DeleteReferences};
+pub use services::query::{QueryFirst, QueryNext};
 pub use services::session::{ActivateSession, Cancel, CloseSession, CreateSession};
 use services::subscriptions::state::SubscriptionState;
-use services::subscriptions::PublishLimits;
 pub use services::subscriptions::{
     CreateMonitoredItems, CreateSubscription, DataChangeCallback, DeleteMonitoredItems,
     DeleteSubscriptions, EventCallback, ModifyMonitoredItems, ModifySubscription, MonitoredItem,
-    OnSubscriptionNotification, OnSubscriptionNotificationCore, Publish, Republish,
-    SetMonitoringMode, SetPublishingMode, SetTriggering, Subscription, SubscriptionActivity,
-    SubscriptionCallbacks, TransferSubscriptions,
+    OnSubscriptionNotification, OnSubscriptionNotificationCore, Publish, PublishStats, Republish,
+    SetMonitoringMode, SetPublishingMode, SetTriggering, StreamBackpressure, Subscription,
+    SubscriptionActivity, SubscriptionCallbacks, SubscriptionNotification,
+    SubscriptionStateSnapshot, SubscriptionStatusAction, SubscriptionStream,
+    SubscriptionStreamSender, TransferSubscriptions,
 };
+use services::subscriptions::{PublishCounters, PublishLimits};
 pub use services::view::{
     Browse, BrowseNext, RegisterNodes, TranslateBrowsePaths, UnregisterNodes,
 };
@@ -109,9 +133,11 @@ pub(crate) use session_trace;
 
 use opcua_core::ResponseMessage;
 use opcua_types::{
-    ApplicationDescription, ContextOwned, DecodingOptions, EndpointDescription, Error, IntegerId,
-    NamespaceMap, NodeId, ReadValueId, RequestHeader, ResponseHeader, StatusCode,
-    TimestampsToReturn, TypeLoader, UAString, VariableId, Variant,
+    ApplicationDescription, BrowsePath, ContextOwned, DataValue, DateTime, DecodingOptions,
+    EUInformation, EndpointDescription, Error, ExpandedNodeId, IntegerId, NamespaceMap, NodeId,
+    QualifiedName, Range, ReadValueId, ReferenceTypeId, RelativePath, RelativePathElement,
+    RequestHeader, ResponseHeader, StatusCode, TimestampsToReturn, TypeLoader, UAString,
+    VariableId, Variant,
 };
 
 use crate::browser::Browser;
@@ -158,6 +184,15 @@ pub(super) enum SessionState {
 
 static NEXT_SESSION_ID: AtomicU32 = AtomicU32::new(1);
 
+type ConnectionStateCallback = Arc<dyn Fn(ConnectionState) + Send + Sync>;
+
+/// The namespace array last read from the server, along with the source timestamp
+/// of the value it was read from.
+struct CachedNamespaces {
+    map: Arc<NamespaceMap>,
+    timestamp: Option<DateTime>,
+}
+
 /// An OPC-UA session. This session provides methods for all supported services that require an open session.
 ///
 /// Note that not all servers may support all service requests and calling an unsupported API
@@ -175,16 +210,28 @@ pub struct Session {
     pub(super) request_timeout: Duration,
     pub(super) publish_timeout: Duration,
     pub(super) recreate_monitored_items_chunk: usize,
+    pub(super) max_concurrent_reads: usize,
+    /// The operation limits of the server, read the first time they are needed
+    /// and cleared when the session is reset.
+    pub(super) operation_limits: Mutex<Option<OperationLimits>>,
     pub(super) recreate_subscriptions: bool,
+    pub(super) max_republish_attempts: usize,
+    pub(super) named_tasks: bool,
     pub(super) should_reconnect: AtomicBool,
     pub(super) session_timeout: f64,
     /// Reference to the subscription cache for the client.
     pub subscription_state: Mutex<SubscriptionState>,
     pub(super) publish_limits_watch_rx: tokio::sync::watch::Receiver<PublishLimits>,
     pub(super) publish_limits_watch_tx: tokio::sync::watch::Sender<PublishLimits>,
+    pub(super) publish_counters: PublishCounters,
     pub(super) monitored_item_handle: AtomicHandle,
     pub(super) trigger_publish_tx: tokio::sync::watch::Sender<Instant>,
+    pub(super) notification_lock: tokio::sync::Mutex<()>,
     pub(super) session_nonce_length: usize,
+    pub(super) server_auditing: AtomicBool,
+    pub(super) sync_type_loaders: bool,
+    namespace_cache: Mutex<Option<CachedNamespaces>>,
+    connection_state_callbacks: Mutex<Vec<ConnectionStateCallback>>,
     decoding_options: DecodingOptions,
 }
 
@@ -198,6 +245,7 @@ impl Session {
         decoding_options: DecodingOptions,
         config: &ClientConfig,
         session_id: Option<NodeId>,
+        sync_type_loaders: bool,
     ) -> (Arc<Self>, SessionEventLoop) {
         let (publish_limits_watch_tx, publish_limits_watch_rx) =
             tokio::sync::watch::channel(PublishLimits::new());
@@ -217,7 +265,11 @@ impl Session {
             session_timeout: config.session_timeout as f64,
             publish_timeout: config.publish_timeout,
             recreate_monitored_items_chunk: config.performance.recreate_monitored_items_chunk,
+            max_concurrent_reads: config.performance.max_concurrent_reads.max(1),
+            operation_limits: Mutex::new(None),
             recreate_subscriptions: config.recreate_subscriptions,
+            max_republish_attempts: config.max_republish_attempts,
+            named_tasks: config.named_tasks,
             should_reconnect: AtomicBool::new(true),
             subscription_state: Mutex::new(SubscriptionState::new(
                 config.min_publish_interval,
@@ -226,8 +278,14 @@ impl Session {
             monitored_item_handle: AtomicHandle::new(1000),
             publish_limits_watch_rx,
             publish_limits_watch_tx,
+            publish_counters: PublishCounters::default(),
             trigger_publish_tx,
+            notification_lock: tokio::sync::Mutex::new(()),
             session_nonce_length: config.session_nonce_length,
+            server_auditing: AtomicBool::new(false),
+            sync_type_loaders,
+            namespace_cache: Mutex::new(None),
+            connection_state_callbacks: Mutex::new(Vec::new()),
             decoding_options,
         });
 
@@ -239,6 +297,7 @@ impl Session {
                 trigger_publish_rx,
                 config.keep_alive_interval,
                 config.max_failed_keep_alive_count,
+                config.keep_alive_only_when_idle,
             ),
         )
     }
@@ -252,6 +311,9 @@ impl Session {
     /// session counter.
     pub(crate) fn reset(&self) {
         self.session_id.store(Arc::new(NodeId::null()));
+        // The server may have restarted with a different namespace array and limits.
+        *self.namespace_cache.lock() = None;
+        *self.operation_limits.lock() = None;
         self.internal_session_id.store(
             NEXT_SESSION_ID.fetch_add(1, Ordering::Relaxed),
             Ordering::Relaxed,
@@ -293,6 +355,27 @@ impl Session {
         self.wait_for_state(true).await
     }
 
+    /// Register a callback that is called each time the state of the connection
+    /// to the server changes, see [`ConnectionState`].
+    ///
+    /// The callback is called from the session event loop, so it should return quickly.
+    /// To detect a dead connection, configure `max_failed_keep_alive_count` on the client.
+    pub fn on_connection_status_change(
+        &self,
+        callback: impl Fn(ConnectionState) + Send + Sync + 'static,
+    ) {
+        self.connection_state_callbacks
+            .lock()
+            .push(Arc::new(callback));
+    }
+
+    pub(super) fn notify_connection_state(&self, state: ConnectionState) {
+        let callbacks = self.connection_state_callbacks.lock().clone();
+        for cb in callbacks {
+            cb(state);
+        }
+    }
+
     /// Disable automatic reconnects.
     /// This will make the event loop quit the next time
     /// it disconnects for whatever reason.
@@ -388,6 +471,28 @@ impl Session {
             .add(type_loader);
     }
 
+    /// Fetch the namespace array from the server and use it for the encoding context,
+    /// so that registered type loaders decode values using the namespace indices
+    /// of the server.
+    ///
+    /// This is called automatically after the session is activated if any type
+    /// loaders were added using [`SessionBuilder::type_loader`](crate::SessionBuilder::type_loader).
+    /// Call it manually if the namespace array on the server changes, or if type loaders
+    /// were added to a session that was built without any.
+    pub async fn ensure_type_loaders(&self) -> Result<NamespaceMap, Error> {
+        let map = self.read_namespace_array().await?;
+        let ctx = self.encoding_context().read();
+        for uri in ctx.loaders().iter().filter_map(|l| l.namespace_uri()) {
+            if map.get_index(uri).is_none() {
+                session_warn!(
+                    self,
+                    "Namespace {uri} of a registered type loader is not in the namespace array of the server"
+                );
+            }
+        }
+        Ok(map)
+    }
+
     /// Get a reference to the encoding
     pub fn context(&self) -> Arc<RwLock<ContextOwned>> {
         self.channel.secure_channel.read().context_arc()
@@ -411,31 +516,322 @@ impl Session {
 
     /// Return namespace array from server and store in namespace cache
     pub async fn read_namespace_array(&self) -> Result<NamespaceMap, Error> {
+        let value = self.read_namespace_array_value().await?;
+        let map = Self::namespace_map_from_value(&value)?;
+        self.store_namespaces(Arc::new(map.clone()), value.source_timestamp);
+        Ok(map)
+    }
+
+    /// Get the namespace array of the server. The namespace array is read from the
+    /// server the first time this is called, and cached until the session is reset,
+    /// or until it is updated with [`Session::refresh_namespaces`] or
+    /// [`Session::update_namespaces`].
+    ///
+    /// The namespace map is also used for the encoding context of the session.
+    pub async fn namespaces(&self) -> Result<Arc<NamespaceMap>, Error> {
+        if let Some(cached) = self.namespace_cache.lock().as_ref() {
+            return Ok(cached.map.clone());
+        }
+        self.refresh_namespaces().await
+    }
+
+    /// Read the namespace array from the server, replacing the cached namespace map
+    /// if the source timestamp of the `NamespaceArray` value has changed since
+    /// it was last read.
+    pub async fn refresh_namespaces(&self) -> Result<Arc<NamespaceMap>, Error> {
+        let value = self.read_namespace_array_value().await?;
+        self.update_namespaces(&value)
+    }
+
+    /// Update the cached namespace map from a value of the `NamespaceArray` variable
+    /// of the server, for example one received from a monitored item on
+    /// `VariableId::Server_NamespaceArray`. The cached map is only replaced if the
+    /// source timestamp of `value` differs from the one it was created from.
+    pub fn update_namespaces(&self, value: &DataValue) -> Result<Arc<NamespaceMap>, Error> {
+        if let Some(cached) = self.namespace_cache.lock().as_ref() {
+            if value.source_timestamp.is_some() && cached.timestamp == value.source_timestamp {
+                return Ok(cached.map.clone());
+            }
+        }
+        let map = Arc::new(Self::namespace_map_from_value(value)?);
+        self.store_namespaces(map.clone(), value.source_timestamp);
+        Ok(map)
+    }
+
+    async fn read_namespace_array_value(&self) -> Result<DataValue, Error> {
         let nodeid: NodeId = VariableId::Server_NamespaceArray.into();
-        let result = self
+        let mut result = self
             .read(
                 &[ReadValueId::from(nodeid)],
-                TimestampsToReturn::Neither,
+                TimestampsToReturn::Source,
                 0.0,
             )
             .await
             .map_err(|status_code| {
                 Error::new(status_code, "Reading Server namespace array failed")
             })?;
+        if result.is_empty() {
+            return Err(Error::new(
+                StatusCode::BadUnexpectedError,
+                "Server returned no results when reading the namespace array",
+            ));
+        }
+        Ok(result.swap_remove(0))
+    }
+
+    fn namespace_map_from_value(value: &DataValue) -> Result<NamespaceMap, Error> {
+        if let Some(Variant::Array(array)) = &value.value {
+            NamespaceMap::new_from_variant_array(&array.values)
+                .map_err(|e| Error::new(StatusCode::Bad, e))
+        } else {
+            Err(Error::new(
+                StatusCode::BadNoValue,
+                format!("Server namespace array is None. The server has an issue {value:?}"),
+            ))
+        }
+    }
+
+    fn store_namespaces(&self, map: Arc<NamespaceMap>, timestamp: Option<DateTime>) {
+        self.set_namespaces((*map).clone());
+        *self.namespace_cache.lock() = Some(CachedNamespaces { map, timestamp });
+    }
+
+    /// Return the server array from the server. The index of a server in this array
+    /// is the server index used in [`ExpandedNodeId`]s. The first entry is always
+    /// the application URI of the server itself.
+    pub async fn read_server_array(&self) -> Result<Vec<String>, Error> {
+        let nodeid: NodeId = VariableId::Server_ServerArray.into();
+        let result = self
+            .read(
+                &[ReadValueId::from(nodeid)],
+                TimestampsToReturn::Neither,
+                0.0,
+            )
+            .await
+            .map_err(|status_code| Error::new(status_code, "Reading Server server array failed"))?;
         if let Some(Variant::Array(array)) = &result[0].value {
-            let map = NamespaceMap::new_from_variant_array(&array.values)
-                .map_err(|e| Error::new(StatusCode::Bad, e))?;
-            let map_clone = map.clone();
-            self.set_namespaces(map);
-            Ok(map_clone)
+            array
+                .values
+                .iter()
+                .map(|v| match v {
+                    Variant::String(s) => Ok(s.as_ref().to_owned()),
+                    _ => Err(Error::new(
+                        StatusCode::BadTypeMismatch,
+                        format!("Server array contains a non-string value {v:?}"),
+                    )),
+                })
+                .collect()
         } else {
             Err(Error::new(
                 StatusCode::BadNoValue,
-                format!("Server namespace array is None. The server has an issue {result:?}"),
+                format!("Server server array is None. The server has an issue {result:?}"),
             ))
         }
     }
 
+    /// Resolve an [`ExpandedNodeId`], for example the target of a reference returned
+    /// from browse, to the node it points to.
+    ///
+    /// Node IDs with a server index of 0 are resolved to a local [`NodeId`], using the
+    /// namespace array of the server if the namespace is given as a URI. Otherwise the
+    /// server index is looked up in the `ServerArray` of the server to find the
+    /// URI of the server containing the node.
+    pub async fn resolve_expanded_node_id(
+        &self,
+        node_id: &ExpandedNodeId,
+    ) -> Result<ResolvedTarget, Error> {
+        if node_id.is_local() {
+            let cached = {
+                let ctx = self.encoding_context().read();
+                node_id
+                    .try_resolve(ctx.namespaces())
+                    .map(|n| n.into_owned())
+            };
+            if let Some(id) = cached {
+                return Ok(ResolvedTarget::Local(id));
+            }
+            // The namespace may have been added since the namespace array was last read.
+            let map = self.read_namespace_array().await?;
+            return node_id
+                .try_resolve(&map)
+                .map(|n| ResolvedTarget::Local(n.into_owned()))
+                .ok_or_else(|| {
+                    Error::new(
+                        StatusCode::BadNodeIdUnknown,
+                        format!(
+                            "Namespace {} not found in namespace array",
+                            node_id.namespace_uri
+                        ),
+                    )
+                });
+        }
+
+        let servers = self.read_server_array().await?;
+        let Some(server_uri) = servers.into_iter().nth(node_id.server_index as usize) else {
+            return Err(Error::new(
+                StatusCode::BadServerIndexInvalid,
+                format!(
+                    "Server index {} not found in server array",
+                    node_id.server_index
+                ),
+            ));
+        };
+        Ok(ResolvedTarget::Remote {
+            server_uri,
+            node_id: node_id.clone().with_server_index(0),
+        })
+    }
+
+    /// Read the `Server_Auditing` variable from the server and store it in
+    /// the session. Use this to check whether the server raises audit events
+    /// before relying on them.
+    pub async fn read_server_auditing(&self) -> Result<bool, Error> {
+        let nodeid: NodeId = VariableId::Server_Auditing.into();
+        let result = self
+            .read(
+                &[ReadValueId::from(nodeid)],
+                TimestampsToReturn::Neither,
+                0.0,
+            )
+            .await
+            .map_err(|status_code| Error::new(status_code, "Reading Server auditing failed"))?;
+        if let Some(Variant::Boolean(auditing)) = &result[0].value {
+            self.server_auditing.store(*auditing, Ordering::Relaxed);
+            Ok(*auditing)
+        } else {
+            Err(Error::new(
+                StatusCode::BadNoValue,
+                format!("Server auditing is not a boolean. The server has an issue {result:?}"),
+            ))
+        }
+    }
+
+    /// Get the last value of the `Server_Auditing` variable read from the server
+    /// using [`Session::read_server_auditing`]. This is `false` if it has
+    /// never been read.
+    pub fn server_auditing(&self) -> bool {
+        self.server_auditing.load(Ordering::Relaxed)
+    }
+
+    /// Read the `EURange` property of an analog item, the range of values the
+    /// item is normally expected to have.
+    ///
+    /// Fails with `BadNoMatch` if the node has no `EURange` property, i.e. it is
+    /// not an analog item.
+    pub async fn read_eu_range(&self, node_id: impl Into<NodeId>) -> Result<Range, Error> {
+        self.read_analog_item_property(node_id.into(), "EURange")
+            .await
+    }
+
+    /// Read the `EngineeringUnits` property of an analog item, describing the
+    /// units of the value of the item.
+    ///
+    /// Fails with `BadNoMatch` if the node has no `EngineeringUnits` property, either
+    /// because it is not an analog item or because the server doesn't define units for it.
+    pub async fn read_eu_information(
+        &self,
+        node_id: impl Into<NodeId>,
+    ) -> Result<EUInformation, Error> {
+        self.read_analog_item_property(node_id.into(), "EngineeringUnits")
+            .await
+    }
+
+    /// Find the property with browse name `name` on `node_id` and read its value as
+    /// an extension object containing `T`.
+    async fn read_analog_item_property<T: Send + Sync + 'static>(
+        &self,
+        node_id: NodeId,
+        name: &str,
+    ) -> Result<T, Error> {
+        let path = BrowsePath {
+            starting_node: node_id.clone(),
+            relative_path: RelativePath {
+                elements: Some(vec![RelativePathElement {
+                    reference_type_id: ReferenceTypeId::HasProperty.into(),
+                    is_inverse: false,
+                    include_subtypes: true,
+                    target_name: QualifiedName::new(0, name),
+                }]),
+            },
+        };
+        let result = self
+            .translate_browse_paths_to_node_ids(&[path])
+            .await
+            .map_err(|status_code| {
+                Error::new(
+                    status_code,
+                    format!("Finding {name} property of {node_id} failed"),
+                )
+            })?
+            .into_iter()
+            .next()
+            .ok_or_else(|| {
+                Error::new(
+                    StatusCode::BadUnexpectedError,
+                    "Server returned no result for browse path",
+                )
+            })?;
+        let property_id = match result.status_code {
+            StatusCode::BadNodeIdUnknown => {
+                return Err(Error::new(
+                    StatusCode::BadNodeIdUnknown,
+                    format!("Node {node_id} does not exist"),
+                ))
+            }
+            s if s.is_bad() && s != StatusCode::BadNoMatch => {
+                return Err(Error::new(
+                    s,
+                    format!("Finding {name} property of {node_id} failed"),
+                ))
+            }
+            _ => result
+                .targets
+                .into_iter()
+                .flatten()
+                .find(|t| t.target_id.server_index == 0 && t.target_id.namespace_uri.is_null())
+                .map(|t| t.target_id.node_id)
+                .ok_or_else(|| {
+                    Error::new(
+                        StatusCode::BadNoMatch,
+                        format!("Node {node_id} has no {name} property, it is not an analog item"),
+                    )
+                })?,
+        };
+
+        let value = self
+            .read(
+                &[ReadValueId::from(property_id)],
+                TimestampsToReturn::Neither,
+                0.0,
+            )
+            .await
+            .map_err(|status_code| {
+                Error::new(
+                    status_code,
+                    format!("Reading {name} property of {node_id} failed"),
+                )
+            })?
+            .into_iter()
+            .next()
+            .unwrap_or_else(|| StatusCode::BadUnexpectedError.into());
+        if let Some(status) = value.status.filter(|s| s.is_bad()) {
+            return Err(Error::new(
+                status,
+                format!("Reading {name} property of {node_id} failed"),
+            ));
+        }
+        match value.value {
+            Some(Variant::ExtensionObject(o)) => o.into_inner_as::<T>().map(|v| *v),
+            _ => None,
+        }
+        .ok_or_else(|| {
+            Error::new(
+                StatusCode::BadTypeMismatch,
+                format!("{name} property of {node_id} has an unexpected type"),
+            )
+        })
+    }
+
     /// Return index of supplied namespace url from cache
     pub fn get_namespace_index_from_cache(&self, url: &str) -> Option<u16> {
         self.encoding_context().read().namespaces().get_index(url)
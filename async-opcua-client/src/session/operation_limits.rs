@@ -0,0 +1,230 @@
+use std::sync::atomic::Ordering;
+
+use opcua_types::{
+    DataValue, NodeId, ReadValueId, StatusCode, TimestampsToReturn, VariableId, Variant,
+};
+use tracing::warn;
+
+use super::Session;
+
+/// A single operation limit of the server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OperationLimit {
+    /// The server publishes this limit as 0, meaning there is no limit.
+    Unlimited,
+    /// The maximum number of operations per call published by the server.
+    Limit(usize),
+    /// The server does not publish this limit, or it could not be read,
+    /// so a conservative default is suggested instead. Methods that split
+    /// requests into batches, like [`Session::read_all`], do not apply this default.
+    Default(usize),
+}
+
+impl OperationLimit {
+    /// Limit used for operations the server does not publish a limit for.
+    pub const DEFAULT: usize = 100;
+
+    /// Get the maximum number of operations per call, or `None` if there is no limit.
+    pub fn get(&self) -> Option<usize> {
+        match self {
+            OperationLimit::Unlimited => None,
+            OperationLimit::Limit(v) | OperationLimit::Default(v) => Some(*v),
+        }
+    }
+
+    /// Return `true` if this is a default value, rather than a limit published by the server.
+    pub fn is_default(&self) -> bool {
+        matches!(self, OperationLimit::Default(_))
+    }
+
+    /// Get the limit published by the server, or `None` if the server
+    /// publishes no limit or does not publish this limit at all.
+    pub fn published(&self) -> Option<usize> {
+        match self {
+            OperationLimit::Limit(v) => Some(*v),
+            OperationLimit::Unlimited | OperationLimit::Default(_) => None,
+        }
+    }
+
+    /// Get the number of operations to send in each call when sending `count` operations.
+    /// Only limits published by the server are applied, otherwise all operations
+    /// are sent in a single call.
+    pub fn chunk_size(&self, count: usize) -> usize {
+        self.published().unwrap_or(count).max(1)
+    }
+
+    fn from_value(value: &DataValue) -> Self {
+        let limit = match &value.value {
+            Some(Variant::UInt32(v)) => Some(*v as usize),
+            Some(Variant::UInt16(v)) => Some(*v as usize),
+            _ => None,
+        };
+        match limit {
+            Some(0) => OperationLimit::Unlimited,
+            Some(v) => OperationLimit::Limit(v),
+            None => OperationLimit::Default(Self::DEFAULT),
+        }
+    }
+}
+
+impl Default for OperationLimit {
+    fn default() -> Self {
+        OperationLimit::Default(Self::DEFAULT)
+    }
+}
+
+/// The operation limits of the server, from the
+/// `Server/ServerCapabilities/OperationLimits` object.
+///
+/// These limit the number of operations the server accepts in a single service call.
+/// Get them using [`Session::operation_limits`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct OperationLimits {
+    /// `MaxNodesPerRead`, the maximum number of nodes in a Read call.
+    pub max_nodes_per_read: OperationLimit,
+    /// `MaxNodesPerHistoryReadData`, the maximum number of nodes in a HistoryRead
+    /// call reading raw, modified, processed or at time data.
+    pub max_nodes_per_history_read_data: OperationLimit,
+    /// `MaxNodesPerHistoryReadEvents`, the maximum number of nodes in a HistoryRead
+    /// call reading events.
+    pub max_nodes_per_history_read_events: OperationLimit,
+    /// `MaxNodesPerWrite`, the maximum number of nodes in a Write call.
+    pub max_nodes_per_write: OperationLimit,
+    /// `MaxNodesPerHistoryUpdateData`, the maximum number of nodes in a HistoryUpdate
+    /// call updating data.
+    pub max_nodes_per_history_update_data: OperationLimit,
+    /// `MaxNodesPerHistoryUpdateEvents`, the maximum number of nodes in a HistoryUpdate
+    /// call updating events.
+    pub max_nodes_per_history_update_events: OperationLimit,
+    /// `MaxNodesPerMethodCall`, the maximum number of methods in a Call call.
+    pub max_nodes_per_method_call: OperationLimit,
+    /// `MaxNodesPerBrowse`, the maximum number of nodes in a Browse or BrowseNext call.
+    pub max_nodes_per_browse: OperationLimit,
+    /// `MaxNodesPerRegisterNodes`, the maximum number of nodes in a RegisterNodes
+    /// or UnregisterNodes call.
+    pub max_nodes_per_register_nodes: OperationLimit,
+    /// `MaxNodesPerTranslateBrowsePathsToNodeIds`, the maximum number of browse paths
+    /// in a TranslateBrowsePathsToNodeIds call.
+    pub max_nodes_per_translate_browse_paths_to_node_ids: OperationLimit,
+    /// `MaxNodesPerNodeManagement`, the maximum number of items in an AddNodes,
+    /// AddReferences, DeleteNodes or DeleteReferences call.
+    pub max_nodes_per_node_management: OperationLimit,
+    /// `MaxMonitoredItemsPerCall`, the maximum number of monitored items in a call to
+    /// CreateMonitoredItems, ModifyMonitoredItems, DeleteMonitoredItems, SetMonitoringMode
+    /// or SetTriggering.
+    pub max_monitored_items_per_call: OperationLimit,
+}
+
+impl OperationLimits {
+    const VARIABLES: [VariableId; 12] = [
+        VariableId::Server_ServerCapabilities_OperationLimits_MaxNodesPerRead,
+        VariableId::Server_ServerCapabilities_OperationLimits_MaxNodesPerHistoryReadData,
+        VariableId::Server_ServerCapabilities_OperationLimits_MaxNodesPerHistoryReadEvents,
+        VariableId::Server_ServerCapabilities_OperationLimits_MaxNodesPerWrite,
+        VariableId::Server_ServerCapabilities_OperationLimits_MaxNodesPerHistoryUpdateData,
+        VariableId::Server_ServerCapabilities_OperationLimits_MaxNodesPerHistoryUpdateEvents,
+        VariableId::Server_ServerCapabilities_OperationLimits_MaxNodesPerMethodCall,
+        VariableId::Server_ServerCapabilities_OperationLimits_MaxNodesPerBrowse,
+        VariableId::Server_ServerCapabilities_OperationLimits_MaxNodesPerRegisterNodes,
+        VariableId::Server_ServerCapabilities_OperationLimits_MaxNodesPerTranslateBrowsePathsToNodeIds,
+        VariableId::Server_ServerCapabilities_OperationLimits_MaxNodesPerNodeManagement,
+        VariableId::Server_ServerCapabilities_OperationLimits_MaxMonitoredItemsPerCall,
+    ];
+
+    /// Build the operation limits from the values of the variables in `VARIABLES`, in order.
+    fn from_values(values: &[DataValue]) -> Self {
+        let mut limits = values.iter().map(OperationLimit::from_value);
+        let mut next = || limits.next().unwrap_or_default();
+        Self {
+            max_nodes_per_read: next(),
+            max_nodes_per_history_read_data: next(),
+            max_nodes_per_history_read_events: next(),
+            max_nodes_per_write: next(),
+            max_nodes_per_history_update_data: next(),
+            max_nodes_per_history_update_events: next(),
+            max_nodes_per_method_call: next(),
+            max_nodes_per_browse: next(),
+            max_nodes_per_register_nodes: next(),
+            max_nodes_per_translate_browse_paths_to_node_ids: next(),
+            max_nodes_per_node_management: next(),
+            max_monitored_items_per_call: next(),
+        }
+    }
+}
+
+impl Session {
+    /// Get the operation limits of the server.
+    ///
+    /// The limits are read from the server in a single call the first time this is
+    /// called, then cached on the session until it is recreated. Limits the server
+    /// does not publish are set to [`OperationLimit::Default`].
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(OperationLimits)` - The operation limits of the server.
+    /// * `Err(StatusCode)` - The read request failed. Nothing is cached in this case.
+    ///
+    pub async fn operation_limits(&self) -> Result<OperationLimits, StatusCode> {
+        if let Some(limits) = *self.operation_limits.lock() {
+            return Ok(limits);
+        }
+        let session_id = self.internal_session_id.load(Ordering::Relaxed);
+        let nodes_to_read: Vec<_> = OperationLimits::VARIABLES
+            .iter()
+            .map(|v| ReadValueId::from(NodeId::from(*v)))
+            .collect();
+        let values = self
+            .read(&nodes_to_read, TimestampsToReturn::Neither, 0.0)
+            .await?;
+        let limits = OperationLimits::from_values(&values);
+        // Don't cache limits read from a session that has since been reset.
+        if self.internal_session_id.load(Ordering::Relaxed) == session_id {
+            *self.operation_limits.lock() = Some(limits);
+        }
+        Ok(limits)
+    }
+
+    /// Get the operation limits of the server, using the defaults if they could not be read.
+    pub(crate) async fn operation_limits_or_default(&self) -> OperationLimits {
+        match self.operation_limits().await {
+            Ok(limits) => limits,
+            Err(e) => {
+                warn!("Failed to read operation limits from the server: {e}");
+                OperationLimits::default()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use opcua_types::{DataValue, StatusCode, Variant};
+
+    use super::{OperationLimit, OperationLimits};
+
+    #[test]
+    fn operation_limits_from_values() {
+        let values = [
+            DataValue::new_now(Variant::UInt32(50)),
+            DataValue::new_now(Variant::UInt32(0)),
+            DataValue::from(StatusCode::BadNodeIdUnknown),
+        ];
+        let limits = OperationLimits::from_values(&values);
+        assert_eq!(limits.max_nodes_per_read, OperationLimit::Limit(50));
+        assert_eq!(limits.max_nodes_per_read.chunk_size(120), 50);
+        assert_eq!(
+            limits.max_nodes_per_history_read_data,
+            OperationLimit::Unlimited
+        );
+        assert_eq!(limits.max_nodes_per_history_read_data.chunk_size(120), 120);
+        // Limits that are not published, or missing from the response, use the default.
+        assert!(limits.max_nodes_per_history_read_events.is_default());
+        assert!(limits.max_monitored_items_per_call.is_default());
+        assert_eq!(
+            limits.max_monitored_items_per_call.get(),
+            Some(OperationLimit::DEFAULT)
+        );
+        assert_eq!(limits.max_monitored_items_per_call.published(), None);
+        assert_eq!(limits.max_monitored_items_per_call.chunk_size(120), 120);
+    }
+}
@@ -1,6 +1,8 @@
 use std::{future::Future, time::Duration};
 
+use opcua_core::{RequestMessage, ResponseMessage};
 use opcua_types::{DateTime, DiagnosticBits, IntegerId, NodeId, RequestHeader, StatusCode};
+use tracing::{debug_span, Instrument};
 
 use crate::AsyncSecureChannel;
 
@@ -62,6 +64,24 @@ impl RequestHeaderBuilder {
     }
 }
 
+/// Send a request on the channel, inside a tracing span containing the session ID,
+/// request handle and service, matching the span on the server side.
+pub(crate) async fn send_request(
+    channel: &AsyncSecureChannel,
+    request: impl Into<RequestMessage>,
+    session_id: u32,
+    timeout: Duration,
+) -> Result<ResponseMessage, StatusCode> {
+    let request = request.into();
+    let span = debug_span!(
+        "Outgoing request",
+        session_id,
+        request_handle = request.request_header().request_handle,
+        service = %request.type_name(),
+    );
+    channel.send(request, timeout).instrument(span).await
+}
+
 macro_rules! builder_base {
     ($st:ident) => {
         impl $st {
@@ -82,7 +102,8 @@ macro_rules! builder_base {
             self
         }
 
-        /// Set the timeout for this request. Defaults to session timeout.
+        /// Set the timeout for this request. Defaults to the `request_timeout`
+        /// from the client configuration.
         pub fn timeout(mut self, timeout: std::time::Duration) -> Self {
             self.header.header.timeout_hint = timeout.as_millis().min(u32::MAX as u128) as u32;
             self.header.timeout = timeout;
@@ -95,6 +116,16 @@ macro_rules! builder_base {
             self
         }
 
+        /// Set the preferred locale IDs for this request only, overriding the
+        /// locale IDs the session was activated with.
+        ///
+        /// The locale IDs are sent in the additional header of the request, servers that
+        /// do not recognize them will use the locale IDs of the session instead.
+        pub fn request_locale_ids(mut self, locale_ids: Vec<opcua_types::UAString>) -> Self {
+            self.header.header.set_locale_ids(locale_ids);
+            self
+        }
+
         /// Get the request header.
         pub fn header(&self) -> &opcua_types::RequestHeader {
             &self.header.header
@@ -146,6 +146,10 @@ impl Session {
             match next_request.send(&self.channel).boxed().await {
                 Ok(r) => break Ok(r),
                 Err(e) => {
+                    // There is no point retrying once the session event loop is gone.
+                    if self.channel.requests_closed() {
+                        break Err(e);
+                    }
                     if let Some(delay) = policy.get_next_delay(e) {
                         session_debug!(self, "Request failed, retrying after {delay:?}");
                         tokio::time::sleep(delay).await;
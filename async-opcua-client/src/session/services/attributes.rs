@@ -3,20 +3,25 @@ use std::time::Duration;
 use crate::{
     session::{
         process_service_result, process_unexpected_response,
-        request_builder::{builder_base, builder_debug, builder_error, RequestHeaderBuilder},
+        request_builder::{
+            builder_base, builder_debug, builder_error, send_request, RequestHeaderBuilder,
+        },
         UARequest,
     },
     AsyncSecureChannel, Session,
 };
+use futures::StreamExt;
 use opcua_core::ResponseMessage;
 use opcua_types::{
-    DataValue, DeleteAtTimeDetails, DeleteEventDetails, DeleteRawModifiedDetails, ExtensionObject,
-    HistoryReadRequest, HistoryReadResponse, HistoryReadResult, HistoryReadValueId,
-    HistoryUpdateRequest, HistoryUpdateResponse, HistoryUpdateResult, IntegerId, NodeId,
-    ReadAtTimeDetails, ReadEventDetails, ReadProcessedDetails, ReadRawModifiedDetails, ReadRequest,
-    ReadResponse, ReadValueId, StatusCode, TimestampsToReturn, UpdateDataDetails,
-    UpdateEventDetails, UpdateStructureDataDetails, WriteRequest, WriteResponse, WriteValue,
+    BinaryEncodable, DataValue, DeleteAtTimeDetails, DeleteEventDetails, DeleteRawModifiedDetails,
+    ExtensionObject, HistoryReadRequest, HistoryReadResponse, HistoryReadResult,
+    HistoryReadValueId, HistoryUpdateRequest, HistoryUpdateResponse, HistoryUpdateResult,
+    IntegerId, NodeId, ReadAtTimeDetails, ReadEventDetails, ReadProcessedDetails,
+    ReadRawModifiedDetails, ReadRequest, ReadResponse, ReadValueId, StatusCode, TimestampsToReturn,
+    UpdateDataDetails, UpdateEventDetails, UpdateStructureDataDetails, WriteRequest, WriteResponse,
+    WriteValue,
 };
+use tracing::warn;
 
 /// Enumeration used with Session::history_read()
 #[derive(Debug, Clone)]
@@ -185,7 +190,13 @@ impl UARequest for Read {
             timestamps_to_return: self.timestamps_to_return,
             nodes_to_read: Some(self.nodes_to_read),
         };
-        let response = channel.send(request, self.header.timeout).await?;
+        let response = send_request(
+            channel,
+            request,
+            self.header.session_id,
+            self.header.timeout,
+        )
+        .await?;
         if let ResponseMessage::Read(response) = response {
             builder_debug!(self, "read(), success");
             process_service_result(&response.response_header)?;
@@ -300,7 +311,13 @@ impl UARequest for HistoryRead {
             },
         };
 
-        let response = channel.send(request, self.header.timeout).await?;
+        let response = send_request(
+            channel,
+            request,
+            self.header.session_id,
+            self.header.timeout,
+        )
+        .await?;
         if let ResponseMessage::HistoryRead(response) = response {
             builder_debug!(self, "history_read(), success");
             process_service_result(&response.response_header)?;
@@ -376,7 +393,13 @@ impl UARequest for Write {
             request_header: self.header.header,
             nodes_to_write: Some(self.nodes_to_write.to_vec()),
         };
-        let response = channel.send(request, self.header.timeout).await?;
+        let response = send_request(
+            channel,
+            request,
+            self.header.session_id,
+            self.header.timeout,
+        )
+        .await?;
         if let ResponseMessage::Write(response) = response {
             builder_debug!(self, "write(), success");
             process_service_result(&response.response_header)?;
@@ -467,7 +490,13 @@ impl UARequest for HistoryUpdate {
             request_header: self.header.header,
             history_update_details: Some(details),
         };
-        let response = channel.send(request, self.header.timeout).await?;
+        let response = send_request(
+            channel,
+            request,
+            self.header.session_id,
+            self.header.timeout,
+        )
+        .await?;
         if let ResponseMessage::HistoryUpdate(response) = response {
             builder_error!(self, "history_update(), success");
             process_service_result(&response.response_header)?;
@@ -514,6 +543,67 @@ impl Session {
             .unwrap_or_default())
     }
 
+    /// Reads the value of an arbitrary number of nodes, splitting the read into multiple
+    /// [`ReadRequest`]s if the server limits the number of nodes per read.
+    ///
+    /// The batch size is the `MaxNodesPerRead` limit from [`Session::operation_limits`].
+    /// If the server does not publish this limit, all nodes are read in a single request.
+    /// Batches are sent concurrently, up to the configured maximum number of concurrent reads.
+    ///
+    /// See OPC UA Part 4 - Services 5.10.2 for complete description of the service and error responses.
+    ///
+    /// # Arguments
+    ///
+    /// * `nodes_to_read` - A list of [`ReadValueId`] to be read by the server.
+    /// * `timestamps_to_return` - The [`TimestampsToReturn`] for each node, Both, Server, Source or None
+    /// * `max_age` - The maximum age of value to read in milliseconds. See [`Session::read`].
+    ///
+    /// # Returns
+    ///
+    /// A list of [`DataValue`] corresponding to each read operation, in the same order as
+    /// `nodes_to_read`. If a batch fails, each value in that batch is returned with the
+    /// status code the batch failed with.
+    ///
+    pub async fn read_all(
+        &self,
+        nodes_to_read: Vec<ReadValueId>,
+        timestamps_to_return: TimestampsToReturn,
+        max_age: f64,
+    ) -> Vec<DataValue> {
+        if nodes_to_read.is_empty() {
+            return Vec::new();
+        }
+        let max_nodes = self
+            .operation_limits_or_default()
+            .await
+            .max_nodes_per_read
+            .chunk_size(nodes_to_read.len());
+
+        let batches = nodes_to_read.chunks(max_nodes).map(|batch| async move {
+            let len = batch.len();
+            match self.read(batch, timestamps_to_return, max_age).await {
+                Ok(mut results) => {
+                    if results.len() != len {
+                        warn!(
+                            "Server returned {} results for a read of {} nodes",
+                            results.len(),
+                            len
+                        );
+                        results.resize_with(len, || StatusCode::BadUnexpectedError.into());
+                    }
+                    results
+                }
+                Err(e) => vec![DataValue::from(e); len],
+            }
+        });
+
+        futures::stream::iter(batches)
+            .buffered(self.max_concurrent_reads)
+            .flat_map(futures::stream::iter)
+            .collect()
+            .await
+    }
+
     /// Reads historical values or events of one or more nodes. The caller is expected to provide
     /// a HistoryReadAction enum which must be one of the following:
     ///
@@ -579,6 +669,40 @@ impl Session {
             .unwrap_or_default())
     }
 
+    /// Writes values to nodes like [`Session::write`], but splits `nodes_to_write` into as many
+    /// [`WriteRequest`]s as needed so that the encoded values in each request take up at most
+    /// `max_request_bytes` bytes. A single value larger than the limit is sent in a request of its own.
+    ///
+    /// Use this to stay below the negotiated message size when writing large values.
+    /// Requests are sent one after another, if one fails the remaining values are not written.
+    ///
+    /// # Arguments
+    ///
+    /// * `nodes_to_write` - A list of [`WriteValue`] to be sent to the server.
+    /// * `max_request_bytes` - Maximum encoded size of the values in a single request.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Vec<StatusCode>)` - A list of [`StatusCode`] results corresponding to each write operation.
+    /// * `Err(StatusCode)` - Request failed, [Status code](StatusCode) is the reason for failure.
+    ///
+    pub async fn write_batched(
+        &self,
+        nodes_to_write: &[WriteValue],
+        max_request_bytes: usize,
+    ) -> Result<Vec<StatusCode>, StatusCode> {
+        let batches = {
+            let ctx = self.encoding_context().read();
+            let ctx = ctx.context();
+            batch_by_byte_len(nodes_to_write, max_request_bytes, |v| v.byte_len(&ctx))
+        };
+        let mut results = Vec::with_capacity(nodes_to_write.len());
+        for batch in batches {
+            results.extend(self.write(batch).await?);
+        }
+        Ok(results)
+    }
+
     /// Updates historical values. The caller is expected to provide one or more history update operations
     /// in a slice of HistoryUpdateAction enums which are one of the following:
     ///
@@ -612,3 +736,41 @@ impl Session {
             .unwrap_or_default())
     }
 }
+
+/// Split `items` into consecutive batches where the sum of `byte_len` in each batch
+/// is at most `max_bytes`. Items larger than `max_bytes` are put in a batch of their own.
+fn batch_by_byte_len<T>(
+    items: &[T],
+    max_bytes: usize,
+    byte_len: impl Fn(&T) -> usize,
+) -> Vec<&[T]> {
+    let mut batches = Vec::new();
+    let mut start = 0;
+    let mut size = 0;
+    for (idx, item) in items.iter().enumerate() {
+        let len = byte_len(item);
+        if idx > start && size + len > max_bytes {
+            batches.push(&items[start..idx]);
+            start = idx;
+            size = 0;
+        }
+        size += len;
+    }
+    if start < items.len() {
+        batches.push(&items[start..]);
+    }
+    batches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::batch_by_byte_len;
+
+    #[test]
+    fn batch_by_byte_len_splits() {
+        let items = [3, 4, 2, 10, 1, 1, 5];
+        let batches = batch_by_byte_len(&items, 7, |v| *v);
+        assert_eq!(batches, vec![&[3, 4][..], &[2], &[10], &[1, 1, 5]]);
+        assert!(batch_by_byte_len(&[] as &[usize], 7, |v| *v).is_empty());
+    }
+}
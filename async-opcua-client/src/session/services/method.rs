@@ -3,17 +3,201 @@ use std::time::Duration;
 use crate::{
     session::{
         process_unexpected_response,
-        request_builder::{builder_base, builder_debug, builder_error, RequestHeaderBuilder},
+        request_builder::{
+            builder_base, builder_debug, builder_error, send_request, RequestHeaderBuilder,
+        },
         session_error,
     },
     AsyncSecureChannel, Session, UARequest,
 };
 use opcua_core::ResponseMessage;
 use opcua_types::{
-    CallMethodRequest, CallMethodResult, CallRequest, CallResponse, IntegerId, MethodId, NodeId,
-    ObjectId, StatusCode, TryFromVariant, Variant,
+    Argument, AttributeId, BinaryEncodable, BrowsePath, CallMethodRequest, CallMethodResult,
+    CallRequest, CallResponse, Identifier, IntegerId, MethodId, NodeId, ObjectId, QualifiedName,
+    ReadValueId, ReferenceTypeId, RelativePath, RelativePathElement, StatusCode,
+    TimestampsToReturn, TryFromVariant, Variant,
 };
 
+/// Error returned when calling a method using [`Session::call_typed`] or
+/// [`Session::call_typed_validated`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum MethodCallError {
+    /// The request failed, or the method returned a bad status code.
+    Status(StatusCode),
+    /// The server rejected one or more of the input arguments.
+    InvalidArguments {
+        /// Status code returned for the method call.
+        status: StatusCode,
+        /// Status code for each input argument, in the order they were given.
+        input_argument_results: Vec<StatusCode>,
+    },
+    /// The number of input arguments does not match the `InputArguments` property of the method.
+    ArgumentCountMismatch {
+        /// Number of arguments declared by the method.
+        expected: usize,
+        /// Number of arguments given.
+        actual: usize,
+    },
+    /// An input argument does not match the type declared in the `InputArguments`
+    /// property of the method.
+    ArgumentTypeMismatch {
+        /// Index of the argument.
+        index: usize,
+        /// Data type declared by the method.
+        expected: NodeId,
+        /// Data type of the given argument, if it has one.
+        actual: Option<NodeId>,
+    },
+    /// The number of output arguments returned by the server does not match the
+    /// `OutputArguments` property of the method.
+    OutputArgumentCountMismatch {
+        /// Number of output arguments declared by the method.
+        expected: usize,
+        /// Number of output arguments returned.
+        actual: usize,
+    },
+}
+
+impl MethodCallError {
+    /// Get a status code representing this error.
+    pub fn status(&self) -> StatusCode {
+        match self {
+            Self::Status(s) => *s,
+            Self::InvalidArguments { status, .. } => *status,
+            Self::ArgumentCountMismatch { expected, actual } if actual < expected => {
+                StatusCode::BadArgumentsMissing
+            }
+            Self::ArgumentCountMismatch { .. } => StatusCode::BadTooManyArguments,
+            Self::ArgumentTypeMismatch { .. } => StatusCode::BadTypeMismatch,
+            Self::OutputArgumentCountMismatch { .. } => StatusCode::BadUnexpectedError,
+        }
+    }
+}
+
+impl From<StatusCode> for MethodCallError {
+    fn from(value: StatusCode) -> Self {
+        Self::Status(value)
+    }
+}
+
+impl std::fmt::Display for MethodCallError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Status(s) => write!(f, "Method call failed: {s}"),
+            Self::InvalidArguments {
+                status,
+                input_argument_results,
+            } => {
+                write!(f, "Method call failed with {status}, invalid arguments:")?;
+                for (idx, res) in input_argument_results.iter().enumerate() {
+                    if res.is_bad() {
+                        write!(f, " {idx}: {res}")?;
+                    }
+                }
+                Ok(())
+            }
+            Self::ArgumentCountMismatch { expected, actual } => {
+                write!(f, "Method expects {expected} input arguments, got {actual}")
+            }
+            Self::ArgumentTypeMismatch {
+                index,
+                expected,
+                actual,
+            } => match actual {
+                Some(actual) => write!(
+                    f,
+                    "Input argument {index} should have data type {expected}, got {actual}"
+                ),
+                None => write!(
+                    f,
+                    "Input argument {index} should have data type {expected}, got null"
+                ),
+            },
+            Self::OutputArgumentCountMismatch { expected, actual } => write!(
+                f,
+                "Method declares {expected} output arguments, server returned {actual}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for MethodCallError {}
+
+/// Trait for types that can be used as input arguments to a method call.
+///
+/// This is implemented for `Vec<T>` where `T` can be converted into a [`Variant`],
+/// and for tuples of up to 8 values that can each be converted into a [`Variant`].
+pub trait IntoMethodArguments {
+    /// Convert this into a list of input arguments.
+    fn into_method_arguments(self) -> Vec<Variant>;
+}
+
+impl<T: Into<Variant>> IntoMethodArguments for Vec<T> {
+    fn into_method_arguments(self) -> Vec<Variant> {
+        self.into_iter().map(Into::into).collect()
+    }
+}
+
+impl IntoMethodArguments for () {
+    fn into_method_arguments(self) -> Vec<Variant> {
+        Vec::new()
+    }
+}
+
+macro_rules! impl_into_method_arguments {
+    ($($name:ident),+) => {
+        impl<$($name: Into<Variant>),+> IntoMethodArguments for ($($name,)+) {
+            #[allow(non_snake_case)]
+            fn into_method_arguments(self) -> Vec<Variant> {
+                let ($($name,)+) = self;
+                vec![$($name.into()),+]
+            }
+        }
+    };
+}
+
+impl_into_method_arguments!(A);
+impl_into_method_arguments!(A, B);
+impl_into_method_arguments!(A, B, C);
+impl_into_method_arguments!(A, B, C, D);
+impl_into_method_arguments!(A, B, C, D, E);
+impl_into_method_arguments!(A, B, C, D, E, F);
+impl_into_method_arguments!(A, B, C, D, E, F, G);
+impl_into_method_arguments!(A, B, C, D, E, F, G, H);
+
+/// Check that `value` can be passed as an argument described by `argument`.
+///
+/// Only built-in data types are checked, since checking subtypes would require
+/// reading the type hierarchy from the server.
+fn argument_type_matches(argument: &Argument, value: &Variant) -> Result<(), Option<NodeId>> {
+    let actual = value.data_type().map(|d| d.node_id);
+    if value.is_empty() {
+        return Ok(());
+    }
+    if argument.value_rank == -1 && value.is_array()
+        || argument.value_rank >= 1 && !value.is_array()
+    {
+        return Err(actual);
+    }
+
+    let is_builtin = argument.data_type.namespace == 0
+        && matches!(
+            argument.data_type.identifier,
+            Identifier::Numeric(1..=21 | 23 | 25)
+        );
+    if !is_builtin {
+        return Ok(());
+    }
+    if actual.as_ref() == Some(&argument.data_type)
+        // Empty arrays have no data type.
+        || value.as_array().is_some_and(|a| a.is_empty())
+    {
+        Ok(())
+    } else {
+        Err(actual)
+    }
+}
+
 #[derive(Debug, Clone)]
 /// Calls a list of methods on the server by sending a [`CallRequest`] to the server.
 ///
@@ -59,6 +243,31 @@ impl Call {
         self.methods.push(method.into());
         self
     }
+
+    /// Check that each input argument fits in a request on `channel` on its own.
+    /// Larger requests are split into chunks by the transport, but a request
+    /// can never be larger than the limits negotiated with the server.
+    fn check_argument_sizes(&self, channel: &AsyncSecureChannel) -> Result<(), StatusCode> {
+        let max_request_size = channel.max_request_size();
+        if max_request_size == 0 {
+            return Ok(());
+        }
+        let ctx_r = channel.encoding_context().read();
+        let ctx = ctx_r.context();
+        for (method_idx, method) in self.methods.iter().enumerate() {
+            for (arg_idx, arg) in method.input_arguments.iter().flatten().enumerate() {
+                let size = arg.byte_len(&ctx);
+                if size > max_request_size {
+                    builder_error!(
+                        self,
+                        "call(), input argument {arg_idx} of method {method_idx} is {size} bytes, which exceeds the maximum request size of {max_request_size} bytes"
+                    );
+                    return Err(StatusCode::BadRequestTooLarge);
+                }
+            }
+        }
+        Ok(())
+    }
 }
 
 impl UARequest for Call {
@@ -72,6 +281,7 @@ impl UARequest for Call {
             builder_error!(self, "call(), was not supplied with any methods to call");
             return Err(StatusCode::BadNothingToDo);
         }
+        self.check_argument_sizes(channel)?;
 
         builder_debug!(self, "call()");
         let cnt = self.methods.len();
@@ -79,7 +289,13 @@ impl UARequest for Call {
             request_header: self.header.header,
             methods_to_call: Some(self.methods),
         };
-        let response = channel.send(request, self.header.timeout).await?;
+        let response = send_request(
+            channel,
+            request,
+            self.header.session_id,
+            self.header.timeout,
+        )
+        .await?;
         if let ResponseMessage::Call(response) = response {
             if let Some(results) = &response.results {
                 if results.len() != cnt {
@@ -110,6 +326,10 @@ impl Session {
     ///
     /// See OPC UA Part 4 - Services 5.11.2 for complete description of the service and error responses.
     ///
+    /// Large requests and responses are split into chunks, within the limits negotiated
+    /// with the server. If a single input argument is larger than
+    /// [`AsyncSecureChannel::max_request_size`], the call fails with `BadRequestTooLarge`.
+    ///
     /// # Arguments
     ///
     /// * `methods` - The method to call.
@@ -158,6 +378,204 @@ impl Session {
             .unwrap())
     }
 
+    /// Calls a single method on an object on the server, taking the input arguments as
+    /// anything that implements [`IntoMethodArguments`], which includes `Vec<Variant>` and
+    /// tuples of values that can be converted into variants.
+    ///
+    /// See OPC UA Part 4 - Services 5.11.2 for complete description of the service and error responses.
+    ///
+    /// # Arguments
+    ///
+    /// * `object_id` - The object or object type the method is called on.
+    /// * `method_id` - The method to call.
+    /// * `arguments` - Input arguments to the method.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Vec<Variant>)` - The output arguments of the method.
+    /// * `Err(MethodCallError)` - The request failed, or the method returned a bad status code.
+    ///   If the server rejected any input arguments, the status of each argument is
+    ///   returned in [`MethodCallError::InvalidArguments`].
+    ///
+    pub async fn call_typed(
+        &self,
+        object_id: impl Into<NodeId>,
+        method_id: impl Into<NodeId>,
+        arguments: impl IntoMethodArguments,
+    ) -> Result<Vec<Variant>, MethodCallError> {
+        let arguments = arguments.into_method_arguments();
+        let result = self
+            .call_one((
+                object_id.into(),
+                method_id.into(),
+                (!arguments.is_empty()).then_some(arguments),
+            ))
+            .await?;
+
+        if result.status_code.is_bad() {
+            let input_argument_results = result.input_argument_results.unwrap_or_default();
+            if input_argument_results.iter().any(|r| r.is_bad()) {
+                return Err(MethodCallError::InvalidArguments {
+                    status: result.status_code,
+                    input_argument_results,
+                });
+            }
+            return Err(MethodCallError::Status(result.status_code));
+        }
+
+        Ok(result.output_arguments.unwrap_or_default())
+    }
+
+    /// Calls a single method on an object on the server, like [`Session::call_typed`],
+    /// but first reads the `InputArguments` and `OutputArguments` properties of the method
+    /// and checks that the number and types of the arguments match before sending the call.
+    ///
+    /// Only arguments with built-in data types are type checked, other arguments are only
+    /// checked for whether they are arrays or scalars. Methods without an `InputArguments`
+    /// property must be called without input arguments.
+    ///
+    /// # Arguments
+    ///
+    /// * `object_id` - The object or object type the method is called on.
+    /// * `method_id` - The method to call.
+    /// * `arguments` - Input arguments to the method.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Vec<Variant>)` - The output arguments of the method.
+    /// * `Err(MethodCallError)` - The arguments did not match the declared arguments of the method,
+    ///   the request failed, or the method returned a bad status code.
+    ///
+    pub async fn call_typed_validated(
+        &self,
+        object_id: impl Into<NodeId>,
+        method_id: impl Into<NodeId>,
+        arguments: impl IntoMethodArguments,
+    ) -> Result<Vec<Variant>, MethodCallError> {
+        let method_id = method_id.into();
+        let arguments = arguments.into_method_arguments();
+        let (input_arguments, output_arguments) = self.read_method_arguments(&method_id).await?;
+
+        let input_arguments = input_arguments.unwrap_or_default();
+        if input_arguments.len() != arguments.len() {
+            return Err(MethodCallError::ArgumentCountMismatch {
+                expected: input_arguments.len(),
+                actual: arguments.len(),
+            });
+        }
+        for (index, (argument, value)) in input_arguments.iter().zip(arguments.iter()).enumerate() {
+            if let Err(actual) = argument_type_matches(argument, value) {
+                return Err(MethodCallError::ArgumentTypeMismatch {
+                    index,
+                    expected: argument.data_type.clone(),
+                    actual,
+                });
+            }
+        }
+
+        let outputs = self.call_typed(object_id, method_id, arguments).await?;
+        if let Some(output_arguments) = output_arguments {
+            if output_arguments.len() != outputs.len() {
+                return Err(MethodCallError::OutputArgumentCountMismatch {
+                    expected: output_arguments.len(),
+                    actual: outputs.len(),
+                });
+            }
+        }
+        Ok(outputs)
+    }
+
+    /// Read the `InputArguments` and `OutputArguments` properties of a method.
+    async fn read_method_arguments(
+        &self,
+        method_id: &NodeId,
+    ) -> Result<(Option<Vec<Argument>>, Option<Vec<Argument>>), StatusCode> {
+        let paths: Vec<_> = ["InputArguments", "OutputArguments"]
+            .into_iter()
+            .map(|name| BrowsePath {
+                starting_node: method_id.clone(),
+                relative_path: RelativePath {
+                    elements: Some(vec![RelativePathElement {
+                        reference_type_id: ReferenceTypeId::HasProperty.into(),
+                        is_inverse: false,
+                        include_subtypes: true,
+                        target_name: QualifiedName::new(0, name),
+                    }]),
+                },
+            })
+            .collect();
+        let results = self.translate_browse_paths_to_node_ids(&paths).await?;
+
+        let mut property_ids = Vec::with_capacity(2);
+        for result in &results {
+            if result.status_code == StatusCode::BadNodeIdUnknown {
+                return Err(StatusCode::BadMethodInvalid);
+            }
+            property_ids.push(
+                result
+                    .targets
+                    .as_ref()
+                    .and_then(|t| t.first())
+                    .filter(|t| {
+                        t.target_id.server_index == 0 && t.target_id.namespace_uri.is_null()
+                    })
+                    .map(|t| t.target_id.node_id.clone()),
+            );
+        }
+
+        let to_read: Vec<_> = property_ids
+            .iter()
+            .flatten()
+            .map(|id| ReadValueId {
+                node_id: id.clone(),
+                attribute_id: AttributeId::Value as u32,
+                ..Default::default()
+            })
+            .collect();
+        let mut values = if to_read.is_empty() {
+            Vec::new()
+        } else {
+            self.read(&to_read, TimestampsToReturn::Neither, 0.0)
+                .await?
+        }
+        .into_iter();
+
+        let mut read_arguments = || -> Result<Option<Vec<Argument>>, StatusCode> {
+            let Some(value) = values.next() else {
+                return Ok(None);
+            };
+            if let Some(status) = value.status.filter(|s| s.is_bad()) {
+                return Err(status);
+            }
+            let Some(Variant::Array(arr)) = value.value else {
+                return Ok(Some(Vec::new()));
+            };
+            arr.values
+                .into_iter()
+                .map(|v| match v {
+                    Variant::ExtensionObject(o) => o
+                        .into_inner_as::<Argument>()
+                        .map(|a| *a)
+                        .ok_or(StatusCode::BadDecodingError),
+                    _ => Err(StatusCode::BadDecodingError),
+                })
+                .collect::<Result<Vec<_>, _>>()
+                .map(Some)
+        };
+
+        let input = if property_ids[0].is_some() {
+            read_arguments()?
+        } else {
+            None
+        };
+        let output = if property_ids[1].is_some() {
+            read_arguments()?
+        } else {
+            None
+        };
+        Ok((input, output))
+    }
+
     /// Calls GetMonitoredItems via call_method(), putting a sane interface on the input / output.
     ///
     /// # Arguments
@@ -178,6 +596,9 @@ impl Session {
         let method_id: NodeId = MethodId::Server_GetMonitoredItems.into();
         let request: CallMethodRequest = (object_id, method_id, args).into();
         let response = self.call_one(request).await?;
+        if response.status_code.is_bad() {
+            return Err(response.status_code);
+        }
         if let Some(mut result) = response.output_arguments {
             if result.len() == 2 {
                 let server_handles = <Vec<u32>>::try_from_variant(result.remove(0))
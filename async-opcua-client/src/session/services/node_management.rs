@@ -3,7 +3,9 @@ use std::time::Duration;
 use crate::{
     session::{
         process_service_result, process_unexpected_response,
-        request_builder::{builder_base, builder_debug, builder_error, RequestHeaderBuilder},
+        request_builder::{
+            builder_base, builder_debug, builder_error, send_request, RequestHeaderBuilder,
+        },
     },
     Session, UARequest,
 };
@@ -78,7 +80,13 @@ impl UARequest for AddNodes {
             request_header: self.header.header,
             nodes_to_add: Some(self.nodes_to_add),
         };
-        let response = channel.send(request, self.header.timeout).await?;
+        let response = send_request(
+            channel,
+            request,
+            self.header.session_id,
+            self.header.timeout,
+        )
+        .await?;
         if let ResponseMessage::AddNodes(response) = response {
             builder_debug!(self, "add_nodes, success");
             process_service_result(&response.response_header)?;
@@ -152,7 +160,13 @@ impl UARequest for AddReferences {
             request_header: self.header.header,
             references_to_add: Some(self.references_to_add),
         };
-        let response = channel.send(request, self.header.timeout).await?;
+        let response = send_request(
+            channel,
+            request,
+            self.header.session_id,
+            self.header.timeout,
+        )
+        .await?;
         if let ResponseMessage::AddReferences(response) = response {
             builder_debug!(self, "add_references, success");
             process_service_result(&response.response_header)?;
@@ -226,7 +240,13 @@ impl UARequest for DeleteNodes {
             request_header: self.header.header,
             nodes_to_delete: Some(self.nodes_to_delete),
         };
-        let response = channel.send(request, self.header.timeout).await?;
+        let response = send_request(
+            channel,
+            request,
+            self.header.session_id,
+            self.header.timeout,
+        )
+        .await?;
         if let ResponseMessage::DeleteNodes(response) = response {
             builder_debug!(self, "delete_nodes, success");
             process_service_result(&response.response_header)?;
@@ -303,7 +323,13 @@ impl UARequest for DeleteReferences {
             request_header: self.header.header,
             references_to_delete: Some(self.references_to_delete),
         };
-        let response = channel.send(request, self.header.timeout).await?;
+        let response = send_request(
+            channel,
+            request,
+            self.header.session_id,
+            self.header.timeout,
+        )
+        .await?;
         if let ResponseMessage::DeleteReferences(response) = response {
             builder_debug!(self, "delete_references, success");
             process_service_result(&response.response_header)?;
@@ -0,0 +1,289 @@
+use std::time::Duration;
+
+use crate::{
+    session::{
+        process_service_result, process_unexpected_response,
+        request_builder::{
+            builder_base, builder_debug, builder_error, send_request, RequestHeaderBuilder,
+        },
+    },
+    Session, UARequest,
+};
+use opcua_core::ResponseMessage;
+use opcua_types::{
+    ByteString, ContentFilter, IntegerId, NodeId, NodeTypeDescription, QueryFirstRequest,
+    QueryFirstResponse, QueryNextRequest, QueryNextResponse, StatusCode, ViewDescription,
+};
+
+#[derive(Debug, Clone)]
+/// Find nodes matching a set of node types and a filter by sending a [`QueryFirstRequest`] to the server.
+///
+/// See OPC UA Part 4 - Services 5.9.3 for complete description of the service and error responses.
+pub struct QueryFirst {
+    view: ViewDescription,
+    node_types: Vec<NodeTypeDescription>,
+    filter: ContentFilter,
+    max_data_sets_to_return: u32,
+    max_references_to_return: u32,
+
+    header: RequestHeaderBuilder,
+}
+
+builder_base!(QueryFirst);
+
+impl QueryFirst {
+    /// Construct a new call to the `QueryFirst` service.
+    pub fn new(session: &Session) -> Self {
+        Self {
+            view: ViewDescription::default(),
+            node_types: Vec::new(),
+            filter: ContentFilter::default(),
+            max_data_sets_to_return: 0,
+            max_references_to_return: 0,
+
+            header: RequestHeaderBuilder::new_from_session(session),
+        }
+    }
+
+    /// Construct a new call to the `QueryFirst` service, setting header parameters manually.
+    pub fn new_manual(
+        session_id: u32,
+        timeout: Duration,
+        auth_token: NodeId,
+        request_handle: IntegerId,
+    ) -> Self {
+        Self {
+            view: ViewDescription::default(),
+            node_types: Vec::new(),
+            filter: ContentFilter::default(),
+            max_data_sets_to_return: 0,
+            max_references_to_return: 0,
+
+            header: RequestHeaderBuilder::new(session_id, timeout, auth_token, request_handle),
+        }
+    }
+
+    /// Set the view to query.
+    pub fn view(mut self, view: ViewDescription) -> Self {
+        self.view = view;
+        self
+    }
+
+    /// Set node types to query, overwriting any that were set previously.
+    pub fn node_types(mut self, node_types: Vec<NodeTypeDescription>) -> Self {
+        self.node_types = node_types;
+        self
+    }
+
+    /// Add a node type to query.
+    pub fn node_type(mut self, node_type: NodeTypeDescription) -> Self {
+        self.node_types.push(node_type);
+        self
+    }
+
+    /// Set the filter that returned nodes must match. The default is an empty filter,
+    /// which matches all instances of the queried node types.
+    pub fn filter(mut self, filter: ContentFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Set the maximum number of data sets to return. The default is zero, meaning server-defined.
+    pub fn max_data_sets_to_return(mut self, max_data_sets_to_return: u32) -> Self {
+        self.max_data_sets_to_return = max_data_sets_to_return;
+        self
+    }
+
+    /// Set the maximum number of references to return. The default is zero, meaning server-defined.
+    pub fn max_references_to_return(mut self, max_references_to_return: u32) -> Self {
+        self.max_references_to_return = max_references_to_return;
+        self
+    }
+}
+
+impl UARequest for QueryFirst {
+    type Out = QueryFirstResponse;
+
+    async fn send<'a>(self, channel: &'a crate::AsyncSecureChannel) -> Result<Self::Out, StatusCode>
+    where
+        Self: 'a,
+    {
+        if self.node_types.is_empty() {
+            builder_error!(self, "query_first was not supplied with any node types");
+            return Err(StatusCode::BadNothingToDo);
+        }
+        let request = QueryFirstRequest {
+            request_header: self.header.header,
+            view: self.view,
+            node_types: Some(self.node_types),
+            filter: self.filter,
+            max_data_sets_to_return: self.max_data_sets_to_return,
+            max_references_to_return: self.max_references_to_return,
+        };
+        let response = send_request(
+            channel,
+            request,
+            self.header.session_id,
+            self.header.timeout,
+        )
+        .await?;
+        if let ResponseMessage::QueryFirst(response) = response {
+            builder_debug!(self, "query_first, success");
+            process_service_result(&response.response_header)?;
+            Ok(*response)
+        } else {
+            builder_error!(self, "query_first failed");
+            Err(process_unexpected_response(response))
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+/// Continue a query by sending a continuation point in a [`QueryNextRequest`] to the server.
+///
+/// See OPC UA Part 4 - Services 5.9.4 for complete description of the service and error responses.
+pub struct QueryNext {
+    continuation_point: ByteString,
+    release_continuation_point: bool,
+
+    header: RequestHeaderBuilder,
+}
+
+builder_base!(QueryNext);
+
+impl QueryNext {
+    /// Construct a new call to the `QueryNext` service.
+    pub fn new(session: &Session, continuation_point: ByteString) -> Self {
+        Self {
+            continuation_point,
+            release_continuation_point: false,
+
+            header: RequestHeaderBuilder::new_from_session(session),
+        }
+    }
+
+    /// Construct a new call to the `QueryNext` service, setting header parameters manually.
+    pub fn new_manual(
+        continuation_point: ByteString,
+        session_id: u32,
+        timeout: Duration,
+        auth_token: NodeId,
+        request_handle: IntegerId,
+    ) -> Self {
+        Self {
+            continuation_point,
+            release_continuation_point: false,
+
+            header: RequestHeaderBuilder::new(session_id, timeout, auth_token, request_handle),
+        }
+    }
+
+    /// Set release continuation point. Default is false, if this is true,
+    /// the continuation point will be released and no results will be returned.
+    pub fn release_continuation_point(mut self, release_continuation_point: bool) -> Self {
+        self.release_continuation_point = release_continuation_point;
+        self
+    }
+}
+
+impl UARequest for QueryNext {
+    type Out = QueryNextResponse;
+
+    async fn send<'a>(self, channel: &'a crate::AsyncSecureChannel) -> Result<Self::Out, StatusCode>
+    where
+        Self: 'a,
+    {
+        if self.continuation_point.is_null_or_empty() {
+            builder_error!(
+                self,
+                "query_next was not supplied with a continuation point"
+            );
+            return Err(StatusCode::BadNothingToDo);
+        }
+        let request = QueryNextRequest {
+            request_header: self.header.header,
+            release_continuation_point: self.release_continuation_point,
+            continuation_point: self.continuation_point,
+        };
+        let response = send_request(
+            channel,
+            request,
+            self.header.session_id,
+            self.header.timeout,
+        )
+        .await?;
+        if let ResponseMessage::QueryNext(response) = response {
+            builder_debug!(self, "query_next, success");
+            process_service_result(&response.response_header)?;
+            Ok(*response)
+        } else {
+            builder_error!(self, "query_next failed");
+            Err(process_unexpected_response(response))
+        }
+    }
+}
+
+impl Session {
+    /// Find all nodes matching one of `node_types` and `filter` by sending a [`QueryFirstRequest`]
+    /// to the server.
+    ///
+    /// See OPC UA Part 4 - Services 5.9.3 for complete description of the service and error responses.
+    ///
+    /// # Arguments
+    ///
+    /// * `node_types` - A list of [`NodeTypeDescription`] describing the types of nodes to return,
+    ///   and which values to return for each node.
+    /// * `filter` - A [`ContentFilter`] the returned nodes must match. An empty filter matches
+    ///   all instances of the given node types.
+    /// * `max_data_sets_to_return` - The maximum number of data sets to return, 0 means server-defined.
+    /// * `view` - The view to query, if any.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(QueryFirstResponse)` - The response, containing the returned data sets, and a
+    ///   continuation point for use with `query_next()` if there are more results.
+    /// * `Err(StatusCode)` - Request failed, [Status code](StatusCode) is the reason for failure.
+    ///
+    pub async fn query_first(
+        &self,
+        node_types: &[NodeTypeDescription],
+        filter: ContentFilter,
+        max_data_sets_to_return: u32,
+        view: Option<ViewDescription>,
+    ) -> Result<QueryFirstResponse, StatusCode> {
+        QueryFirst::new(self)
+            .node_types(node_types.to_vec())
+            .filter(filter)
+            .max_data_sets_to_return(max_data_sets_to_return)
+            .view(view.unwrap_or_default())
+            .send(&self.channel)
+            .await
+    }
+
+    /// Continue a query by sending a continuation point in a [`QueryNextRequest`] to the server.
+    /// This may have to be called repeatedly to get all the results of the initial query.
+    ///
+    /// See OPC UA Part 4 - Services 5.9.4 for complete description of the service and error responses.
+    ///
+    /// # Arguments
+    ///
+    /// * `release_continuation_point` - Flag indicating if the continuation point should be released by the server
+    /// * `continuation_point` - The continuation point from a previous `query_first()` or `query_next()`.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(QueryNextResponse)` - The response, containing the next data sets, and a revised
+    ///   continuation point if there are more results.
+    /// * `Err(StatusCode)` - Request failed, [Status code](StatusCode) is the reason for failure.
+    ///
+    pub async fn query_next(
+        &self,
+        release_continuation_point: bool,
+        continuation_point: ByteString,
+    ) -> Result<QueryNextResponse, StatusCode> {
+        QueryNext::new(self, continuation_point)
+            .release_continuation_point(release_continuation_point)
+            .send(&self.channel)
+            .await
+    }
+}
@@ -21,7 +21,7 @@ use tracing::error;
 use crate::{
     session::{
         process_service_result, process_unexpected_response,
-        request_builder::{builder_base, builder_error, RequestHeaderBuilder},
+        request_builder::{builder_base, builder_error, send_request, RequestHeaderBuilder},
     },
     AsyncSecureChannel, IdentityToken, Session, UARequest,
 };
@@ -183,7 +183,13 @@ impl UARequest for CreateSession<'_> {
             requested_session_timeout: self.session_timeout,
             max_response_message_size: self.max_response_message_size,
         };
-        let response = channel.send(request, self.header.timeout).await?;
+        let response = send_request(
+            channel,
+            request,
+            self.header.session_id,
+            self.header.timeout,
+        )
+        .await?;
 
         if let ResponseMessage::CreateSession(response) = response {
             tracing::debug!("create_session, success");
@@ -428,6 +434,8 @@ impl ActivateSession {
                 ))
             }
             IdentityToken::IssuedToken(source) => {
+                // Fetch the token on every activation, so that an expired token is
+                // replaced when the session is reactivated.
                 let token = source.0.get_issued_token().await?;
                 let nonce = remote_nonce.as_ref();
                 let cert = remote_cert;
@@ -456,15 +464,17 @@ impl ActivateSession {
         self,
         channel: &AsyncSecureChannel,
     ) -> Result<ActivateSessionRequest, StatusCode> {
-        let (remote_cert, remote_nonce, security_policy, message_security_mode) = {
+        let (remote_cert, security_policy, message_security_mode) = {
             let secure_channel = trace_read_lock!(channel.secure_channel);
             (
                 secure_channel.remote_cert(),
-                secure_channel.remote_nonce_as_byte_string(),
                 secure_channel.security_policy(),
                 secure_channel.security_mode(),
             )
         };
+        // Sign with the last nonce returned for the session, not the nonce of the
+        // secure channel, which changes when the session is reactivated on a new channel.
+        let remote_nonce = channel.session_nonce();
         let (user_identity_token, user_token_signature) = self
             .user_identity_token(
                 &remote_nonce,
@@ -529,14 +539,16 @@ impl UARequest for ActivateSession {
         Self: 'a,
     {
         let timeout = self.header.timeout;
+        let session_id = self.header.session_id;
         let request = self.build_request(channel).await?;
 
-        let response = channel.send(request, timeout).await?;
+        let response = send_request(channel, request, session_id, timeout).await?;
 
         if let ResponseMessage::ActivateSession(response) = response {
             tracing::debug!("activate_session success");
             // trace!("ActivateSessionResponse = {:#?}", response);
             process_service_result(&response.response_header)?;
+            channel.set_session_nonce(&response.server_nonce);
             Ok(*response)
         } else {
             tracing::error!("activate_session failed");
@@ -600,7 +612,13 @@ impl UARequest for CloseSession {
             delete_subscriptions: self.delete_subscriptions,
             request_header: self.header.header,
         };
-        let response = channel.send(request, self.header.timeout).await?;
+        let response = send_request(
+            channel,
+            request,
+            self.header.session_id,
+            self.header.timeout,
+        )
+        .await?;
         if let ResponseMessage::CloseSession(response) = response {
             process_service_result(&response.response_header)?;
             Ok(*response)
@@ -658,7 +676,13 @@ impl UARequest for Cancel {
             request_handle: self.request_handle,
         };
 
-        let response = channel.send(request, self.header.timeout).await?;
+        let response = send_request(
+            channel,
+            request,
+            self.header.session_id,
+            self.header.timeout,
+        )
+        .await?;
         if let ResponseMessage::Cancel(response) = response {
             process_service_result(&response.response_header)?;
             Ok(*response)
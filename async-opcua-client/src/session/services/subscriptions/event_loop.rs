@@ -9,6 +9,8 @@ use crate::{
     Session,
 };
 
+use super::service::PublishResult;
+
 /// An event on the subscription event loop.
 #[derive(Debug)]
 pub enum SubscriptionActivity {
@@ -17,6 +19,18 @@ pub enum SubscriptionActivity {
     /// A publish request failed, either due to a timeout or an error.
     /// The publish request will typically be retried.
     PublishFailed(StatusCode),
+    /// A gap in the sequence numbers of a subscription was detected, and
+    /// recovering the missing notifications using Republish failed, or the gap
+    /// was larger than the configured maximum number of republish attempts.
+    /// The missing notifications are lost.
+    RepublishFailed {
+        /// ID of the subscription with missing notifications.
+        subscription_id: u32,
+        /// Status code of the failed republish request, or `BadMessageNotAvailable`
+        /// if there were too many missing notifications, or the server reported that
+        /// they were no longer available.
+        status: StatusCode,
+    },
 }
 
 /// An event loop for running periodic subscription tasks.
@@ -33,6 +47,8 @@ pub(crate) struct SubscriptionEventLoop {
     // This is true if the client has received a no_subscriptions response,
     // and is waiting for a manual trigger or successful response before resuming publishing.
     no_active_subscription: bool,
+    // Activity to report on the next iteration, in addition to the last.
+    pending_activity: Option<SubscriptionActivity>,
 }
 
 impl SubscriptionEventLoop {
@@ -55,6 +71,7 @@ impl SubscriptionEventLoop {
             session,
             waiting_for_response: false,
             no_active_subscription: false,
+            pending_activity: None,
         }
     }
 
@@ -64,6 +81,10 @@ impl SubscriptionEventLoop {
         futures::stream::unfold(
             (self, FuturesUnordered::new()),
             |(mut slf, mut futures)| async move {
+                if let Some(activity) = slf.pending_activity.take() {
+                    return Some((activity, (slf, futures)));
+                }
+
                 // Store the next publish time, or None if there are no active subscriptions.
                 let mut next = slf.session.next_publish_time(false);
                 let mut recv: tokio::sync::watch::Receiver<Instant> =
@@ -127,8 +148,8 @@ impl SubscriptionEventLoop {
                         }
                         res = next_publish_fut => {
                             match res {
-                                Some(Ok(more_notifications)) => {
-                                    if more_notifications
+                                Some(Ok(result)) => {
+                                    if result.more_notifications
                                         || futures.len()
                                             < slf
                                                 .session
@@ -149,6 +170,12 @@ impl SubscriptionEventLoop {
                                     }
                                     slf.waiting_for_response = false;
                                     slf.no_active_subscription = false;
+                                    if let Some((subscription_id, status)) = result.republish_failure {
+                                        slf.pending_activity = Some(SubscriptionActivity::RepublishFailed {
+                                            subscription_id,
+                                            status,
+                                        });
+                                    }
                                     break SubscriptionActivity::Publish
                                 }
                                 Some(Err(e)) => {
@@ -193,7 +220,7 @@ impl SubscriptionEventLoop {
         )
     }
 
-    fn static_publish(&self) -> impl Future<Output = Result<bool, StatusCode>> + 'static {
+    fn static_publish(&self) -> impl Future<Output = Result<PublishResult, StatusCode>> + 'static {
         let inner_session = self.session.clone();
         async move { inner_session.publish().await }
     }
@@ -3,7 +3,10 @@ pub use event_loop::SubscriptionActivity;
 
 mod callbacks;
 mod service;
+mod snapshot;
 pub(crate) mod state;
+mod status;
+mod stream;
 
 pub use callbacks::{
     DataChangeCallback, EventCallback, OnSubscriptionNotification, OnSubscriptionNotificationCore,
@@ -12,6 +15,7 @@ pub use callbacks::{
 
 use std::{
     collections::{BTreeSet, HashMap},
+    sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
     time::Duration,
 };
 
@@ -22,6 +26,11 @@ pub use service::{
     ModifyMonitoredItems, ModifySubscription, Publish, Republish, SetMonitoringMode,
     SetPublishingMode, SetTriggering, TransferSubscriptions,
 };
+pub use snapshot::SubscriptionStateSnapshot;
+pub use status::SubscriptionStatusAction;
+pub use stream::{
+    StreamBackpressure, SubscriptionNotification, SubscriptionStream, SubscriptionStreamSender,
+};
 
 pub(crate) struct CreateMonitoredItem {
     pub id: u32,
@@ -109,6 +118,18 @@ impl MonitoredItem {
         self.discard_oldest
     }
 
+    /// Active filter on the monitored item, this is typically a `DataChangeFilter`,
+    /// `EventFilter`, or `AggregateFilter`, or null if no filter is set.
+    pub fn filter(&self) -> &ExtensionObject {
+        &self.filter
+    }
+
+    /// Active filter on the monitored item downcast to `T`, if the filter
+    /// is set and is an instance of `T`.
+    pub fn decoded_filter<T: Send + Sync + 'static>(&self) -> Option<&T> {
+        self.filter.inner_as()
+    }
+
     pub(crate) fn set_sampling_interval(&mut self, value: f64) {
         self.sampling_interval = value;
     }
@@ -156,6 +177,11 @@ pub struct Subscription {
     monitored_items: HashMap<u32, MonitoredItem>,
     /// A map of client handle to monitored item id
     client_handles: HashMap<u32, u32>,
+    /// Sequence number of the last received notification message, 0 if none have been received.
+    last_sequence_number: u32,
+    /// Sequence numbers available for republish, as reported in the last publish response
+    /// for this subscription.
+    available_sequence_numbers: Option<Vec<u32>>,
 
     callback: Box<dyn OnSubscriptionNotificationCore>,
 }
@@ -183,6 +209,8 @@ impl Subscription {
             priority,
             monitored_items: HashMap::new(),
             client_handles: HashMap::new(),
+            last_sequence_number: 0,
+            available_sequence_numbers: None,
             callback: status_change_callback,
         }
     }
@@ -314,7 +342,100 @@ impl Subscription {
         }
     }
 
+    /// Get the sequence numbers of notification messages the server reported as
+    /// available for republish in the last publish response for this subscription.
+    ///
+    /// These are notification messages the server has sent, but which have not
+    /// yet been acknowledged. `None` if the server did not report any.
+    pub fn available_sequence_numbers(&self) -> Option<&[u32]> {
+        self.available_sequence_numbers.as_deref()
+    }
+
+    pub(crate) fn set_available_sequence_numbers(&mut self, sequence_numbers: Option<Vec<u32>>) {
+        self.available_sequence_numbers = sequence_numbers;
+    }
+
+    /// Get the sequence numbers of notification messages that were skipped
+    /// before `notification`, based on the last received sequence number.
+    ///
+    /// Keep-alive messages contain the sequence number of the _next_ notification message,
+    /// so the same sequence numbers are missing for a keep-alive and a notification message
+    /// with the same sequence number.
+    ///
+    /// If the server has reported which sequence numbers are available, missing sequence
+    /// numbers that are not available are returned as `unavailable`, since attempting
+    /// to republish them would fail.
+    pub(crate) fn missing_sequence_numbers(
+        &self,
+        notification: &NotificationMessage,
+        limit: usize,
+    ) -> MissingSequenceNumbers {
+        if self.last_sequence_number == 0 {
+            return MissingSequenceNumbers::default();
+        }
+        let expected = next_sequence_number(self.last_sequence_number);
+        if notification.sequence_number == expected {
+            return MissingSequenceNumbers::default();
+        }
+
+        // Sequence numbers behind the last received value are ignored, these
+        // are duplicates of messages that have already been recovered.
+        let mut gap = notification.sequence_number.wrapping_sub(expected) as usize;
+        if notification.sequence_number < expected {
+            // Sequence numbers skip 0 when they roll over.
+            gap = gap.saturating_sub(1);
+        }
+        if gap > u32::MAX as usize / 2 {
+            return MissingSequenceNumbers::default();
+        }
+
+        let mut missing = Vec::with_capacity(gap.min(limit));
+        let mut next = expected;
+        while missing.len() < gap.min(limit) {
+            missing.push(next);
+            next = next_sequence_number(next);
+        }
+        let unavailable = match &self.available_sequence_numbers {
+            Some(available) => {
+                let (available, unavailable) =
+                    missing.into_iter().partition(|s| available.contains(s));
+                missing = available;
+                unavailable
+            }
+            None => Vec::new(),
+        };
+        MissingSequenceNumbers {
+            sequence_numbers: missing,
+            unavailable,
+            truncated: gap > limit,
+        }
+    }
+
+    /// Return `true` if `notification` is a data message with a sequence number
+    /// that has already been received.
+    pub(crate) fn is_duplicate(&self, notification: &NotificationMessage) -> bool {
+        if self.last_sequence_number == 0 || notification.notification_data.is_none() {
+            return false;
+        }
+        notification
+            .sequence_number
+            .wrapping_sub(next_sequence_number(self.last_sequence_number))
+            > u32::MAX / 2
+    }
+
     pub(crate) fn on_notification(&mut self, notification: NotificationMessage) {
+        let received = if notification.notification_data.is_some() {
+            notification.sequence_number
+        } else {
+            // Keep-alive messages contain the next sequence number.
+            notification.sequence_number.wrapping_sub(1)
+        };
+        if received != 0
+            && (self.last_sequence_number == 0
+                || received.wrapping_sub(self.last_sequence_number) < u32::MAX / 2)
+        {
+            self.last_sequence_number = received;
+        }
         self.callback.on_subscription_notification(
             notification,
             MonitoredItemMap::new(&self.monitored_items, &self.client_handles),
@@ -322,6 +443,27 @@ impl Subscription {
     }
 }
 
+/// Get the sequence number following `sequence_number`. Sequence numbers
+/// roll over to 1 after reaching the maximum value.
+fn next_sequence_number(sequence_number: u32) -> u32 {
+    if sequence_number == u32::MAX {
+        1
+    } else {
+        sequence_number + 1
+    }
+}
+
+/// Sequence numbers missing from a subscription.
+#[derive(Debug, Default)]
+pub(crate) struct MissingSequenceNumbers {
+    /// Missing sequence numbers, in order, up to the requested limit.
+    pub sequence_numbers: Vec<u32>,
+    /// Missing sequence numbers that the server no longer has available for republish.
+    pub unavailable: Vec<u32>,
+    /// `true` if there were more missing sequence numbers than the requested limit.
+    pub truncated: bool,
+}
+
 /// A map of monitored items associated with a subscription, allowing lookup by client handle.
 pub struct MonitoredItemMap<'a> {
     /// A map of monitored items associated with the subscription (key = monitored_item_id)
@@ -350,6 +492,7 @@ impl<'a> MonitoredItemMap<'a> {
 
 #[derive(Debug)]
 pub(crate) struct PublishLimits {
+    last_message_roundtrip: Option<Duration>,
     message_roundtrip: Duration,
     publish_interval: Duration,
     subscriptions: usize,
@@ -363,6 +506,7 @@ impl PublishLimits {
 
     pub(crate) fn new() -> Self {
         Self {
+            last_message_roundtrip: None,
             message_roundtrip: Self::MIN_MESSAGE_ROUNDTRIP,
             publish_interval: Duration::ZERO,
             subscriptions: 0,
@@ -372,6 +516,7 @@ impl PublishLimits {
     }
 
     pub(crate) fn update_message_roundtrip(&mut self, message_roundtrip: Duration) {
+        self.last_message_roundtrip = Some(message_roundtrip);
         self.message_roundtrip = message_roundtrip.max(Self::MIN_MESSAGE_ROUNDTRIP);
         self.calculate_publish_limits();
     }
@@ -394,3 +539,279 @@ impl PublishLimits {
             * (self.min_publish_requests);
     }
 }
+
+/// Counters for publish requests sent by a session, updated as requests
+/// are sent and completed.
+#[derive(Debug, Default)]
+pub(crate) struct PublishCounters {
+    outstanding: AtomicUsize,
+    sent: AtomicU64,
+    succeeded: AtomicU64,
+    failed: AtomicU64,
+    more_notifications: AtomicBool,
+}
+
+impl PublishCounters {
+    /// Record that a publish request is being sent. The returned guard
+    /// marks the request as no longer outstanding when dropped.
+    pub(crate) fn start(&self) -> OutstandingPublish<'_> {
+        self.sent.fetch_add(1, Ordering::Relaxed);
+        self.outstanding.fetch_add(1, Ordering::Relaxed);
+        OutstandingPublish { counters: self }
+    }
+
+    /// Record the result of a publish request.
+    pub(crate) fn complete(&self, more_notifications: Option<bool>) {
+        match more_notifications {
+            Some(more) => {
+                self.succeeded.fetch_add(1, Ordering::Relaxed);
+                self.more_notifications.store(more, Ordering::Relaxed);
+            }
+            None => {
+                self.failed.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+
+    pub(crate) fn stats(&self, limits: &PublishLimits) -> PublishStats {
+        let outstanding_requests = self.outstanding.load(Ordering::Relaxed);
+        PublishStats {
+            outstanding_requests,
+            min_publish_requests: limits.min_publish_requests,
+            max_publish_requests: limits.max_publish_requests,
+            last_roundtrip: limits.last_message_roundtrip,
+            requests_sent: self.sent.load(Ordering::Relaxed),
+            responses_received: self.succeeded.load(Ordering::Relaxed),
+            requests_failed: self.failed.load(Ordering::Relaxed),
+            publish_starved: outstanding_requests == 0
+                && self.more_notifications.load(Ordering::Relaxed),
+        }
+    }
+}
+
+pub(crate) struct OutstandingPublish<'a> {
+    counters: &'a PublishCounters,
+}
+
+impl Drop for OutstandingPublish<'_> {
+    fn drop(&mut self) {
+        self.counters.outstanding.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// Snapshot of the publish request statistics of a session,
+/// returned by [`Session::publish_stats`](crate::Session::publish_stats).
+#[derive(Debug, Clone, Default)]
+pub struct PublishStats {
+    /// Number of publish requests currently waiting for a response from the server.
+    pub outstanding_requests: usize,
+    /// Number of publish requests the client tries to keep queued on the server,
+    /// based on the number of subscriptions.
+    pub min_publish_requests: usize,
+    /// Maximum number of publish requests the client will have outstanding at once,
+    /// based on the number of subscriptions, the publishing interval, and the
+    /// message round-trip time.
+    pub max_publish_requests: usize,
+    /// Round-trip time of the last keep-alive request, used to estimate the time
+    /// it takes to deliver a publish response. `None` if no keep-alive has completed yet.
+    pub last_roundtrip: Option<Duration>,
+    /// Total number of publish requests sent on this session.
+    pub requests_sent: u64,
+    /// Total number of successful publish responses received on this session.
+    pub responses_received: u64,
+    /// Total number of publish requests that failed or timed out.
+    pub requests_failed: u64,
+    /// `true` if the server reported that it has more notifications ready, but the
+    /// client has no publish requests queued to receive them.
+    pub publish_starved: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use futures::{FutureExt, StreamExt};
+    use opcua_types::{
+        DataChangeNotification, ExtensionObject, NotificationMessage, StatusChangeNotification,
+        StatusCode,
+    };
+
+    use super::{
+        DataChangeCallback, OnSubscriptionNotification, StreamBackpressure, Subscription,
+        SubscriptionNotification, SubscriptionStatusAction, SubscriptionStream,
+    };
+
+    fn subscription() -> Subscription {
+        Subscription::new(
+            1,
+            Duration::from_millis(100),
+            100,
+            10,
+            0,
+            0,
+            true,
+            Box::new(DataChangeCallback::new(|_, _| {})),
+        )
+    }
+
+    fn data(sequence_number: u32) -> NotificationMessage {
+        NotificationMessage {
+            sequence_number,
+            notification_data: Some(vec![ExtensionObject::from_message(
+                DataChangeNotification::default(),
+            )]),
+            ..Default::default()
+        }
+    }
+
+    fn keep_alive(sequence_number: u32) -> NotificationMessage {
+        NotificationMessage {
+            sequence_number,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn missing_sequence_numbers() {
+        let mut sub = subscription();
+        // Nothing is missing before the first notification.
+        assert!(sub
+            .missing_sequence_numbers(&data(5), 10)
+            .sequence_numbers
+            .is_empty());
+        sub.on_notification(data(5));
+
+        assert!(sub
+            .missing_sequence_numbers(&data(6), 10)
+            .sequence_numbers
+            .is_empty());
+        assert!(sub
+            .missing_sequence_numbers(&keep_alive(6), 10)
+            .sequence_numbers
+            .is_empty());
+
+        let missing = sub.missing_sequence_numbers(&data(9), 10);
+        assert_eq!(missing.sequence_numbers, vec![6, 7, 8]);
+        assert!(!missing.truncated);
+
+        // Keep-alive contains the next sequence number.
+        let missing = sub.missing_sequence_numbers(&keep_alive(9), 10);
+        assert_eq!(missing.sequence_numbers, vec![6, 7, 8]);
+
+        let missing = sub.missing_sequence_numbers(&data(20), 3);
+        assert_eq!(missing.sequence_numbers, vec![6, 7, 8]);
+        assert!(missing.truncated);
+
+        // Old sequence numbers are duplicates, not gaps.
+        assert!(sub
+            .missing_sequence_numbers(&data(3), 10)
+            .sequence_numbers
+            .is_empty());
+        assert!(sub.is_duplicate(&data(5)));
+        assert!(sub.is_duplicate(&data(3)));
+        assert!(!sub.is_duplicate(&data(6)));
+
+        // A keep-alive moves the last sequence number forward.
+        sub.on_notification(keep_alive(6));
+        assert!(sub
+            .missing_sequence_numbers(&data(6), 10)
+            .sequence_numbers
+            .is_empty());
+    }
+
+    #[test]
+    fn missing_sequence_numbers_available() {
+        let mut sub = subscription();
+        sub.on_notification(data(5));
+
+        sub.set_available_sequence_numbers(Some(vec![7, 8, 9]));
+        let missing = sub.missing_sequence_numbers(&data(9), 10);
+        assert_eq!(missing.sequence_numbers, vec![7, 8]);
+        assert_eq!(missing.unavailable, vec![6]);
+
+        // If the server did not report available sequence numbers, try all of them.
+        sub.set_available_sequence_numbers(None);
+        let missing = sub.missing_sequence_numbers(&data(9), 10);
+        assert_eq!(missing.sequence_numbers, vec![6, 7, 8]);
+        assert!(missing.unavailable.is_empty());
+    }
+
+    #[test]
+    fn missing_sequence_numbers_wrap() {
+        let mut sub = subscription();
+        sub.on_notification(data(u32::MAX - 1));
+        let missing = sub.missing_sequence_numbers(&data(2), 10);
+        assert_eq!(missing.sequence_numbers, vec![u32::MAX, 1]);
+    }
+
+    fn status_change(status: StatusCode) -> StatusChangeNotification {
+        StatusChangeNotification {
+            status,
+            ..Default::default()
+        }
+    }
+
+    fn next_status(stream: &mut SubscriptionStream) -> Option<StatusCode> {
+        match stream.next().now_or_never()?? {
+            SubscriptionNotification::StatusChange(s) => Some(s.status),
+            n => panic!("Unexpected notification {n:?}"),
+        }
+    }
+
+    #[test]
+    fn subscription_stream_drop_oldest() {
+        let (mut sender, mut stream) = SubscriptionStream::new(StreamBackpressure::DropOldest(2));
+        assert!(stream.next().now_or_never().is_none());
+
+        sender.on_subscription_status_change(status_change(StatusCode::Good));
+        sender.on_subscription_status_change(status_change(StatusCode::BadTimeout));
+        sender.on_subscription_status_change(status_change(StatusCode::BadNoSubscription));
+        assert_eq!(next_status(&mut stream), Some(StatusCode::BadTimeout));
+        assert_eq!(
+            next_status(&mut stream),
+            Some(StatusCode::BadNoSubscription)
+        );
+        assert!(stream.next().now_or_never().is_none());
+
+        // Remaining notifications are delivered before the stream ends.
+        sender.on_subscription_status_change(status_change(StatusCode::Good));
+        drop(sender);
+        assert_eq!(next_status(&mut stream), Some(StatusCode::Good));
+        assert!(matches!(stream.next().now_or_never(), Some(None)));
+    }
+
+    #[test]
+    fn subscription_stream_unbounded() {
+        let (mut sender, mut stream) = SubscriptionStream::new(StreamBackpressure::Unbounded);
+        for _ in 0..100 {
+            sender.on_subscription_status_change(status_change(StatusCode::Good));
+        }
+        for _ in 0..100 {
+            assert_eq!(next_status(&mut stream), Some(StatusCode::Good));
+        }
+        assert!(stream.next().now_or_never().is_none());
+    }
+
+    #[test]
+    fn subscription_status_action() {
+        use SubscriptionStatusAction::*;
+        for (status, action) in [
+            (StatusCode::Good, Recoverable),
+            (StatusCode::GoodSubscriptionTransferred, Transferred),
+            (StatusCode::BadTimeout, SubscriptionGone),
+            (StatusCode::BadNoSubscription, SubscriptionGone),
+            (StatusCode::BadSessionClosed, SubscriptionGone),
+            (StatusCode::BadShutdown, Recoverable),
+            (StatusCode::BadConnectionClosed, Recoverable),
+            (StatusCode::BadUserAccessDenied, Fatal),
+        ] {
+            assert_eq!(
+                SubscriptionStatusAction::from(&status_change(status)),
+                action,
+                "{status}"
+            );
+        }
+        assert!(SubscriptionStatusAction::from_status(StatusCode::BadTimeout).should_recreate());
+        assert!(!SubscriptionStatusAction::from_status(StatusCode::Good).should_recreate());
+    }
+}
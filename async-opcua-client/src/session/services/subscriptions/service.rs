@@ -1,15 +1,17 @@
 use std::{
-    collections::HashSet,
+    collections::{HashMap, HashSet},
     time::{Duration, Instant},
 };
 
 use crate::{
     session::{
         process_service_result, process_unexpected_response,
-        request_builder::{builder_base, builder_debug, builder_error, RequestHeaderBuilder},
+        request_builder::{
+            builder_base, builder_debug, builder_error, send_request, RequestHeaderBuilder,
+        },
         services::subscriptions::{
             callbacks::OnSubscriptionNotificationCore, CreateMonitoredItem, ModifyMonitoredItem,
-            Subscription,
+            PublishStats, StreamBackpressure, Subscription, SubscriptionStream,
         },
         session_debug, session_error, session_warn,
     },
@@ -33,6 +35,15 @@ use tracing::enabled;
 
 use super::state::SubscriptionState;
 
+/// Result of a successful publish request.
+pub(crate) struct PublishResult {
+    /// The server has more notifications ready, and a new publish request
+    /// should be sent immediately.
+    pub more_notifications: bool,
+    /// Recovering missed notifications failed for the given subscription.
+    pub republish_failure: Option<(u32, StatusCode)>,
+}
+
 /// Create a subscription by sending a [`CreateSubscriptionRequest`] to the server.
 ///
 /// See OPC UA Part 4 - Services 5.13.2 for complete description of the service and error responses.
@@ -157,7 +168,13 @@ impl UARequest for CreateSubscription {
             priority: self.priority,
         };
 
-        let response = channel.send(request, self.header.timeout).await?;
+        let response = send_request(
+            channel,
+            request,
+            self.header.session_id,
+            self.header.timeout,
+        )
+        .await?;
 
         if let ResponseMessage::CreateSubscription(response) = response {
             process_service_result(&response.response_header)?;
@@ -299,7 +316,13 @@ impl UARequest for ModifySubscription {
             priority: self.priority,
         };
 
-        let response = channel.send(request, self.header.timeout).await?;
+        let response = send_request(
+            channel,
+            request,
+            self.header.session_id,
+            self.header.timeout,
+        )
+        .await?;
 
         if let ResponseMessage::ModifySubscription(response) = response {
             process_service_result(&response.response_header)?;
@@ -393,7 +416,13 @@ impl UARequest for SetPublishingMode {
             publishing_enabled: self.publishing_enabled,
             subscription_ids: Some(self.subscription_ids.clone()),
         };
-        let response = channel.send(request, self.header.timeout).await?;
+        let response = send_request(
+            channel,
+            request,
+            self.header.session_id,
+            self.header.timeout,
+        )
+        .await?;
         if let ResponseMessage::SetPublishingMode(response) = response {
             process_service_result(&response.response_header)?;
             let num_results = response
@@ -494,7 +523,13 @@ impl UARequest for Publish {
                 Some(self.acks)
             },
         };
-        let response = channel.send(request, self.header.timeout).await?;
+        let response = send_request(
+            channel,
+            request,
+            self.header.session_id,
+            self.header.timeout,
+        )
+        .await?;
         if let ResponseMessage::Publish(response) = response {
             process_service_result(&response.response_header)?;
             builder_debug!(self, "publish success");
@@ -557,7 +592,13 @@ impl UARequest for Republish {
             retransmit_sequence_number: self.retransmit_sequence_number,
         };
 
-        let response = channel.send(request, self.header.timeout).await?;
+        let response = send_request(
+            channel,
+            request,
+            self.header.session_id,
+            self.header.timeout,
+        )
+        .await?;
 
         if let ResponseMessage::Republish(response) = response {
             process_service_result(&response.response_header)?;
@@ -655,7 +696,13 @@ impl UARequest for TransferSubscriptions {
             subscription_ids: Some(self.subscription_ids),
             send_initial_values: self.send_initial_values,
         };
-        let response = channel.send(request, self.header.timeout).await?;
+        let response = send_request(
+            channel,
+            request,
+            self.header.session_id,
+            self.header.timeout,
+        )
+        .await?;
         if let ResponseMessage::TransferSubscriptions(response) = response {
             process_service_result(&response.response_header)?;
             builder_debug!(self, "transfer_subscriptions success");
@@ -730,7 +777,13 @@ impl UARequest for DeleteSubscriptions {
             request_header: self.header.header,
             subscription_ids: Some(self.subscription_ids.clone()),
         };
-        let response = channel.send(request, self.header.timeout).await?;
+        let response = send_request(
+            channel,
+            request,
+            self.header.session_id,
+            self.header.timeout,
+        )
+        .await?;
         if let ResponseMessage::DeleteSubscriptions(response) = response {
             process_service_result(&response.response_header)?;
 
@@ -896,7 +949,13 @@ impl UARequest for CreateMonitoredItems<'_> {
             items_to_create: Some(self.items_to_create.clone()),
         };
 
-        let response = channel.send(request, self.header.timeout).await?;
+        let response = send_request(
+            channel,
+            request,
+            self.header.session_id,
+            self.header.timeout,
+        )
+        .await?;
 
         if let ResponseMessage::CreateMonitoredItems(response) = response {
             process_service_result(&response.response_header)?;
@@ -1035,7 +1094,13 @@ impl UARequest for ModifyMonitoredItems {
             timestamps_to_return: self.timestamps_to_return,
             items_to_modify: Some(self.items_to_modify),
         };
-        let response = channel.send(request, self.header.timeout).await?;
+        let response = send_request(
+            channel,
+            request,
+            self.header.session_id,
+            self.header.timeout,
+        )
+        .await?;
         if let ResponseMessage::ModifyMonitoredItems(response) = response {
             process_service_result(&response.response_header)?;
             let Some(results) = &response.results else {
@@ -1146,7 +1211,13 @@ impl UARequest for SetMonitoringMode {
             monitoring_mode: self.monitoring_mode,
             monitored_item_ids: Some(self.monitored_item_ids),
         };
-        let response = channel.send(request, self.header.timeout).await?;
+        let response = send_request(
+            channel,
+            request,
+            self.header.session_id,
+            self.header.timeout,
+        )
+        .await?;
         if let ResponseMessage::SetMonitoringMode(response) = response {
             let Some(results) = &response.results else {
                 builder_error!(self, "set_monitoring_mode, got empty response");
@@ -1281,7 +1352,13 @@ impl UARequest for SetTriggering {
             },
         };
 
-        let response = channel.send(request, self.header.timeout).await?;
+        let response = send_request(
+            channel,
+            request,
+            self.header.session_id,
+            self.header.timeout,
+        )
+        .await?;
         if let ResponseMessage::SetTriggering(response) = response {
             let to_add_res = response.add_results.as_deref().unwrap_or(&[]);
             let to_remove_res = response.remove_results.as_deref().unwrap_or(&[]);
@@ -1393,7 +1470,13 @@ impl UARequest for DeleteMonitoredItems {
             subscription_id: self.subscription_id,
             monitored_item_ids: Some(self.items_to_delete.clone()),
         };
-        let response = channel.send(request, self.header.timeout).await?;
+        let response = send_request(
+            channel,
+            request,
+            self.header.session_id,
+            self.header.timeout,
+        )
+        .await?;
         if let ResponseMessage::DeleteMonitoredItems(response) = response {
             process_service_result(&response.response_header)?;
             builder_debug!(self, "delete_monitored_items, success");
@@ -1521,6 +1604,44 @@ impl Session {
         .await
     }
 
+    /// Create a subscription by sending a [`CreateSubscriptionRequest`] to the server, and
+    /// return a [`SubscriptionStream`] yielding its notifications.
+    ///
+    /// See [`Session::create_subscription`] for a description of the parameters.
+    /// `backpressure` decides what happens when notifications arrive faster than the
+    /// stream is consumed. The stream ends when the subscription is deleted.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok((u32, SubscriptionStream))` - identifier for new subscription, and its notification stream.
+    /// * `Err(StatusCode)` - Request failed, [Status code](StatusCode) is the reason for failure.
+    ///
+    #[allow(clippy::too_many_arguments)]
+    pub async fn subscribe_stream(
+        &self,
+        publishing_interval: Duration,
+        lifetime_count: u32,
+        max_keep_alive_count: u32,
+        max_notifications_per_publish: u32,
+        priority: u8,
+        publishing_enabled: bool,
+        backpressure: StreamBackpressure,
+    ) -> Result<(u32, SubscriptionStream), StatusCode> {
+        let (sender, stream) = SubscriptionStream::new(backpressure);
+        let id = self
+            .create_subscription(
+                publishing_interval,
+                lifetime_count,
+                max_keep_alive_count,
+                max_notifications_per_publish,
+                priority,
+                publishing_enabled,
+                sender,
+            )
+            .await?;
+        Ok((id, stream))
+    }
+
     fn subscription_exists(&self, subscription_id: u32) -> bool {
         let subscription_state = trace_lock!(self.subscription_state);
         subscription_state.subscription_exists(subscription_id)
@@ -2054,9 +2175,26 @@ impl Session {
         subscription_state.next_publish_time()
     }
 
-    /// Send a publish request, returning `true` if the session should send a new request
-    /// immediately.
-    pub(crate) async fn publish(&self) -> Result<bool, StatusCode> {
+    /// Get a snapshot of statistics about the publish requests sent by this session.
+    /// This can be used to tune subscription throughput, for example by checking whether
+    /// the server is holding back notifications because there are no publish requests
+    /// queued.
+    pub fn publish_stats(&self) -> PublishStats {
+        self.publish_counters
+            .stats(&self.publish_limits_watch_rx.borrow())
+    }
+
+    /// Send a publish request, returning whether the session should send a new request
+    /// immediately, and whether recovering missed notifications failed.
+    pub(crate) async fn publish(&self) -> Result<PublishResult, StatusCode> {
+        let _outstanding = self.publish_counters.start();
+        let res = self.publish_inner().await;
+        self.publish_counters
+            .complete(res.as_ref().ok().map(|r| r.more_notifications));
+        res
+    }
+
+    async fn publish_inner(&self) -> Result<PublishResult, StatusCode> {
         let acks = {
             let mut subscription_state = trace_lock!(self.subscription_state);
             let acks = subscription_state.take_acknowledgements();
@@ -2073,9 +2211,28 @@ impl Session {
             .await
         {
             Ok(r) => {
+                // Notifications are handled one response at a time, so that recovered
+                // notifications are delivered in order.
+                let _guard = self.notification_lock.lock().await;
+                {
+                    let mut subscription_state = trace_lock!(self.subscription_state);
+                    subscription_state.set_available_sequence_numbers(
+                        r.subscription_id,
+                        r.available_sequence_numbers.clone(),
+                    );
+                }
+                let republish_failure = self
+                    .recover_missing_notifications(r.subscription_id, &r.notification_message)
+                    .await
+                    .err()
+                    .map(|status| (r.subscription_id, status));
+
                 let mut subscription_state = trace_lock!(self.subscription_state);
                 subscription_state.handle_notification(r.subscription_id, r.notification_message);
-                Ok(r.more_notifications)
+                Ok(PublishResult {
+                    more_notifications: r.more_notifications,
+                    republish_failure,
+                })
             }
             Err(e) => {
                 if let Some(acks) = acks {
@@ -2087,6 +2244,78 @@ impl Session {
         }
     }
 
+    /// Check for gaps in the sequence numbers before `notification`, and attempt to
+    /// recover any missing notification messages using Republish, delivering them
+    /// to the subscription in order.
+    async fn recover_missing_notifications(
+        &self,
+        subscription_id: u32,
+        notification: &NotificationMessage,
+    ) -> Result<(), StatusCode> {
+        if self.max_republish_attempts == 0 {
+            return Ok(());
+        }
+        let missing = {
+            let subscription_state = trace_lock!(self.subscription_state);
+            subscription_state.missing_sequence_numbers(
+                subscription_id,
+                notification,
+                self.max_republish_attempts,
+            )
+        };
+        if missing.sequence_numbers.is_empty() && missing.unavailable.is_empty() {
+            return Ok(());
+        }
+        session_warn!(
+            self,
+            "Detected gap in sequence numbers for subscription {}, republishing {} notifications",
+            subscription_id,
+            missing.sequence_numbers.len()
+        );
+
+        let mut res = Ok(());
+        if !missing.unavailable.is_empty() {
+            session_warn!(
+                self,
+                "Notifications {:?} for subscription {} are no longer available on the server and were lost",
+                missing.unavailable,
+                subscription_id
+            );
+            res = Err(StatusCode::BadMessageNotAvailable);
+        }
+        for sequence_number in missing.sequence_numbers {
+            match Republish::new(subscription_id, sequence_number, self)
+                .send(&self.channel)
+                .await
+            {
+                Ok(r) => {
+                    let mut subscription_state = trace_lock!(self.subscription_state);
+                    subscription_state.handle_notification(subscription_id, r.notification_message);
+                }
+                Err(e) => {
+                    session_warn!(
+                        self,
+                        "Failed to republish notification {} for subscription {}: {}",
+                        sequence_number,
+                        subscription_id,
+                        e
+                    );
+                    res = Err(e);
+                }
+            }
+        }
+        if missing.truncated {
+            session_warn!(
+                self,
+                "Gap in sequence numbers for subscription {} exceeds the maximum number of republish attempts, some notifications were lost",
+                subscription_id
+            );
+            res = res.and(Err(StatusCode::BadMessageNotAvailable));
+        }
+
+        res
+    }
+
     /// Send a request to re-publish an unacknowledged notification message from the server.
     ///
     /// If this succeeds, the session will automatically acknowledge the notification in the next publish request.
@@ -2186,6 +2415,9 @@ impl Session {
                 continue;
             };
 
+            // Recreate the monitored items with their original client handles, so that
+            // notifications keep being routed to the same items. Only the server-assigned
+            // monitored item IDs change.
             let items_to_create = subscription
                 .monitored_items
                 .values()
@@ -2203,6 +2435,8 @@ impl Session {
                 .collect::<Vec<MonitoredItemCreateRequest>>();
 
             let mut iter = items_to_create.into_iter();
+            // Map from client handle to the new monitored item ID.
+            let mut new_ids = HashMap::new();
 
             loop {
                 let chunk = (&mut iter)
@@ -2213,17 +2447,60 @@ impl Session {
                     break;
                 }
 
-                let _ = self
+                let Ok(created) = self
                     .create_monitored_items(subscription_id, TimestampsToReturn::Both, chunk)
-                    .await;
+                    .await
+                else {
+                    continue;
+                };
+                for item in created {
+                    if item.result.status_code.is_good() {
+                        new_ids.insert(
+                            item.requested_parameters.client_handle,
+                            item.result.monitored_item_id,
+                        );
+                    }
+                }
+            }
+
+            for item in subscription.monitored_items.values() {
+                session_debug!(
+                    self,
+                    "Recreated monitored item with client handle {}, old id {}, new id {:?}",
+                    item.client_handle(),
+                    item.id(),
+                    new_ids.get(&item.client_handle())
+                );
             }
 
+            // Triggering links refer to monitored item IDs, so they must be mapped to the new IDs.
+            let new_id = |old_id: &u32| {
+                subscription
+                    .monitored_items
+                    .get(old_id)
+                    .and_then(|item| new_ids.get(&item.client_handle()))
+                    .copied()
+            };
             for item in subscription.monitored_items.values() {
                 let triggered_items = item.triggered_items();
-                if !triggered_items.is_empty() {
-                    let links_to_add = triggered_items.iter().copied().collect::<Vec<u32>>();
+                if triggered_items.is_empty() {
+                    continue;
+                }
+                let Some(triggering_item_id) = new_id(&item.id()) else {
+                    continue;
+                };
+                let links_to_add = triggered_items
+                    .iter()
+                    .filter_map(&new_id)
+                    .collect::<Vec<u32>>();
+                if !links_to_add.is_empty() {
                     let _ = self
-                        .set_triggering(subscription_id, item.id(), links_to_add.as_slice(), &[])
+                        .set_triggering(
+                            subscription_id,
+                            triggering_item_id,
+                            links_to_add.as_slice(),
+                            &[],
+                        )
                         .await;
                 }
             }
@@ -0,0 +1,251 @@
+use std::{collections::BTreeSet, time::Duration};
+
+use opcua_types::{
+    BinaryDecodable, BinaryEncodable, Context, Error, ExtensionObject, MonitoringMode, NodeId,
+    NumericRange, QualifiedName, ReadValueId, StatusCode,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::Session;
+
+use super::{MonitoredItem, OnSubscriptionNotificationCore, Subscription};
+
+/// Serializable snapshot of the subscriptions registered on a session, created with
+/// [`Session::export_subscription_state`].
+///
+/// This contains everything needed to rebuild the client-side state of the
+/// subscriptions with [`Session::import_subscription_state`], so that the
+/// subscriptions can be recovered from the server after the client restarts,
+/// without recreating their monitored items.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SubscriptionStateSnapshot {
+    subscriptions: Vec<SubscriptionSnapshot>,
+}
+
+impl SubscriptionStateSnapshot {
+    /// IDs of the subscriptions in the snapshot.
+    pub fn subscription_ids(&self) -> Vec<u32> {
+        self.subscriptions
+            .iter()
+            .map(|s| s.subscription_id)
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct SubscriptionSnapshot {
+    subscription_id: u32,
+    publishing_interval: Duration,
+    lifetime_count: u32,
+    max_keep_alive_count: u32,
+    max_notifications_per_publish: u32,
+    priority: u8,
+    publishing_enabled: bool,
+    last_sequence_number: u32,
+    monitored_items: Vec<MonitoredItemSnapshot>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct MonitoredItemSnapshot {
+    id: u32,
+    client_handle: u32,
+    node_id: String,
+    attribute_id: u32,
+    index_range: String,
+    data_encoding: Option<(u16, String)>,
+    queue_size: usize,
+    monitoring_mode: i32,
+    sampling_interval: f64,
+    triggered_items: Vec<u32>,
+    discard_oldest: bool,
+    /// The filter, in OPC UA binary encoding. `None` if there is no filter.
+    filter: Option<Vec<u8>>,
+}
+
+impl SubscriptionSnapshot {
+    fn new(subscription: &Subscription, ctx: &Context<'_>) -> Self {
+        let mut monitored_items: Vec<_> = subscription
+            .monitored_items
+            .values()
+            .map(|item| MonitoredItemSnapshot::new(item, ctx))
+            .collect();
+        monitored_items.sort_by_key(|i| i.id);
+        Self {
+            subscription_id: subscription.subscription_id,
+            publishing_interval: subscription.publishing_interval,
+            lifetime_count: subscription.lifetime_count,
+            max_keep_alive_count: subscription.max_keep_alive_count,
+            max_notifications_per_publish: subscription.max_notifications_per_publish,
+            priority: subscription.priority,
+            publishing_enabled: subscription.publishing_enabled,
+            last_sequence_number: subscription.last_sequence_number,
+            monitored_items,
+        }
+    }
+
+    fn into_subscription(
+        self,
+        ctx: &Context<'_>,
+        callback: Box<dyn OnSubscriptionNotificationCore>,
+    ) -> Result<Subscription, Error> {
+        let mut subscription = Subscription::new(
+            self.subscription_id,
+            self.publishing_interval,
+            self.lifetime_count,
+            self.max_keep_alive_count,
+            self.max_notifications_per_publish,
+            self.priority,
+            self.publishing_enabled,
+            callback,
+        );
+        subscription.last_sequence_number = self.last_sequence_number;
+        for item in self.monitored_items {
+            subscription.insert_existing_monitored_item(item.into_monitored_item(ctx)?);
+        }
+        Ok(subscription)
+    }
+}
+
+impl MonitoredItemSnapshot {
+    fn new(item: &MonitoredItem, ctx: &Context<'_>) -> Self {
+        let target = &item.item_to_monitor;
+        Self {
+            id: item.id,
+            client_handle: item.client_handle,
+            node_id: target.node_id.to_string(),
+            attribute_id: target.attribute_id,
+            index_range: target.index_range.to_string(),
+            data_encoding: (!target.data_encoding.is_null()).then(|| {
+                (
+                    target.data_encoding.namespace_index,
+                    target.data_encoding.name.as_ref().to_owned(),
+                )
+            }),
+            queue_size: item.queue_size,
+            monitoring_mode: item.monitoring_mode as i32,
+            sampling_interval: item.sampling_interval,
+            triggered_items: item.triggered_items.iter().copied().collect(),
+            discard_oldest: item.discard_oldest,
+            filter: (!item.filter.is_null()).then(|| item.filter.encode_to_vec(ctx)),
+        }
+    }
+
+    fn into_monitored_item(self, ctx: &Context<'_>) -> Result<MonitoredItem, Error> {
+        let node_id = self.node_id.parse::<NodeId>().map_err(|_| {
+            Error::new(
+                StatusCode::BadNodeIdInvalid,
+                format!(
+                    "Invalid node ID {} in monitored item {}",
+                    self.node_id, self.id
+                ),
+            )
+        })?;
+        let index_range = self.index_range.parse::<NumericRange>().map_err(|e| {
+            Error::new(
+                StatusCode::BadIndexRangeInvalid,
+                format!("Invalid index range in monitored item {}: {e}", self.id),
+            )
+        })?;
+        let filter = match self.filter {
+            Some(filter) => ExtensionObject::decode(&mut filter.as_slice(), ctx)?,
+            None => ExtensionObject::null(),
+        };
+        Ok(MonitoredItem {
+            id: self.id,
+            client_handle: self.client_handle,
+            item_to_monitor: ReadValueId {
+                node_id,
+                attribute_id: self.attribute_id,
+                index_range,
+                data_encoding: self
+                    .data_encoding
+                    .map(|(ns, name)| QualifiedName::new(ns, name))
+                    .unwrap_or_default(),
+            },
+            queue_size: self.queue_size,
+            monitoring_mode: MonitoringMode::try_from(self.monitoring_mode)?,
+            sampling_interval: self.sampling_interval,
+            triggered_items: BTreeSet::from_iter(self.triggered_items),
+            discard_oldest: self.discard_oldest,
+            filter,
+        })
+    }
+}
+
+impl Session {
+    /// Create a snapshot of the subscriptions and monitored items registered on this session.
+    ///
+    /// The snapshot can be serialized and stored, then used to restore the
+    /// subscriptions with [`Session::import_subscription_state`] after the client restarts.
+    pub fn export_subscription_state(&self) -> SubscriptionStateSnapshot {
+        let ctx_r = self.encoding_context().read();
+        let ctx = ctx_r.context();
+        let state = self.subscription_state.lock();
+        let mut subscriptions: Vec<_> = state
+            .subscription_ids()
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|id| state.get(id))
+            .map(|s| SubscriptionSnapshot::new(s, &ctx))
+            .collect();
+        subscriptions.sort_by_key(|s| s.subscription_id);
+        SubscriptionStateSnapshot { subscriptions }
+    }
+
+    /// Restore the client-side state of the subscriptions in `snapshot`, created with
+    /// [`Session::export_subscription_state`].
+    ///
+    /// This does not call the server. The subscriptions must still exist on the server,
+    /// either on the session this session reactivated, or on a closed session, in which
+    /// case they need to be moved to this session with [`Session::transfer_subscriptions`].
+    ///
+    /// # Arguments
+    ///
+    /// * `snapshot` - The subscriptions to restore.
+    /// * `make_callback` - Called with the ID of each subscription to create the callback
+    ///   that receives its notifications.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Vec<u32>)` - The IDs of the restored subscriptions.
+    /// * `Err(Error)` - The snapshot is invalid, or one of the subscriptions already exists
+    ///   on this session. No subscriptions are restored in this case.
+    ///
+    pub fn import_subscription_state<T: OnSubscriptionNotificationCore + 'static>(
+        &self,
+        snapshot: SubscriptionStateSnapshot,
+        mut make_callback: impl FnMut(u32) -> T,
+    ) -> Result<Vec<u32>, Error> {
+        let subscriptions = {
+            let ctx_r = self.encoding_context().read();
+            let ctx = ctx_r.context();
+            snapshot
+                .subscriptions
+                .into_iter()
+                .map(|s| {
+                    let callback = Box::new(make_callback(s.subscription_id));
+                    s.into_subscription(&ctx, callback)
+                })
+                .collect::<Result<Vec<_>, _>>()?
+        };
+
+        let mut state = self.subscription_state.lock();
+        if let Some(existing) = subscriptions
+            .iter()
+            .find(|s| state.subscription_exists(s.subscription_id()))
+        {
+            return Err(Error::new(
+                StatusCode::BadSubscriptionIdInvalid,
+                format!(
+                    "Subscription {} already exists on the session",
+                    existing.subscription_id()
+                ),
+            ));
+        }
+        let ids = subscriptions.iter().map(|s| s.subscription_id()).collect();
+        for subscription in subscriptions {
+            state.add_subscription(subscription);
+        }
+        Ok(ids)
+    }
+}
@@ -5,7 +5,9 @@ use std::{
 
 use opcua_types::{MonitoringMode, NotificationMessage, SubscriptionAcknowledgement};
 
-use super::{CreateMonitoredItem, ModifyMonitoredItem, PublishLimits, Subscription};
+use super::{
+    CreateMonitoredItem, MissingSequenceNumbers, ModifyMonitoredItem, PublishLimits, Subscription,
+};
 
 /// State containing all known subscriptions in the session.
 pub struct SubscriptionState {
@@ -215,6 +217,14 @@ impl SubscriptionState {
     ) {
         self.add_acknowledgement(subscription_id, notification.sequence_number);
         if let Some(sub) = self.subscriptions.get_mut(&subscription_id) {
+            if sub.is_duplicate(&notification) {
+                tracing::debug!(
+                    "Ignoring notification {} for subscription {}, it has already been received",
+                    notification.sequence_number,
+                    subscription_id
+                );
+                return;
+            }
             sub.on_notification(notification);
         } else {
             tracing::warn!(
@@ -224,6 +234,28 @@ impl SubscriptionState {
         }
     }
 
+    pub(crate) fn set_available_sequence_numbers(
+        &mut self,
+        subscription_id: u32,
+        sequence_numbers: Option<Vec<u32>>,
+    ) {
+        if let Some(sub) = self.subscriptions.get_mut(&subscription_id) {
+            sub.set_available_sequence_numbers(sequence_numbers);
+        }
+    }
+
+    pub(crate) fn missing_sequence_numbers(
+        &self,
+        subscription_id: u32,
+        notification: &NotificationMessage,
+        limit: usize,
+    ) -> MissingSequenceNumbers {
+        self.subscriptions
+            .get(&subscription_id)
+            .map(|s| s.missing_sequence_numbers(notification, limit))
+            .unwrap_or_default()
+    }
+
     fn set_keep_alive_timeout(&mut self) {
         self.keep_alive_timeout = self
             .subscriptions
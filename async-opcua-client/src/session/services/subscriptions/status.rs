@@ -0,0 +1,65 @@
+use opcua_types::{StatusChangeNotification, StatusCode, SubStatusCode};
+
+/// Classification of the status in a [`StatusChangeNotification`], describing
+/// how an application should react to it.
+///
+/// Use [`SubscriptionStatusAction::from_status`] or the `From` implementation on
+/// the notification in
+/// [`OnSubscriptionNotification::on_subscription_status_change`](crate::OnSubscriptionNotification::on_subscription_status_change).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SubscriptionStatusAction {
+    /// The subscription is still alive, or the problem is transient, for example
+    /// a lost connection or a server restart. The client will attempt to recover
+    /// the subscription on its own, so the application should keep waiting.
+    Recoverable,
+    /// The subscription no longer exists on the server, for example because its lifetime
+    /// expired (`BadTimeout`) or the session was closed. It must be recreated to receive
+    /// further notifications.
+    SubscriptionGone,
+    /// The subscription was transferred to another session (`GoodSubscriptionTransferred`).
+    /// This session will receive no more notifications for it.
+    Transferred,
+    /// The subscription failed in a way that recreating it will not fix,
+    /// the application should give up on it.
+    Fatal,
+}
+
+impl SubscriptionStatusAction {
+    /// Classify the status of a [`StatusChangeNotification`].
+    ///
+    /// See the description of `StatusChangeNotification` in OPC UA Part 4 - Services
+    /// for the status codes a server may report.
+    pub fn from_status(status: StatusCode) -> Self {
+        match status.sub_code() {
+            SubStatusCode::GoodSubscriptionTransferred => Self::Transferred,
+            SubStatusCode::BadTimeout
+            | SubStatusCode::BadNoSubscription
+            | SubStatusCode::BadSubscriptionIdInvalid
+            | SubStatusCode::BadSessionIdInvalid
+            | SubStatusCode::BadSessionClosed => Self::SubscriptionGone,
+            SubStatusCode::BadShutdown
+            | SubStatusCode::BadServerHalted
+            | SubStatusCode::BadCommunicationError
+            | SubStatusCode::BadConnectionClosed
+            | SubStatusCode::BadSecureChannelClosed
+            | SubStatusCode::BadServerNotConnected
+            | SubStatusCode::BadNotConnected
+            | SubStatusCode::BadRequestTimeout
+            | SubStatusCode::BadTooManyPublishRequests
+            | SubStatusCode::BadResourceUnavailable => Self::Recoverable,
+            _ if status.is_bad() => Self::Fatal,
+            _ => Self::Recoverable,
+        }
+    }
+
+    /// Return `true` if the subscription must be recreated to keep receiving notifications.
+    pub fn should_recreate(&self) -> bool {
+        matches!(self, Self::SubscriptionGone)
+    }
+}
+
+impl From<&StatusChangeNotification> for SubscriptionStatusAction {
+    fn from(value: &StatusChangeNotification) -> Self {
+        Self::from_status(value.status)
+    }
+}
@@ -0,0 +1,165 @@
+use std::{
+    collections::VecDeque,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    task::{Context, Poll},
+};
+
+use futures::{task::AtomicWaker, Stream};
+use opcua_core::sync::Mutex;
+use opcua_types::{DataValue, NodeId, StatusChangeNotification, Variant};
+
+use crate::MonitoredItem;
+
+use super::OnSubscriptionNotification;
+
+#[derive(Debug, Clone)]
+/// A notification received on a subscription, produced by a [SubscriptionStream].
+pub enum SubscriptionNotification {
+    /// A data value changed on a monitored item.
+    DataChange {
+        /// Server-assigned ID of the monitored item.
+        monitored_item_id: u32,
+        /// Client handle of the monitored item.
+        client_handle: u32,
+        /// Node the monitored item is monitoring.
+        node_id: NodeId,
+        /// The new value.
+        value: DataValue,
+    },
+    /// An event was received on a monitored item.
+    Event {
+        /// Server-assigned ID of the monitored item.
+        monitored_item_id: u32,
+        /// Client handle of the monitored item.
+        client_handle: u32,
+        /// Node the monitored item is monitoring.
+        node_id: NodeId,
+        /// Selected event fields.
+        event_fields: Option<Vec<Variant>>,
+    },
+    /// The subscription changed state on the server.
+    StatusChange(StatusChangeNotification),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// What a [SubscriptionStream] does when notifications arrive faster than
+/// they are consumed.
+///
+/// Notifications are produced on the session event loop, which cannot wait
+/// for the consumer without stalling the session, so there is no blocking option.
+pub enum StreamBackpressure {
+    /// Keep at most this many notifications, discarding the oldest when full.
+    DropOldest(usize),
+    /// Never discard notifications. The queue grows without bound if the consumer
+    /// falls behind.
+    Unbounded,
+}
+
+struct StreamShared {
+    queue: Mutex<VecDeque<SubscriptionNotification>>,
+    waker: AtomicWaker,
+    closed: AtomicBool,
+}
+
+/// Adapter implementing [OnSubscriptionNotification] by forwarding notifications
+/// to a [SubscriptionStream].
+///
+/// The stream ends once this is dropped, which happens when the subscription is deleted.
+pub struct SubscriptionStreamSender {
+    shared: Arc<StreamShared>,
+    backpressure: StreamBackpressure,
+}
+
+/// A stream of notifications on a subscription. Created with [SubscriptionStream::new] or
+/// [Session::subscribe_stream](crate::Session::subscribe_stream).
+pub struct SubscriptionStream {
+    shared: Arc<StreamShared>,
+}
+
+impl SubscriptionStream {
+    /// Create a new subscription stream, and the sender that feeds it.
+    /// Pass the sender as the callback when creating a subscription.
+    pub fn new(backpressure: StreamBackpressure) -> (SubscriptionStreamSender, Self) {
+        let shared = Arc::new(StreamShared {
+            queue: Mutex::new(VecDeque::new()),
+            waker: AtomicWaker::new(),
+            closed: AtomicBool::new(false),
+        });
+        (
+            SubscriptionStreamSender {
+                shared: shared.clone(),
+                backpressure,
+            },
+            Self { shared },
+        )
+    }
+}
+
+impl Stream for SubscriptionStream {
+    type Item = SubscriptionNotification;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if let Some(n) = self.shared.queue.lock().pop_front() {
+            return Poll::Ready(Some(n));
+        }
+        self.shared.waker.register(cx.waker());
+        // Check again after registering, in case a notification arrived in between.
+        if let Some(n) = self.shared.queue.lock().pop_front() {
+            return Poll::Ready(Some(n));
+        }
+        if self.shared.closed.load(Ordering::Acquire) {
+            return Poll::Ready(None);
+        }
+        Poll::Pending
+    }
+}
+
+impl SubscriptionStreamSender {
+    fn push(&self, notification: SubscriptionNotification) {
+        {
+            let mut queue = self.shared.queue.lock();
+            if let StreamBackpressure::DropOldest(max) = self.backpressure {
+                if queue.len() >= max.max(1) {
+                    queue.pop_front();
+                }
+            }
+            queue.push_back(notification);
+        }
+        self.shared.waker.wake();
+    }
+}
+
+impl Drop for SubscriptionStreamSender {
+    fn drop(&mut self) {
+        self.shared.closed.store(true, Ordering::Release);
+        self.shared.waker.wake();
+    }
+}
+
+impl OnSubscriptionNotification for SubscriptionStreamSender {
+    fn on_subscription_status_change(&mut self, notification: StatusChangeNotification) {
+        self.push(SubscriptionNotification::StatusChange(notification));
+    }
+
+    fn on_data_value(&mut self, notification: DataValue, item: &MonitoredItem) {
+        self.push(SubscriptionNotification::DataChange {
+            monitored_item_id: item.id(),
+            client_handle: item.client_handle(),
+            node_id: item.item_to_monitor().node_id.clone(),
+            value: notification,
+        });
+    }
+
+    fn on_event(&mut self, event_fields: Option<Vec<Variant>>, item: &MonitoredItem) {
+        self.push(SubscriptionNotification::Event {
+            monitored_item_id: item.id(),
+            client_handle: item.client_handle(),
+            node_id: item.item_to_monitor().node_id.clone(),
+            event_fields,
+        });
+    }
+}
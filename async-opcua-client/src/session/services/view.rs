@@ -1,17 +1,23 @@
 use std::time::Duration;
 
 use crate::{
+    browser::BrowseFilter,
     session::{
         process_service_result, process_unexpected_response,
-        request_builder::{builder_base, builder_debug, builder_error, RequestHeaderBuilder},
+        request_builder::{
+            builder_base, builder_debug, builder_error, send_request, RequestHeaderBuilder,
+        },
     },
     Session, UARequest,
 };
+use futures::TryStreamExt;
+use hashbrown::HashSet;
 use opcua_core::ResponseMessage;
 use opcua_types::{
-    BrowseDescription, BrowseNextRequest, BrowseNextResponse, BrowsePath, BrowsePathResult,
-    BrowseRequest, BrowseResponse, BrowseResult, ByteString, IntegerId, NodeId,
-    RegisterNodesRequest, RegisterNodesResponse, StatusCode, TranslateBrowsePathsToNodeIdsRequest,
+    BrowseDescription, BrowseDirection, BrowseNextRequest, BrowseNextResponse, BrowsePath,
+    BrowsePathResult, BrowseRequest, BrowseResponse, BrowseResult, ByteString, Error,
+    ExpandedNodeId, IntegerId, NodeId, ReferenceDescription, RegisterNodesRequest,
+    RegisterNodesResponse, StatusCode, TranslateBrowsePathsToNodeIdsRequest,
     TranslateBrowsePathsToNodeIdsResponse, UnregisterNodesRequest, UnregisterNodesResponse,
     ViewDescription,
 };
@@ -100,7 +106,13 @@ impl UARequest for Browse {
             requested_max_references_per_node: self.max_references_per_node,
             nodes_to_browse: Some(self.nodes_to_browse),
         };
-        let response = channel.send(request, self.header.timeout).await?;
+        let response = send_request(
+            channel,
+            request,
+            self.header.session_id,
+            self.header.timeout,
+        )
+        .await?;
         if let ResponseMessage::Browse(response) = response {
             builder_debug!(self, "browse, success");
             process_service_result(&response.response_header)?;
@@ -191,7 +203,13 @@ impl UARequest for BrowseNext {
             continuation_points: Some(self.continuation_points),
             release_continuation_points: self.release_continuation_points,
         };
-        let response = channel.send(request, self.header.timeout).await?;
+        let response = send_request(
+            channel,
+            request,
+            self.header.session_id,
+            self.header.timeout,
+        )
+        .await?;
         if let ResponseMessage::BrowseNext(response) = response {
             builder_debug!(self, "browse_next, success");
             process_service_result(&response.response_header)?;
@@ -273,7 +291,13 @@ impl UARequest for TranslateBrowsePaths {
             request_header: self.header.header,
             browse_paths: Some(self.browse_paths),
         };
-        let response = channel.send(request, self.header.timeout).await?;
+        let response = send_request(
+            channel,
+            request,
+            self.header.session_id,
+            self.header.timeout,
+        )
+        .await?;
         if let ResponseMessage::TranslateBrowsePathsToNodeIds(response) = response {
             builder_debug!(self, "translate_browse_paths_to_node_ids, success");
             process_service_result(&response.response_header)?;
@@ -351,7 +375,13 @@ impl UARequest for RegisterNodes {
             request_header: self.header.header,
             nodes_to_register: Some(self.nodes_to_register),
         };
-        let response = channel.send(request, self.header.timeout).await?;
+        let response = send_request(
+            channel,
+            request,
+            self.header.session_id,
+            self.header.timeout,
+        )
+        .await?;
         if let ResponseMessage::RegisterNodes(response) = response {
             builder_debug!(self, "register_nodes, success");
             process_service_result(&response.response_header)?;
@@ -429,7 +459,13 @@ impl UARequest for UnregisterNodes {
             request_header: self.header.header,
             nodes_to_unregister: Some(self.nodes_to_unregister),
         };
-        let response = channel.send(request, self.header.timeout).await?;
+        let response = send_request(
+            channel,
+            request,
+            self.header.session_id,
+            self.header.timeout,
+        )
+        .await?;
         if let ResponseMessage::UnregisterNodes(response) = response {
             builder_debug!(self, "unregister_nodes, success");
             process_service_result(&response.response_header)?;
@@ -502,6 +538,73 @@ impl Session {
             .unwrap_or_default())
     }
 
+    /// Recursively browse the subtree starting at `node_id`, following forward references
+    /// of type `reference_type_id` and its subtypes.
+    ///
+    /// Continuation points are followed automatically, and nodes reached by multiple
+    /// paths are only browsed and returned once. Requests are split into chunks no larger
+    /// than the `MaxNodesPerBrowse` limit from [`Session::operation_limits`], if the server
+    /// publishes one.
+    ///
+    /// For more control over the browse, use [`Session::browser`] directly.
+    ///
+    /// # Arguments
+    ///
+    /// * `node_id` - The node to start browsing from.
+    /// * `reference_type_id` - The type of references to follow, including subtypes.
+    /// * `max_depth` - The maximum depth to browse. A depth of 0 returns only the direct
+    ///   references of `node_id`.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Vec<ReferenceDescription>)` - The references found in the subtree, at most
+    ///   one per target node.
+    /// * `Err(Error)` - Request failed, the status code is the reason for failure.
+    ///
+    pub async fn browse_recursive(
+        &self,
+        node_id: impl Into<NodeId>,
+        reference_type_id: impl Into<NodeId>,
+        max_depth: usize,
+    ) -> Result<Vec<ReferenceDescription>, Error> {
+        let node_id = node_id.into();
+        let filter = BrowseFilter::new(BrowseDirection::Forward, reference_type_id, true)
+            .max_depth(max_depth.saturating_add(1));
+        let initial = vec![filter.new_description_from_node(node_id.clone())];
+
+        let mut browser = self.browser().handler(filter);
+        if let Some(max_nodes) = self
+            .operation_limits_or_default()
+            .await
+            .max_nodes_per_browse
+            .published()
+        {
+            browser = browser.max_nodes_per_request(max_nodes);
+        }
+
+        let mut seen = HashSet::new();
+        seen.insert(ExpandedNodeId::from(node_id));
+        let mut references = Vec::new();
+        let stream = browser.run(initial);
+        futures::pin_mut!(stream);
+        while let Some(item) = stream.try_next().await? {
+            if item.status().is_bad() {
+                return Err(Error::new(
+                    item.status(),
+                    format!("Browsing node {} failed", item.parent_id()),
+                ));
+            }
+            let (_, refs) = item.into_results();
+            for r in refs {
+                if seen.insert(r.node_id.clone()) {
+                    references.push(r);
+                }
+            }
+        }
+
+        Ok(references)
+    }
+
     /// Translate browse paths to NodeIds by sending a [`TranslateBrowsePathsToNodeIdsRequest`] request to the Server
     /// Each [`BrowsePath`] is constructed of a starting node and a `RelativePath`. The specified starting node
     /// identifies the node from which the RelativePath is based. The RelativePath contains a sequence of
@@ -1,4 +1,11 @@
-use std::{str::FromStr, sync::Arc, time::Duration};
+use std::{
+    str::FromStr,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
 
 use crate::{session::EndpointInfo, transport::core::TransportPollResult};
 use arc_swap::{ArcSwap, ArcSwapOption};
@@ -9,13 +16,15 @@ use opcua_core::{
 };
 use opcua_crypto::{CertificateStore, PrivateKey, SecurityPolicy, X509};
 use opcua_types::{
-    ByteString, CloseSecureChannelRequest, ContextOwned, IntegerId, NodeId, RequestHeader,
-    SecurityTokenRequestType, StatusCode,
+    ByteString, CloseSecureChannelRequest, ContextOwned, DateTime, IntegerId, NodeId,
+    RequestHeader, SecurityTokenRequestType, StatusCode,
 };
+use tokio_util::sync::CancellationToken;
 use tracing::{debug, error};
 
 use super::{
     connect::{Connector, Transport},
+    pool::{PooledConnection, PooledEventLoop},
     state::{Request, RequestSend, SecureChannelState},
     tcp::TcpTransport,
 };
@@ -30,6 +39,42 @@ use crate::{
 // memory if it gets into an unexpected (bad) state.
 const MAX_INFLIGHT_MESSAGES: usize = 1_000_000;
 
+// Number of secure channel events buffered for each listener.
+const CHANNEL_EVENT_BUFFER: usize = 16;
+
+/// Identifies a secure channel and the security token in use on it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SecureChannelTokenId {
+    /// ID of the secure channel, assigned by the server.
+    pub channel_id: u32,
+    /// ID of the security token, assigned by the server.
+    pub token_id: u32,
+}
+
+/// Event emitted by an [`AsyncSecureChannel`] when its security token changes.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub enum SecureChannelEvent {
+    /// A new secure channel was opened. `previous` is the channel that was in use
+    /// before, if this is a reconnect.
+    Opened {
+        /// The channel and token used before reconnecting.
+        previous: Option<SecureChannelTokenId>,
+        /// The new channel and token.
+        current: SecureChannelTokenId,
+    },
+    /// The security token of the secure channel was renewed.
+    Renewed {
+        /// The token used before the renewal.
+        previous: SecureChannelTokenId,
+        /// The new token.
+        current: SecureChannelTokenId,
+    },
+    /// The security token expired before it was renewed. The client
+    /// will attempt to renew it, but the server may have closed the channel.
+    TokenExpired(SecureChannelTokenId),
+}
+
 /// Wrapper around an open secure channel
 pub struct AsyncSecureChannel {
     endpoint_info: EndpointInfo,
@@ -38,24 +83,60 @@ pub struct AsyncSecureChannel {
     certificate_store: Arc<RwLock<CertificateStore>>,
     transport_config: TransportConfiguration,
     state: SecureChannelState,
-    issue_channel_lock: tokio::sync::Mutex<()>,
-    connector: Box<dyn Connector>,
+    issue_channel_lock: Arc<tokio::sync::Mutex<()>>,
+    connection: ChannelConnection,
     channel_lifetime: u32,
 
-    request_send: ArcSwapOption<RequestSend>,
+    request_send: Arc<ArcSwapOption<RequestSend>>,
     encoding_context: Arc<RwLock<ContextOwned>>,
+    events: tokio::sync::broadcast::Sender<SecureChannelEvent>,
+    /// Largest request the transport can send, negotiated when connecting.
+    max_request_size: Arc<AtomicUsize>,
+    /// Last nonce returned by the server in a create or activate session response.
+    /// This is kept separate from the nonce of the secure channel, since the session
+    /// may outlive the channel it was created on.
+    session_nonce: ArcSwap<ByteString>,
+    /// Cancelled once the owner of the channel is gone for good, failing any
+    /// requests that are still waiting for a response.
+    closed: CancellationToken,
+}
+
+/// How a secure channel connects to the server.
+enum ChannelConnection {
+    /// The channel owns its transport, created by the connector.
+    Owned(Box<dyn Connector>),
+    /// The channel shares the transport of a [`ChannelPool`](super::ChannelPool).
+    Pooled(PooledConnection),
+}
+
+enum EventLoopInner {
+    Transport(Box<TcpTransport>),
+    Pooled(PooledEventLoop),
 }
 
 /// Event loop for a secure channel. This must be polled to make progress.
 pub struct SecureChannelEventLoop {
-    transport: TcpTransport,
+    inner: EventLoopInner,
 }
 
 impl SecureChannelEventLoop {
+    pub(super) fn new_pooled(event_loop: PooledEventLoop) -> Self {
+        Self {
+            inner: EventLoopInner::Pooled(event_loop),
+        }
+    }
+
     /// Poll the channel, processing any pending incoming or outgoing messages and returning the
     /// action that was taken.
+    ///
+    /// For channels created from a [`ChannelPool`](super::ChannelPool), the transport
+    /// is polled by the pool, so this only returns once the shared channel is closed,
+    /// or this channel is closed with [`AsyncSecureChannel::close_channel`].
     pub async fn poll(&mut self) -> TransportPollResult {
-        self.transport.poll().await
+        match &mut self.inner {
+            EventLoopInner::Transport(transport) => transport.poll().await,
+            EventLoopInner::Pooled(pooled) => pooled.poll().await,
+        }
     }
 }
 
@@ -76,17 +157,34 @@ impl AsyncSecureChannel {
         auth_token: &NodeId,
     ) -> Result<(), StatusCode> {
         let mut secure_channel = trace_write_lock!(self.secure_channel);
-        secure_channel.set_remote_nonce_from_byte_string(nonce)?;
         secure_channel.set_remote_cert_from_byte_string(certificate)?;
+        self.set_session_nonce(nonce);
         self.set_auth_token(auth_token.clone());
         Ok(())
     }
 
+    /// Get the last nonce the server returned for the session on this channel.
+    pub(crate) fn session_nonce(&self) -> ByteString {
+        (**self.session_nonce.load()).clone()
+    }
+
+    /// Set the nonce used to sign the next activate session request.
+    pub(crate) fn set_session_nonce(&self, nonce: &ByteString) {
+        self.session_nonce.store(Arc::new(nonce.clone()));
+    }
+
     pub(crate) fn security_policy(&self) -> SecurityPolicy {
         let secure_channel = trace_read_lock!(self.secure_channel);
         secure_channel.security_policy()
     }
 
+    /// Get the ID of the secure channel assigned by the server.
+    /// This is 0 if the channel is not open.
+    pub fn secure_channel_id(&self) -> u32 {
+        let secure_channel = trace_read_lock!(self.secure_channel);
+        secure_channel.secure_channel_id()
+    }
+
     /// Get the target endpoint of the secure channel.
     pub fn endpoint_info(&self) -> &EndpointInfo {
         &self.endpoint_info
@@ -115,6 +213,15 @@ impl AsyncSecureChannel {
     pub(crate) fn certificate_store(&self) -> &RwLock<CertificateStore> {
         &self.certificate_store
     }
+
+    /// Get the size in bytes of the largest request that can be sent on this channel,
+    /// given the message size and chunk limits negotiated with the server.
+    /// This is 0 if there is no limit.
+    ///
+    /// Before the channel is connected, this is the limit from the client configuration.
+    pub fn max_request_size(&self) -> usize {
+        self.max_request_size.load(Ordering::Relaxed)
+    }
 }
 
 impl AsyncSecureChannel {
@@ -137,26 +244,132 @@ impl AsyncSecureChannel {
             encoding_context.clone(),
         )));
 
+        let max_request_size = Arc::new(AtomicUsize::new(transport_config.max_request_size()));
+
         Self {
             transport_config,
-            issue_channel_lock: tokio::sync::Mutex::new(()),
+            issue_channel_lock: Default::default(),
             state: SecureChannelState::new(ignore_clock_skew, secure_channel.clone(), auth_token),
             endpoint_info,
             secure_channel,
             certificate_store,
             session_retry_policy,
             request_send: Default::default(),
-            connector,
+            connection: ChannelConnection::Owned(connector),
             channel_lifetime,
             encoding_context,
+            events: tokio::sync::broadcast::channel(CHANNEL_EVENT_BUFFER).0,
+            max_request_size,
+            session_nonce: Default::default(),
+            closed: CancellationToken::new(),
+        }
+    }
+
+    /// Create a channel sharing the secure channel and transport of `self`, but with its
+    /// own authentication token and request handles. Connecting and closing the new
+    /// channel is managed by `connection`.
+    pub(super) fn new_shared(
+        &self,
+        endpoint_info: EndpointInfo,
+        connection: PooledConnection,
+    ) -> Self {
+        Self {
+            transport_config: self.transport_config.clone(),
+            issue_channel_lock: self.issue_channel_lock.clone(),
+            state: self.state.new_shared(Arc::default()),
+            endpoint_info,
+            secure_channel: self.secure_channel.clone(),
+            certificate_store: self.certificate_store.clone(),
+            session_retry_policy: self.session_retry_policy.clone(),
+            request_send: self.request_send.clone(),
+            connection: ChannelConnection::Pooled(connection),
+            channel_lifetime: self.channel_lifetime,
+            encoding_context: self.encoding_context.clone(),
+            events: self.events.clone(),
+            max_request_size: self.max_request_size.clone(),
+            session_nonce: Default::default(),
+            closed: CancellationToken::new(),
+        }
+    }
+
+    /// Subscribe to events emitted when the secure channel is opened, or its
+    /// security token is renewed or expires.
+    ///
+    /// Channels sharing a [`ChannelPool`](super::ChannelPool) receive the events
+    /// of the shared channel.
+    pub fn subscribe_events(&self) -> tokio::sync::broadcast::Receiver<SecureChannelEvent> {
+        self.events.subscribe()
+    }
+
+    fn current_token(&self) -> SecureChannelTokenId {
+        let secure_channel = trace_read_lock!(self.secure_channel);
+        SecureChannelTokenId {
+            channel_id: secure_channel.secure_channel_id(),
+            token_id: secure_channel.token_id(),
         }
     }
 
+    fn token_expired(&self) -> bool {
+        let secure_channel = trace_read_lock!(self.secure_channel);
+        let lifetime = chrono::Duration::milliseconds(secure_channel.token_lifetime() as i64);
+        secure_channel.token_id() != 0
+            && DateTime::now() - secure_channel.token_created_at() > lifetime
+    }
+
+    fn emit(&self, event: SecureChannelEvent) {
+        debug!("Secure channel event: {event:?}");
+        // This only fails if there are no listeners.
+        let _ = self.events.send(event);
+    }
+
+    /// If this channel is shared with other sessions, lock it so that only one
+    /// session is created and activated at a time.
+    ///
+    /// The server nonce from `CreateSession` is stored on the secure channel until
+    /// the session is activated, so this must be held between the two.
+    pub(crate) async fn lock_session_establishment(
+        &self,
+    ) -> Option<tokio::sync::OwnedMutexGuard<()>> {
+        match &self.connection {
+            ChannelConnection::Owned(_) => None,
+            ChannelConnection::Pooled(p) => Some(p.lock_session_establishment().await),
+        }
+    }
+
+    /// Fail all pending and future requests on this channel with `BadSessionClosed`.
+    ///
+    /// This is called when the session event loop is dropped, since nothing
+    /// will drive the channel after that.
+    pub(crate) fn close_requests(&self) {
+        self.closed.cancel();
+    }
+
+    /// Return `true` if requests on this channel have been closed with
+    /// [`AsyncSecureChannel::close_requests`].
+    pub(crate) fn requests_closed(&self) -> bool {
+        self.closed.is_cancelled()
+    }
+
     /// Send a message on the secure channel, and wait for a response.
+    ///
+    /// Fails with `BadSessionClosed` if the session owning the channel is closed
+    /// while waiting. The request is then discarded by the transport.
     pub async fn send(
         &self,
         request: impl Into<RequestMessage>,
         timeout: Duration,
+    ) -> Result<ResponseMessage, StatusCode> {
+        tokio::select! {
+            biased;
+            _ = self.closed.cancelled() => Err(StatusCode::BadSessionClosed),
+            r = self.send_inner(request.into(), timeout) => r,
+        }
+    }
+
+    async fn send_inner(
+        &self,
+        request: RequestMessage,
+        timeout: Duration,
     ) -> Result<ResponseMessage, StatusCode> {
         let sender = self.request_send.load().as_deref().cloned();
         let Some(send) = sender else {
@@ -180,6 +393,11 @@ impl AsyncSecureChannel {
             };
 
             if should_renew_security_token {
+                let previous = self.current_token();
+                if self.token_expired() {
+                    self.emit(SecureChannelEvent::TokenExpired(previous));
+                }
+
                 let request = self.state.begin_issue_or_renew_secure_channel(
                     SecurityTokenRequestType::Renew,
                     self.channel_lifetime,
@@ -190,6 +408,10 @@ impl AsyncSecureChannel {
                 let resp = request.send().await?;
 
                 self.state.end_issue_or_renew_secure_channel(resp)?;
+                self.emit(SecureChannelEvent::Renewed {
+                    previous,
+                    current: self.current_token(),
+                });
             }
 
             drop(guard);
@@ -212,6 +434,7 @@ impl AsyncSecureChannel {
                     let Some(delay) = backoff.next() else {
                         break Err(s);
                     };
+                    debug!("Retrying connection to server in {delay:?}");
 
                     tokio::time::sleep(delay).await
                 }
@@ -221,12 +444,24 @@ impl AsyncSecureChannel {
 
     /// Connect to the server without attempting to retry if it fails.
     pub async fn connect_no_retry(&self) -> Result<SecureChannelEventLoop, StatusCode> {
-        {
+        let connector = match &self.connection {
+            ChannelConnection::Owned(connector) => connector,
+            ChannelConnection::Pooled(p) => return p.connect().await,
+        };
+
+        let previous = {
             let mut secure_channel = trace_write_lock!(self.secure_channel);
+            let previous = SecureChannelTokenId {
+                channel_id: secure_channel.secure_channel_id(),
+                token_id: secure_channel.token_id(),
+            };
             secure_channel.clear_security_token();
-        }
+            (previous.token_id != 0).then_some(previous)
+        };
 
-        let (mut transport, send) = self.create_transport().await?;
+        let (mut transport, send) = self.create_transport(&**connector).await?;
+        self.max_request_size
+            .store(transport.max_request_size(), Ordering::Relaxed);
 
         let request = self.state.begin_issue_or_renew_secure_channel(
             SecurityTokenRequestType::Issue,
@@ -252,12 +487,19 @@ impl AsyncSecureChannel {
 
         self.request_send.store(Some(Arc::new(send)));
         self.state.end_issue_or_renew_secure_channel(resp)?;
-
-        Ok(SecureChannelEventLoop { transport })
+        self.emit(SecureChannelEvent::Opened {
+            previous,
+            current: self.current_token(),
+        });
+
+        Ok(SecureChannelEventLoop {
+            inner: EventLoopInner::Transport(Box::new(transport)),
+        })
     }
 
     async fn create_transport(
         &self,
+        connector: &dyn Connector,
     ) -> Result<(TcpTransport, tokio::sync::mpsc::Sender<OutgoingMessage>), StatusCode> {
         let endpoint_url = self.endpoint_info.endpoint.endpoint_url.clone();
         debug!("Connect");
@@ -297,8 +539,7 @@ impl AsyncSecureChannel {
             }
 
             let (send, recv) = tokio::sync::mpsc::channel(MAX_INFLIGHT_MESSAGES);
-            let transport = self
-                .connector
+            let transport = connector
                 .connect(
                     self.secure_channel.clone(),
                     recv,
@@ -312,7 +553,15 @@ impl AsyncSecureChannel {
     }
 
     /// Close the secure channel, optionally wait for the channel to close.
+    ///
+    /// Channels created from a [`ChannelPool`](super::ChannelPool) only close their own
+    /// event loop, the shared channel stays open until the pool is closed.
     pub async fn close_channel(&self) {
+        if let ChannelConnection::Pooled(p) = &self.connection {
+            p.close();
+            return;
+        }
+
         let msg = CloseSecureChannelRequest {
             request_header: self.state.make_request_header(Duration::from_secs(60)),
         };
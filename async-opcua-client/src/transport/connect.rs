@@ -30,6 +30,29 @@ pub trait Connector: Send + Sync {
     ) -> Result<TcpTransport, StatusCode>;
 }
 
+/// Connector used for WebSocket URLs when the `websocket` feature is disabled,
+/// failing every connection attempt.
+#[cfg(not(feature = "websocket"))]
+pub(super) struct WebSocketDisabledConnector;
+
+#[cfg(not(feature = "websocket"))]
+#[async_trait]
+impl Connector for WebSocketDisabledConnector {
+    async fn connect(
+        &self,
+        _channel: Arc<RwLock<SecureChannel>>,
+        _outgoing_recv: tokio::sync::mpsc::Receiver<OutgoingMessage>,
+        _config: TransportConfiguration,
+        endpoint_url: &str,
+    ) -> Result<TcpTransport, StatusCode> {
+        tracing::error!(
+            "Endpoint url {} requires the `websocket` feature",
+            endpoint_url
+        );
+        Err(StatusCode::BadTcpEndpointUrlInvalid)
+    }
+}
+
 /// Trait for client transport channels.
 ///
 /// Note for implementors:
@@ -9,6 +9,7 @@ use parking_lot::RwLock;
 use tracing::{debug, error, trace, warn};
 
 use opcua_core::comms::buffer::SendBuffer;
+use opcua_core::comms::buffer_pool::BufferPool;
 use opcua_core::comms::message_chunk::MessageIsFinalType;
 use opcua_core::comms::{
     chunker::Chunker, message_chunk::MessageChunk, message_chunk_info::ChunkInfo,
@@ -25,6 +26,8 @@ struct MessageChunkWithChunkInfo {
 pub(crate) struct MessageState {
     callback: tokio::sync::oneshot::Sender<Result<ResponseMessage, StatusCode>>,
     chunks: Vec<MessageChunkWithChunkInfo>,
+    /// Total body length of the chunks received so far.
+    message_size: usize,
     deadline: Instant,
 }
 
@@ -35,13 +38,17 @@ pub(super) struct TransportState {
     message_states: HashMap<u32, MessageState>,
     /// Secure channel
     pub(super) secure_channel: Arc<RwLock<SecureChannel>>,
-    /// Max pending incoming messages
-    max_pending_incoming: usize,
+    /// Max number of chunks in an incoming message, 0 means no limit.
+    max_chunk_count: usize,
+    /// Max size of an incoming message, 0 means no limit.
+    max_message_size: usize,
     /// Last decoded sequence number
     sequence_numbers: SequenceNumberHandle,
     /// Max size of incoming chunks
     #[allow(unused)]
     receive_buffer_size: usize,
+    /// Pool that chunk buffers are returned to once they have been decoded.
+    buffer_pool: BufferPool,
 }
 
 #[derive(Debug)]
@@ -71,8 +78,10 @@ impl TransportState {
     pub(super) fn new(
         secure_channel: Arc<RwLock<SecureChannel>>,
         outgoing_recv: tokio::sync::mpsc::Receiver<OutgoingMessage>,
-        max_pending_incoming: usize,
+        max_chunk_count: usize,
+        max_message_size: usize,
         receive_buffer_size: usize,
+        buffer_pool: BufferPool,
     ) -> Self {
         let legacy_sequence_numbers = secure_channel
             .read()
@@ -83,8 +92,10 @@ impl TransportState {
             outgoing_recv,
             message_states: HashMap::new(),
             sequence_numbers: SequenceNumberHandle::new(legacy_sequence_numbers),
-            max_pending_incoming,
+            max_chunk_count,
+            max_message_size,
             receive_buffer_size,
+            buffer_pool,
         }
     }
 
@@ -107,11 +118,23 @@ impl TransportState {
                     }
                     outgoing = self.outgoing_recv.recv() => {
                         let outgoing = outgoing?;
+                        // Requests may have timed out or been abandoned while queued,
+                        // there is no point in sending those.
+                        if let Some(callback) = &outgoing.callback {
+                            if callback.is_closed() || outgoing.deadline <= Instant::now() {
+                                debug!("Dropping request that timed out before it was sent");
+                                if let Some(callback) = outgoing.callback {
+                                    let _ = callback.send(Err(StatusCode::BadTimeout));
+                                }
+                                continue;
+                            }
+                        }
                         let request_id = send_buffer.next_request_id();
                         if let Some(callback) = outgoing.callback {
                             self.message_states.insert(request_id, MessageState {
                                 callback,
                                 chunks: Vec::new(),
+                                message_size: 0,
                                 deadline: outgoing.deadline,
                             });
                         }
@@ -155,6 +178,16 @@ impl TransportState {
         }
     }
 
+    /// Wait until the next pending request times out, then fail all requests that have
+    /// timed out with `BadTimeout`. This is cancel safe.
+    pub(super) async fn wait_for_timeout(&mut self) {
+        match self.next_timeout() {
+            Some(t) => tokio::time::sleep_until(t.into()).await,
+            None => futures::future::pending::<()>().await,
+        }
+        self.next_timeout();
+    }
+
     fn next_timeout(&mut self) -> Option<Instant> {
         let now = Instant::now();
         let mut next_timeout = None;
@@ -181,7 +214,9 @@ impl TransportState {
 
     fn process_chunk(&mut self, chunk: MessageChunk) -> Result<(), StatusCode> {
         let mut secure_channel = trace_write_lock!(self.secure_channel);
-        let chunk = secure_channel.verify_and_remove_security(&chunk.data)?;
+        let verified = secure_channel.verify_and_remove_security(&chunk.data);
+        self.buffer_pool.put(chunk.data);
+        let chunk = verified?;
 
         let chunk_info = chunk.chunk_info(&secure_channel)?;
         drop(secure_channel);
@@ -201,21 +236,12 @@ impl TransportState {
                     chunk_info.sequence_header.sequence_number,
                     chunk_info.body_length
                 );
+                message_state.message_size += chunk_info.body_length;
                 message_state.chunks.push(MessageChunkWithChunkInfo {
                     header: chunk_info,
                     data_with_header: chunk.data,
                 });
-                let chunks_len: usize = message_state.chunks.len();
-                if self.max_pending_incoming > 0 && chunks_len > self.max_pending_incoming {
-                    error!(
-                        "too many pending incoming messages {} > {}",
-                        chunks_len, self.max_pending_incoming
-                    );
-                    let message_state = self.message_states.remove(&req_id).unwrap();
-                    let _ = message_state
-                        .callback
-                        .send(Err(StatusCode::BadEncodingLimitsExceeded));
-                }
+                self.check_incoming_limits(req_id);
             }
             MessageIsFinalType::FinalError => {
                 warn!("Discarding chunk marked in as final error");
@@ -231,13 +257,21 @@ impl TransportState {
                     chunk_info.sequence_header.sequence_number,
                     chunk_info.body_length
                 );
+                message_state.message_size += chunk_info.body_length;
                 message_state.chunks.push(MessageChunkWithChunkInfo {
                     header: chunk_info,
                     data_with_header: chunk.data,
                 });
+                if !self.check_incoming_limits(req_id) {
+                    return Ok(());
+                }
                 let message_state = self.message_states.remove(&req_id).unwrap();
                 let in_chunks = Self::merge_chunks(message_state.chunks)?;
-                let message = self.turn_received_chunks_into_message(&in_chunks)?;
+                let message = self.turn_received_chunks_into_message(&in_chunks);
+                for chunk in in_chunks {
+                    self.buffer_pool.put(chunk.data);
+                }
+                let message = message?;
 
                 let _ = message_state.callback.send(Ok(message));
             }
@@ -245,6 +279,35 @@ impl TransportState {
         Ok(())
     }
 
+    /// Check that the chunks received so far for the message with the given request ID are
+    /// within the limits we sent to the server in the HELLO message. If they are not,
+    /// the request fails with `BadResponseTooLarge` and `false` is returned.
+    fn check_incoming_limits(&mut self, req_id: u32) -> bool {
+        let Some(message_state) = self.message_states.get(&req_id) else {
+            return false;
+        };
+        let chunk_count = message_state.chunks.len();
+        let message_size = message_state.message_size;
+        if self.max_chunk_count > 0 && chunk_count > self.max_chunk_count {
+            error!(
+                "Response to request {} has more than {} chunks",
+                req_id, self.max_chunk_count
+            );
+        } else if self.max_message_size > 0 && message_size > self.max_message_size {
+            error!(
+                "Response to request {} is at least {} bytes, which exceeds the max message size {}",
+                req_id, message_size, self.max_message_size
+            );
+        } else {
+            return true;
+        }
+        let message_state = self.message_states.remove(&req_id).unwrap();
+        let _ = message_state
+            .callback
+            .send(Err(StatusCode::BadResponseTooLarge));
+        false
+    }
+
     fn turn_received_chunks_into_message(
         &mut self,
         chunks: &[MessageChunk],
@@ -3,11 +3,32 @@
 mod channel;
 mod connect;
 mod core;
+mod pool;
 mod state;
 pub(super) mod tcp;
+#[cfg(feature = "websocket")]
+mod websocket;
 
-pub use channel::{AsyncSecureChannel, SecureChannelEventLoop};
+pub use channel::{
+    AsyncSecureChannel, SecureChannelEvent, SecureChannelEventLoop, SecureChannelTokenId,
+};
+#[cfg(not(feature = "websocket"))]
+use connect::WebSocketDisabledConnector;
 pub use connect::{Connector, Transport};
 pub(crate) use core::OutgoingMessage;
 pub use core::TransportPollResult;
-pub use tcp::TcpConnector;
+pub use pool::{ChannelPool, PooledSessionBuilder};
+pub use tcp::{ReverseConnector, TcpConnector};
+#[cfg(feature = "websocket")]
+pub use websocket::{WebSocketConnector, WS_SUBPROTOCOL_UACP};
+
+/// Get the default connector for the given endpoint URL.
+pub(crate) fn connector_for_url(endpoint_url: &str) -> Box<dyn Connector> {
+    if opcua_core::comms::url::is_opc_ua_websocket_url(endpoint_url) {
+        #[cfg(feature = "websocket")]
+        return Box::new(WebSocketConnector);
+        #[cfg(not(feature = "websocket"))]
+        return Box::new(WebSocketDisabledConnector);
+    }
+    Box::new(TcpConnector)
+}
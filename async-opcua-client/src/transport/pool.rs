@@ -0,0 +1,244 @@
+use std::sync::{Arc, Weak};
+
+use opcua_core::{config::Config, sync::Mutex, task::spawn_named};
+use opcua_types::{NodeId, StatusCode};
+use tracing::{debug, warn};
+
+use crate::{
+    session::{EndpointInfo, SessionEventLoop},
+    AsyncSecureChannel, ClientConfig, IdentityToken, Session,
+};
+
+use super::{SecureChannelEventLoop, TransportPollResult};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PoolState {
+    Disconnected,
+    /// The shared channel is connected. Each new connection gets a new generation,
+    /// so that sessions can tell that the channel was reconnected while they were not looking.
+    Connected(u64),
+}
+
+struct ChannelPoolInner {
+    channel: AsyncSecureChannel,
+    /// Lock held while connecting the shared channel, contains the last used generation.
+    connect_lock: tokio::sync::Mutex<u64>,
+    /// Lock held by sessions between `CreateSession` and `ActivateSession`.
+    session_lock: Arc<tokio::sync::Mutex<()>>,
+    state: tokio::sync::watch::Sender<PoolState>,
+    named_tasks: bool,
+}
+
+impl ChannelPoolInner {
+    /// Connect the shared channel if it is not already connected,
+    /// returning the generation of the connection.
+    async fn ensure_connected(self: &Arc<Self>) -> Result<u64, StatusCode> {
+        let mut generation = self.connect_lock.lock().await;
+        if let PoolState::Connected(current) = *self.state.borrow() {
+            return Ok(current);
+        }
+
+        // The shared channel owns its connector, but the compiler cannot tell that
+        // this does not recurse back into a pooled connection, so the future is boxed.
+        let mut event_loop = Box::pin(self.channel.connect_no_retry()).await?;
+        *generation += 1;
+        let current = *generation;
+        self.state.send_replace(PoolState::Connected(current));
+        debug!("Pooled secure channel connected");
+
+        let weak: Weak<Self> = Arc::downgrade(self);
+        let name = self.named_tasks.then(|| {
+            format!(
+                "opcua-client-channel-pool ({})",
+                self.channel.endpoint_info().endpoint.endpoint_url
+            )
+        });
+        spawn_named(name, async move {
+            let status = loop {
+                if let TransportPollResult::Closed(status) = event_loop.poll().await {
+                    break status;
+                }
+            };
+            warn!("Pooled secure channel closed: {status}");
+            if let Some(inner) = weak.upgrade() {
+                inner.state.send_replace(PoolState::Disconnected);
+            }
+        });
+
+        Ok(current)
+    }
+}
+
+/// Connection of a channel sharing the transport of a [`ChannelPool`].
+pub(super) struct PooledConnection {
+    pool: Arc<ChannelPoolInner>,
+    close: Mutex<Option<tokio::sync::oneshot::Sender<()>>>,
+}
+
+impl PooledConnection {
+    pub(super) async fn connect(&self) -> Result<SecureChannelEventLoop, StatusCode> {
+        let generation = self.pool.ensure_connected().await?;
+        let (close_tx, close_rx) = tokio::sync::oneshot::channel();
+        *self.close.lock() = Some(close_tx);
+        Ok(SecureChannelEventLoop::new_pooled(PooledEventLoop {
+            closed: close_rx,
+            state: self.pool.state.subscribe(),
+            generation,
+            done: false,
+        }))
+    }
+
+    pub(super) fn close(&self) {
+        if let Some(close) = self.close.lock().take() {
+            let _ = close.send(());
+        }
+    }
+
+    pub(super) async fn lock_session_establishment(&self) -> tokio::sync::OwnedMutexGuard<()> {
+        self.pool.session_lock.clone().lock_owned().await
+    }
+}
+
+/// Event loop for a channel sharing the transport of a [`ChannelPool`].
+/// The transport itself is polled by the pool.
+pub(super) struct PooledEventLoop {
+    closed: tokio::sync::oneshot::Receiver<()>,
+    state: tokio::sync::watch::Receiver<PoolState>,
+    generation: u64,
+    done: bool,
+}
+
+impl PooledEventLoop {
+    pub(super) async fn poll(&mut self) -> TransportPollResult {
+        if !self.done {
+            let generation = self.generation;
+            tokio::select! {
+                _ = &mut self.closed => (),
+                _ = self.state.wait_for(|s| *s != PoolState::Connected(generation)) => (),
+            }
+            self.done = true;
+        }
+        TransportPollResult::Closed(StatusCode::BadConnectionClosed)
+    }
+}
+
+/// A pool of sessions sharing a single secure channel to a server endpoint.
+///
+/// This is useful for applications opening many sessions to the same server,
+/// since each secure channel requires a separate connection and handshake.
+/// Requests from all sessions are sent over the same transport, so they are subject
+/// to the message size and chunk count limits negotiated for the shared channel.
+///
+/// The shared channel is opened when the first session connects, and is reconnected
+/// by the sessions if the connection is lost. Security token renewal is handled
+/// by whichever session sends a request first once the token is about to expire,
+/// the other sessions wait for the renewal to complete.
+///
+/// Create a pool with [`SessionBuilder::build_channel_pool`](crate::SessionBuilder::build_channel_pool).
+pub struct ChannelPool {
+    inner: Arc<ChannelPoolInner>,
+    config: ClientConfig,
+}
+
+impl ChannelPool {
+    pub(crate) fn new(channel: AsyncSecureChannel, config: ClientConfig) -> Self {
+        let (state, _) = tokio::sync::watch::channel(PoolState::Disconnected);
+        Self {
+            inner: Arc::new(ChannelPoolInner {
+                channel,
+                connect_lock: tokio::sync::Mutex::new(0),
+                session_lock: Default::default(),
+                state,
+                named_tasks: config.named_tasks,
+            }),
+            config,
+        }
+    }
+
+    /// Create a builder for a session using the shared secure channel.
+    pub fn session_builder(&self) -> PooledSessionBuilder<'_> {
+        PooledSessionBuilder {
+            pool: self,
+            user_identity_token: self
+                .inner
+                .channel
+                .endpoint_info()
+                .user_identity_token
+                .clone(),
+            session_id: None,
+        }
+    }
+
+    /// Get the shared secure channel.
+    pub fn channel(&self) -> &AsyncSecureChannel {
+        &self.inner.channel
+    }
+
+    /// Check whether the shared secure channel is currently connected.
+    pub fn is_connected(&self) -> bool {
+        matches!(*self.inner.state.borrow(), PoolState::Connected(_))
+    }
+
+    /// Close the shared secure channel and wait for it to close.
+    /// Sessions using the pool will attempt to reconnect, so you should
+    /// disconnect them first.
+    pub async fn close(&self) {
+        if !self.is_connected() {
+            return;
+        }
+        let mut state = self.inner.state.subscribe();
+        self.inner.channel.close_channel().await;
+        let _ = state.wait_for(|s| *s == PoolState::Disconnected).await;
+    }
+}
+
+/// Builder for a session using the shared secure channel of a [`ChannelPool`].
+pub struct PooledSessionBuilder<'a> {
+    pool: &'a ChannelPool,
+    user_identity_token: IdentityToken,
+    session_id: Option<NodeId>,
+}
+
+impl PooledSessionBuilder<'_> {
+    /// Set the user identity token to use. Defaults to the identity token
+    /// of the session builder used to create the pool.
+    pub fn user_identity_token(mut self, identity_token: IdentityToken) -> Self {
+        self.user_identity_token = identity_token;
+        self
+    }
+
+    /// Set an initial session ID. The session will try to reactivate this session
+    /// before creating a new session.
+    pub fn session_id(mut self, session_id: NodeId) -> Self {
+        self.session_id = Some(session_id);
+        self
+    }
+
+    /// Build the session and session event loop. Note that you will need to
+    /// start polling the event loop before a connection is actually established.
+    pub fn build(self) -> (Arc<Session>, SessionEventLoop) {
+        let config = &self.pool.config;
+        let inner = &self.pool.inner;
+        let channel = inner.channel.new_shared(
+            EndpointInfo {
+                endpoint: inner.channel.endpoint_info().endpoint.clone(),
+                user_identity_token: self.user_identity_token,
+                preferred_locales: config.preferred_locales.clone(),
+            },
+            PooledConnection {
+                pool: inner.clone(),
+                close: Mutex::new(None),
+            },
+        );
+        Session::new(
+            channel,
+            config.session_name.clone().into(),
+            config.application_description(),
+            config.session_retry_policy(),
+            config.decoding_options.as_comms_decoding_options(),
+            config,
+            self.session_id,
+            false,
+        )
+    }
+}
@@ -22,7 +22,7 @@ pub(crate) type RequestSend = tokio::sync::mpsc::Sender<OutgoingMessage>;
 
 pub(super) struct SecureChannelState {
     /// Time offset between the client and the server.
-    client_offset: ArcSwap<chrono::Duration>,
+    client_offset: Arc<ArcSwap<chrono::Duration>>,
     /// Ignore clock skew between the client and the server.
     ignore_clock_skew: bool,
     /// Secure channel information
@@ -68,11 +68,12 @@ impl Request {
 
     pub(super) async fn send(self) -> Result<ResponseMessage, StatusCode> {
         let (cb_send, cb_recv) = tokio::sync::oneshot::channel();
+        let deadline = Instant::now() + self.timeout;
 
         let message = OutgoingMessage {
             request: self.payload,
             callback: Some(cb_send),
-            deadline: Instant::now() + self.timeout,
+            deadline,
         };
 
         match self.sender.send_timeout(message, self.timeout).await {
@@ -81,10 +82,14 @@ impl Request {
             Err(SendTimeoutError::Timeout(_)) => return Err(StatusCode::BadTimeout),
         }
 
-        match cb_recv.await {
-            Ok(r) => r,
+        // The transport fails the request once the deadline has passed, but it
+        // may not get to it if it is not being polled, so time out here as well.
+        // Dropping the receiver makes the transport discard the request.
+        match tokio::time::timeout_at(deadline.into(), cb_recv).await {
+            Ok(Ok(r)) => r,
             // Should not really happen, would mean something panicked.
-            Err(_) => Err(StatusCode::BadConnectionClosed),
+            Ok(Err(_)) => Err(StatusCode::BadConnectionClosed),
+            Err(_) => Err(StatusCode::BadTimeout),
         }
     }
 }
@@ -98,7 +103,7 @@ impl SecureChannelState {
         authentication_token: Arc<ArcSwap<NodeId>>,
     ) -> Self {
         SecureChannelState {
-            client_offset: ArcSwap::new(Arc::new(chrono::Duration::zero())),
+            client_offset: Arc::new(ArcSwap::new(Arc::new(chrono::Duration::zero()))),
             ignore_clock_skew,
             secure_channel,
             authentication_token,
@@ -106,6 +111,18 @@ impl SecureChannelState {
         }
     }
 
+    /// Create a new state for the same secure channel, sharing the client offset,
+    /// but with a different authentication token.
+    pub(super) fn new_shared(&self, authentication_token: Arc<ArcSwap<NodeId>>) -> Self {
+        SecureChannelState {
+            client_offset: self.client_offset.clone(),
+            ignore_clock_skew: self.ignore_clock_skew,
+            secure_channel: self.secure_channel.clone(),
+            authentication_token,
+            request_handle: AtomicHandle::new(Self::FIRST_REQUEST_HANDLE),
+        }
+    }
+
     pub(super) fn begin_issue_or_renew_secure_channel(
         &self,
         request_type: SecurityTokenRequestType,
@@ -1,14 +1,15 @@
-use std::sync::Arc;
+use std::{net::SocketAddr, sync::Arc, time::Duration};
 
 use super::connect::{Connector, Transport};
 use super::core::{OutgoingMessage, TransportPollResult, TransportState};
 use async_trait::async_trait;
 use futures::StreamExt;
-use opcua_core::comms::tcp_types::AcknowledgeMessage;
+use opcua_core::comms::tcp_types::{AcknowledgeMessage, ErrorMessage};
 use opcua_core::RequestMessage;
 use opcua_core::{
     comms::{
         buffer::SendBuffer,
+        buffer_pool::BufferPool,
         secure_channel::SecureChannel,
         tcp_codec::{Message, TcpCodec},
         tcp_types::HelloMessage,
@@ -19,10 +20,10 @@ use opcua_core::{
 use opcua_crypto::SecurityPolicy;
 use opcua_types::StatusCode;
 use parking_lot::RwLock;
-use tokio::io::{AsyncWriteExt, ReadHalf, WriteHalf};
-use tokio::net::TcpStream;
+use tokio::io::{AsyncRead, AsyncWrite, AsyncWriteExt, ReadHalf, WriteHalf};
+use tokio::net::{TcpListener, TcpStream};
 use tokio_util::codec::FramedRead;
-use tracing::{debug, error};
+use tracing::{debug, error, warn};
 
 #[derive(Debug, Clone, Copy)]
 enum TransportCloseState {
@@ -31,22 +32,59 @@ enum TransportCloseState {
     Closed(StatusCode),
 }
 
+/// Byte stream carrying the OPC UA binary protocol, a TCP socket or
+/// an adapter over some other transport.
+pub(super) trait TransportStream:
+    AsyncRead + AsyncWrite + Send + Sync + Unpin + 'static
+{
+}
+
+impl<T: AsyncRead + AsyncWrite + Send + Sync + Unpin + 'static> TransportStream for T {}
+
+pub(super) type BoxedStream = Box<dyn TransportStream>;
+
 pub struct TcpTransport {
     state: TransportState,
-    read: FramedRead<ReadHalf<TcpStream>, TcpCodec>,
-    write: WriteHalf<TcpStream>,
+    read: FramedRead<ReadHalf<BoxedStream>, TcpCodec>,
+    write: WriteHalf<BoxedStream>,
     send_buffer: SendBuffer,
     should_close: bool,
+    /// Whether data was written to the stream since it was last flushed.
+    needs_flush: bool,
     closed: TransportCloseState,
 }
 
 #[derive(Debug, Clone)]
 pub struct TransportConfiguration {
-    pub max_pending_incoming: usize,
     pub send_buffer_size: usize,
     pub recv_buffer_size: usize,
     pub max_message_size: usize,
     pub max_chunk_count: usize,
+    /// Pool of chunk buffers, shared by all connections made with this configuration.
+    /// Use a disabled pool to allocate new buffers for every chunk.
+    pub buffer_pool: BufferPool,
+}
+
+impl TransportConfiguration {
+    /// Size of the largest request that can be sent with these limits, 0 means no limit.
+    pub(super) fn max_request_size(&self) -> usize {
+        max_request_size(
+            self.max_message_size,
+            self.max_chunk_count,
+            self.send_buffer_size,
+        )
+    }
+}
+
+/// Get the size of the largest message that fits within both the message size limit,
+/// and the given number of chunks of size `chunk_size`. 0 means no limit.
+fn max_request_size(max_message_size: usize, max_chunk_count: usize, chunk_size: usize) -> usize {
+    let chunked = max_chunk_count.saturating_mul(chunk_size);
+    match (max_message_size, chunked) {
+        (0, c) => c,
+        (m, 0) => m,
+        (m, c) => m.min(c),
+    }
 }
 
 /// Connector for `opc.tcp` transport.
@@ -57,15 +95,12 @@ impl TcpConnector {
         secure_channel: &RwLock<SecureChannel>,
         config: &TransportConfiguration,
         endpoint_url: &str,
-    ) -> Result<
-        (
-            FramedRead<ReadHalf<TcpStream>, TcpCodec>,
-            WriteHalf<TcpStream>,
-            AcknowledgeMessage,
-            SecurityPolicy,
-        ),
-        StatusCode,
-    > {
+    ) -> Result<HandshakeResult, StatusCode> {
+        let socket = Self::connect_socket(endpoint_url).await?;
+        hello_handshake(Box::new(socket), secure_channel, config, endpoint_url).await
+    }
+
+    async fn connect_socket(endpoint_url: &str) -> Result<TcpStream, StatusCode> {
         let (host, port) = hostname_port_from_url(
             endpoint_url,
             opcua_core::constants::DEFAULT_OPC_UA_SERVER_PORT,
@@ -94,58 +129,94 @@ impl TcpConnector {
 
         debug!("Connecting to {} with url {}", addr, endpoint_url);
 
-        let socket = TcpStream::connect(&addr).await.map_err(|err| {
+        TcpStream::connect(&addr).await.map_err(|err| {
             error!("Could not connect to host {}, {:?}", addr, err);
             StatusCode::BadCommunicationError
-        })?;
+        })
+    }
+}
 
-        let (reader, mut writer) = tokio::io::split(socket);
+type FramedSocket = (
+    FramedRead<ReadHalf<BoxedStream>, TcpCodec>,
+    WriteHalf<BoxedStream>,
+    SecurityPolicy,
+);
 
-        let hello = HelloMessage::new(
-            endpoint_url,
-            config.send_buffer_size,
-            config.recv_buffer_size,
-            config.max_message_size,
-            config.max_chunk_count,
-        );
-        tracing::trace!("Send hello message: {hello:?}");
-        let (mut framed_read, policy) = {
-            let secure_channel = trace_read_lock!(secure_channel);
-            (
-                FramedRead::new(reader, TcpCodec::new(secure_channel.decoding_options())),
-                secure_channel.security_policy(),
-            )
-        };
+pub(super) type HandshakeResult = (
+    FramedRead<ReadHalf<BoxedStream>, TcpCodec>,
+    WriteHalf<BoxedStream>,
+    AcknowledgeMessage,
+    SecurityPolicy,
+);
+
+fn framed_read(socket: BoxedStream, secure_channel: &RwLock<SecureChannel>) -> FramedSocket {
+    let (reader, writer) = tokio::io::split(socket);
+    let secure_channel = trace_read_lock!(secure_channel);
+    (
+        FramedRead::new(reader, TcpCodec::new(secure_channel.decoding_options())),
+        writer,
+        secure_channel.security_policy(),
+    )
+}
+
+/// Send a HEL message on an open socket and wait for the ACK from the server.
+pub(super) async fn hello_handshake(
+    socket: BoxedStream,
+    secure_channel: &RwLock<SecureChannel>,
+    config: &TransportConfiguration,
+    endpoint_url: &str,
+) -> Result<HandshakeResult, StatusCode> {
+    let (framed_read, writer, policy) = framed_read(socket, secure_channel);
+    hello_handshake_framed(framed_read, writer, policy, config, endpoint_url).await
+}
 
+async fn hello_handshake_framed(
+    mut framed_read: FramedRead<ReadHalf<BoxedStream>, TcpCodec>,
+    mut writer: WriteHalf<BoxedStream>,
+    policy: SecurityPolicy,
+    config: &TransportConfiguration,
+    endpoint_url: &str,
+) -> Result<HandshakeResult, StatusCode> {
+    let hello = HelloMessage::new(
+        endpoint_url,
+        config.send_buffer_size,
+        config.recv_buffer_size,
+        config.max_message_size,
+        config.max_chunk_count,
+    );
+    tracing::trace!("Send hello message: {hello:?}");
+
+    let sent = async {
         writer
             .write_all(&opcua_types::SimpleBinaryEncodable::encode_to_vec(&hello))
-            .await
-            .map_err(|err| {
-                error!("Cannot send hello to server, err = {}", err);
-                StatusCode::BadCommunicationError
-            })?;
-        let ack = match framed_read.next().await {
-            Some(Ok(Message::Acknowledge(ack))) => {
-                if ack.send_buffer_size > hello.receive_buffer_size {
-                    tracing::warn!("Acknowledged send buffer size is greater than receive buffer size in hello message!")
-                }
-                if ack.receive_buffer_size > hello.send_buffer_size {
-                    tracing::warn!("Acknowledged receive buffer size is greater than send buffer size in hello message!")
-                }
-                tracing::trace!("Received acknowledgement: {:?}", ack);
-                ack
+            .await?;
+        writer.flush().await
+    };
+    sent.await.map_err(|err| {
+        error!("Cannot send hello to server, err = {}", err);
+        StatusCode::BadCommunicationError
+    })?;
+    let ack = match framed_read.next().await {
+        Some(Ok(Message::Acknowledge(ack))) => {
+            if ack.send_buffer_size > hello.receive_buffer_size {
+                tracing::warn!("Acknowledged send buffer size is greater than receive buffer size in hello message!")
             }
-            other => {
-                error!(
-                    "Unexpected error while waiting for server ACK. Expected ACK, got {:?}",
-                    other
-                );
-                return Err(StatusCode::BadConnectionClosed);
+            if ack.receive_buffer_size > hello.send_buffer_size {
+                tracing::warn!("Acknowledged receive buffer size is greater than send buffer size in hello message!")
             }
-        };
+            tracing::trace!("Received acknowledgement: {:?}", ack);
+            ack
+        }
+        other => {
+            error!(
+                "Unexpected error while waiting for server ACK. Expected ACK, got {:?}",
+                other
+            );
+            return Err(StatusCode::BadConnectionClosed);
+        }
+    };
 
-        Ok((framed_read, writer, ack, policy))
-    }
+    Ok((framed_read, writer, ack, policy))
 }
 
 #[async_trait]
@@ -157,11 +228,149 @@ impl Connector for TcpConnector {
         config: TransportConfiguration,
         endpoint_url: &str,
     ) -> Result<TcpTransport, StatusCode> {
-        let (framed_read, writer, ack, policy) =
-            match Self::connect_inner(&channel, &config, endpoint_url).await {
-                Ok(k) => k,
-                Err(status) => return Err(status),
+        let handshake = Self::connect_inner(&channel, &config, endpoint_url).await?;
+        Ok(TcpTransport::new(channel, outgoing_recv, config, handshake))
+    }
+}
+
+/// Connector for `opc.tcp` transport using reverse connect, as described in
+/// OPC UA Part 6, 7.1.3. Instead of connecting to the server, the client listens on
+/// a local socket and waits for the server to open a connection and send a
+/// ReverseHello message. The normal handshake is then performed on that connection.
+///
+/// The listener is bound on the first connection attempt, and kept open
+/// for later reconnects.
+pub struct ReverseConnector {
+    bind_addr: SocketAddr,
+    server_uri: Option<String>,
+    listener: tokio::sync::Mutex<Option<TcpListener>>,
+}
+
+impl ReverseConnector {
+    /// Time to wait for a ReverseHello message after a server has connected.
+    const REVERSE_HELLO_TIMEOUT: Duration = Duration::from_secs(10);
+
+    /// Create a new reverse connector listening on `bind_addr`.
+    pub fn new(bind_addr: SocketAddr) -> Self {
+        Self {
+            bind_addr,
+            server_uri: None,
+            listener: tokio::sync::Mutex::new(None),
+        }
+    }
+
+    /// Only accept connections from the server with the given application URI.
+    /// If this is not set, connections from any server are accepted, as long as the
+    /// endpoint URL in the ReverseHello message matches.
+    pub fn server_uri(mut self, server_uri: impl Into<String>) -> Self {
+        self.server_uri = Some(server_uri.into());
+        self
+    }
+
+    /// Wait for a ReverseHello on a newly accepted connection, and check that it
+    /// was sent by the expected server.
+    async fn accept_reverse_hello(
+        &self,
+        socket: TcpStream,
+        secure_channel: &RwLock<SecureChannel>,
+        endpoint_url: &str,
+    ) -> Result<FramedSocket, StatusCode> {
+        let (mut framed_read, mut writer, policy) = framed_read(Box::new(socket), secure_channel);
+        let status =
+            match tokio::time::timeout(Self::REVERSE_HELLO_TIMEOUT, framed_read.next()).await {
+                Ok(Some(Ok(Message::ReverseHello(rhe)))) => {
+                    tracing::trace!("Received reverse hello: {:?}", rhe);
+                    if !rhe.matches_endpoint_url(endpoint_url) {
+                        warn!(
+                            "Rejecting reverse hello for endpoint {}, expected {}",
+                            rhe.endpoint_url, endpoint_url
+                        );
+                        StatusCode::BadTcpEndpointUrlInvalid
+                    } else if self
+                        .server_uri
+                        .as_ref()
+                        .is_some_and(|uri| uri.as_str() != rhe.server_uri.as_ref())
+                    {
+                        warn!(
+                            "Rejecting reverse hello from server {}, expected {:?}",
+                            rhe.server_uri, self.server_uri
+                        );
+                        StatusCode::BadServerUriInvalid
+                    } else {
+                        return Ok((framed_read, writer, policy));
+                    }
+                }
+                Ok(other) => {
+                    error!(
+                        "Unexpected message while waiting for reverse hello, got {:?}",
+                        other
+                    );
+                    StatusCode::BadTcpMessageTypeInvalid
+                }
+                Err(_) => {
+                    warn!("Timed out waiting for reverse hello");
+                    StatusCode::BadTimeout
+                }
             };
+
+        let err = ErrorMessage::from_status_code(status);
+        let _ = writer
+            .write_all(&opcua_types::SimpleBinaryEncodable::encode_to_vec(&err))
+            .await;
+        Err(status)
+    }
+}
+
+#[async_trait]
+impl Connector for ReverseConnector {
+    async fn connect(
+        &self,
+        channel: Arc<RwLock<SecureChannel>>,
+        outgoing_recv: tokio::sync::mpsc::Receiver<OutgoingMessage>,
+        config: TransportConfiguration,
+        endpoint_url: &str,
+    ) -> Result<TcpTransport, StatusCode> {
+        let mut listener = self.listener.lock().await;
+        if listener.is_none() {
+            debug!("Listening for reverse connections on {}", self.bind_addr);
+            *listener = Some(TcpListener::bind(self.bind_addr).await.map_err(|err| {
+                error!(
+                    "Could not listen for reverse connections on {}, {:?}",
+                    self.bind_addr, err
+                );
+                StatusCode::BadCommunicationError
+            })?);
+        }
+        let listener = listener.as_ref().unwrap();
+
+        // Keep accepting connections until a server sends a valid reverse hello.
+        let (framed_read, writer, policy) = loop {
+            let (socket, addr) = listener.accept().await.map_err(|err| {
+                error!("Failed to accept reverse connection, {:?}", err);
+                StatusCode::BadCommunicationError
+            })?;
+            debug!("Accepted reverse connection from {}", addr);
+            if let Ok(r) = self
+                .accept_reverse_hello(socket, &channel, endpoint_url)
+                .await
+            {
+                break r;
+            }
+        };
+
+        let handshake =
+            hello_handshake_framed(framed_read, writer, policy, &config, endpoint_url).await?;
+        Ok(TcpTransport::new(channel, outgoing_recv, config, handshake))
+    }
+}
+
+impl TcpTransport {
+    pub(super) fn new(
+        channel: Arc<RwLock<SecureChannel>>,
+        outgoing_recv: tokio::sync::mpsc::Receiver<OutgoingMessage>,
+        config: TransportConfiguration,
+        (mut framed_read, writer, ack, policy): HandshakeResult,
+    ) -> Self {
         let mut buffer = SendBuffer::new(
             config.send_buffer_size,
             config.max_message_size,
@@ -173,24 +382,41 @@ impl Connector for TcpConnector {
             ack.max_message_size as usize,
             ack.max_chunk_count as usize,
         );
+        buffer.set_buffer_pool(config.buffer_pool.clone());
+        framed_read
+            .decoder_mut()
+            .set_buffer_pool(config.buffer_pool.clone());
 
-        Ok(TcpTransport {
+        TcpTransport {
             state: TransportState::new(
                 channel,
                 outgoing_recv,
-                config.max_pending_incoming,
+                config.max_chunk_count,
+                config.max_message_size,
                 ack.send_buffer_size.min(config.recv_buffer_size as u32) as usize,
+                config.buffer_pool,
             ),
             read: framed_read,
             write: writer,
             send_buffer: buffer,
             should_close: false,
+            needs_flush: false,
             closed: TransportCloseState::Open,
-        })
+        }
     }
 }
 
 impl TcpTransport {
+    /// Size of the largest request that can be sent with the limits negotiated
+    /// with the server, 0 means no limit.
+    pub(super) fn max_request_size(&self) -> usize {
+        max_request_size(
+            self.send_buffer.max_message_size,
+            self.send_buffer.max_chunk_count,
+            self.send_buffer.send_buffer_size,
+        )
+    }
+
     fn handle_incoming_message(
         &mut self,
         incoming: Option<Result<Message, std::io::Error>>,
@@ -238,11 +464,35 @@ impl TcpTransport {
                         error!("write bytes task failed: {}", e);
                         return TransportPollResult::Closed(StatusCode::BadCommunicationError);
                     }
+                    self.needs_flush = true;
+                    TransportPollResult::OutgoingMessageSent
+                }
+                incoming = self.read.next() => {
+                    self.handle_incoming_message(incoming)
+                }
+                // The server may stop reading while we are writing, requests must
+                // still time out in that case.
+                _ = self.state.wait_for_timeout() => {
+                    TransportPollResult::RecoverableError(StatusCode::BadTimeout)
+                }
+            }
+        } else if self.needs_flush {
+            // Some streams buffer writes, so flush once the send buffer is drained.
+            tokio::select! {
+                r = self.write.flush() => {
+                    if let Err(e) = r {
+                        error!("flush stream failed: {}", e);
+                        return TransportPollResult::Closed(StatusCode::BadCommunicationError);
+                    }
+                    self.needs_flush = false;
                     TransportPollResult::OutgoingMessageSent
                 }
                 incoming = self.read.next() => {
                     self.handle_incoming_message(incoming)
                 }
+                _ = self.state.wait_for_timeout() => {
+                    TransportPollResult::RecoverableError(StatusCode::BadTimeout)
+                }
             }
         } else {
             if self.should_close {
@@ -316,3 +566,130 @@ impl Transport for TcpTransport {
         r
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        path::Path,
+        sync::Arc,
+        time::{Duration, Instant},
+    };
+
+    use async_trait::async_trait;
+    use opcua_core::comms::{
+        buffer_pool::BufferPool,
+        secure_channel::{Role, SecureChannel},
+        tcp_types::AcknowledgeMessage,
+    };
+    use opcua_crypto::CertificateStore;
+    use opcua_types::{ReadRequest, ReadValueId, StatusCode, TimestampsToReturn, VariableId};
+    use parking_lot::RwLock;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    use super::{hello_handshake, Connector, TcpTransport, Transport, TransportConfiguration};
+    use crate::transport::{state::Request, OutgoingMessage, TransportPollResult};
+
+    /// Connector for a server that completes the HELLO handshake, then never
+    /// reads or responds to anything.
+    struct StalledConnector;
+
+    #[async_trait]
+    impl Connector for StalledConnector {
+        async fn connect(
+            &self,
+            channel: Arc<RwLock<SecureChannel>>,
+            outgoing_recv: tokio::sync::mpsc::Receiver<OutgoingMessage>,
+            config: TransportConfiguration,
+            endpoint_url: &str,
+        ) -> Result<TcpTransport, StatusCode> {
+            // Use a small buffer, so that writes stall once the server stops reading.
+            let (client, mut server) = tokio::io::duplex(256);
+            tokio::spawn(async move {
+                let mut hello = [0u8; 256];
+                let _ = server.read(&mut hello).await;
+                let ack = AcknowledgeMessage::new(0, 8192, 8192, 0, 0);
+                let _ = server
+                    .write_all(&opcua_types::SimpleBinaryEncodable::encode_to_vec(&ack))
+                    .await;
+                // Keep the connection open without ever reading from it.
+                std::future::pending::<()>().await;
+                drop(server);
+            });
+            let handshake =
+                hello_handshake(Box::new(client), &channel, &config, endpoint_url).await?;
+            Ok(TcpTransport::new(channel, outgoing_recv, config, handshake))
+        }
+    }
+
+    async fn connect_stalled() -> (TcpTransport, tokio::sync::mpsc::Sender<OutgoingMessage>) {
+        let channel = Arc::new(RwLock::new(SecureChannel::new(
+            Arc::new(RwLock::new(CertificateStore::new(Path::new("./pki")))),
+            Role::Client,
+            Default::default(),
+        )));
+        let config = TransportConfiguration {
+            send_buffer_size: 8192,
+            recv_buffer_size: 8192,
+            max_message_size: 0,
+            max_chunk_count: 0,
+            buffer_pool: BufferPool::disabled(),
+        };
+        let (send, recv) = tokio::sync::mpsc::channel(10);
+        let transport = StalledConnector
+            .connect(channel, recv, config, "opc.tcp://localhost:4840")
+            .await
+            .unwrap();
+        (transport, send)
+    }
+
+    fn large_read_request() -> ReadRequest {
+        ReadRequest {
+            max_age: 0.0,
+            timestamps_to_return: TimestampsToReturn::Neither,
+            nodes_to_read: Some(vec![
+                ReadValueId::new_value(
+                    VariableId::Server_ServerStatus_State.into()
+                );
+                100
+            ]),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn request_times_out_when_server_stalls() {
+        let (mut transport, send) = connect_stalled().await;
+
+        // The transport itself fails the request once its deadline has passed,
+        // even while it is stuck writing to the server.
+        let (cb_send, mut cb_recv) = tokio::sync::oneshot::channel();
+        send.send(OutgoingMessage {
+            request: large_read_request().into(),
+            callback: Some(cb_send),
+            deadline: Instant::now() + Duration::from_millis(200),
+        })
+        .await
+        .unwrap();
+        let res = tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                tokio::select! {
+                    r = &mut cb_recv => break r.unwrap(),
+                    r = transport.poll() => {
+                        if let TransportPollResult::Closed(e) = r {
+                            panic!("Transport closed unexpectedly: {e}");
+                        }
+                    }
+                }
+            }
+        })
+        .await
+        .unwrap();
+        assert_eq!(res.unwrap_err(), StatusCode::BadTimeout);
+
+        // A request sent while the transport is not polled times out as well.
+        let res = Request::new(large_read_request(), send, Duration::from_millis(200))
+            .send()
+            .await;
+        assert_eq!(res.unwrap_err(), StatusCode::BadTimeout);
+    }
+}
@@ -0,0 +1,316 @@
+use std::{
+    io,
+    pin::Pin,
+    sync::Arc,
+    task::{ready, Context, Poll},
+};
+
+use super::connect::Connector;
+use super::core::OutgoingMessage;
+use super::tcp::{hello_handshake, HandshakeResult, TcpTransport, TransportConfiguration};
+use async_trait::async_trait;
+use futures::{Sink, Stream};
+use opcua_core::comms::{
+    secure_channel::SecureChannel, tcp_types::MESSAGE_HEADER_LEN, url::is_opc_ua_websocket_url,
+};
+use opcua_types::StatusCode;
+use parking_lot::RwLock;
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio::net::TcpStream;
+use tokio_tungstenite::{
+    tungstenite::{client::IntoClientRequest, http::HeaderValue, Message},
+    MaybeTlsStream, WebSocketStream,
+};
+use tracing::{debug, error};
+
+/// WebSocket subprotocol for OPC UA Connection Protocol messages using the binary encoding.
+pub const WS_SUBPROTOCOL_UACP: &str = "opcua+uacp";
+
+const SEC_WEBSOCKET_PROTOCOL: &str = "Sec-WebSocket-Protocol";
+
+/// Connector for `opc.wss` and `opc.ws` transport, as described in OPC UA Part 6, 7.5.
+///
+/// The connection is upgraded to a WebSocket using the `opcua+uacp` subprotocol,
+/// then the usual HELLO/ACKNOWLEDGE exchange is performed. Each message chunk
+/// is sent in a single binary WebSocket message.
+///
+/// The `opcua+uajson` subprotocol is not supported, since the client only
+/// implements the binary encoding of the secure conversation.
+pub struct WebSocketConnector;
+
+impl WebSocketConnector {
+    async fn connect_inner(
+        secure_channel: &RwLock<SecureChannel>,
+        config: &TransportConfiguration,
+        endpoint_url: &str,
+    ) -> Result<HandshakeResult, StatusCode> {
+        let socket = Self::connect_socket(endpoint_url).await?;
+        hello_handshake(Box::new(socket), secure_channel, config, endpoint_url).await
+    }
+
+    async fn connect_socket(endpoint_url: &str) -> Result<WebSocketByteStream, StatusCode> {
+        // The URL of the HTTP upgrade request is the endpoint URL with a `wss` or `ws` scheme.
+        let ws_url = match endpoint_url.strip_prefix("opc.") {
+            Some(url) if is_opc_ua_websocket_url(endpoint_url) => url,
+            _ => {
+                error!("Endpoint url {} is not a WebSocket url", endpoint_url);
+                return Err(StatusCode::BadTcpEndpointUrlInvalid);
+            }
+        };
+        let mut request = ws_url.into_client_request().map_err(|err| {
+            error!("Invalid WebSocket url {}, {:?}", ws_url, err);
+            StatusCode::BadTcpEndpointUrlInvalid
+        })?;
+        request.headers_mut().insert(
+            SEC_WEBSOCKET_PROTOCOL,
+            HeaderValue::from_static(WS_SUBPROTOCOL_UACP),
+        );
+
+        debug!("Connecting to WebSocket with url {}", ws_url);
+
+        let (stream, response) =
+            tokio_tungstenite::connect_async(request)
+                .await
+                .map_err(|err| {
+                    error!("Could not connect to WebSocket {}, {:?}", ws_url, err);
+                    StatusCode::BadCommunicationError
+                })?;
+
+        let protocol = response.headers().get(SEC_WEBSOCKET_PROTOCOL);
+        if protocol.map(|p| p.as_bytes()) != Some(WS_SUBPROTOCOL_UACP.as_bytes()) {
+            error!(
+                "Server did not accept the {} subprotocol, got {:?}",
+                WS_SUBPROTOCOL_UACP, protocol
+            );
+            return Err(StatusCode::BadProtocolVersionUnsupported);
+        }
+
+        Ok(WebSocketByteStream::new(stream))
+    }
+}
+
+#[async_trait]
+impl Connector for WebSocketConnector {
+    async fn connect(
+        &self,
+        channel: Arc<RwLock<SecureChannel>>,
+        outgoing_recv: tokio::sync::mpsc::Receiver<OutgoingMessage>,
+        config: TransportConfiguration,
+        endpoint_url: &str,
+    ) -> Result<TcpTransport, StatusCode> {
+        let handshake = Self::connect_inner(&channel, &config, endpoint_url).await?;
+        Ok(TcpTransport::new(channel, outgoing_recv, config, handshake))
+    }
+}
+
+/// Adapter exposing a WebSocket as a byte stream, so that it can be read and
+/// written with the same codec as `opc.tcp`.
+///
+/// Written bytes are buffered until a complete message chunk is available,
+/// which is then sent as a single binary message.
+struct WebSocketByteStream<S = MaybeTlsStream<TcpStream>> {
+    inner: WebSocketStream<S>,
+    read_buf: Vec<u8>,
+    read_pos: usize,
+    write_buf: Vec<u8>,
+}
+
+impl<S: AsyncRead + AsyncWrite + Unpin> WebSocketByteStream<S> {
+    fn new(inner: WebSocketStream<S>) -> Self {
+        Self {
+            inner,
+            read_buf: Vec::new(),
+            read_pos: 0,
+            write_buf: Vec::new(),
+        }
+    }
+
+    /// Get the length of the first complete message chunk in the write buffer, if any.
+    fn next_chunk_len(&self) -> Option<usize> {
+        // Each chunk starts with the message type, the chunk type,
+        // and the little endian size of the whole chunk.
+        let header = self.write_buf.get(..MESSAGE_HEADER_LEN)?;
+        let len = u32::from_le_bytes([header[4], header[5], header[6], header[7]]) as usize;
+        let len = len.max(MESSAGE_HEADER_LEN);
+        (self.write_buf.len() >= len).then_some(len)
+    }
+
+    /// Send all complete message chunks in the write buffer.
+    fn poll_send_chunks(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        while let Some(len) = self.next_chunk_len() {
+            ready!(Pin::new(&mut self.inner).poll_ready(cx)).map_err(io::Error::other)?;
+            let rest = self.write_buf.split_off(len);
+            let chunk = std::mem::replace(&mut self.write_buf, rest);
+            Pin::new(&mut self.inner)
+                .start_send(Message::Binary(chunk))
+                .map_err(io::Error::other)?;
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<S: AsyncRead + AsyncWrite + Unpin> AsyncRead for WebSocketByteStream<S> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        while self.read_pos >= self.read_buf.len() {
+            match ready!(Pin::new(&mut self.inner).poll_next(cx)) {
+                Some(Ok(Message::Binary(data))) => {
+                    self.read_buf = data;
+                    self.read_pos = 0;
+                }
+                // Reading nothing signals the end of the stream.
+                Some(Ok(Message::Close(_))) | None => return Poll::Ready(Ok(())),
+                // Pings are answered by the WebSocket itself, other messages
+                // carry no OPC UA data.
+                Some(Ok(_)) => (),
+                Some(Err(err)) => return Poll::Ready(Err(io::Error::other(err))),
+            }
+        }
+        let end = self.read_buf.len().min(self.read_pos + buf.remaining());
+        buf.put_slice(&self.read_buf[self.read_pos..end]);
+        self.read_pos = end;
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<S: AsyncRead + AsyncWrite + Unpin> AsyncWrite for WebSocketByteStream<S> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        ready!(self.poll_send_chunks(cx))?;
+        self.write_buf.extend_from_slice(buf);
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        ready!(self.poll_send_chunks(cx))?;
+        Pin::new(&mut self.inner)
+            .poll_flush(cx)
+            .map_err(io::Error::other)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        ready!(self.as_mut().poll_flush(cx))?;
+        Pin::new(&mut self.inner)
+            .poll_close(cx)
+            .map_err(io::Error::other)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::{SinkExt, StreamExt};
+    use tokio::io::{duplex, AsyncReadExt, AsyncWriteExt, DuplexStream};
+    use tokio_tungstenite::tungstenite::protocol::Role;
+
+    use super::*;
+
+    /// Create a byte stream connected to the other end of an in-memory WebSocket.
+    async fn connect() -> (
+        WebSocketByteStream<DuplexStream>,
+        WebSocketStream<DuplexStream>,
+    ) {
+        let (client, server) = duplex(65536);
+        let client = WebSocketStream::from_raw_socket(client, Role::Client, None).await;
+        let server = WebSocketStream::from_raw_socket(server, Role::Server, None).await;
+        (WebSocketByteStream::new(client), server)
+    }
+
+    /// Create a message chunk of `len` bytes, including the header.
+    fn chunk(len: u32, fill: u8) -> Vec<u8> {
+        let mut chunk = b"MSGF".to_vec();
+        chunk.extend_from_slice(&len.to_le_bytes());
+        chunk.resize(len as usize, fill);
+        chunk
+    }
+
+    #[tokio::test]
+    async fn next_chunk_len() {
+        let (mut stream, _server) = connect().await;
+        assert_eq!(stream.next_chunk_len(), None);
+
+        // Incomplete header
+        stream.write_buf = chunk(16, 1)[..6].to_vec();
+        assert_eq!(stream.next_chunk_len(), None);
+        // Incomplete chunk
+        stream.write_buf = chunk(16, 1)[..12].to_vec();
+        assert_eq!(stream.next_chunk_len(), None);
+        // Complete chunk, followed by part of the next one
+        stream.write_buf = chunk(16, 1);
+        assert_eq!(stream.next_chunk_len(), Some(16));
+        stream.write_buf.extend_from_slice(&chunk(20, 2)[..4]);
+        assert_eq!(stream.next_chunk_len(), Some(16));
+        // A size smaller than the header still consumes the header.
+        stream.write_buf = chunk(8, 0);
+        stream.write_buf[4..8].copy_from_slice(&0u32.to_le_bytes());
+        assert_eq!(stream.next_chunk_len(), Some(8));
+    }
+
+    #[tokio::test]
+    async fn write_sends_one_message_per_chunk() {
+        let (mut stream, mut server) = connect().await;
+        let first = chunk(16, 1);
+        let second = chunk(24, 2);
+        let third = chunk(12, 3);
+
+        // A chunk written in parts is sent as a single message once it is complete.
+        stream.write_all(&first[..5]).await.unwrap();
+        stream.write_all(&first[5..]).await.unwrap();
+        stream.flush().await.unwrap();
+        assert_eq!(
+            server.next().await.unwrap().unwrap(),
+            Message::Binary(first)
+        );
+
+        // Several chunks in a single write are sent as separate messages,
+        // an incomplete chunk is held back until the rest is written.
+        let mut data = second.clone();
+        data.extend_from_slice(&third[..10]);
+        stream.write_all(&data).await.unwrap();
+        stream.flush().await.unwrap();
+        assert_eq!(
+            server.next().await.unwrap().unwrap(),
+            Message::Binary(second)
+        );
+        stream.write_all(&third[10..]).await.unwrap();
+        stream.shutdown().await.unwrap();
+        assert_eq!(
+            server.next().await.unwrap().unwrap(),
+            Message::Binary(third)
+        );
+    }
+
+    #[tokio::test]
+    async fn read_across_messages() {
+        let (mut stream, mut server) = connect().await;
+        let first = chunk(20, 1);
+        let second = chunk(16, 2);
+        server.send(Message::Binary(first.clone())).await.unwrap();
+        server.send(Message::Text("ignored".into())).await.unwrap();
+        server.send(Message::Binary(second.clone())).await.unwrap();
+
+        // A message is returned in parts if the buffer is too small.
+        let mut buf = [0u8; 8];
+        assert_eq!(stream.read(&mut buf).await.unwrap(), 8);
+        assert_eq!(buf, first[..8]);
+
+        // Reads never span more than one message.
+        let mut buf = [0u8; 32];
+        assert_eq!(stream.read(&mut buf).await.unwrap(), 12);
+        assert_eq!(buf[..12], first[8..]);
+
+        // Non-binary messages are skipped.
+        let mut buf = vec![0u8; second.len()];
+        stream.read_exact(&mut buf).await.unwrap();
+        assert_eq!(buf, second);
+
+        // Closing the WebSocket ends the stream.
+        server.send(Message::Close(None)).await.unwrap();
+        assert_eq!(stream.read(&mut buf).await.unwrap(), 0);
+    }
+}
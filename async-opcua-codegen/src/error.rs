@@ -32,14 +32,17 @@ pub enum CodeGenErrorKind {
 pub struct CodeGenError {
     #[source]
     pub kind: Box<CodeGenErrorKind>,
+    /// Outermost context of the error.
     pub context: Option<String>,
+    /// Context replaced by later calls to `with_context`, from innermost to outermost.
+    pub inner_context: Vec<String>,
     pub file: Option<String>,
 }
 
 impl Display for CodeGenError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "Code generation failed: {}", self.kind)?;
-        if let Some(context) = &self.context {
+        for context in self.context_stack() {
             write!(f, ", while {context}")?;
         }
         if let Some(file) = &self.file {
@@ -100,11 +103,23 @@ impl CodeGenError {
         Self::new(CodeGenErrorKind::MissingRequiredValue(name))
     }
 
+    /// Add context to the error. Context added later is assumed to be
+    /// further out, and is displayed after any existing context.
     pub fn with_context(mut self, context: impl Into<String>) -> Self {
-        self.context = Some(context.into());
+        if let Some(inner) = self.context.replace(context.into()) {
+            self.inner_context.push(inner);
+        }
         self
     }
 
+    /// Get all context of the error, from innermost to outermost.
+    pub fn context_stack(&self) -> impl Iterator<Item = &str> {
+        self.inner_context
+            .iter()
+            .chain(self.context.as_ref())
+            .map(|c| c.as_str())
+    }
+
     pub fn in_file(mut self, file: impl Into<String>) -> Self {
         self.file = Some(file.into());
         self
@@ -114,6 +129,7 @@ impl CodeGenError {
         Self {
             kind: Box::new(kind),
             context: None,
+            inner_context: Vec::new(),
             file: None,
         }
     }
@@ -147,9 +147,19 @@ impl SchemaCache {
         docs_path: Option<&str>,
     ) -> Result<(), CodeGenError> {
         let nodeset = NodeSetInput::load(&self.root_path, file_path, docs_path)?;
+        self.add_nodeset(nodeset, file_path);
+        Ok(())
+    }
+
+    /// Add a parsed nodeset to the cache. The nodeset can be looked up by the URI
+    /// of any of the models it defines, or by its file path.
+    pub fn add_nodeset(&mut self, nodeset: NodeSetInput, file_path: &str) {
+        let other_models: Vec<_> = nodeset.model_uris.iter().skip(1).cloned().collect();
         let idx = self.nodesets.insert(nodeset.uri.clone(), nodeset);
+        for uri in other_models {
+            self.nodesets.aliases.insert(uri, idx);
+        }
         self.nodesets.add_file_aliases(file_path, idx);
-        Ok(())
     }
 
     pub fn load_binary_schema(&mut self, file_path: &str) -> Result<(), CodeGenError> {
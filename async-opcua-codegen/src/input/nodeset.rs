@@ -45,15 +45,18 @@ impl TypeInfo {
 pub struct NodeSetInput {
     pub xml: UANodeSet,
     pub aliases: HashMap<String, String>,
+    /// URI of the first model in the nodeset.
     pub uri: String,
+    /// URIs of all models defined in the nodeset, in the order they are declared.
+    pub model_uris: Vec<String>,
     pub required_model_uris: Vec<String>,
     /// Map from numeric ID to documentation link.
     pub documentation: Option<HashMap<i64, String>>,
     pub referenced_xsd_schemas: HashSet<String>,
     pub path: String,
     pub namespaces: Vec<String>,
-    // Index of the model URI in the namespace array.
-    pub own_namespace_index: u16,
+    // Map from model URI to the index of the model in the namespace array.
+    pub model_namespaces: HashMap<String, u16>,
     // A little weird to store it as a result, but since it can fail it's actually the semantically
     // correct thing. It's a cached computation result.
     pub parent_type_ids: OnceLock<Result<HashMap<ParsedNodeId, ParsedNodeId>, CodeGenError>>,
@@ -141,23 +144,25 @@ impl NodeSetInput {
             return Err(CodeGenError::missing_required_value("Models"));
         };
 
-        if models.models.len() > 1 {
-            warn!("Multiple models found in nodeset file, this is not supported, and only the first will be used.");
-        }
-
         let Some(model) = models.models.first() else {
             return Err(CodeGenError::other("No model in model table"));
         };
-
-        let required_model_uris = model
-            .required_model
-            .iter()
-            .map(|v| v.model_uri.clone())
-            .collect();
+        let model_uris: Vec<_> = models.models.iter().map(|m| m.model_uri.clone()).collect();
+
+        // Models in the same nodeset may depend on each other, those dependencies
+        // are resolved within this nodeset.
+        let mut required_model_uris = Vec::new();
+        for required in models.models.iter().flat_map(|m| m.required_model.iter()) {
+            if !model_uris.contains(&required.model_uri)
+                && !required_model_uris.contains(&required.model_uri)
+            {
+                required_model_uris.push(required.model_uri.clone());
+            }
+        }
 
         info!(
             "Loaded nodeset {} with {} nodes",
-            model.model_uri,
+            model_uris.join(", "),
             nodeset.nodes.len(),
         );
 
@@ -182,20 +187,28 @@ impl NodeSetInput {
         let xsd_uris = Self::find_referenced_xsd_schemas(&nodeset);
 
         let mut namespaces = Vec::new();
-        let mut own_namespace_index = 0;
         // Whether they define it or not, all nodesets depend on the base namespace.
         namespaces.push(BASE_NAMESPACE.to_owned());
         for namespace in nodeset.namespace_uris.iter().flat_map(|n| n.uris.iter()) {
             if namespace != BASE_NAMESPACE {
-                if namespace == &model.model_uri {
-                    own_namespace_index = namespaces.len() as u16;
-                }
                 namespaces.push(namespace.clone());
             }
         }
+        let mut model_namespaces = HashMap::new();
+        for uri in &model_uris {
+            let index = match namespaces.iter().position(|n| n == uri) {
+                Some(index) => index as u16,
+                None => {
+                    warn!("Model {uri} is missing from the namespace table, assuming index 0");
+                    0
+                }
+            };
+            model_namespaces.insert(uri.clone(), index);
+        }
 
         Ok(Self {
             uri: model.model_uri.clone(),
+            model_uris,
             xml: nodeset,
             aliases: aliases.unwrap_or_default(),
             required_model_uris,
@@ -204,7 +217,7 @@ impl NodeSetInput {
             path: path.to_owned(),
             parent_type_ids: OnceLock::new(),
             namespaces,
-            own_namespace_index,
+            model_namespaces,
             type_info: OnceLock::new(),
         })
     }
@@ -225,6 +238,19 @@ impl NodeSetInput {
         Self::parse(&data, file_path, docs.as_deref()).map_err(|e| e.in_file(file_path))
     }
 
+    /// Return `true` if the namespace with index `namespace` belongs to one of the
+    /// models defined in this nodeset.
+    pub fn owns_namespace(&self, namespace: u16) -> bool {
+        self.model_namespaces.values().any(|n| *n == namespace)
+    }
+
+    /// Get the index of the model with URI `uri` in the namespace array of this nodeset.
+    pub fn model_namespace_index(&self, uri: &str) -> Result<u16, CodeGenError> {
+        self.model_namespaces.get(uri).copied().ok_or_else(|| {
+            CodeGenError::other(format!("Model {uri} not found in nodeset {}", self.path))
+        })
+    }
+
     pub fn validate(&self, cache: &SchemaCache) -> Result<(), CodeGenError> {
         for uri in &self.required_model_uris {
             cache.get_nodeset(uri)?;
@@ -17,13 +17,15 @@ pub use error::CodeGenError;
 use ids::{generate_node_ids, NodeIdCodeGenTarget};
 use nodeset::{generate_events, generate_target, make_root_module, NodeSetCodeGenTarget};
 use serde::{Deserialize, Serialize};
-use syn::{parse_str, File};
+use syn::{parse_quote, parse_str, File, Ident};
 use tracing::info;
 pub use types::{
     base_ignored_types, base_native_type_mappings, basic_types_import_map, BsdTypeLoader,
     CodeGenItemConfig, GeneratedItem, ItemDefinition, LoadedType, LoadedTypes,
 };
-use types::{generate_types, generate_types_nodeset, type_loader_impl, EncodingIds, ExternalType};
+use types::{
+    generate_types, generate_types_nodeset, type_loader_impl, EncodingIds, ExternalType, ModelTypes,
+};
 pub use utils::{create_module_file, GeneratedOutput};
 
 pub fn write_to_directory<T: GeneratedOutput>(
@@ -117,43 +119,75 @@ pub fn run_codegen(config: &CodeGenConfig, root_path: &str) -> Result<(), CodeGe
         match target {
             CodeGenTarget::Types(t) => {
                 info!("Running data type code generation for {}", t.file);
-                let (types, target_namespace, path) = if t.file.ends_with(".xml") {
+                let (models, path) = if t.file.ends_with(".xml") {
                     let input = cache.get_nodeset(&t.file)?;
                     let r = generate_types_nodeset(t, input, &cache, &config.preferred_locale)
                         .map_err(|e| e.in_file(&input.path))?;
-                    (r.0, r.1, input.path.clone())
+                    (r, input.path.clone())
                 } else {
                     let input = cache.get_binary_schema(&t.file)?;
-                    let r = generate_types(t, input).map_err(|e| e.in_file(&t.file))?;
-                    (r.0, r.1, input.path.clone())
+                    let (types, target_namespace) =
+                        generate_types(t, input).map_err(|e| e.in_file(&t.file))?;
+                    let model = ModelTypes {
+                        module: None,
+                        types,
+                        target_namespace,
+                    };
+                    (vec![model], input.path.clone())
                 };
-                info!("Writing {} types to {}", types.len(), t.output_dir);
 
                 let header = make_header(&path, &[&config.extra_header, &t.extra_header]);
 
-                let mut object_ids: Vec<_> = types
+                let model_modules: Vec<_> =
+                    models.iter().filter_map(|m| m.module.clone()).collect();
+                // Module names are used both as directory names and Rust identifiers,
+                // so they must be valid identifiers as-is.
+                let module_idents = model_modules
                     .iter()
-                    .filter_map(|v| v.encoding_ids.as_ref().map(|i| (i.clone(), v.name.clone())))
-                    .collect();
-                let id_path: syn::Path = parse_str(&t.id_path)?;
-                for (name, typ) in t.types_import_map.iter() {
-                    if typ.add_to_type_loader {
-                        object_ids.push((
-                            EncodingIds::new_external(&id_path, name, typ)?,
-                            format!("{}::{}", typ.path, name),
-                        ));
-                    }
+                    .map(|m| {
+                        let is_plain = m.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+                        parse_str::<Ident>(m)
+                            .ok()
+                            .filter(|_| is_plain)
+                            .ok_or_else(|| {
+                                CodeGenError::other(format!(
+                                    "Model module name {m:?} is not a valid Rust identifier"
+                                ))
+                                .in_file(&path)
+                            })
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                if !model_modules.is_empty() {
+                    // Each model is written to its own directory below the output directory,
+                    // so clear it here instead.
+                    let dir = format!("{root_path}/{}", t.output_dir);
+                    let _ = std::fs::remove_dir_all(&dir);
+                    std::fs::create_dir_all(&dir)
+                        .map_err(|e| CodeGenError::io(&format!("Failed to create dir {dir}"), e))?;
                 }
 
-                let modules = write_to_directory(&t.output_dir, root_path, &header, types)
-                    .map_err(|e| e.in_file(&path))?;
-                let mut module_file = create_module_file(modules);
-                module_file
-                    .items
-                    .extend(type_loader_impl(&object_ids, &target_namespace).into_iter());
+                for (idx, model) in models.into_iter().enumerate() {
+                    let output_dir = match &model.module {
+                        Some(module) => format!("{}/{module}", t.output_dir),
+                        None => t.output_dir.clone(),
+                    };
+                    // Imported types are only added to the type loader of the first model.
+                    write_types(t, root_path, &output_dir, &header, model, idx == 0)
+                        .map_err(|e| e.in_file(&path))?;
+                }
 
-                write_module_file(&t.output_dir, root_path, &header, module_file)
-                    .map_err(|e| e.in_file(&path))?;
+                if !model_modules.is_empty() {
+                    let module_file = File {
+                        shebang: None,
+                        attrs: Vec::new(),
+                        items: module_idents
+                            .iter()
+                            .map(|ident| parse_quote! { pub mod #ident; })
+                            .collect(),
+                    };
+                    write_module_file(&t.output_dir, root_path, &header, module_file)
+                        .map_err(|e| e.in_file(&path))?;
+                }
             }
             CodeGenTarget::Nodes(n) => {
                 info!("Running node set code generation for {}", n.file);
@@ -228,6 +262,40 @@ pub fn run_codegen(config: &CodeGenConfig, root_path: &str) -> Result<(), CodeGe
     Ok(())
 }
 
+fn write_types(
+    target: &TypeCodeGenTarget,
+    root_path: &str,
+    output_dir: &str,
+    header: &str,
+    model: ModelTypes,
+    include_imported: bool,
+) -> Result<(), CodeGenError> {
+    info!("Writing {} types to {}", model.types.len(), output_dir);
+
+    let mut object_ids: Vec<_> = model
+        .types
+        .iter()
+        .filter_map(|v| v.encoding_ids.as_ref().map(|i| (i.clone(), v.name.clone())))
+        .collect();
+    let id_path: syn::Path = parse_str(&target.id_path)?;
+    for (name, typ) in target.types_import_map.iter() {
+        if typ.add_to_type_loader && include_imported {
+            object_ids.push((
+                EncodingIds::new_external(&id_path, name, typ)?,
+                format!("{}::{}", typ.path, name),
+            ));
+        }
+    }
+
+    let modules = write_to_directory(output_dir, root_path, header, model.types)?;
+    let mut module_file = create_module_file(modules);
+    module_file
+        .items
+        .extend(type_loader_impl(&object_ids, &model.target_namespace).into_iter());
+
+    write_module_file(output_dir, root_path, header, module_file)
+}
+
 #[derive(Serialize, Deserialize, Debug, Default)]
 pub struct TypeCodeGenTarget {
     pub file: String,
@@ -236,6 +304,10 @@ pub struct TypeCodeGenTarget {
     pub ignore: Vec<String>,
     #[serde(default)]
     pub types_import_map: HashMap<String, ExternalType>,
+    /// Types that should be replaced by a different type, for example to map
+    /// an opaque type to `ByteString` or a native integer type.
+    #[serde(default)]
+    pub native_type_mappings: HashMap<String, String>,
     #[serde(default)]
     pub default_excluded: HashSet<String>,
     #[serde(default)]
@@ -248,6 +320,21 @@ pub struct TypeCodeGenTarget {
     pub id_path: String,
     #[serde(default)]
     pub node_ids_from_nodeset: bool,
+    /// Derive `serde::Serialize` and `serde::Deserialize` for generated types,
+    /// behind the `serde` feature of the generated crate. The builtin types in
+    /// `opcua::types`, such as `UAString`, `NodeId` or `LocalizedText`, do not
+    /// implement serde, so types that contain them, directly or through other
+    /// generated types, are generated without serde derives, and a warning is logged.
+    #[serde(default)]
+    pub derive_serde: bool,
+    #[serde(default)]
+    pub generate_builders: bool,
+    /// Module names for each model, when generating types from a nodeset
+    /// that defines multiple models. The types from each model are written to
+    /// a separate module. By default the module is named after the last segment
+    /// of the model URI.
+    #[serde(default)]
+    pub model_modules: HashMap<String, String>,
 }
 
 mod defaults {
@@ -220,9 +220,9 @@ pub fn make_root_module(
         });
     }
 
-    let own_ns = &input.uri;
+    let own_ns = &input.model_uris;
     let namespace_out = quote! {
-        #own_ns.to_owned(),
+        #(#own_ns.to_owned()),*
     };
 
     items.push(parse_quote! {
@@ -55,6 +55,10 @@ pub struct ExternalType {
     pub path: String,
     /// Whether this type has a default implementation.
     pub has_default: Option<bool>,
+    /// Whether this type implements `serde::Serialize` and `serde::Deserialize`
+    /// when the `serde` feature is enabled.
+    #[serde(default)]
+    pub has_serde: bool,
     /// Base type, if any
     pub base_type: Option<String>,
     /// Add to type loader impl
@@ -68,6 +72,7 @@ impl ExternalType {
         Self {
             path: path.to_owned(),
             has_default: Some(has_default),
+            has_serde: false,
             base_type: None,
             add_to_type_loader: false,
             ids: None,
@@ -3,8 +3,8 @@ use std::collections::{HashMap, HashSet};
 use convert_case::{Case, Casing};
 use proc_macro2::Span;
 use syn::{
-    parse_quote, parse_str, punctuated::Punctuated, FieldsNamed, File, Generics, Item, ItemEnum,
-    ItemMacro, ItemStruct, Lit, LitByte, Path, Token, Type, Visibility,
+    parse_quote, parse_str, punctuated::Punctuated, Attribute, FieldsNamed, File, Generics, Ident,
+    Item, ItemEnum, ItemMacro, ItemStruct, Lit, LitByte, Path, Token, Type, Visibility,
 };
 use tracing::warn;
 
@@ -16,10 +16,10 @@ use crate::{
 
 use super::{
     encoding_ids::EncodingIds,
-    loaders::{EnumReprType, EnumType, FieldType, StructureFieldType, StructuredType},
+    loaders::{EnumReprType, EnumType, FieldType, OpaqueType, StructureFieldType, StructuredType},
     ExternalType, LoadedType,
 };
-use quote::quote;
+use quote::{format_ident, quote, ToTokens};
 
 pub enum ItemDefinition {
     Struct(ItemStruct),
@@ -67,11 +67,14 @@ pub struct CodeGenItemConfig {
     pub enums_single_file: bool,
     pub structs_single_file: bool,
     pub node_ids_from_nodeset: bool,
+    pub derive_serde: bool,
+    pub generate_builders: bool,
 }
 
 pub struct ImportType {
     path: String,
     has_default: Option<bool>,
+    has_serde: Option<bool>,
     base_type: Option<FieldType>,
     is_defined: bool,
 }
@@ -104,6 +107,7 @@ impl CodeGenerator {
                         k,
                         ImportType {
                             has_default: v.has_default,
+                            has_serde: Some(v.has_serde),
                             base_type: match v.base_type.as_deref() {
                                 Some("ExtensionObject" | "OptionSet") => {
                                     Some(FieldType::ExtensionObject(None))
@@ -129,6 +133,33 @@ impl CodeGenerator {
         }
     }
 
+    fn has_serde(&self, name: &str) -> bool {
+        self.config.derive_serde
+            && self
+                .import_map
+                .get(name)
+                .is_some_and(|v| v.has_serde.is_some_and(|v| v))
+    }
+
+    /// Serde derive for the type `name`, if serde derives are enabled
+    /// and every field of the type supports serde.
+    fn serde_derive(&self, name: &str) -> Option<Attribute> {
+        self.has_serde(name).then(|| {
+            parse_quote! {
+                #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+            }
+        })
+    }
+
+    /// Serde rename to `name` for the type `type_name` or one of its fields or variants.
+    fn serde_rename(&self, type_name: &str, name: &str) -> Option<Attribute> {
+        self.has_serde(type_name).then(|| {
+            parse_quote! {
+                #[cfg_attr(feature = "serde", serde(rename = #name))]
+            }
+        })
+    }
+
     fn is_base_namespace(&self) -> bool {
         self.target_namespace == BASE_NAMESPACE
     }
@@ -152,6 +183,8 @@ impl CodeGenerator {
         };
 
         match input {
+            // Unions default to the null variant.
+            LoadedType::Struct(s) if s.is_union => true,
             LoadedType::Struct(s) => {
                 for k in &s.fields {
                     let has_default = match &k.typ {
@@ -169,6 +202,46 @@ impl CodeGenerator {
             LoadedType::Enum(e) => {
                 e.option || e.default_value.is_some() || e.values.iter().any(|v| v.value == 0)
             }
+            LoadedType::Opaque(_) => true,
+        }
+    }
+
+    /// Check whether the type `name` can derive serde traits. The builtin types
+    /// in `opcua::types` do not implement serde, so only native types and generated types
+    /// whose fields all support serde can. `visiting` holds the types currently being
+    /// checked, recursive fields are assumed to support serde.
+    fn is_serde_recursive(&self, name: &str, visiting: &mut HashSet<String>) -> bool {
+        if self.native_types.contains(name) {
+            return true;
+        }
+
+        let Some(it) = self.import_map.get(name) else {
+            return false;
+        };
+
+        if let Some(has_serde) = it.has_serde {
+            return has_serde;
+        }
+
+        let Some(input) = self.input.get(name) else {
+            return false;
+        };
+
+        if !visiting.insert(name.to_owned()) {
+            return true;
+        }
+
+        match input {
+            LoadedType::Struct(s) => s.visible_fields().all(|f| {
+                let typ = match &f.typ {
+                    StructureFieldType::Field(t) | StructureFieldType::Array(t) => t,
+                };
+                self.is_serde_recursive(typ.as_type_str(), visiting)
+            }),
+            // Bitfields are generated with bitflags, which needs its own serde feature.
+            LoadedType::Enum(e) => !e.option,
+            // Opaque types without a fixed length wrap a `ByteString`.
+            LoadedType::Opaque(o) => o.length_in_bits.is_some(),
         }
     }
 
@@ -180,11 +253,11 @@ impl CodeGenerator {
                 continue;
             }
             let name = match item {
-                LoadedType::Struct(s) => {
+                LoadedType::Struct(_) | LoadedType::Opaque(_) => {
                     if self.config.structs_single_file {
                         "structs".to_owned()
                     } else {
-                        s.name.to_case(Case::Snake)
+                        item.name().to_case(Case::Snake)
                     }
                 }
                 LoadedType::Enum(s) => {
@@ -202,9 +275,10 @@ impl CodeGenerator {
                     path: format!("super::{name}"),
                     // Determined later
                     has_default: None,
+                    has_serde: None,
                     base_type: match &item {
                         LoadedType::Struct(v) => v.base_type.clone(),
-                        LoadedType::Enum(_) => None,
+                        LoadedType::Enum(_) | LoadedType::Opaque(_) => None,
                     },
                     is_defined: false,
                 },
@@ -212,8 +286,16 @@ impl CodeGenerator {
         }
         for key in self.import_map.keys().cloned().collect::<Vec<_>>() {
             let has_default = self.is_default_recursive(&key);
+            let has_serde = self.is_serde_recursive(&key, &mut HashSet::new());
+            if self.config.derive_serde && !has_serde && self.input.contains_key(&key) {
+                warn!(
+                    "Type {key} does not derive serde traits, since it contains types \
+                    that do not implement them"
+                );
+            }
             if let Some(it) = self.import_map.get_mut(&key) {
                 it.has_default = Some(has_default);
+                it.has_serde = Some(has_serde);
             }
         }
 
@@ -228,10 +310,14 @@ impl CodeGenerator {
                 continue;
             }
 
-            match item {
-                LoadedType::Struct(v) => generated.push(self.generate_struct(v)?),
-                LoadedType::Enum(v) => generated.push(self.generate_enum(v)?),
-            }
+            let name = item.name().to_owned();
+            let res = match item {
+                LoadedType::Struct(v) if v.is_union => self.generate_union(v),
+                LoadedType::Struct(v) => self.generate_struct(v),
+                LoadedType::Enum(v) => self.generate_enum(v),
+                LoadedType::Opaque(v) => self.generate_opaque(v),
+            };
+            generated.push(res.map_err(|e| e.with_context(format!("generating type {name}")))?);
         }
 
         Ok(generated)
@@ -268,9 +354,21 @@ impl CodeGenerator {
         };
 
         let mut variants = quote! {};
+        let mut flag_names = quote! {};
+        // An empty bitfield is displayed with the name of the zero value, if the schema has one.
+        let empty_name = item
+            .values
+            .iter()
+            .find(|v| v.value == 0)
+            .map(|v| v.name.as_str())
+            .unwrap_or("0");
 
         for field in &item.values {
             let (name, _) = safe_ident(&field.name);
+            let orig = &field.name;
+            flag_names.extend(quote! {
+                (Self::#name, #orig),
+            });
             let value = field.value;
             let value_token = match item.typ {
                 EnumReprType::u8 => {
@@ -358,6 +456,30 @@ impl CodeGenerator {
             }
         });
 
+        let flag_count = proc_macro2::Literal::usize_unsuffixed(item.values.len());
+        impls.push(parse_quote! {
+            impl std::fmt::Display for #enum_ident {
+                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                    if self.is_empty() {
+                        return f.write_str(#empty_name);
+                    }
+                    let flags: [(Self, &str); #flag_count] = [#flag_names];
+                    let mut first = true;
+                    for (flag, name) in flags {
+                        if flag.is_empty() || !self.contains(flag) {
+                            continue;
+                        }
+                        if !first {
+                            f.write_str(", ")?;
+                        }
+                        f.write_str(name)?;
+                        first = false;
+                    }
+                    Ok(())
+                }
+            }
+        });
+
         let name = &item.name;
         impls.push(parse_quote! {
             #[cfg(feature = "xml")]
@@ -388,6 +510,7 @@ impl CodeGenerator {
 
         let mut attrs = Vec::new();
         let mut variants = Punctuated::new();
+        let mut display_arms = quote! {};
 
         attrs.push(parse_quote! {
             #[opcua::types::ua_encodable]
@@ -400,6 +523,7 @@ impl CodeGenerator {
         attrs.push(parse_quote! {
             #[derive(Debug, Copy, Clone, PartialEq, Eq)]
         });
+        attrs.extend(self.serde_derive(&item.name));
         let ty: Type = syn::parse_str(&item.typ.to_string())?;
         attrs.push(parse_quote! {
             #[repr(#ty)]
@@ -460,7 +584,12 @@ impl CodeGenerator {
                 attrs.extend(quote! {
                     #[opcua(rename = #orig)]
                 });
+                attrs.extend(self.serde_rename(&item.name, orig).into_token_stream());
             }
+            let orig = &field.name;
+            display_arms.extend(quote! {
+                Self::#name => #orig,
+            });
             variants.push(parse_quote! {
                 #attrs
                 #name = #value_token
@@ -473,6 +602,21 @@ impl CodeGenerator {
             attrs.push(parse_quote! {
                 #[opcua(rename = #name)]
             });
+            attrs.extend(self.serde_rename(&item.name, name));
+        }
+
+        // Display uses the names from the schema, not the rust identifiers.
+        let mut impls = Vec::new();
+        if !item.values.is_empty() {
+            impls.push(parse_quote! {
+                impl std::fmt::Display for #enum_ident {
+                    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                        f.write_str(match self {
+                            #display_arms
+                        })
+                    }
+                }
+            });
         }
 
         let res = ItemEnum {
@@ -487,7 +631,7 @@ impl CodeGenerator {
 
         Ok(GeneratedItem {
             item: ItemDefinition::Enum(res),
-            impls: Vec::new(),
+            impls,
             module: if self.config.enums_single_file {
                 "enums".to_owned()
             } else {
@@ -498,6 +642,98 @@ impl CodeGenerator {
         })
     }
 
+    /// Generate a newtype wrapper for an opaque type. Fixed width types of 8, 16, 32 or 64 bits
+    /// wrap an unsigned integer, other types wrap a `ByteString`.
+    fn generate_opaque(&self, item: OpaqueType) -> Result<GeneratedItem, CodeGenError> {
+        let (inner, is_copy): (Type, bool) = match item.length_in_bits {
+            None => (parse_quote! { opcua::types::ByteString }, false),
+            Some(8) => (parse_quote! { u8 }, true),
+            Some(16) => (parse_quote! { u16 }, true),
+            Some(32) => (parse_quote! { u32 }, true),
+            Some(64) => (parse_quote! { u64 }, true),
+            Some(len) => {
+                return Err(CodeGenError::other(format!(
+                    "Unsupported length {len} bits for opaque type {}, \
+                    add a native type mapping for it instead",
+                    item.name
+                )))
+            }
+        };
+
+        let mut attrs: Vec<Attribute> = Vec::new();
+        if let Some(doc) = &item.documentation {
+            attrs.push(parse_quote! {
+                #[doc = #doc]
+            });
+        }
+        attrs.push(parse_quote! {
+            #[derive(Debug, Clone, PartialEq, Default)]
+        });
+        attrs.extend(self.serde_derive(&item.name));
+
+        let (ident, _) = safe_ident(&item.name);
+        let to_inner = if is_copy {
+            quote! { v.0 }
+        } else {
+            quote! { v.0.clone() }
+        };
+
+        let mut impls = Vec::new();
+        impls.push(parse_quote! {
+            opcua::types::impl_encoded_as!(
+                #ident,
+                |v| Ok(#ident(v)),
+                |v: &#ident| Ok::<_, opcua::types::Error>(#to_inner),
+                |v: &#ident| opcua::types::SimpleBinaryEncodable::byte_len(&v.0)
+            );
+        });
+        impls.push(parse_quote! {
+            impl opcua::types::UaNullable for #ident {
+                fn is_ua_null(&self) -> bool {
+                    opcua::types::UaNullable::is_ua_null(&self.0)
+                }
+            }
+        });
+        impls.push(parse_quote! {
+            impl opcua::types::IntoVariant for #ident {
+                fn into_variant(self) -> opcua::types::Variant {
+                    opcua::types::IntoVariant::into_variant(self.0)
+                }
+            }
+        });
+        impls.push(parse_quote! {
+            impl From<#inner> for #ident {
+                fn from(value: #inner) -> Self {
+                    Self(value)
+                }
+            }
+        });
+        let name = &item.name;
+        impls.push(parse_quote! {
+            #[cfg(feature = "xml")]
+            impl opcua::types::xml::XmlType for #ident {
+                const TAG: &'static str = #name;
+            }
+        });
+
+        let res: ItemStruct = parse_quote! {
+            #(#attrs)*
+            pub struct #ident(pub #inner);
+        };
+
+        Ok(GeneratedItem {
+            item: ItemDefinition::Struct(res),
+            impls,
+            module: if self.config.structs_single_file {
+                "structs".to_owned()
+            } else {
+                item.name.to_case(Case::Snake)
+            },
+            name: item.name.clone(),
+            encoding_ids: None,
+        })
+    }
+
     fn is_extension_object(&self, typ: Option<&FieldType>) -> bool {
         let name = match &typ {
             Some(FieldType::Abstract(_)) | Some(FieldType::ExtensionObject(_)) => return true,
@@ -531,67 +767,293 @@ impl CodeGenerator {
         attrs.push(parse_quote! {
             #[derive(Debug, Clone, PartialEq)]
         });
+        attrs.extend(self.serde_derive(&item.name));
 
-        if self.has_default(&item.name) && !self.default_excluded.contains(&item.name) {
+        let has_default =
+            self.has_default(&item.name) && !self.default_excluded.contains(&item.name);
+        if has_default {
             attrs.push(parse_quote! {
                 #[derive(Default)]
             });
         }
 
-        let mut impls = Vec::new();
         let (struct_ident, renamed) = safe_ident(&item.name);
         if renamed {
             let name = &item.name;
             attrs.push(parse_quote! {
                 #[opcua(rename = #name)]
             });
+            attrs.extend(self.serde_rename(&item.name, name));
         }
 
         for field in item.visible_fields() {
-            let typ: Type = match &field.typ {
-                StructureFieldType::Field(f) => {
-                    syn::parse_str(&self.get_type_path(f.as_type_str())).map_err(|e| {
-                        CodeGenError::from(e)
-                            .with_context(format!("Generating path for {}", f.as_type_str()))
-                    })?
+            let typ = self.field_type(&field.typ).map_err(|e| {
+                e.with_context(format!("generating field {}.{}", item.name, field.name))
+            })?;
+            let (ident, changed) = safe_ident(&field.name);
+            let mut attrs = quote! {};
+            let orig = &field.original_name;
+            if changed {
+                attrs = quote! {
+                    #[opcua(rename = #orig)]
+                };
+                attrs.extend(self.serde_rename(&item.name, orig).into_token_stream());
+            } else if field.name.to_case(Case::Pascal) != *orig {
+                // The JSON and XML encodings use the pascal case field name by default,
+                // which does not round trip for names like `URI`.
+                attrs = quote! {
+                    #[opcua(rename = #orig)]
+                };
+            }
+            if let Some(doc) = &field.documentation {
+                attrs.extend(quote! {
+                    #[doc = #doc]
+                });
+            }
+            fields.push(parse_quote! {
+                #attrs
+                pub #ident: #typ
+            });
+        }
+
+        let (mut impls, encoding_ids) = self.generate_message_info(&item, &struct_ident)?;
+
+        // Builders rely on `Default` for fields that are not set.
+        if self.config.generate_builders && has_default {
+            impls.extend(self.generate_builder(&item, &struct_ident)?);
+        }
+
+        let res = ItemStruct {
+            attrs,
+            vis: Visibility::Public(Token![pub](Span::call_site())),
+            struct_token: Token![struct](Span::call_site()),
+            ident: struct_ident,
+            generics: Generics::default(),
+            fields: syn::Fields::Named(FieldsNamed {
+                brace_token: syn::token::Brace(Span::call_site()),
+                named: fields,
+            }),
+            semi_token: None,
+        };
+
+        Ok(GeneratedItem {
+            item: ItemDefinition::Struct(res),
+            impls,
+            module: if self.config.structs_single_file {
+                "structs".to_owned()
+            } else {
+                item.name.to_case(Case::Snake)
+            },
+            name: item.name.clone(),
+            encoding_ids,
+        })
+    }
+    fn generate_builder(
+        &self,
+        item: &StructuredType,
+        struct_ident: &Ident,
+    ) -> Result<Vec<Item>, CodeGenError> {
+        let builder_ident = format_ident!("{}Builder", struct_ident);
+        let mut setters = quote! {};
+
+        for field in item.visible_fields() {
+            let (ident, _) = safe_ident(&field.name);
+            // Fields renamed to avoid the `type` keyword get a raw identifier setter instead.
+            let setter_ident = if field.name == "type" {
+                Ident::new_raw(&field.name, Span::call_site())
+            } else {
+                ident.clone()
+            };
+            match &field.typ {
+                StructureFieldType::Field(_) => {
+                    let typ = self.field_type(&field.typ)?;
+                    let doc = format!("Set the `{ident}` field.");
+                    setters.extend(quote! {
+                        #[doc = #doc]
+                        pub fn #setter_ident(mut self, value: #typ) -> Self {
+                            self.inner.#ident = value;
+                            self
+                        }
+                    });
                 }
                 StructureFieldType::Array(f) => {
                     let path: Path =
                         syn::parse_str(&self.get_type_path(f.as_type_str())).map_err(|e| {
                             CodeGenError::from(e)
-                                .with_context(format!("Generating path for {}", f.as_type_str()))
+                                .with_context(format!("generating path for {}", f.as_type_str()))
                         })?;
-                    parse_quote! { Option<Vec<#path>> }
+                    let add_ident =
+                        format_ident!("add_{}", ident.to_string().trim_start_matches('_'));
+                    let doc = format!("Set the `{ident}` field to the given items.");
+                    let add_doc = format!("Add a single item to the `{ident}` field.");
+                    setters.extend(quote! {
+                        #[doc = #doc]
+                        pub fn #setter_ident(mut self, items: impl IntoIterator<Item = #path>) -> Self {
+                            self.inner.#ident = Some(items.into_iter().collect());
+                            self
+                        }
+
+                        #[doc = #add_doc]
+                        pub fn #add_ident(mut self, item: #path) -> Self {
+                            self.inner.#ident.get_or_insert_with(Vec::new).push(item);
+                            self
+                        }
+                    });
                 }
-            };
-            let (ident, changed) = safe_ident(&field.name);
+            }
+        }
+
+        let builder_doc = format!(
+            "Builder for [`{struct_ident}`]. Fields that are not set are given their default value."
+        );
+        let fn_doc = format!("Create a builder for `{struct_ident}`.");
+        Ok(vec![
+            parse_quote! {
+                impl #struct_ident {
+                    #[doc = #fn_doc]
+                    pub fn builder() -> #builder_ident {
+                        #builder_ident::default()
+                    }
+                }
+            },
+            parse_quote! {
+                #[doc = #builder_doc]
+                #[derive(Debug, Clone, Default)]
+                pub struct #builder_ident {
+                    inner: #struct_ident,
+                }
+            },
+            parse_quote! {
+                impl #builder_ident {
+                    #setters
+
+                    #[doc = "Build the value."]
+                    pub fn build(self) -> #struct_ident {
+                        self.inner
+                    }
+                }
+            },
+        ])
+    }
+
+    fn field_type(&self, typ: &StructureFieldType) -> Result<Type, CodeGenError> {
+        Ok(match typ {
+            StructureFieldType::Field(f) => syn::parse_str(&self.get_type_path(f.as_type_str()))
+                .map_err(|e| {
+                    CodeGenError::from(e)
+                        .with_context(format!("generating path for {}", f.as_type_str()))
+                })?,
+            StructureFieldType::Array(f) => {
+                let path: Path =
+                    syn::parse_str(&self.get_type_path(f.as_type_str())).map_err(|e| {
+                        CodeGenError::from(e)
+                            .with_context(format!("generating path for {}", f.as_type_str()))
+                    })?;
+                parse_quote! { Option<Vec<#path>> }
+            }
+        })
+    }
+
+    fn generate_union(&self, item: StructuredType) -> Result<GeneratedItem, CodeGenError> {
+        let mut attrs = Vec::new();
+        let mut variants = Punctuated::new();
+
+        attrs.push(parse_quote! {
+            #[opcua::types::ua_encodable]
+        });
+        if let Some(doc) = &item.documentation {
+            attrs.push(parse_quote! {
+                #[doc = #doc]
+            });
+        }
+        attrs.push(parse_quote! {
+            #[derive(Debug, Clone, PartialEq, Default)]
+        });
+        attrs.extend(self.serde_derive(&item.name));
+
+        let (enum_ident, renamed) = safe_ident(&item.name);
+        if renamed {
+            let name = &item.name;
+            attrs.push(parse_quote! {
+                #[opcua(rename = #name)]
+            });
+            attrs.extend(self.serde_rename(&item.name, name));
+        }
+
+        // The null variant has discriminant 0, the remaining variants are numbered
+        // from 1 in the order they are declared.
+        let null_name = if item.visible_fields().any(|f| f.original_name == "Null") {
+            "NullValue"
+        } else {
+            "Null"
+        };
+        let null_ident = Ident::new(null_name, Span::call_site());
+        variants.push(parse_quote! {
+            #[default]
+            #null_ident
+        });
+
+        for field in item.visible_fields() {
+            let typ = self.field_type(&field.typ)?;
+            let (ident, changed) = safe_ident(&field.original_name.to_case(Case::Pascal));
             let mut attrs = quote! {};
-            if changed {
+            if changed || ident != field.original_name {
                 let orig = &field.original_name;
                 attrs = quote! {
                     #[opcua(rename = #orig)]
                 };
+                attrs.extend(self.serde_rename(&item.name, orig).into_token_stream());
             }
             if let Some(doc) = &field.documentation {
                 attrs.extend(quote! {
                     #[doc = #doc]
                 });
             }
-            fields.push(parse_quote! {
+            variants.push(parse_quote! {
                 #attrs
-                pub #ident: #typ
+                #ident(#typ)
             });
         }
 
+        let (impls, encoding_ids) = self.generate_message_info(&item, &enum_ident)?;
+
+        let res = ItemEnum {
+            attrs,
+            vis: Visibility::Public(Token![pub](Span::call_site())),
+            enum_token: Token![enum](Span::call_site()),
+            ident: enum_ident,
+            generics: Generics::default(),
+            brace_token: syn::token::Brace(Span::call_site()),
+            variants,
+        };
+
+        Ok(GeneratedItem {
+            item: ItemDefinition::Enum(res),
+            impls,
+            module: if self.config.structs_single_file {
+                "structs".to_owned()
+            } else {
+                item.name.to_case(Case::Snake)
+            },
+            name: item.name.clone(),
+            encoding_ids,
+        })
+    }
+
+    fn generate_message_info(
+        &self,
+        item: &StructuredType,
+        struct_ident: &Ident,
+    ) -> Result<(Vec<Item>, Option<EncodingIds>), CodeGenError> {
+        let mut impls = Vec::new();
         let mut encoding_ids = None;
-        // Generate impls
         // Has message info
         if self.is_extension_object(item.base_type.as_ref()) {
             if self.config.node_ids_from_nodeset {
                 // To allow supporting the other encodings and not just panicing, use the data type id as fallback
                 // if the encoding type isn't set.
-                if let Some(ids) = item.base_type.and_then(|t| match t {
-                    FieldType::ExtensionObject(n) => n,
+                if let Some(ids) = item.base_type.as_ref().and_then(|t| match t {
+                    FieldType::ExtensionObject(n) => n.as_ref(),
                     _ => None,
                 }) {
                     // Should not be null here, since ID is always set when generating from nodeset.
@@ -622,7 +1084,7 @@ impl CodeGenerator {
                             }
                         }
                     });
-                    encoding_ids = Some(EncodingIds::new_raw(&ids)?);
+                    encoding_ids = Some(EncodingIds::new_raw(ids)?);
                 } else {
                     warn!(
                         "Type {} should be extension object but is missing encoding IDs, skipping",
@@ -682,29 +1144,6 @@ impl CodeGenerator {
             }
         }
 
-        let res = ItemStruct {
-            attrs,
-            vis: Visibility::Public(Token![pub](Span::call_site())),
-            struct_token: Token![struct](Span::call_site()),
-            ident: struct_ident,
-            generics: Generics::default(),
-            fields: syn::Fields::Named(FieldsNamed {
-                brace_token: syn::token::Brace(Span::call_site()),
-                named: fields,
-            }),
-            semi_token: None,
-        };
-
-        Ok(GeneratedItem {
-            item: ItemDefinition::Struct(res),
-            impls,
-            module: if self.config.structs_single_file {
-                "structs".to_owned()
-            } else {
-                item.name.to_case(Case::Snake)
-            },
-            name: item.name.clone(),
-            encoding_ids,
-        })
+        Ok((impls, encoding_ids))
     }
 }
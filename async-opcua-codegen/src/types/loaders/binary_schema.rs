@@ -6,8 +6,8 @@ use crate::{error::CodeGenError, utils::to_snake_case};
 
 use super::{
     types::{
-        EnumReprType, EnumType, EnumValue, FieldType, StructureField, StructureFieldType,
-        StructuredType,
+        EnumReprType, EnumType, EnumValue, FieldType, OpaqueType, StructureField,
+        StructureFieldType, StructuredType,
     },
     LoadedType,
 };
@@ -51,6 +51,56 @@ impl<'a> BsdTypeLoader<'a> {
         }
     }
 
+    /// Get the name of the switch field if `item` is a union.
+    ///
+    /// Unions are encoded as a `UInt32` switch field, followed by a list of fields
+    /// that each switch on that field with a distinct `SwitchValue`.
+    fn union_switch_field(
+        item: &opcua_xml::schema::opc_binary_schema::StructuredType,
+    ) -> Option<&str> {
+        let (switch, rest) = item.fields.split_first()?;
+        if switch.type_name.as_deref() != Some("opc:UInt32") || rest.is_empty() {
+            return None;
+        }
+        rest.iter()
+            .all(|f| {
+                f.switch_field.as_deref() == Some(switch.name.as_str()) && f.switch_value.is_some()
+            })
+            .then_some(switch.name.as_str())
+    }
+
+    /// Check that the variants of a union have the switch values 1, 2, 3...
+    ///
+    /// `fields` must be sorted by switch value, and the length field of an array
+    /// variant must have the same switch value as the array itself.
+    fn check_union_switch_values(
+        fields: &[&opcua_xml::schema::opc_binary_schema::FieldType],
+    ) -> Result<(), CodeGenError> {
+        let mut expected = 1;
+        for field in fields {
+            if let Some(array) = fields
+                .iter()
+                .find(|f| f.length_field.as_deref() == Some(field.name.as_str()))
+            {
+                if array.switch_value != field.switch_value {
+                    return Err(CodeGenError::other(format!(
+                        "Union length field {} has switch value {:?}, expected {:?} from array field {}",
+                        field.name, field.switch_value, array.switch_value, array.name
+                    )));
+                }
+                continue;
+            }
+            if field.switch_value != Some(expected) {
+                return Err(CodeGenError::other(format!(
+                    "Union field {} has switch value {:?}, expected {}",
+                    field.name, field.switch_value, expected
+                )));
+            }
+            expected += 1;
+        }
+        Ok(())
+    }
+
     fn load_structure(
         &self,
         item: &opcua_xml::schema::opc_binary_schema::StructuredType,
@@ -58,7 +108,19 @@ impl<'a> BsdTypeLoader<'a> {
         let mut fields_to_add = Vec::new();
         let mut fields_to_hide = Vec::new();
 
-        for field in &item.fields {
+        let union_switch = Self::union_switch_field(item);
+        let mut fields: Vec<_> = item.fields.iter().collect();
+        if let Some(switch) = union_switch {
+            // Union variants are numbered by their position, so sort by switch value.
+            // The length field of an array variant shares the switch value of the array,
+            // so keep it just before the array.
+            fields_to_hide.push(to_snake_case(switch));
+            fields.sort_by_key(|f| (f.switch_value, f.length_field.is_some()));
+            Self::check_union_switch_values(&fields[1..])
+                .map_err(|e| e.with_context(format!("loading union {}", item.description.name)))?;
+        }
+
+        for field in fields {
             let field_name = to_snake_case(&field.name);
             let typ = field
                 .type_name
@@ -67,7 +129,7 @@ impl<'a> BsdTypeLoader<'a> {
                 .and_then(|r| Ok(self.massage_type_name(strip_first_segment(r, ":")?)))
                 .map_err(|e| {
                     e.with_context(format!(
-                        "while loading field {} in struct {}",
+                        "loading field {} in struct {}",
                         field_name, item.description.name
                     ))
                 })?;
@@ -107,24 +169,20 @@ impl<'a> BsdTypeLoader<'a> {
                 .as_ref()
                 .and_then(|d| d.contents.clone()),
             base_type: match item.base_type.as_deref() {
-                Some("ua:ExtensionObject" | "ua:OptionSet") => {
+                Some("ua:ExtensionObject" | "ua:OptionSet" | "ua:Union") => {
                     Some(FieldType::ExtensionObject(None))
                 }
                 Some(base) => Some(FieldType::Normal(self.massage_type_name(base))),
                 None => None,
             },
-            is_union: false,
+            is_union: union_switch.is_some(),
         })
     }
 
     fn load_enum(&self, item: &EnumeratedType) -> Result<EnumType, CodeGenError> {
         let Some(len) = item.opaque.length_in_bits else {
-            return Err(
-                CodeGenError::missing_required_value("LengthInBits").with_context(format!(
-                    "while loading enum {}",
-                    item.opaque.description.name
-                )),
-            );
+            return Err(CodeGenError::missing_required_value("LengthInBits")
+                .with_context(format!("loading enum {}", item.opaque.description.name)));
         };
 
         let len_bytes = ((len as f64) / 8.0).ceil() as u64;
@@ -138,29 +196,18 @@ impl<'a> BsdTypeLoader<'a> {
                     "Unexpected enum length. {r} bytes for {}",
                     item.opaque.description.name
                 ))
-                .with_context(format!(
-                    "while loading enum {}",
-                    item.opaque.description.name
-                )))
+                .with_context(format!("loading enum {}", item.opaque.description.name)))
             }
         };
         let mut variants = Vec::new();
         for val in &item.variants {
             let Some(value) = val.value else {
-                return Err(
-                    CodeGenError::missing_required_value("Value").with_context(format!(
-                        "while loading enum {}",
-                        item.opaque.description.name
-                    )),
-                );
+                return Err(CodeGenError::missing_required_value("Value")
+                    .with_context(format!("loading enum {}", item.opaque.description.name)));
             };
             let Some(name) = &val.name else {
-                return Err(
-                    CodeGenError::missing_required_value("Name").with_context(format!(
-                        "while loading enum {}",
-                        item.opaque.description.name
-                    )),
-                );
+                return Err(CodeGenError::missing_required_value("Name")
+                    .with_context(format!("loading enum {}", item.opaque.description.name)));
             };
 
             variants.push(EnumValue {
@@ -186,6 +233,18 @@ impl<'a> BsdTypeLoader<'a> {
         })
     }
 
+    fn load_opaque(item: &opcua_xml::schema::opc_binary_schema::OpaqueType) -> OpaqueType {
+        OpaqueType {
+            name: item.description.name.clone(),
+            documentation: item
+                .description
+                .documentation
+                .as_ref()
+                .and_then(|d| d.contents.clone()),
+            length_in_bits: item.length_in_bits,
+        }
+    }
+
     pub fn target_namespace(&self) -> String {
         self.xml.target_namespace.clone()
     }
@@ -194,8 +253,15 @@ impl<'a> BsdTypeLoader<'a> {
         let mut types = Vec::new();
         for node in &self.xml.elements {
             match node {
-                // Ignore opaque types for now, should these be mapped to structs with raw binary data?
-                opcua_xml::schema::opc_binary_schema::TypeDictionaryItem::Opaque(_) => continue,
+                opcua_xml::schema::opc_binary_schema::TypeDictionaryItem::Opaque(o) => {
+                    // Opaque types with a native mapping are replaced by the mapped type.
+                    if self.ignored.contains(&o.description.name)
+                        || self.native_type_mappings.contains_key(&o.description.name)
+                    {
+                        continue;
+                    }
+                    types.push(LoadedType::Opaque(Self::load_opaque(o)));
+                }
                 opcua_xml::schema::opc_binary_schema::TypeDictionaryItem::Enumerated(e) => {
                     if self.ignored.contains(&e.opaque.description.name) {
                         continue;
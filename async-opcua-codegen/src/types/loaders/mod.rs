@@ -4,7 +4,9 @@ mod types;
 
 pub use binary_schema::BsdTypeLoader;
 pub use nodeset::NodeSetTypeLoader;
-pub use types::{EnumReprType, EnumType, FieldType, StructureFieldType, StructuredType};
+pub use types::{
+    EnumReprType, EnumType, FieldType, OpaqueType, StructureFieldType, StructuredType,
+};
 
 #[derive(Debug)]
 pub struct LoadedTypes {
@@ -16,6 +18,7 @@ pub struct LoadedTypes {
 pub enum LoadedType {
     Struct(StructuredType),
     Enum(EnumType),
+    Opaque(OpaqueType),
 }
 
 impl LoadedType {
@@ -23,6 +26,7 @@ impl LoadedType {
         match self {
             LoadedType::Struct(s) => &s.name,
             LoadedType::Enum(s) => &s.name,
+            LoadedType::Opaque(s) => &s.name,
         }
     }
 }
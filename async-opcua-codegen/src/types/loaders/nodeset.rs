@@ -17,6 +17,8 @@ pub struct NodeSetTypeLoader<'a> {
     ignored: HashSet<String>,
     native_type_mappings: HashMap<String, String>,
     input: &'a NodeSetInput,
+    /// Only load types in this namespace, if set.
+    namespace: Option<u16>,
     preferred_locale: String,
 }
 
@@ -38,12 +40,14 @@ impl<'a> NodeSetTypeLoader<'a> {
         ignored: HashSet<String>,
         native_type_mappings: HashMap<String, String>,
         input: &'a NodeSetInput,
+        namespace: Option<u16>,
         preferred_locale: &str,
     ) -> Self {
         Self {
             ignored,
             native_type_mappings,
             input,
+            namespace,
             preferred_locale: preferred_locale.to_owned(),
         }
     }
@@ -142,7 +146,7 @@ impl<'a> NodeSetTypeLoader<'a> {
             BuiltInTypeVariant::Structure => {
                 let type_info = self.resolve_type_info(&id, cache)?;
                 let ty = StructuredType {
-                    name,
+                    name: name.clone(),
                     id: Some(id),
                     fields: fields
                         .iter()
@@ -156,12 +160,19 @@ impl<'a> NodeSetTypeLoader<'a> {
                                     .unwrap_or(&f.name)
                                     .clone(),
                                 typ: {
-                                    let type_info = self.resolve_type_info(
-                                        &ParsedNodeId::parse(
-                                            self.input.resolve_alias(&f.data_type.0),
-                                        )?,
-                                        cache,
-                                    )?;
+                                    let context =
+                                        format!("resolving {name}.{} -> {}", f.name, f.data_type.0);
+                                    let id = ParsedNodeId::parse(
+                                        self.input.resolve_alias(&f.data_type.0),
+                                    )
+                                    .map_err(|e| e.with_context(&context))?;
+                                    let type_info = self
+                                        .find_type_info(&id, cache)
+                                        .map_err(|e| e.with_context(&context))?
+                                        .ok_or_else(|| {
+                                            Self::type_not_found(&id)
+                                                .with_context(format!("{context} (unresolved)"))
+                                        })?;
                                     let ty = Self::field_type_for_info(type_info);
                                     if f.value_rank.0 > 0 {
                                         StructureFieldType::Array(ty)
@@ -228,19 +239,27 @@ impl<'a> NodeSetTypeLoader<'a> {
         Ok(res)
     }
 
+    fn type_not_found(id: &ParsedNodeId) -> CodeGenError {
+        CodeGenError::other(format!("Did not find type name for data type {id}"))
+    }
+
     fn resolve_type_info(
         &self,
         id: &ParsedNodeId,
         cache: &SchemaCache,
     ) -> Result<TypeInfo, CodeGenError> {
-        let r = if id.namespace == self.input.own_namespace_index {
-            self.input
-                .get_type_names()?
-                .get(id)
-                .cloned()
-                .ok_or_else(|| {
-                    CodeGenError::other(format!("Did not find type name for data type {id}"))
-                })?
+        self.find_type_info(id, cache)?
+            .ok_or_else(|| Self::type_not_found(id))
+    }
+
+    /// Find the type info for the data type `id`, returning `None` if there is no such type.
+    fn find_type_info(
+        &self,
+        id: &ParsedNodeId,
+        cache: &SchemaCache,
+    ) -> Result<Option<TypeInfo>, CodeGenError> {
+        let r = if self.input.owns_namespace(id.namespace) {
+            self.input.get_type_names()?.get(id).cloned()
         } else {
             let ns = self
                 .input
@@ -256,26 +275,19 @@ impl<'a> NodeSetTypeLoader<'a> {
             let schema = cache.get_nodeset(ns)?;
             let next_id = ParsedNodeId {
                 value: id.value.clone(),
-                namespace: schema.own_namespace_index,
+                namespace: schema.model_namespace_index(ns)?,
             };
-            schema
-                .get_type_names()?
-                .get(&next_id)
-                .cloned()
-                .ok_or_else(|| {
-                    CodeGenError::other(format!("Did not find type name for data type {id}"))
-                })?
+            schema.get_type_names()?.get(&next_id).cloned()
         };
-        if let Some(native) = self.native_type_mappings.get(&r.name) {
-            Ok(TypeInfo {
+        Ok(r.map(|r| match self.native_type_mappings.get(&r.name) {
+            Some(native) => TypeInfo {
                 name: native.to_owned(),
                 is_abstract: false,
                 definition: None,
                 encoding_ids: Default::default(),
-            })
-        } else {
-            Ok(r)
-        }
+            },
+            None => r,
+        }))
     }
 
     fn find_builtin_type_variant(
@@ -296,7 +308,7 @@ impl<'a> NodeSetTypeLoader<'a> {
             }
         }
 
-        if id.namespace == schema.own_namespace_index {
+        if schema.owns_namespace(id.namespace) {
             let Some(parent) = schema.get_parent_type_ids()?.get(id) else {
                 return Err(CodeGenError::other(format!(
                     "Did not find parent of data type {orig}. Last known parent is {id}"
@@ -317,7 +329,7 @@ impl<'a> NodeSetTypeLoader<'a> {
             let schema = cache.get_nodeset(ns)?;
             let next_id = ParsedNodeId {
                 value: id.value.clone(),
-                namespace: schema.own_namespace_index,
+                namespace: schema.model_namespace_index(ns)?,
             };
             let Some(parent) = schema.get_parent_type_ids()?.get(&next_id) else {
                 return Err(CodeGenError::other(format!(
@@ -335,7 +347,15 @@ impl<'a> NodeSetTypeLoader<'a> {
             let UANode::DataType(d) = node else {
                 continue;
             };
-            if let Some(r) = self.load_data_type(d, cache)? {
+            if let Some(namespace) = self.namespace {
+                let id = ParsedNodeId::parse(self.input.resolve_alias(&d.base.base.node_id.0))?;
+                if id.namespace != namespace {
+                    continue;
+                }
+            }
+            if let Some(r) = self.load_data_type(d, cache).map_err(|e| {
+                e.with_context(format!("loading data type {}", d.base.base.node_id.0))
+            })? {
                 res.push(r);
             }
         }
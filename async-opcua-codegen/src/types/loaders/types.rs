@@ -49,6 +49,13 @@ impl StructuredType {
     }
 }
 
+#[derive(Debug)]
+pub struct OpaqueType {
+    pub name: String,
+    pub documentation: Option<String>,
+    pub length_in_bits: Option<i64>,
+}
+
 #[derive(serde::Serialize, Debug)]
 pub struct EnumValue {
     pub name: String,
@@ -6,8 +6,10 @@ mod loaders;
 pub use base_constants::*;
 pub use encoding_ids::EncodingIds;
 pub use gen::{CodeGenItemConfig, CodeGenerator, GeneratedItem, ItemDefinition};
-use loaders::NodeSetTypeLoader;
 pub use loaders::{BsdTypeLoader, LoadedType, LoadedTypes};
+use loaders::{FieldType, NodeSetTypeLoader};
+use std::collections::{HashMap, HashSet};
+
 use proc_macro2::TokenStream;
 use quote::quote;
 use syn::{parse_quote, parse_str, Item, Path};
@@ -15,6 +17,7 @@ use tracing::info;
 
 use crate::{
     input::{BinarySchemaInput, NodeSetInput, SchemaCache},
+    utils::to_snake_case,
     CodeGenError, TypeCodeGenTarget, BASE_NAMESPACE,
 };
 
@@ -37,14 +40,47 @@ pub fn generate_types(
             .cloned()
             .chain(base_ignored_types().into_iter())
             .collect(),
-        base_native_type_mappings(),
+        native_type_mappings(target),
         &input.xml,
     )?;
     let target_namespace = type_loader.target_namespace();
     let types = type_loader.from_bsd().map_err(|e| e.in_file(&input.path))?;
     info!("Loaded {} types", types.len());
 
-    generate_types_inner(target, target_namespace, types)
+    generate_types_inner(target, target_namespace, types, HashMap::new())
+}
+
+/// Types generated for a single model in a nodeset.
+pub struct ModelTypes {
+    /// Module the types should be written to, relative to the output directory.
+    /// `None` if the nodeset only defines a single model, in which case the types
+    /// are written directly to the output directory.
+    pub module: Option<String>,
+    pub types: Vec<GeneratedItem>,
+    pub target_namespace: String,
+}
+
+/// Get the name of the module types from the model with URI `uri` are written to,
+/// when generating types from a nodeset with multiple models. Unless configured
+/// in `model_modules`, this is the last segment of the URI in snake case.
+pub fn model_module_name(target: &TypeCodeGenTarget, uri: &str) -> String {
+    if let Some(module) = target.model_modules.get(uri) {
+        return module.clone();
+    }
+    let segment = uri
+        .split(['/', ':', '#'])
+        .rev()
+        .find(|s| !s.is_empty())
+        .unwrap_or(uri);
+    let name: String = to_snake_case(segment)
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    if name.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("_{name}")
+    } else {
+        name
+    }
 }
 
 pub fn generate_types_nodeset(
@@ -52,31 +88,106 @@ pub fn generate_types_nodeset(
     input: &NodeSetInput,
     cache: &SchemaCache,
     preferred_locale: &str,
-) -> Result<(Vec<GeneratedItem>, String), CodeGenError> {
-    let type_loader = NodeSetTypeLoader::new(
-        target
-            .ignore
-            .iter()
-            .cloned()
-            .chain(base_ignored_types())
-            .collect(),
-        base_native_type_mappings(),
-        input,
-        preferred_locale,
-    );
-    let target_namespace = input.uri.clone();
-    let types = type_loader.load_types(cache)?;
-    info!("Loaded {} types", types.len());
+) -> Result<Vec<ModelTypes>, CodeGenError> {
+    let ignored: HashSet<String> = target
+        .ignore
+        .iter()
+        .cloned()
+        .chain(base_ignored_types())
+        .collect();
+    let type_loader = |namespace| {
+        NodeSetTypeLoader::new(
+            ignored.clone(),
+            native_type_mappings(target),
+            input,
+            namespace,
+            preferred_locale,
+        )
+    };
+
+    if input.model_uris.len() <= 1 {
+        let types = type_loader(None).load_types(cache)?;
+        info!("Loaded {} types", types.len());
+        let (types, target_namespace) =
+            generate_types_inner(target, input.uri.clone(), types, HashMap::new())?;
+        return Ok(vec![ModelTypes {
+            module: None,
+            types,
+            target_namespace,
+        }]);
+    }
+
+    let mut models = Vec::with_capacity(input.model_uris.len());
+    for uri in &input.model_uris {
+        let module = model_module_name(target, uri);
+        if models.iter().any(|(_, m, _)| m == &module) {
+            return Err(CodeGenError::other(format!(
+                "Multiple models map to module {module}, set a module name for {uri} in model_modules"
+            )));
+        }
+        let types = type_loader(Some(input.model_namespace_index(uri)?)).load_types(cache)?;
+        info!("Loaded {} types for model {uri}", types.len());
+        models.push((uri, module, types));
+    }
+
+    let mut res = Vec::with_capacity(models.len());
+    for (uri, module, _) in &models {
+        // Types from the other models in the nodeset are written to sibling modules.
+        // They are passed to the generator as well, so that it can tell
+        // whether they implement `Default`.
+        let mut siblings = HashMap::new();
+        for (_, other_module, types) in models.iter().filter(|m| m.1 != *module) {
+            for ty in types {
+                let base_type = match ty {
+                    LoadedType::Struct(s)
+                        if matches!(s.base_type, Some(FieldType::ExtensionObject(_))) =>
+                    {
+                        Some("ExtensionObject".to_owned())
+                    }
+                    _ => None,
+                };
+                siblings.insert(
+                    ty.name().to_owned(),
+                    ExternalType {
+                        path: format!("super::super::{other_module}"),
+                        has_default: None,
+                        has_serde: false,
+                        base_type,
+                        add_to_type_loader: false,
+                        ids: None,
+                    },
+                );
+            }
+        }
+        let types = type_loader(None).load_types(cache)?;
+        let (types, target_namespace) =
+            generate_types_inner(target, (*uri).clone(), types, siblings)?;
+        res.push(ModelTypes {
+            module: Some(module.clone()),
+            types,
+            target_namespace,
+        });
+    }
+
+    Ok(res)
+}
 
-    generate_types_inner(target, target_namespace, types)
+fn native_type_mappings(target: &TypeCodeGenTarget) -> HashMap<String, String> {
+    let mut mappings = base_native_type_mappings();
+    for (k, v) in &target.native_type_mappings {
+        mappings.insert(k.clone(), v.clone());
+    }
+    mappings
 }
 
 fn generate_types_inner(
     target: &TypeCodeGenTarget,
     target_namespace: String,
     types: Vec<LoadedType>,
+    extra_imports: HashMap<String, ExternalType>,
 ) -> Result<(Vec<GeneratedItem>, String), CodeGenError> {
     let mut types_import_map = basic_types_import_map();
+    types_import_map.extend(extra_imports);
     for (k, v) in &target.types_import_map {
         types_import_map.insert(k.clone(), v.clone());
     }
@@ -95,6 +206,8 @@ fn generate_types_inner(
             enums_single_file: target.enums_single_file,
             structs_single_file: target.structs_single_file,
             node_ids_from_nodeset: target.node_ids_from_nodeset,
+            derive_serde: target.derive_serde,
+            generate_builders: target.generate_builders,
         },
         target_namespace.clone(),
         target.id_path.clone(),
@@ -331,3 +444,646 @@ fn xml_loader_impl(ids: &[&(EncodingIds, String)], namespace: &str) -> (TokenStr
         },
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use convert_case::{Case, Casing};
+    use quote::ToTokens;
+    use syn::{ItemEnum, ItemStruct};
+
+    use crate::{
+        input::{BinarySchemaInput, NodeSetInput, SchemaCache},
+        make_header,
+        utils::RUST_KEYWORDS,
+        write_to_directory, TypeCodeGenTarget,
+    };
+
+    use super::{
+        generate_types, generate_types_nodeset, GeneratedItem, ItemDefinition, ModelTypes,
+    };
+
+    const UNION_BSD: &str = r#"<opc:TypeDictionary
+  xmlns:opc="http://opcfoundation.org/BinarySchema/"
+  xmlns:ua="http://opcfoundation.org/UA/"
+  xmlns:tns="http://test.org/UA/"
+  DefaultByteOrder="LittleEndian"
+  TargetNamespace="http://test.org/UA/">
+  <opc:Import Namespace="http://opcfoundation.org/UA/" />
+  <opc:StructuredType Name="MyUnion" BaseType="ua:Union">
+    <opc:Documentation>A union.</opc:Documentation>
+    <opc:Field Name="SwitchField" TypeName="opc:UInt32" />
+    <opc:Field Name="Text" TypeName="opc:String" SwitchField="SwitchField" SwitchValue="2" />
+    <opc:Field Name="Number" TypeName="opc:Int32" SwitchField="SwitchField" SwitchValue="1" />
+    <opc:Field Name="Flag" TypeName="opc:Boolean" SwitchField="SwitchField" SwitchValue="3" />
+  </opc:StructuredType>
+  <opc:StructuredType Name="MyStruct" BaseType="ua:ExtensionObject">
+    <opc:Field Name="SwitchField" TypeName="opc:UInt32" />
+    <opc:Field Name="Number" TypeName="opc:Int32" />
+  </opc:StructuredType>
+</opc:TypeDictionary>"#;
+
+    fn target() -> TypeCodeGenTarget {
+        TypeCodeGenTarget {
+            id_path: "crate".to_owned(),
+            ..Default::default()
+        }
+    }
+
+    fn variant_names(item: &ItemEnum) -> Vec<String> {
+        item.variants.iter().map(|v| v.ident.to_string()).collect()
+    }
+
+    #[test]
+    fn generate_bsd_union() {
+        let input = BinarySchemaInput::parse(UNION_BSD, "test.bsd").unwrap();
+        let (items, _) = generate_types(&target(), &input).unwrap();
+
+        let un = items.iter().find(|i| i.name == "MyUnion").unwrap();
+        let ItemDefinition::Enum(en) = &un.item else {
+            panic!("Expected union to be generated as an enum");
+        };
+        // Variants are ordered by switch value, with the null variant first.
+        assert_eq!(variant_names(en), vec!["Null", "Number", "Text", "Flag"]);
+        // Unions are extension objects.
+        assert!(un.encoding_ids.is_some());
+
+        let st = items.iter().find(|i| i.name == "MyStruct").unwrap();
+        assert!(matches!(st.item, ItemDefinition::Struct(_)));
+    }
+
+    const SERDE_BSD: &str = include_str!("../../tests/serde/types.bsd");
+
+    fn render(items: &[GeneratedItem], name: &str) -> String {
+        let item = items.iter().find(|i| i.name == name).unwrap();
+        match &item.item {
+            ItemDefinition::Struct(s) => s.to_token_stream().to_string(),
+            ItemDefinition::Enum(e) => e.to_token_stream().to_string(),
+            ItemDefinition::BitField(b) => b.to_token_stream().to_string(),
+        }
+    }
+
+    #[test]
+    fn generate_serde_derives() {
+        let input = BinarySchemaInput::parse(SERDE_BSD, "test.bsd").unwrap();
+        let serde_target = TypeCodeGenTarget {
+            derive_serde: true,
+            ..target()
+        };
+        let (items, _) = generate_types(&serde_target, &input).unwrap();
+
+        let derive =
+            r#"cfg_attr (feature = "serde" , derive (serde :: Serialize , serde :: Deserialize))"#;
+        let st = render(&items, "MyStruct");
+        assert!(st.contains(derive), "{st}");
+        // Fields renamed by codegen keep their original name in serde.
+        assert!(
+            st.contains(r#"# [opcua (rename = "Type")] # [cfg_attr (feature = "serde" , serde (rename = "Type"))] pub __type : i32"#),
+            "{st}"
+        );
+        assert!(!st.contains(r#"rename = "Number""#), "{st}");
+
+        let en = render(&items, "MyEnum");
+        assert!(en.contains(derive), "{en}");
+        assert!(en.contains(r#"serde (rename = "1Value")"#), "{en}");
+
+        // Structs containing serde types derive serde as well.
+        let st = render(&items, "MyNestedStruct");
+        assert!(st.contains(derive), "{st}");
+
+        // Types from `opcua::types` do not implement serde, so structs with
+        // those fields, directly or through another struct, do not derive it.
+        let st = render(&items, "MyTextStruct");
+        assert!(!st.contains("serde"), "{st}");
+        let st = render(&items, "MyTextHolder");
+        assert!(!st.contains("serde"), "{st}");
+
+        // Without the flag, no serde attributes are emitted.
+        let (items, _) = generate_types(&target(), &input).unwrap();
+        assert!(!render(&items, "MyStruct").contains("serde"));
+        assert!(!render(&items, "MyEnum").contains("serde"));
+    }
+
+    /// The types in `tests/serde/generated` are compiled and serialized by the
+    /// `serde` integration test, make sure they match the current output.
+    ///
+    /// This crate has no `serde` feature, so the checked in types have the
+    /// feature gate removed from their serde attributes.
+    #[test]
+    fn serde_test_types_up_to_date() {
+        let input = BinarySchemaInput::parse(SERDE_BSD, "types.bsd").unwrap();
+        let serde_target = TypeCodeGenTarget {
+            derive_serde: true,
+            enums_single_file: true,
+            structs_single_file: true,
+            ..target()
+        };
+        let (items, _) = generate_types(&serde_target, &input).unwrap();
+        let serde_gate =
+            regex::Regex::new(r#"(?s)#\[cfg_attr\(\s*feature = "serde",\s*(?<attr>.*?)\s*\)\]"#)
+                .unwrap();
+
+        let root = std::env::temp_dir().join(format!("opcua-codegen-serde-{}", std::process::id()));
+        let header = make_header("types.bsd", &["use super::opcua;"]);
+        write_to_directory("generated", root.to_str().unwrap(), &header, items).unwrap();
+        for module in ["enums", "structs"] {
+            let path = root.join(format!("generated/{module}.rs"));
+            let generated = std::fs::read_to_string(&path).unwrap();
+            let generated = serde_gate.replace_all(&generated, "#[$attr]").into_owned();
+            std::fs::write(&path, &generated).unwrap();
+            // The checked in files are formatted by `cargo fmt`, so the output must be
+            // formatted with rustfmt to compare them.
+            let status = std::process::Command::new("rustfmt")
+                .args(["--edition", "2021"])
+                .arg(&path)
+                .status()
+                .unwrap_or_else(|e| panic!("Cannot run rustfmt to check {module}.rs: {e}"));
+            assert!(status.success(), "rustfmt failed on {}", path.display());
+            let generated = std::fs::read_to_string(&path).unwrap();
+            let expected_path = format!(
+                "{}/tests/serde/generated/{module}.rs",
+                env!("CARGO_MANIFEST_DIR")
+            );
+            let expected = std::fs::read_to_string(&expected_path).unwrap_or_else(|e| {
+                panic!(
+                    "Cannot read {expected_path}: {e}, create it from {}",
+                    path.display()
+                )
+            });
+            assert!(
+                generated == expected,
+                "tests/serde/generated/{module}.rs is out of date, \
+                replace it with {}",
+                path.display()
+            );
+        }
+        let _ = std::fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn generate_keyword_field_names() {
+        // Each keyword as a PascalCase field name, which codegen converts to snake case.
+        let names: Vec<_> = RUST_KEYWORDS
+            .iter()
+            .filter(|k| **k != "Self")
+            .map(|k| k.to_case(Case::Pascal))
+            .collect();
+        let fields: String = names
+            .iter()
+            .map(|n| format!(r#"<opc:Field Name="{n}" TypeName="opc:Int32" />"#))
+            .collect();
+        let bsd = format!(
+            r#"<opc:TypeDictionary
+  xmlns:opc="http://opcfoundation.org/BinarySchema/"
+  xmlns:ua="http://opcfoundation.org/UA/"
+  DefaultByteOrder="LittleEndian"
+  TargetNamespace="http://test.org/UA/">
+  <opc:Import Namespace="http://opcfoundation.org/UA/" />
+  <opc:StructuredType Name="MyStruct" BaseType="ua:ExtensionObject">{fields}</opc:StructuredType>
+  <opc:EnumeratedType Name="MyEnum" LengthInBits="32">
+    <opc:EnumeratedValue Name="Self" Value="0" />
+    <opc:EnumeratedValue Name="Type" Value="1" />
+  </opc:EnumeratedType>
+</opc:TypeDictionary>"#
+        );
+        let input = BinarySchemaInput::parse(&bsd, "test.bsd").unwrap();
+        let (items, _) = generate_types(&target(), &input).unwrap();
+
+        // The generated items must be valid Rust, which syn checks when parsing.
+        let st = render(&items, "MyStruct");
+        let parsed: ItemStruct = syn::parse_str(&st).unwrap_or_else(|e| panic!("{e}: {st}"));
+        assert_eq!(parsed.fields.len(), names.len());
+        for name in &names {
+            let ident = format!("__{}", name.to_case(Case::Snake));
+            assert!(
+                st.contains(&format!(
+                    r#"# [opcua (rename = "{name}")] pub {ident} : i32"#
+                )),
+                "{st}"
+            );
+        }
+
+        let en = render(&items, "MyEnum");
+        let parsed: ItemEnum = syn::parse_str(&en).unwrap_or_else(|e| panic!("{e}: {en}"));
+        assert_eq!(variant_names(&parsed), vec!["__Self", "Type"]);
+    }
+
+    const ENUM_BSD: &str = r#"<opc:TypeDictionary
+  xmlns:opc="http://opcfoundation.org/BinarySchema/"
+  xmlns:ua="http://opcfoundation.org/UA/"
+  xmlns:tns="http://test.org/UA/"
+  DefaultByteOrder="LittleEndian"
+  TargetNamespace="http://test.org/UA/">
+  <opc:Import Namespace="http://opcfoundation.org/UA/" />
+  <opc:EnumeratedType Name="MyEnum" LengthInBits="32">
+    <opc:EnumeratedValue Name="1Value" Value="0" />
+    <opc:EnumeratedValue Name="Other" Value="1" />
+  </opc:EnumeratedType>
+  <opc:EnumeratedType Name="MyFlags" LengthInBits="32" IsOptionSet="true">
+    <opc:EnumeratedValue Name="None" Value="0" />
+    <opc:EnumeratedValue Name="First" Value="1" />
+    <opc:EnumeratedValue Name="Second" Value="2" />
+  </opc:EnumeratedType>
+</opc:TypeDictionary>"#;
+
+    #[test]
+    fn generate_enum_display() {
+        let input = BinarySchemaInput::parse(ENUM_BSD, "test.bsd").unwrap();
+        let (items, _) = generate_types(&target(), &input).unwrap();
+
+        // Variants renamed by codegen are displayed with their original name.
+        let en = render_impls(&items, "MyEnum");
+        assert!(en.contains("impl std :: fmt :: Display for MyEnum"), "{en}");
+        assert!(en.contains(r#"Self :: __1Value => "1Value""#), "{en}");
+        assert!(en.contains(r#"Self :: Other => "Other""#), "{en}");
+
+        // Bitfields list the names of the flags that are set.
+        let flags = render_impls(&items, "MyFlags");
+        assert!(
+            flags.contains("impl std :: fmt :: Display for MyFlags"),
+            "{flags}"
+        );
+        assert!(
+            flags.contains(r#"[(Self :: None , "None") , (Self :: First , "First") , (Self :: Second , "Second") ,]"#),
+            "{flags}"
+        );
+        // An empty bitfield is displayed with the name of the zero value.
+        assert!(
+            flags.contains(r#"if self . is_empty () { return f . write_str ("None") ; }"#),
+            "{flags}"
+        );
+    }
+
+    const BUILDER_BSD: &str = r#"<opc:TypeDictionary
+  xmlns:opc="http://opcfoundation.org/BinarySchema/"
+  xmlns:ua="http://opcfoundation.org/UA/"
+  xmlns:tns="http://test.org/UA/"
+  DefaultByteOrder="LittleEndian"
+  TargetNamespace="http://test.org/UA/">
+  <opc:Import Namespace="http://opcfoundation.org/UA/" />
+  <opc:StructuredType Name="MyStruct" BaseType="ua:ExtensionObject">
+    <opc:Field Name="Type" TypeName="opc:Int32" />
+    <opc:Field Name="NoOfValues" TypeName="opc:Int32" />
+    <opc:Field Name="Values" TypeName="opc:Int32" LengthField="NoOfValues" />
+  </opc:StructuredType>
+  <opc:StructuredType Name="MyUnion" BaseType="ua:Union">
+    <opc:Field Name="SwitchField" TypeName="opc:UInt32" />
+    <opc:Field Name="Number" TypeName="opc:Int32" SwitchField="SwitchField" SwitchValue="1" />
+  </opc:StructuredType>
+</opc:TypeDictionary>"#;
+
+    fn render_impls(items: &[GeneratedItem], name: &str) -> String {
+        let item = items.iter().find(|i| i.name == name).unwrap();
+        item.impls
+            .iter()
+            .map(|i| i.to_token_stream().to_string())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn generate_builders() {
+        let input = BinarySchemaInput::parse(BUILDER_BSD, "test.bsd").unwrap();
+        let builder_target = TypeCodeGenTarget {
+            generate_builders: true,
+            ..target()
+        };
+        let (items, _) = generate_types(&builder_target, &input).unwrap();
+
+        let st = render_impls(&items, "MyStruct");
+        assert!(st.contains("pub struct MyStructBuilder"), "{st}");
+        assert!(st.contains("pub fn builder () -> MyStructBuilder"), "{st}");
+        assert!(st.contains("pub fn build (self) -> MyStruct"), "{st}");
+        // Renamed fields get a raw identifier setter.
+        assert!(
+            st.contains("pub fn r#type (mut self , value : i32)"),
+            "{st}"
+        );
+        assert!(st.contains("self . inner . __type = value"), "{st}");
+        // Arrays can be set from an iterator, or one item at a time.
+        assert!(
+            st.contains("pub fn values (mut self , items : impl IntoIterator < Item = i32 >)"),
+            "{st}"
+        );
+        assert!(
+            st.contains("pub fn add_values (mut self , item : i32)"),
+            "{st}"
+        );
+
+        // Unions do not get builders.
+        assert!(!render_impls(&items, "MyUnion").contains("Builder"));
+
+        // Without the flag, no builders are generated.
+        let (items, _) = generate_types(&target(), &input).unwrap();
+        assert!(!render_impls(&items, "MyStruct").contains("Builder"));
+    }
+
+    const OPAQUE_BSD: &str = r#"<opc:TypeDictionary
+  xmlns:opc="http://opcfoundation.org/BinarySchema/"
+  xmlns:ua="http://opcfoundation.org/UA/"
+  xmlns:tns="http://test.org/UA/"
+  DefaultByteOrder="LittleEndian"
+  TargetNamespace="http://test.org/UA/">
+  <opc:Import Namespace="http://opcfoundation.org/UA/" />
+  <opc:OpaqueType Name="MyBlob">
+    <opc:Documentation>Raw data.</opc:Documentation>
+  </opc:OpaqueType>
+  <opc:OpaqueType Name="MyCounter" LengthInBits="32" />
+  <opc:OpaqueType Name="MyMapped" LengthInBits="16" />
+  <opc:OpaqueType Name="MyOdd" LengthInBits="24" />
+  <opc:StructuredType Name="MyStruct" BaseType="ua:ExtensionObject">
+    <opc:Field Name="Blob" TypeName="tns:MyBlob" />
+    <opc:Field Name="Counter" TypeName="tns:MyCounter" />
+    <opc:Field Name="Mapped" TypeName="tns:MyMapped" />
+  </opc:StructuredType>
+</opc:TypeDictionary>"#;
+
+    #[test]
+    fn generate_bsd_opaque() {
+        let input = BinarySchemaInput::parse(OPAQUE_BSD, "test.bsd").unwrap();
+        // Opaque types with an unsupported length must be mapped.
+        let err = generate_types(&target(), &input).err().unwrap();
+        assert!(err.to_string().contains("MyOdd"), "{err}");
+
+        let opaque_target = TypeCodeGenTarget {
+            native_type_mappings: [
+                ("MyMapped".to_owned(), "u16".to_owned()),
+                ("MyOdd".to_owned(), "ByteString".to_owned()),
+            ]
+            .into_iter()
+            .collect(),
+            ..target()
+        };
+        let (items, _) = generate_types(&opaque_target, &input).unwrap();
+
+        let blob = render(&items, "MyBlob");
+        assert!(
+            blob.contains("pub struct MyBlob (pub opcua :: types :: ByteString) ;"),
+            "{blob}"
+        );
+        assert!(blob.contains(r#"# [doc = "Raw data."]"#), "{blob}");
+        assert!(render_impls(&items, "MyBlob").contains("impl_encoded_as"));
+
+        let counter = render(&items, "MyCounter");
+        assert!(
+            counter.contains("pub struct MyCounter (pub u32) ;"),
+            "{counter}"
+        );
+
+        // Mapped opaque types are not generated, fields use the mapped type instead.
+        assert!(!items
+            .iter()
+            .any(|i| i.name == "MyMapped" || i.name == "MyOdd"));
+        let st = render(&items, "MyStruct");
+        assert!(st.contains("pub blob : super :: my_blob :: MyBlob"), "{st}");
+        assert!(st.contains("pub mapped : u16"), "{st}");
+    }
+
+    const BASE_NODESET: &str = r#"<UANodeSet xmlns="http://opcfoundation.org/UA/2011/03/UANodeSet.xsd">
+  <Models>
+    <Model ModelUri="http://opcfoundation.org/UA/" Version="1.05" PublicationDate="2023-12-15T00:00:00Z" />
+  </Models>
+  <UADataType NodeId="i=6" BrowseName="Int32">
+    <DisplayName>Int32</DisplayName>
+    <References>
+      <Reference ReferenceType="i=45" IsForward="false">i=27</Reference>
+    </References>
+  </UADataType>
+  <UADataType NodeId="i=22" BrowseName="Structure" IsAbstract="true">
+    <DisplayName>Structure</DisplayName>
+    <References>
+      <Reference ReferenceType="i=45" IsForward="false">i=24</Reference>
+    </References>
+  </UADataType>
+</UANodeSet>"#;
+
+    const TWO_MODEL_NODESET: &str = r#"<UANodeSet xmlns="http://opcfoundation.org/UA/2011/03/UANodeSet.xsd">
+  <NamespaceUris>
+    <Uri>http://test.org/A/</Uri>
+    <Uri>http://test.org/B/</Uri>
+  </NamespaceUris>
+  <Models>
+    <Model ModelUri="http://test.org/A/" Version="1.00" PublicationDate="2023-12-15T00:00:00Z">
+      <RequiredModel ModelUri="http://opcfoundation.org/UA/" />
+    </Model>
+    <Model ModelUri="http://test.org/B/" Version="1.00" PublicationDate="2023-12-15T00:00:00Z">
+      <RequiredModel ModelUri="http://opcfoundation.org/UA/" />
+      <RequiredModel ModelUri="http://test.org/A/" />
+    </Model>
+  </Models>
+  <UADataType NodeId="ns=1;i=1" BrowseName="1:AType">
+    <DisplayName>AType</DisplayName>
+    <References>
+      <Reference ReferenceType="i=45" IsForward="false">i=22</Reference>
+    </References>
+    <Definition Name="1:AType">
+      <Field Name="Value" DataType="i=6" />
+    </Definition>
+  </UADataType>
+  <UAObject NodeId="ns=1;i=2" BrowseName="Default Binary">
+    <DisplayName>Default Binary</DisplayName>
+    <References>
+      <Reference ReferenceType="i=38" IsForward="false">ns=1;i=1</Reference>
+    </References>
+  </UAObject>
+  <UADataType NodeId="ns=2;i=1" BrowseName="2:BType">
+    <DisplayName>BType</DisplayName>
+    <References>
+      <Reference ReferenceType="i=45" IsForward="false">i=22</Reference>
+    </References>
+    <Definition Name="2:BType">
+      <Field Name="Inner" DataType="ns=1;i=1" />
+    </Definition>
+  </UADataType>
+  <UAObject NodeId="ns=2;i=2" BrowseName="Default Binary">
+    <DisplayName>Default Binary</DisplayName>
+    <References>
+      <Reference ReferenceType="i=38" IsForward="false">ns=2;i=1</Reference>
+    </References>
+  </UAObject>
+</UANodeSet>"#;
+
+    #[test]
+    fn generate_nodeset_multiple_models() {
+        let mut cache = SchemaCache::new(".");
+        cache.add_nodeset(
+            NodeSetInput::parse(BASE_NODESET, "base.xml", None).unwrap(),
+            "base.xml",
+        );
+        let input = NodeSetInput::parse(TWO_MODEL_NODESET, "test.xml", None).unwrap();
+        assert_eq!(
+            input.model_namespace_index("http://test.org/A/").unwrap(),
+            1
+        );
+        assert_eq!(
+            input.model_namespace_index("http://test.org/B/").unwrap(),
+            2
+        );
+        // Dependencies between models in the same nodeset are not external.
+        assert_eq!(
+            input.required_model_uris,
+            vec!["http://opcfoundation.org/UA/".to_owned()]
+        );
+        cache.add_nodeset(input, "test.xml");
+        // The nodeset can be found from either model.
+        let input = cache.get_nodeset("http://test.org/B/").unwrap();
+
+        let models = generate_types_nodeset(&target(), input, &cache, "en").unwrap();
+        assert_eq!(models.len(), 2);
+
+        let (a, b) = (&models[0], &models[1]);
+        assert_eq!(a.module.as_deref(), Some("a"));
+        assert_eq!(a.target_namespace, "http://test.org/A/");
+        assert_eq!(b.module.as_deref(), Some("b"));
+        assert_eq!(b.target_namespace, "http://test.org/B/");
+
+        let names = |m: &ModelTypes| m.types.iter().map(|t| t.name.clone()).collect::<Vec<_>>();
+        assert_eq!(names(a), vec!["AType"]);
+        assert_eq!(names(b), vec!["BType"]);
+
+        // Types from other models are referenced from their own module.
+        let st = render(&b.types, "BType");
+        assert!(
+            st.contains("pub inner : super :: super :: a :: AType"),
+            "{st}"
+        );
+        assert!(st.contains("derive (Default)"), "{st}");
+        // Encoding IDs use the namespace of the model defining the type.
+        let impls = render_impls(&b.types, "BType");
+        assert!(impls.contains(r#""http://test.org/B/""#), "{impls}");
+        let impls = render_impls(&a.types, "AType");
+        assert!(impls.contains(r#""http://test.org/A/""#), "{impls}");
+
+        // Module names can be configured.
+        let renamed = TypeCodeGenTarget {
+            model_modules: [("http://test.org/A/".to_owned(), "model_a".to_owned())]
+                .into_iter()
+                .collect(),
+            ..target()
+        };
+        let models = generate_types_nodeset(&renamed, input, &cache, "en").unwrap();
+        assert_eq!(models[0].module.as_deref(), Some("model_a"));
+        let st = render(&models[1].types, "BType");
+        assert!(st.contains("super :: super :: model_a :: AType"), "{st}");
+    }
+
+    const MISSING_TYPE_NODESET: &str = r#"<UANodeSet xmlns="http://opcfoundation.org/UA/2011/03/UANodeSet.xsd">
+  <NamespaceUris>
+    <Uri>http://test.org/A/</Uri>
+  </NamespaceUris>
+  <Models>
+    <Model ModelUri="http://test.org/A/" Version="1.00" PublicationDate="2023-12-15T00:00:00Z">
+      <RequiredModel ModelUri="http://opcfoundation.org/UA/" />
+    </Model>
+  </Models>
+  <UADataType NodeId="ns=1;i=1" BrowseName="1:AType">
+    <DisplayName>AType</DisplayName>
+    <References>
+      <Reference ReferenceType="i=45" IsForward="false">i=22</Reference>
+    </References>
+    <Definition Name="1:AType">
+      <Field Name="Value" DataType="i=6" />
+      <Field Name="Broken" DataType="ns=1;i=15" />
+    </Definition>
+  </UADataType>
+</UANodeSet>"#;
+
+    #[test]
+    fn generate_nodeset_missing_field_type() {
+        let mut cache = SchemaCache::new(".");
+        cache.add_nodeset(
+            NodeSetInput::parse(BASE_NODESET, "base.xml", None).unwrap(),
+            "base.xml",
+        );
+        cache.add_nodeset(
+            NodeSetInput::parse(MISSING_TYPE_NODESET, "test.xml", None).unwrap(),
+            "test.xml",
+        );
+        let input = cache.get_nodeset("http://test.org/A/").unwrap();
+
+        let err = generate_types_nodeset(&target(), input, &cache, "en")
+            .err()
+            .unwrap();
+        assert_eq!(
+            err.context_stack().collect::<Vec<_>>(),
+            vec![
+                "resolving AType.Broken -> ns=1;i=15 (unresolved)",
+                "loading data type ns=1;i=1",
+            ]
+        );
+        assert_eq!(err.context.as_deref(), Some("loading data type ns=1;i=1"));
+        assert!(
+            err.to_string().contains(
+                "Did not find type name for data type ns=1;i=15, \
+                while resolving AType.Broken -> ns=1;i=15 (unresolved), \
+                while loading data type ns=1;i=1"
+            ),
+            "{err}"
+        );
+
+        // Errors other than failed lookups are not marked as unresolved.
+        let mut cache = SchemaCache::new(".");
+        cache.add_nodeset(
+            NodeSetInput::parse(BASE_NODESET, "base.xml", None).unwrap(),
+            "base.xml",
+        );
+        cache.add_nodeset(
+            NodeSetInput::parse(
+                &MISSING_TYPE_NODESET.replace("ns=1;i=15", "NotANodeId"),
+                "test.xml",
+                None,
+            )
+            .unwrap(),
+            "test.xml",
+        );
+        let input = cache.get_nodeset("http://test.org/A/").unwrap();
+        let err = generate_types_nodeset(&target(), input, &cache, "en")
+            .err()
+            .unwrap();
+        assert_eq!(
+            err.context_stack().collect::<Vec<_>>(),
+            vec![
+                "resolving AType.Broken -> NotANodeId",
+                "loading data type ns=1;i=1",
+            ]
+        );
+    }
+
+    #[test]
+    fn generate_bsd_union_array_variant() {
+        let bsd = UNION_BSD.replace(
+            r#"<opc:Field Name="Flag" TypeName="opc:Boolean" SwitchField="SwitchField" SwitchValue="3" />"#,
+            r#"<opc:Field Name="NoOfNumbers" TypeName="opc:Int32" SwitchField="SwitchField" SwitchValue="3" />
+    <opc:Field Name="Numbers" TypeName="opc:Int32" LengthField="NoOfNumbers" SwitchField="SwitchField" SwitchValue="3" />
+    <opc:Field Name="Flag" TypeName="opc:Boolean" SwitchField="SwitchField" SwitchValue="4" />"#,
+        );
+        let input = BinarySchemaInput::parse(&bsd, "test.bsd").unwrap();
+        let (items, _) = generate_types(&target(), &input).unwrap();
+
+        let un = items.iter().find(|i| i.name == "MyUnion").unwrap();
+        let ItemDefinition::Enum(en) = &un.item else {
+            panic!("Expected union to be generated as an enum");
+        };
+        // The length field is not a variant of its own.
+        assert_eq!(
+            variant_names(en),
+            vec!["Null", "Number", "Text", "Numbers", "Flag"]
+        );
+        let numbers = en.variants.iter().find(|v| v.ident == "Numbers").unwrap();
+        let numbers = numbers.fields.to_token_stream().to_string();
+        assert!(numbers.contains("Vec < i32 >"), "{numbers}");
+
+        // Two variants cannot share a switch value.
+        let bsd = bsd.replace("SwitchValue=\"4\"", "SwitchValue=\"3\"");
+        let input = BinarySchemaInput::parse(&bsd, "test.bsd").unwrap();
+        let err = generate_types(&target(), &input).err().unwrap();
+        assert!(err.to_string().contains("expected 4"), "{err}");
+    }
+
+    #[test]
+    fn generate_bsd_union_invalid_switch_values() {
+        let bsd = UNION_BSD.replace("SwitchValue=\"3\"", "SwitchValue=\"5\"");
+        let input = BinarySchemaInput::parse(&bsd, "test.bsd").unwrap();
+        let err = generate_types(&target(), &input).err().unwrap();
+        assert!(err.to_string().contains("expected 3"), "{err}");
+    }
+}
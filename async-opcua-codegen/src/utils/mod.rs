@@ -41,11 +41,24 @@ pub trait GeneratedOutput {
     fn name(&self) -> &str;
 }
 
+/// Strict and reserved keywords in Rust, which cannot be used as identifiers.
+/// `self`, `Self`, `super` and `crate` cannot be raw identifiers either,
+/// so keywords are prefixed instead.
+pub(crate) const RUST_KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "crate",
+    "do", "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl",
+    "in", "let", "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref",
+    "return", "self", "Self", "static", "struct", "super", "trait", "true", "try", "type",
+    "typeof", "unsafe", "unsized", "use", "virtual", "where", "while", "yield",
+];
+
+/// Create an identifier from `val`, prefixing it with `__` if it is not a valid
+/// Rust identifier. Returns `true` if the name was changed.
 pub fn safe_ident(val: &str) -> (Ident, bool) {
     let mut val = val.to_string();
     let mut changed = false;
     if val.starts_with(['0', '1', '2', '3', '4', '5', '6', '7', '8', '9'])
-        || val == "type"
+        || RUST_KEYWORDS.contains(&val.as_str())
         || val.contains(['/'])
     {
         val = format!("__{}", val.replace(['/'], "_"));
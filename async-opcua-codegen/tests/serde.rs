@@ -0,0 +1,92 @@
+//! Serialize and deserialize types generated with `derive_serde`.
+//!
+//! The generated types in `serde/generated` are checked against the current
+//! code generator output by the `serde_test_types_up_to_date` unit test, with
+//! the `serde` feature gate removed from their attributes.
+
+// Generated code refers to the `json` and `xml` features, which this crate does not have.
+#![allow(unexpected_cfgs)]
+
+use opcua_types::NodeId;
+
+mod opcua {
+    pub use opcua_types as types;
+}
+
+#[path = "serde/generated/enums.rs"]
+mod enums;
+#[path = "serde/generated/structs.rs"]
+mod structs;
+
+#[allow(non_camel_case_types, clippy::enum_variant_names)]
+#[derive(Clone, Copy)]
+enum ObjectId {
+    MyStruct_Encoding_DefaultBinary = 1,
+    MyStruct_Encoding_DefaultJson,
+    MyStruct_Encoding_DefaultXml,
+    MyNestedStruct_Encoding_DefaultBinary,
+    MyNestedStruct_Encoding_DefaultJson,
+    MyNestedStruct_Encoding_DefaultXml,
+    MyTextStruct_Encoding_DefaultBinary,
+    MyTextStruct_Encoding_DefaultJson,
+    MyTextStruct_Encoding_DefaultXml,
+    MyTextHolder_Encoding_DefaultBinary,
+    MyTextHolder_Encoding_DefaultJson,
+    MyTextHolder_Encoding_DefaultXml,
+}
+
+#[allow(clippy::enum_variant_names)]
+#[derive(Clone, Copy)]
+enum DataTypeId {
+    MyStruct = 100,
+    MyNestedStruct,
+    MyTextStruct,
+    MyTextHolder,
+}
+
+impl From<ObjectId> for NodeId {
+    fn from(value: ObjectId) -> Self {
+        NodeId::new(1, value as u32)
+    }
+}
+
+impl From<DataTypeId> for NodeId {
+    fn from(value: DataTypeId) -> Self {
+        NodeId::new(1, value as u32)
+    }
+}
+
+#[test]
+fn serde_round_trip_generated_struct() {
+    let value = structs::MyNestedStruct {
+        items: Some(vec![
+            structs::MyStruct {
+                __type: 1,
+                number: 2,
+                values: Some(vec![1.5, -2.0]),
+                kind: enums::MyEnum::__1Value,
+            },
+            structs::MyStruct {
+                __type: 3,
+                number: 4,
+                values: None,
+                kind: enums::MyEnum::Other,
+            },
+        ]),
+    };
+
+    let json = serde_json::to_value(&value).unwrap();
+    // Renamed fields and variants use their names from the schema.
+    assert_eq!(
+        json,
+        serde_json::json!({
+            "items": [
+                { "Type": 1, "number": 2, "values": [1.5, -2.0], "kind": "1Value" },
+                { "Type": 3, "number": 4, "values": null, "kind": "Other" },
+            ]
+        })
+    );
+
+    let decoded: structs::MyNestedStruct = serde_json::from_value(json).unwrap();
+    assert_eq!(decoded, value);
+}
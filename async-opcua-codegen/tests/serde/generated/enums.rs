@@ -0,0 +1,23 @@
+// This file was autogenerated from types.bsd by async-opcua-codegen
+//
+// DO NOT EDIT THIS FILE
+
+use super::opcua;
+#[opcua::types::ua_encodable]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[repr(i32)]
+pub enum MyEnum {
+    #[opcua(default)]
+    #[opcua(rename = "1Value")]
+    #[serde(rename = "1Value")]
+    __1Value = 0i32,
+    Other = 1i32,
+}
+impl std::fmt::Display for MyEnum {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::__1Value => "1Value",
+            Self::Other => "Other",
+        })
+    }
+}
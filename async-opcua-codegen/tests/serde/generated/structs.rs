@@ -0,0 +1,105 @@
+// This file was autogenerated from types.bsd by async-opcua-codegen
+//
+// DO NOT EDIT THIS FILE
+
+use super::opcua;
+#[opcua::types::ua_encodable]
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, Default)]
+pub struct MyNestedStruct {
+    pub items: Option<Vec<super::structs::MyStruct>>,
+}
+impl opcua::types::ExpandedMessageInfo for MyNestedStruct {
+    fn full_type_id(&self) -> opcua::types::ExpandedNodeId {
+        let id: opcua::types::NodeId =
+            crate::ObjectId::MyNestedStruct_Encoding_DefaultBinary.into();
+        opcua::types::ExpandedNodeId::from((id, "http://test.org/UA/"))
+    }
+    fn full_json_type_id(&self) -> opcua::types::ExpandedNodeId {
+        let id: opcua::types::NodeId = crate::ObjectId::MyNestedStruct_Encoding_DefaultJson.into();
+        opcua::types::ExpandedNodeId::from((id, "http://test.org/UA/"))
+    }
+    fn full_xml_type_id(&self) -> opcua::types::ExpandedNodeId {
+        let id: opcua::types::NodeId = crate::ObjectId::MyNestedStruct_Encoding_DefaultXml.into();
+        opcua::types::ExpandedNodeId::from((id, "http://test.org/UA/"))
+    }
+    fn full_data_type_id(&self) -> opcua::types::ExpandedNodeId {
+        let id: opcua::types::NodeId = crate::DataTypeId::MyNestedStruct.into();
+        opcua::types::ExpandedNodeId::from((id, "http://test.org/UA/"))
+    }
+}
+#[opcua::types::ua_encodable]
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, Default)]
+pub struct MyStruct {
+    #[opcua(rename = "Type")]
+    #[serde(rename = "Type")]
+    pub __type: i32,
+    pub number: i32,
+    pub values: Option<Vec<f64>>,
+    pub kind: super::enums::MyEnum,
+}
+impl opcua::types::ExpandedMessageInfo for MyStruct {
+    fn full_type_id(&self) -> opcua::types::ExpandedNodeId {
+        let id: opcua::types::NodeId = crate::ObjectId::MyStruct_Encoding_DefaultBinary.into();
+        opcua::types::ExpandedNodeId::from((id, "http://test.org/UA/"))
+    }
+    fn full_json_type_id(&self) -> opcua::types::ExpandedNodeId {
+        let id: opcua::types::NodeId = crate::ObjectId::MyStruct_Encoding_DefaultJson.into();
+        opcua::types::ExpandedNodeId::from((id, "http://test.org/UA/"))
+    }
+    fn full_xml_type_id(&self) -> opcua::types::ExpandedNodeId {
+        let id: opcua::types::NodeId = crate::ObjectId::MyStruct_Encoding_DefaultXml.into();
+        opcua::types::ExpandedNodeId::from((id, "http://test.org/UA/"))
+    }
+    fn full_data_type_id(&self) -> opcua::types::ExpandedNodeId {
+        let id: opcua::types::NodeId = crate::DataTypeId::MyStruct.into();
+        opcua::types::ExpandedNodeId::from((id, "http://test.org/UA/"))
+    }
+}
+#[opcua::types::ua_encodable]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct MyTextHolder {
+    pub inner: super::structs::MyTextStruct,
+}
+impl opcua::types::ExpandedMessageInfo for MyTextHolder {
+    fn full_type_id(&self) -> opcua::types::ExpandedNodeId {
+        let id: opcua::types::NodeId = crate::ObjectId::MyTextHolder_Encoding_DefaultBinary.into();
+        opcua::types::ExpandedNodeId::from((id, "http://test.org/UA/"))
+    }
+    fn full_json_type_id(&self) -> opcua::types::ExpandedNodeId {
+        let id: opcua::types::NodeId = crate::ObjectId::MyTextHolder_Encoding_DefaultJson.into();
+        opcua::types::ExpandedNodeId::from((id, "http://test.org/UA/"))
+    }
+    fn full_xml_type_id(&self) -> opcua::types::ExpandedNodeId {
+        let id: opcua::types::NodeId = crate::ObjectId::MyTextHolder_Encoding_DefaultXml.into();
+        opcua::types::ExpandedNodeId::from((id, "http://test.org/UA/"))
+    }
+    fn full_data_type_id(&self) -> opcua::types::ExpandedNodeId {
+        let id: opcua::types::NodeId = crate::DataTypeId::MyTextHolder.into();
+        opcua::types::ExpandedNodeId::from((id, "http://test.org/UA/"))
+    }
+}
+#[opcua::types::ua_encodable]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct MyTextStruct {
+    #[opcua(rename = "Type")]
+    pub __type: i32,
+    pub text: opcua::types::string::UAString,
+}
+impl opcua::types::ExpandedMessageInfo for MyTextStruct {
+    fn full_type_id(&self) -> opcua::types::ExpandedNodeId {
+        let id: opcua::types::NodeId = crate::ObjectId::MyTextStruct_Encoding_DefaultBinary.into();
+        opcua::types::ExpandedNodeId::from((id, "http://test.org/UA/"))
+    }
+    fn full_json_type_id(&self) -> opcua::types::ExpandedNodeId {
+        let id: opcua::types::NodeId = crate::ObjectId::MyTextStruct_Encoding_DefaultJson.into();
+        opcua::types::ExpandedNodeId::from((id, "http://test.org/UA/"))
+    }
+    fn full_xml_type_id(&self) -> opcua::types::ExpandedNodeId {
+        let id: opcua::types::NodeId = crate::ObjectId::MyTextStruct_Encoding_DefaultXml.into();
+        opcua::types::ExpandedNodeId::from((id, "http://test.org/UA/"))
+    }
+    fn full_data_type_id(&self) -> opcua::types::ExpandedNodeId {
+        let id: opcua::types::NodeId = crate::DataTypeId::MyTextStruct.into();
+        opcua::types::ExpandedNodeId::from((id, "http://test.org/UA/"))
+    }
+}
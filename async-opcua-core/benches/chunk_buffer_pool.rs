@@ -0,0 +1,172 @@
+//! Benchmark of chunk buffer pooling under a sustained notification workload.
+//!
+//! Encodes and decodes a stream of publish responses carrying data change
+//! notifications, once without a buffer pool and once with one, and reports the
+//! number of heap allocations and the time taken for each run.
+//!
+//! Run with `cargo bench -p async-opcua-core --bench chunk_buffer_pool`.
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    path::Path,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use bytes::BytesMut;
+use opcua_core::{
+    comms::{
+        buffer::SendBuffer,
+        buffer_pool::BufferPool,
+        chunker::Chunker,
+        message_chunk::MessageIsFinalType,
+        secure_channel::{Role, SecureChannel},
+        tcp_codec::{Message, TcpCodec},
+    },
+    sync::RwLock,
+    ResponseMessage,
+};
+use opcua_crypto::CertificateStore;
+use opcua_types::{
+    DataChangeNotification, DataValue, DateTime, DecodingOptions, ExtensionObject,
+    MonitoredItemNotification, NotificationMessage, PublishResponse, ResponseHeader,
+};
+use tokio_util::codec::Decoder;
+
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc_zeroed(layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+const MESSAGE_COUNT: u32 = 10_000;
+const ITEMS_PER_NOTIFICATION: usize = 200;
+const BUFFER_SIZE: usize = 8192;
+
+fn publish_response(sequence_number: u32) -> ResponseMessage {
+    let monitored_items = (0..ITEMS_PER_NOTIFICATION)
+        .map(|i| MonitoredItemNotification {
+            client_handle: i as u32,
+            value: DataValue::new_now(sequence_number as f64 + i as f64),
+        })
+        .collect();
+    PublishResponse {
+        response_header: ResponseHeader::new_good(sequence_number),
+        subscription_id: 1,
+        available_sequence_numbers: Some(vec![sequence_number]),
+        more_notifications: false,
+        notification_message: NotificationMessage {
+            sequence_number,
+            publish_time: DateTime::now(),
+            notification_data: Some(vec![ExtensionObject::new(DataChangeNotification {
+                monitored_items: Some(monitored_items),
+                diagnostic_infos: None,
+            })]),
+        },
+        results: None,
+        diagnostic_infos: None,
+    }
+    .into()
+}
+
+struct RunResult {
+    allocations: u64,
+    elapsed: Duration,
+}
+
+async fn run(pool: BufferPool, messages: &[ResponseMessage]) -> RunResult {
+    let mut channel = SecureChannel::new(
+        Arc::new(RwLock::new(CertificateStore::new(Path::new("./pki")))),
+        Role::Server,
+        Default::default(),
+    );
+    let mut send_buffer = SendBuffer::new(BUFFER_SIZE, 0, 0, true);
+    send_buffer.set_buffer_pool(pool.clone());
+    let mut codec = TcpCodec::new(DecodingOptions::default());
+    codec.set_buffer_pool(pool.clone());
+
+    let mut wire = Vec::with_capacity(BUFFER_SIZE * 2);
+    let mut incoming = BytesMut::with_capacity(BUFFER_SIZE * 4);
+    let mut chunks = Vec::new();
+
+    let start_allocations = ALLOCATIONS.load(Ordering::Relaxed);
+    let start = Instant::now();
+    for (idx, message) in messages.iter().enumerate() {
+        send_buffer
+            .write(idx as u32 + 1, message.clone(), &channel)
+            .unwrap();
+        while send_buffer.should_encode_chunks() {
+            send_buffer.encode_next_chunk(&channel).unwrap();
+            while send_buffer.can_read() {
+                send_buffer.read_into_async(&mut wire).await.unwrap();
+            }
+            incoming.extend_from_slice(&wire);
+            wire.clear();
+
+            while let Some(Message::Chunk(raw)) = codec.decode(&mut incoming).unwrap() {
+                let chunk = channel.verify_and_remove_security(&raw.data).unwrap();
+                pool.put(raw.data);
+                let header = chunk.message_header(&channel.decoding_options()).unwrap();
+                let is_final = header.is_final != MessageIsFinalType::Intermediate;
+                chunks.push(chunk);
+                if is_final {
+                    let _: ResponseMessage = Chunker::decode(&chunks, &channel, None).unwrap();
+                    for chunk in chunks.drain(..) {
+                        pool.put(chunk.data);
+                    }
+                }
+            }
+        }
+    }
+    RunResult {
+        allocations: ALLOCATIONS.load(Ordering::Relaxed) - start_allocations,
+        elapsed: start.elapsed(),
+    }
+}
+
+fn main() {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    let messages: Vec<_> = (1..=MESSAGE_COUNT).map(publish_response).collect();
+
+    let unpooled = runtime.block_on(run(BufferPool::disabled(), &messages));
+    let pool = BufferPool::new(16, BUFFER_SIZE + 1024);
+    let pooled = runtime.block_on(run(pool.clone(), &messages));
+
+    println!("{MESSAGE_COUNT} publish responses, {ITEMS_PER_NOTIFICATION} notifications each");
+    for (name, result) in [("unpooled", &unpooled), ("pooled", &pooled)] {
+        println!(
+            "{name:>10}: {:>10} allocations, {:>8.1} allocations/message, {:?}",
+            result.allocations,
+            result.allocations as f64 / MESSAGE_COUNT as f64,
+            result.elapsed
+        );
+    }
+    println!("pool: {:?}", pool.stats());
+}
@@ -10,7 +10,10 @@ use tokio::io::AsyncWriteExt;
 use tracing::trace;
 
 use crate::{
-    comms::{chunker::Chunker, message_chunk::MessageChunk, secure_channel::SecureChannel},
+    comms::{
+        buffer_pool::BufferPool, chunker::Chunker, message_chunk::MessageChunk,
+        secure_channel::SecureChannel,
+    },
     Message,
 };
 
@@ -50,6 +53,8 @@ pub struct SendBuffer {
     pub max_chunk_count: usize,
     /// Maximum size of each individual chunk.
     pub send_buffer_size: usize,
+    /// Pool of chunk buffers, chunks are returned to the pool once they are encoded.
+    buffer_pool: BufferPool,
 
     state: SendBufferState,
 }
@@ -75,6 +80,7 @@ impl SendBuffer {
             max_message_size,
             max_chunk_count,
             send_buffer_size: buffer_size,
+            buffer_pool: BufferPool::disabled(),
             state: SendBufferState::Writing,
         }
     }
@@ -90,7 +96,11 @@ impl SendBuffer {
         };
 
         let size = match next_chunk {
-            PendingPayload::Chunk(c) => secure_channel.apply_security(&c, self.buffer.get_mut())?,
+            PendingPayload::Chunk(c) => {
+                let size = secure_channel.apply_security(&c, self.buffer.get_mut())?;
+                self.buffer_pool.put(c.data);
+                size
+            }
             PendingPayload::Ack(a) => {
                 a.encode(&mut self.buffer)?;
                 self.buffer.position() as usize
@@ -106,6 +116,17 @@ impl SendBuffer {
         Ok(())
     }
 
+    /// Set the pool used for chunk buffers. By default the send buffer does not
+    /// recycle chunk buffers.
+    pub fn set_buffer_pool(&mut self, buffer_pool: BufferPool) {
+        self.buffer_pool = buffer_pool;
+    }
+
+    /// Get the pool used for chunk buffers.
+    pub fn buffer_pool(&self) -> &BufferPool {
+        &self.buffer_pool
+    }
+
     /// Set whether we are using legacy sequence numbers or not.
     /// This depends on the active security policy.
     pub fn set_sequence_number_legacy(&mut self, is_legacy: bool) {
@@ -137,19 +158,25 @@ impl SendBuffer {
         trace!("Writing request to buffer");
 
         // Turn message to chunk(s)
-        let chunks = Chunker::encode(
+        let chunks = Chunker::encode_pooled(
             self.sequence_numbers.clone(),
             request_id,
             self.max_message_size,
             self.send_buffer_size,
             secure_channel,
             &message,
+            &self.buffer_pool,
         )
         .map_err(|e| e.with_context(Some(request_id), Some(message.request_handle())))?;
 
         if self.max_chunk_count > 0 && chunks.len() > self.max_chunk_count {
+            // Client stack should report a BadRequestTooLarge, server BadResponseTooLarge
             Err(Error::new(
-                StatusCode::BadCommunicationError,
+                if secure_channel.is_client_role() {
+                    StatusCode::BadRequestTooLarge
+                } else {
+                    StatusCode::BadResponseTooLarge
+                },
                 format!(
                     "Cannot write message since {} chunks exceeds {} chunk limit",
                     chunks.len(),
@@ -246,6 +273,7 @@ mod tests {
 
     use super::SendBuffer;
 
+    use crate::comms::buffer_pool::BufferPool;
     use crate::comms::secure_channel::{Role, SecureChannel};
     use crate::RequestMessage;
     use opcua_crypto::CertificateStore;
@@ -337,6 +365,43 @@ mod tests {
         assert!(cursor.get_ref().len() > 8196 * 2 && cursor.get_ref().len() < 8196 * 3);
     }
 
+    #[tokio::test]
+    async fn test_buffer_pooled() {
+        let (mut buffer, channel) = get_buffer_and_channel();
+        let pool = BufferPool::new(10, 8196 + 1024);
+        buffer.set_buffer_pool(pool.clone());
+
+        let mut cursor = Cursor::new(Vec::new());
+        for i in 0..10 {
+            let message = ReadRequest {
+                request_header: RequestHeader::new(&NodeId::null(), &DateTime::null(), 101 + i),
+                max_age: 0.0,
+                timestamps_to_return: TimestampsToReturn::Both,
+                nodes_to_read: Some(
+                    (0..1000)
+                        .map(|r| ReadValueId {
+                            node_id: (1, r).into(),
+                            attribute_id: 1,
+                            ..Default::default()
+                        })
+                        .collect(),
+                ),
+            };
+            let m: RequestMessage = message.into();
+            buffer.write(i, m, &channel).unwrap();
+            while buffer.should_encode_chunks() {
+                buffer.encode_next_chunk(&channel).unwrap();
+                buffer.read_into_async(&mut cursor).await.unwrap();
+            }
+        }
+
+        // After the first message, chunk buffers are taken from the pool.
+        let stats = pool.stats();
+        assert!(stats.allocated <= 6, "{stats:?}");
+        assert!(stats.reused >= 50, "{stats:?}");
+        assert!(stats.pooled > 0);
+    }
+
     #[test]
     fn test_buffer_too_large_message() {
         // Write a very large message exceeding the max message size.
@@ -384,7 +449,7 @@ mod tests {
 
         let m: RequestMessage = message.into();
         let err = buffer.write(1, m, &channel).unwrap_err();
-        assert_eq!(err.status(), StatusCode::BadCommunicationError);
+        assert_eq!(err.status(), StatusCode::BadRequestTooLarge);
     }
 
     #[tokio::test]
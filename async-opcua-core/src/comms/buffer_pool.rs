@@ -0,0 +1,254 @@
+// OPCUA for Rust
+// SPDX-License-Identifier: MPL-2.0
+// Copyright (C) 2017-2024 Adam Lock
+
+//! A bounded pool of byte buffers, used to recycle chunk buffers
+//! when encoding and decoding messages.
+
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use parking_lot::Mutex;
+
+/// Time after which a buffer that has not been taken from the pool is freed.
+const IDLE_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug)]
+struct PooledBuffer {
+    buf: Vec<u8>,
+    returned_at: Instant,
+}
+
+#[derive(Debug)]
+struct BufferPoolInner {
+    buffers: Mutex<Vec<PooledBuffer>>,
+    max_buffers: usize,
+    max_buffer_size: usize,
+    allocated: AtomicU64,
+    reused: AtomicU64,
+}
+
+/// Statistics about a [`BufferPool`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BufferPoolStats {
+    /// Number of buffers that were allocated or grown because no large enough
+    /// buffer was available in the pool.
+    pub allocated: u64,
+    /// Number of buffers that were taken from the pool without allocating.
+    pub reused: u64,
+    /// Number of buffers currently held by the pool.
+    pub pooled: usize,
+}
+
+/// A pool of byte buffers used for message chunks.
+///
+/// Buffers are taken from the pool when chunks are encoded or decoded, and returned
+/// once the chunk has been written to the stream or turned into a message.
+/// The pool holds at most `max_buffers` buffers, each of at most `max_buffer_size`
+/// bytes, any other buffers are freed when returned. Buffers that stay in the pool
+/// for more than 30 seconds are freed by [`BufferPool::trim`], which is also called
+/// whenever a buffer is returned, so the pool shrinks again after a burst of traffic.
+///
+/// The pool is cheap to clone, and clones share the same buffers, so a single pool
+/// can be shared between connections. Buffers are only held by the pool between uses,
+/// so idle connections do not keep any memory alive.
+///
+/// The default pool is disabled, and always allocates new buffers.
+#[derive(Debug, Clone, Default)]
+pub struct BufferPool {
+    inner: Option<Arc<BufferPoolInner>>,
+}
+
+impl BufferPool {
+    /// Create a new buffer pool holding at most `max_buffers` buffers of at most
+    /// `max_buffer_size` bytes. If `max_buffers` is zero, the pool is disabled.
+    pub fn new(max_buffers: usize, max_buffer_size: usize) -> Self {
+        if max_buffers == 0 {
+            return Self::disabled();
+        }
+        Self {
+            inner: Some(Arc::new(BufferPoolInner {
+                buffers: Mutex::new(Vec::with_capacity(max_buffers)),
+                max_buffers,
+                max_buffer_size,
+                allocated: AtomicU64::new(0),
+                reused: AtomicU64::new(0),
+            })),
+        }
+    }
+
+    /// Create a disabled buffer pool, which always allocates new buffers.
+    pub fn disabled() -> Self {
+        Self { inner: None }
+    }
+
+    /// Return `true` if this pool recycles buffers.
+    pub fn is_enabled(&self) -> bool {
+        self.inner.is_some()
+    }
+
+    /// Get a zeroed buffer of length `len`, reusing a buffer from the pool if one is available.
+    pub fn get(&self, len: usize) -> Vec<u8> {
+        let Some(inner) = &self.inner else {
+            return vec![0u8; len];
+        };
+        let buf = {
+            let mut buffers = inner.buffers.lock();
+            // Prefer a buffer that is large enough, buffers for the last chunk
+            // of a message are often smaller than the chunk size.
+            // Buffers are taken from the end, so that the least recently returned
+            // buffers stay at the start of the pool and are freed once idle.
+            match buffers.iter().rposition(|b| b.buf.capacity() >= len) {
+                Some(idx) => Some(buffers.remove(idx).buf),
+                None => buffers.pop().map(|b| b.buf),
+            }
+        };
+        let Some(mut buf) = buf else {
+            inner.allocated.fetch_add(1, Ordering::Relaxed);
+            // Allocate the full buffer size, so that the buffer can be reused
+            // for any chunk once it is returned.
+            let mut buf = Vec::with_capacity(len.max(inner.max_buffer_size));
+            buf.resize(len, 0);
+            return buf;
+        };
+        // A buffer that is too small is grown, which is an allocation.
+        if buf.capacity() < len {
+            inner.allocated.fetch_add(1, Ordering::Relaxed);
+        } else {
+            inner.reused.fetch_add(1, Ordering::Relaxed);
+        }
+        buf.clear();
+        buf.resize(len, 0);
+        buf
+    }
+
+    /// Return a buffer to the pool. The buffer is freed if the pool is full,
+    /// or the buffer is larger than the maximum buffer size.
+    pub fn put(&self, buf: Vec<u8>) {
+        let Some(inner) = &self.inner else {
+            return;
+        };
+        if buf.capacity() == 0 || buf.capacity() > inner.max_buffer_size {
+            return;
+        }
+        let now = Instant::now();
+        let mut buffers = inner.buffers.lock();
+        Self::trim_buffers(&mut buffers, now);
+        if buffers.len() < inner.max_buffers {
+            buffers.push(PooledBuffer {
+                buf,
+                returned_at: now,
+            });
+        }
+    }
+
+    /// Free buffers that have not been taken from the pool for 30 seconds.
+    ///
+    /// This is called when buffers are returned, call it periodically as well
+    /// to free the buffers of a pool that is no longer used.
+    pub fn trim(&self) {
+        if let Some(inner) = &self.inner {
+            Self::trim_buffers(&mut inner.buffers.lock(), Instant::now());
+        }
+    }
+
+    fn trim_buffers(buffers: &mut Vec<PooledBuffer>, now: Instant) {
+        // Buffers are ordered by the time they were returned.
+        let idle = buffers
+            .iter()
+            .take_while(|b| now.saturating_duration_since(b.returned_at) > IDLE_TIMEOUT)
+            .count();
+        buffers.drain(..idle);
+    }
+
+    /// Free all buffers currently held by the pool.
+    pub fn clear(&self) {
+        if let Some(inner) = &self.inner {
+            inner.buffers.lock().clear();
+        }
+    }
+
+    /// Get statistics about the buffers allocated and reused by this pool.
+    pub fn stats(&self) -> BufferPoolStats {
+        let Some(inner) = &self.inner else {
+            return BufferPoolStats::default();
+        };
+        BufferPoolStats {
+            allocated: inner.allocated.load(Ordering::Relaxed),
+            reused: inner.reused.load(Ordering::Relaxed),
+            pooled: inner.buffers.lock().len(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::{BufferPool, BufferPoolStats, IDLE_TIMEOUT};
+
+    #[test]
+    fn buffer_pool_reuse() {
+        let pool = BufferPool::new(2, 100);
+        let mut buf = pool.get(50);
+        assert_eq!(buf.len(), 50);
+        buf[0] = 1;
+        pool.put(buf);
+
+        let buf = pool.get(80);
+        assert_eq!(buf.len(), 80);
+        assert!(buf.iter().all(|b| *b == 0));
+        pool.put(buf);
+        assert_eq!(
+            pool.stats(),
+            BufferPoolStats {
+                allocated: 1,
+                reused: 1,
+                pooled: 1
+            }
+        );
+
+        // The pool is bounded, and does not keep oversized buffers.
+        pool.put(vec![0; 200]);
+        pool.put(vec![0; 10]);
+        pool.put(vec![0; 10]);
+        assert_eq!(pool.stats().pooled, 2);
+        pool.clear();
+        assert_eq!(pool.stats().pooled, 0);
+    }
+
+    #[test]
+    fn buffer_pool_trim_idle() {
+        let pool = BufferPool::new(4, 100);
+        pool.put(vec![0; 10]);
+        pool.put(vec![0; 10]);
+        pool.put(vec![0; 10]);
+        // Nothing is idle yet.
+        pool.trim();
+        assert_eq!(pool.stats().pooled, 3);
+
+        let inner = pool.inner.as_ref().unwrap();
+        let later = Instant::now() + IDLE_TIMEOUT + Duration::from_secs(1);
+        // The most recently returned buffer was used again since.
+        inner.buffers.lock()[2].returned_at = later;
+        BufferPool::trim_buffers(&mut inner.buffers.lock(), later);
+        assert_eq!(pool.stats().pooled, 1);
+
+        BufferPool::trim_buffers(&mut inner.buffers.lock(), later + IDLE_TIMEOUT * 2);
+        assert_eq!(pool.stats().pooled, 0);
+    }
+
+    #[test]
+    fn buffer_pool_disabled() {
+        let pool = BufferPool::new(0, 100);
+        assert!(!pool.is_enabled());
+        pool.put(vec![0; 10]);
+        assert_eq!(pool.get(10).len(), 10);
+        assert_eq!(pool.stats(), BufferPoolStats::default());
+    }
+}
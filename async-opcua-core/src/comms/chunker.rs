@@ -8,6 +8,7 @@ use std::io::{Read, Write};
 
 use crate::{
     comms::{
+        buffer_pool::BufferPool,
         message_chunk::{MessageChunk, MessageIsFinalType},
         secure_channel::SecureChannel,
         sequence_number::SequenceNumberHandle,
@@ -104,6 +105,7 @@ impl<'a, T: Iterator<Item = &'a MessageChunk>> Read for ReceiveStream<'a, T> {
 
 struct ChunkingStream<'a> {
     secure_channel: &'a SecureChannel,
+    pool: &'a BufferPool,
     chunks: Vec<MessageChunk>,
     expected_chunk_count: usize,
     max_body_per_chunk: usize,
@@ -117,9 +119,11 @@ struct ChunkingStream<'a> {
 }
 
 impl<'a> ChunkingStream<'a> {
+    #[allow(clippy::too_many_arguments)]
     fn new(
         message_type: MessageChunkType,
         secure_channel: &'a SecureChannel,
+        pool: &'a BufferPool,
         max_chunk_size: usize,
         message_size: usize,
         request_id: u32,
@@ -157,10 +161,11 @@ impl<'a> ChunkingStream<'a> {
 
             Ok(Self {
                 secure_channel,
+                pool,
                 chunks: Vec::with_capacity(expected_chunk_count),
                 expected_chunk_count,
                 max_body_per_chunk,
-                next_buf: vec![0; next_buf_size],
+                next_buf: pool.get(next_buf_size),
                 buf_position: 0,
                 is_closed: false,
                 sequence_number,
@@ -174,10 +179,11 @@ impl<'a> ChunkingStream<'a> {
             let next_buf_size = message_size;
             Ok(Self {
                 secure_channel,
+                pool,
                 chunks: Vec::with_capacity(expected_chunk_count),
                 expected_chunk_count,
                 max_body_per_chunk,
-                next_buf: vec![0; next_buf_size],
+                next_buf: pool.get(next_buf_size),
                 buf_position: 0,
                 is_closed: false,
                 sequence_number,
@@ -201,14 +207,16 @@ impl<'a> ChunkingStream<'a> {
             MessageIsFinalType::Intermediate
         };
 
-        let chunk = MessageChunk::new(
+        let chunk = MessageChunk::new_pooled(
             self.sequence_number.current(),
             self.request_id,
             self.message_type,
             is_final,
             self.secure_channel,
             &buf,
+            self.pool,
         )?;
+        self.pool.put(buf);
         self.sequence_number.increment(1);
         self.chunks.push(chunk);
 
@@ -218,7 +226,7 @@ impl<'a> ChunkingStream<'a> {
             } else {
                 self.max_body_per_chunk
             };
-            self.next_buf = vec![0; next_buf_size];
+            self.next_buf = self.pool.get(next_buf_size);
             self.buf_position = 0;
         }
 
@@ -354,6 +362,28 @@ impl Chunker {
         max_chunk_size: usize,
         secure_channel: &SecureChannel,
         supported_message: &impl Message,
+    ) -> std::result::Result<Vec<MessageChunk>, Error> {
+        Self::encode_pooled(
+            sequence_number,
+            request_id,
+            max_message_size,
+            max_chunk_size,
+            secure_channel,
+            supported_message,
+            &BufferPool::disabled(),
+        )
+    }
+
+    /// Encodes a message like [`Chunker::encode`], taking the buffers for the chunks from `pool`.
+    /// Chunk buffers can be returned to the pool once the chunks have been sent.
+    pub fn encode_pooled(
+        sequence_number: SequenceNumberHandle,
+        request_id: u32,
+        max_message_size: usize,
+        max_chunk_size: usize,
+        secure_channel: &SecureChannel,
+        supported_message: &impl Message,
+        pool: &BufferPool,
     ) -> std::result::Result<Vec<MessageChunk>, Error> {
         let security_policy = secure_channel.security_policy();
         if security_policy == SecurityPolicy::Unknown {
@@ -396,6 +426,7 @@ impl Chunker {
         let mut stream = ChunkingStream::new(
             message_type,
             secure_channel,
+            pool,
             max_chunk_size,
             message_size,
             request_id,
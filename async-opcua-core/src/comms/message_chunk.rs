@@ -15,6 +15,7 @@ use opcua_types::{
 use tracing::{error, trace};
 
 use super::{
+    buffer_pool::BufferPool,
     message_chunk_info::ChunkInfo,
     secure_channel::SecureChannel,
     security_header::{SecurityHeader, SequenceHeader},
@@ -163,6 +164,17 @@ impl SimpleBinaryDecodable for MessageChunk {
     fn decode<S: Read + ?Sized>(
         in_stream: &mut S,
         decoding_options: &DecodingOptions,
+    ) -> EncodingResult<Self> {
+        Self::decode_pooled(in_stream, decoding_options, &BufferPool::disabled())
+    }
+}
+
+impl MessageChunk {
+    /// Decode a message chunk, taking the buffer for the chunk data from `pool`.
+    pub fn decode_pooled<S: Read + ?Sized>(
+        in_stream: &mut S,
+        decoding_options: &DecodingOptions,
+        pool: &BufferPool,
     ) -> EncodingResult<Self> {
         // Read the header out first
         let chunk_header =
@@ -186,7 +198,7 @@ impl SimpleBinaryDecodable for MessageChunk {
             ))
         } else {
             // Now make a buffer to write the header and message into
-            let data = vec![0u8; message_size];
+            let data = pool.get(message_size);
             let mut stream = Cursor::new(data);
 
             // Write header to a buffer
@@ -218,6 +230,27 @@ impl MessageChunk {
         is_final: MessageIsFinalType,
         secure_channel: &SecureChannel,
         data: &[u8],
+    ) -> EncodingResult<MessageChunk> {
+        Self::new_pooled(
+            sequence_number,
+            request_id,
+            message_type,
+            is_final,
+            secure_channel,
+            data,
+            &BufferPool::disabled(),
+        )
+    }
+
+    /// Create a new message chunk, taking the buffer for the chunk data from `pool`.
+    pub(crate) fn new_pooled(
+        sequence_number: u32,
+        request_id: u32,
+        message_type: MessageChunkType,
+        is_final: MessageIsFinalType,
+        secure_channel: &SecureChannel,
+        data: &[u8],
+        pool: &BufferPool,
     ) -> EncodingResult<MessageChunk> {
         // security header depends on message type
         let security_header = secure_channel.make_security_header(message_type);
@@ -244,7 +277,7 @@ impl MessageChunk {
             secure_channel_id,
         };
 
-        let mut buf = vec![0u8; message_size];
+        let mut buf = pool.get(message_size);
         let buf_ref = &mut buf as &mut [u8];
         let mut stream = Cursor::new(buf_ref);
         // write chunk header
@@ -8,7 +8,10 @@ use std::{
     collections::HashMap,
     io::{Cursor, Write},
     ops::{Deref, Range},
-    sync::Arc,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
     time::Instant,
 };
 
@@ -25,10 +28,10 @@ use opcua_crypto::{
 };
 use opcua_types::{
     status_code::StatusCode, write_bytes, write_u32, write_u8, ByteString, ChannelSecurityToken,
-    ContextOwned, DateTime, DecodingOptions, Error, MessageSecurityMode, NamespaceMap,
+    ContextOwned, DateTime, DecodingOptions, Error, MessageSecurityMode, NamespaceMap, ObjectId,
     SimpleBinaryDecodable,
 };
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
 
 use super::{
     message_chunk::{MessageChunk, MessageChunkHeader, MessageChunkType, MESSAGE_SIZE_OFFSET},
@@ -92,6 +95,11 @@ pub struct SecureChannel {
     local_keys: Option<(Vec<u8>, AesKey, Vec<u8>)>,
     /// Decoding options
     encoding_context: Arc<RwLock<ContextOwned>>,
+    /// Whether to log chunks on this channel in hex, regardless of the `hex` log level.
+    trace_chunks: Arc<AtomicBool>,
+    /// Request ID of the last `ActivateSession` message, whose chunks are
+    /// logged without their body.
+    redacted_request_id: Mutex<Option<u32>>,
 }
 
 impl SecureChannel {
@@ -114,6 +122,8 @@ impl SecureChannel {
             local_keys: None,
             encoding_context: Default::default(),
             remote_keys: HashMap::new(),
+            trace_chunks: Default::default(),
+            redacted_request_id: Default::default(),
         }
     }
 
@@ -158,6 +168,8 @@ impl SecureChannel {
             local_keys: None,
             encoding_context,
             remote_keys: HashMap::new(),
+            trace_chunks: Default::default(),
+            redacted_request_id: Default::default(),
         }
     }
 
@@ -647,8 +659,55 @@ impl SecureChannel {
         Ok(data)
     }
 
-    fn log_crypto_data(message: &str, data: &[u8]) {
-        crate::debug::log_buffer(message, data);
+    /// Enable or disable hex logging of chunks sent and received on this channel.
+    /// If this is disabled, chunks are still logged if trace logging is enabled
+    /// for the `hex` target.
+    pub fn set_trace_chunks(&self, enabled: bool) {
+        self.trace_chunks.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Get a shared handle to the flag controlling hex logging of chunks on this channel,
+    /// so that it can be toggled from outside the task that owns the channel.
+    pub fn trace_chunks_handle(&self) -> Arc<AtomicBool> {
+        self.trace_chunks.clone()
+    }
+
+    fn log_crypto_data(&self, message: &str, data: &[u8]) {
+        let force = self.trace_chunks.load(Ordering::Relaxed);
+        if !force && !tracing::enabled!(target: "hex", tracing::Level::TRACE) {
+            return;
+        }
+        let loggable = self.loggable_chunk(data);
+        if loggable.len() < data.len() {
+            let message = format!("{message} (body redacted)");
+            crate::debug::log_buffer_traced(force, &message, loggable);
+        } else {
+            crate::debug::log_buffer_traced(force, message, data);
+        }
+    }
+
+    /// Get the part of the plaintext chunk `data` that may be logged. The body of
+    /// chunks belonging to an `ActivateSession` message is left out, since it holds
+    /// the user identity token. Continuation chunks of the message are recognized by
+    /// their request ID.
+    pub(crate) fn loggable_chunk<'a>(&self, data: &'a [u8]) -> &'a [u8] {
+        // Message header, secure channel ID, token ID, sequence number and request ID.
+        const BODY_OFFSET: usize = 24;
+        if data.len() < BODY_OFFSET || &data[..3] != b"MSG" {
+            return data;
+        }
+        let request_id = u32::from_le_bytes([data[20], data[21], data[22], data[23]]);
+        let mut redacted = self.redacted_request_id.lock();
+        if *redacted != Some(request_id) {
+            // Encoding ID of an ActivateSessionRequest, as a four byte node ID.
+            let [lo, hi, ..] =
+                (ObjectId::ActivateSessionRequest_Encoding_DefaultBinary as u32).to_le_bytes();
+            if data.get(BODY_OFFSET..BODY_OFFSET + 4) != Some(&[0x01, 0x00, lo, hi]) {
+                return data;
+            }
+            *redacted = Some(request_id);
+        }
+        &data[..BODY_OFFSET]
     }
 
     /// Applies security to a message chunk and yields a encrypted/signed block to be streamed
@@ -672,8 +731,8 @@ impl SecureChannel {
             //     Signature       - E
 
             let mut data = self.add_space_for_padding_and_signature(message_chunk)?;
-            Self::log_crypto_data("Chunk before padding", &message_chunk.data[..]);
-            Self::log_crypto_data("Chunk after padding", &data[..]);
+            self.log_crypto_data("Chunk before padding", &message_chunk.data[..]);
+            self.log_crypto_data("Chunk after padding", &data[..]);
 
             // Encrypted range is from the sequence header to the end
             let encrypted_range = encrypted_data_offset..data.len();
@@ -693,7 +752,7 @@ impl SecureChannel {
                 self.symmetric_sign_and_encrypt(&mut data, signed_range, encrypted_range, dst)?
             };
 
-            Self::log_crypto_data("Chunk after encryption", &dst[..encrypted_size]);
+            self.log_crypto_data("Chunk after encryption", &dst[..encrypted_size]);
 
             encrypted_size
         } else {
@@ -703,6 +762,7 @@ impl SecureChannel {
                 return Err(StatusCode::BadEncodingLimitsExceeded);
             }
             dst[..size].copy_from_slice(&message_chunk.data[..]);
+            self.log_crypto_data("Outgoing chunk", &dst[..size]);
             size
         };
         Ok(size)
@@ -722,6 +782,7 @@ impl SecureChannel {
         src: &[u8],
         their_key: Option<PrivateKey>,
     ) -> Result<MessageChunk, Error> {
+        self.log_crypto_data("Incoming chunk", src);
         // Get message & security header from data
         let decoding_options = self.decoding_options();
         let (message_header, security_header, encrypted_data_offset) = {
@@ -915,7 +976,7 @@ impl SecureChannel {
         // tmp[signature_range.clone()].copy_from_slice(&signature);
         assert_eq!(encrypted_range.end, signature_range.end);
 
-        Self::log_crypto_data("Chunk after signing", &dst[..signature_range.end]);
+        self.log_crypto_data("Chunk after signing", &dst[..signature_range.end]);
 
         // Encrypt the sequence header, payload, signature portion into dst
         let encrypted_size = security_policy.asymmetric_encrypt(
@@ -1371,7 +1432,7 @@ impl SecureChannel {
                 let encrypted_range =
                     encrypted_range.start..(encrypted_range.start + decrypted_size);
                 dst[encrypted_range.clone()].copy_from_slice(&decrypted_tmp[..decrypted_size]);
-                Self::log_crypto_data("Decrypted buffer", &dst[..encrypted_range.end]);
+                self.log_crypto_data("Decrypted buffer", &dst[..encrypted_range.end]);
 
                 // Verify signature (after encrypted portion)
                 let signature_range = (encrypted_range.end
@@ -55,13 +55,7 @@ impl SecurityHeader {
         if is_open_secure_channel {
             let security_header = AsymmetricSecurityHeader::decode(stream, decoding_options)?;
 
-            let security_policy = if security_header.security_policy_uri.is_null() {
-                SecurityPolicy::None
-            } else {
-                SecurityPolicy::from_uri(security_header.security_policy_uri.as_ref())
-            };
-
-            if security_policy == SecurityPolicy::Unknown {
+            if security_header.security_policy() == SecurityPolicy::Unknown {
                 return Err(Error::new(
                     StatusCode::BadSecurityPolicyRejected,
                     format!(
@@ -214,6 +208,16 @@ impl AsymmetricSecurityHeader {
             receiver_certificate_thumbprint,
         }
     }
+
+    /// Get the security policy given by the security policy URI in this header.
+    /// A null URI is treated as `SecurityPolicy::None`.
+    pub fn security_policy(&self) -> SecurityPolicy {
+        if self.security_policy_uri.is_null() {
+            SecurityPolicy::None
+        } else {
+            SecurityPolicy::from_uri(self.security_policy_uri.as_ref())
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
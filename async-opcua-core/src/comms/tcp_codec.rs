@@ -8,6 +8,7 @@
 //! * HEL - Hello message
 //! * ACK - Acknowledge message
 //! * ERR - Error message
+//! * RHE - Reverse hello message
 //! * MSG - Message chunk
 //! * OPN - Open Secure Channel message
 //! * CLO - Close Secure Channel message
@@ -23,10 +24,11 @@ use opcua_types::{
 };
 
 use super::{
+    buffer_pool::BufferPool,
     message_chunk::MessageChunk,
     tcp_types::{
         AcknowledgeMessage, ErrorMessage, HelloMessage, MessageHeader, MessageType,
-        MESSAGE_HEADER_LEN,
+        ReverseHelloMessage, MESSAGE_HEADER_LEN,
     },
 };
 
@@ -42,6 +44,8 @@ pub enum Message {
     Error(ErrorMessage),
     /// Part of a general OPC-UA message.
     Chunk(MessageChunk),
+    /// Reverse hello message, sent by a server that initiates the connection.
+    ReverseHello(ReverseHelloMessage),
 }
 
 /// Implements a tokio codec that as close as possible, allows incoming data to be transformed into
@@ -49,6 +53,7 @@ pub enum Message {
 /// messages so there is still some buffers within message chunks, but not at the raw socket level.
 pub struct TcpCodec {
     decoding_options: DecodingOptions,
+    buffer_pool: BufferPool,
 }
 
 impl Decoder for TcpCodec {
@@ -72,12 +77,16 @@ impl Decoder for TcpCodec {
             if buf.len() >= message_size {
                 // Extract the message bytes from the buffer & decode them into a message
                 let mut buf = buf.split_to(message_size);
-                let message =
-                    Self::decode_message(message_header, &mut buf, &self.decoding_options)
-                        .map_err(|e| {
-                            error!("Codec got an error {} while decoding a message", e);
-                            io::Error::from(e)
-                        })?;
+                let message = Self::decode_message(
+                    message_header,
+                    &mut buf,
+                    &self.decoding_options,
+                    &self.buffer_pool,
+                )
+                .map_err(|e| {
+                    error!("Codec got an error {} while decoding a message", e);
+                    io::Error::from(e)
+                })?;
                 Ok(Some(message))
             } else {
                 // Not enough bytes
@@ -98,6 +107,7 @@ impl Encoder<Message> for TcpCodec {
             Message::Acknowledge(msg) => self.write(msg, buf),
             Message::Error(msg) => self.write(msg, buf),
             Message::Chunk(msg) => self.write(msg, buf),
+            Message::ReverseHello(msg) => self.write(msg, buf),
         }
     }
 }
@@ -106,7 +116,15 @@ impl TcpCodec {
     /// Constructs a new TcpCodec. The abort flag is set to terminate the codec even while it is
     /// waiting for a frame to arrive.
     pub fn new(decoding_options: DecodingOptions) -> TcpCodec {
-        TcpCodec { decoding_options }
+        TcpCodec {
+            decoding_options,
+            buffer_pool: BufferPool::disabled(),
+        }
+    }
+
+    /// Set the pool used for the buffers of decoded message chunks.
+    pub fn set_buffer_pool(&mut self, buffer_pool: BufferPool) {
+        self.buffer_pool = buffer_pool;
     }
 
     // Writes the encodable thing into the buffer.
@@ -126,6 +144,7 @@ impl TcpCodec {
         message_header: MessageHeader,
         buf: &mut BytesMut,
         decoding_options: &DecodingOptions,
+        buffer_pool: &BufferPool,
     ) -> Result<Message, StatusCode> {
         let mut buf = io::Cursor::new(&buf[..]);
         match message_header.message_type {
@@ -141,7 +160,12 @@ impl TcpCodec {
                 &mut buf,
                 decoding_options,
             )?)),
-            MessageType::Chunk => Ok(Message::Chunk(MessageChunk::decode(
+            MessageType::Chunk => Ok(Message::Chunk(MessageChunk::decode_pooled(
+                &mut buf,
+                decoding_options,
+                buffer_pool,
+            )?)),
+            MessageType::ReverseHello => Ok(Message::ReverseHello(ReverseHelloMessage::decode(
                 &mut buf,
                 decoding_options,
             )?)),
@@ -13,7 +13,7 @@ use opcua_types::{
 };
 use tracing::error;
 
-use super::url::url_matches_except_host;
+use super::url::{url_matches, url_matches_except_host};
 
 /// Message header type for message chunks.
 pub(crate) const CHUNK_MESSAGE: &[u8] = b"MSG";
@@ -27,6 +27,8 @@ pub(crate) const HELLO_MESSAGE: &[u8] = b"HEL";
 pub(crate) const ACKNOWLEDGE_MESSAGE: &[u8] = b"ACK";
 /// Message header type for error messages.
 pub(crate) const ERROR_MESSAGE: &[u8] = b"ERR";
+/// Message header type for reverse hello messages.
+pub(crate) const REVERSE_HELLO_MESSAGE: &[u8] = b"RHE";
 
 /// ChunkIsFinal type for the final chunk in a message.
 pub(crate) const CHUNK_FINAL: u8 = b'F';
@@ -54,6 +56,8 @@ pub enum MessageType {
     Chunk,
     /// Fatal error, followed by shutting down the channel.
     Error,
+    /// RHE message, sent by the server when it initiates the connection.
+    ReverseHello,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -75,6 +79,7 @@ impl SimpleBinaryEncodable for MessageHeader {
             MessageType::Hello => stream.write_all(HELLO_MESSAGE),
             MessageType::Acknowledge => stream.write_all(ACKNOWLEDGE_MESSAGE),
             MessageType::Error => stream.write_all(ERROR_MESSAGE),
+            MessageType::ReverseHello => stream.write_all(REVERSE_HELLO_MESSAGE),
             MessageType::Chunk => {
                 panic!("Don't write chunks to stream with this call, use Chunk and Chunker");
             }
@@ -160,6 +165,7 @@ impl MessageHeader {
                 HELLO_MESSAGE => MessageType::Hello,
                 ACKNOWLEDGE_MESSAGE => MessageType::Acknowledge,
                 ERROR_MESSAGE => MessageType::Error,
+                REVERSE_HELLO_MESSAGE => MessageType::ReverseHello,
                 CHUNK_MESSAGE | OPEN_SECURE_CHANNEL_MESSAGE | CLOSE_SECURE_CHANNEL_MESSAGE => {
                     MessageType::Chunk
                 }
@@ -269,14 +275,30 @@ impl HelloMessage {
         msg
     }
 
-    /// Check if the endpoint URL is valid.
+    /// Check if the endpoint URL is valid, ignoring its host and port.
+    #[deprecated(note = "Use `HelloMessage::is_endpoint_url_valid_for_host` instead")]
     pub fn is_endpoint_url_valid(&self, endpoints: &[EndpointDescription]) -> bool {
-        if self.is_endpoint_valid_length() {
-            self.matches_endpoint(endpoints)
-        } else {
+        self.is_endpoint_url_valid_for_host(endpoints, true)
+    }
+
+    /// Check if the endpoint URL is valid, meaning that it refers to one of the given endpoints.
+    ///
+    /// If `ignore_host` is `true`, the host and port of the endpoint URL are not checked.
+    /// This is needed if clients reach the server through NAT or a proxy, and use a
+    /// different host than the one the server is configured with.
+    pub fn is_endpoint_url_valid_for_host(
+        &self,
+        endpoints: &[EndpointDescription],
+        ignore_host: bool,
+    ) -> bool {
+        if !self.is_endpoint_valid_length() {
             // Length > 4096
             error!("Supplied endpoint url exceeds maximum length");
             false
+        } else if ignore_host {
+            self.matches_endpoint(endpoints)
+        } else {
+            self.matches_endpoint_exact(endpoints)
         }
     }
 
@@ -300,6 +322,14 @@ impl HelloMessage {
         })
     }
 
+    /// Check if any endpoint in the list matches the endpoint in this message,
+    /// including the host and port.
+    pub fn matches_endpoint_exact(&self, endpoints: &[EndpointDescription]) -> bool {
+        endpoints
+            .iter()
+            .any(|e| url_matches(e.endpoint_url.as_ref(), self.endpoint_url.as_ref()))
+    }
+
     /// Check if the requested buffer sizes are valid.
     pub fn is_valid_buffer_sizes(&self) -> bool {
         // Set in part 6 as minimum transport buffer size
@@ -383,6 +413,64 @@ impl AcknowledgeMessage {
     }
 }
 
+/// Implementation of the RHE message in OPC UA, sent by a server that opens
+/// the connection to a client, before the client sends its HEL message.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReverseHelloMessage {
+    message_header: MessageHeader,
+    /// Application URI of the server sending the message.
+    pub server_uri: UAString,
+    /// Endpoint URL that the client should use when sending the HEL message.
+    pub endpoint_url: UAString,
+}
+
+impl SimpleBinaryEncodable for ReverseHelloMessage {
+    fn byte_len(&self) -> usize {
+        self.message_header.byte_len() + self.server_uri.byte_len() + self.endpoint_url.byte_len()
+    }
+
+    fn encode<S: Write + ?Sized>(&self, stream: &mut S) -> EncodingResult<()> {
+        self.message_header.encode(stream)?;
+        self.server_uri.encode(stream)?;
+        self.endpoint_url.encode(stream)
+    }
+}
+
+impl SimpleBinaryDecodable for ReverseHelloMessage {
+    fn decode<S: Read + ?Sized>(
+        stream: &mut S,
+        decoding_options: &DecodingOptions,
+    ) -> EncodingResult<Self> {
+        let message_header = MessageHeader::decode(stream, decoding_options)?;
+        let server_uri = UAString::decode(stream, decoding_options)?;
+        let endpoint_url = UAString::decode(stream, decoding_options)?;
+        Ok(ReverseHelloMessage {
+            message_header,
+            server_uri,
+            endpoint_url,
+        })
+    }
+}
+
+impl ReverseHelloMessage {
+    /// Create a new reverse hello message.
+    pub fn new(server_uri: &str, endpoint_url: &str) -> Self {
+        let mut msg = ReverseHelloMessage {
+            message_header: MessageHeader::new(MessageType::ReverseHello),
+            server_uri: UAString::from(server_uri),
+            endpoint_url: UAString::from(endpoint_url),
+        };
+        msg.message_header.message_size = msg.byte_len() as u32;
+        msg
+    }
+
+    /// Check whether this message was sent for the given endpoint URL, ignoring the hostname.
+    pub fn matches_endpoint_url(&self, endpoint_url: &str) -> bool {
+        !self.endpoint_url.is_empty()
+            && url_matches_except_host(self.endpoint_url.as_ref(), endpoint_url)
+    }
+}
+
 /// Implementation of the ERR message in OPC UA
 #[derive(Debug, Clone, PartialEq)]
 pub struct ErrorMessage {
@@ -443,10 +531,12 @@ impl ErrorMessage {
 mod tests {
     use std::io::Cursor;
 
-    use crate::comms::tcp_types::{AcknowledgeMessage, HelloMessage, MessageHeader, MessageType};
+    use crate::comms::tcp_types::{
+        AcknowledgeMessage, HelloMessage, MessageHeader, MessageType, ReverseHelloMessage,
+    };
     use opcua_types::{
         ApplicationDescription, ByteString, DecodingOptions, EndpointDescription,
-        MessageSecurityMode, SimpleBinaryDecodable, UAString,
+        MessageSecurityMode, SimpleBinaryDecodable, SimpleBinaryEncodable, UAString,
     };
 
     fn hello_data() -> Vec<u8> {
@@ -500,6 +590,25 @@ mod tests {
         assert_eq!(ack.max_chunk_count, 65535);
     }
 
+    #[test]
+    fn reverse_hello() {
+        let msg = ReverseHelloMessage::new("urn:server", "opc.tcp://foo:4855/");
+        let data = msg.encode_to_vec();
+        assert_eq!(&data[0..4], b"RHEF");
+        assert_eq!(data.len(), msg.message_header.message_size as usize);
+
+        let mut stream = Cursor::new(data);
+        let decoded = ReverseHelloMessage::decode(&mut stream, &DecodingOptions::test()).unwrap();
+        assert_eq!(decoded, msg);
+        assert_eq!(
+            decoded.message_header.message_type,
+            MessageType::ReverseHello
+        );
+
+        assert!(msg.matches_endpoint_url("opc.tcp://bar:4855/"));
+        assert!(!msg.matches_endpoint_url("opc.tcp://foo:4855/other"));
+    }
+
     #[test]
     fn endpoint_url() {
         // Ensure hello with None endpoint is invalid
@@ -547,6 +656,19 @@ mod tests {
         assert!(h.is_endpoint_valid_length());
         h.endpoint_url = UAString::from("opc.tcp://foo:1234"); // Ignore port
         assert!(h.matches_endpoint(&endpoints));
+
+        // Exact matching checks the host and port as well.
+        assert!(!h.matches_endpoint_exact(&endpoints));
+        assert!(h.is_endpoint_url_valid_for_host(&endpoints, true));
+        assert!(!h.is_endpoint_url_valid_for_host(&endpoints, false));
+        h.endpoint_url = UAString::from("opc.tcp://bar/");
+        assert!(!h.is_endpoint_url_valid_for_host(&endpoints, false));
+        h.endpoint_url = UAString::from("opc.tcp://FOO:4840");
+        assert!(h.is_endpoint_url_valid_for_host(&endpoints, false));
+        #[allow(deprecated)]
+        {
+            assert!(h.is_endpoint_url_valid(&endpoints));
+        }
     }
 
     #[test]
@@ -4,6 +4,8 @@
 
 //! Provides functions for parsing Urls from strings.
 
+use std::net::IpAddr;
+
 use tracing::error;
 use url::Url;
 
@@ -11,14 +13,28 @@ use opcua_types::status_code::StatusCode;
 
 /// Scheme for OPC-UA TCP.
 pub const OPC_TCP_SCHEME: &str = "opc.tcp";
+/// Scheme for OPC-UA over secure WebSockets.
+pub const OPC_WSS_SCHEME: &str = "opc.wss";
+/// Scheme for OPC-UA over plain WebSockets, typically behind a proxy terminating TLS.
+pub const OPC_WS_SCHEME: &str = "opc.ws";
+
+/// Get the port used for `scheme` if a url does not specify one. WebSocket
+/// urls use the standard HTTP ports, other urls use 4840.
+fn default_port(scheme: &str) -> u16 {
+    match scheme {
+        OPC_WSS_SCHEME => 443,
+        OPC_WS_SCHEME => 80,
+        _ => crate::constants::DEFAULT_OPC_UA_SERVER_PORT,
+    }
+}
 
 /// Creates a `Url` from the input string, supplying a default port if necessary.
 fn opc_url_from_str(s: &str) -> Result<Url, url::ParseError> {
     Url::parse(s)
         .map(|mut url| {
             if url.port().is_none() {
-                // If no port is supplied, then treat it as the default port 4840
-                let _ = url.set_port(Some(crate::constants::DEFAULT_OPC_UA_SERVER_PORT));
+                // If no port is supplied, then treat it as the default port for the scheme
+                let _ = url.set_port(Some(default_port(url.scheme())));
             }
             url
         })
@@ -52,6 +68,46 @@ pub fn url_matches_except_host(url1: &str, url2: &str) -> bool {
     false
 }
 
+/// Test if two hostnames refer to the same host. Names are compared case-insensitively,
+/// `localhost` and loopback addresses are considered equal, and an unspecified address,
+/// i.e. `0.0.0.0` or `::`, matches any host.
+fn hosts_match(host1: &str, host2: &str) -> bool {
+    fn parse_ip(host: &str) -> Option<IpAddr> {
+        host.trim_start_matches('[')
+            .trim_end_matches(']')
+            .parse()
+            .ok()
+    }
+    fn is_loopback(host: &str) -> bool {
+        host.eq_ignore_ascii_case("localhost") || parse_ip(host).is_some_and(|ip| ip.is_loopback())
+    }
+    fn is_unspecified(host: &str) -> bool {
+        parse_ip(host).is_some_and(|ip| ip.is_unspecified())
+    }
+
+    host1.eq_ignore_ascii_case(host2)
+        || is_loopback(host1) && is_loopback(host2)
+        || is_unspecified(host1)
+        || is_unspecified(host2)
+}
+
+/// Test if the two urls refer to the same endpoint. Unlike [`url_matches_except_host`],
+/// the host and port must match as well, where a missing port is treated as the
+/// default port for the scheme.
+pub fn url_matches(url1: &str, url2: &str) -> bool {
+    if !url_matches_except_host(url1, url2) {
+        return false;
+    }
+    let (Ok(url1), Ok(url2)) = (opc_url_from_str(url1), opc_url_from_str(url2)) else {
+        return false;
+    };
+    url1.port() == url2.port()
+        && hosts_match(
+            url1.host_str().unwrap_or_default(),
+            url2.host_str().unwrap_or_default(),
+        )
+}
+
 /// Takes an endpoint url and strips off the path and args to leave just the protocol, host & port.
 pub fn server_url_from_endpoint_url(
     endpoint_url: &str,
@@ -60,7 +116,7 @@ pub fn server_url_from_endpoint_url(
         url.set_query(None);
         if let Some(port) = url.port() {
             // If the port is the default, strip it so the url string omits it.
-            if port == crate::constants::DEFAULT_OPC_UA_SERVER_PORT {
+            if port == default_port(url.scheme()) {
                 let _ = url.set_port(None);
             }
         }
@@ -73,10 +129,23 @@ pub fn is_valid_opc_ua_url(url: &str) -> bool {
     is_opc_ua_binary_url(url)
 }
 
-/// Check if this is an OPC-UA TCP URL.
+/// Check if this is an OPC-UA URL using the binary protocol, either over TCP
+/// or WebSockets.
 pub fn is_opc_ua_binary_url(url: &str) -> bool {
     if let Ok(url) = opc_url_from_str(url) {
-        url.scheme() == OPC_TCP_SCHEME
+        matches!(
+            url.scheme(),
+            OPC_TCP_SCHEME | OPC_WSS_SCHEME | OPC_WS_SCHEME
+        )
+    } else {
+        false
+    }
+}
+
+/// Check if this is an OPC-UA WebSocket URL.
+pub fn is_opc_ua_websocket_url(url: &str) -> bool {
+    if let Ok(url) = opc_url_from_str(url) {
+        matches!(url.scheme(), OPC_WSS_SCHEME | OPC_WS_SCHEME)
     } else {
         false
     }
@@ -132,6 +201,10 @@ mod tests {
             "opc.tcp://[FEDC:BA98:7654:3210:FEDC:BA98:7654:3210]:80/xyz"
         ));
         assert!(!is_opc_ua_binary_url("http://foo/xyz"));
+        assert!(is_opc_ua_binary_url("opc.wss://foo/xyz"));
+        assert!(is_opc_ua_websocket_url("opc.wss://foo:443/xyz"));
+        assert!(is_opc_ua_websocket_url("opc.ws://foo/xyz"));
+        assert!(!is_opc_ua_websocket_url("opc.tcp://foo/xyz"));
     }
 
     #[test]
@@ -144,6 +217,31 @@ mod tests {
             "opc.tcp://localhost/xyz",
             "opc.tcp://127.0.0.1/abc"
         ));
+
+        assert!(url_matches("opc.tcp://Foo:4840/xyz", "opc.tcp://foo/xyz/"));
+        assert!(url_matches(
+            "opc.tcp://localhost:4855/xyz",
+            "opc.tcp://127.0.0.1:4855/xyz"
+        ));
+        assert!(url_matches(
+            "opc.tcp://[::1]:4855/",
+            "opc.tcp://localhost:4855/"
+        ));
+        assert!(url_matches(
+            "opc.tcp://0.0.0.0:4855/",
+            "opc.tcp://foo:4855/"
+        ));
+        assert!(!url_matches("opc.tcp://foo:4855/", "opc.tcp://bar:4855/"));
+        assert!(!url_matches("opc.tcp://foo:4855/", "opc.tcp://foo:4856/"));
+        assert!(!url_matches("opc.tcp://foo:4855/", "opc.tcp://foo/"));
+        // WebSocket urls default to the HTTP ports.
+        assert!(url_matches("opc.wss://foo/xyz", "opc.wss://foo:443/xyz"));
+        assert!(!url_matches("opc.wss://foo/xyz", "opc.wss://foo:4840/xyz"));
+        assert!(url_matches("opc.ws://foo/xyz", "opc.ws://foo:80/xyz"));
+        assert!(!url_matches(
+            "opc.tcp://foo:4855/xyz",
+            "opc.tcp://foo:4855/abc"
+        ));
     }
 
     #[test]
@@ -160,6 +258,14 @@ mod tests {
             "opc.tcp://localhost:4841",
             server_url_from_endpoint_url("opc.tcp://localhost:4841").unwrap()
         );
+        assert_eq!(
+            "opc.wss://localhost",
+            server_url_from_endpoint_url("opc.wss://localhost:443").unwrap()
+        );
+        assert_eq!(
+            "opc.wss://localhost:4840",
+            server_url_from_endpoint_url("opc.wss://localhost:4840").unwrap()
+        );
         assert_eq!(
             "opc.tcp://localhost/xyz/abc",
             server_url_from_endpoint_url("opc.tcp://localhost/xyz/abc?1").unwrap()
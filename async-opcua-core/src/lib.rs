@@ -19,11 +19,34 @@ pub mod debug {
             return;
         }
 
+        write_buffer(message, buf, |line| trace!(target: "hex", "{}", line));
+    }
+
+    /// Like [`log_buffer`], but if `force` is set the buffer is logged to the
+    /// `connection_trace` target when trace logging is disabled for the `hex` target.
+    /// This is used to trace a single connection without enabling hex logging for
+    /// every connection, by enabling trace logging for `connection_trace`.
+    pub fn log_buffer_traced(force: bool, message: &str, buf: &[u8]) {
+        if force && !enabled!(target: "hex", tracing::Level::TRACE) {
+            if !enabled!(target: "connection_trace", tracing::Level::TRACE) {
+                return;
+            }
+            write_buffer(
+                message,
+                buf,
+                |line| trace!(target: "connection_trace", "{}", line),
+            );
+        } else {
+            log_buffer(message, buf);
+        }
+    }
+
+    fn write_buffer(message: &str, buf: &[u8], log: impl Fn(&str)) {
         let line_len = 32;
         let len = buf.len();
         let last_line_padding = ((len / line_len) + 1) * line_len - len;
 
-        trace!(target: "hex", "{}", message);
+        log(message);
 
         let mut char_line = String::new();
         let mut hex_line = format!("{:08x}: ", 0);
@@ -31,7 +54,7 @@ pub mod debug {
         for (i, b) in buf.iter().enumerate() {
             let value = { *b };
             if i > 0 && i % line_len == 0 {
-                trace!(target: "hex", "{} {}", hex_line, char_line);
+                log(&format!("{hex_line} {char_line}"));
                 hex_line = format!("{i:08}: ");
                 char_line.clear();
             }
@@ -46,7 +69,7 @@ pub mod debug {
             for _ in 0..last_line_padding {
                 hex_line.push_str("   ");
             }
-            trace!(target: "hex", "{} {}", hex_line, char_line);
+            log(&format!("{hex_line} {char_line}"));
         }
     }
 }
@@ -65,6 +88,7 @@ pub mod constants {
 pub mod comms;
 pub mod config;
 pub mod handle;
+pub mod task;
 
 pub mod messages;
 use std::sync::atomic::AtomicBool;
@@ -66,7 +66,13 @@ macro_rules! request_enum {
                         Ok($value::decode(stream, ctx)?.into())
                     }, )*
                     _ => {
-                        Err(Error::decoding(format!("decoding unsupported for object id {:?}", object_id)))
+                        // All requests start with the request header, decode that so that
+                        // the caller can respond with a service fault instead of closing the channel.
+                        let header = RequestHeader::decode(stream, ctx)?;
+                        Err(Error::new(
+                            StatusCode::BadServiceUnsupported,
+                            format!("Service with encoding id {:?} is not supported", object_id),
+                        ).with_request_handle(header.request_handle))
                     }
                 }
             }
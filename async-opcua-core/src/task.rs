@@ -0,0 +1,46 @@
+//! Utilities for spawning tokio tasks.
+
+use std::future::Future;
+
+use tokio::task::JoinHandle;
+
+/// Spawn a new tokio task, giving it `name` if it is set.
+///
+/// Task names are only visible to tools such as `tokio-console` when the
+/// `tokio-console` feature is enabled and the crate is compiled with
+/// `--cfg tokio_unstable`. Otherwise the name is ignored, and this is
+/// equivalent to `tokio::task::spawn`. If the named task cannot be spawned,
+/// the future is spawned without a name instead.
+pub fn spawn_named<F>(name: Option<String>, future: F) -> JoinHandle<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    #[cfg(all(tokio_unstable, feature = "tokio-console"))]
+    if let Some(name) = name {
+        // The builder consumes the task even if it fails to spawn it, so the future is
+        // only sent to the named task once it is running, and kept for the fallback otherwise.
+        let (send, recv) = tokio::sync::oneshot::channel::<F>();
+        let task = async move {
+            match recv.await {
+                Ok(future) => future.await,
+                // The sender is never dropped without sending once the task is spawned.
+                Err(_) => std::future::pending().await,
+            }
+        };
+        match tokio::task::Builder::new().name(&name).spawn(task) {
+            Ok(handle) => {
+                // This only fails if the task has already been aborted.
+                let _ = send.send(future);
+                return handle;
+            }
+            Err(e) => {
+                tracing::warn!("Failed to spawn task {name}, spawning it without a name: {e}");
+                return tokio::task::spawn(future);
+            }
+        }
+    }
+    #[cfg(not(all(tokio_unstable, feature = "tokio-console")))]
+    let _ = name;
+    tokio::task::spawn(future)
+}
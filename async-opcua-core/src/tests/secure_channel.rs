@@ -233,3 +233,43 @@ fn symmetric_sign_and_encrypt_message_chunk_basic256sha256() {
         SecurityPolicy::Basic256Sha256,
     );
 }
+
+#[test]
+fn activate_session_chunks_redacted_in_logs() {
+    use crate::{comms::tcp_types::MIN_CHUNK_SIZE, RequestMessage};
+    use opcua_types::{ActivateSessionRequest, ReadRequest, UserNameIdentityToken};
+
+    let secure_channel = SecureChannel::new_no_certificate_store();
+    let encode = |request_id: u32, message: RequestMessage| {
+        Chunker::encode(
+            SequenceNumberHandle::new(true),
+            request_id,
+            0,
+            MIN_CHUNK_SIZE,
+            &secure_channel,
+            &message,
+        )
+        .unwrap()
+    };
+
+    // A large identity token, so that the message is split into several chunks.
+    let activate = ActivateSessionRequest {
+        user_identity_token: ExtensionObject::from_message(UserNameIdentityToken {
+            user_name: "user".into(),
+            password: ByteString::from(vec![b'x'; 20_000]),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    let chunks = encode(1, activate.into());
+    assert!(chunks.len() > 1);
+    for chunk in &chunks {
+        assert_eq!(secure_channel.loggable_chunk(&chunk.data).len(), 24);
+    }
+
+    // Other messages are logged in full.
+    let chunks = encode(2, ReadRequest::default().into());
+    for chunk in &chunks {
+        assert_eq!(secure_channel.loggable_chunk(&chunk.data), &chunk.data[..]);
+    }
+}
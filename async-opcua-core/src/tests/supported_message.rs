@@ -13,3 +13,30 @@ fn size() {
     println!("ResponseMessage size = {size}");
     assert!(size <= 16);
 }
+
+#[test]
+fn unsupported_request() {
+    use crate::Message;
+    use opcua_types::{BinaryEncodable, ContextOwned, ObjectId, RequestHeader, StatusCode};
+    use std::io::Cursor;
+
+    let ctx_f = ContextOwned::default();
+    let ctx = ctx_f.context();
+    let header = RequestHeader {
+        request_handle: 123,
+        ..Default::default()
+    };
+    let mut stream = Cursor::new(header.encode_to_vec(&ctx));
+
+    // Unsupported requests fail with a status code and the request handle,
+    // so that the server can respond with a service fault.
+    let err = RequestMessage::decode_by_object_id(
+        &mut stream,
+        ObjectId::ReadResponse_Encoding_DefaultBinary,
+        &ctx,
+    )
+    .unwrap_err();
+    assert_eq!(err.status(), StatusCode::BadServiceUnsupported);
+    assert_eq!(err.full_context(), None);
+    assert_eq!(err.with_request_id(1).full_context(), Some((1, 123)));
+}
@@ -2,44 +2,78 @@
 // SPDX-License-Identifier: MPL-2.0
 // Copyright (C) 2017-2024 Adam Lock
 
-//! The certificate store holds and retrieves private keys and certificates from disk. It is responsible
-//! for checking certificates supplied by the remote end to see if they are valid and trusted or not.
+//! The certificate store holds and retrieves private keys and certificates from a storage backend,
+//! which is a directory on disk by default. It is responsible for checking certificates supplied
+//! by the remote end to see if they are valid and trusted or not.
 
 use std::fs::File;
 use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
 
+use parking_lot::Mutex;
 use tracing::{debug, error, info, trace, warn};
 
 use opcua_types::status_code::StatusCode;
 
 use super::{
+    crl::CertificateRevocationList,
     pkey::PrivateKey,
     security_policy::SecurityPolicy,
+    store_backend::{CertificateList, CertificateStoreBackend, FileSystemCertificateStore},
+    validation_cache::{ValidationCache, ValidationCacheStats},
     x509::{X509Data, X509},
 };
 
 use rsa;
 use x509_cert;
 
-/// Default path to the applications own certificate
-const OWN_CERTIFICATE_PATH: &str = "own/cert.der";
-/// Default path to the applications own private key
-const OWN_PRIVATE_KEY_PATH: &str = "private/private.pem";
-/// The directory holding trusted certificates
-const TRUSTED_CERTS_DIR: &str = "trusted";
-/// The directory holding rejected certificates
-const REJECTED_CERTS_DIR: &str = "rejected";
+/// The maximum number of issuers between a certificate and a trusted CA
+const MAX_CHAIN_LENGTH: usize = 10;
+
+/// Time after which the CA certificates and revocation lists used to verify
+/// certificates are read from the backend again.
+const CHAIN_STORE_REFRESH: Duration = Duration::from_secs(10);
+
+/// Trusted and issuer certificates and verified revocation lists read from the
+/// backend, so that they are not read again for every certificate that is validated.
+struct ChainStore {
+    trusted: Vec<X509>,
+    issuers: Vec<X509>,
+    crls: Vec<CertificateRevocationList>,
+    read_at: Instant,
+}
+
+impl ChainStore {
+    /// Return `true` if a revocation list that has not expired at `now` was
+    /// issued by the issuer of `cert`.
+    fn has_current_crl_for(&self, cert: &X509, now: SystemTime) -> bool {
+        self.crls
+            .iter()
+            .any(|crl| crl.is_issuer_of(cert) && !crl.is_expired(now))
+    }
+
+    /// Return `true` if both stores hold the same certificates and revocation lists.
+    fn has_same_contents(&self, other: &ChainStore) -> bool {
+        self.trusted == other.trusted && self.issuers == other.issuers && self.crls == other.crls
+    }
+}
+
+/// State used while verifying the chain of a single certificate.
+struct ChainContext<'a> {
+    store: Arc<ChainStore>,
+    cert_file_name: &'a str,
+}
 
 /// The certificate store manages the storage of a server/client's own certificate & private key
 /// and the trust / rejection of certificates from the other end.
 pub struct CertificateStore {
-    /// Path to the applications own certificate
-    own_certificate_path: PathBuf,
-    /// Path to the applications own private key
-    own_private_key_path: PathBuf,
-    /// Path to the certificate store on disk
-    pub(crate) pki_path: PathBuf,
+    /// Storage for the certificates, private key and revocation lists
+    backend: Box<dyn CertificateStoreBackend>,
+    /// Copy of the backend if the store was created on a PKI directory, used by the
+    /// deprecated path getters.
+    fs_backend: Option<FileSystemCertificateStore>,
     /// Timestamps of the cert are normally checked on the cert to ensure it cannot be used before
     /// or after its limits, but this check can be disabled.
     check_time: bool,
@@ -51,6 +85,13 @@ pub struct CertificateStore {
     /// into the trusted folder if this flag is set. Certs in the trusted folder must still pass
     /// validity checks.
     trust_unknown_certs: bool,
+    /// Optional cache of successful certificate validations.
+    validation_cache: Option<ValidationCache>,
+    /// Reject certificates that are not self-signed if there is no current revocation
+    /// list signed by their issuer in the CRL folder.
+    strict_revocation_check: bool,
+    /// Cached trusted and issuer certificates and revocation lists.
+    chain_store: Mutex<Option<Arc<ChainStore>>>,
 }
 
 impl CertificateStore {
@@ -58,13 +99,35 @@ impl CertificateStore {
     /// It is a bad idea to have more than one running instance pointing to the same path
     /// location on disk.
     pub fn new(pki_path: &Path) -> CertificateStore {
+        CertificateStore::new_with_fs_backend(FileSystemCertificateStore::new(pki_path))
+    }
+
+    /// Create a certificate store using the given storage backend, for example an
+    /// [`InMemoryCertificateStore`](crate::InMemoryCertificateStore) to avoid any disk I/O.
+    pub fn new_with_backend(backend: impl CertificateStoreBackend + 'static) -> CertificateStore {
+        CertificateStore::new_with_boxed_backend(Box::new(backend), None)
+    }
+
+    /// Create a certificate store on a PKI directory, keeping a copy of the
+    /// backend for the deprecated path getters.
+    fn new_with_fs_backend(backend: FileSystemCertificateStore) -> CertificateStore {
+        let fs_backend = Some(backend.clone());
+        CertificateStore::new_with_boxed_backend(Box::new(backend), fs_backend)
+    }
+
+    fn new_with_boxed_backend(
+        backend: Box<dyn CertificateStoreBackend>,
+        fs_backend: Option<FileSystemCertificateStore>,
+    ) -> CertificateStore {
         CertificateStore {
-            own_certificate_path: PathBuf::from(OWN_CERTIFICATE_PATH),
-            own_private_key_path: PathBuf::from(OWN_PRIVATE_KEY_PATH),
-            pki_path: pki_path.to_path_buf(),
+            backend,
+            fs_backend,
             check_time: true,
             skip_verify_certs: false,
             trust_unknown_certs: false,
+            validation_cache: None,
+            strict_revocation_check: false,
+            chain_store: Mutex::new(None),
         }
     }
 
@@ -80,59 +143,228 @@ impl CertificateStore {
     where
         X: Into<X509Data>,
     {
-        let mut certificate_store = CertificateStore::new(pki_path);
+        let mut backend = FileSystemCertificateStore::new(pki_path);
         if let (Some(cert_path), Some(pkey_path)) = (cert_path, pkey_path) {
-            certificate_store.own_certificate_path = cert_path.to_path_buf();
-            certificate_store.own_private_key_path = pkey_path.to_path_buf();
+            backend.set_own_paths(cert_path, pkey_path);
         }
-        let (cert, pkey) = if certificate_store.ensure_pki_path().is_err() {
+        if backend.ensure_pki_path().is_err() {
             error!("Folder for storing certificates ({pki_path:?}) cannot be examined so server has no application instance certificate or private key.");
-            (None, None)
-        } else {
-            let cert = certificate_store.read_own_cert();
-            let pkey = certificate_store.read_own_pkey();
-            match (cert, pkey, x509_data) {
-                (Ok(cert), Ok(pkey), _) => (Some(cert), Some(pkey)),
-                (_, _, Some(x509_data)) => {
-                    info!("Creating sample application instance certificate and private key");
-                    let x509_data = x509_data.into();
-                    let result = certificate_store
-                        .create_and_store_application_instance_cert(&x509_data, overwrite);
-                    match result {
-                        Ok((cert, pkey)) => (Some(cert), Some(pkey)),
-                        Err(err) => {
-                            error!("Certificate creation failed, error = {}", err);
-                            (None, None)
-                        }
+            return (CertificateStore::new_with_fs_backend(backend), None, None);
+        }
+        let certificate_store = CertificateStore::new_with_fs_backend(backend);
+        let (cert, pkey) = certificate_store.read_or_create_own_cert(overwrite, x509_data);
+        (certificate_store, cert, pkey)
+    }
+
+    /// Read the application certificate and private key from the store. If they cannot be
+    /// read and `x509_data` is given, a new certificate and private key are created and stored.
+    pub fn read_or_create_own_cert<X>(
+        &self,
+        overwrite: bool,
+        x509_data: Option<X>,
+    ) -> (Option<X509>, Option<PrivateKey>)
+    where
+        X: Into<X509Data>,
+    {
+        let cert = self.read_own_cert();
+        let pkey = self.read_own_pkey();
+        match (cert, pkey, x509_data) {
+            (Ok(cert), Ok(pkey), _) => (Some(cert), Some(pkey)),
+            (_, _, Some(x509_data)) => {
+                info!("Creating sample application instance certificate and private key");
+                let x509_data = x509_data.into();
+                let result = self.create_and_store_application_instance_cert(&x509_data, overwrite);
+                match result {
+                    Ok((cert, pkey)) => (Some(cert), Some(pkey)),
+                    Err(err) => {
+                        error!("Certificate creation failed, error = {}", err);
+                        (None, None)
                     }
                 }
-                (Err(e1), Err(e2), _) => {
-                    error!("Failed to get cert and private key: {e1}, {e2}");
-                    (None, None)
-                }
-                (Err(e), _, _) | (_, Err(e), _) => {
-                    error!("Failed to get cert or private key: {e}");
-                    (None, None)
-                }
             }
-        };
-        (certificate_store, cert, pkey)
+            (Err(e1), Err(e2), _) => {
+                error!("Failed to get cert and private key: {e1}, {e2}");
+                (None, None)
+            }
+            (Err(e), _, _) | (_, Err(e), _) => {
+                error!("Failed to get cert or private key: {e}");
+                (None, None)
+            }
+        }
+    }
+
+    /// Get the storage backend of the certificate store.
+    pub fn backend(&self) -> &dyn CertificateStoreBackend {
+        self.backend.as_ref()
+    }
+
+    /// Creates the PKI directory structure. Does nothing if the store is not
+    /// stored on the filesystem.
+    ///
+    /// # Errors
+    ///
+    /// A string description of any failure
+    ///
+    #[deprecated(note = "Use `FileSystemCertificateStore::ensure_pki_path` instead")]
+    pub fn ensure_pki_path(&self) -> Result<(), String> {
+        match &self.fs_backend {
+            Some(backend) => backend.ensure_pki_path(),
+            None => Ok(()),
+        }
+    }
+
+    /// Get path to application instance certificate. This is empty if the store was
+    /// not created on a PKI directory.
+    #[deprecated(note = "Use `FileSystemCertificateStore::own_certificate_path` instead")]
+    pub fn own_certificate_path(&self) -> PathBuf {
+        self.fs_backend
+            .as_ref()
+            .map(|b| b.own_certificate_path())
+            .unwrap_or_default()
+    }
+
+    /// Get path to application instance private key. This is empty if the store was
+    /// not created on a PKI directory.
+    #[deprecated(note = "Use `FileSystemCertificateStore::own_private_key_path` instead")]
+    pub fn own_private_key_path(&self) -> PathBuf {
+        self.fs_backend
+            .as_ref()
+            .map(|b| b.own_private_key_path())
+            .unwrap_or_default()
+    }
+
+    /// Get the path to the rejected certs dir. This is empty if the store was
+    /// not created on a PKI directory.
+    #[deprecated(note = "Use `FileSystemCertificateStore::rejected_certs_dir` instead")]
+    pub fn rejected_certs_dir(&self) -> PathBuf {
+        self.fs_backend
+            .as_ref()
+            .map(|b| b.rejected_certs_dir())
+            .unwrap_or_default()
+    }
+
+    /// Get the path to the trusted certs dir. This is empty if the store was
+    /// not created on a PKI directory.
+    #[deprecated(note = "Use `FileSystemCertificateStore::trusted_certs_dir` instead")]
+    pub fn trusted_certs_dir(&self) -> PathBuf {
+        self.fs_backend
+            .as_ref()
+            .map(|b| b.trusted_certs_dir())
+            .unwrap_or_default()
     }
 
     /// Set `skip_verify_certs` to not verify incoming certificates.
     pub fn set_skip_verify_certs(&mut self, skip_verify_certs: bool) {
         self.skip_verify_certs = skip_verify_certs;
+        self.clear_validation_cache();
     }
 
     /// Set `trust_unknown_certs` to automatically trust valid but
     /// untrusted certificates.
     pub fn set_trust_unknown_certs(&mut self, trust_unknown_certs: bool) {
         self.trust_unknown_certs = trust_unknown_certs;
+        self.clear_validation_cache();
     }
 
     /// Check expiration time of incoming certificates.
     pub fn set_check_time(&mut self, check_time: bool) {
         self.check_time = check_time;
+        self.clear_validation_cache();
+    }
+
+    /// Set `strict_revocation_check` to reject certificates issued by a CA
+    /// if there is no revocation list signed by the CA in the CRL directory, or
+    /// if its next update is overdue. Self-signed certificates are never checked against revocation lists.
+    pub fn set_strict_revocation_check(&mut self, strict_revocation_check: bool) {
+        self.strict_revocation_check = strict_revocation_check;
+        self.clear_validation_cache();
+    }
+
+    /// Cache successful certificate validations for up to `ttl`, keeping at most
+    /// `max_entries` entries. Set `max_entries` to 0 to disable the cache.
+    ///
+    /// Certificates are always checked against the trusted and rejected directories.
+    /// Changes to the issuer and CRL directories made outside of this store are noticed
+    /// when they are next read from the backend, at most 10 seconds later, or immediately
+    /// after calling [`CertificateStore::clear_validation_cache`].
+    pub fn set_validation_cache(&mut self, max_entries: usize, ttl: Duration) {
+        self.validation_cache = (max_entries > 0).then(|| ValidationCache::new(max_entries, ttl));
+    }
+
+    /// Remove all cached certificate validations, and the cached trusted and issuer
+    /// certificates and revocation lists used to verify certificates. These are
+    /// otherwise read from the backend again every 10 seconds.
+    pub fn clear_validation_cache(&self) {
+        self.clear_cached_validations();
+        *self.chain_store.lock() = None;
+    }
+
+    fn clear_cached_validations(&self) {
+        if let Some(cache) = &self.validation_cache {
+            cache.clear();
+        }
+    }
+
+    /// Get the trusted and issuer certificates and the revocation lists, reading
+    /// them from the backend if they have not been read recently.
+    fn chain_store(&self) -> Arc<ChainStore> {
+        let mut cached = self.chain_store.lock();
+        if let Some(store) = cached
+            .as_ref()
+            .filter(|s| s.read_at.elapsed() < CHAIN_STORE_REFRESH)
+        {
+            return store.clone();
+        }
+        let trusted = self.backend.read_certs(CertificateList::Trusted);
+        let issuers = self.backend.read_certs(CertificateList::Issuer);
+        let crls = self
+            .backend
+            .read_crls()
+            .into_iter()
+            .filter(|crl| Self::is_crl_verified(crl, &trusted, &issuers))
+            .collect();
+        let store = Arc::new(ChainStore {
+            trusted,
+            issuers,
+            crls,
+            read_at: Instant::now(),
+        });
+        // Cached validations may depend on certificates or revocation lists that have
+        // since been added or removed.
+        if cached
+            .as_ref()
+            .is_some_and(|old| !old.has_same_contents(&store))
+        {
+            self.clear_cached_validations();
+        }
+        *cached = Some(store.clone());
+        store
+    }
+
+    /// Return `true` if the revocation list was signed by a CA in the trusted or
+    /// issuers directories that is allowed to sign revocation lists. Other lists
+    /// are ignored, since anyone could have created them.
+    fn is_crl_verified(
+        crl: &CertificateRevocationList,
+        trusted: &[X509],
+        issuers: &[X509],
+    ) -> bool {
+        let verified = trusted
+            .iter()
+            .chain(issuers)
+            .any(|ca| ca.is_valid_crl_issuer() && crl.is_signed_by(ca));
+        if !verified {
+            warn!(
+                "Revocation list {:?} is not signed by a known CA and will be ignored",
+                crl
+            );
+        }
+        verified
+    }
+
+    /// Get statistics for the certificate validation cache, if it is enabled.
+    pub fn validation_cache_stats(&self) -> Option<ValidationCacheStats> {
+        self.validation_cache.as_ref().map(|c| c.stats())
     }
 
     /// Reads a private key from a path on disk.
@@ -146,22 +378,12 @@ impl CertificateStore {
 
     /// Reads the store's own certificate
     pub fn read_own_cert(&self) -> Result<X509, String> {
-        CertificateStore::read_cert(&self.own_certificate_path()).map_err(|e| {
-            format!(
-                "Cannot read cert from path {:?}: {e}",
-                self.own_certificate_path()
-            )
-        })
+        self.backend.read_own_cert()
     }
 
-    /// Read own private key from file.
+    /// Read own private key from the store.
     pub fn read_own_pkey(&self) -> Result<PrivateKey, String> {
-        CertificateStore::read_pkey(&self.own_private_key_path()).map_err(|e| {
-            format!(
-                "Cannot read pkey from path {:?}: {e}",
-                self.own_private_key_path()
-            )
-        })
+        self.backend.read_own_pkey()
     }
 
     /// Create a certificate and key pair to the specified locations
@@ -172,9 +394,20 @@ impl CertificateStore {
         pkey_path: &Path,
     ) -> Result<(X509, PrivateKey), String> {
         let (cert, pkey) = X509::cert_and_pkey(args)?;
+        CertificateStore::write_certificate_and_key(&cert, &pkey, overwrite, cert_path, pkey_path)?;
+        Ok((cert, pkey))
+    }
 
+    /// Write a certificate and private key to the specified locations
+    pub(crate) fn write_certificate_and_key(
+        cert: &X509,
+        pkey: &PrivateKey,
+        overwrite: bool,
+        cert_path: &Path,
+        pkey_path: &Path,
+    ) -> Result<(), String> {
         // Write the public cert
-        let _ = CertificateStore::store_cert(&cert, cert_path, overwrite)?;
+        let _ = CertificateStore::store_cert(cert, cert_path, overwrite)?;
 
         // Write the private key
         use rsa::pkcs8;
@@ -184,23 +417,21 @@ impl CertificateStore {
             .to_pem(rsa::pkcs8::PrivateKeyInfo::PEM_LABEL, pkcs8::LineEnding::CR)
             .unwrap();
         let _ = CertificateStore::write_to_file(pem.as_bytes(), pkey_path, overwrite)?;
-        Ok((cert, pkey))
+        Ok(())
     }
 
     /// This function will use the supplied arguments to create an Application Instance Certificate
     /// consisting of a X509v3 certificate and public/private key pair. The cert (including pubkey)
-    /// and private key will be written to disk under the pki path.
+    /// and private key will be written to the store's backend.
     pub fn create_and_store_application_instance_cert(
         &self,
         args: &X509Data,
         overwrite: bool,
     ) -> Result<(X509, PrivateKey), String> {
-        CertificateStore::create_certificate_and_key(
-            args,
-            overwrite,
-            &self.own_certificate_path(),
-            &self.own_private_key_path(),
-        )
+        let (cert, pkey) = X509::cert_and_pkey(args)?;
+        self.backend
+            .store_own_cert_and_pkey(&cert, &pkey, overwrite)?;
+        Ok((cert, pkey))
     }
 
     /// Validates the cert as trusted and valid. If the cert is unknown, it will be written to
@@ -221,45 +452,15 @@ impl CertificateStore {
         self.validate_application_instance_cert(cert, security_policy, hostname, application_uri)
     }
 
-    /// Ensures that the cert provided is the same as the one specified by a path. This is a
+    /// Ensures that the cert provided is the same as the one found in the store. This is a
     /// security check to stop someone from renaming a cert on disk to match another cert and
-    /// somehow bypassing or subverting a check. The disk cert must exactly match the memory cert
+    /// somehow bypassing or subverting a check. The stored cert must exactly match the memory cert
     /// or the test is assumed to fail.
-    fn ensure_cert_and_file_are_the_same(cert: &X509, cert_path: &Path) -> bool {
-        if !cert_path.exists() {
-            trace!("Cannot find cert on disk");
-            false
-        } else {
-            match CertificateStore::read_cert(cert_path) {
-                Ok(file_der) => {
-                    // Compare the buffers
-                    trace!("Comparing cert on disk to memory");
-                    let der;
-                    {
-                        let r = cert.to_der();
-                        match r {
-                            Err(_) => return false,
-                            Ok(val) => der = val,
-                        }
-                    }
-
-                    let target_der;
-                    {
-                        let r = file_der.to_der();
-                        match r {
-                            Err(_) => return false,
-                            Ok(val) => target_der = val,
-                        }
-                    }
-
-                    der == target_der
-                }
-                Err(err) => {
-                    trace!("Cannot read cert from disk {:?} - {}", cert_path, err);
-                    // No cert2 to compare to
-                    false
-                }
-            }
+    fn is_same_cert(cert: &X509, stored: &X509) -> bool {
+        trace!("Comparing stored cert to memory");
+        match (cert.to_der(), stored.to_der()) {
+            (Ok(der), Ok(stored_der)) => der == stored_der,
+            _ => false,
         }
     }
 
@@ -281,61 +482,140 @@ impl CertificateStore {
         application_uri: Option<&str>,
     ) -> Result<(), StatusCode> {
         let cert_file_name = CertificateStore::cert_file_name(cert);
-        debug!("Validating cert with name on disk {}", cert_file_name);
+        let thumbprint = cert.thumbprint_sha256();
+        debug!(
+            "Validating cert with name on disk {}, SHA-256 thumbprint {}",
+            cert_file_name, thumbprint
+        );
 
         // Look for the cert in the rejected folder. If it's rejected there is no purpose going
         // any further
-        {
-            let mut cert_path = self.rejected_certs_dir();
-            if !cert_path.exists() {
-                error!(
-                    "Path for rejected certificates {} does not exist",
-                    cert_path.display()
-                );
+        match self.backend.find_cert(CertificateList::Rejected, cert) {
+            Err(e) => {
+                error!("{}", e);
                 return Err(StatusCode::BadUnexpectedError);
             }
-            cert_path.push(&cert_file_name);
-            if cert_path.exists() {
+            Ok(Some(_)) => {
                 warn!(
                     "Certificate {} is untrusted because it resides in the rejected directory",
                     cert_file_name
                 );
                 return Err(StatusCode::BadSecurityChecksFailed);
             }
+            Ok(None) => (),
         }
 
-        // Check the trusted folder. These checks are more strict to ensure the cert is genuinely
-        // trusted
-        {
-            // Check the trusted folder
-            let mut cert_path = self.trusted_certs_dir();
-            if !cert_path.exists() {
-                error!(
-                    "Path for rejected certificates {} does not exist",
-                    cert_path.display()
-                );
+        // Check the trusted folder
+        let stored = match self.backend.find_cert(CertificateList::Trusted, cert) {
+            Err(e) => {
+                error!("{}", e);
                 return Err(StatusCode::BadUnexpectedError);
             }
-            cert_path.push(&cert_file_name);
+            Ok(stored) => stored,
+        };
 
+        let Some(cache) = &self.validation_cache else {
+            return self.validate_trust(
+                cert,
+                &cert_file_name,
+                stored,
+                security_policy,
+                hostname,
+                application_uri,
+            );
+        };
+
+        // Refreshing the chain store drops the cached validations if the trusted or
+        // issuer certificates or the revocation lists have changed.
+        self.chain_store();
+        let directly_trusted = stored
+            .as_ref()
+            .is_some_and(|stored| CertificateStore::is_same_cert(cert, stored));
+        if cache.check(
+            &thumbprint,
+            directly_trusted,
+            security_policy,
+            hostname,
+            application_uri,
+        ) {
+            debug!(
+                "Certificate with thumbprint {} was found in the validation cache",
+                thumbprint
+            );
+            return Ok(());
+        }
+        self.validate_trust(
+            cert,
+            &cert_file_name,
+            stored,
+            security_policy,
+            hostname,
+            application_uri,
+        )?;
+        cache.insert(
+            cert,
+            &thumbprint,
+            directly_trusted,
+            security_policy,
+            hostname,
+            application_uri,
+        );
+        Ok(())
+    }
+
+    /// Validate a certificate that is not in the rejected folder. `stored` is the
+    /// matching certificate in the trusted folder, if any.
+    fn validate_trust(
+        &self,
+        cert: &X509,
+        cert_file_name: &str,
+        mut stored: Option<X509>,
+        security_policy: SecurityPolicy,
+        hostname: Option<&str>,
+        application_uri: Option<&str>,
+    ) -> Result<(), StatusCode> {
+        // Check the trusted folder. These checks are more strict to ensure the cert is genuinely
+        // trusted
+        {
             // Check if cert is in the trusted folder
-            if !cert_path.exists() {
-                // ... trust checks based on ca could be added here to add cert straight to trust folder
-                if self.trust_unknown_certs {
-                    // Put the unknown cert into the trusted folder
-                    warn!("Certificate {} is unknown but policy will store it into the trusted directory", cert_file_name);
-                    let _ = self.store_trusted_cert(cert);
-                // Note that we drop through and still check the cert for validity
+            let mut trusted_by_issuer = false;
+            if stored.is_none() {
+                // A cert that is not trusted itself is still trusted if it was issued by a trusted CA
+                let chain = if cert.is_self_issued() {
+                    Err(StatusCode::BadCertificateUntrusted)
                 } else {
-                    warn!("Certificate {} is unknown and untrusted so it will be stored in rejected directory", cert_file_name);
-                    let _ = self.store_rejected_cert(cert);
-                    return Err(StatusCode::BadCertificateUntrusted);
+                    self.verify_chain(cert, cert_file_name)
+                };
+                match chain {
+                    Ok(()) => trusted_by_issuer = true,
+                    Err(e)
+                        if self.trust_unknown_certs
+                            && (e == StatusCode::BadCertificateUntrusted
+                                || e == StatusCode::BadCertificateChainIncomplete) =>
+                    {
+                        // Put the unknown cert into the trusted folder
+                        warn!("Certificate {} is unknown but policy will store it into the trusted directory", cert_file_name);
+                        let _ = self.store_trusted_cert(cert);
+                        stored = self
+                            .backend
+                            .find_cert(CertificateList::Trusted, cert)
+                            .ok()
+                            .flatten();
+                        // Note that we drop through and still check the cert for validity
+                    }
+                    Err(e) => {
+                        warn!("Certificate {} is unknown and untrusted so it will be stored in rejected directory", cert_file_name);
+                        let _ = self.store_rejected_cert(cert);
+                        return Err(e);
+                    }
                 }
             }
 
             // Read the cert from the trusted folder to make sure it matches the one supplied
-            if !CertificateStore::ensure_cert_and_file_are_the_same(cert, &cert_path) {
-                error!("Certificate in memory does not match the one on disk {} so cert will automatically be treated as untrusted", cert_path.display());
+            if !trusted_by_issuer
+                && !stored.is_some_and(|stored| CertificateStore::is_same_cert(cert, &stored))
+            {
+                error!("Certificate in memory does not match the stored certificate {} so cert will automatically be treated as untrusted", cert_file_name);
                 return Err(StatusCode::BadUnexpectedError);
             }
 
@@ -356,6 +636,8 @@ impl CertificateStore {
                 }
             }
 
+            self.check_revocation(cert, cert_file_name)?;
+
             if self.skip_verify_certs {
                 debug!(
                     "Skipping additional verifications for certificate {}",
@@ -380,15 +662,155 @@ impl CertificateStore {
             if let Some(application_uri) = application_uri {
                 cert.is_application_uri_valid(application_uri)?;
             }
+        }
+        Ok(())
+    }
 
-            // Other tests that we might do with trust lists
-            // ... issuer
-            // ... trust (self-signed, ca etc.)
-            // ... revocation
+    /// Check the certificate against the revocation lists in the CRL directory.
+    ///
+    /// Only revocation lists signed by a CA in the trusted or issuers directories
+    /// are used. Expired lists still revoke the certificates they contain, but do
+    /// not satisfy the strict revocation check.
+    fn check_revocation(&self, cert: &X509, cert_file_name: &str) -> Result<(), StatusCode> {
+        if cert.is_self_issued() {
+            return Ok(());
+        }
+
+        let store = self.chain_store();
+        if store.crls.iter().any(|crl| crl.is_revoked(cert)) {
+            warn!(
+                "Certificate {} has been revoked by its issuer",
+                cert_file_name
+            );
+            return Err(StatusCode::BadCertificateRevoked);
+        }
+        if self.strict_revocation_check && !store.has_current_crl_for(cert, SystemTime::now()) {
+            warn!(
+                "No current revocation list found for the issuer of certificate {}",
+                cert_file_name
+            );
+            return Err(StatusCode::BadCertificateRevocationUnknown);
         }
         Ok(())
     }
 
+    /// Build a chain from the certificate to a CA in the trusted directory, using
+    /// the CA certificates in the trusted and issuers directories.
+    ///
+    /// Every issuer in the chain must be allowed to sign certificates, be valid
+    /// at the current time and not be revoked. If several certificates could have
+    /// issued a certificate in the chain, each of them is tried in turn.
+    fn verify_chain(&self, cert: &X509, cert_file_name: &str) -> Result<(), StatusCode> {
+        let chain = ChainContext {
+            store: self.chain_store(),
+            cert_file_name,
+        };
+        self.verify_chain_from(&chain, cert, &mut Vec::new())
+    }
+
+    /// Try to complete the chain of `current` through each of its candidate issuers,
+    /// returning the error for the first candidate if none of them lead to a trusted CA.
+    /// `path` holds the issuers already in the chain.
+    fn verify_chain_from<'a>(
+        &self,
+        chain: &'a ChainContext,
+        current: &X509,
+        path: &mut Vec<&'a X509>,
+    ) -> Result<(), StatusCode> {
+        let cert_file_name = chain.cert_file_name;
+        if path.len() >= MAX_CHAIN_LENGTH {
+            warn!(
+                "The chain of certificate {} is longer than {} certificates",
+                cert_file_name, MAX_CHAIN_LENGTH
+            );
+            return Err(StatusCode::BadCertificateChainIncomplete);
+        }
+
+        // Prefer trusted issuers, so the chain ends as early as possible.
+        let candidates: Vec<_> = chain
+            .store
+            .trusted
+            .iter()
+            .map(|c| (c, true))
+            .chain(chain.store.issuers.iter().map(|c| (c, false)))
+            .filter(|(c, _)| {
+                c.subject() == current.issuer_name()
+                    && !path.iter().any(|p| std::ptr::eq(*p, *c))
+                    && current.is_signed_by(c)
+            })
+            .collect();
+
+        let mut first_error = None;
+        for (issuer, is_trusted) in candidates {
+            path.push(issuer);
+            let result = self.verify_issuer(chain, issuer, is_trusted, path);
+            path.pop();
+            match result {
+                Ok(()) => return Ok(()),
+                Err(e) => {
+                    first_error.get_or_insert(e);
+                }
+            }
+        }
+
+        Err(first_error.unwrap_or_else(|| {
+            warn!(
+                "Could not find an issuer for the chain of certificate {}",
+                cert_file_name
+            );
+            StatusCode::BadCertificateChainIncomplete
+        }))
+    }
+
+    /// Check an issuer in the chain of a certificate, and continue the chain
+    /// from it if it is not trusted itself.
+    fn verify_issuer<'a>(
+        &self,
+        chain: &'a ChainContext,
+        issuer: &'a X509,
+        is_trusted: bool,
+        path: &mut Vec<&'a X509>,
+    ) -> Result<(), StatusCode> {
+        let cert_file_name = chain.cert_file_name;
+        let issuer_file_name = CertificateStore::cert_file_name(issuer);
+
+        if !issuer.is_valid_issuer() {
+            warn!(
+                "Certificate {} in the chain of certificate {} is not allowed to sign certificates",
+                issuer_file_name, cert_file_name
+            );
+            return Err(StatusCode::BadCertificateIssuerUseNotAllowed);
+        }
+
+        if self.check_time && !self.skip_verify_certs {
+            use chrono::Utc;
+            let now = Utc::now();
+            if issuer.is_time_valid(&now).is_err() {
+                return Err(StatusCode::BadCertificateIssuerTimeInvalid);
+            }
+        }
+
+        if !issuer.is_self_issued() && chain.store.crls.iter().any(|crl| crl.is_revoked(issuer)) {
+            warn!(
+                "Certificate {} in the chain of certificate {} has been revoked",
+                issuer_file_name, cert_file_name
+            );
+            return Err(StatusCode::BadCertificateIssuerRevoked);
+        }
+
+        if is_trusted {
+            return Ok(());
+        }
+        if issuer.is_self_issued() {
+            warn!(
+                "Root certificate {} in the chain of certificate {} is not trusted",
+                issuer_file_name, cert_file_name
+            );
+            return Err(StatusCode::BadCertificateUntrusted);
+        }
+        self.verify_chain_from(chain, issuer, path)
+    }
+
     /// Returns a certificate file name from the cert's issuer and thumbprint fields.
     /// File name is either "prefix - \[thumbprint\].der" or "thumbprint.der" depending on
     /// the cert's common name being empty or not
@@ -407,30 +829,13 @@ impl CertificateStore {
         }
     }
 
-    /// Creates the PKI directory structure
-    ///
-    /// # Errors
-    ///
-    /// A string description of any failure
-    ///
-    pub fn ensure_pki_path(&self) -> Result<(), String> {
-        let mut path = self.pki_path.clone();
-        let subdirs = [TRUSTED_CERTS_DIR, REJECTED_CERTS_DIR];
-        for subdir in &subdirs {
-            path.push(subdir);
-            CertificateStore::ensure_dir(&path)?;
-            path.pop();
-        }
-        Ok(())
-    }
-
     /// Ensure the directory exists, creating it if necessary
     ///
     /// # Errors
     ///
     /// A string description of any failure
     ///
-    fn ensure_dir(path: &Path) -> Result<(), String> {
+    pub(crate) fn ensure_dir(path: &Path) -> Result<(), String> {
         if path.exists() {
             if !path.is_dir() {
                 Err(format!("{} is not a directory ", path.display()))
@@ -443,64 +848,28 @@ impl CertificateStore {
         }
     }
 
-    /// Get path to application instance certificate
-    pub fn own_certificate_path(&self) -> PathBuf {
-        let mut path = PathBuf::from(&self.pki_path);
-        path.push(&self.own_certificate_path);
-        path
-    }
-
-    /// Get path to application instance private key
-    pub fn own_private_key_path(&self) -> PathBuf {
-        let mut path = PathBuf::from(&self.pki_path);
-        path.push(&self.own_private_key_path);
-        path
-    }
-
-    /// Get the path to the rejected certs dir
-    pub fn rejected_certs_dir(&self) -> PathBuf {
-        let mut path = PathBuf::from(&self.pki_path);
-        path.push(REJECTED_CERTS_DIR);
-        path
-    }
-
-    /// Get the path to the trusted certs dir
-    pub fn trusted_certs_dir(&self) -> PathBuf {
-        let mut path = PathBuf::from(&self.pki_path);
-        path.push(TRUSTED_CERTS_DIR);
-        path
-    }
-
-    /// Write a cert to the rejected directory. If the write succeeds, the function
-    /// returns a path to the written file.
+    /// Store a cert in the rejected list, where untrusted certs go.
     ///
     /// # Errors
     ///
     /// A string description of any failure
     ///
-    pub fn store_rejected_cert(&self, cert: &X509) -> Result<PathBuf, String> {
-        // Store the cert in the rejected folder where untrusted certs go
-        let cert_file_name = CertificateStore::cert_file_name(cert);
-        let mut cert_path = self.rejected_certs_dir();
-        cert_path.push(&cert_file_name);
-        let _ = CertificateStore::store_cert(cert, &cert_path, true)?;
-        Ok(cert_path)
+    pub fn store_rejected_cert(&self, cert: &X509) -> Result<(), String> {
+        self.backend.store_cert(CertificateList::Rejected, cert)?;
+        self.clear_cached_validations();
+        Ok(())
     }
 
-    /// Writes a cert to the trusted directory. If the write succeeds, the function
-    /// returns a path to the written file.
+    /// Store a cert in the trusted list.
     ///
     /// # Errors
     ///
     /// A string description of any failure
     ///
-    fn store_trusted_cert(&self, cert: &X509) -> Result<PathBuf, String> {
-        // Store the cert in the trusted folder where trusted certs go
-        let cert_file_name = CertificateStore::cert_file_name(cert);
-        let mut cert_path = self.trusted_certs_dir();
-        cert_path.push(&cert_file_name);
-        let _ = CertificateStore::store_cert(cert, &cert_path, true)?;
-        Ok(cert_path)
+    pub fn store_trusted_cert(&self, cert: &X509) -> Result<(), String> {
+        self.backend.store_cert(CertificateList::Trusted, cert)?;
+        self.clear_validation_cache();
+        Ok(())
     }
 
     /// Writes a cert to the specified directory
@@ -509,7 +878,7 @@ impl CertificateStore {
     ///
     /// A string description of any failure
     ///
-    fn store_cert(cert: &X509, path: &Path, overwrite: bool) -> Result<usize, String> {
+    pub(crate) fn store_cert(cert: &X509, path: &Path, overwrite: bool) -> Result<usize, String> {
         let der = cert.to_der().unwrap();
         info!("Writing X509 cert to {}", path.display());
         CertificateStore::write_to_file(&der, path, overwrite)
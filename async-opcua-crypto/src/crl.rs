@@ -0,0 +1,101 @@
+// OPCUA for Rust
+// SPDX-License-Identifier: MPL-2.0
+// Copyright (C) 2017-2024 Adam Lock
+
+//! Certificate revocation lists, used to reject certificates that have been
+//! revoked by their issuer.
+
+use std::{path::Path, time::SystemTime};
+
+use x509_cert::{self as x509, der::Decode};
+
+use super::x509::{X509Error, X509};
+
+/// Wrapper around an X509 certificate revocation list.
+#[derive(Clone, PartialEq, Eq)]
+pub struct CertificateRevocationList {
+    value: x509::crl::CertificateList,
+}
+
+impl std::fmt::Debug for CertificateRevocationList {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[crl issuer={}]", self.value.tbs_cert_list.issuer)
+    }
+}
+
+impl CertificateRevocationList {
+    /// Load a certificate revocation list from DER encoded data.
+    pub fn from_der(data: &[u8]) -> Result<Self, X509Error> {
+        let value = x509::crl::CertificateList::from_der(data)?;
+        Ok(Self { value })
+    }
+
+    /// Load a certificate revocation list from PEM encoded data.
+    pub fn from_pem(data: &[u8]) -> Result<Self, X509Error> {
+        use x509::der::{PemReader, Reader};
+
+        let mut reader = PemReader::new(data)?;
+        let value = x509::crl::CertificateList::decode(&mut reader)?;
+        let value = reader.finish(value)?;
+        Ok(Self { value })
+    }
+
+    /// Read a certificate revocation list from a file, which may be either
+    /// DER or PEM encoded.
+    pub fn read_file(path: &Path) -> Result<Self, String> {
+        let data = std::fs::read(path)
+            .map_err(|e| format!("Could not read CRL file {}: {e}", path.display()))?;
+        if data.starts_with(b"-----BEGIN") {
+            Self::from_pem(&data)
+        } else {
+            Self::from_der(&data)
+        }
+        .map_err(|_| format!("Could not parse CRL file {}", path.display()))
+    }
+
+    /// Return `true` if the signature on this list was made with the key of `issuer`,
+    /// and `issuer` is the CA named as the issuer of the list.
+    pub fn is_signed_by(&self, issuer: &X509) -> bool {
+        use x509::der::Encode;
+
+        if &self.value.tbs_cert_list.issuer != issuer.subject() {
+            return false;
+        }
+        let Ok(tbs) = self.value.tbs_cert_list.to_der() else {
+            return false;
+        };
+        issuer.verify_signature(
+            &self.value.signature_algorithm,
+            &tbs,
+            self.value.signature.raw_bytes(),
+        )
+    }
+
+    /// Return `true` if the next update of this list is due before `now`, meaning
+    /// that the issuer may have revoked certificates that are not in the list.
+    /// Lists without a next update time never expire.
+    pub fn is_expired(&self, now: SystemTime) -> bool {
+        self.value
+            .tbs_cert_list
+            .next_update
+            .is_some_and(|next_update| next_update.to_system_time() < now)
+    }
+
+    /// Return `true` if this list was issued by the issuer of `cert`.
+    pub fn is_issuer_of(&self, cert: &X509) -> bool {
+        &self.value.tbs_cert_list.issuer == cert.issuer_name()
+    }
+
+    /// Return `true` if this list was issued by the issuer of `cert`, and
+    /// contains the serial number of `cert`.
+    pub fn is_revoked(&self, cert: &X509) -> bool {
+        self.is_issuer_of(cert)
+            && self
+                .value
+                .tbs_cert_list
+                .revoked_certificates
+                .iter()
+                .flatten()
+                .any(|r| &r.serial_number == cert.serial_number())
+    }
+}
@@ -13,8 +13,8 @@ use opcua_types::{
 };
 use tracing::{error, trace};
 pub use {
-    aeskey::*, certificate_store::*, hash::*, pkey::*, security_policy::*, thumbprint::*,
-    user_identity::*, x509::*,
+    aeskey::*, certificate_store::*, crl::*, hash::*, pkey::*, security_policy::*,
+    store_backend::*, thumbprint::*, user_identity::*, validation_cache::*, x509::*,
 };
 
 #[cfg(test)]
@@ -22,12 +22,15 @@ mod tests;
 
 pub mod aeskey;
 pub mod certificate_store;
+pub mod crl;
 pub mod hash;
 pub mod pkey;
 pub mod random;
 pub mod security_policy;
+pub mod store_backend;
 pub mod thumbprint;
 pub mod user_identity;
+pub mod validation_cache;
 pub mod x509;
 
 /// Size of a SHA1 hash value in bytes
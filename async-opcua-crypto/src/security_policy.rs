@@ -173,7 +173,7 @@ impl SecurityPolicyConstants for Basic256 {
 
 /// SecurityPolicy implies what encryption and signing algorithms and their relevant key strengths
 /// are used during an encrypted session.
-#[derive(Debug, Clone, PartialEq, Copy)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Copy)]
 pub enum SecurityPolicy {
     /// Security policy is unknown, this is generally an error.
     Unknown,
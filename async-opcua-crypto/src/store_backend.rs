@@ -0,0 +1,417 @@
+// OPCUA for Rust
+// SPDX-License-Identifier: MPL-2.0
+// Copyright (C) 2017-2024 Adam Lock
+
+//! Storage backends for the [`CertificateStore`]. The default backend keeps everything in
+//! a PKI directory on disk, the in-memory backend is useful for tests and short lived
+//! applications that should not write anything to disk.
+
+use std::path::{Path, PathBuf};
+
+use parking_lot::RwLock;
+use tracing::error;
+
+use super::{
+    certificate_store::CertificateStore, crl::CertificateRevocationList, pkey::PrivateKey,
+    x509::X509,
+};
+
+/// Default path to the applications own certificate
+const OWN_CERTIFICATE_PATH: &str = "own/cert.der";
+/// Default path to the applications own private key
+const OWN_PRIVATE_KEY_PATH: &str = "private/private.pem";
+/// The directory holding trusted certificates
+const TRUSTED_CERTS_DIR: &str = "trusted";
+/// The directory holding rejected certificates
+const REJECTED_CERTS_DIR: &str = "rejected";
+/// The directory holding certificate revocation lists
+const CRL_DIR: &str = "crl";
+/// The directory holding CA certificates used to build certificate chains
+const ISSUER_CERTS_DIR: &str = "issuers";
+
+/// The lists of certificates kept by a certificate store backend.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CertificateList {
+    /// Certificates that are trusted, either directly or as the CA of other certificates.
+    Trusted,
+    /// Certificates that have been rejected, kept so they can be reviewed and moved
+    /// to the trusted list.
+    Rejected,
+    /// Intermediate CA certificates used to build the chain of a certificate to a trusted CA.
+    Issuer,
+}
+
+/// Storage for the certificates, private key and revocation lists used by a [`CertificateStore`].
+pub trait CertificateStoreBackend: Send + Sync {
+    /// Read the application's own certificate.
+    fn read_own_cert(&self) -> Result<X509, String>;
+
+    /// Read the application's own private key.
+    fn read_own_pkey(&self) -> Result<PrivateKey, String>;
+
+    /// Store the application's own certificate and private key. Unless `overwrite` is
+    /// set, this fails if a certificate or private key is already stored.
+    fn store_own_cert_and_pkey(
+        &self,
+        cert: &X509,
+        pkey: &PrivateKey,
+        overwrite: bool,
+    ) -> Result<(), String>;
+
+    /// Find the certificate stored in `list` under the same name as `cert`.
+    ///
+    /// The stored certificate is returned as-is, callers must check that it is
+    /// identical to `cert`. Fails if the list cannot be read at all.
+    fn find_cert(&self, list: CertificateList, cert: &X509) -> Result<Option<X509>, String>;
+
+    /// Read all certificates in `list`. Certificates that cannot be read are skipped.
+    fn read_certs(&self, list: CertificateList) -> Vec<X509>;
+
+    /// Store a certificate in `list`, replacing any certificate with the same name.
+    fn store_cert(&self, list: CertificateList, cert: &X509) -> Result<(), String>;
+
+    /// Read all certificate revocation lists. Lists that cannot be read are skipped.
+    fn read_crls(&self) -> Vec<CertificateRevocationList>;
+}
+
+/// Certificate store backend using a PKI directory on disk, with the following layout:
+///
+/// ```text
+/// pki/
+///   own/cert.der        - the application's own certificate
+///   private/private.pem - the application's own private key
+///   trusted/            - trusted certificates
+///   rejected/           - rejected certificates
+///   issuers/            - intermediate CA certificates
+///   crl/                - certificate revocation lists, with the `.crl` extension
+/// ```
+///
+/// Certificates are stored in files named by [`CertificateStore::cert_file_name`].
+#[derive(Debug, Clone)]
+pub struct FileSystemCertificateStore {
+    /// Path to the certificate store on disk
+    pki_path: PathBuf,
+    /// Path to the applications own certificate
+    own_certificate_path: PathBuf,
+    /// Path to the applications own private key
+    own_private_key_path: PathBuf,
+}
+
+impl FileSystemCertificateStore {
+    /// Create a backend using the PKI directory at `pki_path`.
+    /// It is a bad idea to have more than one running instance pointing to the same path
+    /// location on disk.
+    pub fn new(pki_path: &Path) -> Self {
+        Self {
+            pki_path: pki_path.to_path_buf(),
+            own_certificate_path: PathBuf::from(OWN_CERTIFICATE_PATH),
+            own_private_key_path: PathBuf::from(OWN_PRIVATE_KEY_PATH),
+        }
+    }
+
+    /// Set the paths of the applications own certificate and private key,
+    /// relative to the PKI directory.
+    pub fn set_own_paths(&mut self, cert_path: &Path, pkey_path: &Path) {
+        self.own_certificate_path = cert_path.to_path_buf();
+        self.own_private_key_path = pkey_path.to_path_buf();
+    }
+
+    /// Creates the PKI directory structure
+    ///
+    /// # Errors
+    ///
+    /// A string description of any failure
+    ///
+    pub fn ensure_pki_path(&self) -> Result<(), String> {
+        let mut path = self.pki_path.clone();
+        let subdirs = [
+            TRUSTED_CERTS_DIR,
+            REJECTED_CERTS_DIR,
+            CRL_DIR,
+            ISSUER_CERTS_DIR,
+        ];
+        for subdir in &subdirs {
+            path.push(subdir);
+            CertificateStore::ensure_dir(&path)?;
+            path.pop();
+        }
+        Ok(())
+    }
+
+    /// Get the path to the PKI directory
+    pub fn pki_path(&self) -> &Path {
+        &self.pki_path
+    }
+
+    /// Get path to application instance certificate
+    pub fn own_certificate_path(&self) -> PathBuf {
+        let mut path = PathBuf::from(&self.pki_path);
+        path.push(&self.own_certificate_path);
+        path
+    }
+
+    /// Get path to application instance private key
+    pub fn own_private_key_path(&self) -> PathBuf {
+        let mut path = PathBuf::from(&self.pki_path);
+        path.push(&self.own_private_key_path);
+        path
+    }
+
+    /// Get the path to the rejected certs dir
+    pub fn rejected_certs_dir(&self) -> PathBuf {
+        let mut path = PathBuf::from(&self.pki_path);
+        path.push(REJECTED_CERTS_DIR);
+        path
+    }
+
+    /// Get the path to the trusted certs dir
+    pub fn trusted_certs_dir(&self) -> PathBuf {
+        let mut path = PathBuf::from(&self.pki_path);
+        path.push(TRUSTED_CERTS_DIR);
+        path
+    }
+
+    /// Get the path to the issuer certs dir
+    pub fn issuer_certs_dir(&self) -> PathBuf {
+        let mut path = PathBuf::from(&self.pki_path);
+        path.push(ISSUER_CERTS_DIR);
+        path
+    }
+
+    /// Get the path to the certificate revocation list dir
+    pub fn crl_dir(&self) -> PathBuf {
+        let mut path = PathBuf::from(&self.pki_path);
+        path.push(CRL_DIR);
+        path
+    }
+
+    /// Get the path to the directory holding `list`.
+    pub fn list_dir(&self, list: CertificateList) -> PathBuf {
+        match list {
+            CertificateList::Trusted => self.trusted_certs_dir(),
+            CertificateList::Rejected => self.rejected_certs_dir(),
+            CertificateList::Issuer => self.issuer_certs_dir(),
+        }
+    }
+
+    /// Get the path a certificate is stored at in `list`.
+    pub fn cert_path(&self, list: CertificateList, cert: &X509) -> PathBuf {
+        let mut path = self.list_dir(list);
+        path.push(CertificateStore::cert_file_name(cert));
+        path
+    }
+}
+
+impl CertificateStoreBackend for FileSystemCertificateStore {
+    fn read_own_cert(&self) -> Result<X509, String> {
+        CertificateStore::read_cert(&self.own_certificate_path()).map_err(|e| {
+            format!(
+                "Cannot read cert from path {:?}: {e}",
+                self.own_certificate_path()
+            )
+        })
+    }
+
+    fn read_own_pkey(&self) -> Result<PrivateKey, String> {
+        CertificateStore::read_pkey(&self.own_private_key_path()).map_err(|e| {
+            format!(
+                "Cannot read pkey from path {:?}: {e}",
+                self.own_private_key_path()
+            )
+        })
+    }
+
+    fn store_own_cert_and_pkey(
+        &self,
+        cert: &X509,
+        pkey: &PrivateKey,
+        overwrite: bool,
+    ) -> Result<(), String> {
+        CertificateStore::write_certificate_and_key(
+            cert,
+            pkey,
+            overwrite,
+            &self.own_certificate_path(),
+            &self.own_private_key_path(),
+        )
+    }
+
+    fn find_cert(&self, list: CertificateList, cert: &X509) -> Result<Option<X509>, String> {
+        let dir = self.list_dir(list);
+        if !dir.exists() {
+            return Err(format!(
+                "Path for {:?} certificates {} does not exist",
+                list,
+                dir.display()
+            ));
+        }
+        let cert_path = self.cert_path(list, cert);
+        if !cert_path.exists() {
+            return Ok(None);
+        }
+        CertificateStore::read_cert(&cert_path).map(Some)
+    }
+
+    fn read_certs(&self, list: CertificateList) -> Vec<X509> {
+        let Ok(entries) = std::fs::read_dir(self.list_dir(list)) else {
+            return Vec::new();
+        };
+        entries
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .filter(|p| p.is_file())
+            .filter_map(|p| match CertificateStore::read_cert(&p) {
+                Ok(cert) => Some(cert),
+                Err(e) => {
+                    error!("{}", e);
+                    None
+                }
+            })
+            .collect()
+    }
+
+    fn store_cert(&self, list: CertificateList, cert: &X509) -> Result<(), String> {
+        let _ = CertificateStore::store_cert(cert, &self.cert_path(list, cert), true)?;
+        Ok(())
+    }
+
+    fn read_crls(&self) -> Vec<CertificateRevocationList> {
+        let Ok(entries) = std::fs::read_dir(self.crl_dir()) else {
+            return Vec::new();
+        };
+        entries
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .filter(|p| p.extension().is_some_and(|e| e == "crl"))
+            .filter_map(|p| match CertificateRevocationList::read_file(&p) {
+                Ok(crl) => Some(crl),
+                Err(e) => {
+                    error!("{}", e);
+                    None
+                }
+            })
+            .collect()
+    }
+}
+
+#[derive(Default)]
+struct InMemoryCertificates {
+    own_cert: Option<X509>,
+    own_pkey: Option<PrivateKey>,
+    trusted: Vec<X509>,
+    rejected: Vec<X509>,
+    issuers: Vec<X509>,
+    crls: Vec<CertificateRevocationList>,
+}
+
+impl InMemoryCertificates {
+    fn list(&self, list: CertificateList) -> &Vec<X509> {
+        match list {
+            CertificateList::Trusted => &self.trusted,
+            CertificateList::Rejected => &self.rejected,
+            CertificateList::Issuer => &self.issuers,
+        }
+    }
+
+    fn list_mut(&mut self, list: CertificateList) -> &mut Vec<X509> {
+        match list {
+            CertificateList::Trusted => &mut self.trusted,
+            CertificateList::Rejected => &mut self.rejected,
+            CertificateList::Issuer => &mut self.issuers,
+        }
+    }
+}
+
+/// Certificate store backend keeping everything in memory, so nothing is ever
+/// written to disk. Certificates are identified by their thumbprint.
+///
+/// Anything stored here is lost when the store is dropped, so certificates that are
+/// trusted automatically must be trusted again the next time the application runs.
+#[derive(Default)]
+pub struct InMemoryCertificateStore {
+    inner: RwLock<InMemoryCertificates>,
+}
+
+impl InMemoryCertificateStore {
+    /// Create a new, empty in-memory backend.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a new in-memory backend with the given application certificate and private key.
+    pub fn new_with_own_cert(cert: X509, pkey: PrivateKey) -> Self {
+        let store = Self::new();
+        {
+            let mut inner = store.inner.write();
+            inner.own_cert = Some(cert);
+            inner.own_pkey = Some(pkey);
+        }
+        store
+    }
+
+    /// Add a certificate revocation list.
+    pub fn add_crl(&self, crl: CertificateRevocationList) {
+        self.inner.write().crls.push(crl);
+    }
+}
+
+impl CertificateStoreBackend for InMemoryCertificateStore {
+    fn read_own_cert(&self) -> Result<X509, String> {
+        self.inner
+            .read()
+            .own_cert
+            .clone()
+            .ok_or_else(|| "No application certificate in the in-memory store".to_string())
+    }
+
+    fn read_own_pkey(&self) -> Result<PrivateKey, String> {
+        self.inner
+            .read()
+            .own_pkey
+            .clone()
+            .ok_or_else(|| "No private key in the in-memory store".to_string())
+    }
+
+    fn store_own_cert_and_pkey(
+        &self,
+        cert: &X509,
+        pkey: &PrivateKey,
+        overwrite: bool,
+    ) -> Result<(), String> {
+        let mut inner = self.inner.write();
+        if !overwrite && (inner.own_cert.is_some() || inner.own_pkey.is_some()) {
+            return Err("Application certificate already exists and will not be overwritten. Enable overwrite to disable this safeguard.".to_string());
+        }
+        inner.own_cert = Some(cert.clone());
+        inner.own_pkey = Some(pkey.clone());
+        Ok(())
+    }
+
+    fn find_cert(&self, list: CertificateList, cert: &X509) -> Result<Option<X509>, String> {
+        let thumbprint = cert.thumbprint();
+        Ok(self
+            .inner
+            .read()
+            .list(list)
+            .iter()
+            .find(|c| c.thumbprint() == thumbprint)
+            .cloned())
+    }
+
+    fn read_certs(&self, list: CertificateList) -> Vec<X509> {
+        self.inner.read().list(list).clone()
+    }
+
+    fn store_cert(&self, list: CertificateList, cert: &X509) -> Result<(), String> {
+        let thumbprint = cert.thumbprint();
+        let mut inner = self.inner.write();
+        let certs = inner.list_mut(list);
+        certs.retain(|c| c.thumbprint() != thumbprint);
+        certs.push(cert.clone());
+        Ok(())
+    }
+
+    fn read_crls(&self) -> Vec<CertificateRevocationList> {
+        self.inner.read().crls.clone()
+    }
+}
@@ -0,0 +1,246 @@
+use std::{str::FromStr, time::Duration};
+
+use opcua_types::StatusCode;
+use rsa::{pkcs1v15, pss};
+use x509_cert::{
+    builder::{Builder, CertificateBuilder, Profile},
+    der::Encode,
+    name::Name,
+    serial_number::SerialNumber,
+    time::Validity,
+};
+
+use crate::{
+    certificate_store::CertificateStore,
+    pkey::PrivateKey,
+    store_backend::CertificateList,
+    tests::{make_certificate_store, pki_dirs},
+    x509::X509,
+    SecurityPolicy,
+};
+
+struct TestCa {
+    name: Name,
+    key: PrivateKey,
+    cert: X509,
+}
+
+fn build_cert(
+    profile: Profile,
+    serial: u32,
+    subject: &Name,
+    key: &PrivateKey,
+    signer: &PrivateKey,
+) -> X509 {
+    let signing_key = pkcs1v15::SigningKey::<sha2::Sha256>::new(signer.value.clone());
+    let builder = CertificateBuilder::new(
+        profile,
+        SerialNumber::from(serial),
+        Validity::from_now(Duration::from_secs(86400)).unwrap(),
+        subject.clone(),
+        key.public_key_to_info().unwrap(),
+        &signing_key,
+    )
+    .unwrap();
+    let cert = builder.build::<pkcs1v15::Signature>().unwrap();
+    X509::from_der(&cert.to_der().unwrap()).unwrap()
+}
+
+impl TestCa {
+    fn root(name: &str) -> Self {
+        let name = Name::from_str(&format!("CN={name}")).unwrap();
+        let key = PrivateKey::new(2048).unwrap();
+        let cert = build_cert(Profile::Root, 1, &name, &key, &key);
+        Self { name, key, cert }
+    }
+
+    /// Issue an intermediate CA. If `ca` is false the certificate is issued
+    /// without the basic constraints extension, so it may not sign certificates.
+    fn issue_ca(&self, name: &str, serial: u32, ca: bool) -> Self {
+        let name = Name::from_str(&format!("CN={name}")).unwrap();
+        let key = PrivateKey::new(2048).unwrap();
+        let cert = self.certify(&name, &key, serial, ca);
+        Self { name, key, cert }
+    }
+
+    /// Issue a certificate for an existing name and key, with or without
+    /// the basic constraints extension.
+    fn certify(&self, name: &Name, key: &PrivateKey, serial: u32, ca: bool) -> X509 {
+        let profile = if ca {
+            Profile::SubCA {
+                issuer: self.name.clone(),
+                path_len_constraint: None,
+            }
+        } else {
+            Profile::Manual {
+                issuer: Some(self.name.clone()),
+            }
+        };
+        build_cert(profile, serial, name, key, &self.key)
+    }
+
+    fn issue(&self, common_name: &str, serial: u32) -> X509 {
+        build_cert(
+            Profile::Manual {
+                issuer: Some(self.name.clone()),
+            },
+            serial,
+            &Name::from_str(&format!("CN={common_name}")).unwrap(),
+            &PrivateKey::new(2048).unwrap(),
+            &self.key,
+        )
+    }
+}
+
+fn store_issuer(cert_store: &CertificateStore, cert: &X509) {
+    cert_store
+        .backend()
+        .store_cert(CertificateList::Issuer, cert)
+        .unwrap();
+}
+
+fn validate(cert_store: &CertificateStore, cert: &X509) -> Result<(), StatusCode> {
+    cert_store.validate_application_instance_cert(cert, SecurityPolicy::Basic256Sha256, None, None)
+}
+
+#[test]
+fn validate_certificate_chain() {
+    let (tmp_dir, cert_store) = make_certificate_store();
+    assert!(pki_dirs(&tmp_dir).issuer_certs_dir().exists());
+
+    let root = TestCa::root("Root CA");
+    let intermediate = root.issue_ca("Intermediate CA", 2, true);
+    let leaf = intermediate.issue("leaf", 3);
+    cert_store.store_trusted_cert(&root.cert).unwrap();
+    store_issuer(&cert_store, &intermediate.cert);
+
+    validate(&cert_store, &leaf).unwrap();
+    validate(&cert_store, &intermediate.cert).unwrap();
+    // Certificates trusted through their issuer are not copied to the trusted directory.
+    assert!(cert_store
+        .backend()
+        .find_cert(CertificateList::Trusted, &leaf)
+        .unwrap()
+        .is_none());
+
+    drop(tmp_dir);
+}
+
+#[test]
+fn reject_incomplete_certificate_chain() {
+    let (tmp_dir, cert_store) = make_certificate_store();
+
+    let root = TestCa::root("Root CA");
+    let intermediate = root.issue_ca("Intermediate CA", 2, true);
+    let leaf = intermediate.issue("leaf", 3);
+    cert_store.store_trusted_cert(&root.cert).unwrap();
+
+    assert_eq!(
+        validate(&cert_store, &leaf).unwrap_err(),
+        StatusCode::BadCertificateChainIncomplete
+    );
+    assert!(cert_store
+        .backend()
+        .find_cert(CertificateList::Rejected, &leaf)
+        .unwrap()
+        .is_some());
+
+    // An issuer with the same name but a different key does not complete the chain.
+    let (tmp_dir_2, cert_store) = make_certificate_store();
+    let other_root = TestCa::root("Root CA");
+    cert_store.store_trusted_cert(&other_root.cert).unwrap();
+    store_issuer(&cert_store, &intermediate.cert);
+    assert_eq!(
+        validate(&cert_store, &leaf).unwrap_err(),
+        StatusCode::BadCertificateChainIncomplete
+    );
+
+    drop(tmp_dir);
+    drop(tmp_dir_2);
+}
+
+#[test]
+fn reject_untrusted_root() {
+    let (tmp_dir, cert_store) = make_certificate_store();
+
+    let root = TestCa::root("Root CA");
+    let intermediate = root.issue_ca("Intermediate CA", 2, true);
+    let leaf = intermediate.issue("leaf", 3);
+    store_issuer(&cert_store, &root.cert);
+    store_issuer(&cert_store, &intermediate.cert);
+
+    assert_eq!(
+        validate(&cert_store, &leaf).unwrap_err(),
+        StatusCode::BadCertificateUntrusted
+    );
+
+    drop(tmp_dir);
+}
+
+#[test]
+fn reject_issuer_without_ca_constraint() {
+    let (tmp_dir, cert_store) = make_certificate_store();
+
+    let root = TestCa::root("Root CA");
+    let intermediate = root.issue_ca("Intermediate CA", 2, false);
+    let leaf = intermediate.issue("leaf", 3);
+    cert_store.store_trusted_cert(&root.cert).unwrap();
+    store_issuer(&cert_store, &intermediate.cert);
+
+    assert_eq!(
+        validate(&cert_store, &leaf).unwrap_err(),
+        StatusCode::BadCertificateIssuerUseNotAllowed
+    );
+    // The intermediate itself is still a valid certificate issued by the root.
+    validate(&cert_store, &intermediate.cert).unwrap();
+
+    drop(tmp_dir);
+}
+
+#[test]
+fn validate_chain_with_alternative_issuer() {
+    let (tmp_dir, cert_store) = make_certificate_store();
+
+    let root = TestCa::root("Root CA");
+    let intermediate = root.issue_ca("Intermediate CA", 2, true);
+    let leaf = intermediate.issue("leaf", 3);
+    // A trusted certificate for the intermediate that may not sign certificates
+    // is tried first, the chain is then completed through the issuer directory.
+    let not_ca = root.certify(&intermediate.name, &intermediate.key, 4, false);
+    cert_store.store_trusted_cert(&root.cert).unwrap();
+    cert_store.store_trusted_cert(&not_ca).unwrap();
+    store_issuer(&cert_store, &intermediate.cert);
+
+    validate(&cert_store, &leaf).unwrap();
+
+    drop(tmp_dir);
+}
+
+#[test]
+fn validate_chain_signed_with_pss() {
+    let (tmp_dir, cert_store) = make_certificate_store();
+
+    let root = TestCa::root("Root CA");
+    cert_store.store_trusted_cert(&root.cert).unwrap();
+
+    let signing_key =
+        pss::SigningKey::<sha2::Sha512>::new_with_salt_len(root.key.value.clone(), 20);
+    // PSS signing keys only implement randomized signing without `getrandom`.
+    let cert = CertificateBuilder::new(
+        Profile::Manual {
+            issuer: Some(root.name.clone()),
+        },
+        SerialNumber::from(2u32),
+        Validity::from_now(Duration::from_secs(86400)).unwrap(),
+        Name::from_str("CN=leaf").unwrap(),
+        PrivateKey::new(2048).unwrap().public_key_to_info().unwrap(),
+        &signing_key,
+    )
+    .unwrap()
+    .build_with_rng::<pss::Signature>(&mut rand::thread_rng())
+    .unwrap();
+    let leaf = X509::from_der(&cert.to_der().unwrap()).unwrap();
+    validate(&cert_store, &leaf).unwrap();
+
+    drop(tmp_dir);
+}
@@ -0,0 +1,292 @@
+use std::{
+    fs::File,
+    io::Write,
+    path::Path,
+    str::FromStr,
+    time::{Duration, SystemTime},
+};
+
+use opcua_types::StatusCode;
+use rsa::{
+    pkcs1v15,
+    signature::{SignatureEncoding, Signer},
+};
+use x509_cert::{
+    builder::{Builder, CertificateBuilder, Profile},
+    crl::{CertificateList as X509CertificateList, RevokedCert, TbsCertList},
+    der::{asn1::BitString, Encode},
+    name::Name,
+    serial_number::SerialNumber,
+    spki::DynSignatureAlgorithmIdentifier,
+    time::{Time, Validity},
+};
+
+use crate::{
+    certificate_store::CertificateStore,
+    crl::CertificateRevocationList,
+    pkey::PrivateKey,
+    store_backend::CertificateList,
+    tests::{make_certificate_store, pki_dirs},
+    x509::X509,
+    SecurityPolicy,
+};
+
+struct TestCa {
+    name: Name,
+    key: PrivateKey,
+    cert: X509,
+}
+
+impl TestCa {
+    fn new(name: &str) -> Self {
+        let name = Name::from_str(&format!("CN={name}")).unwrap();
+        let key = PrivateKey::new(2048).unwrap();
+        let signing_key = pkcs1v15::SigningKey::<sha2::Sha256>::new(key.value.clone());
+        let builder = CertificateBuilder::new(
+            Profile::Root,
+            SerialNumber::from(1u32),
+            Validity::from_now(Duration::from_secs(86400)).unwrap(),
+            name.clone(),
+            key.public_key_to_info().unwrap(),
+            &signing_key,
+        )
+        .unwrap();
+        let cert = builder.build::<pkcs1v15::Signature>().unwrap();
+        let cert = X509::from_der(&cert.to_der().unwrap()).unwrap();
+        Self { name, key, cert }
+    }
+
+    fn signing_key(&self) -> pkcs1v15::SigningKey<sha2::Sha256> {
+        pkcs1v15::SigningKey::<sha2::Sha256>::new(self.key.value.clone())
+    }
+
+    fn issue(&self, common_name: &str, serial: u32) -> X509 {
+        let leaf_key = PrivateKey::new(2048).unwrap();
+        let signing_key = self.signing_key();
+        let builder = CertificateBuilder::new(
+            Profile::Manual {
+                issuer: Some(self.name.clone()),
+            },
+            SerialNumber::from(serial),
+            Validity::from_now(Duration::from_secs(86400)).unwrap(),
+            Name::from_str(&format!("CN={common_name}")).unwrap(),
+            leaf_key.public_key_to_info().unwrap(),
+            &signing_key,
+        )
+        .unwrap();
+        let cert = builder.build::<pkcs1v15::Signature>().unwrap();
+        X509::from_der(&cert.to_der().unwrap()).unwrap()
+    }
+
+    fn crl(&self, revoked: &[u32]) -> Vec<u8> {
+        self.crl_with_next_update(revoked, None)
+    }
+
+    fn crl_with_next_update(&self, revoked: &[u32], next_update: Option<SystemTime>) -> Vec<u8> {
+        let signing_key = self.signing_key();
+        let now = Time::try_from(SystemTime::now()).unwrap();
+        let tbs_cert_list = TbsCertList {
+            version: x509_cert::Version::V2,
+            signature: signing_key.signature_algorithm_identifier().unwrap(),
+            issuer: self.name.clone(),
+            this_update: now,
+            next_update: next_update.map(|t| Time::try_from(t).unwrap()),
+            revoked_certificates: Some(
+                revoked
+                    .iter()
+                    .map(|s| RevokedCert {
+                        serial_number: SerialNumber::from(*s),
+                        revocation_date: now,
+                        crl_entry_extensions: None,
+                    })
+                    .collect(),
+            ),
+            crl_extensions: None,
+        };
+        let signature: pkcs1v15::Signature = signing_key.sign(&tbs_cert_list.to_der().unwrap());
+        X509CertificateList {
+            signature_algorithm: tbs_cert_list.signature.clone(),
+            tbs_cert_list,
+            signature: BitString::from_bytes(&signature.to_vec()).unwrap(),
+        }
+        .to_der()
+        .unwrap()
+    }
+}
+
+fn write_crl(dir: &Path, name: &str, data: &[u8]) {
+    let mut file = File::create(dir.join(name)).unwrap();
+    assert!(file.write(data).is_ok());
+}
+
+fn store_issuer(cert_store: &CertificateStore, cert: &X509) {
+    cert_store
+        .backend()
+        .store_cert(CertificateList::Issuer, cert)
+        .unwrap();
+}
+
+fn validate(cert_store: &CertificateStore, cert: &X509) -> Result<(), StatusCode> {
+    cert_store.validate_application_instance_cert(cert, SecurityPolicy::Basic256Sha256, None, None)
+}
+
+#[test]
+fn parse_crl() {
+    let ca = TestCa::new("Test CA");
+    let revoked = ca.issue("revoked", 2);
+    let valid = ca.issue("valid", 3);
+    let other = TestCa::new("Other CA").issue("other", 2);
+
+    let crl = CertificateRevocationList::from_der(&ca.crl(&[2])).unwrap();
+    assert!(crl.is_issuer_of(&revoked));
+    assert!(crl.is_revoked(&revoked));
+    assert!(!crl.is_revoked(&valid));
+    // Same serial, but a different issuer.
+    assert!(!crl.is_issuer_of(&other));
+    assert!(!crl.is_revoked(&other));
+
+    assert!(crl.is_signed_by(&ca.cert));
+    // Same name, but a different key.
+    assert!(!crl.is_signed_by(&TestCa::new("Test CA").cert));
+    assert!(!crl.is_expired(SystemTime::now()));
+
+    let next_update = SystemTime::now() + Duration::from_secs(3600);
+    let crl =
+        CertificateRevocationList::from_der(&ca.crl_with_next_update(&[2], Some(next_update)))
+            .unwrap();
+    assert!(!crl.is_expired(SystemTime::now()));
+    assert!(crl.is_expired(next_update + Duration::from_secs(1)));
+}
+
+#[test]
+fn reject_revoked_certificate() {
+    let (tmp_dir, mut cert_store) = make_certificate_store();
+    assert!(pki_dirs(&tmp_dir).crl_dir().exists());
+
+    let ca = TestCa::new("Test CA");
+    let revoked = ca.issue("revoked", 2);
+    let valid = ca.issue("valid", 3);
+    store_issuer(&cert_store, &ca.cert);
+    cert_store.store_trusted_cert(&revoked).unwrap();
+    cert_store.store_trusted_cert(&valid).unwrap();
+
+    // No CRL, so both certificates are accepted unless strict checking is enabled.
+    validate(&cert_store, &revoked).unwrap();
+    validate(&cert_store, &valid).unwrap();
+    cert_store.set_strict_revocation_check(true);
+    assert_eq!(
+        validate(&cert_store, &valid).unwrap_err(),
+        StatusCode::BadCertificateRevocationUnknown
+    );
+
+    write_crl(&pki_dirs(&tmp_dir).crl_dir(), "ca.crl", &ca.crl(&[2]));
+    cert_store.clear_validation_cache();
+    assert_eq!(
+        validate(&cert_store, &revoked).unwrap_err(),
+        StatusCode::BadCertificateRevoked
+    );
+    validate(&cert_store, &valid).unwrap();
+
+    cert_store.set_strict_revocation_check(false);
+    assert_eq!(
+        validate(&cert_store, &revoked).unwrap_err(),
+        StatusCode::BadCertificateRevoked
+    );
+
+    drop(tmp_dir);
+}
+
+#[test]
+fn ignore_crl_not_signed_by_issuer() {
+    let (tmp_dir, mut cert_store) = make_certificate_store();
+
+    let ca = TestCa::new("Test CA");
+    let forger = TestCa::new("Test CA");
+    let cert = ca.issue("valid", 2);
+    store_issuer(&cert_store, &ca.cert);
+    cert_store.store_trusted_cert(&cert).unwrap();
+
+    // A list with the name of the CA, signed with another key, revokes nothing.
+    write_crl(
+        &pki_dirs(&tmp_dir).crl_dir(),
+        "forged.crl",
+        &forger.crl(&[2]),
+    );
+    cert_store.clear_validation_cache();
+    validate(&cert_store, &cert).unwrap();
+
+    // And it does not count as a revocation list for the CA.
+    cert_store.set_strict_revocation_check(true);
+    assert_eq!(
+        validate(&cert_store, &cert).unwrap_err(),
+        StatusCode::BadCertificateRevocationUnknown
+    );
+
+    // A list signed by a CA that is not in the store is not used either.
+    let unknown = TestCa::new("Unknown CA");
+    let unknown_cert = unknown.issue("unknown", 2);
+    cert_store.store_trusted_cert(&unknown_cert).unwrap();
+    write_crl(
+        &pki_dirs(&tmp_dir).crl_dir(),
+        "unknown.crl",
+        &unknown.crl(&[2]),
+    );
+    cert_store.clear_validation_cache();
+    assert_eq!(
+        validate(&cert_store, &unknown_cert).unwrap_err(),
+        StatusCode::BadCertificateRevocationUnknown
+    );
+
+    drop(tmp_dir);
+}
+
+#[test]
+fn expired_crl_fails_strict_revocation_check() {
+    let (tmp_dir, mut cert_store) = make_certificate_store();
+    cert_store.set_strict_revocation_check(true);
+
+    let ca = TestCa::new("Test CA");
+    let revoked = ca.issue("revoked", 2);
+    let valid = ca.issue("valid", 3);
+    store_issuer(&cert_store, &ca.cert);
+    cert_store.store_trusted_cert(&revoked).unwrap();
+    cert_store.store_trusted_cert(&valid).unwrap();
+
+    let next_update = SystemTime::now() - Duration::from_secs(60);
+    write_crl(
+        &pki_dirs(&tmp_dir).crl_dir(),
+        "ca.crl",
+        &ca.crl_with_next_update(&[2], Some(next_update)),
+    );
+    cert_store.clear_validation_cache();
+
+    // Certificates in an expired list are still revoked...
+    assert_eq!(
+        validate(&cert_store, &revoked).unwrap_err(),
+        StatusCode::BadCertificateRevoked
+    );
+    // ...but the list can no longer vouch for the other certificates of the CA.
+    assert_eq!(
+        validate(&cert_store, &valid).unwrap_err(),
+        StatusCode::BadCertificateRevocationUnknown
+    );
+
+    drop(tmp_dir);
+}
+
+#[test]
+fn self_signed_certificate_skips_revocation_check() {
+    let (tmp_dir, mut cert_store) = make_certificate_store();
+    cert_store.set_strict_revocation_check(true);
+
+    let (cert, _) = super::make_test_cert_2048();
+    cert_store.store_trusted_cert(&cert).unwrap();
+    // A file that is not a valid CRL is ignored.
+    write_crl(&pki_dirs(&tmp_dir).crl_dir(), "invalid.crl", b"not a crl");
+
+    cert_store
+        .validate_application_instance_cert(&cert, SecurityPolicy::Basic256Sha256, None, None)
+        .unwrap();
+
+    drop(tmp_dir);
+}
@@ -1,5 +1,8 @@
 use std::fs::File;
 use std::io::Write;
+use std::path::PathBuf;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
 
 use opcua_types::StatusCode;
 
@@ -9,10 +12,12 @@ use crate::{
     from_hex, hash,
     pkey::{KeySize, PrivateKey, RsaPadding},
     random,
+    store_backend::{CertificateList, InMemoryCertificateStore},
     tests::{
-        make_certificate_store, make_test_cert_1024, make_test_cert_2048, APPLICATION_HOSTNAME,
-        APPLICATION_URI,
+        make_certificate_store, make_test_cert_1024, make_test_cert_2048, pki_dirs,
+        CountingBackend, APPLICATION_HOSTNAME, APPLICATION_URI,
     },
+    thumbprint::CertificateThumbprint,
     user_identity::{legacy_secret_decrypt, legacy_secret_encrypt},
     x509::{X509Data, X509},
     SecurityPolicy, SHA1_SIZE, SHA256_SIZE,
@@ -70,9 +75,9 @@ fn create_cert() {
 
 #[test]
 fn ensure_pki_path() {
-    let (tmp_dir, cert_store) = make_certificate_store();
-    let pki = cert_store.pki_path.clone();
-    for dirname in ["rejected", "trusted"].iter() {
+    let (tmp_dir, _) = make_certificate_store();
+    let pki = tmp_dir.path();
+    for dirname in ["rejected", "trusted", "crl", "issuers"].iter() {
         let mut subdir = pki.to_path_buf();
         subdir.push(dirname);
         assert!(subdir.exists());
@@ -80,6 +85,31 @@ fn ensure_pki_path() {
     drop(tmp_dir);
 }
 
+#[test]
+#[allow(deprecated)]
+fn deprecated_pki_paths() {
+    let (tmp_dir, cert_store) = make_certificate_store();
+    let dirs = pki_dirs(&tmp_dir);
+    assert!(cert_store.ensure_pki_path().is_ok());
+    assert_eq!(
+        cert_store.own_certificate_path(),
+        dirs.own_certificate_path()
+    );
+    assert_eq!(
+        cert_store.own_private_key_path(),
+        dirs.own_private_key_path()
+    );
+    assert_eq!(cert_store.rejected_certs_dir(), dirs.rejected_certs_dir());
+    assert_eq!(cert_store.trusted_certs_dir(), dirs.trusted_certs_dir());
+
+    let cert_store = CertificateStore::new_with_backend(InMemoryCertificateStore::new());
+    assert!(cert_store.ensure_pki_path().is_ok());
+    assert_eq!(cert_store.own_certificate_path(), PathBuf::new());
+    assert_eq!(cert_store.own_private_key_path(), PathBuf::new());
+    assert_eq!(cert_store.rejected_certs_dir(), PathBuf::new());
+    assert_eq!(cert_store.trusted_certs_dir(), PathBuf::new());
+}
+
 #[test]
 fn create_own_cert_in_pki() {
     let args = X509Data {
@@ -115,11 +145,82 @@ fn create_rejected_cert_in_pki() {
     let result = cert_store.store_rejected_cert(&cert);
     assert!(result.is_ok());
 
-    let path = result.unwrap();
+    let path = pki_dirs(&tmp_dir).cert_path(CertificateList::Rejected, &cert);
     assert!(path.exists());
     drop(tmp_dir);
 }
 
+#[test]
+fn in_memory_certificate_store() {
+    let args = X509Data {
+        key_size: 2048,
+        common_name: "x".to_string(),
+        organization: "x.org".to_string(),
+        organizational_unit: "x.org ops".to_string(),
+        country: "EN".to_string(),
+        state: "London".to_string(),
+        alt_host_names: vec!["host1".to_string(), "host2".to_string()].into(),
+        certificate_duration_days: 60,
+    };
+
+    let backend = CountingBackend::new(InMemoryCertificateStore::new());
+    let calls = backend.calls();
+    let mut cert_store = CertificateStore::new_with_backend(backend);
+    assert!(cert_store.read_own_cert().is_err());
+    let (own_cert, _) = cert_store
+        .create_and_store_application_instance_cert(&args, false)
+        .unwrap();
+    assert!(cert_store
+        .create_and_store_application_instance_cert(&args, false)
+        .is_err());
+    assert_eq!(
+        cert_store.read_own_cert().unwrap().thumbprint(),
+        own_cert.thumbprint()
+    );
+    assert!(cert_store.read_own_pkey().is_ok());
+
+    let validate = |cert_store: &CertificateStore, c: &X509| {
+        cert_store.validate_application_instance_cert(c, SecurityPolicy::Basic256Sha256, None, None)
+    };
+
+    // Unknown certs are rejected, and stay rejected.
+    let (unknown, _) = make_test_cert_2048();
+    assert_eq!(
+        validate(&cert_store, &unknown).unwrap_err(),
+        StatusCode::BadCertificateUntrusted
+    );
+    assert!(cert_store
+        .backend()
+        .find_cert(CertificateList::Rejected, &unknown)
+        .unwrap()
+        .is_some());
+    assert_eq!(
+        validate(&cert_store, &unknown).unwrap_err(),
+        StatusCode::BadSecurityChecksFailed
+    );
+
+    let (trusted, _) = make_test_cert_2048();
+    cert_store.store_trusted_cert(&trusted).unwrap();
+    validate(&cert_store, &trusted).unwrap();
+
+    // Unknown certs are stored in the trusted list if the policy allows it.
+    cert_store.set_trust_unknown_certs(true);
+    let (auto_trusted, _) = make_test_cert_2048();
+    validate(&cert_store, &auto_trusted).unwrap();
+    assert_eq!(
+        cert_store
+            .backend()
+            .read_certs(CertificateList::Trusted)
+            .len(),
+        2
+    );
+
+    // Validation reads the certificates through the in-memory backend.
+    let before = calls.load(Ordering::Relaxed);
+    validate(&cert_store, &trusted).unwrap();
+    assert!(calls.load(Ordering::Relaxed) > before);
+}
+
 #[test]
 fn test_and_reject_application_instance_cert() {
     let (tmp_dir, cert_store) = make_certificate_store();
@@ -146,7 +247,7 @@ fn test_and_trust_application_instance_cert() {
 
     // Simulate user/admin copying cert to the trusted folder
     let der = cert.to_der().unwrap();
-    let mut cert_trusted_path = cert_store.trusted_certs_dir();
+    let mut cert_trusted_path = pki_dirs(&tmp_dir).trusted_certs_dir();
     cert_trusted_path.push(CertificateStore::cert_file_name(&cert));
     {
         println!("Writing der file to {cert_trusted_path:?}");
@@ -166,6 +267,61 @@ fn test_and_trust_application_instance_cert() {
     drop(tmp_dir);
 }
 
+#[test]
+fn test_validation_cache() {
+    let (tmp_dir, mut cert_store) = make_certificate_store();
+    cert_store.set_validation_cache(1, Duration::from_secs(60));
+
+    let (cert, _) = make_test_cert_1024();
+    let (cert2, _) = make_test_cert_1024();
+    for c in [&cert, &cert2] {
+        let mut cert_trusted_path = pki_dirs(&tmp_dir).trusted_certs_dir();
+        cert_trusted_path.push(CertificateStore::cert_file_name(c));
+        let mut file = File::create(cert_trusted_path).unwrap();
+        assert!(file.write(&c.to_der().unwrap()).is_ok());
+    }
+
+    let validate = |c: &X509| {
+        cert_store.validate_application_instance_cert(c, SecurityPolicy::Basic128Rsa15, None, None)
+    };
+
+    // The first validation is a miss, the second a hit.
+    validate(&cert).unwrap();
+    validate(&cert).unwrap();
+    let stats = cert_store.validation_cache_stats().unwrap();
+    assert_eq!((stats.hits, stats.misses, stats.entries), (1, 1, 1));
+
+    // The cache is full, so validating another cert evicts the first.
+    validate(&cert2).unwrap();
+    let stats = cert_store.validation_cache_stats().unwrap();
+    assert_eq!((stats.misses, stats.evictions, stats.entries), (2, 1, 1));
+
+    // Rejecting a cert clears the cache, so it is validated again and rejected.
+    cert_store.store_rejected_cert(&cert2).unwrap();
+    assert_eq!(cert_store.validation_cache_stats().unwrap().entries, 0);
+    assert_eq!(
+        validate(&cert2).unwrap_err(),
+        StatusCode::BadSecurityChecksFailed
+    );
+    assert_eq!(cert_store.validation_cache_stats().unwrap().entries, 0);
+
+    // The trusted directory is always checked, so removing a cert from it outside
+    // of the store takes effect even if its validation is cached.
+    validate(&cert).unwrap();
+    validate(&cert).unwrap();
+    assert_eq!(cert_store.validation_cache_stats().unwrap().hits, 2);
+    let mut cert_trusted_path = pki_dirs(&tmp_dir).trusted_certs_dir();
+    cert_trusted_path.push(CertificateStore::cert_file_name(&cert));
+    std::fs::remove_file(cert_trusted_path).unwrap();
+    assert_eq!(
+        validate(&cert).unwrap_err(),
+        StatusCode::BadCertificateUntrusted
+    );
+    assert_eq!(cert_store.validation_cache_stats().unwrap().hits, 2);
+
+    drop(tmp_dir);
+}
+
 #[test]
 fn test_and_reject_thumbprint_mismatch() {
     let (tmp_dir, cert_store) = make_certificate_store();
@@ -177,7 +333,7 @@ fn test_and_reject_thumbprint_mismatch() {
     // Simulate user/admin copying cert to the trusted folder and renaming it to cert2's name,
     // e.g. to trick the cert store to trust an untrusted cert
     let der = cert.to_der().unwrap();
-    let mut cert_trusted_path = cert_store.trusted_certs_dir();
+    let mut cert_trusted_path = pki_dirs(&tmp_dir).trusted_certs_dir();
     cert_trusted_path.push(CertificateStore::cert_file_name(&cert2));
     {
         let mut file = File::create(cert_trusted_path).unwrap();
@@ -607,3 +763,38 @@ fn encrypt_decrypt_password() {
         String::from_utf8(password2.value.unwrap()).unwrap()
     );
 }
+
+#[test]
+fn certificate_thumbprints() {
+    let (cert, _) = make_test_cert_2048();
+    let (other, _) = make_test_cert_1024();
+
+    let sha1 = CertificateThumbprint::from(cert.thumbprint());
+    assert!(!sha1.is_sha256());
+    assert_eq!(sha1.to_string(), cert.thumbprint().as_hex_string());
+    let sha256 = cert.thumbprint_sha256();
+    assert!(sha256.is_sha256());
+    assert_eq!(sha256.to_string().len(), SHA256_SIZE * 2);
+
+    // Round trip through the hex representation, including separators and upper case.
+    let parsed: CertificateThumbprint = sha256.to_string().parse().unwrap();
+    assert_eq!(parsed, sha256);
+    let separated = sha1
+        .value()
+        .iter()
+        .map(|b| format!("{b:02X}"))
+        .collect::<Vec<_>>()
+        .join(":");
+    let parsed: CertificateThumbprint = separated.parse().unwrap();
+    assert_eq!(parsed, sha1);
+
+    assert!(cert.matches_thumbprint(&sha1));
+    assert!(cert.matches_thumbprint(&sha256));
+    assert!(!other.matches_thumbprint(&sha1));
+    assert!(!other.matches_thumbprint(&sha256));
+
+    // Invalid hex, or the wrong length.
+    assert!("abc".parse::<CertificateThumbprint>().is_err());
+    assert!("zz".repeat(20).parse::<CertificateThumbprint>().is_err());
+    assert!("ab".repeat(16).parse::<CertificateThumbprint>().is_err());
+}
@@ -1,6 +1,14 @@
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
 use tempdir::TempDir;
 
-use crate::CertificateStore;
+use crate::{
+    CertificateList, CertificateRevocationList, CertificateStore, CertificateStoreBackend,
+    FileSystemCertificateStore,
+};
 
 use crate::{
     pkey::PrivateKey,
@@ -12,11 +20,77 @@ const APPLICATION_HOSTNAME: &str = "testhost";
 
 fn make_certificate_store() -> (TempDir, CertificateStore) {
     let tmp_dir = TempDir::new("pki").unwrap();
+    assert!(pki_dirs(&tmp_dir).ensure_pki_path().is_ok());
     let cert_store = CertificateStore::new(tmp_dir.path());
-    assert!(cert_store.ensure_pki_path().is_ok());
     (tmp_dir, cert_store)
 }
 
+/// Get the directory layout of a certificate store made by `make_certificate_store`.
+fn pki_dirs(tmp_dir: &TempDir) -> FileSystemCertificateStore {
+    FileSystemCertificateStore::new(tmp_dir.path())
+}
+
+/// Backend counting the calls made to the backend it wraps, so tests can check
+/// which backend a certificate store uses.
+struct CountingBackend<B> {
+    inner: B,
+    calls: Arc<AtomicUsize>,
+}
+
+impl<B> CountingBackend<B> {
+    fn new(inner: B) -> Self {
+        Self {
+            inner,
+            calls: Arc::default(),
+        }
+    }
+
+    /// Get the number of calls made to the backend, shared with the backend.
+    fn calls(&self) -> Arc<AtomicUsize> {
+        self.calls.clone()
+    }
+
+    fn call(&self) -> &B {
+        self.calls.fetch_add(1, Ordering::Relaxed);
+        &self.inner
+    }
+}
+
+impl<B: CertificateStoreBackend> CertificateStoreBackend for CountingBackend<B> {
+    fn read_own_cert(&self) -> Result<X509, String> {
+        self.call().read_own_cert()
+    }
+
+    fn read_own_pkey(&self) -> Result<PrivateKey, String> {
+        self.call().read_own_pkey()
+    }
+
+    fn store_own_cert_and_pkey(
+        &self,
+        cert: &X509,
+        pkey: &PrivateKey,
+        overwrite: bool,
+    ) -> Result<(), String> {
+        self.call().store_own_cert_and_pkey(cert, pkey, overwrite)
+    }
+
+    fn find_cert(&self, list: CertificateList, cert: &X509) -> Result<Option<X509>, String> {
+        self.call().find_cert(list, cert)
+    }
+
+    fn read_certs(&self, list: CertificateList) -> Vec<X509> {
+        self.call().read_certs(list)
+    }
+
+    fn store_cert(&self, list: CertificateList, cert: &X509) -> Result<(), String> {
+        self.call().store_cert(list, cert)
+    }
+
+    fn read_crls(&self) -> Vec<CertificateRevocationList> {
+        self.call().read_crls()
+    }
+}
+
 fn make_test_cert(key_size: u32) -> (X509, PrivateKey) {
     let args = X509Data {
         key_size,
@@ -48,5 +122,7 @@ fn make_test_cert_2048() -> (X509, PrivateKey) {
 }
 
 mod authentication;
+mod chain;
+mod crl;
 mod crypto;
 mod security_policy;
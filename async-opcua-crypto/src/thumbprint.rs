@@ -3,9 +3,13 @@
 // Copyright (C) 2017-2024 Adam Lock
 
 //! Functionality for holding a message digest.
+use std::{fmt, str::FromStr};
+
 use serde::{Deserialize, Serialize};
 
-use opcua_types::ByteString;
+use opcua_types::{ByteString, Error, StatusCode};
+
+use crate::{SHA1_SIZE, SHA256_SIZE};
 
 /// The thumbprint holds a 20 byte representation of a certificate that can be used as a hash,
 /// handshake comparison, a filename hint or similar purpose where a shortened representation
@@ -56,3 +60,110 @@ impl Thumbprint {
         &self.value[..]
     }
 }
+
+impl fmt::Display for Thumbprint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_hex(f, &self.value)
+    }
+}
+
+fn write_hex(f: &mut fmt::Formatter<'_>, value: &[u8]) -> fmt::Result {
+    for b in value {
+        write!(f, "{b:02x}")?;
+    }
+    Ok(())
+}
+
+/// A certificate thumbprint, either the SHA-1 or SHA-256 digest of the DER form of
+/// a certificate.
+///
+/// This is displayed and parsed as a hexadecimal string, so it can be used to
+/// configure certificate pins, or to log which certificates are in use.
+/// When parsing, `:` separators and whitespace are ignored, so thumbprints copied
+/// from tools like `openssl x509 -fingerprint` can be used directly.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub struct CertificateThumbprint {
+    value: Vec<u8>,
+}
+
+impl CertificateThumbprint {
+    /// Create a certificate thumbprint from a SHA-1 or SHA-256 digest.
+    ///
+    /// Returns an error if the digest is not 20 or 32 bytes long.
+    pub fn new(digest: &[u8]) -> Result<Self, Error> {
+        if digest.len() != SHA1_SIZE && digest.len() != SHA256_SIZE {
+            return Err(Error::new(
+                StatusCode::BadInvalidArgument,
+                format!(
+                    "Certificate thumbprint must be {SHA1_SIZE} or {SHA256_SIZE} bytes, got {}",
+                    digest.len()
+                ),
+            ));
+        }
+        Ok(Self {
+            value: digest.to_vec(),
+        })
+    }
+
+    /// Returns `true` if this is a SHA-256 thumbprint, `false` if it is SHA-1.
+    pub fn is_sha256(&self) -> bool {
+        self.value.len() == SHA256_SIZE
+    }
+
+    /// Returns the thumbprint
+    pub fn value(&self) -> &[u8] {
+        &self.value
+    }
+}
+
+impl From<Thumbprint> for CertificateThumbprint {
+    fn from(value: Thumbprint) -> Self {
+        Self {
+            value: value.value.to_vec(),
+        }
+    }
+}
+
+impl fmt::Display for CertificateThumbprint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_hex(f, &self.value)
+    }
+}
+
+impl FromStr for CertificateThumbprint {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let digits: Vec<u8> = s
+            .bytes()
+            .filter(|b| *b != b':' && !b.is_ascii_whitespace())
+            .collect();
+        let pairs = digits.chunks_exact(2);
+        if !pairs.remainder().is_empty() {
+            return Err(Error::new(
+                StatusCode::BadInvalidArgument,
+                "Certificate thumbprint has an odd number of hex digits",
+            ));
+        }
+        let mut value = Vec::with_capacity(digits.len() / 2);
+        for pair in pairs {
+            let (Some(hi), Some(lo)) = (hex_digit(pair[0]), hex_digit(pair[1])) else {
+                return Err(Error::new(
+                    StatusCode::BadInvalidArgument,
+                    format!("Certificate thumbprint {s} is not a valid hex string"),
+                ));
+            };
+            value.push(hi << 4 | lo);
+        }
+        Self::new(&value)
+    }
+}
+
+fn hex_digit(b: u8) -> Option<u8> {
+    match b {
+        b'0'..=b'9' => Some(b - b'0'),
+        b'a'..=b'f' => Some(b - b'a' + 10),
+        b'A'..=b'F' => Some(b - b'A' + 10),
+        _ => None,
+    }
+}
@@ -0,0 +1,184 @@
+//! Cache for certificate trust decisions.
+
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use parking_lot::Mutex;
+
+use crate::{CertificateThumbprint, SecurityPolicy, X509};
+
+/// Statistics for a [`ValidationCache`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ValidationCacheStats {
+    /// Number of validations answered from the cache.
+    pub hits: u64,
+    /// Number of validations that were not in the cache.
+    pub misses: u64,
+    /// Number of entries removed to make room for new ones.
+    pub evictions: u64,
+    /// Current number of entries in the cache.
+    pub entries: usize,
+}
+
+#[derive(PartialEq, Eq, Hash, Clone)]
+struct CacheKey {
+    thumbprint: CertificateThumbprint,
+    directly_trusted: bool,
+    security_policy: SecurityPolicy,
+    hostname: Option<String>,
+    application_uri: Option<String>,
+}
+
+struct CacheInner {
+    entries: HashMap<CacheKey, Instant>,
+    stats: ValidationCacheStats,
+}
+
+/// A bounded cache of successful certificate validations, keyed by the SHA-256 certificate
+/// thumbprint, whether the certificate was in the trusted list, and the parameters of the
+/// validation. Entries expire after a fixed time to live,
+/// or when the certificate itself expires, whichever comes first.
+///
+/// Only successful validations are cached, so certificates that are rejected are
+/// always validated again.
+pub struct ValidationCache {
+    inner: Mutex<CacheInner>,
+    max_entries: usize,
+    ttl: Duration,
+}
+
+impl ValidationCache {
+    /// Create a new validation cache with room for `max_entries` entries
+    /// that each live for at most `ttl`.
+    pub fn new(max_entries: usize, ttl: Duration) -> Self {
+        Self {
+            inner: Mutex::new(CacheInner {
+                entries: HashMap::new(),
+                stats: ValidationCacheStats::default(),
+            }),
+            max_entries,
+            ttl,
+        }
+    }
+
+    fn key(
+        thumbprint: &CertificateThumbprint,
+        directly_trusted: bool,
+        security_policy: SecurityPolicy,
+        hostname: Option<&str>,
+        application_uri: Option<&str>,
+    ) -> CacheKey {
+        CacheKey {
+            thumbprint: thumbprint.clone(),
+            directly_trusted,
+            security_policy,
+            hostname: hostname.map(|h| h.to_owned()),
+            application_uri: application_uri.map(|a| a.to_owned()),
+        }
+    }
+
+    /// Check whether a successful validation for the given parameters is cached,
+    /// updating the cache statistics.
+    pub(crate) fn check(
+        &self,
+        thumbprint: &CertificateThumbprint,
+        directly_trusted: bool,
+        security_policy: SecurityPolicy,
+        hostname: Option<&str>,
+        application_uri: Option<&str>,
+    ) -> bool {
+        let key = Self::key(
+            thumbprint,
+            directly_trusted,
+            security_policy,
+            hostname,
+            application_uri,
+        );
+        let mut inner = self.inner.lock();
+        let now = Instant::now();
+        match inner.entries.get(&key) {
+            Some(expiry) if *expiry > now => {
+                inner.stats.hits += 1;
+                true
+            }
+            Some(_) => {
+                inner.entries.remove(&key);
+                inner.stats.misses += 1;
+                false
+            }
+            None => {
+                inner.stats.misses += 1;
+                false
+            }
+        }
+    }
+
+    /// Store a successful validation of `cert` with the given parameters.
+    pub(crate) fn insert(
+        &self,
+        cert: &X509,
+        thumbprint: &CertificateThumbprint,
+        directly_trusted: bool,
+        security_policy: SecurityPolicy,
+        hostname: Option<&str>,
+        application_uri: Option<&str>,
+    ) {
+        if self.max_entries == 0 {
+            return;
+        }
+        let now = Instant::now();
+        let mut expiry = now + self.ttl;
+        // Do not cache the certificate beyond its own expiry.
+        if let Ok(not_after) = cert.not_after() {
+            let remaining = (not_after - chrono::Utc::now())
+                .to_std()
+                .unwrap_or(Duration::ZERO);
+            expiry = expiry.min(now + remaining);
+        }
+        if expiry <= now {
+            return;
+        }
+
+        let key = Self::key(
+            thumbprint,
+            directly_trusted,
+            security_policy,
+            hostname,
+            application_uri,
+        );
+        let mut inner = self.inner.lock();
+        if !inner.entries.contains_key(&key) && inner.entries.len() >= self.max_entries {
+            inner.entries.retain(|_, e| *e > now);
+            // If the cache is still full, evict the entry closest to expiring.
+            while inner.entries.len() >= self.max_entries {
+                let Some(oldest) = inner
+                    .entries
+                    .iter()
+                    .min_by_key(|(_, e)| **e)
+                    .map(|(k, _)| k.clone())
+                else {
+                    break;
+                };
+                inner.entries.remove(&oldest);
+                inner.stats.evictions += 1;
+            }
+        }
+        inner.entries.insert(key, expiry);
+    }
+
+    /// Remove all entries from the cache. Statistics are kept.
+    pub fn clear(&self) {
+        self.inner.lock().entries.clear();
+    }
+
+    /// Get the current cache statistics.
+    pub fn stats(&self) -> ValidationCacheStats {
+        let inner = self.inner.lock();
+        ValidationCacheStats {
+            entries: inner.entries.len(),
+            ..inner.stats
+        }
+    }
+}
@@ -34,7 +34,7 @@ use opcua_types::{status_code::StatusCode, ApplicationDescription, ByteString, E
 use super::{
     hostname,
     pkey::{PrivateKey, PublicKey},
-    thumbprint::Thumbprint,
+    thumbprint::{CertificateThumbprint, Thumbprint},
 };
 
 const DEFAULT_KEYSIZE: u32 = 2048;
@@ -393,7 +393,7 @@ impl From<x509::der::Error> for X509Error {
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, PartialEq, Eq)]
 /// Wrapper around an X509 certificate.
 pub struct X509 {
     value: x509::certificate::Certificate,
@@ -608,6 +608,139 @@ impl X509 {
         Ok(X509 { value: built })
     }
 
+    /// Get the name of the issuer of this certificate.
+    pub(crate) fn issuer_name(&self) -> &x509::name::Name {
+        &self.value.tbs_certificate.issuer
+    }
+
+    /// Get the serial number of this certificate.
+    pub(crate) fn serial_number(&self) -> &x509::serial_number::SerialNumber {
+        &self.value.tbs_certificate.serial_number
+    }
+
+    /// Return `true` if the issuer and subject of this certificate are the same.
+    pub(crate) fn is_self_issued(&self) -> bool {
+        self.value.tbs_certificate.issuer == self.value.tbs_certificate.subject
+    }
+
+    /// Get the name of the subject of this certificate.
+    pub(crate) fn subject(&self) -> &x509::name::Name {
+        &self.value.tbs_certificate.subject
+    }
+
+    /// Return `true` if this certificate may be used to issue other certificates,
+    /// meaning that it is marked as a CA in its basic constraints, and its key usage,
+    /// if present, allows signing certificates.
+    pub(crate) fn is_valid_issuer(&self) -> bool {
+        use x509::ext::pkix::{BasicConstraints, KeyUsage};
+
+        let is_ca = matches!(
+            self.value.tbs_certificate.get::<BasicConstraints>(),
+            Ok(Some((_, BasicConstraints { ca: true, .. })))
+        );
+        let can_sign = match self.value.tbs_certificate.get::<KeyUsage>() {
+            Ok(Some((_, usage))) => usage.key_cert_sign(),
+            Ok(None) => true,
+            Err(_) => false,
+        };
+        is_ca && can_sign
+    }
+
+    /// Return `true` if this certificate may be used to sign revocation lists, meaning
+    /// that its key usage, if present, allows signing CRLs.
+    pub(crate) fn is_valid_crl_issuer(&self) -> bool {
+        use x509::ext::pkix::KeyUsage;
+
+        match self.value.tbs_certificate.get::<KeyUsage>() {
+            Ok(Some((_, usage))) => usage.crl_sign(),
+            Ok(None) => true,
+            Err(_) => false,
+        }
+    }
+
+    /// Return `true` if the signature on this certificate was made with the key of `issuer`.
+    pub(crate) fn is_signed_by(&self, issuer: &X509) -> bool {
+        use x509::der::Encode;
+
+        let Ok(tbs) = self.value.tbs_certificate.to_der() else {
+            return false;
+        };
+        issuer.verify_signature(
+            &self.value.signature_algorithm,
+            &tbs,
+            self.value.signature.raw_bytes(),
+        )
+    }
+
+    /// Return `true` if `signature` is a signature of `data` made with the key of this
+    /// certificate, using the signature algorithm `algorithm`.
+    pub(crate) fn verify_signature(
+        &self,
+        algorithm: &x509_cert::spki::AlgorithmIdentifierOwned,
+        data: &[u8],
+        signature: &[u8],
+    ) -> bool {
+        use const_oid::db::rfc5912;
+        use rsa::{pkcs1::RsaPssParams, pss, signature::Verifier};
+
+        let Ok(key) = self.public_key() else {
+            return false;
+        };
+
+        macro_rules! verify {
+            ($scheme:ident, $hash:ty) => {
+                $scheme::Signature::try_from(signature).is_ok_and(|sig| {
+                    $scheme::VerifyingKey::<$hash>::new(key.value.clone())
+                        .verify(data, &sig)
+                        .is_ok()
+                })
+            };
+            (pss, $hash:ty, $salt_len:expr) => {
+                pss::Signature::try_from(signature).is_ok_and(|sig| {
+                    pss::VerifyingKey::<$hash>::new_with_salt_len(key.value.clone(), $salt_len)
+                        .verify(data, &sig)
+                        .is_ok()
+                })
+            };
+        }
+
+        match algorithm.oid {
+            rfc5912::SHA_1_WITH_RSA_ENCRYPTION => verify!(pkcs1v15, sha1::Sha1),
+            rfc5912::SHA_256_WITH_RSA_ENCRYPTION => verify!(pkcs1v15, sha2::Sha256),
+            rfc5912::SHA_384_WITH_RSA_ENCRYPTION => verify!(pkcs1v15, sha2::Sha384),
+            rfc5912::SHA_512_WITH_RSA_ENCRYPTION => verify!(pkcs1v15, sha2::Sha512),
+            rfc5912::ID_RSASSA_PSS => {
+                // The hash and salt length are given by the parameters of the algorithm,
+                // which default to SHA-1 with a 20 byte salt if they are absent.
+                let params = match &algorithm.parameters {
+                    Some(params) => match params.decode_as::<RsaPssParams>() {
+                        Ok(params) => params,
+                        Err(e) => {
+                            warn!("Invalid RSASSA-PSS signature parameters: {}", e);
+                            return false;
+                        }
+                    },
+                    None => RsaPssParams::default(),
+                };
+                let salt_len = params.salt_len.into();
+                match params.hash.oid {
+                    rfc5912::ID_SHA_1 => verify!(pss, sha1::Sha1, salt_len),
+                    rfc5912::ID_SHA_256 => verify!(pss, sha2::Sha256, salt_len),
+                    rfc5912::ID_SHA_384 => verify!(pss, sha2::Sha384, salt_len),
+                    rfc5912::ID_SHA_512 => verify!(pss, sha2::Sha512, salt_len),
+                    oid => {
+                        warn!("Unsupported RSASSA-PSS hash algorithm {}", oid);
+                        false
+                    }
+                }
+            }
+            oid => {
+                warn!("Unsupported signature algorithm {}", oid);
+                false
+            }
+        }
+    }
+
     /// Load a certificate from a der byte string.
     pub fn from_byte_string(data: &ByteString) -> Result<X509, Error> {
         if data.is_null() {
@@ -813,6 +946,32 @@ impl X509 {
         Thumbprint::new(&digest)
     }
 
+    /// The SHA-256 digest of the DER form of the certificate.
+    ///
+    /// This is not used by the OPC UA protocol itself, but is the thumbprint most
+    /// commonly shown by other tools, and is suitable for certificate pinning.
+    pub fn thumbprint_sha256(&self) -> CertificateThumbprint {
+        use sha2::Digest;
+        use x509_cert::der::Encode;
+
+        let der = self.value.to_der().unwrap();
+
+        let mut hasher = sha2::Sha256::new();
+        hasher.update(&der);
+        let digest = hasher.finalize();
+        CertificateThumbprint::new(&digest).unwrap()
+    }
+
+    /// Returns `true` if `thumbprint` is the SHA-1 or SHA-256 thumbprint
+    /// of this certificate.
+    pub fn matches_thumbprint(&self, thumbprint: &CertificateThumbprint) -> bool {
+        if thumbprint.is_sha256() {
+            self.thumbprint_sha256() == *thumbprint
+        } else {
+            self.thumbprint().value() == thumbprint.value()
+        }
+    }
+
     /// Turn the Asn1 values into useful portable types
     pub fn not_before(&self) -> Result<ChronoUtc, X509Error> {
         let dur = self
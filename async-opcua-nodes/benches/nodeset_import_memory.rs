@@ -0,0 +1,106 @@
+//! Benchmark of peak memory use when importing a NodeSet2 file.
+//!
+//! Loads the core node set, once by parsing the whole file up front and once by
+//! streaming it, and reports the peak heap size and the time taken for each run.
+//! Imported nodes are dropped as they are produced, so only the memory used by
+//! the import itself is measured.
+//!
+//! Run with `cargo bench -p async-opcua-nodes --bench nodeset_import_memory`.
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    fs::File,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+    time::{Duration, Instant},
+};
+
+use opcua_nodes::{NodeSet2Import, NodeSetImport, NodeSetNamespaceMapper};
+use opcua_types::NamespaceMap;
+
+struct PeakAllocator;
+
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+fn add_allocated(size: usize) {
+    let current = CURRENT.fetch_add(size, Ordering::Relaxed) + size;
+    PEAK.fetch_max(current, Ordering::Relaxed);
+}
+
+unsafe impl GlobalAlloc for PeakAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        add_allocated(layout.size());
+        System.alloc(layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        add_allocated(layout.size());
+        System.alloc_zeroed(layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        if new_size > layout.size() {
+            add_allocated(new_size - layout.size());
+        } else {
+            CURRENT.fetch_sub(layout.size() - new_size, Ordering::Relaxed);
+        }
+        System.realloc(ptr, layout, new_size)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        CURRENT.fetch_sub(layout.size(), Ordering::Relaxed);
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: PeakAllocator = PeakAllocator;
+
+struct RunResult {
+    nodes: usize,
+    peak_bytes: usize,
+    elapsed: Duration,
+}
+
+fn run(create: impl FnOnce() -> NodeSet2Import) -> RunResult {
+    let baseline = CURRENT.load(Ordering::Relaxed);
+    PEAK.store(baseline, Ordering::Relaxed);
+    let start = Instant::now();
+
+    let import = create();
+    let mut namespaces = NamespaceMap::new();
+    let mut mapper = NodeSetNamespaceMapper::new(&mut namespaces);
+    import.register_namespaces(&mut mapper);
+    let nodes = import.load(&mapper).count();
+    if let Some(e) = import.take_stream_error() {
+        panic!("Failed to import node set: {e}");
+    }
+    drop(import);
+
+    RunResult {
+        nodes,
+        peak_bytes: PEAK.load(Ordering::Relaxed) - baseline,
+        elapsed: start.elapsed(),
+    }
+}
+
+fn main() {
+    let path: PathBuf =
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("../schemas/1.05/Opc.Ua.NodeSet2.xml");
+    let size = std::fs::metadata(&path).unwrap().len();
+
+    let loaded = run(|| NodeSet2Import::new("en", &path, Vec::new()).unwrap());
+    let streamed =
+        run(|| NodeSet2Import::new_stream("en", File::open(&path).unwrap(), Vec::new()).unwrap());
+
+    println!("{}, {:.1} MB", path.display(), size as f64 / 1e6);
+    for (name, result) in [("loaded", &loaded), ("streamed", &streamed)] {
+        println!(
+            "{name:>10}: {:>6} nodes, {:>6.1} MB peak heap, {:?}",
+            result.nodes,
+            result.peak_bytes as f64 / 1e6,
+            result.elapsed
+        );
+    }
+}
@@ -0,0 +1,343 @@
+use std::collections::HashMap;
+
+use opcua_types::{
+    event_field::EventField, AttributeId, ByteString, DateTime, Guid, NodeId, NumericRange,
+    ObjectTypeId, QualifiedName, StatusCode, Variant, VariantScalarTypeId,
+};
+
+use crate::{Event, TypeTree};
+
+/// Data types of the fields of `BaseEventType` that can be set on an [`EventBuilder`].
+fn base_event_field_type(name: &str) -> Option<VariantScalarTypeId> {
+    Some(match name {
+        "EventId" => VariantScalarTypeId::ByteString,
+        "SourceNode" => VariantScalarTypeId::NodeId,
+        "SourceName" => VariantScalarTypeId::String,
+        "Time" | "ReceiveTime" => VariantScalarTypeId::DateTime,
+        "LocalTime" => VariantScalarTypeId::ExtensionObject,
+        "Message" => VariantScalarTypeId::LocalizedText,
+        "Severity" => VariantScalarTypeId::UInt16,
+        "ConditionClassId" | "ConditionSubClassId" => VariantScalarTypeId::NodeId,
+        "ConditionClassName" | "ConditionSubClassName" => VariantScalarTypeId::LocalizedText,
+        _ => return None,
+    })
+}
+
+/// An event with fields set at runtime, created using an [`EventBuilder`].
+///
+/// Use this for events that do not have a generated or derived type.
+#[derive(Debug, Clone)]
+pub struct DynamicEvent {
+    event_type: NodeId,
+    /// The event type followed by its supertypes, ending with `BaseEventType`.
+    type_chain: Vec<NodeId>,
+    time: DateTime,
+    /// Field values by browse path, with the index in `type_chain` of the type
+    /// declaring the field.
+    fields: HashMap<Vec<QualifiedName>, (usize, Variant)>,
+}
+
+impl DynamicEvent {
+    fn field(&self, browse_path: &[QualifiedName]) -> Option<&(usize, Variant)> {
+        self.fields.get(browse_path)
+    }
+}
+
+impl Event for DynamicEvent {
+    fn get_field(
+        &self,
+        type_definition_id: &NodeId,
+        attribute_id: AttributeId,
+        index_range: &NumericRange,
+        browse_path: &[QualifiedName],
+    ) -> Variant {
+        // Like generated events, fields are resolved relative to `BaseEventType`,
+        // or to the type declaring them or one of its subtypes.
+        if type_definition_id != &ObjectTypeId::BaseEventType {
+            let Some(idx) = self.type_chain.iter().position(|t| t == type_definition_id) else {
+                return Variant::Empty;
+            };
+            if self.field(browse_path).is_none_or(|(decl, _)| *decl < idx) {
+                return Variant::Empty;
+            }
+        }
+        self.get_value(attribute_id, index_range, browse_path)
+    }
+
+    fn time(&self) -> &DateTime {
+        &self.time
+    }
+
+    fn event_type_id(&self) -> &NodeId {
+        &self.event_type
+    }
+}
+
+impl EventField for DynamicEvent {
+    fn get_value(
+        &self,
+        attribute_id: AttributeId,
+        index_range: &NumericRange,
+        remaining_path: &[QualifiedName],
+    ) -> Variant {
+        if attribute_id != AttributeId::Value {
+            return Variant::Empty;
+        }
+        self.field(remaining_path)
+            .and_then(|(_, v)| v.range_of(index_range).ok())
+            .unwrap_or(Variant::Empty)
+    }
+}
+
+/// Builder for a [`DynamicEvent`], setting fields by browse path.
+///
+/// Fields are checked against the event type in the type tree, and the
+/// well-known fields of `BaseEventType` are converted to their data type.
+/// `EventId`, `EventType`, `Time`, and `ReceiveTime` are filled in automatically
+/// if they are not set.
+///
+/// # Example
+///
+/// ```ignore
+/// let event = EventBuilder::new(ObjectTypeId::BaseEventType, &*type_tree)?
+///     .field("SourceNode", NodeId::from(ObjectId::Server))?
+///     .field("Severity", 500)?
+///     .field("Message", LocalizedText::from("Something happened"))?
+///     .build();
+/// subscriptions.notify_events([(&event as &dyn Event, &ObjectId::Server.into())].into_iter());
+/// ```
+pub struct EventBuilder<'a> {
+    type_tree: &'a dyn TypeTree,
+    event: DynamicEvent,
+}
+
+impl<'a> EventBuilder<'a> {
+    /// Create a new event builder for the event type `event_type`.
+    ///
+    /// Fails with `BadTypeDefinitionInvalid` if `event_type` is not a
+    /// subtype of `BaseEventType`.
+    pub fn new(
+        event_type: impl Into<NodeId>,
+        type_tree: &'a dyn TypeTree,
+    ) -> Result<Self, StatusCode> {
+        let event_type = event_type.into();
+        let base_type: NodeId = ObjectTypeId::BaseEventType.into();
+        if !type_tree.is_subtype_of(&event_type, &base_type) {
+            return Err(StatusCode::BadTypeDefinitionInvalid);
+        }
+        let mut type_chain = vec![event_type.clone()];
+        let mut current = &event_type;
+        while current != &base_type {
+            let Some(parent) = type_tree.get_supertype(current) else {
+                return Err(StatusCode::BadTypeDefinitionInvalid);
+            };
+            type_chain.push(parent.clone());
+            current = parent;
+        }
+
+        Ok(Self {
+            type_tree,
+            event: DynamicEvent {
+                event_type,
+                type_chain,
+                time: DateTime::null(),
+                fields: HashMap::new(),
+            },
+        })
+    }
+
+    /// Set the field given by `path`, which is a list of browse names separated by `/`,
+    /// for example `"Severity"`, or `"2:MyObject/2:MyField"`.
+    /// See [`QualifiedName::parse`] for the format of each browse name.
+    pub fn field(self, path: &str, value: impl Into<Variant>) -> Result<Self, StatusCode> {
+        let path: Vec<_> = path
+            .split('/')
+            .map(|p| QualifiedName::parse(p, self.type_tree.namespaces()))
+            .collect();
+        self.field_path(path, value)
+    }
+
+    /// Set the field given by the browse path `path`.
+    ///
+    /// Fails with `BadBrowseNameInvalid` if the event type has no such field,
+    /// `BadNotWritable` if the field is `EventType`, and `BadTypeMismatch` if the
+    /// value cannot be converted to the data type of a `BaseEventType` field.
+    pub fn field_path(
+        mut self,
+        path: Vec<QualifiedName>,
+        value: impl Into<Variant>,
+    ) -> Result<Self, StatusCode> {
+        let mut value = value.into();
+        let base_idx = self.event.type_chain.len() - 1;
+        let base_field = match path.as_slice() {
+            [name] if name.namespace_index == 0 => Some(name.name.as_ref()),
+            _ => None,
+        };
+
+        let decl = match base_field.map(|n| (n, base_event_field_type(n))) {
+            Some(("EventType", _)) => return Err(StatusCode::BadNotWritable),
+            Some((name, Some(typ))) => {
+                if value.scalar_type_id() != Some(typ) {
+                    let converted = value.cast(typ);
+                    if converted.is_empty() {
+                        return Err(StatusCode::BadTypeMismatch);
+                    }
+                    value = converted;
+                }
+                if name == "Time" {
+                    if let Variant::DateTime(t) = &value {
+                        self.event.time = **t;
+                    }
+                }
+                base_idx
+            }
+            _ => self
+                .event
+                .type_chain
+                .iter()
+                .position(|t| {
+                    self.type_tree
+                        .find_type_prop_by_browse_path(t, &path)
+                        .is_some()
+                })
+                .ok_or(StatusCode::BadBrowseNameInvalid)?,
+        };
+        self.event.fields.insert(path, (decl, value));
+        Ok(self)
+    }
+
+    /// Build the event, filling in any missing required `BaseEventType` fields.
+    pub fn build(mut self) -> DynamicEvent {
+        if self.event.time.is_null() {
+            self.event.time = DateTime::now();
+        }
+        let base_idx = self.event.type_chain.len() - 1;
+        let defaults: [(&str, Variant); 4] = [
+            ("EventId", ByteString::from(Guid::new()).into()),
+            ("EventType", self.event.event_type.clone().into()),
+            ("Time", self.event.time.into()),
+            ("ReceiveTime", self.event.time.into()),
+        ];
+        for (name, value) in defaults {
+            self.event
+                .fields
+                .entry(vec![QualifiedName::new(0, name)])
+                .or_insert((base_idx, value));
+        }
+        self.event
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use opcua_types::{
+        AttributeId, DateTime, LocalizedText, NodeClass, NodeId, NumericRange, ObjectId,
+        ObjectTypeId, QualifiedName, StatusCode, Variant,
+    };
+
+    use crate::{DefaultTypeTree, Event};
+
+    use super::EventBuilder;
+
+    fn type_tree() -> DefaultTypeTree {
+        let mut type_tree = DefaultTypeTree::new();
+        type_tree.add_type_node(
+            &NodeId::new(1, "MyEventType"),
+            &ObjectTypeId::BaseEventType.into(),
+            NodeClass::ObjectType,
+        );
+        type_tree.add_type_property(
+            &NodeId::new(1, "MyField"),
+            &NodeId::new(1, "MyEventType"),
+            &[&QualifiedName::new(1, "MyField")],
+            NodeClass::Variable,
+        );
+        type_tree
+    }
+
+    fn field(evt: &dyn Event, type_id: impl Into<NodeId>, path: &[QualifiedName]) -> Variant {
+        evt.get_field(
+            &type_id.into(),
+            AttributeId::Value,
+            &NumericRange::None,
+            path,
+        )
+    }
+
+    #[test]
+    fn build_event() {
+        let type_tree = type_tree();
+        let type_id = NodeId::new(1, "MyEventType");
+        let evt = EventBuilder::new(type_id.clone(), &type_tree)
+            .unwrap()
+            .field("SourceNode", NodeId::from(ObjectId::Server))
+            .unwrap()
+            .field("Severity", 500)
+            .unwrap()
+            .field("Message", LocalizedText::from("Hello"))
+            .unwrap()
+            .field_path(vec![QualifiedName::new(1, "MyField")], 15i32)
+            .unwrap()
+            .build();
+
+        assert_eq!(evt.event_type_id(), &type_id);
+        assert!(!evt.time().is_null());
+        let base = |name: &str| field(&evt, ObjectTypeId::BaseEventType, &[name.into()]);
+        assert_eq!(base("Severity"), Variant::UInt16(500));
+        assert_eq!(
+            base("SourceNode"),
+            Variant::from(NodeId::from(ObjectId::Server))
+        );
+        assert_eq!(base("Message"), Variant::from(LocalizedText::from("Hello")));
+        assert_eq!(base("EventType"), Variant::from(type_id.clone()));
+        assert_eq!(base("Time"), Variant::from(*evt.time()));
+        assert_eq!(base("ReceiveTime"), Variant::from(*evt.time()));
+        assert!(matches!(base("EventId"), Variant::ByteString(b) if !b.is_null_or_empty()));
+
+        // Fields declared on the event type are only visible from that type,
+        // or from BaseEventType.
+        let my_field = [QualifiedName::new(1, "MyField")];
+        assert_eq!(field(&evt, type_id.clone(), &my_field), Variant::Int32(15));
+        assert_eq!(
+            field(&evt, ObjectTypeId::BaseEventType, &my_field),
+            Variant::Int32(15)
+        );
+        assert_eq!(
+            field(&evt, NodeId::new(1, "OtherType"), &my_field),
+            Variant::Empty
+        );
+    }
+
+    #[test]
+    fn build_event_errors() {
+        let type_tree = type_tree();
+        assert_eq!(
+            EventBuilder::new(ObjectTypeId::BaseObjectType, &type_tree).err(),
+            Some(StatusCode::BadTypeDefinitionInvalid)
+        );
+        let builder = || EventBuilder::new(NodeId::new(1, "MyEventType"), &type_tree).unwrap();
+        assert_eq!(
+            builder().field("Severity", "high").err(),
+            Some(StatusCode::BadTypeMismatch)
+        );
+        assert_eq!(
+            builder().field("NotAField", 1).err(),
+            Some(StatusCode::BadBrowseNameInvalid)
+        );
+        assert_eq!(
+            builder()
+                .field("EventType", NodeId::from(ObjectTypeId::BaseEventType))
+                .err(),
+            Some(StatusCode::BadNotWritable)
+        );
+
+        // Setting Time also sets the time of the event, and ReceiveTime by default.
+        let time = DateTime::parse_from_rfc3339("2024-01-01T00:00:00Z").unwrap();
+        let evt = builder().field("Time", time).unwrap().build();
+        assert_eq!(evt.time(), &time);
+        assert_eq!(
+            field(&evt, ObjectTypeId::BaseEventType, &["ReceiveTime".into()]),
+            Variant::from(time)
+        );
+    }
+}
@@ -138,7 +138,11 @@ impl ParsedContentFilter {
         };
 
         match op.operator {
-            FilterOperator::Equals => cmp_op!(self, item, type_tree, op, Some(Ordering::Equal)),
+            FilterOperator::Equals => Self::equals_op(
+                self.evaluate_operand(item, type_tree, &op.operands[0]),
+                self.evaluate_operand(item, type_tree, &op.operands[1]),
+            )
+            .into(),
             FilterOperator::IsNull => {
                 (self.evaluate_operand(item, type_tree, &op.operands[0]) == Variant::Empty).into()
             }
@@ -243,7 +247,7 @@ impl ParsedContentFilter {
 
     fn in_list(lhs: Variant, rhs: impl Iterator<Item = Variant>) -> bool {
         for it in rhs {
-            if matches!(Self::compare_op(lhs.clone(), it), Some(Ordering::Equal)) {
+            if Self::equals_op(lhs.clone(), it) {
                 return true;
             }
         }
@@ -307,20 +311,8 @@ impl ParsedContentFilter {
         lhs.cast(type_id)
     }
 
-    fn convert(lhs: Variant, rhs: Variant) -> (Variant, Variant) {
-        let lhs_type = lhs.type_id();
-        match lhs_type.precedence().cmp(&rhs.type_id().precedence()) {
-            std::cmp::Ordering::Less => {
-                let c = rhs.convert(lhs_type);
-                (lhs, c)
-            }
-            std::cmp::Ordering::Equal => (lhs, rhs),
-            std::cmp::Ordering::Greater => (lhs.convert(rhs.type_id()), rhs),
-        }
-    }
-
     fn bitwise_op(lhs: Variant, rhs: Variant, op: BitOperation) -> Variant {
-        let (lhs, rhs) = Self::convert(lhs, rhs);
+        let (lhs, rhs) = lhs.convert_to_common_type(&rhs);
 
         match (lhs, rhs) {
             (Variant::SByte(lhs), Variant::SByte(rhs)) => bw_op!(lhs, rhs, op),
@@ -336,20 +328,16 @@ impl ParsedContentFilter {
     }
 
     fn compare_op(lhs: Variant, rhs: Variant) -> Option<Ordering> {
-        let (lhs, rhs) = Self::convert(lhs, rhs);
-        match (lhs, rhs) {
-            (Variant::SByte(lhs), Variant::SByte(rhs)) => Some(lhs.cmp(&rhs)),
-            (Variant::Byte(lhs), Variant::Byte(rhs)) => Some(lhs.cmp(&rhs)),
-            (Variant::Int16(lhs), Variant::Int16(rhs)) => Some(lhs.cmp(&rhs)),
-            (Variant::Int32(lhs), Variant::Int32(rhs)) => Some(lhs.cmp(&rhs)),
-            (Variant::Int64(lhs), Variant::Int64(rhs)) => Some(lhs.cmp(&rhs)),
-            (Variant::UInt16(lhs), Variant::UInt16(rhs)) => Some(lhs.cmp(&rhs)),
-            (Variant::UInt32(lhs), Variant::UInt32(rhs)) => Some(lhs.cmp(&rhs)),
-            (Variant::UInt64(lhs), Variant::UInt64(rhs)) => Some(lhs.cmp(&rhs)),
-            (Variant::Double(lhs), Variant::Double(rhs)) => Some(lhs.total_cmp(&rhs)),
-            (Variant::Float(lhs), Variant::Float(rhs)) => Some(lhs.total_cmp(&rhs)),
-            (Variant::Boolean(lhs), Variant::Boolean(rhs)) => Some(lhs.cmp(&rhs)),
-            _ => None,
+        lhs.partial_cmp_numeric(&rhs)
+    }
+
+    fn equals_op(lhs: Variant, rhs: Variant) -> bool {
+        let (lhs, rhs) = lhs.convert_to_common_type(&rhs);
+        match lhs.partial_cmp_numeric(&rhs) {
+            Some(ord) => ord == Ordering::Equal,
+            // Types without an ordering, like String or NodeId, are compared by value.
+            // NaN is not equal to anything.
+            None => !lhs.is_empty() && !lhs.is_numeric() && lhs == rhs,
         }
     }
 
@@ -635,6 +623,85 @@ mod tests {
         assert!(f.evaluate(&event as &dyn Event, &type_tree));
     }
 
+    #[test]
+    fn test_mixed_type_filter() {
+        let type_tree = type_tree();
+        let field = Operand::simple_attribute(
+            ObjectTypeId::BaseEventType,
+            "Field",
+            AttributeId::Value,
+            NumericRange::None,
+        );
+        let cmp = |lhs: Operand, op: FilterOperator| {
+            filter(vec![filter_elem(&[field.clone(), lhs], op)], &type_tree)
+        };
+        let evt = event(2);
+        // The Int32 field is converted to Double, or the String to Int32.
+        assert!(cmp(Operand::literal(2.0), FilterOperator::Equals)
+            .evaluate(&evt as &dyn Event, &type_tree));
+        assert!(cmp(Operand::literal(1.5), FilterOperator::GreaterThan)
+            .evaluate(&evt as &dyn Event, &type_tree));
+        assert!(cmp(Operand::literal(2u8), FilterOperator::Equals)
+            .evaluate(&evt as &dyn Event, &type_tree));
+        assert!(cmp(Operand::literal("2"), FilterOperator::Equals)
+            .evaluate(&evt as &dyn Event, &type_tree));
+        // No conversion, so the comparison is false either way.
+        assert!(!cmp(Operand::literal("two"), FilterOperator::Equals)
+            .evaluate(&evt as &dyn Event, &type_tree));
+        assert!(!cmp(Operand::literal("two"), FilterOperator::LessThan)
+            .evaluate(&evt as &dyn Event, &type_tree));
+        assert!(!cmp(
+            Operand::literal(f64::NAN),
+            FilterOperator::GreaterThanOrEqual
+        )
+        .evaluate(&evt as &dyn Event, &type_tree));
+        assert!(!cmp(Operand::literal(f64::NAN), FilterOperator::Equals)
+            .evaluate(&evt as &dyn Event, &type_tree));
+
+        // Types without an ordering can only be compared for equality.
+        let cmp_values = |lhs: Operand, rhs: Operand, op: FilterOperator| {
+            filter(vec![filter_elem(&[lhs, rhs], op)], &type_tree)
+                .evaluate(&evt as &dyn Event, &type_tree)
+        };
+        let event_type = Operand::simple_attribute(
+            ObjectTypeId::BaseEventType,
+            "EventType",
+            AttributeId::Value,
+            NumericRange::None,
+        );
+        assert!(cmp_values(
+            Operand::literal("abc"),
+            Operand::literal("abc"),
+            FilterOperator::Equals
+        ));
+        assert!(!cmp_values(
+            Operand::literal("abc"),
+            Operand::literal("abd"),
+            FilterOperator::Equals
+        ));
+        assert!(!cmp_values(
+            Operand::literal("abc"),
+            Operand::literal("abc"),
+            FilterOperator::LessThanOrEqual
+        ));
+        assert!(cmp_values(
+            event_type.clone(),
+            Operand::literal(NodeId::new(1, 123)),
+            FilterOperator::Equals
+        ));
+        assert!(!cmp_values(
+            event_type.clone(),
+            Operand::literal(NodeId::new(1, 124)),
+            FilterOperator::Equals
+        ));
+        // The String is converted to a NodeId.
+        assert!(cmp_values(
+            event_type,
+            Operand::literal("ns=1;i=123"),
+            FilterOperator::Equals
+        ));
+    }
+
     #[test]
     fn test_lt_filter() {
         let type_tree = type_tree();
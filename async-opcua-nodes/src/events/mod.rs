@@ -1,8 +1,10 @@
+mod builder;
 #[macro_use]
 mod event;
 mod evaluate;
 mod validation;
 
+pub use builder::{DynamicEvent, EventBuilder};
 pub use evaluate::AttributeQueryable;
 pub use event::{BaseEventType, Event, MethodEventField};
 pub use opcua_types::event_field::EventField;
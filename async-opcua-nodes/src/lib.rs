@@ -13,7 +13,7 @@ mod type_tree;
 #[cfg(feature = "xml")]
 mod xml;
 #[cfg(feature = "xml")]
-pub use xml::NodeSet2Import;
+pub use xml::{LoadXmlError, NodeSet2Import};
 
 pub use base::Base;
 pub use data_type::{DataType, DataTypeBuilder};
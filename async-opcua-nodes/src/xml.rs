@@ -1,9 +1,12 @@
 use std::{
+    io::{BufRead, BufReader, Read},
     path::Path,
     str::FromStr,
-    sync::{Arc, OnceLock},
+    sync::{Arc, Mutex, OnceLock},
 };
 
+use flate2::bufread::GzDecoder;
+
 use hashbrown::HashMap;
 use opcua_types::{
     Context, DataTypeDefinition, DataValue, DecodingOptions, EnumDefinition, EnumField, Error,
@@ -13,10 +16,10 @@ use opcua_types::{
 use opcua_xml::{
     load_nodeset2_file,
     schema::ua_node_set::{
-        self, ArrayDimensions, ListOfReferences, UADataType, UAMethod, UANodeSet, UAObject,
-        UAObjectType, UAReferenceType, UAVariable, UAVariableType, UAView,
+        self, AliasTable, ArrayDimensions, ListOfReferences, UADataType, UAMethod, UANode,
+        UANodeSet, UAObject, UAObjectType, UAReferenceType, UAVariable, UAVariableType, UAView,
     },
-    XmlError,
+    NodeSet2Reader, XmlError,
 };
 use regex::Regex;
 use tracing::warn;
@@ -35,7 +38,17 @@ pub struct NodeSet2Import {
     dependent_namespaces: Vec<String>,
     preferred_locale: String,
     aliases: HashMap<String, String>,
-    file: UANodeSet,
+    namespace_uris: Vec<String>,
+    nodes: NodeSource,
+    stream_error: Mutex<Option<XmlError>>,
+}
+
+type NodeSetStream = Box<NodeSet2Reader<Box<dyn BufRead + Send>>>;
+
+enum NodeSource {
+    Loaded(Vec<UANode>),
+    /// Nodes are read from the stream when loaded. This can only happen once.
+    Stream(Mutex<Option<NodeSetStream>>),
 }
 
 static QUALIFIED_NAME_REGEX: OnceLock<Regex> = OnceLock::new();
@@ -104,6 +117,47 @@ impl NodeSet2Import {
         ))
     }
 
+    /// Create a new NodeSet2 importer that reads the `NodeSet2.xml` file from `reader`
+    /// one node at a time as the nodes are imported, instead of loading the whole file up front.
+    /// This uses far less memory for large node sets. The file may be gzip-compressed,
+    /// this is detected from its content.
+    ///
+    /// Only the namespaces and aliases of the node set are read here. If the file
+    /// is invalid further in, the import ends early, and the error is returned from
+    /// [`NodeSet2Import::take_stream_error`].
+    /// Since the file is not kept in memory, the nodes can only be loaded once.
+    ///
+    /// See documentation of [NodeSet2Import::new].
+    pub fn new_stream(
+        preferred_locale: &str,
+        reader: impl Read + Send + 'static,
+        dependent_namespaces: Vec<String>,
+    ) -> Result<Self, LoadXmlError> {
+        let mut reader = BufReader::new(reader);
+        let is_gzip = reader.fill_buf()?.starts_with(&[0x1f, 0x8b]);
+        let reader: Box<dyn BufRead + Send> = if is_gzip {
+            Box::new(BufReader::new(GzDecoder::new(reader)))
+        } else {
+            Box::new(reader)
+        };
+        let stream = NodeSet2Reader::new(reader)?;
+        let header = stream.header();
+
+        Ok(Self {
+            preferred_locale: preferred_locale.to_owned(),
+            type_loaders: TypeLoaderCollection::new(),
+            dependent_namespaces,
+            aliases: Self::make_aliases(header.aliases.as_ref()),
+            namespace_uris: header
+                .namespace_uris
+                .as_ref()
+                .map(|n| n.uris.clone())
+                .unwrap_or_default(),
+            nodes: NodeSource::Stream(Mutex::new(Some(Box::new(stream)))),
+            stream_error: Mutex::new(None),
+        })
+    }
+
     /// Create a new importer with a pre-loaded nodeset.
     /// The `dependent_namespaces` array contains namespaces that this nodeset requires, in order,
     /// but that are _not_ included in the nodeset file itself.
@@ -113,21 +167,36 @@ impl NodeSet2Import {
         nodeset: UANodeSet,
         dependent_namespaces: Vec<String>,
     ) -> Self {
-        let aliases = nodeset
-            .aliases
-            .iter()
-            .flat_map(|i| i.aliases.iter())
-            .map(|alias| (alias.alias.clone(), alias.id.0.clone()))
-            .collect();
         Self {
             preferred_locale: preferred_locale.to_owned(),
             type_loaders: TypeLoaderCollection::new(),
-            file: nodeset,
+            aliases: Self::make_aliases(nodeset.aliases.as_ref()),
+            namespace_uris: nodeset.namespace_uris.map(|n| n.uris).unwrap_or_default(),
+            nodes: NodeSource::Loaded(nodeset.nodes),
             dependent_namespaces,
-            aliases,
+            stream_error: Mutex::new(None),
         }
     }
 
+    /// Take the error that ended loading the nodes of a streamed node set early, if any.
+    /// Nodes read before the error have already been returned from
+    /// [`NodeSetImport::load`], so the import is incomplete if this returns an error.
+    pub fn take_stream_error(&self) -> Option<LoadXmlError> {
+        self.stream_error
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .take()
+            .map(LoadXmlError::Xml)
+    }
+
+    fn make_aliases(aliases: Option<&AliasTable>) -> HashMap<String, String> {
+        aliases
+            .iter()
+            .flat_map(|i| i.aliases.iter())
+            .map(|alias| (alias.alias.clone(), alias.id.0.clone()))
+            .collect()
+    }
+
     /// Add a type loader for importing types from XML.
     ///
     /// Any custom variable Value must be supported by one of the added
@@ -451,6 +520,26 @@ impl NodeSet2Import {
             .into(),
         })
     }
+
+    fn import_node(&self, ctx: &Context<'_>, raw_node: &UANode) -> Option<ImportedItem> {
+        let r = match raw_node {
+            UANode::Object(node) => self.make_object(ctx, node),
+            UANode::Variable(node) => self.make_variable(ctx, node),
+            UANode::Method(node) => self.make_method(ctx, node),
+            UANode::View(node) => self.make_view(ctx, node),
+            UANode::ObjectType(node) => self.make_object_type(ctx, node),
+            UANode::VariableType(node) => self.make_variable_type(ctx, node),
+            UANode::DataType(node) => self.make_data_type(ctx, node),
+            UANode::ReferenceType(node) => self.make_reference_type(ctx, node),
+        };
+        match r {
+            Ok(r) => Some(r),
+            Err(e) => {
+                println!("Failed to import node {}: {e}", raw_node.base().node_id.0);
+                None
+            }
+        }
+    }
 }
 
 impl NodeSetImport for NodeSet2Import {
@@ -475,11 +564,7 @@ impl NodeSetImport for NodeSet2Import {
     }
 
     fn get_own_namespaces(&self) -> Vec<String> {
-        self.file
-            .namespace_uris
-            .as_ref()
-            .map(|n| n.uris.clone())
-            .unwrap_or_default()
+        self.namespace_uris.clone()
     }
 
     fn load<'a>(
@@ -492,39 +577,33 @@ impl NodeSetImport for NodeSet2Import {
             DecodingOptions::default(),
         );
         ctx.set_aliases(&self.aliases);
-        Box::new(self.file.nodes.iter().filter_map(move |raw_node| {
-            let r = match raw_node {
-                opcua_xml::schema::ua_node_set::UANode::Object(node) => {
-                    self.make_object(&ctx, node)
-                }
-                opcua_xml::schema::ua_node_set::UANode::Variable(node) => {
-                    self.make_variable(&ctx, node)
-                }
-                opcua_xml::schema::ua_node_set::UANode::Method(node) => {
-                    self.make_method(&ctx, node)
-                }
-                opcua_xml::schema::ua_node_set::UANode::View(node) => self.make_view(&ctx, node),
-                opcua_xml::schema::ua_node_set::UANode::ObjectType(node) => {
-                    self.make_object_type(&ctx, node)
-                }
-                opcua_xml::schema::ua_node_set::UANode::VariableType(node) => {
-                    self.make_variable_type(&ctx, node)
-                }
-                opcua_xml::schema::ua_node_set::UANode::DataType(node) => {
-                    self.make_data_type(&ctx, node)
-                }
-                opcua_xml::schema::ua_node_set::UANode::ReferenceType(node) => {
-                    self.make_reference_type(&ctx, node)
-                }
-            };
-            match r {
-                Ok(r) => Some(r),
-                Err(e) => {
-                    println!("Failed to import node {}: {e}", raw_node.base().node_id.0);
-                    None
+        match &self.nodes {
+            NodeSource::Loaded(nodes) => Box::new(
+                nodes
+                    .iter()
+                    .filter_map(move |raw_node| self.import_node(&ctx, raw_node)),
+            ),
+            NodeSource::Stream(stream) => {
+                let stream = stream.lock().unwrap_or_else(|e| e.into_inner()).take();
+                if stream.is_none() {
+                    warn!("Nodes of a streamed NodeSet2 file can only be loaded once");
                 }
+                Box::new(
+                    stream
+                        .into_iter()
+                        .flatten()
+                        .map_while(|r| match r {
+                            Ok(node) => Some(node),
+                            Err(e) => {
+                                *self.stream_error.lock().unwrap_or_else(|e| e.into_inner()) =
+                                    Some(e);
+                                None
+                            }
+                        })
+                        .filter_map(move |raw_node| self.import_node(&ctx, &raw_node)),
+                )
             }
-        }))
+        }
     }
 }
 
@@ -535,7 +614,11 @@ mod tests {
         NodeSetNamespaceMapper, QualifiedName, Variant,
     };
 
-    use crate::{NodeBase, NodeSetImport, NodeType};
+    use std::io::{Cursor, Write};
+
+    use flate2::{write::GzEncoder, Compression};
+
+    use crate::{ImportedItem, NodeBase, NodeSetImport, NodeType};
 
     use super::NodeSet2Import;
 
@@ -626,4 +709,46 @@ mod tests {
             )))
         );
     }
+
+    #[test]
+    fn test_load_xml_nodeset_stream_gzip() {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(TEST_NODESET.as_bytes()).unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        let expected = NodeSet2Import::new_str("en", TEST_NODESET, vec![]).unwrap();
+        let mut ns = NamespaceMap::new();
+        let mut map = NodeSetNamespaceMapper::new(&mut ns);
+        expected.register_namespaces(&mut map);
+        // Compare the nodes without timestamps, which are set when the nodes are created.
+        let summarize = |n: ImportedItem| {
+            let value = match &n.node {
+                NodeType::Variable(v) => v.value.value.clone(),
+                _ => None,
+            };
+            (
+                n.node.as_node().node_id().clone(),
+                n.node.as_node().browse_name().clone(),
+                n.references.len(),
+                value,
+            )
+        };
+        let expected: Vec<_> = expected.load(&map).map(summarize).collect();
+        assert_eq!(expected.len(), 2);
+
+        for data in [compressed, TEST_NODESET.as_bytes().to_vec()] {
+            let import = NodeSet2Import::new_stream("en", Cursor::new(data), vec![]).unwrap();
+            assert_eq!(
+                import.get_own_namespaces(),
+                vec!["http://test.com".to_owned()]
+            );
+            let mut ns = NamespaceMap::new();
+            let mut map = NodeSetNamespaceMapper::new(&mut ns);
+            import.register_namespaces(&mut map);
+            let nodes: Vec<_> = import.load(&map).map(summarize).collect();
+            assert_eq!(nodes, expected);
+            // The stream is consumed by the first load.
+            assert_eq!(import.load(&map).count(), 0);
+        }
+    }
 }
@@ -63,6 +63,31 @@ impl AddressSpace {
         info!("Imported {count} nodes");
     }
 
+    /// Import a NodeSet2 file from `reader` into this address space, reading and
+    /// importing one node at a time. The file may be gzip-compressed.
+    ///
+    /// This is much lighter on memory than loading large node sets with
+    /// [`NodeSet2Import::new`]. Values are imported with the preferred locale `en`,
+    /// and the node set may only depend on the base namespace. Use
+    /// [`NodeSet2Import::new_stream`] with [`AddressSpace::import_node_set`]
+    /// to configure the import.
+    ///
+    /// If the file is invalid or truncated after its header, the error is returned,
+    /// but the nodes read before it have already been imported.
+    #[cfg(feature = "xml")]
+    pub fn import_nodeset2_stream(
+        &mut self,
+        reader: impl std::io::Read + Send + 'static,
+        namespaces: &mut NamespaceMap,
+    ) -> Result<(), LoadXmlError> {
+        let import = NodeSet2Import::new_stream("en", reader, Vec::new())?;
+        self.import_node_set(&import, namespaces);
+        match import.take_stream_error() {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
+
     /// Load types from this address space into the given type tree.
     pub fn load_into_type_tree(&self, type_tree: &mut DefaultTypeTree) {
         let mut found_ids = VecDeque::new();
@@ -560,6 +585,82 @@ mod tests {
         }
     }
 
+    #[cfg(feature = "xml")]
+    #[test]
+    fn import_nodeset2_stream_gzip() {
+        use std::io::{Cursor, Write};
+
+        use flate2::{write::GzEncoder, Compression};
+
+        const NODESET: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<UANodeSet xmlns="http://opcfoundation.org/UA/2011/03/UANodeSet.xsd" xmlns:uax="http://opcfoundation.org/UA/2008/02/Types.xsd">
+  <NamespaceUris>
+    <Uri>http://test.com</Uri>
+  </NamespaceUris>
+  <Aliases>
+    <Alias Alias="Int32">i=6</Alias>
+    <Alias Alias="Organizes">i=35</Alias>
+  </Aliases>
+  <UAObject NodeId="ns=1;i=1" BrowseName="1:Folder">
+    <DisplayName>Folder</DisplayName>
+    <References>
+      <Reference ReferenceType="Organizes" IsForward="false">i=85</Reference>
+    </References>
+  </UAObject>
+  <UAVariable NodeId="ns=1;i=2" BrowseName="1:Var" DataType="Int32">
+    <DisplayName>Var</DisplayName>
+    <References>
+      <Reference ReferenceType="Organizes" IsForward="false">ns=1;i=1</Reference>
+    </References>
+    <Value><uax:Int32>15</uax:Int32></Value>
+  </UAVariable>
+</UANodeSet>"#;
+
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(NODESET.as_bytes()).unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        let mut address_space = AddressSpace::new();
+        let mut namespaces = NamespaceMap::new();
+        address_space.import_node_set(&CoreNamespace, &mut namespaces);
+        address_space
+            .import_nodeset2_stream(Cursor::new(compressed), &mut namespaces)
+            .unwrap();
+
+        let ns = namespaces.get_index("http://test.com").unwrap();
+        let folder_id = NodeId::new(ns, 1);
+        let var_id = NodeId::new(ns, 2);
+        assert!(address_space.has_reference(
+            &ObjectId::ObjectsFolder.into(),
+            &folder_id,
+            ReferenceTypeId::Organizes
+        ));
+        assert!(address_space.has_reference(&folder_id, &var_id, ReferenceTypeId::Organizes));
+        let Some(NodeType::Variable(var)) = address_space.find(&var_id) else {
+            panic!("Expected variable");
+        };
+        assert_eq!(var.browse_name(), &QualifiedName::new(ns, "Var"));
+        assert_eq!(
+            var.value(
+                TimestampsToReturn::Neither,
+                &NumericRange::None,
+                &opcua_types::DataEncoding::Binary,
+                0.0
+            )
+            .value,
+            Some(Variant::Int32(15))
+        );
+
+        // A truncated file is an error, not a partial import.
+        let truncated = &NODESET[..NODESET.find("<UAVariable").unwrap() + 20];
+        let mut address_space = AddressSpace::new();
+        let mut namespaces = NamespaceMap::new();
+        address_space.import_node_set(&CoreNamespace, &mut namespaces);
+        assert!(address_space
+            .import_nodeset2_stream(Cursor::new(truncated.to_owned()), &mut namespaces)
+            .is_err());
+    }
+
     #[test]
     fn find_root_folder() {
         let address_space = make_sample_address_space();
@@ -74,6 +74,8 @@ impl UserToken {
 pub struct CoreServerPermissions {
     /// Whether the user can read the server diagnostics.
     pub read_diagnostics: bool,
+    /// Whether the user can enable or disable diagnostics at runtime.
+    pub write_diagnostics: bool,
 }
 
 #[allow(unused)]
@@ -344,6 +346,7 @@ impl AuthManager for DefaultAuthenticator {
             .get(token.0.as_str())
             .map(|r| CoreServerPermissions {
                 read_diagnostics: r.read_diagnostics,
+                write_diagnostics: r.write_diagnostics,
             })
             .unwrap_or_default()
     }
@@ -382,8 +385,98 @@ pub fn issued_token_security_policy(endpoint: &ServerEndpoint) -> UAString {
 }
 
 /// Get the username and password policy URI for the given endpioint.
-pub fn user_pass_security_policy_uri(_endpoint: &ServerEndpoint) -> UAString {
-    // TODO we could force the security policy uri for passwords to be something other than the default
-    //  here to ensure they're secure even when the endpoint's security policy is None.
-    UAString::null()
+///
+/// This is null, meaning the security policy of the endpoint, unless the endpoint
+/// has a different password security policy.
+pub fn user_pass_security_policy_uri(endpoint: &ServerEndpoint) -> UAString {
+    let security_policy = endpoint.password_security_policy();
+    if security_policy == endpoint.security_policy() {
+        UAString::null()
+    } else {
+        UAString::from(security_policy.to_uri())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeMap, path::Path};
+
+    use opcua_crypto::SecurityPolicy;
+    use opcua_types::{MessageSecurityMode, UserTokenType};
+
+    use super::{AuthManager, DefaultAuthenticator};
+    use crate::{config::ANONYMOUS_USER_TOKEN_ID, ServerEndpoint, ServerUserToken};
+
+    fn authenticator() -> DefaultAuthenticator {
+        let mut users = BTreeMap::new();
+        users.insert(
+            "user".to_owned(),
+            ServerUserToken::user_pass("user", "pass"),
+        );
+        users.insert(
+            "x509".to_owned(),
+            ServerUserToken::x509("x509", Path::new("user.der")),
+        );
+        DefaultAuthenticator::new(users)
+    }
+
+    fn policies(endpoint: &ServerEndpoint) -> Vec<(UserTokenType, String, String)> {
+        authenticator()
+            .user_token_policies(endpoint)
+            .into_iter()
+            .map(|p| {
+                (
+                    p.token_type,
+                    p.policy_id.as_ref().to_owned(),
+                    p.security_policy_uri.as_ref().to_owned(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn user_token_policies() {
+        let ids = [
+            ANONYMOUS_USER_TOKEN_ID.to_owned(),
+            "user".to_owned(),
+            "x509".to_owned(),
+        ];
+        let mut endpoint = ServerEndpoint::new(
+            "/",
+            SecurityPolicy::Basic256Sha256,
+            MessageSecurityMode::SignAndEncrypt,
+            &ids,
+        );
+        assert_eq!(
+            policies(&endpoint),
+            vec![
+                (
+                    UserTokenType::Anonymous,
+                    "anonymous".to_owned(),
+                    "".to_owned()
+                ),
+                (
+                    UserTokenType::UserName,
+                    "userpass_rsa_oaep".to_owned(),
+                    "".to_owned()
+                ),
+                (
+                    UserTokenType::Certificate,
+                    "x509".to_owned(),
+                    SecurityPolicy::Basic128Rsa15.to_uri().to_owned()
+                ),
+            ]
+        );
+
+        // Passwords secured with a different policy than the endpoint advertise its URI.
+        endpoint.password_security_policy = Some(SecurityPolicy::Aes256Sha256RsaPss.to_string());
+        assert_eq!(
+            policies(&endpoint)[1],
+            (
+                UserTokenType::UserName,
+                "userpass_rsa_oaep_sha256".to_owned(),
+                SecurityPolicy::Aes256Sha256RsaPss.to_uri().to_owned()
+            )
+        );
+    }
 }
@@ -1,4 +1,4 @@
-use std::{path::PathBuf, sync::Arc};
+use std::{path::PathBuf, sync::Arc, time::Duration};
 
 use tokio_util::sync::CancellationToken;
 use tracing::warn;
@@ -9,8 +9,9 @@ use opcua_crypto::SecurityPolicy;
 use opcua_types::{BuildInfo, MessageSecurityMode, TypeLoader, TypeLoaderCollection};
 
 use super::{
-    authenticator::AuthManager, node_manager::NodeManagerBuilder, Limits, Server, ServerConfig,
-    ServerEndpoint, ServerHandle, ServerUserToken, ANONYMOUS_USER_TOKEN_ID,
+    authenticator::AuthManager, node_manager::NodeManagerBuilder, Limits, Server,
+    ServerCapabilities, ServerConfig, ServerEndpoint, ServerHandle, ServerUserToken,
+    ANONYMOUS_USER_TOKEN_ID,
 };
 
 /// Server builder, used to configure the server programatically,
@@ -18,12 +19,21 @@ use super::{
 /// and type collections.
 pub struct ServerBuilder {
     pub(crate) config: ServerConfig,
-    pub(crate) node_managers: Vec<Box<dyn NodeManagerBuilder>>,
+    pub(crate) node_managers: Vec<NodeManagerRegistration>,
     pub(crate) authenticator: Option<Arc<dyn AuthManager>>,
     pub(crate) type_tree_getter: Option<Arc<dyn TypeTreeForUser>>,
     pub(crate) type_loaders: TypeLoaderCollection,
     pub(crate) token: CancellationToken,
     pub(crate) build_info: BuildInfo,
+    pub(crate) capabilities: ServerCapabilities,
+}
+
+/// A node manager builder registered on the server builder, with its limits.
+pub(crate) struct NodeManagerRegistration {
+    pub(crate) builder: Box<dyn NodeManagerBuilder>,
+    /// Maximum number of monitored items on nodes owned by this node manager,
+    /// across all sessions. 0 means no limit.
+    pub(crate) max_monitored_items: usize,
 }
 
 impl Default for ServerBuilder {
@@ -36,6 +46,7 @@ impl Default for ServerBuilder {
             type_tree_getter: None,
             build_info: BuildInfo::default(),
             type_loaders: TypeLoaderCollection::new(),
+            capabilities: ServerCapabilities::default(),
         };
         #[cfg(feature = "generated-address-space")]
         {
@@ -251,8 +262,25 @@ impl ServerBuilder {
     /// Once the server is created you can retrieve it from
     /// `handle.node_managers()`. This allows node managers to contain
     /// core server types without late initialization.
-    pub fn with_node_manager(mut self, node_manager: impl NodeManagerBuilder + 'static) -> Self {
-        self.node_managers.push(Box::new(node_manager));
+    pub fn with_node_manager(self, node_manager: impl NodeManagerBuilder + 'static) -> Self {
+        self.with_node_manager_max_monitored_items(node_manager, 0)
+    }
+
+    /// Add a node manager builder to the list of node managers, limiting the
+    /// total number of monitored items on nodes owned by the node manager,
+    /// across all sessions, to `max_monitored_items`.
+    ///
+    /// Monitored items created past this limit fail with `BadTooManyMonitoredItems`.
+    /// A limit of 0 means no limit, same as [`ServerBuilder::with_node_manager`].
+    pub fn with_node_manager_max_monitored_items(
+        mut self,
+        node_manager: impl NodeManagerBuilder + 'static,
+        max_monitored_items: usize,
+    ) -> Self {
+        self.node_managers.push(NodeManagerRegistration {
+            builder: Box::new(node_manager),
+            max_monitored_items,
+        });
         self
     }
 
@@ -343,6 +371,23 @@ impl ServerBuilder {
         self
     }
 
+    /// Reject client certificates issued by a CA if there is no revocation list for
+    /// the CA in the `crl` directory of the PKI folder. Certificates found in a revocation
+    /// list are always rejected.
+    pub fn strict_revocation_check(mut self, strict_revocation_check: bool) -> Self {
+        self.config.certificate_validation.strict_revocation_check = strict_revocation_check;
+        self
+    }
+
+    /// Cache up to `cache_size` successful client certificate validations for `ttl`.
+    /// This avoids validating the certificates of clients that reconnect frequently.
+    /// Set `cache_size` to 0 to disable the cache, which is the default.
+    pub fn certificate_validation_cache(mut self, cache_size: usize, ttl: Duration) -> Self {
+        self.config.certificate_validation.cache_size = cache_size;
+        self.config.certificate_validation.cache_ttl_ms = ttl.as_millis() as u64;
+        self
+    }
+
     /// PKI folder, either absolute or relative to executable.
     pub fn pki_dir(mut self, pki_dir: impl Into<PathBuf>) -> Self {
         self.config.pki_dir = pki_dir.into();
@@ -376,6 +421,14 @@ impl ServerBuilder {
         self
     }
 
+    /// Accept connections whose `HELLO` message contains an endpoint URL with a
+    /// different host or port than the configured endpoints. Only the scheme and
+    /// path are checked. Use this if clients connect through NAT or a proxy.
+    pub fn relax_endpoint_url_validation(mut self, relax: bool) -> Self {
+        self.config.tcp_config.relax_endpoint_url_validation = relax;
+        self
+    }
+
     /// General server limits.
     pub fn limits(mut self, limits: Limits) -> Self {
         self.config.limits = limits;
@@ -498,12 +551,30 @@ impl ServerBuilder {
         self
     }
 
+    /// Maximum number of chunk buffers kept for reuse by connections, instead of
+    /// allocating new buffers for each chunk sent or received. The pool is shared by
+    /// all connections, and holds buffers of at most the send or receive buffer size.
+    /// Buffers that are not reused for 30 seconds are freed. The default is 0, which disables buffer pooling.
+    pub fn max_pooled_buffers(mut self, max_pooled_buffers: usize) -> Self {
+        self.config.limits.max_pooled_buffers = max_pooled_buffers;
+        self
+    }
+
     /// Maximum number of browse continuation points per session.
+    /// Browse and BrowseNext return `BadNoContinuationPoints` for nodes
+    /// that would need a continuation point beyond this limit. 0 means no limit.
     pub fn max_browse_continuation_points(mut self, max_browse_continuation_points: usize) -> Self {
         self.config.limits.max_browse_continuation_points = max_browse_continuation_points;
         self
     }
 
+    /// Time in milliseconds after which an unused browse continuation point expires.
+    /// The default is 0, meaning that continuation points never expire.
+    pub fn browse_continuation_point_timeout_ms(mut self, timeout_ms: u64) -> Self {
+        self.config.limits.browse_continuation_point_timeout_ms = timeout_ms;
+        self
+    }
+
     /// Maximum number of history continuation points per session.
     pub fn max_history_continuation_points(
         mut self,
@@ -525,6 +596,41 @@ impl ServerBuilder {
         self
     }
 
+    /// Maximum number of new connections accepted from a single IP address per
+    /// rate limit interval, 0 for no limit. Connections past the limit are closed
+    /// immediately. See [`ServerBuilder::rate_limit_interval_ms`].
+    pub fn max_connections_per_ip(mut self, max_connections_per_ip: usize) -> Self {
+        self.config.limits.rate_limits.max_connections_per_ip = max_connections_per_ip;
+        self
+    }
+
+    /// Maximum number of service requests per session per rate limit interval,
+    /// 0 for no limit. Requests past the limit fail with `BadTooManyOperations`.
+    /// Publish and Republish requests are not limited.
+    /// See [`ServerBuilder::rate_limit_interval_ms`].
+    pub fn max_requests_per_session(mut self, max_requests_per_session: usize) -> Self {
+        self.config.limits.rate_limits.max_requests_per_session = max_requests_per_session;
+        self
+    }
+
+    /// Length of the interval connection and request rate limits apply to, in milliseconds.
+    /// The default is one second.
+    pub fn rate_limit_interval_ms(mut self, interval_ms: u64) -> Self {
+        self.config.limits.rate_limits.interval_ms = interval_ms;
+        self
+    }
+
+    /// Maximum number of monitored items across all sessions, 0 for no limit.
+    /// Monitored items created past this limit fail with `BadTooManyMonitoredItems`,
+    /// even if the per-subscription limit is not reached.
+    ///
+    /// Transferring subscriptions between sessions does not change the total,
+    /// so it is not affected by this limit.
+    pub fn max_total_monitored_items(mut self, max_total_monitored_items: usize) -> Self {
+        self.config.limits.subscriptions.max_total_monitored_items = max_total_monitored_items;
+        self
+    }
+
     /// Maximum time in milliseconds a session can be inactive before it is timed out and removed.
     /// The client can request a lower value than this.
     pub fn max_session_timeout_ms(mut self, max_session_timeout_ms: u64) -> Self {
@@ -550,10 +656,84 @@ impl ServerBuilder {
         self
     }
 
+    /// Set the server capabilities, exposed in the `ServerCapabilities` object
+    /// of the server. These should reflect what the registered node managers support.
+    pub fn capabilities(mut self, capabilities: ServerCapabilities) -> Self {
+        self.capabilities = capabilities;
+        self
+    }
+
+    /// Add a profile or facet URI to the `ServerProfileArray` of the server.
+    /// Use this to advertise optional services, such as query, implemented
+    /// by custom node managers.
+    pub fn add_server_profile(mut self, profile_uri: impl Into<String>) -> Self {
+        self.capabilities.profiles.push(profile_uri.into());
+        self
+    }
+
     /// Set whether to enable diagnostics on the server or not.
     /// Only users with the right permissions can read the diagnostics
+    ///
+    /// Diagnostics can also be toggled at runtime by writing to
+    /// `Server_ServerDiagnostics_EnabledFlag`, which requires the
+    /// [`write_diagnostics`](crate::authenticator::CoreServerPermissions::write_diagnostics) permission.
     pub fn diagnostics_enabled(mut self, enabled: bool) -> Self {
         self.config.diagnostics = enabled;
         self
     }
+
+    /// Set whether users need permission to read diagnostics to see the diagnostics
+    /// of their own session. If this is `false`, which is the default, all users can read
+    /// the diagnostics of their own session, but only users with the
+    /// [`read_diagnostics`](crate::authenticator::CoreServerPermissions::read_diagnostics) permission
+    /// can read the rest of the server diagnostics.
+    pub fn diagnostics_require_permission(mut self, require: bool) -> Self {
+        self.config.diagnostics_require_permission = require;
+        self
+    }
+
+    /// Set whether auditing is enabled on the server or not.
+    /// This sets the value of the `Server_Auditing` variable, which clients
+    /// can use to check whether audit events are raised by the server.
+    pub fn auditing_enabled(mut self, enabled: bool) -> Self {
+        self.config.auditing = enabled;
+        self
+    }
+
+    /// Set whether the server should emit audit events for security-relevant operations,
+    /// such as opening secure channels, activating sessions, and rejecting client certificates.
+    /// The events are raised on the `Server` object, and only reported to sessions whose user
+    /// has the [`read_diagnostics`](crate::authenticator::CoreServerPermissions::read_diagnostics)
+    /// permission.
+    ///
+    /// This enables auditing in the same way as [`ServerBuilder::auditing_enabled`].
+    /// Audit events are only emitted if the `generated-address-space` feature is enabled.
+    pub fn audit_events(self, enabled: bool) -> Self {
+        self.auditing_enabled(enabled)
+    }
+
+    /// Set whether the tokio tasks spawned by the server should be given
+    /// descriptive names. Names are only visible to tools like `tokio-console`
+    /// when compiled with `--cfg tokio_unstable`.
+    pub fn named_tasks(mut self, enabled: bool) -> Self {
+        self.config.named_tasks = enabled;
+        self
+    }
+
+    /// Set whether the server checks the `AccessLevel` and `UserAccessLevel` of variables
+    /// before passing reads and writes of their values to node managers. This is enabled
+    /// by default, and may be disabled if all node managers check access levels themselves.
+    pub fn check_access_levels(mut self, enabled: bool) -> Self {
+        self.config.check_access_levels = enabled;
+        self
+    }
+
+    /// Set whether the server checks the arguments of method calls against the
+    /// `InputArguments` of each method before passing the calls to node managers.
+    /// This is enabled by default, and may be disabled if all node managers
+    /// validate method arguments themselves.
+    pub fn validate_method_arguments(mut self, enabled: bool) -> Self {
+        self.config.validate_method_arguments = enabled;
+        self
+    }
 }
@@ -1,4 +1,4 @@
-use opcua_types::NodeId;
+use opcua_types::{profiles, NodeId};
 
 #[derive(Debug, Clone, Default)]
 /// History capabilities.
@@ -40,11 +40,26 @@ pub struct HistoryServerCapabilities {
     pub aggregates: Vec<NodeId>,
 }
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone)]
 /// Server capabilities object.
 pub struct ServerCapabilities {
     /// Historical server capabilities.
     pub history: HistoryServerCapabilities,
-    /// Supported server profiles.
+    /// Supported server profiles. Clients use these to find out which
+    /// services the server supports, so only list profiles and facets
+    /// actually implemented by the registered node managers.
     pub profiles: Vec<String>,
 }
+
+impl Default for ServerCapabilities {
+    fn default() -> Self {
+        Self {
+            history: HistoryServerCapabilities::default(),
+            profiles: vec![
+                profiles::SERVER_FACET_CORE_2017.to_owned(),
+                profiles::SERVER_FACET_STANDARD_DATA_CHANGE_SUBSCRIPTION_2017.to_owned(),
+                profiles::SERVER_FACET_METHODS.to_owned(),
+            ],
+        }
+    }
+}
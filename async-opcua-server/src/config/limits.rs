@@ -24,13 +24,19 @@ pub struct Limits {
     /// Receive buffer size in bytes
     #[serde(default = "defaults::receive_buffer_size")]
     pub receive_buffer_size: usize,
+    /// Maximum number of chunk buffers kept for reuse, shared by all connections.
+    /// Use 0 to allocate new buffers for every chunk.
+    #[serde(default)]
+    pub max_pooled_buffers: usize,
     /// Limits specific to subscriptions.
     #[serde(default)]
     pub subscriptions: SubscriptionLimits,
     /// Limits on service calls.
     #[serde(default)]
     pub operational: OperationalLimits,
-    /// Maximum number of browse continuation points per session.
+    /// Maximum number of browse continuation points per session. Browse and BrowseNext
+    /// return `BadNoContinuationPoints` for nodes that would need a continuation point
+    /// beyond this limit. 0 means no limit.
     #[serde(default = "defaults::max_browse_continuation_points")]
     pub max_browse_continuation_points: usize,
     /// Maximum number of history continuation points per session.
@@ -39,9 +45,18 @@ pub struct Limits {
     /// Maximum number of query continuation points per session.
     #[serde(default = "defaults::max_query_continuation_points")]
     pub max_query_continuation_points: usize,
+    /// Time in milliseconds after which an unused browse continuation point expires.
+    /// Expired continuation points are released in the background, and `BrowseNext` calls using them fail
+    /// with `BadContinuationPointInvalid`. The default is 0, meaning that continuation
+    /// points live until they are used, released, or the session is closed.
+    #[serde(default)]
+    pub browse_continuation_point_timeout_ms: u64,
     /// Maximum number of registered sessions before new ones are rejected.
     #[serde(default = "defaults::max_sessions")]
     pub max_sessions: usize,
+    /// Limits on the rate of incoming connections and requests.
+    #[serde(default)]
+    pub rate_limits: RateLimits,
 }
 
 impl Default for Limits {
@@ -54,12 +69,15 @@ impl Default for Limits {
             max_chunk_count: defaults::max_chunk_count(),
             send_buffer_size: defaults::send_buffer_size(),
             receive_buffer_size: defaults::receive_buffer_size(),
+            max_pooled_buffers: 0,
             subscriptions: Default::default(),
             max_browse_continuation_points: defaults::max_browse_continuation_points(),
             max_history_continuation_points: defaults::max_history_continuation_points(),
             max_query_continuation_points: defaults::max_query_continuation_points(),
+            browse_continuation_point_timeout_ms: 0,
             operational: OperationalLimits::default(),
             max_sessions: defaults::max_sessions(),
+            rate_limits: RateLimits::default(),
         }
     }
 }
@@ -83,6 +101,11 @@ pub struct SubscriptionLimits {
     /// Specifies the minimum publishing interval for this server in milliseconds.
     #[serde(default = "defaults::min_publishing_interval_ms")]
     pub min_publishing_interval_ms: f64,
+    /// Requested publishing intervals are rounded to the nearest multiple of this
+    /// value in milliseconds, so that subscriptions with similar publishing intervals
+    /// are published together. 0 to use the requested interval as-is.
+    #[serde(default = "defaults::publishing_interval_resolution_ms")]
+    pub publishing_interval_resolution_ms: f64,
     /// Maximum value of `KeepAliveCount`
     #[serde(default = "defaults::max_keep_alive_count")]
     pub max_keep_alive_count: u32,
@@ -92,6 +115,9 @@ pub struct SubscriptionLimits {
     /// Maximum number of monitored items per subscription, 0 for no limit
     #[serde(default = "defaults::max_monitored_items_per_sub")]
     pub max_monitored_items_per_sub: usize,
+    /// Maximum number of monitored items across all sessions on the server, 0 for no limit
+    #[serde(default = "defaults::max_total_monitored_items")]
+    pub max_total_monitored_items: usize,
     /// Maximum number of values in a monitored item queue
     #[serde(default = "defaults::max_monitored_item_queue_size")]
     pub max_monitored_item_queue_size: usize,
@@ -115,9 +141,11 @@ impl Default for SubscriptionLimits {
             ),
             min_sampling_interval_ms: defaults::min_sampling_interval_ms(),
             min_publishing_interval_ms: defaults::min_publishing_interval_ms(),
+            publishing_interval_resolution_ms: defaults::publishing_interval_resolution_ms(),
             max_keep_alive_count: defaults::max_keep_alive_count(),
             default_keep_alive_count: defaults::default_keep_alive_count(),
             max_monitored_items_per_sub: defaults::max_monitored_items_per_sub(),
+            max_total_monitored_items: defaults::max_total_monitored_items(),
             max_monitored_item_queue_size: defaults::max_monitored_item_queue_size(),
             max_lifetime_count: defaults::max_lifetime_count(),
             max_notifications_per_publish: defaults::max_notifications_per_publish(),
@@ -147,6 +175,10 @@ pub struct OperationalLimits {
     /// Maximum number of nodes per RegisterNodes call.
     #[serde(default = "defaults::max_nodes_per_register_nodes")]
     pub max_nodes_per_register_nodes: usize,
+    /// Maximum number of nodes registered under a handle on a single session at once.
+    /// RegisterNodes calls that would exceed this fail with `BadTooManyOperations`.
+    #[serde(default = "defaults::max_registered_nodes_per_session")]
+    pub max_registered_nodes_per_session: usize,
     /// Maximum number of nodes per create/modify/delete monitored items call.
     #[serde(default = "defaults::max_monitored_items_per_call")]
     pub max_monitored_items_per_call: usize,
@@ -192,6 +224,7 @@ impl Default for OperationalLimits {
             max_nodes_per_method_call: defaults::max_nodes_per_method_call(),
             max_nodes_per_browse: defaults::max_nodes_per_browse(),
             max_nodes_per_register_nodes: defaults::max_nodes_per_register_nodes(),
+            max_registered_nodes_per_session: defaults::max_registered_nodes_per_session(),
             max_monitored_items_per_call: defaults::max_monitored_items_per_call(),
             max_nodes_per_history_read_data: defaults::max_nodes_per_history_read_data(),
             max_nodes_per_history_read_events: defaults::max_nodes_per_history_read_events(),
@@ -208,6 +241,34 @@ impl Default for OperationalLimits {
     }
 }
 
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+/// Limits on the rate of incoming connections and requests, used to throttle
+/// abusive clients.
+pub struct RateLimits {
+    /// Maximum number of new connections accepted from a single IP address per interval,
+    /// 0 for no limit. Connections past the limit are closed immediately.
+    #[serde(default)]
+    pub max_connections_per_ip: usize,
+    /// Maximum number of service requests per session per interval, 0 for no limit.
+    /// Requests past the limit fail with `BadTooManyOperations`. Publish and Republish
+    /// requests are not limited.
+    #[serde(default)]
+    pub max_requests_per_session: usize,
+    /// Length of the interval the rate limits apply to, in milliseconds.
+    #[serde(default = "defaults::rate_limit_interval_ms")]
+    pub interval_ms: u64,
+}
+
+impl Default for RateLimits {
+    fn default() -> Self {
+        Self {
+            max_connections_per_ip: 0,
+            max_requests_per_session: 0,
+            interval_ms: defaults::rate_limit_interval_ms(),
+        }
+    }
+}
+
 mod defaults {
     use crate::constants;
     pub(super) fn max_array_length() -> usize {
@@ -243,6 +304,9 @@ mod defaults {
     pub(super) fn max_sessions() -> usize {
         constants::MAX_SESSIONS
     }
+    pub(super) fn rate_limit_interval_ms() -> u64 {
+        1000
+    }
 
     pub(super) fn max_subscriptions_per_session() -> usize {
         constants::MAX_SUBSCRIPTIONS_PER_SESSION
@@ -259,6 +323,9 @@ mod defaults {
     pub(super) fn min_publishing_interval_ms() -> f64 {
         constants::MIN_PUBLISHING_INTERVAL_MS
     }
+    pub(super) fn publishing_interval_resolution_ms() -> f64 {
+        0.0
+    }
     pub(super) fn max_keep_alive_count() -> u32 {
         constants::MAX_KEEP_ALIVE_COUNT
     }
@@ -268,6 +335,9 @@ mod defaults {
     pub(super) fn max_monitored_items_per_sub() -> usize {
         constants::DEFAULT_MAX_MONITORED_ITEMS_PER_SUB
     }
+    pub(super) fn max_total_monitored_items() -> usize {
+        0
+    }
     pub(super) fn max_monitored_item_queue_size() -> usize {
         constants::MAX_DATA_CHANGE_QUEUE_SIZE
     }
@@ -299,6 +369,9 @@ mod defaults {
     pub(super) fn max_nodes_per_register_nodes() -> usize {
         constants::MAX_NODES_PER_REGISTER_NODES
     }
+    pub(super) fn max_registered_nodes_per_session() -> usize {
+        constants::MAX_REGISTERED_NODES_PER_SESSION
+    }
     pub(super) fn max_monitored_items_per_call() -> usize {
         constants::MAX_MONITORED_ITEMS_PER_CALL
     }
@@ -34,6 +34,11 @@ pub struct TcpConfig {
     pub host: String,
     /// The port number of the service
     pub port: u16,
+    /// Accept `HELLO` messages with an endpoint URL whose host or port differs from the
+    /// configured endpoints. By default these are rejected with `BadTcpEndpointUrlInvalid`.
+    /// Enable this if clients reach the server through NAT or a proxy.
+    #[serde(default)]
+    pub relax_endpoint_url_validation: bool,
 }
 
 #[derive(Debug, PartialEq, Serialize, Deserialize, Clone, Default)]
@@ -53,6 +58,9 @@ pub struct ServerUserToken {
     #[serde(default)]
     /// Access to read diagnostics on the server.
     pub read_diagnostics: bool,
+    #[serde(default)]
+    /// Access to enable or disable diagnostics on the server.
+    pub write_diagnostics: bool,
 }
 
 impl ServerUserToken {
@@ -67,6 +75,7 @@ impl ServerUserToken {
             x509: None,
             thumbprint: None,
             read_diagnostics: false,
+            write_diagnostics: false,
         }
     }
 
@@ -81,6 +90,7 @@ impl ServerUserToken {
             x509: Some(cert_path.to_string_lossy().to_string()),
             thumbprint: None,
             read_diagnostics: false,
+            write_diagnostics: false,
         }
     }
 
@@ -141,6 +151,13 @@ impl ServerUserToken {
         self.read_diagnostics = read;
         self
     }
+
+    /// Set the ability for the user to enable or disable diagnostics on the server,
+    /// by writing to the `EnabledFlag` of the server diagnostics.
+    pub fn write_diagnostics(mut self, write: bool) -> Self {
+        self.write_diagnostics = write;
+        self
+    }
 }
 
 #[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
@@ -151,6 +168,17 @@ pub struct CertificateValidation {
     pub trust_client_certs: bool,
     /// Check the valid from/to fields of a certificate
     pub check_time: bool,
+    /// Maximum number of successful client certificate validations to cache.
+    /// Cached certificates skip validation until the entry expires. 0 disables the cache.
+    #[serde(default)]
+    pub cache_size: usize,
+    /// Time in milliseconds a successful client certificate validation is cached for.
+    #[serde(default = "defaults::certificate_validation_cache_ttl_ms")]
+    pub cache_ttl_ms: u64,
+    /// Reject client certificates issued by a CA if there is no revocation list
+    /// for the CA in the `crl` directory of the PKI folder.
+    #[serde(default)]
+    pub strict_revocation_check: bool,
 }
 
 impl Default for CertificateValidation {
@@ -158,6 +186,9 @@ impl Default for CertificateValidation {
         Self {
             trust_client_certs: false,
             check_time: true,
+            cache_size: 0,
+            cache_ttl_ms: defaults::certificate_validation_cache_ttl_ms(),
+            strict_revocation_check: false,
         }
     }
 }
@@ -236,6 +267,46 @@ pub struct ServerConfig {
     /// Enable server diagnostics.
     #[serde(default)]
     pub diagnostics: bool,
+    /// Require permission to read any session diagnostics. By default, users without
+    /// permission to read diagnostics can still read the diagnostics of their own session.
+    /// If this is set, reads of session diagnostics are rejected with `BadUserAccessDenied`
+    /// for these users, and the session diagnostics nodes are hidden from browse.
+    #[serde(default)]
+    pub diagnostics_require_permission: bool,
+    /// Enable auditing on the server. This is reported to clients through the
+    /// `Server_Auditing` variable. If enabled, the server raises audit events
+    /// on the `Server` object for security-relevant operations. Audit events are only
+    /// reported to sessions whose user has permission to read diagnostics.
+    #[serde(default)]
+    pub auditing: bool,
+    /// Give descriptive names to the tokio tasks spawned by the server, including
+    /// connection and session identifiers. Names are visible in tools like `tokio-console`
+    /// when compiled with `--cfg tokio_unstable`.
+    #[serde(default)]
+    pub named_tasks: bool,
+    /// Check the `AccessLevel` and `UserAccessLevel` of variables before dispatching reads
+    /// and writes of their values to node managers. Reads of variables without `CurrentRead`
+    /// fail with `BadNotReadable`, writes to variables without `CurrentWrite` fail with
+    /// `BadNotWritable`, and `BadUserAccessDenied` is returned if the user access level
+    /// does not allow the operation, or if the access levels of the variable cannot be read.
+    /// The user access level is passed through
+    /// [`AuthManager::effective_user_access_level`](crate::authenticator::AuthManager::effective_user_access_level).
+    ///
+    /// This is enabled by default. It costs an extra read of two attributes for each value
+    /// read or written, except for nodes owned by node managers that check access levels
+    /// themselves, like the in-memory node managers, see
+    /// [`NodeManager::checks_access_levels`](crate::node_manager::NodeManager::checks_access_levels),
+    /// and nodes owned by unhealthy node managers.
+    #[serde(default = "defaults::check_access_levels")]
+    pub check_access_levels: bool,
+    /// Check the arguments of method calls against the `InputArguments` property of
+    /// each method before dispatching the calls to node managers. Methods without an
+    /// `InputArguments` property are left to the node manager.
+    ///
+    /// This is enabled by default, and costs an extra browse and read for each call.
+    /// Servers whose node managers validate arguments themselves may disable it.
+    #[serde(default = "defaults::validate_method_arguments")]
+    pub validate_method_arguments: bool,
     /// Length of the nonce generated for CreateSession responses.
     #[serde(default = "defaults::session_nonce_length")]
     pub session_nonce_length: usize,
@@ -267,6 +338,17 @@ mod defaults {
     pub(super) fn session_nonce_length() -> usize {
         32
     }
+
+    pub(super) fn certificate_validation_cache_ttl_ms() -> u64 {
+        60_000
+    }
+    pub(super) fn check_access_levels() -> bool {
+        true
+    }
+
+    pub(super) fn validate_method_arguments() -> bool {
+        true
+    }
 }
 
 impl Config for ServerConfig {
@@ -318,6 +400,15 @@ impl Config for ServerConfig {
                 "Server configuration is invalid. Max byte string length is invalid".to_owned(),
             );
         }
+        let rate_limits = &self.limits.rate_limits;
+        if rate_limits.interval_ms == 0
+            && (rate_limits.max_connections_per_ip > 0 || rate_limits.max_requests_per_session > 0)
+        {
+            errors.push(
+                "Server configuration is invalid. Rate limit interval must be greater than 0"
+                    .to_owned(),
+            );
+        }
         if self.discovery_urls.is_empty() {
             errors.push("Server configuration is invalid. Discovery urls not set".to_owned());
         }
@@ -383,6 +474,7 @@ impl Default for ServerConfig {
                 host: "127.0.0.1".to_string(),
                 port: constants::DEFAULT_RUST_OPC_UA_SERVER_PORT,
                 hello_timeout: constants::DEFAULT_HELLO_TIMEOUT_SECONDS,
+                relax_endpoint_url_validation: false,
             },
             limits: Limits::default(),
             user_tokens: BTreeMap::new(),
@@ -396,6 +488,11 @@ impl Default for ServerConfig {
             max_secure_channel_token_lifetime_ms: defaults::max_secure_channel_token_lifetime_ms(),
             max_session_timeout_ms: defaults::max_session_timeout_ms(),
             diagnostics: false,
+            diagnostics_require_permission: false,
+            auditing: false,
+            named_tasks: false,
+            check_access_levels: defaults::check_access_levels(),
+            validate_method_arguments: defaults::validate_method_arguments(),
             session_nonce_length: defaults::session_nonce_length(),
         }
     }
@@ -435,6 +532,7 @@ impl ServerConfig {
             certificate_validation: CertificateValidation {
                 trust_client_certs: false,
                 check_time: true,
+                ..Default::default()
             },
             pki_dir,
             discovery_server_url,
@@ -442,6 +540,7 @@ impl ServerConfig {
                 host,
                 port,
                 hello_timeout: constants::DEFAULT_HELLO_TIMEOUT_SECONDS,
+                relax_endpoint_url_validation: false,
             },
             locale_ids,
             user_tokens,
@@ -2,10 +2,15 @@
 
 mod node_manager;
 mod server;
-pub use node_manager::{DiagnosticsNodeManager, DiagnosticsNodeManagerBuilder, NamespaceMetadata};
+mod session;
+pub use node_manager::{
+    DiagnosticsNodeManager, DiagnosticsNodeManagerBuilder, NamespaceMetadata,
+    NodeManagerMonitoredItems,
+};
 use opcua_core::sync::Mutex;
 use opcua_types::{DataValue, DateTime, IntoVariant};
 pub use server::{ServerDiagnostics, ServerDiagnosticsSummary};
+pub use session::SessionDiagnostics;
 
 #[derive(Default)]
 /// Wrapper around a value in memory, used for metrics.
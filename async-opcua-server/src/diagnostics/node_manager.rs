@@ -11,18 +11,22 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     address_space::AccessLevel,
+    diagnostics::SessionDiagnostics,
+    info::ServerInfo,
     node_manager::{
         as_opaque_node_id, from_opaque_node_id, impl_translate_browse_paths_using_browse,
         AddReferenceResult, BrowseNode, BrowsePathItem, DynNodeManager, ExternalReferenceRequest,
-        NodeManager, NodeManagerBuilder, NodeManagersRef, NodeMetadata, ReadNode, RequestContext,
-        ServerContext, SyncSampler,
+        MonitoredItemRef, MonitoredItemUpdateRef, NodeManager, NodeManagerBuilder, NodeManagersRef,
+        NodeMetadata, ReadNode, RequestContext, ServerContext, SyncSampler,
     },
+    subscriptions::CreateMonitoredItem,
+    SubscriptionCache,
 };
 use opcua_types::{
     AccessLevelExType, AccessRestrictionType, AttributeId, BrowseDirection, DataTypeId, DataValue,
-    DateTime, ExpandedNodeId, ExtensionObject, IdType, LocalizedText, NodeClass, NodeId,
-    NumericRange, ObjectId, ObjectTypeId, QualifiedName, ReferenceDescription, ReferenceTypeId,
-    RolePermissionType, StatusCode, TimestampsToReturn, VariableTypeId, Variant,
+    DateTime, ExpandedNodeId, ExtensionObject, IdType, LocalizedText, MonitoringMode, NodeClass,
+    NodeId, NumericRange, ObjectId, ObjectTypeId, QualifiedName, ReferenceDescription,
+    ReferenceTypeId, RolePermissionType, StatusCode, TimestampsToReturn, VariableTypeId, Variant,
 };
 
 /// Node manager handling nodes in the server hierarchy that are not part of the
@@ -32,6 +36,8 @@ pub struct DiagnosticsNodeManager {
     sampler: SyncSampler,
     node_managers: NodeManagersRef,
     namespace_index: u16,
+    info: Arc<ServerInfo>,
+    subscriptions: Arc<SubscriptionCache>,
 }
 
 /*
@@ -75,6 +81,18 @@ pub struct NamespaceMetadata {
     pub namespace_index: u16,
 }
 
+#[derive(Debug, Clone)]
+/// Monitored item usage for a node manager registered with a
+/// monitored item limit.
+pub struct NodeManagerMonitoredItems {
+    /// Name of the node manager.
+    pub node_manager: String,
+    /// Number of monitored items on nodes owned by the node manager, across all sessions.
+    pub current: usize,
+    /// Maximum number of monitored items allowed on nodes owned by the node manager.
+    pub max: usize,
+}
+
 #[derive(Default)]
 struct BrowseContinuationPoint {
     nodes: VecDeque<ReferenceDescription>,
@@ -86,11 +104,21 @@ struct NamespaceNode {
     property: Option<String>,
 }
 
+#[derive(Serialize, Deserialize, Debug)]
+struct SessionNode {
+    session_id: u32,
+    property: Option<String>,
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 enum DiagnosticsNode {
     Namespace(NamespaceNode),
+    Session(SessionNode),
+    MonitoredItemCount,
 }
 
+const MONITORED_ITEM_COUNT_NAME: &str = "CurrentMonitoredItemCount";
+
 /// Builder for the diagnostics node manager.
 pub struct DiagnosticsNodeManagerBuilder;
 
@@ -112,9 +140,31 @@ impl DiagnosticsNodeManager {
             sampler: SyncSampler::new(),
             node_managers: context.node_managers.clone(),
             namespace_index,
+            info: context.info.clone(),
+            subscriptions: context.subscriptions.clone(),
         }
     }
 
+    /// Get the current monitored item usage of each node manager
+    /// registered with a monitored item limit.
+    pub fn monitored_item_limits(&self) -> Vec<NodeManagerMonitoredItems> {
+        self.node_managers
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, mgr)| {
+                let max = self.info.max_monitored_items_for_node_manager(idx)?;
+                Some(NodeManagerMonitoredItems {
+                    node_manager: mgr.name().to_owned(),
+                    current: self
+                        .subscriptions
+                        .monitored_item_counters()
+                        .node_manager(idx),
+                    max,
+                })
+            })
+            .collect()
+    }
+
     fn namespaces(&self, context: &RequestContext) -> BTreeMap<String, NamespaceMetadata> {
         self.node_managers
             .iter()
@@ -546,6 +596,534 @@ impl DiagnosticsNodeManager {
             self.read_namespace_metadata_node(start_time, node_to_read, namespace);
         }
     }
+
+    fn sessions(&self, context: &RequestContext) -> Vec<Arc<SessionDiagnostics>> {
+        let perms = context.authenticator.core_permissions(&context.token);
+        if perms.read_diagnostics {
+            context.info.diagnostics.sessions()
+        } else if context.info.config.diagnostics_require_permission {
+            Vec::new()
+        } else {
+            // Users without access to server diagnostics can only see their own session.
+            context
+                .info
+                .diagnostics
+                .session(context.session_id)
+                .into_iter()
+                .collect()
+        }
+    }
+
+    fn can_read_session(&self, context: &RequestContext, session_id: u32) -> bool {
+        context
+            .authenticator
+            .core_permissions(&context.token)
+            .read_diagnostics
+            || session_id == context.session_id
+                && !context.info.config.diagnostics_require_permission
+    }
+
+    fn session(
+        &self,
+        context: &RequestContext,
+        session_id: u32,
+    ) -> Option<Arc<SessionDiagnostics>> {
+        if !self.can_read_session(context, session_id) {
+            return None;
+        }
+        context.info.diagnostics.session(session_id)
+    }
+
+    fn is_valid_session_property(prop: &str) -> bool {
+        matches!(prop, "SessionDiagnostics" | "SessionSecurityDiagnostics")
+    }
+
+    fn session_node_metadata(&self, session: &SessionDiagnostics) -> NodeMetadata {
+        NodeMetadata {
+            node_id: ExpandedNodeId::new(
+                as_opaque_node_id(
+                    &DiagnosticsNode::Session(SessionNode {
+                        session_id: session.session_id_numeric(),
+                        property: None,
+                    }),
+                    self.namespace_index,
+                )
+                .unwrap(),
+            ),
+            type_definition: ExpandedNodeId::new(ObjectTypeId::SessionDiagnosticsObjectType),
+            browse_name: QualifiedName::new(self.namespace_index, session.session_name().as_ref()),
+            display_name: LocalizedText::new("", session.session_name().as_ref()),
+            node_class: NodeClass::Object,
+        }
+    }
+
+    fn session_property_node_metadata(&self, session_id: u32, name: &str) -> NodeMetadata {
+        NodeMetadata {
+            node_id: ExpandedNodeId::new(
+                as_opaque_node_id(
+                    &DiagnosticsNode::Session(SessionNode {
+                        session_id,
+                        property: Some(name.to_owned()),
+                    }),
+                    self.namespace_index,
+                )
+                .unwrap_or_default(),
+            ),
+            type_definition: if name == "SessionDiagnostics" {
+                VariableTypeId::SessionDiagnosticsVariableType.into()
+            } else {
+                VariableTypeId::SessionSecurityDiagnosticsType.into()
+            },
+            browse_name: QualifiedName::new(0, name),
+            display_name: LocalizedText::new("", name),
+            node_class: NodeClass::Variable,
+        }
+    }
+
+    fn browse_sessions(
+        &self,
+        node_to_browse: &mut BrowseNode,
+        type_tree: &DefaultTypeTree,
+        sessions: &[Arc<SessionDiagnostics>],
+    ) {
+        if !matches!(
+            node_to_browse.browse_direction(),
+            BrowseDirection::Forward | BrowseDirection::Both
+        ) {
+            return;
+        }
+
+        if !node_to_browse.allows_reference_type(&ReferenceTypeId::HasComponent.into(), type_tree) {
+            return;
+        }
+
+        let mut cp = BrowseContinuationPoint::default();
+
+        for session in sessions {
+            let metadata = self.session_node_metadata(session);
+            let ref_desc = metadata.into_ref_desc(true, ReferenceTypeId::HasComponent);
+
+            if let AddReferenceResult::Full(c) = node_to_browse.add(type_tree, ref_desc) {
+                cp.nodes.push_back(c);
+            }
+        }
+
+        if !cp.nodes.is_empty() {
+            node_to_browse.set_next_continuation_point(Box::new(cp));
+        }
+    }
+
+    fn browse_session_object_node(
+        &self,
+        node_to_browse: &mut BrowseNode,
+        type_tree: &DefaultTypeTree,
+        session: &SessionDiagnostics,
+    ) {
+        let mut cp = BrowseContinuationPoint::default();
+
+        if matches!(
+            node_to_browse.browse_direction(),
+            BrowseDirection::Forward | BrowseDirection::Both
+        ) {
+            if node_to_browse
+                .allows_reference_type(&ReferenceTypeId::HasComponent.into(), type_tree)
+                && node_to_browse.allows_node_class(NodeClass::Variable)
+            {
+                for prop in ["SessionDiagnostics", "SessionSecurityDiagnostics"] {
+                    let meta =
+                        self.session_property_node_metadata(session.session_id_numeric(), prop);
+                    let ref_desc = meta.into_ref_desc(true, ReferenceTypeId::HasComponent);
+
+                    if let AddReferenceResult::Full(c) = node_to_browse.add(type_tree, ref_desc) {
+                        cp.nodes.push_back(c);
+                    }
+                }
+            }
+
+            if node_to_browse
+                .allows_reference_type(&ReferenceTypeId::HasTypeDefinition.into(), type_tree)
+            {
+                let ref_desc = ReferenceDescription {
+                    reference_type_id: ReferenceTypeId::HasTypeDefinition.into(),
+                    is_forward: true,
+                    node_id: ObjectTypeId::SessionDiagnosticsObjectType.into(),
+                    browse_name: QualifiedName::new(0, "SessionDiagnosticsObjectType"),
+                    display_name: LocalizedText::new("", "SessionDiagnosticsObjectType"),
+                    node_class: NodeClass::ObjectType,
+                    type_definition: ExpandedNodeId::null(),
+                };
+                if let AddReferenceResult::Full(c) = node_to_browse.add(type_tree, ref_desc) {
+                    cp.nodes.push_back(c);
+                }
+            }
+        }
+
+        if matches!(
+            node_to_browse.browse_direction(),
+            BrowseDirection::Inverse | BrowseDirection::Both
+        ) {
+            let ref_desc = ReferenceDescription {
+                reference_type_id: ReferenceTypeId::HasComponent.into(),
+                is_forward: false,
+                node_id: ObjectId::Server_ServerDiagnostics_SessionsDiagnosticsSummary.into(),
+                browse_name: QualifiedName::new(0, "SessionsDiagnosticsSummary"),
+                display_name: LocalizedText::new("", "SessionsDiagnosticsSummary"),
+                node_class: NodeClass::Object,
+                type_definition: ObjectTypeId::SessionsDiagnosticsSummaryType.into(),
+            };
+            if let AddReferenceResult::Full(c) = node_to_browse.add(type_tree, ref_desc) {
+                cp.nodes.push_back(c);
+            }
+        }
+
+        if !cp.nodes.is_empty() {
+            node_to_browse.set_next_continuation_point(Box::new(cp));
+        }
+    }
+
+    fn browse_session_property_node(
+        &self,
+        node_to_browse: &mut BrowseNode,
+        type_tree: &DefaultTypeTree,
+        session: &SessionDiagnostics,
+        prop: &str,
+    ) {
+        let mut cp = BrowseContinuationPoint::default();
+
+        if matches!(
+            node_to_browse.browse_direction(),
+            BrowseDirection::Forward | BrowseDirection::Both
+        ) {
+            let (type_id, type_name) = if prop == "SessionDiagnostics" {
+                (
+                    VariableTypeId::SessionDiagnosticsVariableType,
+                    "SessionDiagnosticsVariableType",
+                )
+            } else {
+                (
+                    VariableTypeId::SessionSecurityDiagnosticsType,
+                    "SessionSecurityDiagnosticsType",
+                )
+            };
+            let ref_desc = ReferenceDescription {
+                reference_type_id: ReferenceTypeId::HasTypeDefinition.into(),
+                is_forward: true,
+                node_id: type_id.into(),
+                browse_name: QualifiedName::new(0, type_name),
+                display_name: LocalizedText::new("", type_name),
+                node_class: NodeClass::VariableType,
+                type_definition: ExpandedNodeId::null(),
+            };
+            if let AddReferenceResult::Full(c) = node_to_browse.add(type_tree, ref_desc) {
+                cp.nodes.push_back(c);
+            }
+        }
+
+        if matches!(
+            node_to_browse.browse_direction(),
+            BrowseDirection::Inverse | BrowseDirection::Both
+        ) {
+            let metadata = self.session_node_metadata(session);
+            let ref_desc = metadata.into_ref_desc(false, ReferenceTypeId::HasComponent);
+
+            if let AddReferenceResult::Full(c) = node_to_browse.add(type_tree, ref_desc) {
+                cp.nodes.push_back(c);
+            }
+        }
+
+        if !cp.nodes.is_empty() {
+            node_to_browse.set_next_continuation_point(Box::new(cp));
+        }
+    }
+
+    fn browse_session_node(
+        &self,
+        context: &RequestContext,
+        node_to_browse: &mut BrowseNode,
+        type_tree: &DefaultTypeTree,
+        session_node: &SessionNode,
+    ) {
+        let Some(session) = self.session(context, session_node.session_id) else {
+            node_to_browse.set_status(StatusCode::BadNodeIdUnknown);
+            return;
+        };
+
+        match &session_node.property {
+            Some(prop) if Self::is_valid_session_property(prop) => {
+                self.browse_session_property_node(node_to_browse, type_tree, &session, prop);
+            }
+            Some(_) => node_to_browse.set_status(StatusCode::BadNodeIdUnknown),
+            None => self.browse_session_object_node(node_to_browse, type_tree, &session),
+        }
+    }
+
+    fn read_session_object_node(
+        &self,
+        start_time: DateTime,
+        node_to_read: &mut ReadNode,
+        session: &SessionDiagnostics,
+    ) {
+        let metadata = self.session_node_metadata(session);
+        let v: Variant = match node_to_read.node().attribute_id {
+            AttributeId::NodeId => metadata.node_id.node_id.into(),
+            AttributeId::NodeClass => (NodeClass::Object as i32).into(),
+            AttributeId::BrowseName => metadata.browse_name.into(),
+            AttributeId::DisplayName => metadata.display_name.into(),
+            AttributeId::EventNotifier => 0u8.into(),
+            AttributeId::WriteMask | AttributeId::UserWriteMask => 0u32.into(),
+            _ => {
+                node_to_read.set_error(StatusCode::BadAttributeIdInvalid);
+                return;
+            }
+        };
+
+        node_to_read.set_result(DataValue {
+            value: Some(v),
+            status: Some(StatusCode::Good),
+            source_timestamp: Some(start_time),
+            source_picoseconds: None,
+            server_timestamp: Some(start_time),
+            server_picoseconds: None,
+        });
+    }
+
+    fn read_session_property_node(
+        &self,
+        start_time: DateTime,
+        node_to_read: &mut ReadNode,
+        session: &SessionDiagnostics,
+        prop: &str,
+    ) {
+        let is_session_diagnostics = prop == "SessionDiagnostics";
+        let v: Variant = match node_to_read.node().attribute_id {
+            AttributeId::NodeId => self
+                .session_property_node_metadata(session.session_id_numeric(), prop)
+                .node_id
+                .node_id
+                .into(),
+            AttributeId::NodeClass => (NodeClass::Variable as i32).into(),
+            AttributeId::BrowseName => QualifiedName::new(0, prop).into(),
+            AttributeId::DisplayName => LocalizedText::new("", prop).into(),
+            AttributeId::Value => {
+                let mut value = if is_session_diagnostics {
+                    session.sample()
+                } else {
+                    session.sample_security()
+                };
+                value.server_timestamp = Some(DateTime::now());
+                node_to_read.set_result(value);
+                return;
+            }
+            AttributeId::DataType => {
+                if is_session_diagnostics {
+                    Variant::NodeId(Box::new(DataTypeId::SessionDiagnosticsDataType.into()))
+                } else {
+                    Variant::NodeId(Box::new(
+                        DataTypeId::SessionSecurityDiagnosticsDataType.into(),
+                    ))
+                }
+            }
+            AttributeId::ValueRank => (-1).into(),
+            AttributeId::ArrayDimensions => Variant::Empty,
+            AttributeId::AccessLevel | AttributeId::UserAccessLevel => {
+                AccessLevel::CURRENT_READ.bits().into()
+            }
+            AttributeId::AccessLevelEx => (AccessLevelExType::CurrentRead.bits() as u32).into(),
+            AttributeId::MinimumSamplingInterval => 0.0.into(),
+            AttributeId::Historizing => false.into(),
+            AttributeId::WriteMask | AttributeId::UserWriteMask => 0u32.into(),
+            _ => {
+                node_to_read.set_error(StatusCode::BadAttributeIdInvalid);
+                return;
+            }
+        };
+
+        node_to_read.set_result(DataValue {
+            value: Some(v),
+            status: Some(StatusCode::Good),
+            source_timestamp: Some(start_time),
+            source_picoseconds: None,
+            server_timestamp: Some(start_time),
+            server_picoseconds: None,
+        });
+    }
+
+    fn read_session_node(
+        &self,
+        context: &RequestContext,
+        start_time: DateTime,
+        node_to_read: &mut ReadNode,
+        session_node: &SessionNode,
+    ) {
+        if context.info.config.diagnostics_require_permission
+            && !self.can_read_session(context, session_node.session_id)
+        {
+            node_to_read.set_error(StatusCode::BadUserAccessDenied);
+            return;
+        }
+        let Some(session) = self.session(context, session_node.session_id) else {
+            node_to_read.set_error(StatusCode::BadNodeIdUnknown);
+            return;
+        };
+
+        match &session_node.property {
+            Some(prop) if Self::is_valid_session_property(prop) => {
+                self.read_session_property_node(start_time, node_to_read, &session, prop);
+            }
+            Some(_) => node_to_read.set_error(StatusCode::BadNodeIdUnknown),
+            None => self.read_session_object_node(start_time, node_to_read, &session),
+        }
+    }
+
+    fn monitored_item_count_node_id(&self) -> NodeId {
+        as_opaque_node_id(&DiagnosticsNode::MonitoredItemCount, self.namespace_index).unwrap()
+    }
+
+    fn monitored_item_count_metadata(&self) -> NodeMetadata {
+        NodeMetadata {
+            node_id: ExpandedNodeId::new(self.monitored_item_count_node_id()),
+            type_definition: VariableTypeId::BaseDataVariableType.into(),
+            browse_name: QualifiedName::new(self.namespace_index, MONITORED_ITEM_COUNT_NAME),
+            display_name: LocalizedText::new("", MONITORED_ITEM_COUNT_NAME),
+            node_class: NodeClass::Variable,
+        }
+    }
+
+    fn browse_server_diagnostics(
+        &self,
+        node_to_browse: &mut BrowseNode,
+        type_tree: &DefaultTypeTree,
+    ) {
+        if !matches!(
+            node_to_browse.browse_direction(),
+            BrowseDirection::Forward | BrowseDirection::Both
+        ) {
+            return;
+        }
+
+        if !node_to_browse.allows_reference_type(&ReferenceTypeId::HasComponent.into(), type_tree)
+            || !node_to_browse.allows_node_class(NodeClass::Variable)
+        {
+            return;
+        }
+
+        let ref_desc = self
+            .monitored_item_count_metadata()
+            .into_ref_desc(true, ReferenceTypeId::HasComponent);
+        if let AddReferenceResult::Full(c) = node_to_browse.add(type_tree, ref_desc) {
+            let mut cp = BrowseContinuationPoint::default();
+            cp.nodes.push_back(c);
+            node_to_browse.set_next_continuation_point(Box::new(cp));
+        }
+    }
+
+    fn browse_monitored_item_count_node(
+        &self,
+        node_to_browse: &mut BrowseNode,
+        type_tree: &DefaultTypeTree,
+    ) {
+        let mut cp = BrowseContinuationPoint::default();
+
+        if matches!(
+            node_to_browse.browse_direction(),
+            BrowseDirection::Forward | BrowseDirection::Both
+        ) {
+            let ref_desc = ReferenceDescription {
+                reference_type_id: ReferenceTypeId::HasTypeDefinition.into(),
+                is_forward: true,
+                node_id: VariableTypeId::BaseDataVariableType.into(),
+                browse_name: QualifiedName::new(0, "BaseDataVariableType"),
+                display_name: LocalizedText::new("", "BaseDataVariableType"),
+                node_class: NodeClass::VariableType,
+                type_definition: ExpandedNodeId::null(),
+            };
+            if let AddReferenceResult::Full(c) = node_to_browse.add(type_tree, ref_desc) {
+                cp.nodes.push_back(c);
+            }
+        }
+
+        if matches!(
+            node_to_browse.browse_direction(),
+            BrowseDirection::Inverse | BrowseDirection::Both
+        ) {
+            let ref_desc = ReferenceDescription {
+                reference_type_id: ReferenceTypeId::HasComponent.into(),
+                is_forward: false,
+                node_id: ObjectId::Server_ServerDiagnostics.into(),
+                browse_name: QualifiedName::new(0, "ServerDiagnostics"),
+                display_name: LocalizedText::new("", "ServerDiagnostics"),
+                node_class: NodeClass::Object,
+                type_definition: ObjectTypeId::ServerDiagnosticsType.into(),
+            };
+            if let AddReferenceResult::Full(c) = node_to_browse.add(type_tree, ref_desc) {
+                cp.nodes.push_back(c);
+            }
+        }
+
+        if !cp.nodes.is_empty() {
+            node_to_browse.set_next_continuation_point(Box::new(cp));
+        }
+    }
+
+    fn read_monitored_item_count_attribute(
+        &self,
+        context: &RequestContext,
+        start_time: DateTime,
+        attribute_id: AttributeId,
+    ) -> Result<DataValue, StatusCode> {
+        if !context
+            .authenticator
+            .core_permissions(&context.token)
+            .read_diagnostics
+        {
+            return Err(StatusCode::BadUserAccessDenied);
+        }
+
+        let v: Variant = match attribute_id {
+            AttributeId::NodeId => self.monitored_item_count_node_id().into(),
+            AttributeId::NodeClass => (NodeClass::Variable as i32).into(),
+            AttributeId::BrowseName => {
+                QualifiedName::new(self.namespace_index, MONITORED_ITEM_COUNT_NAME).into()
+            }
+            AttributeId::DisplayName => LocalizedText::new("", MONITORED_ITEM_COUNT_NAME).into(),
+            AttributeId::Value => {
+                let mut value = context
+                    .info
+                    .diagnostics
+                    .summary
+                    .sample_monitored_item_count();
+                value.server_timestamp = Some(DateTime::now());
+                return Ok(value);
+            }
+            AttributeId::DataType => Variant::NodeId(Box::new(DataTypeId::UInt32.into())),
+            AttributeId::ValueRank => (-1).into(),
+            AttributeId::ArrayDimensions => Variant::Empty,
+            AttributeId::AccessLevel | AttributeId::UserAccessLevel => {
+                AccessLevel::CURRENT_READ.bits().into()
+            }
+            AttributeId::AccessLevelEx => (AccessLevelExType::CurrentRead.bits() as u32).into(),
+            AttributeId::MinimumSamplingInterval => 0.0.into(),
+            AttributeId::Historizing => false.into(),
+            AttributeId::WriteMask | AttributeId::UserWriteMask => 0u32.into(),
+            _ => return Err(StatusCode::BadAttributeIdInvalid),
+        };
+
+        Ok(DataValue {
+            value: Some(v),
+            status: Some(StatusCode::Good),
+            source_timestamp: Some(start_time),
+            source_picoseconds: None,
+            server_timestamp: Some(start_time),
+            server_picoseconds: None,
+        })
+    }
+
+    fn is_monitored_item_count_node(&self, node_id: &NodeId) -> bool {
+        matches!(
+            from_opaque_node_id::<DiagnosticsNode>(node_id),
+            Some(DiagnosticsNode::MonitoredItemCount)
+        )
+    }
 }
 
 #[async_trait]
@@ -577,7 +1155,12 @@ impl NodeManager for DiagnosticsNodeManager {
             .min_sampling_interval_ms
             .floor() as u64;
         let sampler_interval = if interval > 0 { interval } else { 100 };
-        self.sampler.run(
+        self.sampler.run_named(
+            context
+                .info
+                .config
+                .named_tasks
+                .then(|| "opcua-server-diagnostics-sampler".to_owned()),
             Duration::from_millis(sampler_interval),
             context.subscriptions.clone(),
         );
@@ -611,6 +1194,26 @@ impl NodeManager for DiagnosticsNodeManager {
                         self.namespace_node_metadata(ns_node)
                     }
                 }
+                DiagnosticsNode::Session(s) => {
+                    let Some(session) = self.session(context, s.session_id) else {
+                        continue;
+                    };
+
+                    if let Some(prop) = &s.property {
+                        if !Self::is_valid_session_property(prop) {
+                            continue;
+                        }
+                        self.session_property_node_metadata(s.session_id, prop)
+                    } else {
+                        self.session_node_metadata(&session)
+                    }
+                }
+                DiagnosticsNode::MonitoredItemCount => {
+                    if !context.info.diagnostics.is_enabled() {
+                        continue;
+                    }
+                    self.monitored_item_count_metadata()
+                }
             };
             req.set(meta);
         }
@@ -646,6 +1249,12 @@ impl NodeManager for DiagnosticsNodeManager {
                     ObjectId::Server_Namespaces => {
                         self.browse_namespaces(node, &type_tree, namespaces);
                     }
+                    ObjectId::Server_ServerDiagnostics_SessionsDiagnosticsSummary => {
+                        self.browse_sessions(node, &type_tree, &self.sessions(context));
+                    }
+                    ObjectId::Server_ServerDiagnostics if context.info.diagnostics.is_enabled() => {
+                        self.browse_server_diagnostics(node, &type_tree);
+                    }
                     _ => continue,
                 }
             } else if node.node_id().namespace == self.namespace_index {
@@ -659,6 +1268,16 @@ impl NodeManager for DiagnosticsNodeManager {
                             lazy_namespaces.get_or_insert_with(|| self.namespaces(context));
                         self.browse_namespace_node(node, &type_tree, namespaces, &ns);
                     }
+                    DiagnosticsNode::Session(s) => {
+                        self.browse_session_node(context, node, &type_tree, &s);
+                    }
+                    DiagnosticsNode::MonitoredItemCount => {
+                        if context.info.diagnostics.is_enabled() {
+                            self.browse_monitored_item_count_node(node, &type_tree);
+                        } else {
+                            node.set_status(StatusCode::BadNodeIdUnknown);
+                        }
+                    }
                 }
             }
         }
@@ -688,11 +1307,101 @@ impl NodeManager for DiagnosticsNodeManager {
                         lazy_namespaces.get_or_insert_with(|| self.namespaces(context));
                     self.read_namespace_node(start_time, node, namespaces, &ns);
                 }
+                DiagnosticsNode::Session(s) => {
+                    self.read_session_node(context, start_time, node, &s);
+                }
+                DiagnosticsNode::MonitoredItemCount => {
+                    if !context.info.diagnostics.is_enabled() {
+                        node.set_error(StatusCode::BadNodeIdUnknown);
+                        continue;
+                    }
+                    match self.read_monitored_item_count_attribute(
+                        context,
+                        start_time,
+                        node.node().attribute_id,
+                    ) {
+                        Ok(v) => node.set_result(v),
+                        Err(e) => node.set_error(e),
+                    }
+                }
             }
         }
         Ok(())
     }
 
+    async fn create_monitored_items(
+        &self,
+        context: &RequestContext,
+        items: &mut [&mut CreateMonitoredItem],
+    ) -> Result<(), StatusCode> {
+        let start_time = **context.info.start_time.load();
+        for item in items {
+            // Only the monitored item count can be monitored.
+            if !context.info.diagnostics.is_enabled()
+                || !self.is_monitored_item_count_node(&item.item_to_monitor().node_id)
+            {
+                item.set_status(StatusCode::BadServiceUnsupported);
+                continue;
+            }
+            let attribute_id = item.item_to_monitor().attribute_id;
+            match self.read_monitored_item_count_attribute(context, start_time, attribute_id) {
+                Ok(v) => item.set_initial_value(v),
+                Err(e) => {
+                    item.set_status(e);
+                    continue;
+                }
+            }
+            item.set_status(StatusCode::Good);
+
+            if attribute_id == AttributeId::Value {
+                let info = context.info.clone();
+                self.sampler.add_sampler(
+                    item.item_to_monitor().node_id.clone(),
+                    attribute_id,
+                    move || Some(info.diagnostics.summary.sample_monitored_item_count()),
+                    item.monitoring_mode(),
+                    item.handle(),
+                    Duration::from_millis(item.sampling_interval() as u64),
+                );
+            }
+        }
+        Ok(())
+    }
+
+    async fn modify_monitored_items(
+        &self,
+        _context: &RequestContext,
+        items: &[&MonitoredItemUpdateRef],
+    ) {
+        for item in items {
+            self.sampler.update_sampler(
+                item.node_id(),
+                item.attribute(),
+                item.handle(),
+                Duration::from_millis(item.update().revised_sampling_interval as u64),
+            );
+        }
+    }
+
+    async fn set_monitoring_mode(
+        &self,
+        _context: &RequestContext,
+        mode: MonitoringMode,
+        items: &[&MonitoredItemRef],
+    ) {
+        for item in items {
+            self.sampler
+                .set_sampler_mode(item.node_id(), item.attribute(), item.handle(), mode);
+        }
+    }
+
+    async fn delete_monitored_items(&self, _context: &RequestContext, items: &[&MonitoredItemRef]) {
+        for item in items {
+            self.sampler
+                .remove_sampler(item.node_id(), item.attribute(), item.handle());
+        }
+    }
+
     async fn translate_browse_paths_to_node_ids(
         &self,
         context: &RequestContext,
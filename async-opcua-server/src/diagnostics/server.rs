@@ -1,6 +1,19 @@
-use opcua_types::{DataValue, ServerDiagnosticsSummaryDataType, VariableId};
+use std::{
+    collections::BTreeMap,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
 
-use super::LocalValue;
+use opcua_core::sync::RwLock;
+use opcua_types::{
+    DataValue, ExtensionObject, ServerDiagnosticsSummaryDataType, VariableId, Variant,
+};
+
+use crate::session::instance::Session;
+
+use super::{LocalValue, SessionDiagnostics};
 
 /// The server diagnostics struct, containing shared
 /// types for various forms of server diagnostics.
@@ -9,14 +22,38 @@ pub struct ServerDiagnostics {
     /// Server diagnostics summary.
     pub summary: ServerDiagnosticsSummary,
     /// Whether diagnostics are enabled or not.
-    /// Set on server startup.
-    pub enabled: bool,
+    /// Set on server startup, and by writing to the `EnabledFlag` of the server diagnostics.
+    enabled: AtomicBool,
+    /// Diagnostics for each open session, by numeric session ID.
+    pub(crate) sessions: RwLock<BTreeMap<u32, Arc<SessionDiagnostics>>>,
 }
 
 impl ServerDiagnostics {
+    pub(crate) fn new(enabled: bool) -> Self {
+        Self {
+            enabled: AtomicBool::new(enabled),
+            ..Default::default()
+        }
+    }
+
+    /// Check whether diagnostics are currently collected.
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    /// Enable or disable collecting diagnostics at runtime.
+    ///
+    /// The current session, subscription and monitored item counts, and the list of
+    /// open sessions, are kept up to date while diagnostics are disabled, so they are
+    /// correct as soon as diagnostics are enabled. Cumulated counters, and the request
+    /// counters of each session, only count events that happen while diagnostics are enabled.
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+    }
+
     /// Check if the given variable ID is managed by this object.
     pub fn is_mapped(&self, variable_id: VariableId) -> bool {
-        self.enabled && self.summary.is_mapped(variable_id)
+        self.is_enabled() && self.summary.is_mapped(variable_id)
     }
 
     /// Get the value of a diagnostics element by its ID.
@@ -26,79 +63,146 @@ impl ServerDiagnostics {
 
     /// Set the current session count.
     pub fn set_current_session_count(&self, count: u32) {
-        if self.enabled {
-            self.summary.current_session_count.set(count);
-        }
+        self.summary.current_session_count.set(count);
     }
 
     /// Set the current subscription count.
     pub fn set_current_subscription_count(&self, count: u32) {
-        if self.enabled {
-            self.summary.current_subscription_count.set(count);
-        }
+        self.summary.current_subscription_count.set(count);
+    }
+
+    /// Set the current number of monitored items across all subscriptions.
+    pub fn set_current_monitored_item_count(&self, count: u32) {
+        self.summary.current_monitored_item_count.set(count);
     }
 
     /// Increment the cumulated session count.
     pub fn inc_session_count(&self) {
-        if self.enabled {
+        if self.is_enabled() {
             self.summary.cumulated_session_count.increment();
         }
     }
 
     /// Increment the cumulated subscription count.
     pub fn inc_subscription_count(&self) {
-        if self.enabled {
+        if self.is_enabled() {
             self.summary.cumulated_subscription_count.increment();
         }
     }
 
     /// Increment the rejected requests count.
     pub fn inc_rejected_requests(&self) {
-        if self.enabled {
+        if self.is_enabled() {
             self.summary.rejected_requests_count.increment();
         }
     }
 
     /// Increment the security rejected requests count.
     pub fn inc_security_rejected_requests(&self) {
-        if self.enabled {
+        if self.is_enabled() {
             self.summary.security_rejected_requests_count.increment();
         }
     }
 
     /// Increment the security rejected session count.
     pub fn inc_security_rejected_session_count(&self) {
-        if self.enabled {
+        if self.is_enabled() {
             self.summary.security_rejected_session_count.increment();
         }
     }
 
     /// Set the number of server-created views.
     pub fn set_server_view_count(&self, count: u32) {
-        if self.enabled {
-            self.summary.server_view_count.set(count);
-        }
+        self.summary.server_view_count.set(count);
     }
 
     /// Increment the session abort count.
     pub fn inc_session_abort_count(&self) {
-        if self.enabled {
+        if self.is_enabled() {
             self.summary.session_abort_count.increment();
         }
     }
 
     /// Increment the session timeout count.
     pub fn inc_session_timeout_count(&self) {
-        if self.enabled {
+        if self.is_enabled() {
             self.summary.session_timeout_count.increment();
         }
     }
 
-    /// Set the number of publishing intervals supported by the server.
+    /// Set the number of distinct publishing intervals currently used by subscriptions on the server.
     pub fn set_publishing_interval_count(&self, count: u32) {
-        if self.enabled {
-            self.summary.publishing_interval_count.set(count);
+        self.summary.publishing_interval_count.set(count);
+    }
+
+    /// Start collecting diagnostics for a newly created session.
+    pub(crate) fn register_session(&self, session: &Session, server_uri: &str) {
+        self.sessions.write().insert(
+            session.session_id_numeric(),
+            Arc::new(SessionDiagnostics::new(session, server_uri)),
+        );
+    }
+
+    /// Update the user identity of a session after it has been activated.
+    pub(crate) fn set_session_identity(&self, session: &Session) {
+        if let Some(diagnostics) = self.sessions.read().get(&session.session_id_numeric()) {
+            diagnostics.set_identity(session);
+        }
+    }
+
+    /// Stop collecting diagnostics for a session that has been closed.
+    pub(crate) fn remove_session(&self, session_id: u32) {
+        self.sessions.write().remove(&session_id);
+    }
+
+    /// Get the diagnostics for the session with the given numeric ID,
+    /// if diagnostics are enabled.
+    pub fn session(&self, session_id: u32) -> Option<Arc<SessionDiagnostics>> {
+        if !self.is_enabled() {
+            return None;
+        }
+        self.sessions.read().get(&session_id).cloned()
+    }
+
+    /// Get the diagnostics for all open sessions, if diagnostics are enabled.
+    pub fn sessions(&self) -> Vec<Arc<SessionDiagnostics>> {
+        if !self.is_enabled() {
+            return Vec::new();
         }
+        self.sessions.read().values().cloned().collect()
+    }
+
+    /// Check if the given variable ID is one of the session diagnostics arrays.
+    pub fn is_session_array(&self, variable_id: VariableId) -> bool {
+        self.is_enabled()
+            && matches!(
+                variable_id,
+                VariableId::Server_ServerDiagnostics_SessionsDiagnosticsSummary_SessionDiagnosticsArray
+                    | VariableId::Server_ServerDiagnostics_SessionsDiagnosticsSummary_SessionSecurityDiagnosticsArray
+            )
+    }
+
+    /// Get the value of one of the session diagnostics arrays.
+    /// If `only_session` is set, the array only contains the diagnostics for that session.
+    pub fn get_session_array(
+        &self,
+        variable_id: VariableId,
+        only_session: Option<u32>,
+    ) -> Option<Variant> {
+        let sessions = self.sessions.read();
+        let sessions = sessions
+            .values()
+            .filter(|s| only_session.is_none() || only_session == Some(s.session_id_numeric()));
+        let values: Vec<_> = match variable_id {
+            VariableId::Server_ServerDiagnostics_SessionsDiagnosticsSummary_SessionDiagnosticsArray => sessions
+                .map(|s| ExtensionObject::from_message(s.get_with_time().0))
+                .collect(),
+            VariableId::Server_ServerDiagnostics_SessionsDiagnosticsSummary_SessionSecurityDiagnosticsArray => sessions
+                .map(|s| ExtensionObject::from_message(s.security_diagnostics()))
+                .collect(),
+            _ => return None,
+        };
+        Some(values.into())
     }
 }
 
@@ -114,7 +218,10 @@ pub struct ServerDiagnosticsSummary {
     current_session_count: LocalValue<u32>,
     /// The number of subscriptions that are currently active.
     current_subscription_count: LocalValue<u32>,
-    /// The number of publishing intervals that have been created since the server started.
+    /// The number of monitored items that currently exist, across all subscriptions.
+    /// This is not part of the standard `ServerDiagnosticsSummaryDataType`.
+    current_monitored_item_count: LocalValue<u32>,
+    /// The number of distinct publishing intervals currently in use.
     publishing_interval_count: LocalValue<u32>,
     /// The number of rejected requests since the server started.
     rejected_requests_count: LocalValue<u32>,
@@ -172,6 +279,17 @@ impl ServerDiagnosticsSummary {
         }
     }
 
+    /// Get the number of monitored items that currently exist on the server,
+    /// across all subscriptions.
+    pub fn current_monitored_item_count(&self) -> u32 {
+        self.current_monitored_item_count.get()
+    }
+
+    /// Get the current monitored item count as a data value.
+    pub fn sample_monitored_item_count(&self) -> DataValue {
+        self.current_monitored_item_count.sample()
+    }
+
     /// Get the current value of the server diagnostics summary.
     pub fn sample(&self) -> DataValue {
         let values = [
@@ -0,0 +1,185 @@
+use opcua_core::sync::Mutex;
+use opcua_types::{
+    profiles, ApplicationDescription, ByteString, DataValue, DateTime, MessageSecurityMode, NodeId,
+    ServiceCounterDataType, SessionDiagnosticsDataType, SessionSecurityDiagnosticsDataType,
+    UAString,
+};
+
+use crate::{identity_token::IdentityToken, session::instance::Session};
+
+use super::LocalValue;
+
+#[derive(Default)]
+struct SessionIdentity {
+    client_user_id: UAString,
+    client_user_id_history: Vec<UAString>,
+    authentication_mechanism: UAString,
+    locale_ids: Option<Vec<UAString>>,
+}
+
+/// Diagnostics for a single session. Users can read the diagnostics of their own session,
+/// users with permission to read server diagnostics can read the diagnostics of every session.
+pub struct SessionDiagnostics {
+    session_id: NodeId,
+    session_id_numeric: u32,
+    session_name: UAString,
+    client_description: ApplicationDescription,
+    server_uri: UAString,
+    endpoint_url: UAString,
+    actual_session_timeout: f64,
+    max_response_message_size: u32,
+    client_connection_time: DateTime,
+    security_mode: MessageSecurityMode,
+    security_policy_uri: UAString,
+    client_certificate: ByteString,
+    /// Time of the last request from the client.
+    client_last_contact_time: LocalValue<DateTime>,
+    /// Total number of requests made on this session.
+    total_request_count: LocalValue<u32>,
+    /// Number of requests on this session that returned a bad status.
+    total_error_count: LocalValue<u32>,
+    /// Number of requests rejected because the session was not
+    /// valid for the request.
+    unauthorized_request_count: LocalValue<u32>,
+    identity: Mutex<SessionIdentity>,
+}
+
+impl SessionDiagnostics {
+    pub(crate) fn new(session: &Session, server_uri: &str) -> Self {
+        let now = DateTime::now();
+        Self {
+            session_id: session.session_id().clone(),
+            session_id_numeric: session.session_id_numeric(),
+            session_name: session.session_name().into(),
+            client_description: session.application_description().clone(),
+            server_uri: server_uri.into(),
+            endpoint_url: session.endpoint_url().clone(),
+            actual_session_timeout: session.session_timeout().as_millis() as f64,
+            max_response_message_size: session.max_response_message_size(),
+            client_connection_time: now,
+            security_mode: session.message_security_mode(),
+            security_policy_uri: session.security_policy_uri().into(),
+            client_certificate: session
+                .client_certificate()
+                .map(|c| c.as_byte_string())
+                .unwrap_or_else(ByteString::null),
+            client_last_contact_time: LocalValue::new(now),
+            total_request_count: LocalValue::default(),
+            total_error_count: LocalValue::default(),
+            unauthorized_request_count: LocalValue::default(),
+            identity: Mutex::default(),
+        }
+    }
+
+    /// Get the ID of the session these diagnostics belong to.
+    pub fn session_id(&self) -> &NodeId {
+        &self.session_id
+    }
+
+    /// Get the numeric ID of the session these diagnostics belong to.
+    pub fn session_id_numeric(&self) -> u32 {
+        self.session_id_numeric
+    }
+
+    /// Get the name of the session these diagnostics belong to.
+    pub fn session_name(&self) -> &UAString {
+        &self.session_name
+    }
+
+    /// Update the user identity after the session is activated.
+    pub(crate) fn set_identity(&self, session: &Session) {
+        let mut identity = self.identity.lock();
+        let user_id: UAString = session.user_token().map(|t| t.0.clone()).into();
+        if !user_id.is_null() && identity.client_user_id != user_id {
+            identity.client_user_id_history.push(user_id.clone());
+        }
+        identity.client_user_id = user_id;
+        identity.authentication_mechanism = match session.user_identity() {
+            IdentityToken::Anonymous(_) => "Anonymous".into(),
+            IdentityToken::UserName(_) => "UserName".into(),
+            IdentityToken::X509(_) => "Certificate".into(),
+            IdentityToken::IssuedToken(_) => "IssuedToken".into(),
+            IdentityToken::None | IdentityToken::Invalid(_) => UAString::null(),
+        };
+        identity.locale_ids = session.locale_ids().cloned();
+    }
+
+    /// Register a request from the client.
+    pub fn on_request(&self) {
+        self.total_request_count.increment();
+        self.client_last_contact_time.set(DateTime::now());
+    }
+
+    /// Register a request that failed with a bad status code.
+    pub fn on_error(&self) {
+        self.total_error_count.increment();
+    }
+
+    /// Register a request that was rejected because the session was not valid.
+    pub fn on_unauthorized_request(&self) {
+        self.unauthorized_request_count.increment();
+        self.client_last_contact_time.set(DateTime::now());
+    }
+
+    /// Get the current value of the session diagnostics as a data value.
+    pub fn sample(&self) -> DataValue {
+        let (value, ts) = self.get_with_time();
+        DataValue::new_at(value, ts)
+    }
+
+    /// Get the current value of the session diagnostics, and the time it was last changed.
+    pub fn get_with_time(&self) -> (SessionDiagnosticsDataType, DateTime) {
+        let last_contact = self.client_last_contact_time.get_with_time();
+        let total = self.total_request_count.get_with_time();
+        let errors = self.total_error_count.get_with_time();
+        let unauthorized = self.unauthorized_request_count.get_with_time();
+        let ts = [last_contact.1, total.1, errors.1, unauthorized.1]
+            .into_iter()
+            .max()
+            .unwrap();
+        let locale_ids = self.identity.lock().locale_ids.clone();
+
+        (
+            SessionDiagnosticsDataType {
+                session_id: self.session_id.clone(),
+                session_name: self.session_name.clone(),
+                client_description: self.client_description.clone(),
+                server_uri: self.server_uri.clone(),
+                endpoint_url: self.endpoint_url.clone(),
+                locale_ids,
+                actual_session_timeout: self.actual_session_timeout,
+                max_response_message_size: self.max_response_message_size,
+                client_connection_time: self.client_connection_time,
+                client_last_contact_time: last_contact.0,
+                total_request_count: ServiceCounterDataType {
+                    total_count: total.0,
+                    error_count: errors.0,
+                },
+                unauthorized_request_count: unauthorized.0,
+                ..Default::default()
+            },
+            ts,
+        )
+    }
+
+    /// Get the current value of the session security diagnostics as a data value.
+    pub fn sample_security(&self) -> DataValue {
+        DataValue::new_at(self.security_diagnostics(), self.client_connection_time)
+    }
+
+    /// Get the current value of the session security diagnostics.
+    pub fn security_diagnostics(&self) -> SessionSecurityDiagnosticsDataType {
+        let identity = self.identity.lock();
+        SessionSecurityDiagnosticsDataType {
+            session_id: self.session_id.clone(),
+            client_user_id_of_session: identity.client_user_id.clone(),
+            client_user_id_history: Some(identity.client_user_id_history.clone()),
+            authentication_mechanism: identity.authentication_mechanism.clone(),
+            encoding: "UA Binary".into(),
+            transport_protocol: profiles::TRANSPORT_PROFILE_URI_BINARY.into(),
+            security_mode: self.security_mode,
+            security_policy_uri: self.security_policy_uri.clone(),
+            client_certificate: self.client_certificate.clone(),
+        }
+    }
+}
@@ -57,6 +57,9 @@ pub struct ServerInfo {
     pub server_pkey: Option<PrivateKey>,
     /// Operational limits
     pub(crate) operational_limits: OperationalLimits,
+    /// Maximum number of monitored items per node manager, by node manager index.
+    /// 0 means no limit.
+    pub(crate) node_manager_monitored_item_limits: Vec<usize>,
     /// Current state
     pub state: ArcSwap<ServerStateType>,
     /// Audit log
@@ -92,6 +95,15 @@ pub struct ServerInfo {
 }
 
 impl ServerInfo {
+    /// Get the maximum number of monitored items allowed on nodes owned by
+    /// the node manager at `index`, or `None` if there is no limit.
+    pub fn max_monitored_items_for_node_manager(&self, index: usize) -> Option<usize> {
+        self.node_manager_monitored_item_limits
+            .get(index)
+            .copied()
+            .filter(|l| *l > 0)
+    }
+
     /// Get the list of endpoints that match the provided filters.
     pub fn endpoints(
         &self,
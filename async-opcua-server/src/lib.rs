@@ -37,8 +37,9 @@ pub use server_handle::ServerHandle;
 pub use server_status::ServerStatusWrapper;
 pub use session::continuation_points::ContinuationPoint;
 pub use subscriptions::{
-    CreateMonitoredItem, MonitoredItem, MonitoredItemHandle, SessionSubscriptions, Subscription,
-    SubscriptionCache, SubscriptionState,
+    ConditionManager, ConditionMethod, CreateMonitoredItem, MonitoredItem, MonitoredItemHandle,
+    MonitoredItemMetrics, SessionSubscriptions, Subscription, SubscriptionCache,
+    SubscriptionMetrics, SubscriptionState,
 };
 
 /// Contains constaints for default configuration values.
@@ -90,6 +91,8 @@ pub mod constants {
     pub const MAX_NODES_PER_BROWSE: usize = 1000;
     /// Maximum number of nodes per register/deregister request.
     pub const MAX_NODES_PER_REGISTER_NODES: usize = 1000;
+    /// Maximum number of nodes registered under a handle on a single session.
+    pub const MAX_REGISTERED_NODES_PER_SESSION: usize = 10000;
     /// Maximum number of nodes per node manaument operation
     pub const MAX_NODES_PER_NODE_MANAGEMENT: usize = 1000;
     /// Maximum number of references per reference management operation.
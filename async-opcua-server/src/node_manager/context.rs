@@ -8,14 +8,19 @@ use crate::{
 };
 use opcua_core::{sync::RwLock, trace_read_lock};
 use opcua_nodes::TypeTree;
-use opcua_types::{BrowseDescriptionResultMask, NodeId};
+use opcua_types::{
+    Argument, AttributeId, BrowseDescription, BrowseDescriptionResultMask, BrowseDirection,
+    DiagnosticBits, LocalizedText, NodeClass, NodeId, ReadValueId, ReferenceTypeId,
+    TimestampsToReturn, UAString, Variant,
+};
 use parking_lot::lock_api::{RawRwLock, RwLockReadGuard};
 use tracing::debug_span;
 use tracing_futures::Instrument;
 
 use super::{
+    get_node_metadata,
     view::{ExternalReferenceRequest, NodeMetadata},
-    DefaultTypeTree, NodeManagers,
+    BrowseNode, DefaultTypeTree, NodeManagerCollection, NodeManagers, ReadNode,
 };
 
 /// Trait for providing a static reference to the type tree for a specific user.
@@ -105,6 +110,9 @@ pub struct RequestContext {
     /// Server info object, containing configuration and other shared server
     /// state.
     pub info: Arc<ServerInfo>,
+    /// Locale IDs set by the client for this request only. If this is set
+    /// it overrides the locale IDs of the session.
+    pub request_locale_ids: Option<Vec<UAString>>,
 }
 
 impl RequestContext {
@@ -112,6 +120,209 @@ impl RequestContext {
     pub fn get_type_tree_for_user<'a>(&'a self) -> Box<dyn TypeTreeReadContext + 'a> {
         self.type_tree_getter.get_type_tree_for_user(self)
     }
+
+    /// Get the preferred locale IDs for this request, in order of preference.
+    /// These are the locale IDs set on the request, if any, otherwise the
+    /// locale IDs the session was activated with.
+    pub fn locale_ids(&self) -> Vec<UAString> {
+        if let Some(locale_ids) = &self.request_locale_ids {
+            return locale_ids.clone();
+        }
+        trace_read_lock!(self.session)
+            .locale_ids()
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Pick the translation from `texts` that best matches the preferred locale IDs
+    /// of this request.
+    ///
+    /// A text with the exact locale is preferred, otherwise a text with the same language
+    /// is used, so `fr` matches `fr-CA`. If no text matches any preferred locale
+    /// the first text is returned.
+    pub fn localize(&self, texts: &[LocalizedText]) -> Option<LocalizedText> {
+        fn language(locale: &str) -> &str {
+            locale.split('-').next().unwrap_or(locale)
+        }
+
+        for locale in self.locale_ids() {
+            let locale = locale.as_ref();
+            if locale.is_empty() {
+                continue;
+            }
+            if let Some(text) = texts
+                .iter()
+                .find(|t| t.locale.as_ref().eq_ignore_ascii_case(locale))
+            {
+                return Some(text.clone());
+            }
+            if let Some(text) = texts
+                .iter()
+                .find(|t| language(t.locale.as_ref()).eq_ignore_ascii_case(language(locale)))
+            {
+                return Some(text.clone());
+            }
+        }
+        texts.first().cloned()
+    }
+
+    /// Get the input and output arguments of each method in `method_ids`.
+    ///
+    /// This browses the `HasProperty` references of the methods in the node managers that
+    /// own them, and reads the values of the `InputArguments` and `OutputArguments` properties
+    /// from the node managers that own those. A missing property means the method has no
+    /// input or output arguments. The lookups for all methods are done together, so each
+    /// node manager is called at most once for each step.
+    ///
+    /// The result for a method is `None` if it does not exist or could not be browsed,
+    /// or if one of its properties does not contain a list of arguments.
+    pub async fn get_method_arguments(
+        &self,
+        node_managers: &impl NodeManagerCollection,
+        method_ids: &[NodeId],
+    ) -> Vec<Option<(Vec<Argument>, Vec<Argument>)>> {
+        let metadata = get_node_metadata(self, node_managers, method_ids).await;
+        let mut pending: Vec<_> = method_ids
+            .iter()
+            .zip(metadata)
+            .enumerate()
+            .filter(|(_, (_, m))| {
+                m.as_ref()
+                    .is_some_and(|m| m.node_class == NodeClass::Method)
+            })
+            .map(|(idx, (id, _))| {
+                let node = BrowseNode::new(
+                    BrowseDescription {
+                        node_id: id.clone(),
+                        browse_direction: BrowseDirection::Forward,
+                        reference_type_id: ReferenceTypeId::HasProperty.into(),
+                        include_subtypes: true,
+                        node_class_mask: NodeClass::Variable as u32,
+                        result_mask: BrowseDescriptionResultMask::RESULT_MASK_BROWSE_NAME.bits(),
+                    },
+                    self.info.operational_limits.max_references_per_browse_node,
+                    0,
+                );
+                (idx, node)
+            })
+            .collect();
+        // Browsed methods, as (method index, browse node).
+        let mut browse = Vec::with_capacity(pending.len());
+        for mgr in node_managers.iter_node_managers() {
+            let (owned, rest): (Vec<_>, Vec<_>) = pending
+                .into_iter()
+                .partition(|(_, n)| mgr.owns_node(n.node_id()));
+            pending = rest;
+            if owned.is_empty() {
+                continue;
+            }
+            let (indexes, mut nodes): (Vec<_>, Vec<_>) = owned.into_iter().unzip();
+            if let Err(e) = mgr.browse(self, &mut nodes).await {
+                for node in &mut nodes {
+                    node.set_status(e);
+                }
+            }
+            browse.extend(indexes.into_iter().zip(nodes));
+        }
+
+        // Properties of each browsed method, as (method index, property ID, browse name).
+        let mut properties = Vec::new();
+        let mut external = Vec::new();
+        let mut external_methods = Vec::new();
+        for (idx, node) in &browse {
+            if node.status().is_bad() {
+                continue;
+            }
+            properties.extend(
+                node.references()
+                    .iter()
+                    .filter(|r| r.node_id.server_index == 0)
+                    .map(|r| (*idx, r.node_id.node_id.clone(), r.browse_name.name.clone())),
+            );
+            for id in node.get_external_refs() {
+                external.push(id.clone());
+                external_methods.push(*idx);
+            }
+        }
+        if !external.is_empty() {
+            let metadata = get_node_metadata(self, node_managers, &external).await;
+            properties.extend(
+                external_methods
+                    .into_iter()
+                    .zip(metadata)
+                    .filter_map(|(idx, m)| {
+                        let m = m?;
+                        Some((idx, m.node_id.node_id, m.browse_name.name))
+                    }),
+            );
+        }
+
+        // Read the argument properties of every method together.
+        let mut reads = Vec::new();
+        for (idx, id, name) in properties {
+            let is_input = match name.as_ref() {
+                "InputArguments" => true,
+                "OutputArguments" => false,
+                _ => continue,
+            };
+            let node = ReadNode::new(
+                ReadValueId {
+                    node_id: id,
+                    attribute_id: AttributeId::Value as u32,
+                    ..Default::default()
+                },
+                DiagnosticBits::empty(),
+            );
+            reads.push((idx, is_input, node));
+        }
+        for mgr in node_managers.iter_node_managers() {
+            let mut owned: Vec<_> = reads
+                .iter_mut()
+                .map(|(_, _, n)| n)
+                .filter(|n| mgr.owns_node(&n.node().node_id))
+                .collect();
+            if owned.is_empty() {
+                continue;
+            }
+            // Errors are left in the results of the read nodes.
+            let _ = mgr
+                .read(self, 0.0, TimestampsToReturn::Neither, &mut owned)
+                .await;
+        }
+
+        let mut results: Vec<_> = method_ids.iter().map(|_| None).collect();
+        for (idx, node) in &browse {
+            if node.status().is_good() {
+                results[*idx] = Some((Vec::new(), Vec::new()));
+            }
+        }
+        for (idx, is_input, node) in reads {
+            let Some((input_arguments, output_arguments)) = &mut results[idx] else {
+                continue;
+            };
+            let target = if is_input {
+                input_arguments
+            } else {
+                output_arguments
+            };
+            let arguments = match node.result.value {
+                Some(Variant::Array(arr)) => arr
+                    .values
+                    .into_iter()
+                    .map(|v| match v {
+                        Variant::ExtensionObject(o) => o.into_inner_as::<Argument>().map(|a| *a),
+                        _ => None,
+                    })
+                    .collect::<Option<Vec<_>>>(),
+                _ => None,
+            };
+            match arguments {
+                Some(arguments) => target.extend(arguments),
+                None => results[idx] = None,
+            }
+        }
+        results
+    }
 }
 
 /// Resolve a list of references.
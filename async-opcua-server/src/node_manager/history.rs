@@ -83,6 +83,17 @@ impl HistoryUpdateDetails {
             HistoryUpdateDetails::DeleteEvent(d) => &d.node_id,
         }
     }
+
+    pub(crate) fn node_id_mut(&mut self) -> &mut NodeId {
+        match self {
+            HistoryUpdateDetails::UpdateData(d) => &mut d.node_id,
+            HistoryUpdateDetails::UpdateStructureData(d) => &mut d.node_id,
+            HistoryUpdateDetails::UpdateEvent(d) => &mut d.node_id,
+            HistoryUpdateDetails::DeleteRawModified(d) => &mut d.node_id,
+            HistoryUpdateDetails::DeleteAtTime(d) => &mut d.node_id,
+            HistoryUpdateDetails::DeleteEvent(d) => &mut d.node_id,
+        }
+    }
 }
 
 /// Trait for values storable as history data.
@@ -6,21 +6,21 @@ use hashbrown::HashMap;
 use opcua_nodes::NodeType;
 
 use crate::{
-    address_space::{read_node_value, AddressSpace, CoreNamespace},
+    address_space::{read_node_value, AccessLevel, AddressSpace, CoreNamespace},
     diagnostics::NamespaceMetadata,
     load_method_args,
     node_manager::{
         MethodCall, MonitoredItemRef, MonitoredItemUpdateRef, NodeManagersRef, ParsedReadValueId,
-        RequestContext, ServerContext, SyncSampler,
+        ParsedWriteValue, RequestContext, ServerContext, SyncSampler, WriteNode,
     },
     subscriptions::CreateMonitoredItem,
     ServerCapabilities, ServerStatusWrapper,
 };
 use opcua_core::{sync::RwLock, trace_lock};
 use opcua_types::{
-    DataValue, DateTime, ExtensionObject, IdType, Identifier, MethodId, MonitoringMode, NodeId,
-    NumericRange, ObjectId, ReferenceTypeId, StatusCode, TimeZoneDataType, TimestampsToReturn,
-    VariableId, Variant, VariantScalarTypeId, VariantTypeId,
+    AttributeId, DataValue, DateTime, ExtensionObject, IdType, Identifier, MethodId,
+    MonitoringMode, NodeId, NumericRange, ObjectId, ReferenceTypeId, StatusCode, TimeZoneDataType,
+    TimestampsToReturn, VariableId, Variant, VariantScalarTypeId, VariantTypeId,
 };
 
 use super::{InMemoryNodeManager, InMemoryNodeManagerImpl, InMemoryNodeManagerImplBuilder};
@@ -72,13 +72,28 @@ impl InMemoryNodeManagerImpl for CoreNodeManagerImpl {
             .min_sampling_interval_ms
             .floor() as u64;
         let sampler_interval = if interval > 0 { interval } else { 100 };
-        self.sampler.run(
+        self.sampler.run_named(
+            context
+                .info
+                .config
+                .named_tasks
+                .then(|| "opcua-server-core-sampler".to_owned()),
             Duration::from_millis(sampler_interval),
             context.subscriptions.clone(),
         );
         // Some core methods should be generally executable
         Self::set_method_executable(address_space, MethodId::Server_GetMonitoredItems);
         Self::set_method_executable(address_space, MethodId::Server_ResendData);
+        Self::set_method_executable(address_space, MethodId::ConditionType_ConditionRefresh);
+        Self::set_method_executable(address_space, MethodId::ConditionType_ConditionRefresh2);
+        // Diagnostics can be toggled by users with the `write_diagnostics` permission,
+        // this is checked in `write_server_value`.
+        if let Some(NodeType::Variable(v)) =
+            address_space.find_mut(VariableId::Server_ServerDiagnostics_EnabledFlag)
+        {
+            v.set_access_level(v.access_level() | AccessLevel::CURRENT_WRITE);
+            v.set_user_access_level(v.user_access_level() | AccessLevel::CURRENT_WRITE);
+        }
     }
 
     fn namespaces(&self) -> Vec<NamespaceMetadata> {
@@ -132,6 +147,24 @@ impl InMemoryNodeManagerImpl for CoreNodeManagerImpl {
         Ok(())
     }
 
+    async fn write(
+        &self,
+        context: &RequestContext,
+        address_space: &RwLock<AddressSpace>,
+        nodes_to_write: &mut [&mut WriteNode],
+    ) -> Result<(), StatusCode> {
+        let address_space = address_space.read();
+        for node in nodes_to_write {
+            if address_space.find(&node.value().node_id).is_none() {
+                node.set_status(StatusCode::BadNodeIdUnknown);
+                continue;
+            }
+            let status = self.write_server_value(context, node.value());
+            node.set_status(status);
+        }
+        Ok(())
+    }
+
     async fn create_value_monitored_items(
         &self,
         context: &RequestContext,
@@ -316,16 +349,22 @@ impl CoreNodeManagerImpl {
                 (limits.max_array_length as u32).into()
             }
             VariableId::Server_ServerCapabilities_MaxBrowseContinuationPoints => {
-                (limits.max_browse_continuation_points as u16).into()
+                u16::try_from(limits.max_browse_continuation_points)
+                    .unwrap_or(u16::MAX)
+                    .into()
             }
             VariableId::Server_ServerCapabilities_MaxByteStringLength => {
                 (limits.max_byte_string_length as u32).into()
             }
             VariableId::Server_ServerCapabilities_MaxHistoryContinuationPoints => {
-                (limits.max_history_continuation_points as u16).into()
+                u16::try_from(limits.max_history_continuation_points)
+                    .unwrap_or(u16::MAX)
+                    .into()
             }
             VariableId::Server_ServerCapabilities_MaxQueryContinuationPoints => {
-                (limits.max_query_continuation_points as u16).into()
+                u16::try_from(limits.max_query_continuation_points)
+                    .unwrap_or(u16::MAX)
+                    .into()
             }
             VariableId::Server_ServerCapabilities_MaxStringLength => {
                 (limits.max_string_length as u32).into()
@@ -421,6 +460,9 @@ impl CoreNodeManagerImpl {
             }
 
             // Misc server status
+            VariableId::Server_Auditing => {
+                context.info.config.auditing.into()
+            }
             VariableId::Server_ServiceLevel => {
                 context.info.service_level.load(std::sync::atomic::Ordering::Relaxed).into()
             }
@@ -434,6 +476,10 @@ impl CoreNodeManagerImpl {
                 }).into()
             }
 
+            VariableId::Server_ServerDiagnostics_EnabledFlag => {
+                context.info.diagnostics.is_enabled().into()
+            }
+
             // ServerStatus
             VariableId::Server_ServerStatus => {
                 self.status.full_status_obj().into()
@@ -478,6 +524,10 @@ impl CoreNodeManagerImpl {
                 (self.status.state() as i32).into()
             }
 
+            VariableId::Server_ServerArray => {
+                context.info.servers.clone().into()
+            }
+
             VariableId::Server_NamespaceArray => {
                 // This actually calls into other node managers to obtain the value, in fact
                 // it calls into _this_ node manager as well.
@@ -492,6 +542,30 @@ impl CoreNodeManagerImpl {
                 namespaces.into()
             }
 
+            r if context.info.diagnostics.is_session_array(r) => {
+                let perms = context.info.authenticator.core_permissions(&context.token);
+                if !perms.read_diagnostics && context.info.config.diagnostics_require_permission {
+                    return Some(DataValue::new_now_status(Variant::Empty, StatusCode::BadUserAccessDenied));
+                }
+                // Users without access to server diagnostics can only see their own session.
+                let only_session = (!perms.read_diagnostics).then_some(context.session_id);
+                context.info.diagnostics.get_session_array(r, only_session)?
+            }
+
+            VariableId::Server_ServerDiagnostics_SubscriptionDiagnosticsArray if context.info.diagnostics.is_enabled() => {
+                let perms = context.info.authenticator.core_permissions(&context.token);
+                if !perms.read_diagnostics && context.info.config.diagnostics_require_permission {
+                    return Some(DataValue::new_now_status(Variant::Empty, StatusCode::BadUserAccessDenied));
+                }
+                // Users without access to server diagnostics can only see their own subscriptions.
+                let only_session = (!perms.read_diagnostics).then_some(context.session_id);
+                let values: Vec<_> = context.subscriptions.subscription_diagnostics(only_session)
+                    .into_iter()
+                    .map(ExtensionObject::from_message)
+                    .collect();
+                values.into()
+            }
+
             r if context.info.diagnostics.is_mapped(r) => {
                 let perms = context.info.authenticator.core_permissions(&context.token);
                 if !perms.read_diagnostics {
@@ -529,6 +603,38 @@ impl CoreNodeManagerImpl {
         })
     }
 
+    /// Write one of the few core values that can be changed at runtime.
+    /// Currently this is only the `EnabledFlag` of the server diagnostics.
+    fn write_server_value(&self, context: &RequestContext, write: &ParsedWriteValue) -> StatusCode {
+        if self.get_variable_id(&write.node_id)
+            != Some(VariableId::Server_ServerDiagnostics_EnabledFlag)
+            || write.attribute_id != AttributeId::Value
+        {
+            return StatusCode::BadNotWritable;
+        }
+        if !matches!(write.index_range, NumericRange::None) {
+            return StatusCode::BadWriteNotSupported;
+        }
+        let perms = context.info.authenticator.core_permissions(&context.token);
+        if !perms.write_diagnostics {
+            return StatusCode::BadUserAccessDenied;
+        }
+        let Some(Variant::Boolean(enabled)) = write.value.value else {
+            return StatusCode::BadTypeMismatch;
+        };
+
+        context.info.diagnostics.set_enabled(enabled);
+        context.subscriptions.notify_data_change(
+            [(
+                DataValue::new_now(enabled),
+                &write.node_id,
+                AttributeId::Value,
+            )]
+            .into_iter(),
+        );
+        StatusCode::Good
+    }
+
     fn add_aggregates(&self, address_space: &mut AddressSpace, capabilities: &ServerCapabilities) {
         for aggregate in &capabilities.history.aggregates {
             address_space.insert_reference(
@@ -559,14 +665,9 @@ impl CoreNodeManagerImpl {
         match id {
             MethodId::Server_GetMonitoredItems => {
                 let id = load_method_args!(call, UInt32)?;
-                let subs = context
+                let (ids, handles) = context
                     .subscriptions
-                    .get_session_subscriptions(context.session_id)
-                    .ok_or(StatusCode::BadSessionIdInvalid)?;
-                let subs = trace_lock!(subs);
-                let sub = subs.get(id).ok_or(StatusCode::BadSubscriptionIdInvalid)?;
-                let (ids, handles): (Vec<_>, Vec<_>) =
-                    sub.items().map(|i| (i.id(), i.client_handle())).unzip();
+                    .get_monitored_items(context.session_id, id)?;
                 call.set_outputs(vec![ids.into(), handles.into()]);
                 call.set_status(StatusCode::Good);
             }
@@ -583,6 +684,20 @@ impl CoreNodeManagerImpl {
                 sub.set_resend_data();
                 call.set_status(StatusCode::Good);
             }
+            MethodId::ConditionType_ConditionRefresh => {
+                let id = load_method_args!(call, UInt32)?;
+                context
+                    .subscriptions
+                    .condition_refresh(context.session_id, id, None)?;
+                call.set_status(StatusCode::Good);
+            }
+            MethodId::ConditionType_ConditionRefresh2 => {
+                let (id, item_id) = load_method_args!(call, UInt32, UInt32)?;
+                context
+                    .subscriptions
+                    .condition_refresh(context.session_id, id, Some(item_id))?;
+                call.set_status(StatusCode::Good);
+            }
             _ => return Err(StatusCode::BadNotSupported),
         }
         Ok(())
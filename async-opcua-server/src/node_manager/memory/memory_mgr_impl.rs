@@ -5,8 +5,8 @@ use crate::{
     diagnostics::NamespaceMetadata,
     node_manager::{
         AddNodeItem, AddReferenceItem, DeleteNodeItem, DeleteReferenceItem, HistoryNode,
-        HistoryUpdateNode, MethodCall, MonitoredItemRef, MonitoredItemUpdateRef, ParsedReadValueId,
-        RegisterNodeItem, RequestContext, ServerContext, WriteNode,
+        HistoryUpdateNode, MethodCall, MonitoredItemRef, MonitoredItemUpdateRef, NodeManagerHealth,
+        ParsedReadValueId, RegisterNodeItem, RequestContext, ServerContext, WriteNode,
     },
     subscriptions::CreateMonitoredItem,
 };
@@ -53,6 +53,21 @@ pub trait InMemoryNodeManagerImpl: Send + Sync + 'static {
     /// Return the static list of namespaces this node manager uses.
     fn namespaces(&self) -> Vec<NamespaceMetadata>;
 
+    /// Return the current health of this node manager,
+    /// see [`NodeManager::health`](crate::node_manager::NodeManager::health).
+    fn health(&self) -> NodeManagerHealth {
+        NodeManagerHealth::Healthy
+    }
+
+    /// Return whether this node manager checks access levels itself,
+    /// see [`NodeManager::checks_access_levels`](crate::node_manager::NodeManager::checks_access_levels).
+    /// Reads are checked before `read_values` is called, and the address space helpers
+    /// used to write values check access levels, so this is `true` by default.
+    /// Implementations of `write` that do not check access levels should return `false`.
+    fn checks_access_levels(&self) -> bool {
+        true
+    }
+
     /// Return whether this node should handle requests to create a node
     /// for the given parent ID. This is only called if no new node ID is
     /// requested, otherwise owns_node is called on the requested node ID.
@@ -71,6 +86,10 @@ pub trait InMemoryNodeManagerImpl: Send + Sync + 'static {
 
     /// Perform the register nodes service. The default behavior for this service is to
     /// do nothing and pretend the nodes were registered.
+    ///
+    /// Node managers may instead hand back opaque handles using
+    /// [`RegisterNodeItem::set_registered_handle`], which clients will use in place of the
+    /// original node IDs until they are unregistered.
     async fn register_nodes(
         &self,
         context: &RequestContext,
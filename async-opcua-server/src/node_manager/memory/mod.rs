@@ -3,7 +3,9 @@
 //! details to a type implementing [InMemoryNodeManagerImpl].
 
 mod memory_mgr_impl;
+mod polling;
 mod simple;
+mod value_cache;
 
 #[cfg(feature = "generated-address-space")]
 mod core;
@@ -13,12 +15,14 @@ pub use core::{CoreNodeManager, CoreNodeManagerBuilder, CoreNodeManagerImpl};
 
 pub use memory_mgr_impl::*;
 use opcua_core::{trace_read_lock, trace_write_lock};
+pub use polling::*;
 pub use simple::*;
 use tracing::warn;
 
 use std::{
     collections::{HashSet, VecDeque},
     sync::Arc,
+    time::{Duration, Instant},
 };
 
 use async_trait::async_trait;
@@ -31,15 +35,16 @@ use crate::{
     },
     diagnostics::NamespaceMetadata,
     subscriptions::CreateMonitoredItem,
-    SubscriptionCache,
+    ContinuationPoint, SubscriptionCache,
 };
 use opcua_core::sync::RwLock;
+use opcua_nodes::{AttributeQueryable, HasNodeId, TypeTree};
 use opcua_types::{
     argument::Argument, AttributeId, BrowseDescriptionResultMask, BrowseDirection, DataEncoding,
     DataValue, DateTime, ExpandedNodeId, MonitoringMode, NodeClass, NodeId, NumericRange,
-    ReadAnnotationDataDetails, ReadAtTimeDetails, ReadEventDetails, ReadProcessedDetails,
-    ReadRawModifiedDetails, ReferenceDescription, ReferenceTypeId, StatusCode, TimestampsToReturn,
-    Variant,
+    QualifiedName, QueryDataSet, ReadAnnotationDataDetails, ReadAtTimeDetails, ReadEventDetails,
+    ReadProcessedDetails, ReadRawModifiedDetails, ReferenceDescription, ReferenceTypeId,
+    StatusCode, TimestampsToReturn, Variant,
 };
 
 use super::{
@@ -47,17 +52,126 @@ use super::{
     view::{AddReferenceResult, ExternalReference, ExternalReferenceRequest, NodeMetadata},
     AddNodeItem, AddReferenceItem, BrowseNode, BrowsePathItem, DefaultTypeTree, DeleteNodeItem,
     DeleteReferenceItem, DynNodeManager, HistoryNode, HistoryUpdateDetails, HistoryUpdateNode,
-    MethodCall, MonitoredItemRef, MonitoredItemUpdateRef, NodeManager, ReadNode, RegisterNodeItem,
+    MethodCall, MonitoredItemRef, MonitoredItemUpdateRef, NodeManager, NodeManagerHealth,
+    ParsedQueryDataDescription, ParsedReadValueId, QueryRequest, ReadNode, RegisterNodeItem,
     RequestContext, ServerContext, WriteNode,
 };
 
 use crate::address_space::AddressSpace;
 
+use value_cache::ValueCache;
+
 #[derive(Default)]
 struct BrowseContinuationPoint {
     nodes: VecDeque<ReferenceDescription>,
 }
 
+struct QueryDataSetsContinuationPoint {
+    data_sets: VecDeque<QueryDataSet>,
+}
+
+/// A node in the address space being evaluated by a query content filter.
+#[derive(Clone, Copy)]
+struct QueryNode<'a> {
+    address_space: &'a AddressSpace,
+    type_tree: &'a DefaultTypeTree,
+    context: &'a RequestContext,
+    node_id: &'a NodeId,
+    type_definition: &'a NodeId,
+}
+
+impl QueryNode<'_> {
+    fn read(
+        &self,
+        node: &NodeType,
+        attribute_id: AttributeId,
+        index_range: &NumericRange,
+    ) -> Variant {
+        self.address_space
+            .read(
+                self.context,
+                &ParsedReadValueId {
+                    node_id: node.node_id().clone(),
+                    attribute_id,
+                    index_range: index_range.clone(),
+                    data_encoding: DataEncoding::Binary,
+                },
+                0.0,
+                TimestampsToReturn::Neither,
+            )
+            .value
+            .unwrap_or_default()
+    }
+
+    fn read_relative_path(&self, desc: &ParsedQueryDataDescription) -> Variant {
+        let mut node_id = self.node_id;
+        for element in desc.relative_path.elements.iter().flatten() {
+            let reference_filter = if element.reference_type_id.is_null() {
+                None
+            } else {
+                Some((element.reference_type_id.clone(), element.include_subtypes))
+            };
+            let next = self
+                .address_space
+                .find_references(
+                    node_id,
+                    reference_filter,
+                    self.type_tree,
+                    if element.is_inverse {
+                        BrowseDirection::Inverse
+                    } else {
+                        BrowseDirection::Forward
+                    },
+                )
+                .find(|rf| {
+                    self.address_space
+                        .find_node(rf.target_node)
+                        .is_some_and(|n| n.as_node().browse_name() == &element.target_name)
+                });
+            let Some(next) = next else {
+                return Variant::Empty;
+            };
+            node_id = next.target_node;
+        }
+        let Some(node) = self.address_space.find_node(node_id) else {
+            return Variant::Empty;
+        };
+        self.read(node, desc.attribute_id, &desc.index_range)
+    }
+}
+
+impl AttributeQueryable for QueryNode<'_> {
+    fn get_attribute(
+        &self,
+        type_definition_id: &NodeId,
+        browse_path: &[QualifiedName],
+        attribute_id: AttributeId,
+        index_range: &NumericRange,
+    ) -> Variant {
+        if !type_definition_id.is_null()
+            && !self
+                .type_tree
+                .is_subtype_of(self.type_definition, type_definition_id)
+        {
+            return Variant::Empty;
+        }
+        let Some(node) = self.address_space.find_node_by_browse_path(
+            self.node_id,
+            Some((ReferenceTypeId::HierarchicalReferences, true)),
+            self.type_tree,
+            BrowseDirection::Forward,
+            browse_path,
+        ) else {
+            return Variant::Empty;
+        };
+        self.read(node, attribute_id, index_range)
+    }
+
+    fn get_type(&self) -> NodeId {
+        self.type_definition.clone()
+    }
+}
+
 /// A node manager that stores its nodes in an in-memory [AddressSpace]. This
 /// only supports a static list of namespaces, and a attributes stored in memory.
 ///
@@ -67,18 +181,39 @@ pub struct InMemoryNodeManager<TImpl> {
     address_space: Arc<RwLock<AddressSpace>>,
     namespaces: HashMap<u16, String>,
     inner: TImpl,
+    value_cache: Option<ValueCache>,
 }
 
 /// Builder for the in-memory node manager.
 pub struct InMemoryNodeManagerBuilder<T> {
     impl_builder: T,
+    value_cache_ttl: Option<Duration>,
 }
 
 impl<T: InMemoryNodeManagerImplBuilder> InMemoryNodeManagerBuilder<T> {
     /// Create a new in memory node manager builder with the given
     /// builder for the [InMemoryNodeManagerImpl].
     pub fn new(impl_builder: T) -> Self {
-        Self { impl_builder }
+        Self {
+            impl_builder,
+            value_cache_ttl: None,
+        }
+    }
+
+    /// Cache values returned from [InMemoryNodeManagerImpl::read_values] for up to `ttl`.
+    ///
+    /// A `Read` with a nonzero `maxAge` is answered from the cache if the cached value
+    /// was read no more than `maxAge` milliseconds ago, and `read_values` is only called
+    /// for nodes without a fresh enough value. Values written through this node manager
+    /// are removed from the cache, but changes made elsewhere, for example directly in
+    /// the backend of a read callback, may be served stale for up to `ttl`.
+    /// Values are cached separately for each user, so a user is never served a value
+    /// that was read for a different user.
+    ///
+    /// This is useful if reading values from the backend is expensive.
+    pub fn with_value_cache(mut self, ttl: Duration) -> Self {
+        self.value_cache_ttl = Some(ttl);
+        self
     }
 }
 
@@ -86,7 +221,9 @@ impl<T: InMemoryNodeManagerImplBuilder> NodeManagerBuilder for InMemoryNodeManag
     fn build(self: Box<Self>, context: ServerContext) -> Arc<DynNodeManager> {
         let mut address_space = AddressSpace::new();
         let inner = self.impl_builder.build(context, &mut address_space);
-        Arc::new(InMemoryNodeManager::new(inner, address_space))
+        let mut node_manager = InMemoryNodeManager::new(inner, address_space);
+        node_manager.value_cache = self.value_cache_ttl.map(ValueCache::new);
+        Arc::new(node_manager)
     }
 }
 
@@ -96,6 +233,7 @@ impl<TImpl: InMemoryNodeManagerImpl> InMemoryNodeManager<TImpl> {
             namespaces: address_space.namespaces().clone(),
             address_space: Arc::new(RwLock::new(address_space)),
             inner,
+            value_cache: None,
         }
     }
 
@@ -141,6 +279,15 @@ impl<TImpl: InMemoryNodeManagerImpl> InMemoryNodeManager<TImpl> {
             }
         }
 
+        if let Some(cache) = &self.value_cache {
+            cache.invalidate(
+                output
+                    .iter()
+                    .filter(|(_, a)| *a == AttributeId::Value)
+                    .map(|(id, _)| *id),
+            );
+        }
+
         subscriptions.maybe_notify(
             output.into_iter(),
             |node_id, attribute_id, index_range, data_encoding| {
@@ -211,6 +358,10 @@ impl<TImpl: InMemoryNodeManagerImpl> InMemoryNodeManager<TImpl> {
             output.push((id, AttributeId::Value));
         }
 
+        if let Some(cache) = &self.value_cache {
+            cache.invalidate(output.iter().map(|(id, _)| *id));
+        }
+
         subscriptions.maybe_notify(
             output.into_iter(),
             |node_id, attribute_id, index_range, data_encoding| {
@@ -435,6 +586,76 @@ impl<TImpl: InMemoryNodeManagerImpl> InMemoryNodeManager<TImpl> {
         }
     }
 
+    /// Find all instances of the queried node types in the address space that match
+    /// the query filter.
+    ///
+    /// Values are read directly from the address space.
+    fn query_node_types(
+        &self,
+        address_space: &AddressSpace,
+        type_tree: &DefaultTypeTree,
+        context: &RequestContext,
+        request: &QueryRequest,
+    ) -> VecDeque<QueryDataSet> {
+        let mut found = HashSet::new();
+        let mut data_sets = VecDeque::new();
+
+        for node_type in request.node_types() {
+            let Some(type_definition) = node_type
+                .type_definition_node
+                .try_resolve(type_tree.namespaces())
+            else {
+                continue;
+            };
+            let type_ids = if node_type.include_sub_types {
+                type_tree.get_all_children(&type_definition)
+            } else {
+                vec![type_definition.as_ref()]
+            };
+
+            for type_id in type_ids {
+                for rf in address_space.find_references(
+                    type_id,
+                    Some((ReferenceTypeId::HasTypeDefinition, false)),
+                    type_tree,
+                    BrowseDirection::Inverse,
+                ) {
+                    if !self.owns_node(rf.target_node)
+                        || found.contains(rf.target_node)
+                        || !address_space.node_exists(rf.target_node)
+                    {
+                        continue;
+                    }
+                    let node = QueryNode {
+                        address_space,
+                        type_tree,
+                        context,
+                        node_id: rf.target_node,
+                        type_definition: type_id,
+                    };
+                    if !request.filter().evaluate(node, type_tree) {
+                        continue;
+                    }
+                    found.insert(rf.target_node);
+
+                    data_sets.push_back(QueryDataSet {
+                        node_id: rf.target_node.clone().into(),
+                        type_definition_node: type_id.clone().into(),
+                        values: Some(
+                            node_type
+                                .data_to_return
+                                .iter()
+                                .map(|d| node.read_relative_path(d))
+                                .collect(),
+                        ),
+                    });
+                }
+            }
+        }
+
+        data_sets
+    }
+
     fn validate_history_read_nodes<'a, 'b>(
         &self,
         context: &RequestContext,
@@ -659,6 +880,14 @@ impl<TImpl: InMemoryNodeManagerImpl> NodeManager for InMemoryNodeManager<TImpl>
         self.inner.name()
     }
 
+    fn health(&self) -> NodeManagerHealth {
+        self.inner.health()
+    }
+
+    fn checks_access_levels(&self) -> bool {
+        self.inner.checks_access_levels()
+    }
+
     #[allow(clippy::await_holding_lock)]
     async fn init(&self, type_tree: &mut DefaultTypeTree, context: ServerContext) {
         // During init we effectively own the address space, so this should be safe.
@@ -677,6 +906,19 @@ impl<TImpl: InMemoryNodeManagerImpl> NodeManager for InMemoryNodeManager<TImpl>
         self.inner.handle_new_node(parent_id)
     }
 
+    fn parent_event_notifiers(&self, node: &NodeId, type_tree: &dyn TypeTree) -> Vec<NodeId> {
+        let address_space = trace_read_lock!(self.address_space);
+        address_space
+            .find_references(
+                node,
+                Some((ReferenceTypeId::HasEventSource, true)),
+                type_tree,
+                BrowseDirection::Inverse,
+            )
+            .map(|r| r.target_node.clone())
+            .collect()
+    }
+
     async fn resolve_external_references(
         &self,
         context: &RequestContext,
@@ -745,12 +987,31 @@ impl<TImpl: InMemoryNodeManagerImpl> NodeManager for InMemoryNodeManager<TImpl>
         timestamps_to_return: TimestampsToReturn,
         nodes_to_read: &mut [&mut ReadNode],
     ) -> Result<(), StatusCode> {
+        let now = Instant::now();
         let mut read_values = Vec::new();
         {
             let address_space = trace_read_lock!(self.address_space);
             for node in nodes_to_read {
                 if node.node().attribute_id == AttributeId::Value {
-                    read_values.push(node);
+                    // Cached values are only returned if the user may read the node.
+                    let cached = self.value_cache.as_ref().and_then(|cache| {
+                        address_space
+                            .validate_node_read(context, node.node())
+                            .ok()
+                            .and_then(|_| {
+                                cache.get(
+                                    node.node(),
+                                    &context.token,
+                                    max_age,
+                                    timestamps_to_return,
+                                    now,
+                                )
+                            })
+                    });
+                    match cached {
+                        Some(value) => node.set_result(value),
+                        None => read_values.push(node),
+                    }
                     continue;
                 }
 
@@ -775,6 +1036,14 @@ impl<TImpl: InMemoryNodeManagerImpl> NodeManager for InMemoryNodeManager<TImpl>
                     timestamps_to_return,
                 )
                 .await;
+            if let Some(cache) = &self.value_cache {
+                cache.insert(
+                    ids.iter().copied().zip(values.iter()),
+                    &context.token,
+                    timestamps_to_return,
+                    now,
+                );
+            }
             for (read, value) in read_values.iter_mut().zip(values) {
                 read.set_result(value);
             }
@@ -951,6 +1220,36 @@ impl<TImpl: InMemoryNodeManagerImpl> NodeManager for InMemoryNodeManager<TImpl>
         self.inner.delete_monitored_items(context, &items).await;
     }
 
+    async fn query(
+        &self,
+        context: &RequestContext,
+        request: &mut QueryRequest,
+    ) -> Result<(), StatusCode> {
+        let mut data_sets = if let Some(point) =
+            request.take_continuation_point::<QueryDataSetsContinuationPoint>()
+        {
+            point.data_sets
+        } else {
+            let address_space = trace_read_lock!(self.address_space);
+            let type_tree = trace_read_lock!(context.type_tree);
+            self.query_node_types(&address_space, &type_tree, context, request)
+        };
+
+        while request.remaining_data_sets() > 0 {
+            let Some(data_set) = data_sets.pop_front() else {
+                break;
+            };
+            request.add_data_set(data_set);
+        }
+        if !data_sets.is_empty() {
+            request.set_next_continuation_point(Some(ContinuationPoint::new(Box::new(
+                QueryDataSetsContinuationPoint { data_sets },
+            ))));
+        }
+
+        Ok(())
+    }
+
     async fn history_read_raw_modified(
         &self,
         context: &RequestContext,
@@ -1021,9 +1320,19 @@ impl<TImpl: InMemoryNodeManagerImpl> NodeManager for InMemoryNodeManager<TImpl>
         context: &RequestContext,
         nodes_to_write: &mut [&mut WriteNode],
     ) -> Result<(), StatusCode> {
-        self.inner
+        let res = self
+            .inner
             .write(context, &self.address_space, nodes_to_write)
-            .await
+            .await;
+        if let Some(cache) = &self.value_cache {
+            cache.invalidate(
+                nodes_to_write
+                    .iter()
+                    .filter(|n| n.value().attribute_id == AttributeId::Value)
+                    .map(|n| &n.value().node_id),
+            );
+        }
+        res
     }
 
     async fn history_update(
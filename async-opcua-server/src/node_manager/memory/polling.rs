@@ -0,0 +1,499 @@
+use std::{
+    collections::{HashMap, HashSet},
+    future::Future,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use opcua_core::{
+    sync::{Mutex, RwLock},
+    task::spawn_named,
+    trace_read_lock, trace_write_lock,
+};
+use opcua_nodes::NodeType;
+use opcua_types::{
+    AttributeId, DataChangeTrigger, DataValue, Deadband, MonitoringMode, NodeId, StatusCode,
+    TimestampsToReturn, Variant,
+};
+use tokio_util::sync::{CancellationToken, DropGuard};
+
+use crate::{
+    address_space::{read_node_value, AddressSpace},
+    node_manager::{
+        MonitoredItemRef, MonitoredItemUpdateRef, NodeManagerBuilder, ParsedReadValueId,
+        RequestContext, ServerContext,
+    },
+    CreateMonitoredItem, MonitoredItemHandle, SubscriptionCache,
+};
+
+use super::{
+    InMemoryNodeManager, InMemoryNodeManagerBuilder, InMemoryNodeManagerImpl,
+    InMemoryNodeManagerImplBuilder, NamespaceMetadata,
+};
+
+/// A node manager for servers backed by a poll-based data source, such as a
+/// Modbus device or a REST API.
+///
+/// The address space is kept in memory, while the values of nodes registered with
+/// [`PollingNodeManagerImpl::add_polled_node`] are sampled from a [`PollingSource`],
+/// both on `Read` and periodically for monitored items.
+pub type PollingNodeManager<S> = InMemoryNodeManager<PollingNodeManagerImpl<S>>;
+
+/// A data source sampled by the [`PollingNodeManager`].
+///
+/// This is implemented for async closures taking a `Vec<NodeId>` and returning
+/// a `Vec<DataValue>`.
+#[async_trait]
+pub trait PollingSource: Send + Sync + 'static {
+    /// Sample the current values of `nodes`, returning one value per node, in the same order.
+    ///
+    /// Values missing from the result are reported as `BadNoCommunication`.
+    async fn sample(&self, nodes: &[NodeId]) -> Vec<DataValue>;
+}
+
+#[async_trait]
+impl<F, Fut> PollingSource for F
+where
+    F: Fn(Vec<NodeId>) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Vec<DataValue>> + Send,
+{
+    async fn sample(&self, nodes: &[NodeId]) -> Vec<DataValue> {
+        self(nodes.to_vec()).await
+    }
+}
+
+/// Builder for the [`PollingNodeManager`].
+pub struct PollingNodeManagerBuilder<S> {
+    namespace: NamespaceMetadata,
+    name: String,
+    source: S,
+}
+
+impl<S: PollingSource> PollingNodeManagerBuilder<S> {
+    /// Create a new polling node manager builder with the given namespace,
+    /// name, and data source.
+    pub fn new(namespace: NamespaceMetadata, name: &str, source: S) -> Self {
+        Self {
+            namespace,
+            name: name.to_owned(),
+            source,
+        }
+    }
+}
+
+impl<S: PollingSource> InMemoryNodeManagerImplBuilder for PollingNodeManagerBuilder<S> {
+    type Impl = PollingNodeManagerImpl<S>;
+
+    fn build(mut self, context: ServerContext, address_space: &mut AddressSpace) -> Self::Impl {
+        {
+            let mut type_tree = context.type_tree.write();
+            self.namespace.namespace_index = type_tree
+                .namespaces_mut()
+                .add_namespace(&self.namespace.namespace_uri);
+        }
+        address_space.add_namespace(
+            &self.namespace.namespace_uri,
+            self.namespace.namespace_index,
+        );
+        PollingNodeManagerImpl::new(self.namespace, &self.name, self.source)
+    }
+}
+
+/// Create a node manager builder for the polling node manager with the given
+/// namespace, name, and data source.
+pub fn polling_node_manager<S: PollingSource>(
+    namespace: NamespaceMetadata,
+    name: &str,
+    source: S,
+) -> impl NodeManagerBuilder {
+    InMemoryNodeManagerBuilder::new(PollingNodeManagerBuilder::new(namespace, name, source))
+}
+
+struct PolledValue {
+    value: DataValue,
+    sampled_at: Instant,
+}
+
+struct ItemRef {
+    mode: MonitoringMode,
+    sampling_interval: Duration,
+}
+
+#[derive(Default)]
+struct MonitoredNode {
+    items: HashMap<MonitoredItemHandle, ItemRef>,
+    sampling_interval: Option<Duration>,
+    last_sample: Option<Instant>,
+}
+
+impl MonitoredNode {
+    fn refresh(&mut self) {
+        self.sampling_interval = self
+            .items
+            .values()
+            .filter(|i| i.mode != MonitoringMode::Disabled)
+            .map(|i| i.sampling_interval)
+            .min();
+    }
+
+    fn is_due(&self, now: Instant) -> bool {
+        match (self.sampling_interval, self.last_sample) {
+            (None, _) => false,
+            (Some(_), None) => true,
+            (Some(interval), Some(last)) => last + interval <= now,
+        }
+    }
+}
+
+struct PollingState<S> {
+    source: S,
+    polled: RwLock<HashSet<NodeId>>,
+    values: RwLock<HashMap<NodeId, PolledValue>>,
+    monitored: Mutex<HashMap<NodeId, MonitoredNode>>,
+}
+
+impl<S: PollingSource> PollingState<S> {
+    /// Sample `nodes` from the source, store the new values, and notify
+    /// subscriptions about any values that changed since the last sample.
+    async fn sample(&self, nodes: &[NodeId], subscriptions: &SubscriptionCache) {
+        let mut sampled = self.source.sample(nodes).await.into_iter();
+        let now = Instant::now();
+        let mut changed = Vec::new();
+        {
+            let mut values = trace_write_lock!(self.values);
+            for node_id in nodes {
+                let value = sampled.next().unwrap_or_else(|| {
+                    DataValue::new_now_status(Variant::Empty, StatusCode::BadNoCommunication)
+                });
+                let is_changed = values.get(node_id).is_none_or(|prev| {
+                    value.has_changed(&prev.value, DataChangeTrigger::StatusValue, &Deadband::None)
+                });
+                if is_changed {
+                    changed.push((value.clone(), node_id));
+                }
+                values.insert(
+                    node_id.clone(),
+                    PolledValue {
+                        value,
+                        sampled_at: now,
+                    },
+                );
+            }
+        }
+        subscriptions.notify_data_change(
+            changed
+                .into_iter()
+                .map(|(value, node_id)| (value, node_id, AttributeId::Value)),
+        );
+    }
+
+    async fn run(&self, interval: Duration, subscriptions: Arc<SubscriptionCache>) {
+        let mut tick = tokio::time::interval(interval);
+        tick.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        loop {
+            tick.tick().await;
+            let now = Instant::now();
+            let due: Vec<_> = {
+                let mut monitored = self.monitored.lock();
+                monitored
+                    .iter_mut()
+                    .filter(|(_, node)| node.is_due(now))
+                    .map(|(node_id, node)| {
+                        node.last_sample = Some(now);
+                        node_id.clone()
+                    })
+                    .collect()
+            };
+            if !due.is_empty() {
+                self.sample(&due, &subscriptions).await;
+            }
+        }
+    }
+
+    fn update_item(
+        &self,
+        node_id: &NodeId,
+        handle: MonitoredItemHandle,
+        update: impl FnOnce(&mut ItemRef),
+    ) {
+        let mut monitored = self.monitored.lock();
+        if let Some(node) = monitored.get_mut(node_id) {
+            if let Some(item) = node.items.get_mut(&handle) {
+                update(item);
+                node.refresh();
+            }
+        }
+    }
+}
+
+/// Read a sampled value like the Value attribute of a variable, applying
+/// the index range and the timestamps to return.
+fn read_polled_value(
+    value: &DataValue,
+    node: &ParsedReadValueId,
+    timestamps_to_return: TimestampsToReturn,
+) -> DataValue {
+    let mut result = value.clone();
+    if let Some(v) = &value.value {
+        match v.range_of(&node.index_range) {
+            Ok(v) => result.value = Some(v),
+            Err(e) => {
+                result.value = None;
+                result.status = Some(e);
+            }
+        }
+    }
+    if matches!(
+        timestamps_to_return,
+        TimestampsToReturn::Server | TimestampsToReturn::Neither
+    ) {
+        result.source_timestamp = None;
+        result.source_picoseconds = None;
+    }
+    if matches!(
+        timestamps_to_return,
+        TimestampsToReturn::Source | TimestampsToReturn::Neither
+    ) {
+        result.server_timestamp = None;
+        result.server_picoseconds = None;
+    }
+    result
+}
+
+/// Node manager implementation that samples values from a poll-based
+/// [`PollingSource`], see [`PollingNodeManager`].
+///
+/// Reads of polled nodes are served from the last sample if it is no older than
+/// the requested `maxAge`, otherwise the source is sampled. Monitored items on polled
+/// nodes are sampled at their sampling interval, and only changes in value or status
+/// are reported.
+pub struct PollingNodeManagerImpl<S> {
+    state: Arc<PollingState<S>>,
+    namespace: NamespaceMetadata,
+    name: String,
+    token: CancellationToken,
+    _guard: DropGuard,
+}
+
+impl<S: PollingSource> PollingNodeManagerImpl<S> {
+    fn new(namespace: NamespaceMetadata, name: &str, source: S) -> Self {
+        let token = CancellationToken::new();
+        Self {
+            state: Arc::new(PollingState {
+                source,
+                polled: Default::default(),
+                values: Default::default(),
+                monitored: Default::default(),
+            }),
+            namespace,
+            name: name.to_owned(),
+            _guard: token.clone().drop_guard(),
+            token,
+        }
+    }
+
+    /// Get a reference to the data source of this node manager.
+    pub fn source(&self) -> &S {
+        &self.state.source
+    }
+
+    /// Sample the value of the node given by `id` from the data source.
+    /// The node must be a variable in the address space of this node manager.
+    pub fn add_polled_node(&self, id: NodeId) {
+        trace_write_lock!(self.state.polled).insert(id);
+    }
+
+    /// Stop sampling the value of the node given by `id`. Reads of the node
+    /// will return the value stored in the address space.
+    pub fn remove_polled_node(&self, id: &NodeId) {
+        trace_write_lock!(self.state.polled).remove(id);
+        trace_write_lock!(self.state.values).remove(id);
+    }
+
+    fn is_polled(&self, node: &ParsedReadValueId) -> bool {
+        node.attribute_id == AttributeId::Value
+            && trace_read_lock!(self.state.polled).contains(&node.node_id)
+    }
+}
+
+#[async_trait]
+impl<S: PollingSource> InMemoryNodeManagerImpl for PollingNodeManagerImpl<S> {
+    async fn init(&self, _address_space: &mut AddressSpace, context: ServerContext) {
+        let interval = Duration::from_millis(
+            context
+                .info
+                .config
+                .limits
+                .subscriptions
+                .min_sampling_interval_ms as u64,
+        )
+        .max(Duration::from_millis(1));
+        let state = self.state.clone();
+        let token = self.token.clone();
+        let subscriptions = context.subscriptions.clone();
+        spawn_named(Some(format!("{} polling", self.name)), async move {
+            tokio::select! {
+                _ = state.run(interval, subscriptions) => {},
+                _ = token.cancelled() => {}
+            }
+        });
+    }
+
+    fn namespaces(&self) -> Vec<NamespaceMetadata> {
+        vec![self.namespace.clone()]
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn read_values(
+        &self,
+        context: &RequestContext,
+        address_space: &RwLock<AddressSpace>,
+        nodes: &[&ParsedReadValueId],
+        max_age: f64,
+        timestamps_to_return: TimestampsToReturn,
+    ) -> Vec<DataValue> {
+        // Find the polled nodes the user may read that have no recent enough sample.
+        let to_sample: Vec<_> = {
+            let address_space = trace_read_lock!(address_space);
+            let values = trace_read_lock!(self.state.values);
+            let now = Instant::now();
+            let max_age =
+                Duration::try_from_secs_f64(max_age.max(0.0) / 1000.0).unwrap_or(Duration::MAX);
+            nodes
+                .iter()
+                .filter(|n| self.is_polled(n))
+                .filter(|n| address_space.validate_node_read(context, n).is_ok())
+                .filter(|n| {
+                    values
+                        .get(&n.node_id)
+                        .is_none_or(|v| now.saturating_duration_since(v.sampled_at) > max_age)
+                })
+                .map(|n| n.node_id.clone())
+                .collect::<HashSet<_>>()
+                .into_iter()
+                .collect()
+        };
+        if !to_sample.is_empty() {
+            self.state.sample(&to_sample, &context.subscriptions).await;
+        }
+
+        let address_space = trace_read_lock!(address_space);
+        let values = trace_read_lock!(self.state.values);
+        nodes
+            .iter()
+            .map(|n| {
+                let node = match address_space.validate_node_read(context, n) {
+                    Ok(_) => address_space.find(&n.node_id),
+                    Err(e) => {
+                        return DataValue {
+                            status: Some(e),
+                            ..Default::default()
+                        }
+                    }
+                };
+                let Some(node) = node else {
+                    return DataValue {
+                        status: Some(StatusCode::BadNodeIdUnknown),
+                        ..Default::default()
+                    };
+                };
+                if let (NodeType::Variable(_), Some(polled)) = (node, values.get(&n.node_id)) {
+                    if self.is_polled(n) {
+                        return read_polled_value(&polled.value, n, timestamps_to_return);
+                    }
+                }
+                read_node_value(node, context, n, max_age, timestamps_to_return)
+            })
+            .collect()
+    }
+
+    async fn create_value_monitored_items(
+        &self,
+        context: &RequestContext,
+        address_space: &RwLock<AddressSpace>,
+        items: &mut [&mut &mut CreateMonitoredItem],
+    ) {
+        let to_read: Vec<_> = items.iter().map(|r| r.item_to_monitor()).collect();
+        let values = self
+            .read_values(
+                context,
+                address_space,
+                &to_read,
+                0.0,
+                TimestampsToReturn::Both,
+            )
+            .await;
+
+        let now = Instant::now();
+        let mut monitored = self.state.monitored.lock();
+        for (value, node) in values.into_iter().zip(items.iter_mut()) {
+            if value.status() != StatusCode::BadAttributeIdInvalid {
+                node.set_initial_value(value);
+            }
+            node.set_status(StatusCode::Good);
+
+            if !self.is_polled(node.item_to_monitor()) {
+                continue;
+            }
+            let entry = monitored
+                .entry(node.item_to_monitor().node_id.clone())
+                .or_insert_with(|| MonitoredNode {
+                    // The node was just sampled by the read above.
+                    last_sample: Some(now),
+                    ..Default::default()
+                });
+            entry.items.insert(
+                node.handle(),
+                ItemRef {
+                    mode: node.monitoring_mode(),
+                    sampling_interval: Duration::from_millis(node.sampling_interval() as u64),
+                },
+            );
+            entry.refresh();
+        }
+    }
+
+    async fn modify_monitored_items(
+        &self,
+        _context: &RequestContext,
+        items: &[&MonitoredItemUpdateRef],
+    ) {
+        for it in items {
+            let interval = Duration::from_millis(it.update().revised_sampling_interval as u64);
+            self.state.update_item(it.node_id(), it.handle(), |item| {
+                item.sampling_interval = interval
+            });
+        }
+    }
+
+    async fn set_monitoring_mode(
+        &self,
+        _context: &RequestContext,
+        mode: MonitoringMode,
+        items: &[&MonitoredItemRef],
+    ) {
+        for it in items {
+            self.state
+                .update_item(it.node_id(), it.handle(), |item| item.mode = mode);
+        }
+    }
+
+    async fn delete_monitored_items(&self, _context: &RequestContext, items: &[&MonitoredItemRef]) {
+        let mut monitored = self.state.monitored.lock();
+        for it in items {
+            let Some(node) = monitored.get_mut(it.node_id()) else {
+                continue;
+            };
+            node.items.remove(&it.handle());
+            if node.items.is_empty() {
+                monitored.remove(it.node_id());
+            } else {
+                node.refresh();
+            }
+        }
+    }
+}
@@ -0,0 +1,140 @@
+use std::time::{Duration, Instant};
+
+use hashbrown::{Equivalent, HashMap, HashSet};
+use opcua_core::{sync::RwLock, trace_read_lock, trace_write_lock};
+use opcua_types::{DataEncoding, DataValue, NodeId, NumericRange, TimestampsToReturn};
+
+use crate::{authenticator::UserToken, node_manager::ParsedReadValueId};
+
+struct CachedValue {
+    value: DataValue,
+    read_at: Instant,
+}
+
+#[derive(Debug, PartialEq, Eq, Hash)]
+struct CacheKey {
+    node_id: NodeId,
+    user: String,
+    timestamps_to_return: i32,
+}
+
+#[derive(Debug, PartialEq, Eq, Hash)]
+struct CacheKeyRef<'a> {
+    node_id: &'a NodeId,
+    user: &'a str,
+    timestamps_to_return: i32,
+}
+
+impl Equivalent<CacheKey> for CacheKeyRef<'_> {
+    fn equivalent(&self, key: &CacheKey) -> bool {
+        self.node_id == &key.node_id
+            && self.user == key.user
+            && self.timestamps_to_return == key.timestamps_to_return
+    }
+}
+
+struct CacheInner {
+    values: HashMap<CacheKey, CachedValue>,
+    /// Time of the next sweep for expired entries.
+    next_sweep: Instant,
+}
+
+/// Cache of values returned by
+/// [InMemoryNodeManagerImpl::read_values](super::InMemoryNodeManagerImpl::read_values),
+/// used to honor the `maxAge` parameter of `Read` requests.
+///
+/// Only reads of the full value with the default encoding are cached, keyed by
+/// node ID, user and the requested timestamps, since `read_values` may return
+/// different values to different users. Expired entries are ignored on lookup,
+/// and removed by a sweep at most once per time to live.
+pub(super) struct ValueCache {
+    ttl: Duration,
+    inner: RwLock<CacheInner>,
+}
+
+impl ValueCache {
+    pub(super) fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            inner: RwLock::new(CacheInner {
+                values: HashMap::new(),
+                next_sweep: Instant::now() + ttl,
+            }),
+        }
+    }
+
+    fn is_cacheable(node: &ParsedReadValueId) -> bool {
+        node.index_range == NumericRange::None && matches!(node.data_encoding, DataEncoding::Binary)
+    }
+
+    /// Get a cached value for `node` read by `user`, if one was read no more than `max_age`
+    /// milliseconds ago, and is still within the time to live of the cache.
+    pub(super) fn get(
+        &self,
+        node: &ParsedReadValueId,
+        user: &UserToken,
+        max_age: f64,
+        timestamps_to_return: TimestampsToReturn,
+        now: Instant,
+    ) -> Option<DataValue> {
+        if max_age <= 0.0 || !Self::is_cacheable(node) {
+            return None;
+        }
+        let max_age = Duration::try_from_secs_f64(max_age / 1000.0)
+            .unwrap_or(Duration::MAX)
+            .min(self.ttl);
+        let inner = trace_read_lock!(self.inner);
+        let cached = inner.values.get(&CacheKeyRef {
+            node_id: &node.node_id,
+            user: &user.0,
+            timestamps_to_return: timestamps_to_return as i32,
+        })?;
+        (now.saturating_duration_since(cached.read_at) <= max_age).then(|| cached.value.clone())
+    }
+
+    /// Store values read from the node manager for `user`. Values with a bad status
+    /// are not cached.
+    pub(super) fn insert<'a>(
+        &self,
+        values: impl Iterator<Item = (&'a ParsedReadValueId, &'a DataValue)>,
+        user: &UserToken,
+        timestamps_to_return: TimestampsToReturn,
+        now: Instant,
+    ) {
+        let mut inner = trace_write_lock!(self.inner);
+        // Drop expired entries every so often, so the cache does not grow
+        // with nodes that are no longer read.
+        if now >= inner.next_sweep {
+            inner
+                .values
+                .retain(|_, v| now.saturating_duration_since(v.read_at) <= self.ttl);
+            inner.next_sweep = now + self.ttl;
+        }
+        for (node, value) in values {
+            if !Self::is_cacheable(node) || value.status().is_bad() {
+                continue;
+            }
+            inner.values.insert(
+                CacheKey {
+                    node_id: node.node_id.clone(),
+                    user: user.0.clone(),
+                    timestamps_to_return: timestamps_to_return as i32,
+                },
+                CachedValue {
+                    value: value.clone(),
+                    read_at: now,
+                },
+            );
+        }
+    }
+
+    /// Remove any cached values for the given nodes, after they have been written.
+    pub(super) fn invalidate<'a>(&self, node_ids: impl Iterator<Item = &'a NodeId>) {
+        let mut inner = trace_write_lock!(self.inner);
+        if inner.values.is_empty() {
+            return;
+        }
+        let node_ids: HashSet<_> = node_ids.collect();
+        inner.values.retain(|k, _| !node_ids.contains(&k.node_id));
+    }
+}
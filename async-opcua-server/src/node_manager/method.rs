@@ -1,6 +1,7 @@
+use opcua_nodes::TypeTree;
 use opcua_types::{
-    CallMethodRequest, CallMethodResult, DiagnosticBits, DiagnosticInfo, NodeId, StatusCode,
-    Variant,
+    Argument, CallMethodRequest, CallMethodResult, DataTypeId, DiagnosticBits, DiagnosticInfo,
+    NodeId, StatusCode, Variant,
 };
 
 use super::IntoResult;
@@ -82,6 +83,97 @@ impl MethodCall {
     pub fn set_diagnostic_info(&mut self, diagnostic_info: DiagnosticInfo) {
         self.diagnostic_info = Some(diagnostic_info);
     }
+
+    /// Validate the arguments of this call against the `InputArguments` of the method.
+    ///
+    /// Sets the status to `BadArgumentsMissing` or `BadTooManyArguments` if the number
+    /// of arguments is wrong, or to `BadInvalidArgument` with `BadTypeMismatch` for each
+    /// argument that does not match the declared data type and value rank.
+    /// Returns `true` if the arguments are valid.
+    pub fn validate_arguments(
+        &mut self,
+        input_arguments: &[Argument],
+        type_tree: &dyn TypeTree,
+    ) -> bool {
+        if self.arguments.len() < input_arguments.len() {
+            self.status = StatusCode::BadArgumentsMissing;
+            return false;
+        }
+        if self.arguments.len() > input_arguments.len() {
+            self.status = StatusCode::BadTooManyArguments;
+            return false;
+        }
+
+        let results: Vec<_> = self
+            .arguments
+            .iter()
+            .zip(input_arguments)
+            .map(|(value, arg)| {
+                if argument_matches(value, arg, type_tree) {
+                    StatusCode::Good
+                } else {
+                    StatusCode::BadTypeMismatch
+                }
+            })
+            .collect();
+        if results.iter().any(|r| r.is_bad()) {
+            self.set_argument_error(results);
+            return false;
+        }
+        true
+    }
+}
+
+/// Check whether `value` matches the value rank and data type of `argument`.
+fn argument_matches(value: &Variant, argument: &Argument, type_tree: &dyn TypeTree) -> bool {
+    // Arguments of type `BaseDataType` accept any value.
+    if argument.data_type == DataTypeId::BaseDataType {
+        return true;
+    }
+    let rank_matches = match (argument.value_rank, value) {
+        (-2, _) => true,
+        (-1, v) => !v.is_array(),
+        (-3, Variant::Array(arr)) => arr.dimensions.as_ref().is_none_or(|d| d.len() <= 1),
+        (-3, _) => true,
+        (_, v) => v.is_array(),
+    };
+    if !rank_matches {
+        return false;
+    }
+
+    match value {
+        Variant::Array(arr) => arr
+            .values
+            .iter()
+            .all(|v| scalar_type_matches(v, &argument.data_type, type_tree)),
+        v => scalar_type_matches(v, &argument.data_type, type_tree),
+    }
+}
+
+fn scalar_type_matches(value: &Variant, data_type: &NodeId, type_tree: &dyn TypeTree) -> bool {
+    // Null values are passed on to the implementation.
+    if matches!(value, Variant::Empty) {
+        return true;
+    }
+    let value_type = match value {
+        Variant::ExtensionObject(o) => o.data_type(),
+        v => v.data_type(),
+    };
+    // Types we cannot resolve are left for the implementation to check.
+    let Some(value_type) = value_type.filter(|t| t.namespace_uri.is_null()) else {
+        return true;
+    };
+    let value_type = value_type.node_id;
+    if type_tree.get_node(&value_type).is_none() || type_tree.get_node(data_type).is_none() {
+        return true;
+    }
+
+    // Allow values of a more specific type, and values of the base type of
+    // a more specific argument type, like a `Double` for a `Duration`.
+    type_tree.is_subtype_of(&value_type, data_type)
+        || type_tree.is_subtype_of(data_type, &value_type)
+        || matches!(value, Variant::Int32(_))
+            && type_tree.is_subtype_of(data_type, &DataTypeId::Enumeration.into())
 }
 
 impl IntoResult for MethodCall {
@@ -5,13 +5,14 @@
 
 use std::{
     any::{Any, TypeId},
+    collections::{HashSet, VecDeque},
     ops::Index,
     sync::{Arc, Weak},
 };
 
 use async_trait::async_trait;
 use opcua_core::sync::RwLock;
-use opcua_nodes::DefaultTypeTree;
+use opcua_nodes::{DefaultTypeTree, TypeTree};
 use opcua_types::{
     ExpandedNodeId, MonitoringMode, NodeId, ReadAnnotationDataDetails, ReadAtTimeDetails,
     ReadEventDetails, ReadProcessedDetails, ReadRawModifiedDetails, StatusCode, TimestampsToReturn,
@@ -59,6 +60,29 @@ pub(crate) use history::HistoryReadDetails;
 pub(crate) use query::QueryContinuationPoint;
 pub(crate) use view::{BrowseContinuationPoint, ExternalReferencesContPoint};
 
+/// Health of a node manager, reported by [`NodeManager::health`].
+///
+/// This lets node managers backed by an external system, for example
+/// an aggregating server, report that the system is unavailable, so that
+/// the server can fail requests immediately instead of waiting for it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NodeManagerHealth {
+    /// The node manager is ready to handle requests.
+    #[default]
+    Healthy,
+    /// The node manager cannot currently handle requests. Reads of nodes owned by
+    /// the node manager are not passed to it, and instead fail with the given status,
+    /// typically `BadServerNotConnected`, or `UncertainNoCommunicationLastUsableValue`.
+    Unhealthy(StatusCode),
+}
+
+impl NodeManagerHealth {
+    /// Return `true` if the node manager is ready to handle requests.
+    pub fn is_healthy(&self) -> bool {
+        matches!(self, Self::Healthy)
+    }
+}
+
 /// Trait for a collection of node managers, to allow abstracting over
 /// weak or strong references to the node manager collection.
 pub trait NodeManagerCollection {
@@ -137,6 +161,27 @@ impl NodeManagers {
         None
     }
 
+    /// Find `source` and every notifier above it in the event hierarchy, by following
+    /// inverse `HasEventSource` references, including `HasNotifier`, in all node managers.
+    ///
+    /// Events raised by `source` should be reported to monitored items on each of these nodes.
+    pub fn event_notifiers(&self, source: &NodeId, type_tree: &dyn TypeTree) -> Vec<NodeId> {
+        let mut found = HashSet::new();
+        let mut queue = VecDeque::new();
+        found.insert(source.clone());
+        queue.push_back(source.clone());
+        while let Some(node) = queue.pop_front() {
+            for mgr in self {
+                for notifier in mgr.parent_event_notifiers(&node, type_tree) {
+                    if found.insert(notifier.clone()) {
+                        queue.push_back(notifier);
+                    }
+                }
+            }
+        }
+        found.into_iter().collect()
+    }
+
     /// Create a weak reference to the node managers.
     /// A node manager should avoid holding a copy of the `NodeManagers` object since that
     /// results in a circular reference which will leak memory once dropped.
@@ -290,6 +335,22 @@ pub trait NodeManager: IntoAnyArc + Any {
     /// Name of this node manager, for debug purposes.
     fn name(&self) -> &str;
 
+    /// Return the current health of this node manager. This is checked before
+    /// each Read service call that targets nodes owned by this node manager,
+    /// so it should be cheap, for example by reading a cached connection state.
+    fn health(&self) -> NodeManagerHealth {
+        NodeManagerHealth::Healthy
+    }
+
+    /// Return whether this node manager checks the `AccessLevel` and `UserAccessLevel`
+    /// of variables itself when reading and writing their values. If this returns `true`
+    /// the server does not read the access levels of nodes owned by this node manager
+    /// before dispatching reads and writes, see
+    /// [`ServerConfig::check_access_levels`](crate::ServerConfig::check_access_levels).
+    fn checks_access_levels(&self) -> bool {
+        false
+    }
+
     /// Return whether this node manager owns events on the server.
     /// The first node manager that returns true here will be called when
     /// reading or updating historical server events.
@@ -308,6 +369,16 @@ pub trait NodeManager: IntoAnyArc + Any {
         false
     }
 
+    /// Return the nodes directly above `node` in the event hierarchy, that is,
+    /// the sources of any `HasEventSource` reference, or subtype, targeting `node`.
+    ///
+    /// This is used to propagate events raised with [`ServerHandle::notify_events_from`](crate::ServerHandle::notify_events_from)
+    /// to notifiers above the event source. Node managers should return any such
+    /// references they know about, even if they do not own `node`.
+    fn parent_event_notifiers(&self, node: &NodeId, type_tree: &dyn TypeTree) -> Vec<NodeId> {
+        Vec::new()
+    }
+
     /// Namespaces for a given user, used to populate the namespace array.
     /// This being a method allows different users to see different namespaces.
     fn namespaces_for_user(&self, context: &RequestContext) -> Vec<NamespaceMetadata>;
@@ -317,6 +388,10 @@ pub trait NodeManager: IntoAnyArc + Any {
     async fn init(&self, type_tree: &mut DefaultTypeTree, context: ServerContext);
 
     /// Resolve a list of references given by a different node manager.
+    ///
+    /// This is called once per service call with every node owned by this node manager,
+    /// both when browsing and when translating browse paths, so node managers backed by
+    /// an external store can fetch metadata for all the requested nodes in a single query.
     async fn resolve_external_references(
         &self,
         context: &RequestContext,
@@ -350,6 +425,8 @@ pub trait NodeManager: IntoAnyArc + Any {
 
     /// Perform the history read processed service. This should write results
     /// to the `nodes` list of type `HistoryData`.
+    ///
+    /// [`AggregateCalculator`] can be used to compute common aggregates from raw values.
     async fn history_read_processed(
         &self,
         context: &RequestContext,
@@ -360,8 +437,10 @@ pub trait NodeManager: IntoAnyArc + Any {
         Err(StatusCode::BadHistoryOperationUnsupported)
     }
 
-    /// Perform the history read processed service. This should write results
+    /// Perform the history read at time service. This should write results
     /// to the `nodes` list of type `HistoryData`.
+    ///
+    /// [`AggregateCalculator::values_at_time`] can be used to interpolate raw values.
     async fn history_read_at_time(
         &self,
         context: &RequestContext,
@@ -437,6 +516,10 @@ pub trait NodeManager: IntoAnyArc + Any {
 
     /// Perform the register nodes service. The default behavior for this service is to
     /// do nothing and pretend the nodes were registered.
+    ///
+    /// Node managers may instead hand back opaque handles using
+    /// [`RegisterNodeItem::set_registered_handle`], which clients will use in place of the
+    /// original node IDs until they are unregistered.
     async fn register_nodes(
         &self,
         context: &RequestContext,
@@ -524,8 +607,9 @@ pub trait NodeManager: IntoAnyArc + Any {
 
     /// Perform a query on the address space.
     ///
-    /// All node managers must be able to query in order for the
-    /// server to support querying.
+    /// Node managers that do not support querying should return `BadNotSupported`,
+    /// they are then treated as having no matching nodes. If no node manager
+    /// supports querying, the service fails with `BadServiceUnsupported`.
     ///
     /// The node manager should set a continuation point if it reaches
     /// limits, but is responsible for not exceeding max_data_sets_to_return
@@ -535,13 +619,18 @@ pub trait NodeManager: IntoAnyArc + Any {
         context: &RequestContext,
         request: &mut QueryRequest,
     ) -> Result<(), StatusCode> {
-        Err(StatusCode::BadServiceUnsupported)
+        Err(StatusCode::BadNotSupported)
     }
 
     /// Call a list of methods.
     ///
     /// The node manager should validate the method arguments and set
-    /// an output error if the arguments are invalid.
+    /// an output error if the arguments are invalid. Unless
+    /// [`ServerConfig::validate_method_arguments`](crate::ServerConfig::validate_method_arguments)
+    /// is disabled, the server has already checked the number and types of the arguments
+    /// of methods with an `InputArguments` property, see
+    /// [`RequestContext::get_method_arguments`]. Methods without argument metadata
+    /// are left to the node manager, which may use [`MethodCall::validate_arguments`].
     ///
     /// The node manager _must_ ensure that argument output lists and
     /// method output lists are of the correct length according to the
@@ -158,6 +158,18 @@ impl QueryRequest {
         self.continuation_point.as_ref()
     }
 
+    /// Take the continuation point created by this node manager in a previous call,
+    /// if it is of type `T`.
+    pub fn take_continuation_point<T: Send + Sync + 'static>(&mut self) -> Option<Box<T>> {
+        self.continuation_point.take().and_then(|c| c.take())
+    }
+
+    /// Add a data set to the query result. Node managers are responsible for
+    /// checking [`QueryRequest::remaining_data_sets`] before adding data sets.
+    pub fn add_data_set(&mut self, data_set: QueryDataSet) {
+        self.data_sets.push(data_set);
+    }
+
     /// Maximum number of references to return.
     pub fn max_references_to_return(&self) -> usize {
         self.max_references_to_return
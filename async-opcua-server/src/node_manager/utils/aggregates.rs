@@ -0,0 +1,689 @@
+use opcua_types::{
+    AggregateConfiguration, DataValue, DateTime, NodeId, ObjectId, StatusCode, StatusCodeInfoType,
+    StatusCodeValueType, Variant,
+};
+
+const TICKS_PER_MILLISECOND: f64 = 10_000.0;
+
+/// An aggregate function supported by the [`AggregateCalculator`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AggregateType {
+    /// The value interpolated at the start of each interval.
+    Interpolative,
+    /// The arithmetic mean of the good raw values in each interval.
+    Average,
+    /// The smallest good raw value in each interval.
+    Minimum,
+    /// The largest good raw value in each interval.
+    Maximum,
+    /// The number of good raw values in each interval.
+    Count,
+}
+
+impl AggregateType {
+    /// Get the ID of the `AggregateFunction` object for this aggregate.
+    pub fn object_id(&self) -> ObjectId {
+        match self {
+            AggregateType::Interpolative => ObjectId::AggregateFunction_Interpolative,
+            AggregateType::Average => ObjectId::AggregateFunction_Average,
+            AggregateType::Minimum => ObjectId::AggregateFunction_Minimum,
+            AggregateType::Maximum => ObjectId::AggregateFunction_Maximum,
+            AggregateType::Count => ObjectId::AggregateFunction_Count,
+        }
+    }
+}
+
+impl TryFrom<&NodeId> for AggregateType {
+    type Error = StatusCode;
+
+    fn try_from(value: &NodeId) -> Result<Self, Self::Error> {
+        match value.as_object_id() {
+            Ok(ObjectId::AggregateFunction_Interpolative) => Ok(AggregateType::Interpolative),
+            Ok(ObjectId::AggregateFunction_Average) => Ok(AggregateType::Average),
+            Ok(ObjectId::AggregateFunction_Minimum) => Ok(AggregateType::Minimum),
+            Ok(ObjectId::AggregateFunction_Maximum) => Ok(AggregateType::Maximum),
+            Ok(ObjectId::AggregateFunction_Count) => Ok(AggregateType::Count),
+            _ => Err(StatusCode::BadAggregateNotSupported),
+        }
+    }
+}
+
+struct RawPoint<'a> {
+    time: i64,
+    value: Option<f64>,
+    status: StatusCode,
+    data_value: &'a DataValue,
+}
+
+/// Utility for computing aggregates over raw historical data as described in
+/// OPC-UA Part 13. This can be used to implement `history_read_processed` and
+/// `history_read_at_time` on top of a store that only holds raw values.
+///
+/// Raw values are timestamped by their source timestamp, or their server timestamp
+/// if the source timestamp is missing. Values with neither are ignored.
+/// Values that are not numeric are treated as bad.
+#[derive(Debug, Clone)]
+pub struct AggregateCalculator {
+    treat_uncertain_as_bad: bool,
+    percent_data_bad: u8,
+    percent_data_good: u8,
+    use_sloped_extrapolation: bool,
+}
+
+impl Default for AggregateCalculator {
+    fn default() -> Self {
+        Self {
+            treat_uncertain_as_bad: true,
+            percent_data_bad: 100,
+            percent_data_good: 100,
+            use_sloped_extrapolation: false,
+        }
+    }
+}
+
+impl AggregateCalculator {
+    /// Create a new aggregate calculator from the aggregate configuration
+    /// given in a `ReadProcessedDetails`. If `use_server_capabilities_defaults`
+    /// is set, the defaults from Part 13 are used instead.
+    pub fn new(config: &AggregateConfiguration) -> Self {
+        if config.use_server_capabilities_defaults {
+            return Self::default();
+        }
+        Self {
+            treat_uncertain_as_bad: config.treat_uncertain_as_bad,
+            percent_data_bad: config.percent_data_bad,
+            percent_data_good: config.percent_data_good,
+            use_sloped_extrapolation: config.use_sloped_extrapolation,
+        }
+    }
+
+    /// Compute `aggregate` over `raw` for each processing interval between `start`
+    /// and `end`, returning one value per interval, timestamped with the start
+    /// of the interval.
+    ///
+    /// `processing_interval` is given in milliseconds, if it is zero a single
+    /// interval covering the whole range is used. If the range is not a multiple
+    /// of the processing interval the last interval is shorter, and its result is
+    /// marked as partial.
+    ///
+    /// `start` must be before `end`, reading backwards in time is not supported.
+    pub fn process(
+        &self,
+        aggregate: AggregateType,
+        raw: &[DataValue],
+        start: DateTime,
+        end: DateTime,
+        processing_interval: f64,
+    ) -> Result<Vec<DataValue>, StatusCode> {
+        let start = start.ticks();
+        let end = end.ticks();
+        if start >= end {
+            return Err(StatusCode::BadInvalidTimestampArgument);
+        }
+        if processing_interval < 0.0 || processing_interval.is_nan() {
+            return Err(StatusCode::BadInvalidArgument);
+        }
+        let interval = if processing_interval > 0.0 {
+            ((processing_interval * TICKS_PER_MILLISECOND) as i64).max(1)
+        } else {
+            end - start
+        };
+
+        let points = self.points(raw);
+        let mut res = Vec::new();
+        let mut interval_start = start;
+        while interval_start < end {
+            let interval_end = interval_start.saturating_add(interval).min(end);
+            let value = if aggregate == AggregateType::Interpolative {
+                self.interpolate(&points, interval_start, false)
+            } else {
+                let mut value =
+                    self.compute_interval(aggregate, &points, interval_start, interval_end);
+                if interval_end - interval_start < interval {
+                    value.status = value.status.map(|s| s.set_partial(true));
+                }
+                value
+            };
+            res.push(value);
+            interval_start = interval_end;
+        }
+
+        Ok(res)
+    }
+
+    /// Get the value of `raw` at each of the given timestamps, interpolating between
+    /// raw values where necessary, as needed for the `ReadAtTimeDetails` history read.
+    ///
+    /// If `use_simple_bounds` is set, the nearest raw values on either side are used
+    /// as bounds regardless of their status, otherwise the nearest good values are used.
+    pub fn values_at_time(
+        &self,
+        raw: &[DataValue],
+        times: &[DateTime],
+        use_simple_bounds: bool,
+    ) -> Vec<DataValue> {
+        let points = self.points(raw);
+        times
+            .iter()
+            .map(|t| self.interpolate(&points, t.ticks(), use_simple_bounds))
+            .collect()
+    }
+
+    fn points<'a>(&self, raw: &'a [DataValue]) -> Vec<RawPoint<'a>> {
+        let mut points: Vec<_> = raw
+            .iter()
+            .filter_map(|dv| {
+                let time = dv.source_timestamp.or(dv.server_timestamp)?;
+                Some(RawPoint {
+                    time: time.ticks(),
+                    value: dv.value.as_ref().and_then(|v| v.as_f64()),
+                    status: dv.status(),
+                    data_value: dv,
+                })
+            })
+            .collect();
+        points.sort_by_key(|p| p.time);
+        points
+    }
+
+    fn is_good(&self, point: &RawPoint<'_>) -> bool {
+        point.value.is_some()
+            && (point.status.is_good()
+                || point.status.is_uncertain() && !self.treat_uncertain_as_bad)
+    }
+
+    /// Compute the status of the interval from the fraction of time covered by
+    /// good and bad data, where each raw value lasts until the next one.
+    fn interval_status(
+        &self,
+        points: &[RawPoint<'_>],
+        first: usize,
+        start: i64,
+        end: i64,
+    ) -> StatusCode {
+        let mut good = 0;
+        let mut bad = 0;
+        // Data before the first value in the interval has the status of the previous value.
+        let lead = points[first].time - start;
+        if first > 0 && self.is_good(&points[first - 1]) {
+            good += lead;
+        } else {
+            bad += lead;
+        }
+        for (idx, point) in points.iter().enumerate().skip(first) {
+            if point.time >= end {
+                break;
+            }
+            let next = points.get(idx + 1).map(|p| p.time.min(end)).unwrap_or(end);
+            if self.is_good(point) {
+                good += next - point.time;
+            } else {
+                bad += next - point.time;
+            }
+        }
+
+        let total = (end - start) as f64;
+        if bad as f64 * 100.0 / total >= self.percent_data_bad as f64 {
+            StatusCode::Bad
+        } else if good as f64 * 100.0 / total >= self.percent_data_good as f64 {
+            StatusCode::Good
+        } else {
+            StatusCode::UncertainDataSubNormal
+        }
+    }
+
+    fn compute_interval(
+        &self,
+        aggregate: AggregateType,
+        points: &[RawPoint<'_>],
+        start: i64,
+        end: i64,
+    ) -> DataValue {
+        let first = points.partition_point(|p| p.time < start);
+        let last = points.partition_point(|p| p.time < end);
+        if first == last {
+            return Self::result(None, StatusCode::BadNoData, start);
+        }
+        let status = self.interval_status(points, first, start, end);
+        if status.is_bad() {
+            return Self::result(None, status, start);
+        }
+        let status = status
+            .set_info_type(StatusCodeInfoType::DataValue)
+            .set_value_type(StatusCodeValueType::Calculated);
+
+        let mut good = points[first..last].iter().filter(|p| self.is_good(p));
+        match aggregate {
+            AggregateType::Average => {
+                let (sum, count) = good.fold((0.0, 0), |(sum, count), p| {
+                    (sum + p.value.unwrap_or_default(), count + 1)
+                });
+                if count == 0 {
+                    return Self::result(None, StatusCode::BadNoData, start);
+                }
+                Self::result(Some(Variant::Double(sum / count as f64)), status, start)
+            }
+            AggregateType::Minimum | AggregateType::Maximum => {
+                let Some(mut best) = good.next() else {
+                    return Self::result(None, StatusCode::BadNoData, start);
+                };
+                let mut multi_value = false;
+                for p in good {
+                    let (value, best_value) =
+                        (p.value.unwrap_or_default(), best.value.unwrap_or_default());
+                    if value == best_value {
+                        multi_value = true;
+                    } else if (aggregate == AggregateType::Minimum) == (value < best_value) {
+                        best = p;
+                        multi_value = false;
+                    }
+                }
+                Self::result(
+                    best.data_value.value.clone(),
+                    status.set_multi_value(multi_value),
+                    start,
+                )
+            }
+            AggregateType::Count => {
+                Self::result(Some(Variant::Int32(good.count() as i32)), status, start)
+            }
+            AggregateType::Interpolative => self.interpolate(points, start, false),
+        }
+    }
+
+    fn interpolate(
+        &self,
+        points: &[RawPoint<'_>],
+        time: i64,
+        use_simple_bounds: bool,
+    ) -> DataValue {
+        let before = points.partition_point(|p| p.time < time);
+        let after = points.partition_point(|p| p.time <= time);
+
+        // A raw value at the requested time is returned as is.
+        if let Some(exact) = points[before..after].last() {
+            if use_simple_bounds || self.is_good(exact) {
+                return Self::result(
+                    exact.data_value.value.clone(),
+                    exact
+                        .status
+                        .set_info_type(StatusCodeInfoType::DataValue)
+                        .set_value_type(StatusCodeValueType::Raw),
+                    time,
+                );
+            }
+        }
+
+        let (early, late) = if use_simple_bounds {
+            let early = before.checked_sub(1);
+            if early.is_some_and(|e| !self.is_good(&points[e])) {
+                return Self::result(None, StatusCode::Bad, time);
+            }
+            // A bad late bound is ignored, and the value is extrapolated instead.
+            let late = Some(after).filter(|l| points.get(*l).is_some_and(|p| self.is_good(p)));
+            (early, late)
+        } else {
+            let early = points[..before].iter().rposition(|p| self.is_good(p));
+            let late = points[after..]
+                .iter()
+                .position(|p| self.is_good(p))
+                .map(|l| l + after);
+            (early, late)
+        };
+
+        let Some(early) = early else {
+            return Self::result(None, StatusCode::BadNoData, time);
+        };
+        let early_point = &points[early];
+        let early_value = early_point.value.unwrap_or_default();
+
+        let (value, status) = if let Some(late) = late {
+            let late_point = &points[late];
+            let late_value = late_point.value.unwrap_or_default();
+            let value = early_value
+                + (late_value - early_value) * (time - early_point.time) as f64
+                    / (late_point.time - early_point.time) as f64;
+            // Skipping over non-good values, or using uncertain bounds, makes the result uncertain.
+            let status = if early + 1 != before
+                || before != after
+                || late != after
+                || !early_point.status.is_good()
+                || !late_point.status.is_good()
+            {
+                StatusCode::UncertainDataSubNormal
+            } else {
+                StatusCode::Good
+            };
+            (value, status)
+        } else {
+            let previous = points[..early].iter().rposition(|p| self.is_good(p));
+            let value = match previous {
+                Some(previous) if self.use_sloped_extrapolation => {
+                    let previous = &points[previous];
+                    let previous_value = previous.value.unwrap_or_default();
+                    early_value
+                        + (early_value - previous_value) * (time - early_point.time) as f64
+                            / (early_point.time - previous.time) as f64
+                }
+                _ => early_value,
+            };
+            (value, StatusCode::UncertainDataSubNormal)
+        };
+
+        Self::result(
+            Some(Variant::Double(value)),
+            status
+                .set_info_type(StatusCodeInfoType::DataValue)
+                .set_value_type(StatusCodeValueType::Interpolated),
+            time,
+        )
+    }
+
+    fn result(value: Option<Variant>, status: StatusCode, time: i64) -> DataValue {
+        let time = DateTime::from(time);
+        DataValue {
+            value,
+            status: Some(status),
+            source_timestamp: Some(time),
+            source_picoseconds: None,
+            server_timestamp: Some(time),
+            server_picoseconds: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use opcua_types::{
+        AggregateConfiguration, DataValue, DateTime, NodeId, ObjectId, StatusCode,
+        StatusCodeInfoType, StatusCodeValueType, Variant,
+    };
+
+    use super::{AggregateCalculator, AggregateType};
+
+    fn at(secs: i64) -> DateTime {
+        DateTime::ymd_hms(2024, 1, 1, 0, 0, 0) + chrono::Duration::seconds(secs)
+    }
+
+    fn raw() -> Vec<DataValue> {
+        vec![
+            DataValue::new_at_status(1.0, at(0), StatusCode::Good),
+            DataValue::new_at_status(3.0, at(10), StatusCode::Good),
+            DataValue::new_at_status(5.0, at(20), StatusCode::BadSensorFailure),
+            DataValue::new_at_status(7.0, at(30), StatusCode::Good),
+            DataValue::new_at_status(2.0, at(40), StatusCode::UncertainLastUsableValue),
+            DataValue::new_at_status(4.0, at(50), StatusCode::Good),
+        ]
+    }
+
+    fn process(
+        calc: &AggregateCalculator,
+        aggregate: AggregateType,
+        interval: f64,
+    ) -> Vec<DataValue> {
+        calc.process(aggregate, &raw(), at(0), at(60), interval)
+            .unwrap()
+    }
+
+    fn values(res: &[DataValue]) -> Vec<Option<f64>> {
+        res.iter()
+            .map(|v| v.value.as_ref().and_then(|v| v.as_f64()))
+            .collect()
+    }
+
+    fn statuses(res: &[DataValue]) -> Vec<StatusCode> {
+        // Ignore the info bits, those are checked separately.
+        res.iter()
+            .map(|v| StatusCode::from(v.status().bits() & 0xFFFF_0000))
+            .collect()
+    }
+
+    #[test]
+    fn average() {
+        let res = process(
+            &AggregateCalculator::default(),
+            AggregateType::Average,
+            20_000.0,
+        );
+        assert_eq!(values(&res), vec![Some(2.0), Some(7.0), Some(4.0)]);
+        assert_eq!(
+            statuses(&res),
+            vec![
+                StatusCode::Good,
+                StatusCode::UncertainDataSubNormal,
+                StatusCode::UncertainDataSubNormal
+            ]
+        );
+        assert_eq!(
+            res.iter()
+                .map(|v| v.source_timestamp.unwrap())
+                .collect::<Vec<_>>(),
+            vec![at(0), at(20), at(40)]
+        );
+        for v in &res {
+            assert_eq!(v.status().value_type(), StatusCodeValueType::Calculated);
+            assert!(!v.status().partial());
+        }
+    }
+
+    #[test]
+    fn minimum_maximum_count() {
+        let calc = AggregateCalculator::default();
+        let res = process(&calc, AggregateType::Minimum, 20_000.0);
+        assert_eq!(values(&res), vec![Some(1.0), Some(7.0), Some(4.0)]);
+        let res = process(&calc, AggregateType::Maximum, 20_000.0);
+        assert_eq!(values(&res), vec![Some(3.0), Some(7.0), Some(4.0)]);
+        let res = process(&calc, AggregateType::Count, 20_000.0);
+        assert_eq!(res[0].value, Some(Variant::Int32(2)));
+        assert_eq!(res[1].value, Some(Variant::Int32(1)));
+        assert_eq!(res[2].value, Some(Variant::Int32(1)));
+        assert_eq!(
+            statuses(&res),
+            vec![
+                StatusCode::Good,
+                StatusCode::UncertainDataSubNormal,
+                StatusCode::UncertainDataSubNormal
+            ]
+        );
+    }
+
+    #[test]
+    fn uncertain_as_good() {
+        let calc = AggregateCalculator::new(&AggregateConfiguration {
+            use_server_capabilities_defaults: false,
+            treat_uncertain_as_bad: false,
+            percent_data_bad: 100,
+            percent_data_good: 100,
+            use_sloped_extrapolation: false,
+        });
+        let res = process(&calc, AggregateType::Average, 20_000.0);
+        assert_eq!(values(&res), vec![Some(2.0), Some(7.0), Some(3.0)]);
+        assert_eq!(
+            statuses(&res),
+            vec![
+                StatusCode::Good,
+                StatusCode::UncertainDataSubNormal,
+                StatusCode::Good
+            ]
+        );
+    }
+
+    #[test]
+    fn percent_data_good() {
+        let calc = AggregateCalculator::new(&AggregateConfiguration {
+            use_server_capabilities_defaults: false,
+            treat_uncertain_as_bad: true,
+            percent_data_bad: 100,
+            percent_data_good: 50,
+            use_sloped_extrapolation: false,
+        });
+        let res = process(&calc, AggregateType::Average, 20_000.0);
+        assert_eq!(
+            statuses(&res),
+            vec![StatusCode::Good, StatusCode::Good, StatusCode::Good]
+        );
+
+        // The server defaults are used if requested.
+        let calc = AggregateCalculator::new(&AggregateConfiguration {
+            use_server_capabilities_defaults: true,
+            ..Default::default()
+        });
+        let res = process(&calc, AggregateType::Average, 20_000.0);
+        assert_eq!(
+            res[1].status(),
+            StatusCode::UncertainDataSubNormal
+                .set_info_type(StatusCodeInfoType::DataValue)
+                .set_value_type(StatusCodeValueType::Calculated)
+        );
+    }
+
+    #[test]
+    fn bad_and_empty_intervals() {
+        let calc = AggregateCalculator::default();
+        let res = calc
+            .process(AggregateType::Average, &raw(), at(20), at(40), 10_000.0)
+            .unwrap();
+        assert_eq!(values(&res), vec![None, Some(7.0)]);
+        assert_eq!(statuses(&res), vec![StatusCode::Bad, StatusCode::Good]);
+
+        let res = calc
+            .process(AggregateType::Maximum, &raw(), at(60), at(80), 0.0)
+            .unwrap();
+        assert_eq!(values(&res), vec![None]);
+        assert_eq!(statuses(&res), vec![StatusCode::BadNoData]);
+
+        assert_eq!(
+            calc.process(AggregateType::Average, &raw(), at(60), at(0), 0.0),
+            Err(StatusCode::BadInvalidTimestampArgument)
+        );
+    }
+
+    #[test]
+    fn partial_interval() {
+        let res = process(
+            &AggregateCalculator::default(),
+            AggregateType::Count,
+            25_000.0,
+        );
+        assert_eq!(res.len(), 3);
+        assert!(!res[0].status().partial());
+        assert!(!res[1].status().partial());
+        assert!(res[2].status().partial());
+        assert_eq!(res[2].value, Some(Variant::Int32(1)));
+    }
+
+    #[test]
+    fn multi_value() {
+        let raw = vec![
+            DataValue::new_at_status(3.0, at(0), StatusCode::Good),
+            DataValue::new_at_status(1.0, at(5), StatusCode::Good),
+            DataValue::new_at_status(3.0, at(10), StatusCode::Good),
+        ];
+        let calc = AggregateCalculator::default();
+        let res = calc
+            .process(AggregateType::Maximum, &raw, at(0), at(20), 0.0)
+            .unwrap();
+        assert_eq!(values(&res), vec![Some(3.0)]);
+        assert!(res[0].status().multi_value());
+        let res = calc
+            .process(AggregateType::Minimum, &raw, at(0), at(20), 0.0)
+            .unwrap();
+        assert_eq!(values(&res), vec![Some(1.0)]);
+        assert!(!res[0].status().multi_value());
+    }
+
+    #[test]
+    fn interpolative() {
+        let res = process(
+            &AggregateCalculator::default(),
+            AggregateType::Interpolative,
+            20_000.0,
+        );
+        // Raw value at 0, interpolated around the bad value at 20 and the uncertain value at 40.
+        assert_eq!(values(&res), vec![Some(1.0), Some(5.0), Some(5.5)]);
+        assert_eq!(
+            statuses(&res),
+            vec![
+                StatusCode::Good,
+                StatusCode::UncertainDataSubNormal,
+                StatusCode::UncertainDataSubNormal
+            ]
+        );
+        assert_eq!(res[0].status().value_type(), StatusCodeValueType::Raw);
+        assert_eq!(
+            res[1].status().value_type(),
+            StatusCodeValueType::Interpolated
+        );
+    }
+
+    #[test]
+    fn at_time() {
+        let calc = AggregateCalculator::default();
+        let times = [at(-5), at(5), at(15), at(25), at(60)];
+
+        let res = calc.values_at_time(&raw(), &times, false);
+        assert_eq!(
+            values(&res),
+            vec![None, Some(2.0), Some(4.0), Some(6.0), Some(4.0)]
+        );
+        assert_eq!(
+            statuses(&res),
+            vec![
+                StatusCode::BadNoData,
+                StatusCode::Good,
+                StatusCode::UncertainDataSubNormal,
+                StatusCode::UncertainDataSubNormal,
+                StatusCode::UncertainDataSubNormal
+            ]
+        );
+
+        // With simple bounds a bad late bound gives a stepped extrapolation,
+        // and a bad early bound gives a bad result.
+        let res = calc.values_at_time(&raw(), &times, true);
+        assert_eq!(
+            values(&res),
+            vec![None, Some(2.0), Some(3.0), None, Some(4.0)]
+        );
+        assert_eq!(
+            statuses(&res),
+            vec![
+                StatusCode::BadNoData,
+                StatusCode::Good,
+                StatusCode::UncertainDataSubNormal,
+                StatusCode::Bad,
+                StatusCode::UncertainDataSubNormal
+            ]
+        );
+    }
+
+    #[test]
+    fn sloped_extrapolation() {
+        let calc = AggregateCalculator::new(&AggregateConfiguration {
+            use_server_capabilities_defaults: false,
+            treat_uncertain_as_bad: true,
+            percent_data_bad: 100,
+            percent_data_good: 100,
+            use_sloped_extrapolation: true,
+        });
+        // The last two good values are 7.0 at 30 and 4.0 at 50.
+        let res = calc.values_at_time(&raw(), &[at(60)], false);
+        assert_eq!(values(&res), vec![Some(2.5)]);
+        assert_eq!(statuses(&res), vec![StatusCode::UncertainDataSubNormal]);
+    }
+
+    #[test]
+    fn aggregate_type_from_node_id() {
+        assert_eq!(
+            AggregateType::try_from(&NodeId::from(ObjectId::AggregateFunction_Average)),
+            Ok(AggregateType::Average)
+        );
+        assert_eq!(
+            AggregateType::try_from(&NodeId::from(ObjectId::AggregateFunction_TimeAverage)),
+            Err(StatusCode::BadAggregateNotSupported)
+        );
+        assert_eq!(
+            AggregateType::Count.object_id(),
+            ObjectId::AggregateFunction_Count
+        );
+    }
+}
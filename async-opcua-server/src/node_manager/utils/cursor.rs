@@ -0,0 +1,149 @@
+//! Compact continuation point for stateless pagination of browse results.
+
+use std::iter::Skip;
+
+use opcua_types::{ByteString, NodeId};
+use serde::{Deserialize, Serialize};
+
+/// Continuation point storing a position in an ordered list of references,
+/// rather than the remaining references themselves.
+///
+/// The simplest way to implement browse continuation in a node manager is to store
+/// every reference that did not fit in the response in a queue, and return those on
+/// the next call to `BrowseNext`. This is easy to get right, but the server holds on to
+/// the full remaining result for as long as the continuation point lives, which
+/// is a problem for nodes with very large numbers of references.
+///
+/// A `BrowseCursor` instead stores how far the node manager got in its list of
+/// references, and the node ID of the last reference it visited. When the browse resumes,
+/// the node manager regenerates its references in the same order and continues after
+/// the last visited node, either with [`BrowseCursor::resume`], or by seeking directly to
+/// [`BrowseCursor::last_node_id`] if it keeps its references in a sorted structure.
+/// This keeps the size of each continuation point constant, at the cost of
+/// recomputing the references, and requires the order of references to be stable
+/// between calls. Since the cursor does not refer to the node manager's data,
+/// references added or removed in the meantime do not invalidate it.
+///
+/// Store the cursor with `BrowseNode::set_next_continuation_point`, and get it back
+/// with `BrowseNode::take_continuation_point::<BrowseCursor>`. Like any other continuation
+/// point, it is kept by the server, and released when the session closes. The cursor can
+/// also be converted to an opaque byte string with [`BrowseCursor::encode`], if the node
+/// manager needs to store it elsewhere.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BrowseCursor {
+    offset: usize,
+    last_node_id: Option<NodeId>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct EncodedCursor {
+    offset: u64,
+    last_node_id: Option<String>,
+}
+
+impl BrowseCursor {
+    /// Create a new cursor pointing at the start of the references.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of references that were visited before this cursor.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Node ID of the last reference visited before this cursor, if any.
+    pub fn last_node_id(&self) -> Option<&NodeId> {
+        self.last_node_id.as_ref()
+    }
+
+    /// Move the cursor past the reference to `node_id`.
+    pub fn advance(&mut self, node_id: &NodeId) {
+        self.offset += 1;
+        self.last_node_id = Some(node_id.clone());
+    }
+
+    /// Skip the references visited before this cursor in `references`, which must
+    /// produce references in the same order as when the cursor was advanced.
+    /// `node_id` gets the node ID of each reference.
+    ///
+    /// If the last visited node is still present, this resumes right after it, so references
+    /// added or removed before it are not returned twice or skipped. Otherwise, the first
+    /// [`BrowseCursor::offset`] references are skipped.
+    pub fn resume<I>(&mut self, references: I, node_id: impl Fn(&I::Item) -> &NodeId) -> Skip<I>
+    where
+        I: Iterator + Clone,
+    {
+        if let Some(last) = &self.last_node_id {
+            if let Some(pos) = references.clone().position(|r| node_id(&r) == last) {
+                self.offset = pos + 1;
+            }
+        }
+        references.skip(self.offset)
+    }
+
+    /// Encode the cursor as an opaque byte string, using `postcard` binary encoding.
+    pub fn encode(&self) -> ByteString {
+        let encoded = EncodedCursor {
+            offset: self.offset as u64,
+            last_node_id: self.last_node_id.as_ref().map(|id| id.to_string()),
+        };
+        postcard::to_stdvec(&encoded)
+            .map(ByteString::from)
+            .unwrap_or_default()
+    }
+
+    /// Decode a cursor that was created using [`BrowseCursor::encode`].
+    /// Returns `None` if the byte string is not a valid cursor.
+    pub fn decode(bytes: &ByteString) -> Option<Self> {
+        let encoded: EncodedCursor = postcard::from_bytes(bytes.value.as_ref()?).ok()?;
+        let last_node_id = match encoded.last_node_id {
+            Some(id) => Some(id.parse().ok()?),
+            None => None,
+        };
+        Some(Self {
+            offset: encoded.offset.try_into().ok()?,
+            last_node_id,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use opcua_types::{ByteString, NodeId};
+
+    use super::BrowseCursor;
+
+    #[test]
+    fn cursor_encode_decode() {
+        let mut cursor = BrowseCursor::new();
+        assert_eq!(BrowseCursor::decode(&cursor.encode()), Some(cursor.clone()));
+
+        cursor.advance(&NodeId::new(2, "tag1"));
+        cursor.advance(&NodeId::new(2, 15));
+        let decoded = BrowseCursor::decode(&cursor.encode()).unwrap();
+        assert_eq!(decoded.offset(), 2);
+        assert_eq!(decoded.last_node_id(), Some(&NodeId::new(2, 15)));
+
+        assert_eq!(BrowseCursor::decode(&ByteString::null()), None);
+        assert_eq!(BrowseCursor::decode(&ByteString::from(vec![0xff])), None);
+    }
+
+    #[test]
+    fn cursor_resume() {
+        let ids: Vec<_> = (0..5).map(|i| NodeId::new(2, i)).collect();
+        let mut cursor = BrowseCursor::new();
+        for id in &ids[0..2] {
+            cursor.advance(id);
+        }
+
+        // Resumes after the last visited node, even if earlier nodes were removed.
+        let rest: Vec<_> = cursor.resume(ids[1..].iter(), |id| id).collect();
+        assert_eq!(rest, ids[2..].iter().collect::<Vec<_>>());
+        assert_eq!(cursor.offset(), 1);
+
+        // Falls back to the offset if the last visited node is gone.
+        let rest: Vec<_> = cursor.resume(ids[2..].iter(), |id| id).collect();
+        assert_eq!(rest, ids[3..].iter().collect::<Vec<_>>());
+    }
+}
@@ -1,9 +1,13 @@
+use crate::address_space::AccessLevel;
 use crate::node_manager::{
     view::{ExternalReferenceRequest, NodeMetadata},
-    NodeManagerCollection, RequestContext,
+    NodeManagerCollection, ReadNode, RequestContext,
 };
 use hashbrown::HashMap;
-use opcua_types::{BrowseDescriptionResultMask, NamespaceMap, NodeId};
+use opcua_types::{
+    AttributeId, BrowseDescriptionResultMask, DiagnosticBits, NamespaceMap, NodeId, ReadValueId,
+    StatusCode, TimestampsToReturn, Variant,
+};
 
 /// Fetch external references by requesting them from their owning node manager.
 ///
@@ -44,3 +48,77 @@ pub fn get_namespaces_for_user(
 
     NamespaceMap::new_full(nss)
 }
+
+/// Get the `AccessLevel` and effective `UserAccessLevel` of each node in `ids`.
+///
+/// This reads both attributes from the node managers that own the nodes, and computes
+/// the effective user access level from the `UserAccessLevel` attribute using
+/// [`AuthManager::effective_user_access_level`](crate::authenticator::AuthManager::effective_user_access_level).
+///
+/// The result is `Ok(None)` for nodes without access levels, meaning nodes that are
+/// unknown or are not variables. If the access levels of a variable cannot be read,
+/// the result is `Err(BadUserAccessDenied)`.
+///
+/// Node managers that are unhealthy, or that check access levels themselves, see
+/// [`NodeManager::checks_access_levels`](crate::node_manager::NodeManager::checks_access_levels),
+/// are not called, and the result is `Ok(None)` for the nodes they own.
+pub async fn get_access_levels(
+    context: &RequestContext,
+    node_managers: &impl NodeManagerCollection,
+    ids: &[NodeId],
+) -> Vec<Result<Option<(AccessLevel, AccessLevel)>, StatusCode>> {
+    let mut nodes: Vec<_> = ids
+        .iter()
+        .flat_map(|id| [AttributeId::AccessLevel, AttributeId::UserAccessLevel].map(|a| (id, a)))
+        .map(|(id, attribute_id)| {
+            ReadNode::new(
+                ReadValueId {
+                    node_id: id.clone(),
+                    attribute_id: attribute_id as u32,
+                    ..Default::default()
+                },
+                DiagnosticBits::empty(),
+            )
+        })
+        .collect();
+    for mgr in node_managers.iter_node_managers() {
+        if mgr.checks_access_levels() || !mgr.health().is_healthy() {
+            continue;
+        }
+        let mut owned: Vec<_> = nodes
+            .iter_mut()
+            .filter(|n| mgr.owns_node(&n.node().node_id))
+            .collect();
+        if owned.is_empty() {
+            continue;
+        }
+        // Errors are left in the results of the read nodes.
+        let _ = mgr
+            .read(context, 0.0, TimestampsToReturn::Neither, &mut owned)
+            .await;
+    }
+
+    let as_access_level = |node: &ReadNode| match node.result.value {
+        Some(Variant::Byte(b)) if node.status().is_good() => Ok(AccessLevel::from_bits_truncate(b)),
+        _ => Err(StatusCode::BadUserAccessDenied),
+    };
+    nodes
+        .chunks_exact(2)
+        .zip(ids)
+        .map(|(pair, id)| {
+            // Only variables have access levels.
+            let status = pair[0].status();
+            if status == StatusCode::BadNodeIdUnknown || status == StatusCode::BadAttributeIdInvalid
+            {
+                return Ok(None);
+            }
+            let access_level = as_access_level(&pair[0])?;
+            let user_access_level = context.authenticator.effective_user_access_level(
+                &context.token,
+                as_access_level(&pair[1])?,
+                id,
+            );
+            Ok(Some((access_level, user_access_level)))
+        })
+        .collect()
+}
@@ -7,7 +7,7 @@ use std::{
 use tokio_util::sync::{CancellationToken, DropGuard};
 
 use crate::{MonitoredItemHandle, SubscriptionCache};
-use opcua_core::sync::Mutex;
+use opcua_core::{sync::Mutex, task::spawn_named};
 use opcua_types::{AttributeId, DataValue, MonitoringMode, NodeId};
 
 struct ItemRef {
@@ -73,9 +73,20 @@ impl SyncSampler {
     /// this is called in `build_nodes` or `init`. The sampler will automatically shut down
     /// once it is dropped.
     pub fn run(&self, interval: Duration, subscriptions: Arc<SubscriptionCache>) {
+        self.run_named(None, interval, subscriptions);
+    }
+
+    /// Start the sampler, like [`SyncSampler::run`], giving the spawned task
+    /// the name `name`, if it is set.
+    pub fn run_named(
+        &self,
+        name: Option<String>,
+        interval: Duration,
+        subscriptions: Arc<SubscriptionCache>,
+    ) {
         let token = self.token.clone();
         let samplers = self.samplers.clone();
-        tokio::spawn(async move {
+        spawn_named(name, async move {
             tokio::select! {
                 _ = Self::run_internal(samplers, interval, subscriptions) => {},
                 _ = token.cancelled() => {}
@@ -1,10 +1,14 @@
-use std::collections::{HashMap, VecDeque};
+use std::{
+    collections::{HashMap, VecDeque},
+    time::{Duration, Instant},
+};
 
 use crate::{
     address_space::ReferenceDirection,
     session::{
         continuation_points::{ContinuationPoint, EmptyContinuationPoint},
         instance::Session,
+        registered_nodes::RegisteredNodes,
     },
 };
 use opcua_crypto::random;
@@ -96,6 +100,11 @@ impl ExternalReferenceRequest {
 
 #[derive(Debug)]
 /// A reference pointing to some node in a different node manager.
+///
+/// If the target has a non-zero server index, the reference points to a node on a
+/// different server. These are not resolved by the local node managers, and are returned
+/// without browse name, display name, node class, or type definition. If you know these,
+/// add the reference directly with [`BrowseNode::add`] instead.
 pub struct ExternalReference {
     target_id: ExpandedNodeId,
     reference_type_id: NodeId,
@@ -116,6 +125,20 @@ impl ExternalReference {
         }
     }
 
+    /// Create a reference description for a node on a remote server, without any
+    /// metadata about the target node.
+    fn into_remote_reference(self) -> ReferenceDescription {
+        ReferenceDescription {
+            reference_type_id: self.reference_type_id,
+            is_forward: matches!(self.direction, ReferenceDirection::Forward),
+            node_id: self.target_id,
+            browse_name: QualifiedName::null(),
+            display_name: LocalizedText::null(),
+            node_class: NodeClass::Unspecified,
+            type_definition: ExpandedNodeId::null(),
+        }
+    }
+
     /// Create a reference description from this and a `NodeMetadata` object.
     pub fn into_reference(self, meta: NodeMetadata) -> ReferenceDescription {
         ReferenceDescription {
@@ -182,6 +205,14 @@ pub(crate) struct BrowseContinuationPoint {
     pub(crate) max_references_per_node: usize,
 
     external_references: Vec<ExternalReference>,
+    created: Instant,
+}
+
+impl BrowseContinuationPoint {
+    /// Whether this continuation point was created more than `timeout` ago.
+    pub(crate) fn is_expired(&self, timeout: Duration) -> bool {
+        self.created.elapsed() >= timeout
+    }
 }
 
 impl BrowseNode {
@@ -255,6 +286,13 @@ impl BrowseNode {
     }
 
     /// Set the continuation point that will be returned to the client.
+    ///
+    /// The server keeps the continuation point until the client calls `BrowseNext`,
+    /// releases it, closes the session, or it expires after the configured
+    /// `browse_continuation_point_timeout_ms`. The value is dropped when the continuation
+    /// point is released, so any resources it holds can be cleaned up in its `Drop`
+    /// implementation. For nodes with many references, consider using a
+    /// [`BrowseCursor`](super::BrowseCursor) instead of storing the remaining references.
     pub fn set_next_continuation_point<T: Send + Sync + 'static>(
         &mut self,
         continuation_point: Box<T>,
@@ -262,6 +300,16 @@ impl BrowseNode {
         self.next_continuation_point = Some(ContinuationPoint::new(continuation_point));
     }
 
+    /// Get the current status of the browse operation.
+    pub(crate) fn status(&self) -> StatusCode {
+        self.status_code
+    }
+
+    /// Get the references added to the result so far.
+    pub(crate) fn references(&self) -> &[ReferenceDescription] {
+        &self.references
+    }
+
     /// Get the current number of added references.
     pub fn result_len(&self) -> usize {
         self.references.len()
@@ -330,6 +378,14 @@ impl BrowseNode {
         )
     }
 
+    fn allows_direction(&self, is_forward: bool) -> bool {
+        if is_forward {
+            self.allows_forward()
+        } else {
+            self.allows_inverse()
+        }
+    }
+
     /// Return `true` if the given reference should be returned.
     pub fn matches_filter(
         &self,
@@ -365,6 +421,11 @@ impl BrowseNode {
             return false;
         }
 
+        // Check the browse direction, node managers may push both forward and inverse references.
+        if !self.allows_direction(reference.is_forward) {
+            return false;
+        }
+
         // Check the reference type filter.
         self.allows_reference_type(&reference.reference_type_id, type_tree)
     }
@@ -494,6 +555,7 @@ impl BrowseNode {
             result_mask: self.result_mask,
             max_references_per_node: self.max_references_per_node,
             external_references: self.external_references,
+            created: Instant::now(),
         });
 
         let mut result = BrowseResult {
@@ -506,11 +568,13 @@ impl BrowseNode {
         };
 
         // If we're out of continuation points, the correct response is to not store it, and
-        // set the status code to BadNoContinuationPoints.
+        // set the status code to BadNoContinuationPoints. The references are discarded as well,
+        // since the client has no way to get the rest of them.
         if let Some(c) = continuation_point {
             if session.add_browse_continuation_point(c).is_err() {
                 result.status_code = StatusCode::BadNoContinuationPoints;
                 result.continuation_point = ByteString::null();
+                result.references = None;
             }
         }
 
@@ -529,10 +593,11 @@ impl BrowseNode {
         self.external_references.push(reference);
     }
 
-    /// Get an iterator over the external references.
+    /// Get an iterator over the external references to nodes on the local server.
     pub fn get_external_refs(&self) -> impl Iterator<Item = &NodeId> {
         self.external_references
             .iter()
+            .filter(|n| n.target_id.is_local())
             .map(|n| &n.target_id.node_id)
     }
 
@@ -552,6 +617,23 @@ impl BrowseNode {
 
         let refs = std::mem::take(&mut self.external_references);
         for rf in refs {
+            if !rf.target_id.is_local() {
+                // We know nothing about nodes on other servers, so we cannot filter
+                // on node class.
+                let rf = rf.into_remote_reference();
+                if !self.node_class_mask.is_empty()
+                    || !self.allows_direction(rf.is_forward)
+                    || !self.allows_reference_type(&rf.reference_type_id, type_tree)
+                {
+                    continue;
+                }
+                if self.remaining() > 0 {
+                    self.add_unchecked(rf);
+                } else {
+                    cont_point.items.push_back(rf);
+                }
+                continue;
+            }
             if let Some(meta) = resolved_nodes.get(&rf.target_id.node_id) {
                 let rf = rf.into_reference((*meta).clone());
                 if !self.matches_filter(type_tree, &rf) {
@@ -718,6 +800,8 @@ impl<'a> BrowsePathItem<'a> {
 pub struct RegisterNodeItem {
     node_id: NodeId,
     registered: bool,
+    registered_node_id: Option<NodeId>,
+    use_handle: bool,
 }
 
 impl RegisterNodeItem {
@@ -725,6 +809,8 @@ impl RegisterNodeItem {
         Self {
             node_id,
             registered: false,
+            registered_node_id: None,
+            use_handle: false,
         }
     }
 
@@ -733,17 +819,48 @@ impl RegisterNodeItem {
         &self.node_id
     }
 
-    /// Set the node registered status. This is returned to the client.
+    /// Set the node registered status. Nodes that are not registered are returned
+    /// to the client unchanged.
     pub fn set_registered(&mut self, registered: bool) {
         self.registered = registered;
     }
 
-    pub(crate) fn into_result(self) -> Option<NodeId> {
-        if self.registered {
-            Some(self.node_id)
-        } else {
-            None
-        }
+    /// Mark the node as registered under a different node ID, which the client
+    /// will use in place of the original in subsequent requests.
+    ///
+    /// The new node ID must be owned by the same node manager, which is responsible
+    /// for resolving it in every service. Use [`RegisterNodeItem::set_registered_handle`]
+    /// to let the server do this instead.
+    pub fn set_registered_node_id(&mut self, node_id: NodeId) {
+        self.registered = true;
+        self.registered_node_id = Some(node_id);
+        self.use_handle = false;
+    }
+
+    /// Mark the node as registered under an opaque handle, which the client will use
+    /// in place of the original in subsequent requests on this session.
+    ///
+    /// The server resolves the handle back to the original node ID before calling
+    /// node managers, until it is unregistered or the session is closed.
+    pub fn set_registered_handle(&mut self) {
+        self.registered = true;
+        self.registered_node_id = None;
+        self.use_handle = true;
+    }
+
+    pub(crate) fn into_result(self, registered_nodes: &mut RegisteredNodes) -> NodeId {
+        // Nodes that were not registered are returned unchanged, which is always valid.
+        if !self.registered {
+            return self.node_id;
+        }
+        if self.use_handle {
+            // If the session is out of handles the original node ID is returned,
+            // which clients can use in place of a handle.
+            return registered_nodes
+                .register(&self.node_id)
+                .unwrap_or(self.node_id);
+        }
+        self.registered_node_id.unwrap_or(self.node_id)
     }
 }
 
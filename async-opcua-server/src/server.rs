@@ -5,23 +5,23 @@ use std::{
         atomic::{AtomicU16, AtomicU8},
         Arc,
     },
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 use arc_swap::ArcSwap;
 use futures::{future::Either, never::Never, stream::FuturesUnordered, FutureExt, StreamExt};
-use opcua_core::{sync::RwLock, trace_read_lock, trace_write_lock};
+use opcua_core::{sync::RwLock, task::spawn_named, trace_read_lock, trace_write_lock};
 use opcua_nodes::DefaultTypeTree;
 use tokio::{
     net::TcpListener,
     pin,
-    sync::Notify,
+    sync::{watch, Notify},
     task::{JoinError, JoinHandle},
 };
 use tokio_util::sync::CancellationToken;
 use tracing::{error, info, warn};
 
-use opcua_core::{config::Config, handle::AtomicHandle};
+use opcua_core::{comms::buffer_pool::BufferPool, config::Config, handle::AtomicHandle};
 use opcua_crypto::CertificateStore;
 
 use crate::{
@@ -42,7 +42,6 @@ use super::{
     server_handle::ServerHandle,
     session::manager::SessionManager,
     subscriptions::SubscriptionCache,
-    ServerCapabilities,
 };
 
 struct ConnectionInfo {
@@ -74,6 +73,10 @@ pub struct Server {
     session_notify: Arc<Notify>,
     /// Wrapper managing the `ServerStatus` server variable.
     status: Arc<ServerStatusWrapper>,
+    /// Pool of chunk buffers shared by all connections.
+    buffer_pool: BufferPool,
+    /// Receives the deadline for draining the server, set by `ServerHandle::shutdown`.
+    drain: watch::Receiver<Option<Instant>>,
 }
 
 impl Server {
@@ -126,6 +129,17 @@ impl Server {
             certificate_store.set_trust_unknown_certs(true);
         }
         certificate_store.set_check_time(config.certificate_validation.check_time);
+        certificate_store
+            .set_strict_revocation_check(config.certificate_validation.strict_revocation_check);
+        certificate_store.set_validation_cache(
+            config.certificate_validation.cache_size,
+            Duration::from_millis(config.certificate_validation.cache_ttl_ms),
+        );
+
+        let buffer_pool = BufferPool::new(
+            config.limits.max_pooled_buffers,
+            send_buffer_size.max(receive_buffer_size),
+        );
 
         let config = Arc::new(config);
 
@@ -149,6 +163,11 @@ impl Server {
             server_certificate,
             server_pkey,
             operational_limits: config.limits.operational.clone(),
+            node_manager_monitored_item_limits: builder
+                .node_managers
+                .iter()
+                .map(|r| r.max_monitored_items)
+                .collect(),
             state: ArcSwap::new(Arc::new(ServerState::Shutdown)),
             send_buffer_size,
             receive_buffer_size,
@@ -156,28 +175,29 @@ impl Server {
             subscription_id_handle: AtomicHandle::new(1),
             monitored_item_id_handle: AtomicHandle::new(1),
             secure_channel_id_handle: Arc::new(AtomicHandle::new(1)),
-            capabilities: ServerCapabilities::default(),
+            capabilities: builder.capabilities,
             service_level: service_level.clone(),
             port: AtomicU16::new(0),
             type_tree_getter: builder
                 .type_tree_getter
                 .unwrap_or_else(|| Arc::new(DefaultTypeTreeGetter)),
             type_loaders: RwLock::new(builder.type_loaders),
-            diagnostics: ServerDiagnostics {
-                enabled: config.diagnostics,
-                ..Default::default()
-            },
+            diagnostics: ServerDiagnostics::new(config.diagnostics),
         };
 
         let certificate_store = Arc::new(RwLock::new(certificate_store));
 
         let info = Arc::new(info);
-        let subscriptions = Arc::new(SubscriptionCache::new(config.limits.subscriptions));
+        let subscriptions = Arc::new(SubscriptionCache::new(
+            config.limits.subscriptions,
+            builder.node_managers.len(),
+        ));
 
         let node_managers_ref = NodeManagersRef::new_empty();
         let status_wrapper = Arc::new(ServerStatusWrapper::new(
             builder.build_info,
             subscriptions.clone(),
+            config.named_tasks,
         ));
         let context = ServerContext {
             node_managers: node_managers_ref.clone(),
@@ -190,8 +210,8 @@ impl Server {
         };
 
         let mut final_node_managers = Vec::new();
-        for nm_builder in builder.node_managers {
-            final_node_managers.push(nm_builder.build(context.clone()));
+        for registration in builder.node_managers {
+            final_node_managers.push(registration.builder.build(context.clone()));
         }
 
         let node_managers = NodeManagers::new(final_node_managers);
@@ -203,6 +223,7 @@ impl Server {
             session_notify.clone(),
         )));
 
+        let (drain_send, drain) = watch::channel(None);
         let handle = ServerHandle::new(
             info.clone(),
             service_level,
@@ -211,7 +232,9 @@ impl Server {
             session_manager.clone(),
             type_tree.clone(),
             status_wrapper.clone(),
+            certificate_store.clone(),
             builder.token.clone(),
+            drain_send,
         );
         Ok((
             Self {
@@ -226,6 +249,8 @@ impl Server {
                 token: builder.token,
                 session_notify,
                 status: status_wrapper.clone(),
+                buffer_pool,
+                drain,
             },
             handle,
         ))
@@ -279,6 +304,9 @@ impl Server {
     ///
     /// This is useful for testing, as you can bind a `TcpListener` to port `0` auto-assign
     /// a port.
+    ///
+    /// This returns once the server is cancelled and all connections are closed, or once
+    /// draining started by [`ServerHandle::shutdown`] has completed.
     pub async fn run_with(mut self, listener: TcpListener) -> Result<(), String> {
         let context = ServerContext {
             node_managers: self.node_managers.as_weak(),
@@ -307,6 +335,9 @@ impl Server {
         self.log_endpoint_info();
 
         let mut connection_counter = 0;
+        // The listener is dropped once the server starts draining, to stop accepting connections.
+        let mut listener = Some(listener);
+        let mut drain_deadline = None;
 
         #[cfg(feature = "discovery-server-registration")]
         let discovery_fut = Self::run_discovery_server_registration(self.info.clone());
@@ -324,15 +355,22 @@ impl Server {
             Self::run_session_expiry(&self.session_manager, &self.session_notify);
         pin!(session_expiry_fut);
 
+        let buffer_trim_fut = Self::run_buffer_pool_trim(&self.buffer_pool);
+        pin!(buffer_trim_fut);
+
         loop {
             let conn_fut = if self.connections.is_empty() {
-                if self.token.is_cancelled() {
+                if self.token.is_cancelled() || drain_deadline.is_some() {
                     break;
                 }
                 Either::Left(futures::future::pending::<Option<Result<u32, JoinError>>>())
             } else {
                 Either::Right(self.connections.next())
             };
+            let accept_fut = match &listener {
+                Some(listener) => Either::Left(listener.accept()),
+                None => Either::Right(futures::future::pending()),
+            };
 
             tokio::select! {
                 conn_res = conn_fut => {
@@ -347,9 +385,19 @@ impl Server {
                 _ = &mut subscription_fut => {}
                 _ = &mut discovery_fut => {}
                 _ = &mut session_expiry_fut => {}
-                rs = listener.accept() => {
+                _ = &mut buffer_trim_fut => {}
+                rs = accept_fut => {
                     match rs {
                         Ok((socket, addr)) => {
+                            let allowed = trace_read_lock!(self.session_manager)
+                                .rate_limiter()
+                                .allow_connection(addr.ip());
+                            if !allowed {
+                                warn!("Rejected connection from {addr}, too many connections from this address");
+                                self.info.diagnostics.inc_rejected_requests();
+                                drop(socket);
+                                continue;
+                            }
                             info!("Accept new connection from {addr} ({connection_counter})");
                             let conn = SessionStarter::new(
                                 TcpConnector::new(socket, TransportConfig {
@@ -358,6 +406,7 @@ impl Server {
                                     max_chunk_count: self.info.config.limits.max_chunk_count,
                                     receive_buffer_size: self.info.config.limits.receive_buffer_size,
                                     hello_timeout: Duration::from_secs(self.info.config.tcp_config.hello_timeout as u64),
+                                    buffer_pool: self.buffer_pool.clone(),
                                 }, self.info.decoding_options()),
                                 self.info.clone(),
                                 self.session_manager.clone(),
@@ -367,7 +416,9 @@ impl Server {
                             );
 
                             let (send, recv) = tokio::sync::mpsc::channel(5);
-                            let handle = tokio::spawn(conn.run(recv).map(move |_| connection_counter));
+                            let name = self.info.config.named_tasks
+                                .then(|| format!("opcua-server-connection-{connection_counter} ({addr})"));
+                            let handle = spawn_named(name, conn.run(recv).map(move |_| connection_counter));
                             self.connections.push(handle);
                             self.connection_map.insert(connection_counter, ConnectionInfo {
                                 command_send: send
@@ -379,6 +430,22 @@ impl Server {
                         }
                     }
                 }
+                deadline = Self::wait_for_drain(&mut self.drain), if drain_deadline.is_none() => {
+                    info!("Stopped accepting connections, draining {} open connections", self.connection_map.len());
+                    drain_deadline = Some(deadline);
+                    self.status.set_state(ServerState::Shutdown);
+                    listener = None;
+                    trace_write_lock!(self.session_manager).halt();
+                    self.subscriptions.halt(&context).await;
+                    for conn in self.connection_map.values() {
+                        let _ = conn.command_send.send(ControllerCommand::Drain).await;
+                    }
+                }
+                _ = tokio::time::sleep_until(drain_deadline.unwrap_or_else(Instant::now).into()),
+                    if drain_deadline.is_some() && !self.token.is_cancelled() => {
+                    warn!("Server drain timed out, closing {} remaining connections", self.connection_map.len());
+                    self.token.cancel();
+                }
                 _ = self.token.cancelled() => {
                     for conn in self.connection_map.values() {
                         let _ = conn.command_send.send(ControllerCommand::Close).await;
@@ -387,9 +454,26 @@ impl Server {
             }
         }
 
+        if drain_deadline.is_some() {
+            trace_write_lock!(self.session_manager).close_all_sessions();
+            info!("Server drained");
+        }
+
         Ok(())
     }
 
+    async fn wait_for_drain(drain: &mut watch::Receiver<Option<Instant>>) -> Instant {
+        let deadline = drain
+            .wait_for(|d| d.is_some())
+            .await
+            .map(|d| d.unwrap_or_else(Instant::now));
+        match deadline {
+            Ok(deadline) => deadline,
+            // The server handle is gone, so the server can no longer be drained.
+            Err(_) => futures::future::pending().await,
+        }
+    }
+
     /// Run the server. The provided `token` can be used to stop the server gracefully.
     pub async fn run(self) -> Result<(), String> {
         let addr = self.get_socket_address();
@@ -426,6 +510,21 @@ impl Server {
         }
     }
 
+    /// Free idle buffers in the shared buffer pool, so that memory used during
+    /// a burst of traffic is given back once the server is idle.
+    async fn run_buffer_pool_trim(pool: &BufferPool) -> Never {
+        if !pool.is_enabled() {
+            futures::future::pending().await
+        } else {
+            let mut tick = tokio::time::interval(Duration::from_secs(30));
+            tick.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+            loop {
+                tick.tick().await;
+                pool.trim();
+            }
+        }
+    }
+
     async fn run_session_expiry(sessions: &RwLock<SessionManager>, notify: &Notify) -> Never {
         loop {
             let ((expiry, expired), notified) = {
@@ -3,12 +3,14 @@ use std::{
     time::{Duration, Instant},
 };
 
-use opcua_nodes::DefaultTypeTree;
+use opcua_nodes::{DefaultTypeTree, Event};
+use tokio::sync::watch;
 use tokio_util::sync::CancellationToken;
 use tracing::info;
 
-use opcua_core::sync::RwLock;
-use opcua_types::{AttributeId, DataValue, LocalizedText, ServerState, VariableId};
+use opcua_core::{sync::RwLock, task::spawn_named, trace_read_lock};
+use opcua_crypto::{CertificateStore, ValidationCacheStats};
+use opcua_types::{AttributeId, DataValue, LocalizedText, NodeId, ServerState, VariableId};
 
 use crate::ServerStatusWrapper;
 
@@ -29,6 +31,8 @@ pub struct ServerHandle {
     type_tree: Arc<RwLock<DefaultTypeTree>>,
     token: CancellationToken,
     status: Arc<ServerStatusWrapper>,
+    certificate_store: Arc<RwLock<CertificateStore>>,
+    drain: Arc<watch::Sender<Option<Instant>>>,
 }
 
 impl ServerHandle {
@@ -41,7 +45,9 @@ impl ServerHandle {
         session_manager: Arc<RwLock<SessionManager>>,
         type_tree: Arc<RwLock<DefaultTypeTree>>,
         status: Arc<ServerStatusWrapper>,
+        certificate_store: Arc<RwLock<CertificateStore>>,
         token: CancellationToken,
+        drain: watch::Sender<Option<Instant>>,
     ) -> Self {
         Self {
             info,
@@ -51,7 +57,9 @@ impl ServerHandle {
             session_manager,
             type_tree,
             status,
+            certificate_store,
             token,
+            drain: Arc::new(drain),
         }
     }
 
@@ -60,6 +68,19 @@ impl ServerHandle {
         &self.info
     }
 
+    /// Get statistics for the client certificate validation cache,
+    /// or `None` if the cache is disabled.
+    pub fn certificate_validation_cache_stats(&self) -> Option<ValidationCacheStats> {
+        self.certificate_store.read().validation_cache_stats()
+    }
+
+    /// Clear the client certificate validation cache. Call this after modifying the
+    /// trusted or rejected certificate directories, so that the changes take effect for
+    /// clients with cached certificates.
+    pub fn clear_certificate_validation_cache(&self) {
+        self.certificate_store.read().clear_validation_cache();
+    }
+
     /// Get a reference to the subscription cache.
     pub fn subscriptions(&self) -> &Arc<SubscriptionCache> {
         &self.subscriptions
@@ -79,6 +100,20 @@ impl ServerHandle {
         );
     }
 
+    /// Notify listening clients of an event raised by `source`.
+    ///
+    /// The event is reported to monitored items on `source`, on every notifier above it
+    /// in the event hierarchy, found by following inverse `HasEventSource` and `HasNotifier`
+    /// references in all node managers, and on the `Server` object.
+    pub fn notify_events_from(&self, source: &NodeId, event: &dyn Event) {
+        let notifiers = {
+            let type_tree = trace_read_lock!(self.type_tree);
+            self.node_managers.event_notifiers(source, &*type_tree)
+        };
+        self.subscriptions
+            .notify_events_to([(event, notifiers.iter())].into_iter());
+    }
+
     /// Get a reference to the node managers on the server.
     pub fn node_managers(&self) -> &NodeManagers {
         &self.node_managers
@@ -89,6 +124,18 @@ impl ServerHandle {
         &self.session_manager
     }
 
+    /// Enable or disable hex logging of all chunks sent and received on the connection
+    /// used by the session given by `session_id`. This makes it possible to debug a single
+    /// client without enabling trace logging on the `hex` target for every connection.
+    /// When enabled, chunks are logged at trace level on the `connection_trace` target,
+    /// so trace logging must be enabled for that target. The bodies of `ActivateSession`
+    /// messages are not logged, since they contain user identity tokens.
+    ///
+    /// Returns `false` if the session does not exist or is not attached to a connection.
+    pub fn set_connection_trace(&self, session_id: &NodeId, enabled: bool) -> bool {
+        trace_read_lock!(self.session_manager).set_connection_trace(session_id, enabled)
+    }
+
     /// Get a reference to the type tree, containing shared information about types in the server.
     pub fn type_tree(&self) -> &RwLock<DefaultTypeTree> {
         &self.type_tree
@@ -110,6 +157,22 @@ impl ServerHandle {
         self.token.cancel();
     }
 
+    /// Gracefully shut down the server.
+    ///
+    /// The server stops accepting new connections and sets its state to `Shutdown`.
+    /// New sessions and subscriptions are rejected with `BadServerHalted`.
+    /// All subscriptions are closed, sending a final status change notification with
+    /// `BadServerHalted` to their clients. Each connection is closed once its in-flight
+    /// requests have completed and its final notifications have been sent. Any connections
+    /// still open after `timeout` are closed as if the server was cancelled.
+    ///
+    /// Once all connections are closed, the remaining sessions are closed
+    /// and `Server::run` returns.
+    pub fn shutdown(&self, timeout: Duration) {
+        info!("Draining server, shutting down in at most {timeout:?}");
+        self.drain.send_replace(Some(Instant::now() + timeout));
+    }
+
     /// Shorthand for getting the index of a namespace defined in the global server type tree.
     pub fn get_namespace_index(&self, namespace: &str) -> Option<u16> {
         self.type_tree.read().namespaces().get_index(namespace)
@@ -123,7 +186,12 @@ impl ServerHandle {
             .schedule_shutdown(reason.into(), Instant::now() + time);
         let token = self.token.clone();
         info!("Shutting down server in {time:?}");
-        tokio::task::spawn(async move {
+        let name = self
+            .info
+            .config
+            .named_tasks
+            .then(|| "opcua-server-shutdown-timer".to_owned());
+        spawn_named(name, async move {
             tokio::time::sleep_until(deadline.into()).await;
             token.cancel();
         });
@@ -31,9 +31,17 @@ struct ShutdownTarget {
 
 #[allow(unused)]
 impl ServerStatusWrapper {
-    pub(crate) fn new(build_info: BuildInfo, subscriptions: Arc<SubscriptionCache>) -> Self {
+    pub(crate) fn new(
+        build_info: BuildInfo,
+        subscriptions: Arc<SubscriptionCache>,
+        named_tasks: bool,
+    ) -> Self {
         let sampler = SyncSampler::new();
-        sampler.run(Duration::from_secs(1), subscriptions.clone());
+        sampler.run_named(
+            named_tasks.then(|| "opcua-server-status-sampler".to_owned()),
+            Duration::from_secs(1),
+            subscriptions.clone(),
+        );
 
         Self {
             status: Arc::new(Mutex::new(ServerStatusDataType {
@@ -0,0 +1,249 @@
+//! Audit events raised by the server for security-relevant operations.
+//!
+//! These are only emitted if auditing is enabled in the server configuration,
+//! and are reported to clients through the `Server` object. Audit events contain
+//! user names and client certificates, so they are only reported to sessions whose
+//! user has the `read_diagnostics` permission.
+
+use opcua_core::{comms::security_header::AsymmetricSecurityHeader, trace_read_lock};
+use opcua_core_namespace::events::{
+    AuditActivateSessionEventType, AuditCertificateDataMismatchEventType, AuditEventType,
+    AuditOpenSecureChannelEventType,
+};
+use opcua_crypto::{random, X509};
+use opcua_nodes::Event;
+use opcua_types::{
+    ActivateSessionRequest, ByteString, CreateSessionRequest, DateTime, ExtensionObject,
+    NamespaceMap, NodeId, ObjectId, ObjectTypeId, OpenSecureChannelRequest, RequestHeader,
+    StatusCode, UAString, UserNameIdentityToken,
+};
+use parking_lot::RwLock;
+
+use crate::{info::ServerInfo, subscriptions::SubscriptionCache};
+
+use super::manager::SessionManager;
+
+/// Severity of audit events for successful operations.
+const SEVERITY_SUCCESS: u16 = 100;
+/// Severity of audit events for failed operations.
+const SEVERITY_FAILURE: u16 = 500;
+
+/// Create a new audit event using the `new_event` constructor of the event type.
+fn new_audit_event<T>(
+    info: &ServerInfo,
+    ctor: impl FnOnce(NodeId, ByteString, String, &NamespaceMap, DateTime) -> T,
+    type_id: NodeId,
+    message: String,
+) -> T {
+    let type_tree = trace_read_lock!(info.type_tree);
+    ctor(
+        type_id,
+        random::byte_string(16),
+        message,
+        type_tree.namespaces(),
+        DateTime::now(),
+    )
+}
+
+fn set_audit_fields(
+    evt: &mut AuditEventType,
+    info: &ServerInfo,
+    request_header: &RequestHeader,
+    source_name: &str,
+    client_user_id: UAString,
+    status: StatusCode,
+) {
+    evt.base.source_node = ObjectId::Server.into();
+    evt.base.source_name = source_name.into();
+    evt.base.severity = if status.is_good() {
+        SEVERITY_SUCCESS
+    } else {
+        SEVERITY_FAILURE
+    };
+    evt.action_time_stamp = evt.base.time;
+    evt.status = status.is_good();
+    evt.server_id = info.application_uri.clone();
+    evt.client_audit_entry_id = request_header.audit_entry_id.clone();
+    evt.client_user_id = client_user_id;
+}
+
+fn notify(
+    subscriptions: &SubscriptionCache,
+    session_manager: &RwLock<SessionManager>,
+    evt: &dyn Event,
+) {
+    let allowed = trace_read_lock!(session_manager).sessions_with_read_diagnostics();
+    if allowed.is_empty() {
+        return;
+    }
+    let server_id: NodeId = ObjectId::Server.into();
+    subscriptions.notify_events_to_sessions(
+        [(evt, std::iter::once(&server_id))].into_iter(),
+        |session_id| allowed.contains(&session_id),
+    );
+}
+
+/// Emit an `AuditOpenSecureChannelEventType` for an `OpenSecureChannel` request.
+pub(super) fn open_secure_channel(
+    info: &ServerInfo,
+    subscriptions: &SubscriptionCache,
+    session_manager: &RwLock<SessionManager>,
+    secure_channel_id: u32,
+    security_header: &AsymmetricSecurityHeader,
+    request: &OpenSecureChannelRequest,
+    status: StatusCode,
+) {
+    if !info.config.auditing {
+        return;
+    }
+    let mut evt = new_audit_event(
+        info,
+        AuditOpenSecureChannelEventType::new_event,
+        AuditOpenSecureChannelEventType::event_type_id(),
+        format!(
+            "OpenSecureChannel request with security policy {} completed with status {status}",
+            security_header.security_policy_uri
+        ),
+    );
+    set_audit_fields(
+        &mut evt.base.base.base,
+        info,
+        &request.request_header,
+        "SecureChannel/OpenSecureChannel",
+        UAString::null(),
+        status,
+    );
+    evt.base.base.status_code_id = status;
+    evt.base.secure_channel_id = secure_channel_id.to_string().into();
+    evt.client_certificate = security_header.sender_certificate.clone();
+    if !security_header.sender_certificate.is_null() {
+        if let Ok(cert) = X509::from_byte_string(&security_header.sender_certificate) {
+            evt.client_certificate_thumbprint = cert.thumbprint().as_hex_string().into();
+        }
+    }
+    evt.request_type = request.request_type;
+    evt.requested_lifetime = request.requested_lifetime as f64;
+    evt.security_mode = request.security_mode;
+    evt.security_policy_uri = security_header.security_policy_uri.clone();
+
+    notify(subscriptions, session_manager, &evt);
+}
+
+/// Emit an audit event for a rejected client application instance certificate
+/// in a `CreateSession` request.
+///
+/// Certificates with a hostname or application URI that does not match the request
+/// are reported using `AuditCertificateDataMismatchEventType`, other errors are reported
+/// as the matching subtype of `AuditCertificateEventType`.
+pub(super) fn certificate_rejected(
+    info: &ServerInfo,
+    subscriptions: &SubscriptionCache,
+    session_manager: &RwLock<SessionManager>,
+    request: &CreateSessionRequest,
+    status: StatusCode,
+) {
+    if !info.config.auditing {
+        return;
+    }
+    let type_id: NodeId = match status {
+        StatusCode::BadCertificateHostNameInvalid | StatusCode::BadCertificateUriInvalid => {
+            ObjectTypeId::AuditCertificateDataMismatchEventType
+        }
+        StatusCode::BadCertificateUntrusted => ObjectTypeId::AuditCertificateUntrustedEventType,
+        StatusCode::BadCertificateTimeInvalid | StatusCode::BadCertificateIssuerTimeInvalid => {
+            ObjectTypeId::AuditCertificateExpiredEventType
+        }
+        StatusCode::BadCertificateRevoked | StatusCode::BadCertificateIssuerRevoked => {
+            ObjectTypeId::AuditCertificateRevokedEventType
+        }
+        _ => ObjectTypeId::AuditCertificateInvalidEventType,
+    }
+    .into();
+
+    // The certificate subtypes other than the data mismatch event have no additional
+    // fields, so we use the data mismatch event for all of them, with the correct type ID.
+    let mut evt = new_audit_event(
+        info,
+        AuditCertificateDataMismatchEventType::new_event,
+        type_id,
+        format!("Client certificate in CreateSession request rejected with status {status}"),
+    );
+    set_audit_fields(
+        &mut evt.base.base.base,
+        info,
+        &request.request_header,
+        "Security/Certificate",
+        UAString::null(),
+        status,
+    );
+    evt.base.base.status_code_id = status;
+    evt.base.certificate = request.client_certificate.clone();
+    match status {
+        StatusCode::BadCertificateHostNameInvalid => {
+            evt.invalid_hostname = request.endpoint_url.clone();
+        }
+        StatusCode::BadCertificateUriInvalid => {
+            evt.invalid_uri = request.client_description.application_uri.clone();
+        }
+        _ => (),
+    }
+
+    notify(subscriptions, session_manager, &evt);
+}
+
+/// Get the client user ID and a copy of the user identity token without any secrets.
+fn sanitize_identity_token(token: &ExtensionObject) -> (UAString, ExtensionObject) {
+    if let Some(token) = token.inner_as::<UserNameIdentityToken>() {
+        let sanitized = UserNameIdentityToken {
+            password: ByteString::null(),
+            ..token.clone()
+        };
+        (
+            token.user_name.clone(),
+            ExtensionObject::from_message(sanitized),
+        )
+    } else {
+        (UAString::null(), token.clone())
+    }
+}
+
+/// Emit an `AuditActivateSessionEventType` for an `ActivateSession` request.
+pub(super) fn activate_session(
+    info: &ServerInfo,
+    subscriptions: &SubscriptionCache,
+    session_manager: &RwLock<SessionManager>,
+    secure_channel_id: u32,
+    request: &ActivateSessionRequest,
+    status: StatusCode,
+) {
+    if !info.config.auditing {
+        return;
+    }
+    let session_id = trace_read_lock!(session_manager)
+        .find_by_token(&request.request_header.authentication_token)
+        .map(|s| trace_read_lock!(s).session_id().clone())
+        .unwrap_or_default();
+
+    let mut evt = new_audit_event(
+        info,
+        AuditActivateSessionEventType::new_event,
+        AuditActivateSessionEventType::event_type_id(),
+        format!("ActivateSession request for session {session_id} completed with status {status}"),
+    );
+    let (client_user_id, user_identity_token) =
+        sanitize_identity_token(&request.user_identity_token);
+    set_audit_fields(
+        &mut evt.base.base.base,
+        info,
+        &request.request_header,
+        "Session/ActivateSession",
+        client_user_id,
+        status,
+    );
+    evt.base.base.status_code_id = status;
+    evt.base.session_id = session_id;
+    evt.secure_channel_id = secure_channel_id.to_string().into();
+    evt.user_identity_token = user_identity_token;
+
+    notify(subscriptions, session_manager, &evt);
+}
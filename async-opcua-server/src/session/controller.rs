@@ -18,9 +18,9 @@ use opcua_core::{
 };
 use opcua_crypto::{CertificateStore, SecurityPolicy};
 use opcua_types::{
-    ChannelSecurityToken, DateTime, FindServersResponse, GetEndpointsResponse, MessageSecurityMode,
-    OpenSecureChannelRequest, OpenSecureChannelResponse, ResponseHeader, SecurityTokenRequestType,
-    ServiceFault, StatusCode,
+    ChannelSecurityToken, DateTime, Error, FindServersResponse, GetEndpointsResponse,
+    MessageSecurityMode, OpenSecureChannelRequest, OpenSecureChannelResponse, ResponseHeader,
+    SecurityTokenRequestType, ServiceFault, StatusCode,
 };
 use tokio_util::sync::CancellationToken;
 use tracing_futures::Instrument;
@@ -66,6 +66,9 @@ impl Response {
 
 pub(crate) enum ControllerCommand {
     Close,
+    /// Finish in-flight requests and deliver final subscription notifications,
+    /// then close the connection.
+    Drain,
 }
 
 type PendingMessageResponse = dyn Future<Output = Result<Response, String>> + Send + Sync + 'static;
@@ -78,9 +81,11 @@ pub(crate) struct SessionController {
     session_manager: Arc<RwLock<SessionManager>>,
     certificate_store: Arc<RwLock<CertificateStore>>,
     message_handler: MessageHandler,
+    subscriptions: Arc<SubscriptionCache>,
     pending_messages: FuturesUnordered<Pin<Box<PendingMessageResponse>>>,
     info: Arc<ServerInfo>,
     deadline: Instant,
+    draining: bool,
 }
 
 enum RequestProcessResult {
@@ -127,7 +132,7 @@ impl<T: Connector> SessionStarter<T> {
         let transport = tokio::select! {
             cmd = command.recv() => {
                 match cmd {
-                    Some(ControllerCommand::Close) | None => {
+                    Some(ControllerCommand::Close | ControllerCommand::Drain) | None => {
                         token.cancel();
                         let _ = fut.await;
                         return;
@@ -180,16 +185,26 @@ impl SessionController {
             secure_channel_state: SecureChannelState::new(info.secure_channel_id_handle.clone()),
             session_manager,
             certificate_store,
-            message_handler: MessageHandler::new(info.clone(), node_managers, subscriptions),
+            message_handler: MessageHandler::new(
+                info.clone(),
+                node_managers,
+                subscriptions.clone(),
+            ),
+            subscriptions,
             deadline: Instant::now()
                 + Duration::from_secs(info.config.tcp_config.hello_timeout as u64),
             info,
             pending_messages: FuturesUnordered::new(),
+            draining: false,
         }
     }
 
     async fn run(mut self, mut command: tokio::sync::mpsc::Receiver<ControllerCommand>) {
         loop {
+            if self.draining && !self.transport.is_closing() && self.is_drained() {
+                self.fatal_error(StatusCode::BadServerHalted, "Server stopped");
+            }
+
             let resp_fut = if self.pending_messages.is_empty() {
                 Either::Left(futures::future::pending::<Option<Result<Response, String>>>())
             } else {
@@ -206,6 +221,9 @@ impl SessionController {
                         Some(ControllerCommand::Close) | None => {
                             self.fatal_error(StatusCode::BadServerHalted, "Server stopped");
                         }
+                        Some(ControllerCommand::Drain) => {
+                            self.draining = true;
+                        }
                     }
                 }
                 msg = resp_fut => {
@@ -245,9 +263,9 @@ impl SessionController {
                                 self.fatal_error(e, "Encoding error");
                             }
                         }
-                        TransportPollResult::Error(s) => {
-                            error!("Fatal transport error: {s}");
-                            self.fatal_error(s, "Transport error");
+                        TransportPollResult::Error(e) => {
+                            error!("Fatal transport error: {e}");
+                            self.fatal_error(e.status(), &e.to_string());
                         }
                         TransportPollResult::Closed => break,
                         _ => (),
@@ -255,10 +273,13 @@ impl SessionController {
                 }
             }
         }
+
+        trace_write_lock!(self.session_manager)
+            .unregister_channel_trace(self.channel.secure_channel_id());
     }
 
     fn response_metrics(&self, msg: &Response) {
-        if self.info.diagnostics.enabled {
+        if self.info.diagnostics.is_enabled() {
             let status = msg.message.response_header().service_result;
             if status.is_bad() {
                 self.info.diagnostics.inc_rejected_requests();
@@ -274,6 +295,22 @@ impl SessionController {
         }
     }
 
+    /// Check whether a draining connection is done, meaning it has no requests in flight,
+    /// no responses left to write, and its sessions have no subscriptions with notifications
+    /// left to send.
+    fn is_drained(&self) -> bool {
+        // Closing the connection discards any outgoing messages, so wait for the
+        // last responses to be written first.
+        if !self.pending_messages.is_empty() || self.transport.is_sending() {
+            return false;
+        }
+        let sessions = trace_read_lock!(self.session_manager)
+            .sessions_on_channel(self.channel.secure_channel_id());
+        !sessions
+            .into_iter()
+            .any(|id| self.subscriptions.has_subscriptions(id))
+    }
+
     fn fatal_error(&mut self, err: StatusCode, msg: &str) {
         if !self.transport.is_closing() {
             self.transport.enqueue_error(ErrorMessage::new(err, msg));
@@ -282,22 +319,42 @@ impl SessionController {
     }
 
     async fn process_request(&mut self, req: Request) -> RequestProcessResult {
+        // The session ID is recorded once the request has been validated.
         let span = debug_span!(
             "Incoming request",
             request_id = req.request_id,
-            request_type = %req.message.type_name(),
+            service = %req.message.type_name(),
             request_handle = req.message.request_handle(),
+            session_id = tracing::field::Empty,
         );
 
         let id = req.request_id;
         match req.message {
             RequestMessage::OpenSecureChannel(r) => {
                 let _h = span.enter();
+                if let Err(e) = self.validate_security_policy(&req.chunk_info.security_header, &r) {
+                    error!("Rejected open secure channel request: {e}");
+                    #[cfg(feature = "generated-address-space")]
+                    self.audit_open_secure_channel(&req.chunk_info.security_header, &r, e.status());
+                    self.info.diagnostics.inc_rejected_requests();
+                    self.info.diagnostics.inc_security_rejected_requests();
+                    self.fatal_error(e.status(), &e.to_string());
+                    return RequestProcessResult::Close;
+                }
                 let res = self.open_secure_channel(
                     &req.chunk_info.security_header,
                     self.transport.client_protocol_version,
                     &r,
                 );
+                #[cfg(feature = "generated-address-space")]
+                self.audit_open_secure_channel(
+                    &req.chunk_info.security_header,
+                    &r,
+                    match &res {
+                        Ok(r) => r.response_header().service_result,
+                        Err(e) => *e,
+                    },
+                );
                 if res.is_ok() {
                     self.deadline = self.channel.token_renewal_deadline();
                 } else {
@@ -333,6 +390,18 @@ impl SessionController {
                 let mut mgr = trace_write_lock!(self.session_manager);
                 let res = mgr.create_session(&mut self.channel, &self.certificate_store, &request);
                 drop(mgr);
+                #[cfg(feature = "generated-address-space")]
+                if let Err(e) = &res {
+                    if is_certificate_error(*e) {
+                        super::audit::certificate_rejected(
+                            &self.info,
+                            &self.subscriptions,
+                            &self.session_manager,
+                            &request,
+                            *e,
+                        );
+                    }
+                }
                 self.process_service_result(res, request.request_header.request_handle, id)
             }
 
@@ -346,6 +415,18 @@ impl SessionController {
                 .instrument(span.clone())
                 .await;
                 let _h = span.enter();
+                #[cfg(feature = "generated-address-space")]
+                super::audit::activate_session(
+                    &self.info,
+                    &self.subscriptions,
+                    &self.session_manager,
+                    self.channel.secure_channel_id(),
+                    &request,
+                    match &res {
+                        Ok(r) => r.response_header.service_result,
+                        Err(e) => *e,
+                    },
+                );
                 self.process_service_result(res, request.request_header.request_handle, id)
             }
 
@@ -456,6 +537,11 @@ impl SessionController {
                 let now = Instant::now();
                 let mgr = trace_read_lock!(self.session_manager);
                 let session = mgr.find_by_token(&message.request_header().authentication_token);
+                let session_diagnostics = session.as_ref().and_then(|s| {
+                    self.info
+                        .diagnostics
+                        .session(trace_read_lock!(s).session_id_numeric())
+                });
 
                 let (session_id, session, user_token) =
                     match Self::validate_request(&message, session, &self.channel) {
@@ -463,6 +549,9 @@ impl SessionController {
                         Err(e) => {
                             self.info.diagnostics.inc_rejected_requests();
                             self.info.diagnostics.inc_security_rejected_requests();
+                            if let Some(diagnostics) = &session_diagnostics {
+                                diagnostics.on_unauthorized_request();
+                            }
                             match self
                                 .transport
                                 .enqueue_message_for_send(&mut self.channel, e, id)
@@ -476,8 +565,25 @@ impl SessionController {
                         }
                     };
 
+                span.record("session_id", session_id);
                 debug!("Received request on session {session_id}");
 
+                // Publish requests are queued by the client to receive notifications,
+                // so they are not counted towards the request rate limit.
+                let rate_limited = !matches!(
+                    message,
+                    RequestMessage::Publish(_) | RequestMessage::Republish(_)
+                );
+                if rate_limited && !mgr.rate_limiter().allow_request(session_id) {
+                    drop(mgr);
+                    warn!("Rejected request on session {session_id}, too many requests");
+                    return self.process_service_result(
+                        Err::<ResponseMessage, _>(StatusCode::BadTooManyOperations),
+                        message.request_handle(),
+                        id,
+                    );
+                }
+
                 let deadline = {
                     let timeout = message.request_header().timeout_hint;
                     let max_timeout = self.info.config.max_timeout_ms;
@@ -495,6 +601,9 @@ impl SessionController {
                     }
                 };
                 let request_handle = message.request_handle();
+                if let Some(diagnostics) = &session_diagnostics {
+                    diagnostics.on_request();
+                }
 
                 match self
                     .message_handler
@@ -506,7 +615,7 @@ impl SessionController {
                                 // Select biased because if for some reason there's a long time between polls,
                                 // we want to return the response even if the timeout expired. We only want to send a timeout
                                 // if the call has not been finished yet.
-                                tokio::select! {
+                                let res = tokio::select! {
                                     biased;
                                     r = &mut handle => {
                                         match r {
@@ -527,7 +636,13 @@ impl SessionController {
                                         handle.abort();
                                         Ok(Response { message: ServiceFault::new(request_handle, StatusCode::BadTimeout).into(), request_id: id })
                                     }
+                                };
+                                if let (Some(diagnostics), Ok(r)) = (&session_diagnostics, &res) {
+                                    if r.message.response_header().service_result.is_bad() {
+                                        diagnostics.on_error();
+                                    }
                                 }
+                                res
                             }.instrument(span.clone())));
                         RequestProcessResult::Ok
                     }
@@ -537,6 +652,11 @@ impl SessionController {
                             "Sending response of type {}", s.message.type_name()
                         );
                         self.response_metrics(&s);
+                        if let Some(diagnostics) = &session_diagnostics {
+                            if s.message.response_header().service_result.is_bad() {
+                                diagnostics.on_error();
+                            }
+                        }
 
                         if let Err(e) = self.transport.enqueue_message_for_send(
                             &mut self.channel,
@@ -549,7 +669,8 @@ impl SessionController {
                         RequestProcessResult::Ok
                     }
                     super::message_handler::HandleMessageResult::PublishResponse(resp) => {
-                        self.pending_messages.push(Box::pin(resp.recv()));
+                        self.pending_messages
+                            .push(Box::pin(resp.recv().instrument(span.clone())));
                         RequestProcessResult::Ok
                     }
                 }
@@ -614,6 +735,70 @@ impl SessionController {
         Ok((id, session, user_token))
     }
 
+    /// Check that the security policy and security mode requested by the client
+    /// are offered by at least one endpoint on the server.
+    ///
+    /// Channels without security are always allowed, since clients need them for discovery.
+    fn validate_security_policy(
+        &self,
+        security_header: &SecurityHeader,
+        request: &OpenSecureChannelRequest,
+    ) -> Result<(), Error> {
+        let SecurityHeader::Asymmetric(security_header) = security_header else {
+            return Ok(());
+        };
+        let security_policy = security_header.security_policy();
+        if security_policy == SecurityPolicy::None {
+            return Ok(());
+        }
+        let mut endpoints = self
+            .info
+            .config
+            .endpoints
+            .values()
+            .filter(|e| e.security_policy() == security_policy)
+            .peekable();
+        if endpoints.peek().is_none() {
+            return Err(Error::new(
+                StatusCode::BadSecurityPolicyRejected,
+                format!(
+                    "Security policy \"{}\" requested by client is not offered by any endpoint",
+                    security_header.security_policy_uri
+                ),
+            ));
+        }
+        if !endpoints.any(|e| e.message_security_mode() == request.security_mode) {
+            return Err(Error::new(
+                StatusCode::BadSecurityModeRejected,
+                format!(
+                    "Security mode {:?} is not offered by any endpoint with security policy \"{}\"",
+                    request.security_mode, security_header.security_policy_uri
+                ),
+            ));
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "generated-address-space")]
+    fn audit_open_secure_channel(
+        &self,
+        security_header: &SecurityHeader,
+        request: &OpenSecureChannelRequest,
+        status: StatusCode,
+    ) {
+        if let SecurityHeader::Asymmetric(security_header) = security_header {
+            super::audit::open_secure_channel(
+                &self.info,
+                &self.subscriptions,
+                &self.session_manager,
+                self.channel.secure_channel_id(),
+                security_header,
+                request,
+                status,
+            );
+        }
+    }
+
     fn open_secure_channel(
         &mut self,
         security_header: &SecurityHeader,
@@ -704,6 +889,8 @@ impl SessionController {
         self.channel
             .set_token_id(self.secure_channel_state.create_token_id());
         self.channel.set_secure_channel_id(secure_channel_id);
+        trace_write_lock!(self.session_manager)
+            .register_channel_trace(secure_channel_id, self.channel.trace_chunks_handle());
         self.channel
             .set_remote_cert_from_byte_string(&security_header.sender_certificate)?;
 
@@ -773,3 +960,24 @@ impl SecureChannelState {
         self.last_token_id
     }
 }
+
+/// Check whether a status code returned from `CreateSession` is caused by
+/// validation of the client certificate.
+#[cfg(feature = "generated-address-space")]
+fn is_certificate_error(status: StatusCode) -> bool {
+    matches!(
+        status,
+        StatusCode::BadCertificateInvalid
+            | StatusCode::BadCertificateUntrusted
+            | StatusCode::BadCertificateTimeInvalid
+            | StatusCode::BadCertificateIssuerTimeInvalid
+            | StatusCode::BadCertificateHostNameInvalid
+            | StatusCode::BadCertificateUriInvalid
+            | StatusCode::BadCertificateRevoked
+            | StatusCode::BadCertificateIssuerRevoked
+            | StatusCode::BadCertificateRevocationUnknown
+            | StatusCode::BadCertificateIssuerRevocationUnknown
+            | StatusCode::BadCertificateUseNotAllowed
+            | StatusCode::BadSecurityChecksFailed
+    )
+}
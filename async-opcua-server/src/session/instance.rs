@@ -7,6 +7,7 @@ use tracing::error;
 
 use super::continuation_points::ContinuationPoint;
 use super::manager::next_session_id;
+use super::registered_nodes::RegisteredNodes;
 use crate::authenticator::UserToken;
 use crate::identity_token::IdentityToken;
 use crate::info::ServerInfo;
@@ -48,6 +49,8 @@ pub struct Session {
     endpoint_url: UAString,
     /// Maximum number of continuation points for browse
     max_browse_continuation_points: usize,
+    /// Time after which unused browse continuation points expire, if any.
+    browse_continuation_point_timeout: Option<Duration>,
     /// Maximum number of continuation points for history.
     max_history_continuation_points: usize,
     /// Maximum number of continuation points for query.
@@ -64,6 +67,8 @@ pub struct Session {
     history_continuation_points: HashMap<ByteString, ContinuationPoint>,
     /// Continuation points for querying.
     query_continuation_points: HashMap<ByteString, QueryContinuationPoint>,
+    /// Handles given out by `RegisterNodes`.
+    registered_nodes: RegisteredNodes,
     /// User token.
     user_token: Option<UserToken>,
     /// Whether the session has been closed.
@@ -111,11 +116,22 @@ impl Session {
             max_response_message_size,
             endpoint_url,
             max_browse_continuation_points: info.config.limits.max_browse_continuation_points,
+            browse_continuation_point_timeout: match info
+                .config
+                .limits
+                .browse_continuation_point_timeout_ms
+            {
+                0 => None,
+                ms => Some(Duration::from_millis(ms)),
+            },
             max_history_continuation_points: info.config.limits.max_history_continuation_points,
             max_query_continuation_points: info.config.limits.max_query_continuation_points,
             browse_continuation_points: Default::default(),
             history_continuation_points: Default::default(),
             query_continuation_points: Default::default(),
+            registered_nodes: RegisteredNodes::new(
+                info.operational_limits.max_registered_nodes_per_session,
+            ),
             user_token: None,
             application_description,
             message_security_mode,
@@ -189,6 +205,7 @@ impl Session {
 
     pub(crate) fn close(&mut self) {
         self.is_closed = true;
+        self.registered_nodes.clear();
     }
 
     /// Get the session ID of this session, this is known to the client, and is what they
@@ -229,6 +246,9 @@ impl Session {
         &mut self,
         cp: BrowseContinuationPoint,
     ) -> Result<(), ()> {
+        // Release expired continuation points first, so that they don't count
+        // towards the limit.
+        self.release_expired_browse_continuation_points();
         if self.max_browse_continuation_points <= self.browse_continuation_points.len()
             && self.max_browse_continuation_points > 0
         {
@@ -239,11 +259,24 @@ impl Session {
         }
     }
 
+    /// Release any browse continuation points that have not been used within
+    /// the configured timeout.
+    pub(crate) fn release_expired_browse_continuation_points(&mut self) {
+        if let Some(timeout) = self.browse_continuation_point_timeout {
+            self.browse_continuation_points
+                .retain(|_, c| !c.is_expired(timeout));
+        }
+    }
+
     pub(crate) fn remove_browse_continuation_point(
         &mut self,
         id: &ByteString,
     ) -> Option<BrowseContinuationPoint> {
-        self.browse_continuation_points.remove(id)
+        let cp = self.browse_continuation_points.remove(id)?;
+        match self.browse_continuation_point_timeout {
+            Some(timeout) if cp.is_expired(timeout) => None,
+            _ => Some(cp),
+        }
     }
 
     pub(crate) fn add_history_continuation_point(
@@ -290,6 +323,14 @@ impl Session {
         self.query_continuation_points.remove(id)
     }
 
+    pub(crate) fn registered_nodes(&self) -> &RegisteredNodes {
+        &self.registered_nodes
+    }
+
+    pub(crate) fn registered_nodes_mut(&mut self) -> &mut RegisteredNodes {
+        &mut self.registered_nodes
+    }
+
     /// Get the application description of the client that created this session.
     pub fn application_description(&self) -> &ApplicationDescription {
         &self.application_description
@@ -330,4 +371,18 @@ impl Session {
     pub fn security_policy_uri(&self) -> &str {
         &self.security_policy_uri
     }
+
+    /// Get the revised session timeout.
+    pub fn session_timeout(&self) -> Duration {
+        self.session_timeout
+    }
+
+    /// Get the locale IDs requested by the client when activating the session.
+    pub fn locale_ids(&self) -> Option<&Vec<UAString>> {
+        self.locale_ids.as_ref()
+    }
+
+    pub(crate) fn user_identity(&self) -> &IdentityToken {
+        &self.user_identity
+    }
 }
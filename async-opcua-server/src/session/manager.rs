@@ -1,7 +1,7 @@
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     sync::{
-        atomic::{AtomicU32, Ordering},
+        atomic::{AtomicBool, AtomicU32, Ordering},
         Arc,
     },
     time::{Duration, Instant},
@@ -20,7 +20,7 @@ use opcua_types::{
     StatusCode,
 };
 
-use super::{instance::Session, message_handler::MessageHandler};
+use super::{instance::Session, message_handler::MessageHandler, rate_limit::RateLimiter};
 
 static NEXT_SESSION_ID: AtomicU32 = AtomicU32::new(1);
 
@@ -33,19 +33,54 @@ pub(super) fn next_session_id() -> (NodeId, u32) {
 /// Manages all sessions on the server.
 pub struct SessionManager {
     sessions: HashMap<NodeId, Arc<RwLock<Session>>>,
+    channel_traces: HashMap<u32, Arc<AtomicBool>>,
     info: Arc<ServerInfo>,
     notify: Arc<Notify>,
+    rate_limiter: RateLimiter,
+    /// Whether the server is shutting down, in which case no new sessions are created.
+    halted: bool,
 }
 
 impl SessionManager {
     pub(crate) fn new(info: Arc<ServerInfo>, notify: Arc<Notify>) -> Self {
         Self {
             sessions: Default::default(),
+            channel_traces: Default::default(),
+            rate_limiter: RateLimiter::new(&info.config.limits.rate_limits),
             info,
             notify,
+            halted: false,
         }
     }
 
+    /// Stop creating new sessions, as the server is shutting down. Any later
+    /// `CreateSession` request fails with `BadServerHalted`.
+    pub(crate) fn halt(&mut self) {
+        self.halted = true;
+    }
+
+    /// Get the limiter for the rate of new connections and requests per session.
+    pub(crate) fn rate_limiter(&self) -> &RateLimiter {
+        &self.rate_limiter
+    }
+
+    /// Get the numeric IDs of the sessions whose user has permission to read
+    /// diagnostics. Only these sessions receive audit events.
+    pub(crate) fn sessions_with_read_diagnostics(&self) -> HashSet<u32> {
+        self.sessions
+            .values()
+            .filter_map(|s| {
+                let s = trace_read_lock!(s);
+                let token = s.user_token()?;
+                self.info
+                    .authenticator
+                    .core_permissions(token)
+                    .read_diagnostics
+                    .then(|| s.session_id_numeric())
+            })
+            .collect()
+    }
+
     /// Get a session by its authentication token.
     pub fn find_by_token(&self, authentication_token: &NodeId) -> Option<Arc<RwLock<Session>>> {
         Self::find_by_token_int(&self.sessions, authentication_token)
@@ -67,6 +102,9 @@ impl SessionManager {
         certificate_store: &RwLock<CertificateStore>,
         request: &CreateSessionRequest,
     ) -> Result<CreateSessionResponse, StatusCode> {
+        if self.halted {
+            return Err(StatusCode::BadServerHalted);
+        }
         if self.sessions.len() >= self.info.config.limits.max_sessions {
             return Err(StatusCode::BadTooManySessions);
         }
@@ -160,6 +198,9 @@ impl SessionManager {
         info!("Created new session with ID {}", session.session_id());
 
         let session_id = session.session_id().clone();
+        self.info
+            .diagnostics
+            .register_session(&session, self.info.application_uri.as_ref());
         self.sessions
             .insert(session_id.clone(), Arc::new(RwLock::new(session)));
 
@@ -222,6 +263,9 @@ impl SessionManager {
         self.info
             .diagnostics
             .set_current_session_count(self.sessions.len() as u32);
+        self.info
+            .diagnostics
+            .remove_session(session.read().session_id_numeric());
         self.info.diagnostics.inc_session_timeout_count();
 
         info!("Session {id} has expired, removing it from the session map. Subscriptions will remain until they individually expire");
@@ -230,16 +274,84 @@ impl SessionManager {
         session.close();
     }
 
+    /// Get the numeric IDs of all sessions currently attached to the secure channel
+    /// given by `secure_channel_id`.
+    pub(crate) fn sessions_on_channel(&self, secure_channel_id: u32) -> Vec<u32> {
+        self.sessions
+            .values()
+            .filter_map(|s| {
+                let session = trace_read_lock!(s);
+                (session.secure_channel_id() == secure_channel_id)
+                    .then(|| session.session_id_numeric())
+            })
+            .collect()
+    }
+
+    /// Register the flag controlling hex logging of chunks on the secure channel
+    /// given by `secure_channel_id`.
+    pub(crate) fn register_channel_trace(
+        &mut self,
+        secure_channel_id: u32,
+        trace: Arc<AtomicBool>,
+    ) {
+        self.channel_traces.insert(secure_channel_id, trace);
+    }
+
+    /// Remove the hex logging flag for a secure channel that has been closed.
+    pub(crate) fn unregister_channel_trace(&mut self, secure_channel_id: u32) {
+        self.channel_traces.remove(&secure_channel_id);
+    }
+
+    /// Enable or disable hex logging of all chunks sent and received on the connection
+    /// the session given by `session_id` is currently attached to.
+    ///
+    /// Returns `false` if the session or its connection does not exist.
+    pub fn set_connection_trace(&self, session_id: &NodeId, enabled: bool) -> bool {
+        let Some(session) = self.sessions.get(session_id) else {
+            return false;
+        };
+        let secure_channel_id = trace_read_lock!(session).secure_channel_id();
+        let Some(trace) = self.channel_traces.get(&secure_channel_id) else {
+            return false;
+        };
+        trace.store(enabled, Ordering::Relaxed);
+        true
+    }
+
+    /// Close and remove all sessions, called when the server shuts down.
+    pub(crate) fn close_all_sessions(&mut self) {
+        for (id, session) in self.sessions.drain() {
+            info!("Server is shutting down, closing session {id}");
+            let mut session = trace_write_lock!(session);
+            self.info
+                .diagnostics
+                .remove_session(session.session_id_numeric());
+            session.close();
+        }
+        self.info.diagnostics.set_current_session_count(0);
+    }
+
     pub(crate) fn check_session_expiry(&self) -> (Instant, Vec<NodeId>) {
         let now = Instant::now();
         let mut expired = Vec::new();
         let mut expiry = now + Duration::from_millis(self.info.config.max_session_timeout_ms);
+        // Expired browse continuation points are also released here, so that they
+        // are not kept around until the client uses them again.
+        let cp_timeout = self.info.config.limits.browse_continuation_point_timeout_ms;
+        if cp_timeout > 0 {
+            expiry = expiry.min(now + Duration::from_millis(cp_timeout));
+        }
         for (id, session) in &self.sessions {
             let deadline = session.read().deadline();
             if deadline < now {
                 expired.push(id.clone());
-            } else if deadline < expiry {
-                expiry = deadline;
+            } else {
+                if cp_timeout > 0 {
+                    trace_write_lock!(session).release_expired_browse_continuation_points();
+                }
+                if deadline < expiry {
+                    expiry = deadline;
+                }
             }
         }
 
@@ -283,6 +395,7 @@ pub(crate) async fn close_session(
         mgr.info
             .diagnostics
             .set_current_session_count(mgr.sessions.len() as u32);
+        mgr.info.diagnostics.remove_session(id);
         (session, id, token)
     };
 
@@ -379,6 +492,7 @@ pub(crate) async fn activate_session(
             request.locale_ids.clone(),
             user_token.clone(),
         );
+        info.diagnostics.set_session_identity(&session);
         (
             session.session_nonce().clone(),
             session.session_id_numeric(),
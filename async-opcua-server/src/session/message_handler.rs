@@ -1,10 +1,11 @@
 use std::{sync::Arc, time::Instant};
 
 use chrono::Utc;
-use opcua_core::{Message, RequestMessage, ResponseMessage};
+use opcua_core::{task::spawn_named, Message, RequestMessage, ResponseMessage};
 use parking_lot::RwLock;
 use tokio::task::JoinHandle;
-use tracing::{debug, warn};
+use tracing::{warn, Span};
+use tracing_futures::Instrument;
 
 use crate::{
     authenticator::UserToken,
@@ -15,7 +16,7 @@ use crate::{
 };
 use opcua_types::{
     NamespaceMap, PublishRequest, ResponseHeader, ServiceFault, SetTriggeringRequest,
-    SetTriggeringResponse, StatusCode,
+    SetTriggeringResponse, StatusCode, UAString,
 };
 
 use super::{controller::Response, instance::Session};
@@ -81,6 +82,7 @@ pub(super) struct Request<T> {
     pub token: UserToken,
     pub subscriptions: Arc<SubscriptionCache>,
     pub session_id: u32,
+    pub locale_ids: Option<Vec<UAString>>,
 }
 
 /// Convenient macro for creating a response containing a service fault.
@@ -105,6 +107,7 @@ impl<T> Request<T> {
         token: UserToken,
         subscriptions: Arc<SubscriptionCache>,
         session_id: u32,
+        locale_ids: Option<Vec<UAString>>,
     ) -> Self {
         Self {
             request,
@@ -115,6 +118,7 @@ impl<T> Request<T> {
             token,
             subscriptions,
             session_id,
+            locale_ids,
         }
     }
 
@@ -130,6 +134,7 @@ impl<T> Request<T> {
             subscriptions: self.subscriptions.clone(),
             session_id: self.session_id,
             info: self.info.clone(),
+            request_locale_ids: self.locale_ids.clone(),
         }
     }
 }
@@ -137,19 +142,29 @@ impl<T> Request<T> {
 /// Macro for calling a service asynchronously.
 macro_rules! async_service_call {
     ($m:path, $slf:ident, $req:ident, $r:ident) => {
-        HandleMessageResult::AsyncMessage(tokio::task::spawn($m(
-            $slf.node_managers.clone(),
-            Request::new(
-                $req,
-                $slf.info.clone(),
-                $r.request_id,
-                $r.request_handle,
-                $r.session,
-                $r.token,
-                $slf.subscriptions.clone(),
-                $r.session_id,
-            ),
-        )))
+        HandleMessageResult::AsyncMessage(spawn_named(
+            $slf.info
+                .config
+                .named_tasks
+                .then(|| format!("opcua-server-{} session {}", stringify!($m), $r.session_id)),
+            $m(
+                $slf.node_managers.clone(),
+                Request::new(
+                    $req,
+                    $slf.info.clone(),
+                    $r.request_id,
+                    $r.request_handle,
+                    $r.session,
+                    $r.token,
+                    $slf.subscriptions.clone(),
+                    $r.session_id,
+                    $r.locale_ids,
+                ),
+            )
+            // Spawned tasks do not inherit the current span, so attach the
+            // request span explicitly.
+            .instrument(Span::current()),
+        ))
     };
 }
 
@@ -159,6 +174,7 @@ struct RequestData {
     session: Arc<RwLock<Session>>,
     token: UserToken,
     session_id: u32,
+    locale_ids: Option<Vec<UAString>>,
 }
 
 impl MessageHandler {
@@ -193,6 +209,7 @@ impl MessageHandler {
             session,
             token,
             session_id,
+            locale_ids: message.request_header().locale_ids(),
         };
         // Session management requests are not handled here.
         match message {
@@ -337,9 +354,9 @@ impl MessageHandler {
             }
 
             message => {
-                debug!(
-                    "Message handler does not handle this kind of message {:?}",
-                    message
+                warn!(
+                    "Message handler does not handle service {}, returning BadServiceUnsupported",
+                    message.type_name()
                 );
                 HandleMessageResult::SyncMessage(Response {
                     message: ServiceFault::new(
@@ -375,6 +392,7 @@ impl MessageHandler {
             subscriptions: self.subscriptions.clone(),
             info: self.info.clone(),
             type_tree_getter: self.info.type_tree_getter.clone(),
+            request_locale_ids: None,
         };
 
         // Ignore the result
@@ -406,6 +424,7 @@ impl MessageHandler {
             subscriptions: self.subscriptions.clone(),
             session_id,
             info: self.info.clone(),
+            request_locale_ids: None,
         };
         get_namespaces_for_user(&ctx, &self.node_managers)
     }
@@ -449,6 +468,7 @@ impl MessageHandler {
             dt.token,
             self.subscriptions.clone(),
             dt.session_id,
+            dt.locale_ids,
         )
     }
 
@@ -1,7 +1,11 @@
+#[cfg(feature = "generated-address-space")]
+mod audit;
 pub(crate) mod continuation_points;
 pub(crate) mod controller;
 pub(crate) mod instance;
 pub(crate) mod manager;
 #[macro_use]
 pub(crate) mod message_handler;
+mod rate_limit;
+pub(crate) mod registered_nodes;
 mod services;
@@ -0,0 +1,127 @@
+use std::{
+    collections::HashMap,
+    hash::Hash,
+    net::IpAddr,
+    time::{Duration, Instant},
+};
+
+use parking_lot::Mutex;
+
+use crate::config::RateLimits;
+
+/// Counter of events per key, limiting the number of events for each key
+/// in fixed windows of time.
+struct FixedWindow<K> {
+    max: usize,
+    windows: HashMap<K, (Instant, usize)>,
+    last_prune: Instant,
+}
+
+impl<K: Hash + Eq> FixedWindow<K> {
+    fn new(max: usize) -> Self {
+        Self {
+            max,
+            windows: HashMap::new(),
+            last_prune: Instant::now(),
+        }
+    }
+
+    /// Record an event for `key` at `now`, returning `false` if the limit
+    /// has already been reached in the current window of `key`.
+    fn check(&mut self, key: K, now: Instant, interval: Duration) -> bool {
+        if self.max == 0 {
+            return true;
+        }
+        // Remove keys that haven't seen an event for a whole interval,
+        // so that the map doesn't grow with every client we've ever seen.
+        if now.saturating_duration_since(self.last_prune) >= interval {
+            self.windows
+                .retain(|_, (start, _)| now.saturating_duration_since(*start) < interval);
+            self.last_prune = now;
+        }
+
+        let (start, count) = self.windows.entry(key).or_insert((now, 0));
+        if now.saturating_duration_since(*start) >= interval {
+            *start = now;
+            *count = 0;
+        }
+        if *count >= self.max {
+            return false;
+        }
+        *count += 1;
+        true
+    }
+}
+
+/// Rate limiter for incoming connections per IP address and requests per session.
+pub(crate) struct RateLimiter {
+    interval: Duration,
+    connections: Mutex<FixedWindow<IpAddr>>,
+    requests: Mutex<FixedWindow<u32>>,
+}
+
+impl RateLimiter {
+    pub(crate) fn new(limits: &RateLimits) -> Self {
+        Self {
+            interval: Duration::from_millis(limits.interval_ms),
+            connections: Mutex::new(FixedWindow::new(limits.max_connections_per_ip)),
+            requests: Mutex::new(FixedWindow::new(limits.max_requests_per_session)),
+        }
+    }
+
+    /// Record a new connection from `addr`, returning `false` if it should be rejected.
+    pub(crate) fn allow_connection(&self, addr: IpAddr) -> bool {
+        self.connections
+            .lock()
+            .check(addr, Instant::now(), self.interval)
+    }
+
+    /// Record a service request on the session with numeric ID `session_id`,
+    /// returning `false` if it should be rejected.
+    pub(crate) fn allow_request(&self, session_id: u32) -> bool {
+        self.requests
+            .lock()
+            .check(session_id, Instant::now(), self.interval)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::FixedWindow;
+
+    #[test]
+    fn fixed_window_limits_per_key() {
+        let interval = Duration::from_millis(100);
+        let start = Instant::now();
+        let mut window = FixedWindow::new(3);
+
+        for _ in 0..3 {
+            assert!(window.check(1, start, interval));
+        }
+        assert!(!window.check(1, start + Duration::from_millis(50), interval));
+        // Other keys have their own limit.
+        assert!(window.check(2, start + Duration::from_millis(50), interval));
+
+        // The limit resets once the interval has passed.
+        assert!(window.check(1, start + interval, interval));
+        assert!(window.check(1, start + interval, interval));
+        assert!(window.check(1, start + interval, interval));
+        assert!(!window.check(1, start + interval, interval));
+
+        // Keys without events in the last interval are removed.
+        assert!(window.check(1, start + interval * 3, interval));
+        assert_eq!(window.windows.len(), 1);
+    }
+
+    #[test]
+    fn fixed_window_no_limit() {
+        let mut window = FixedWindow::new(0);
+        let now = Instant::now();
+        for _ in 0..1000 {
+            assert!(window.check(1, now, Duration::from_millis(100)));
+        }
+        assert!(window.windows.is_empty());
+    }
+}
@@ -0,0 +1,79 @@
+use std::collections::HashMap;
+
+use opcua_types::{Guid, Identifier, NodeId};
+
+/// Handles given out by the `RegisterNodes` service on a single session.
+///
+/// Handles are random GUID node IDs in the namespace of the registered node, so they
+/// cannot be guessed or used from other sessions, and resolving one is a single map lookup.
+/// The server resolves them before calling node managers, so node managers only ever
+/// see the original node IDs.
+pub(crate) struct RegisteredNodes {
+    nodes: HashMap<Guid, NodeId>,
+    max_nodes: usize,
+}
+
+impl RegisteredNodes {
+    /// Create an empty set of handles, holding at most `max_nodes` handles.
+    pub(crate) fn new(max_nodes: usize) -> Self {
+        Self {
+            nodes: HashMap::new(),
+            max_nodes,
+        }
+    }
+
+    /// Register `node_id`, returning the handle that should be returned to the client,
+    /// or `None` if the session already holds the maximum number of handles.
+    pub(crate) fn register(&mut self, node_id: &NodeId) -> Option<NodeId> {
+        if self.nodes.len() >= self.max_nodes {
+            return None;
+        }
+        let handle = Guid::new();
+        self.nodes.insert(handle.clone(), node_id.clone());
+        Some(NodeId::new(node_id.namespace, handle))
+    }
+
+    /// Get the number of handles that can still be registered.
+    pub(crate) fn remaining(&self) -> usize {
+        self.max_nodes.saturating_sub(self.nodes.len())
+    }
+
+    /// Get the node ID registered under the handle `node_id`, or `None` if `node_id`
+    /// is not a registered handle.
+    pub(crate) fn resolve(&self, node_id: &NodeId) -> Option<&NodeId> {
+        let Identifier::Guid(handle) = &node_id.identifier else {
+            return None;
+        };
+        self.nodes
+            .get(handle)
+            .filter(|n| n.namespace == node_id.namespace)
+    }
+
+    /// Replace `node_id` with the node ID it was registered for, if it is a handle.
+    pub(crate) fn resolve_in_place(&self, node_id: &mut NodeId) {
+        if let Some(resolved) = self.resolve(node_id) {
+            *node_id = resolved.clone();
+        }
+    }
+
+    /// Remove the handle `node_id`, returning the node ID it was registered for.
+    pub(crate) fn unregister(&mut self, node_id: &NodeId) -> Option<NodeId> {
+        let Identifier::Guid(handle) = &node_id.identifier else {
+            return None;
+        };
+        if self.nodes.get(handle)?.namespace != node_id.namespace {
+            return None;
+        }
+        self.nodes.remove(handle)
+    }
+
+    /// Whether there are no registered nodes.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Remove all registered nodes.
+    pub(crate) fn clear(&mut self) {
+        self.nodes.clear();
+    }
+}
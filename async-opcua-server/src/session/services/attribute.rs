@@ -1,25 +1,33 @@
+use hashbrown::HashSet;
 use opcua_core::trace_write_lock;
-use tracing::{debug_span, Instrument};
+use tracing::{debug, debug_span, Instrument};
 
+use super::resolve_registered_nodes;
 use crate::{
+    address_space::AccessLevel,
     node_manager::{
-        consume_results, HistoryNode, HistoryReadDetails, HistoryUpdateDetails, HistoryUpdateNode,
-        NodeManagers, ReadNode, WriteNode,
+        consume_results, get_access_levels, HistoryNode, HistoryReadDetails, HistoryUpdateDetails,
+        HistoryUpdateNode, NodeManagerHealth, NodeManagers, ReadNode, RequestContext, WriteNode,
     },
     session::{controller::Response, message_handler::Request},
 };
 use opcua_types::{
-    ByteString, DeleteAtTimeDetails, ExtensionObject, HistoryReadRequest, HistoryReadResponse,
-    HistoryReadResult, HistoryUpdateRequest, HistoryUpdateResponse, NodeId, ObjectId, ReadRequest,
-    ReadResponse, ResponseHeader, StatusCode, TimestampsToReturn, WriteRequest, WriteResponse,
+    AttributeId, ByteString, DeleteAtTimeDetails, ExtensionObject, HistoryReadRequest,
+    HistoryReadResponse, HistoryReadResult, HistoryUpdateRequest, HistoryUpdateResponse, NodeId,
+    ObjectId, ReadRequest, ReadResponse, ResponseHeader, StatusCode, TimestampsToReturn,
+    WriteRequest, WriteResponse,
 };
 pub(crate) async fn read(node_managers: NodeManagers, request: Request<ReadRequest>) -> Response {
     let mut context = request.context();
-    let nodes_to_read = take_service_items!(
+    let mut nodes_to_read = take_service_items!(
         request,
         request.request.nodes_to_read,
         request.info.operational_limits.max_nodes_per_read
     );
+    resolve_registered_nodes(
+        &request.session,
+        nodes_to_read.iter_mut().map(|n| &mut n.node_id),
+    );
     if request.request.max_age < 0.0 {
         return service_fault!(request, StatusCode::BadMaxAgeInvalid);
     }
@@ -32,6 +40,27 @@ pub(crate) async fn read(node_managers: NodeManagers, request: Request<ReadReque
         .map(|n| ReadNode::new(n, request.request.request_header.return_diagnostics))
         .collect();
 
+    if request.info.config.check_access_levels {
+        let mut to_check: Vec<_> = results
+            .iter_mut()
+            .filter(|n| {
+                n.node().attribute_id == AttributeId::Value
+                    && n.status() == StatusCode::BadNodeIdUnknown
+            })
+            .collect();
+        let ids: Vec<_> = to_check.iter().map(|n| n.node().node_id.clone()).collect();
+        let levels = get_access_levels(&context, &node_managers, &ids).await;
+        for (node, levels) in to_check.iter_mut().zip(levels) {
+            if let Err(e) = check_access_level(
+                levels,
+                AccessLevel::CURRENT_READ,
+                StatusCode::BadNotReadable,
+            ) {
+                node.set_error(e);
+            }
+        }
+    }
+
     for (idx, node_manager) in node_managers.into_iter().enumerate() {
         context.current_node_manager_index = idx;
         let mut batch: Vec<_> = results
@@ -46,6 +75,18 @@ pub(crate) async fn read(node_managers: NodeManagers, request: Request<ReadReque
             continue;
         }
 
+        if let NodeManagerHealth::Unhealthy(status) = node_manager.health() {
+            debug!(
+                "Node manager {} is unhealthy, failing read of {} nodes with {status}",
+                node_manager.name(),
+                batch.len()
+            );
+            for node in &mut batch {
+                node.set_error(status);
+            }
+            continue;
+        }
+
         if let Err(e) = node_manager
             .read(
                 &context,
@@ -78,25 +119,114 @@ pub(crate) async fn read(node_managers: NodeManagers, request: Request<ReadReque
 
 pub(crate) async fn write(node_managers: NodeManagers, request: Request<WriteRequest>) -> Response {
     let mut context = request.context();
-    let nodes_to_write = take_service_items!(
+    let mut nodes_to_write = take_service_items!(
         request,
         request.request.nodes_to_write,
         request.info.operational_limits.max_nodes_per_write
     );
+    resolve_registered_nodes(
+        &request.session,
+        nodes_to_write.iter_mut().map(|n| &mut n.node_id),
+    );
 
     let mut results: Vec<_> = nodes_to_write
         .into_iter()
         .map(|n| WriteNode::new(n, request.request.request_header.return_diagnostics))
         .collect();
 
+    // A write to the value of a node that follows a write to the access levels of the
+    // same node must be checked against the new access levels, so these are
+    // dispatched after the other writes.
+    let mut deferred = vec![false; results.len()];
+    if request.info.config.check_access_levels {
+        let mut access_level_writes = HashSet::new();
+        for (node, deferred) in results.iter().zip(deferred.iter_mut()) {
+            match node.value().attribute_id {
+                AttributeId::AccessLevel | AttributeId::UserAccessLevel => {
+                    access_level_writes.insert(node.value().node_id.clone());
+                }
+                AttributeId::Value => {
+                    *deferred = access_level_writes.contains(&node.value().node_id);
+                }
+                _ => (),
+            }
+        }
+    }
+
+    let mut first = Vec::with_capacity(results.len());
+    let mut second = Vec::new();
+    for (node, deferred) in results.iter_mut().zip(deferred) {
+        if deferred {
+            second.push(node);
+        } else {
+            first.push(node);
+        }
+    }
+    for nodes in [&mut first, &mut second] {
+        if nodes.is_empty() {
+            continue;
+        }
+        if request.info.config.check_access_levels {
+            check_write_access_levels(&context, &node_managers, nodes).await;
+        }
+        dispatch_writes(&mut context, &node_managers, nodes).await;
+    }
+
+    let (results, diagnostic_infos) =
+        consume_results(results, request.request.request_header.return_diagnostics);
+
+    Response {
+        message: WriteResponse {
+            response_header: ResponseHeader::new_good(request.request_handle),
+            results,
+            diagnostic_infos,
+        }
+        .into(),
+        request_id: request.request_id,
+    }
+}
+
+/// Fail writes to the values of variables whose access levels do not allow them.
+async fn check_write_access_levels(
+    context: &RequestContext,
+    node_managers: &NodeManagers,
+    nodes: &mut [&mut WriteNode],
+) {
+    let mut to_check: Vec<_> = nodes
+        .iter_mut()
+        .filter(|n| {
+            n.value().attribute_id == AttributeId::Value
+                && n.status() == StatusCode::BadNodeIdUnknown
+        })
+        .collect();
+    let ids: Vec<_> = to_check.iter().map(|n| n.value().node_id.clone()).collect();
+    let levels = get_access_levels(context, node_managers, &ids).await;
+    for (node, levels) in to_check.iter_mut().zip(levels) {
+        if let Err(e) = check_access_level(
+            levels,
+            AccessLevel::CURRENT_WRITE,
+            StatusCode::BadNotWritable,
+        ) {
+            node.set_status(e);
+        }
+    }
+}
+
+/// Pass each write to the node manager that owns the node.
+async fn dispatch_writes(
+    context: &mut RequestContext,
+    node_managers: &NodeManagers,
+    nodes: &mut [&mut WriteNode],
+) {
     for (idx, node_manager) in node_managers.into_iter().enumerate() {
         context.current_node_manager_index = idx;
-        let mut batch: Vec<_> = results
+        let mut batch: Vec<_> = nodes
             .iter_mut()
             .filter(|n| {
                 node_manager.owns_node(&n.value().node_id)
                     && n.status() == StatusCode::BadNodeIdUnknown
             })
+            .map(|n| &mut **n)
             .collect();
 
         if batch.is_empty() {
@@ -104,7 +234,7 @@ pub(crate) async fn write(node_managers: NodeManagers, request: Request<WriteReq
         }
 
         if let Err(e) = node_manager
-            .write(&context, &mut batch)
+            .write(context, &mut batch)
             .instrument(debug_span!("Write", node_manager = %node_manager.name()))
             .await
         {
@@ -113,18 +243,25 @@ pub(crate) async fn write(node_managers: NodeManagers, request: Request<WriteReq
             }
         }
     }
+}
 
-    let (results, diagnostic_infos) =
-        consume_results(results, request.request.request_header.return_diagnostics);
-
-    Response {
-        message: WriteResponse {
-            response_header: ResponseHeader::new_good(request.request_handle),
-            results,
-            diagnostic_infos,
-        }
-        .into(),
-        request_id: request.request_id,
+/// Check that the access levels of a node, as returned by `get_access_levels`,
+/// allow the operation given by `required`, failing with `denied` if the node itself
+/// does not allow it. Nodes without access levels are left to the node manager.
+fn check_access_level(
+    levels: Result<Option<(AccessLevel, AccessLevel)>, StatusCode>,
+    required: AccessLevel,
+    denied: StatusCode,
+) -> Result<(), StatusCode> {
+    let Some((access_level, user_access_level)) = levels? else {
+        return Ok(());
+    };
+    if !access_level.contains(required) {
+        Err(denied)
+    } else if !user_access_level.contains(required) {
+        Err(StatusCode::BadUserAccessDenied)
+    } else {
+        Ok(())
     }
 }
 
@@ -133,7 +270,7 @@ pub(crate) async fn history_read(
     request: Request<HistoryReadRequest>,
 ) -> Response {
     let mut context = request.context();
-    let Some(items) = request.request.nodes_to_read else {
+    let Some(mut items) = request.request.nodes_to_read else {
         return service_fault!(request, StatusCode::BadNothingToDo);
     };
     if items.is_empty() {
@@ -164,6 +301,7 @@ pub(crate) async fn history_read(
     {
         return service_fault!(request, StatusCode::BadTooManyOperations);
     }
+    resolve_registered_nodes(&request.session, items.iter_mut().map(|n| &mut n.node_id));
     let mut nodes: Vec<_> = {
         let mut session = trace_write_lock!(request.session);
         items
@@ -329,10 +467,22 @@ pub(crate) async fn history_update(
         request.info.operational_limits.max_nodes_per_history_update
     );
 
-    let mut nodes: Vec<_> = items
+    let mut details: Vec<_> = items
+        .into_iter()
+        .map(HistoryUpdateDetails::from_extension_object)
+        .collect();
+    resolve_registered_nodes(
+        &request.session,
+        details
+            .iter_mut()
+            .filter_map(|d| d.as_mut().ok())
+            .map(|d| d.node_id_mut()),
+    );
+
+    let mut nodes: Vec<_> = details
         .into_iter()
-        .map(|obj| {
-            let details = match HistoryUpdateDetails::from_extension_object(obj) {
+        .map(|details| {
+            let details = match details {
                 Ok(h) => h,
                 Err(e) => {
                     // need some empty history update node here, it won't be passed to node managers.
@@ -1,3 +1,4 @@
+use super::resolve_registered_nodes;
 use crate::{
     node_manager::{consume_results, MethodCall, NodeManagers},
     session::{controller::Response, message_handler::Request},
@@ -8,17 +9,56 @@ use tracing_futures::Instrument;
 
 pub(crate) async fn call(node_managers: NodeManagers, request: Request<CallRequest>) -> Response {
     let mut context = request.context();
-    let method_calls = take_service_items!(
+    let mut method_calls = take_service_items!(
         request,
         request.request.methods_to_call,
         request.info.operational_limits.max_nodes_per_method_call
     );
+    resolve_registered_nodes(
+        &request.session,
+        method_calls
+            .iter_mut()
+            .flat_map(|c| [&mut c.object_id, &mut c.method_id]),
+    );
 
     let mut calls: Vec<_> = method_calls
         .into_iter()
         .map(|c| MethodCall::new(c, request.request.request_header.return_diagnostics))
         .collect();
 
+    // Validate the arguments of each call against the `InputArguments`
+    // of each method. Methods without argument metadata are left to the node manager.
+    if request.info.config.validate_method_arguments {
+        let method_ids: Vec<_> = calls.iter().map(|c| c.method_id().clone()).collect();
+        let method_arguments = context
+            .get_method_arguments(&node_managers, &method_ids)
+            .await;
+        let type_tree = context.get_type_tree_for_user();
+        for (call, arguments) in calls.iter_mut().zip(method_arguments) {
+            if let Some((input_arguments, _)) = arguments {
+                call.validate_arguments(&input_arguments, type_tree.get());
+            }
+        }
+    }
+
+    // Acknowledge and Confirm on retained conditions are handled by the condition manager.
+    // These are called through the method declarations on `AcknowledgeableConditionType`,
+    // which are not executable themselves, so only the user's permissions are checked.
+    let conditions = context.subscriptions.conditions();
+    for call in calls.iter_mut() {
+        if call.status() != StatusCode::BadMethodInvalid || !conditions.handles(call) {
+            continue;
+        }
+        if context
+            .authenticator
+            .is_user_executable(&context.token, call.method_id())
+        {
+            conditions.call(&context, call);
+        } else {
+            call.set_status(StatusCode::BadUserAccessDenied);
+        }
+    }
+
     for (idx, node_manager) in node_managers.into_iter().enumerate() {
         context.current_node_manager_index = idx;
         let mut owned: Vec<_> = calls
@@ -28,3 +28,24 @@ pub(super) use node_management::*;
 pub(super) use query::*;
 pub(super) use subscriptions::*;
 pub(super) use view::*;
+
+use opcua_core::{sync::RwLock, trace_read_lock};
+use opcua_types::NodeId;
+
+use super::instance::Session;
+
+/// Replace any node IDs that are handles from `RegisterNodes` with the node IDs
+/// they were registered for.
+fn resolve_registered_nodes<'a>(
+    session: &RwLock<Session>,
+    node_ids: impl IntoIterator<Item = &'a mut NodeId>,
+) {
+    let session = trace_read_lock!(session);
+    let registered_nodes = session.registered_nodes();
+    if registered_nodes.is_empty() {
+        return;
+    }
+    for node_id in node_ids {
+        registered_nodes.resolve_in_place(node_id);
+    }
+}
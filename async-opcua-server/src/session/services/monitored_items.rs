@@ -17,7 +17,7 @@ use opcua_types::{
 use tracing::debug_span;
 use tracing_futures::Instrument;
 
-use super::{read, translate_browse_paths};
+use super::{read, resolve_registered_nodes, translate_browse_paths};
 
 // OPC-UA is sometimes very painful. In order to actually implement percent-deadband, we need to
 // fetch the EURange property from the node hierarchy. This method does that by calling TranslateBrowsePaths
@@ -60,6 +60,7 @@ async fn get_eu_range(
         token: context.token.clone(),
         subscriptions: context.subscriptions.clone(),
         session_id: context.session_id,
+        locale_ids: context.request_locale_ids.clone(),
     };
     let response = translate_browse_paths(node_managers.clone(), req).await;
     let ResponseMessage::TranslateBrowsePathsToNodeIds(translated) = response.message else {
@@ -109,6 +110,7 @@ async fn get_eu_range(
         token: context.token.clone(),
         subscriptions: context.subscriptions.clone(),
         session_id: context.session_id,
+        locale_ids: context.request_locale_ids.clone(),
     };
     let read_res = read(node_managers.clone(), read_req).await;
     let ResponseMessage::Read(read) = read_res.message else {
@@ -138,16 +140,83 @@ async fn get_eu_range(
     res
 }
 
+// Deadband filters are only allowed on numeric values, so we need the DataType of
+// each node monitored with one.
+async fn get_data_types(
+    items: &[&NodeId],
+    context: &RequestContext,
+    node_managers: &NodeManagers,
+) -> HashMap<NodeId, NodeId> {
+    let mut res = HashMap::with_capacity(items.len());
+    if items.is_empty() {
+        return res;
+    }
+
+    let read_req = Request {
+        request: Box::new(ReadRequest {
+            request_header: RequestHeader::dummy(),
+            max_age: 0.0,
+            timestamps_to_return: TimestampsToReturn::Neither,
+            nodes_to_read: Some(
+                items
+                    .iter()
+                    .map(|i| opcua_types::ReadValueId {
+                        node_id: (*i).clone(),
+                        attribute_id: AttributeId::DataType as u32,
+                        ..Default::default()
+                    })
+                    .collect(),
+            ),
+        }),
+        request_id: 0,
+        request_handle: 0,
+        info: context.info.clone(),
+        session: context.session.clone(),
+        token: context.token.clone(),
+        subscriptions: context.subscriptions.clone(),
+        session_id: context.session_id,
+        locale_ids: context.request_locale_ids.clone(),
+    };
+    let read_res = read(node_managers.clone(), read_req).await;
+    let ResponseMessage::Read(read) = read_res.message else {
+        return res;
+    };
+    if !read.response_header.service_result.is_good() {
+        return res;
+    }
+
+    for (id, dv) in items
+        .iter()
+        .zip(read.results.into_iter().flat_map(|r| r.into_iter()))
+    {
+        if dv.status.is_some_and(|s| !s.is_good()) {
+            continue;
+        }
+        let Some(Variant::NodeId(data_type)) = dv.value else {
+            continue;
+        };
+        res.insert((*id).clone(), *data_type);
+    }
+
+    res
+}
+
 pub(crate) async fn create_monitored_items(
     node_managers: NodeManagers,
     request: Request<CreateMonitoredItemsRequest>,
 ) -> Response {
     let mut context = request.context();
-    let items_to_create = take_service_items!(
+    let mut items_to_create = take_service_items!(
         request,
         request.request.items_to_create,
         request.info.operational_limits.max_monitored_items_per_call
     );
+    resolve_registered_nodes(
+        &request.session,
+        items_to_create
+            .iter_mut()
+            .map(|i| &mut i.item_to_monitor.node_id),
+    );
     let Some(len) = request
         .subscriptions
         .get_monitored_item_count(request.session_id, request.request.subscription_id)
@@ -165,8 +234,10 @@ pub(crate) async fn create_monitored_items(
         return service_fault!(request, StatusCode::BadTooManyMonitoredItems);
     }
 
-    // Try to get EURange for each item with a percent deadband filter.
+    // Try to get EURange for each item with a percent deadband filter, and the
+    // DataType for each item with any deadband filter.
     let mut items_needing_deadband = Vec::new();
+    let mut items_needing_data_type = Vec::new();
     for item in &items_to_create {
         let Some(filter) = item
             .requested_parameters
@@ -179,8 +250,12 @@ pub(crate) async fn create_monitored_items(
         if filter.deadband_type == DeadbandType::Percent as u32 {
             items_needing_deadband.push(&item.item_to_monitor.node_id);
         }
+        if filter.deadband_type != DeadbandType::None as u32 {
+            items_needing_data_type.push(&item.item_to_monitor.node_id);
+        }
     }
     let ranges = get_eu_range(&items_needing_deadband, &context, &node_managers).await;
+    let data_types = get_data_types(&items_needing_data_type, &context, &node_managers).await;
 
     let mut items: Vec<_> = {
         let type_tree = context.get_type_tree_for_user();
@@ -188,7 +263,8 @@ pub(crate) async fn create_monitored_items(
             .into_iter()
             .map(|r| {
                 let range = ranges.get(&r.item_to_monitor.node_id).copied();
-                CreateMonitoredItem::new(
+                let data_type = data_types.get(&r.item_to_monitor.node_id);
+                let mut item = CreateMonitoredItem::new(
                     r,
                     request.info.monitored_item_id_handle.next(),
                     request.request.subscription_id,
@@ -196,11 +272,35 @@ pub(crate) async fn create_monitored_items(
                     request.request.timestamps_to_return,
                     type_tree.get(),
                     range,
-                )
+                );
+                if let Some(data_type) = data_type {
+                    item.validate_deadband(data_type, type_tree.get());
+                }
+                item
             })
             .collect()
     };
 
+    // Reserve capacity for each item up front, so that concurrent requests
+    // cannot exceed the limits together. Items that are not created release
+    // their reservation.
+    let max_total = request
+        .info
+        .config
+        .limits
+        .subscriptions
+        .max_total_monitored_items;
+    let counters = request.subscriptions.monitored_item_counters();
+    for item in items
+        .iter_mut()
+        .filter(|n| n.status_code() == StatusCode::BadNodeIdUnknown)
+    {
+        match counters.reserve(max_total) {
+            Some(reservation) => item.set_reservation(reservation),
+            None => item.set_status(StatusCode::BadTooManyMonitoredItems),
+        }
+    }
+
     for (idx, mgr) in node_managers.iter().enumerate() {
         context.current_node_manager_index = idx;
         let mut owned: Vec<_> = items
@@ -211,6 +311,20 @@ pub(crate) async fn create_monitored_items(
             })
             .collect();
 
+        let limit = request
+            .info
+            .max_monitored_items_for_node_manager(idx)
+            .unwrap_or_default();
+        owned.retain_mut(|n| {
+            let reserved = n
+                .reservation_mut()
+                .is_some_and(|r| r.reserve_node_manager(idx, limit));
+            if !reserved {
+                n.set_status(StatusCode::BadTooManyMonitoredItems);
+            }
+            reserved
+        });
+
         if owned.is_empty() {
             continue;
         }
@@ -241,7 +355,8 @@ pub(crate) async fn create_monitored_items(
     let res = match request.subscriptions.create_monitored_items(
         request.session_id,
         request.request.subscription_id,
-        &items,
+        &mut items,
+        &request.info,
     ) {
         Ok(r) => r,
         // Shouldn't happen, would be due to a race condition. If it does happen we're fine with failing.
@@ -279,6 +394,33 @@ pub(crate) async fn modify_monitored_items(
         request.info.operational_limits.max_monitored_items_per_call
     );
 
+    // Get the DataType of each item that is modified to use a deadband filter.
+    let with_deadband: Vec<_> = items_to_modify
+        .iter()
+        .filter(|i| {
+            i.requested_parameters
+                .filter
+                .inner_as::<DataChangeFilter>()
+                .is_some_and(|f| f.deadband_type != DeadbandType::None as u32)
+        })
+        .map(|i| i.monitored_item_id)
+        .collect();
+    let mut data_types = hashbrown::HashMap::new();
+    if !with_deadband.is_empty() {
+        let nodes = request.subscriptions.get_monitored_value_nodes(
+            request.session_id,
+            request.request.subscription_id,
+            &with_deadband,
+        );
+        let node_ids: Vec<_> = nodes.values().collect();
+        let types = get_data_types(&node_ids, &context, &node_managers).await;
+        for (id, node_id) in &nodes {
+            if let Some(data_type) = types.get(node_id) {
+                data_types.insert(*id, data_type.clone());
+            }
+        }
+    }
+
     // Call modify first, then only pass successful modify's to the node managers.
     let results = {
         let type_tree = context.get_type_tree_for_user();
@@ -289,6 +431,7 @@ pub(crate) async fn modify_monitored_items(
             &request.info,
             request.request.timestamps_to_return,
             items_to_modify,
+            &data_types,
             type_tree.get(),
         ) {
             Ok(r) => r,
@@ -387,6 +530,7 @@ pub(crate) async fn delete_monitored_items(
         request.session_id,
         request.request.subscription_id,
         &items,
+        &request.info,
     ) {
         Ok(r) => r,
         Err(e) => return service_fault!(request, e),
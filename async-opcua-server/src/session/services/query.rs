@@ -96,35 +96,46 @@ pub(crate) async fn query_first(
         max_references_to_return,
     );
 
+    let mut supported = false;
     for (index, node_manager) in node_managers.iter().enumerate() {
         context.current_node_manager_index = index;
-        // All node managers must succeed. Partial success is really
+        // All node managers that support query must succeed. Partial success is really
         // hard to quantify for query...
-        // TODO: This is pretty much impossible to implement
-        // until we actually implement this in the core node manager.
-        if let Err(e) = node_manager
+        match node_manager
             .query(&context, &mut query_request)
             .instrument(debug_span!("Query", node_manager = %node_manager.name()))
             .await
         {
-            return Response {
-                message: QueryFirstResponse {
-                    response_header: ResponseHeader::new_service_result(request.request_handle, e),
-                    query_data_sets: None,
-                    continuation_point: ByteString::null(),
-                    parsing_results: Some(parsing_results),
-                    filter_result,
-                    diagnostic_infos: None,
+            Ok(()) => supported = true,
+            // Node managers that do not support query have no matching nodes.
+            Err(StatusCode::BadNotSupported) => (),
+            Err(e) => {
+                return Response {
+                    message: QueryFirstResponse {
+                        response_header: ResponseHeader::new_service_result(
+                            request.request_handle,
+                            e,
+                        ),
+                        query_data_sets: None,
+                        continuation_point: ByteString::null(),
+                        parsing_results: Some(parsing_results),
+                        filter_result,
+                        diagnostic_infos: None,
+                    }
+                    .into(),
+                    request_id: request.request_id,
                 }
-                .into(),
-                request_id: request.request_id,
-            };
+            }
         }
 
         if query_request.is_completed() {
             break;
         }
     }
+    if !supported {
+        return service_fault!(request, StatusCode::BadServiceUnsupported);
+    }
+
     let (result, continuation_point, status) = {
         let mut session = trace_write_lock!(request.session);
         query_request.into_result(
@@ -180,12 +191,13 @@ pub(crate) async fn query_next(
         }
         context.current_node_manager_index = index;
 
-        if let Err(e) = node_manager
+        match node_manager
             .query(&context, &mut query_request)
             .instrument(debug_span!("QueryNext", node_manager = %node_manager.name()))
             .await
         {
-            return service_fault!(request, e);
+            Ok(()) | Err(StatusCode::BadNotSupported) => (),
+            Err(e) => return service_fault!(request, e),
         }
 
         if query_request.is_completed() {
@@ -1,9 +1,10 @@
 use std::collections::HashMap;
 
-use opcua_core::trace_write_lock;
-use tracing::{debug_span, error, info};
+use opcua_core::{trace_read_lock, trace_write_lock};
+use tracing::{debug, debug_span, error, info};
 use tracing_futures::Instrument;
 
+use super::resolve_registered_nodes;
 use crate::{
     node_manager::{
         resolve_external_references, BrowseNode, BrowsePathItem, ExternalReferencesContPoint,
@@ -23,11 +24,15 @@ pub(crate) async fn browse(
     request: Request<BrowseRequest>,
 ) -> Response {
     let mut context: RequestContext = request.context();
-    let nodes_to_browse = take_service_items!(
+    let mut nodes_to_browse = take_service_items!(
         request,
         request.request.nodes_to_browse,
         request.info.operational_limits.max_nodes_per_browse
     );
+    resolve_registered_nodes(
+        &request.session,
+        nodes_to_browse.iter_mut().map(|n| &mut n.node_id),
+    );
     if !request.request.view.view_id.is_null() || !request.request.view.timestamp.is_null() {
         info!("Browse request ignored because view was specified (views not supported)");
         return service_fault!(request, StatusCode::BadViewIdUnknown);
@@ -347,7 +352,7 @@ pub(crate) async fn translate_browse_paths(
     //   returned node, the service is finished and we can collect all the node IDs in the bottom layer.
 
     let mut context = request.context();
-    let paths = take_service_items!(
+    let mut paths = take_service_items!(
         request,
         request.request.browse_paths,
         request
@@ -355,6 +360,10 @@ pub(crate) async fn translate_browse_paths(
             .operational_limits
             .max_nodes_per_translate_browse_paths_to_node_ids
     );
+    resolve_registered_nodes(
+        &request.session,
+        paths.iter_mut().map(|p| &mut p.starting_node),
+    );
 
     let mut items: Vec<_> = paths
         .iter()
@@ -492,6 +501,20 @@ pub(crate) async fn register_nodes(
         return service_fault!(request, StatusCode::BadTooManyOperations);
     }
 
+    // Handles live until they are unregistered or the session is closed, so the
+    // number of them on each session is limited.
+    let remaining = trace_read_lock!(request.session)
+        .registered_nodes()
+        .remaining();
+    if nodes_to_register.len() > remaining {
+        debug!(
+            "Rejecting RegisterNodes with {} nodes, the session can only register {} more",
+            nodes_to_register.len(),
+            remaining
+        );
+        return service_fault!(request, StatusCode::BadTooManyOperations);
+    }
+
     let mut items: Vec<_> = nodes_to_register
         .into_iter()
         .map(RegisterNodeItem::new)
@@ -518,7 +541,14 @@ pub(crate) async fn register_nodes(
         }
     }
 
-    let registered_node_ids: Vec<_> = items.into_iter().filter_map(|n| n.into_result()).collect();
+    let registered_node_ids: Vec<_> = {
+        let mut session = trace_write_lock!(request.session);
+        let registered_nodes = session.registered_nodes_mut();
+        items
+            .into_iter()
+            .map(|n| n.into_result(registered_nodes))
+            .collect()
+    };
 
     Response {
         message: RegisterNodesResponse {
@@ -536,7 +566,7 @@ pub(crate) async fn unregister_nodes(
 ) -> Response {
     let context = request.context();
 
-    let Some(nodes_to_unregister) = request.request.nodes_to_unregister else {
+    let Some(mut nodes_to_unregister) = request.request.nodes_to_unregister else {
         return service_fault!(request, StatusCode::BadNothingToDo);
     };
 
@@ -548,6 +578,17 @@ pub(crate) async fn unregister_nodes(
         return service_fault!(request, StatusCode::BadTooManyOperations);
     }
 
+    // Handles are released here, node managers are called with the original node IDs.
+    {
+        let mut session = trace_write_lock!(request.session);
+        let registered_nodes = session.registered_nodes_mut();
+        for node_id in &mut nodes_to_unregister {
+            if let Some(original) = registered_nodes.unregister(node_id) {
+                *node_id = original;
+            }
+        }
+    }
+
     for mgr in &node_managers {
         let owned: Vec<_> = nodes_to_unregister
             .iter()
@@ -0,0 +1,180 @@
+use std::sync::Arc;
+
+use hashbrown::HashMap;
+use opcua_core::{sync::RwLock, trace_read_lock, trace_write_lock};
+use opcua_nodes::Event;
+use opcua_types::{
+    AttributeId, ByteString, LocalizedText, MethodId, NodeId, NumericRange, ObjectTypeId,
+    QualifiedName, StatusCode, Variant, VariantScalarTypeId, VariantTypeId,
+};
+
+use crate::{
+    load_method_args,
+    node_manager::{MethodCall, RequestContext},
+};
+
+/// A method called on a retained condition, see [`ConditionManager::set_method_callback`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConditionMethod {
+    /// The `Acknowledge` method of `AcknowledgeableConditionType`.
+    Acknowledge,
+    /// The `Confirm` method of `AcknowledgeableConditionType`.
+    Confirm,
+}
+
+type ConditionMethodCallback = Arc<
+    dyn Fn(&RequestContext, ConditionMethod, &NodeId, &LocalizedText) -> StatusCode
+        + Send
+        + Sync
+        + 'static,
+>;
+
+pub(super) struct RetainedCondition {
+    pub(super) event: Box<dyn Event + Send + Sync>,
+    pub(super) notifier: NodeId,
+}
+
+impl RetainedCondition {
+    fn field(&self, name: &str) -> Variant {
+        self.event.get_field(
+            &ObjectTypeId::BaseEventType.into(),
+            AttributeId::Value,
+            &NumericRange::None,
+            &[QualifiedName::new(0, name)],
+        )
+    }
+}
+
+/// Keeps track of retained conditions, i.e. condition events with `Retain` set to `true`,
+/// which are replayed to subscriptions calling `ConditionRefresh`.
+///
+/// Conditions are reported using
+/// [`SubscriptionCache::notify_condition`](super::SubscriptionCache::notify_condition).
+/// Calls to `Acknowledge` and `Confirm` on retained conditions are passed to the callback
+/// set with [`ConditionManager::set_method_callback`].
+#[derive(Default)]
+pub struct ConditionManager {
+    retained: RwLock<HashMap<NodeId, RetainedCondition>>,
+    method_callback: RwLock<Option<ConditionMethodCallback>>,
+}
+
+impl ConditionManager {
+    /// Set the callback called when a client calls `Acknowledge` or `Confirm` on a
+    /// retained condition. The callback is given the condition ID and the comment passed
+    /// by the client, and should update the state of the condition, typically by
+    /// reporting a new condition event.
+    ///
+    /// The `EventId` argument is checked against the last event reported for the
+    /// condition before the callback is called.
+    pub fn set_method_callback(
+        &self,
+        cb: impl Fn(&RequestContext, ConditionMethod, &NodeId, &LocalizedText) -> StatusCode
+            + Send
+            + Sync
+            + 'static,
+    ) {
+        *trace_write_lock!(self.method_callback) = Some(Arc::new(cb));
+    }
+
+    /// Return `true` if the condition given by `condition_id` is currently retained.
+    pub fn is_retained(&self, condition_id: &NodeId) -> bool {
+        trace_read_lock!(self.retained).contains_key(condition_id)
+    }
+
+    /// Get the number of retained conditions.
+    pub fn retained_count(&self) -> usize {
+        trace_read_lock!(self.retained).len()
+    }
+
+    /// Stop retaining the condition given by `condition_id`, without reporting an event.
+    /// Returns `true` if the condition was retained.
+    pub fn remove(&self, condition_id: &NodeId) -> bool {
+        trace_write_lock!(self.retained)
+            .remove(condition_id)
+            .is_some()
+    }
+
+    /// Store or remove the condition, depending on the `Retain` field of `event`.
+    pub(super) fn update(
+        &self,
+        condition_id: &NodeId,
+        event: Box<dyn Event + Send + Sync>,
+        notifier: &NodeId,
+    ) {
+        let condition = RetainedCondition {
+            event,
+            notifier: notifier.clone(),
+        };
+        let retain = condition.event.get_field(
+            &ObjectTypeId::ConditionType.into(),
+            AttributeId::Value,
+            &NumericRange::None,
+            &[QualifiedName::new(0, "Retain")],
+        );
+        let mut retained = trace_write_lock!(self.retained);
+        if retain == Variant::Boolean(true) {
+            retained.insert(condition_id.clone(), condition);
+        } else {
+            retained.remove(condition_id);
+        }
+    }
+
+    pub(super) fn retained(
+        &self,
+    ) -> impl std::ops::Deref<Target = HashMap<NodeId, RetainedCondition>> + '_ {
+        trace_read_lock!(self.retained)
+    }
+
+    /// Get whether `call` is a call to `Acknowledge` or `Confirm` on a retained condition,
+    /// which should be handled by [`ConditionManager::call`].
+    pub(crate) fn handles(&self, call: &MethodCall) -> bool {
+        Self::condition_method(call).is_some()
+            && trace_read_lock!(self.method_callback).is_some()
+            && trace_read_lock!(self.retained).contains_key(call.object_id())
+    }
+
+    fn condition_method(call: &MethodCall) -> Option<ConditionMethod> {
+        match call.method_id().as_method_id() {
+            Ok(MethodId::AcknowledgeableConditionType_Acknowledge) => {
+                Some(ConditionMethod::Acknowledge)
+            }
+            Ok(MethodId::AcknowledgeableConditionType_Confirm) => Some(ConditionMethod::Confirm),
+            _ => None,
+        }
+    }
+
+    /// Handle a call to `Acknowledge` or `Confirm` on a retained condition.
+    /// Calls on anything else are left for the node managers.
+    ///
+    /// The caller is responsible for checking that the method may be executed.
+    pub(crate) fn call(&self, context: &RequestContext, call: &mut MethodCall) {
+        let Some(method) = Self::condition_method(call) else {
+            return;
+        };
+        let Some(cb) = trace_read_lock!(self.method_callback).clone() else {
+            return;
+        };
+        let last_event_id = {
+            let retained = trace_read_lock!(self.retained);
+            let Some(condition) = retained.get(call.object_id()) else {
+                return;
+            };
+            condition.field("EventId")
+        };
+
+        let (event_id, comment): (ByteString, Box<LocalizedText>) =
+            match load_method_args!(call, ByteString, LocalizedText) {
+                Ok(r) => r,
+                Err(e) => {
+                    call.set_status(e);
+                    return;
+                }
+            };
+        if last_event_id != Variant::ByteString(event_id) {
+            call.set_status(StatusCode::BadEventIdUnknown);
+            return;
+        }
+        let status = cb(context, method, call.object_id(), &comment);
+        call.set_status(status);
+    }
+}
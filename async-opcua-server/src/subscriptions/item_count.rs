@@ -0,0 +1,149 @@
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+/// Number of monitored items on the server, in total and for each node manager.
+///
+/// Capacity for a monitored item is reserved before it is created, using a
+/// compare-and-swap on the counters, so concurrent requests cannot exceed
+/// the limits together.
+#[derive(Debug)]
+pub(crate) struct MonitoredItemCounters {
+    total: AtomicUsize,
+    node_managers: Vec<AtomicUsize>,
+}
+
+/// Increment `counter` if it is below `limit`, or unconditionally if `limit` is zero.
+fn try_increment(counter: &AtomicUsize, limit: usize) -> bool {
+    counter
+        .fetch_update(Ordering::AcqRel, Ordering::Acquire, |count| {
+            (limit == 0 || count < limit).then_some(count + 1)
+        })
+        .is_ok()
+}
+
+impl MonitoredItemCounters {
+    pub(crate) fn new(node_manager_count: usize) -> Self {
+        Self {
+            total: AtomicUsize::new(0),
+            node_managers: (0..node_manager_count)
+                .map(|_| AtomicUsize::new(0))
+                .collect(),
+        }
+    }
+
+    /// Current number of monitored items on the server.
+    pub(crate) fn total(&self) -> usize {
+        self.total.load(Ordering::Acquire)
+    }
+
+    /// Current number of monitored items on nodes owned by the node manager at `index`.
+    pub(crate) fn node_manager(&self, index: usize) -> usize {
+        self.node_managers
+            .get(index)
+            .map(|c| c.load(Ordering::Acquire))
+            .unwrap_or_default()
+    }
+
+    /// Reserve capacity for a monitored item on the server, or return `None` if the
+    /// server already has `max_total` monitored items. A limit of zero means no limit.
+    pub(crate) fn reserve(self: &Arc<Self>, max_total: usize) -> Option<MonitoredItemReservation> {
+        try_increment(&self.total, max_total).then(|| MonitoredItemReservation {
+            counters: self.clone(),
+            node_manager: None,
+        })
+    }
+}
+
+/// Capacity reserved for a single monitored item. The capacity is released
+/// when this is dropped, together with the monitored item holding it.
+#[derive(Debug)]
+pub(crate) struct MonitoredItemReservation {
+    counters: Arc<MonitoredItemCounters>,
+    node_manager: Option<usize>,
+}
+
+impl MonitoredItemReservation {
+    /// Reserve capacity for the monitored item on the node manager at `index` as well,
+    /// returning `false` if the node manager already has `limit` monitored items.
+    pub(crate) fn reserve_node_manager(&mut self, index: usize, limit: usize) -> bool {
+        debug_assert!(self.node_manager.is_none());
+        let Some(counter) = self.counters.node_managers.get(index) else {
+            return true;
+        };
+        if try_increment(counter, limit) {
+            self.node_manager = Some(index);
+            true
+        } else {
+            false
+        }
+    }
+}
+
+impl Drop for MonitoredItemReservation {
+    fn drop(&mut self) {
+        self.counters.total.fetch_sub(1, Ordering::AcqRel);
+        if let Some(counter) = self
+            .node_manager
+            .and_then(|idx| self.counters.node_managers.get(idx))
+        {
+            counter.fetch_sub(1, Ordering::AcqRel);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::MonitoredItemCounters;
+
+    #[test]
+    fn monitored_item_reservations() {
+        let counters = Arc::new(MonitoredItemCounters::new(2));
+        let mut a = counters.reserve(2).unwrap();
+        assert!(a.reserve_node_manager(0, 1));
+        let mut b = counters.reserve(2).unwrap();
+        // Node manager limit reached.
+        assert!(!b.reserve_node_manager(0, 1));
+        assert!(b.reserve_node_manager(1, 0));
+        // Total limit reached.
+        assert!(counters.reserve(2).is_none());
+        assert_eq!(counters.total(), 2);
+        assert_eq!(counters.node_manager(0), 1);
+        assert_eq!(counters.node_manager(1), 1);
+
+        drop(a);
+        assert_eq!(counters.total(), 1);
+        assert_eq!(counters.node_manager(0), 0);
+        drop(b);
+        assert_eq!(counters.total(), 0);
+        assert_eq!(counters.node_manager(1), 0);
+    }
+
+    #[test]
+    fn concurrent_reservations() {
+        let counters = Arc::new(MonitoredItemCounters::new(1));
+        let threads: Vec<_> = (0..8)
+            .map(|_| {
+                let counters = counters.clone();
+                std::thread::spawn(move || {
+                    (0..100)
+                        .filter_map(|_| {
+                            let mut r = counters.reserve(0)?;
+                            r.reserve_node_manager(0, 250).then_some(r)
+                        })
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        let reserved: Vec<_> = threads
+            .into_iter()
+            .flat_map(|t| t.join().unwrap())
+            .collect();
+        assert_eq!(reserved.len(), 250);
+        assert_eq!(counters.node_manager(0), 250);
+        assert_eq!(counters.total(), 250);
+    }
+}
@@ -1,29 +1,41 @@
+mod conditions;
+mod item_count;
 mod monitored_item;
+mod scheduler;
 mod session_subscriptions;
 mod subscription;
 
-use std::{sync::Arc, time::Instant};
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Instant,
+};
 
 use chrono::Utc;
+pub use conditions::{ConditionManager, ConditionMethod};
 use hashbrown::{Equivalent, HashMap};
-pub use monitored_item::{CreateMonitoredItem, MonitoredItem};
+pub(crate) use item_count::MonitoredItemCounters;
+pub use monitored_item::{CreateMonitoredItem, MonitoredItem, MonitoredItemMetrics};
 use opcua_core::{trace_read_lock, trace_write_lock, ResponseMessage};
-use opcua_nodes::{Event, TypeTree};
+use opcua_nodes::{BaseEventType, Event, TypeTree};
+use scheduler::PublishScheduler;
 pub use session_subscriptions::SessionSubscriptions;
 use subscription::TickReason;
-pub use subscription::{MonitoredItemHandle, Subscription, SubscriptionState};
+pub use subscription::{MonitoredItemHandle, Subscription, SubscriptionMetrics, SubscriptionState};
 use tracing::error;
 
 use opcua_core::sync::{Mutex, RwLock};
 
 use opcua_types::{
-    AttributeId, CreateSubscriptionRequest, CreateSubscriptionResponse, DataEncoding, DataValue,
-    DateTimeUtc, MessageSecurityMode, ModifySubscriptionRequest, ModifySubscriptionResponse,
-    MonitoredItemCreateResult, MonitoredItemModifyRequest, MonitoringMode, NodeId,
-    NotificationMessage, NumericRange, ObjectId, PublishRequest, RepublishRequest,
-    RepublishResponse, ResponseHeader, SetPublishingModeRequest, SetPublishingModeResponse,
-    StatusCode, TimestampsToReturn, TransferResult, TransferSubscriptionsRequest,
-    TransferSubscriptionsResponse,
+    AttributeId, ByteString, CreateSubscriptionRequest, CreateSubscriptionResponse, DataEncoding,
+    DataValue, DateTimeUtc, Guid, MessageSecurityMode, ModifySubscriptionRequest,
+    ModifySubscriptionResponse, MonitoredItemCreateResult, MonitoredItemModifyRequest,
+    MonitoringMode, NodeId, NotificationMessage, NumericRange, ObjectId, ObjectTypeId,
+    PublishRequest, RepublishRequest, RepublishResponse, ResponseHeader, SetPublishingModeRequest,
+    SetPublishingModeResponse, StatusCode, SubscriptionDiagnosticsDataType, TimestampsToReturn,
+    TransferResult, TransferSubscriptionsRequest, TransferSubscriptionsResponse,
 };
 
 use super::{
@@ -65,6 +77,8 @@ struct SubscriptionCacheInner {
     subscription_to_session: HashMap<u32, u32>,
     /// Map from notifier node ID to monitored item handles.
     monitored_items: HashMap<MonitoredItemKey, HashMap<MonitoredItemHandle, MonitoredItemEntry>>,
+    /// Whether the server is shutting down, in which case no new subscriptions are created.
+    halted: bool,
 }
 
 /// Structure storing all subscriptions and monitored items on the server.
@@ -77,20 +91,66 @@ pub struct SubscriptionCache {
     inner: RwLock<SubscriptionCacheInner>,
     /// Configured limits on subscriptions.
     limits: SubscriptionLimits,
+    /// Number of notification messages produced by all subscriptions.
+    notification_messages: Arc<AtomicU64>,
+    /// Shared publishing timer, grouping subscriptions by publishing interval.
+    scheduler: Mutex<PublishScheduler>,
+    /// Number of monitored items on the server, used to enforce monitored item limits.
+    monitored_item_counters: Arc<MonitoredItemCounters>,
+    /// Retained conditions, replayed on `ConditionRefresh`.
+    conditions: ConditionManager,
 }
 
 impl SubscriptionCache {
-    pub(crate) fn new(limits: SubscriptionLimits) -> Self {
+    pub(crate) fn new(limits: SubscriptionLimits, node_manager_count: usize) -> Self {
         Self {
             inner: RwLock::new(SubscriptionCacheInner {
                 session_subscriptions: HashMap::new(),
                 subscription_to_session: HashMap::new(),
                 monitored_items: HashMap::new(),
+                halted: false,
             }),
             limits,
+            notification_messages: Arc::new(AtomicU64::new(0)),
+            scheduler: Mutex::new(PublishScheduler::default()),
+            monitored_item_counters: Arc::new(MonitoredItemCounters::new(node_manager_count)),
+            conditions: ConditionManager::default(),
         }
     }
 
+    /// Get the total number of notification messages containing notifications
+    /// produced by subscriptions on this server since it started.
+    ///
+    /// Each message contains at most `MaxNotificationsPerPublish` notifications
+    /// for its subscription, so this can be used to observe how notifications
+    /// are split across publish responses. Keep-alive and status change messages
+    /// are not counted.
+    pub fn notification_message_count(&self) -> u64 {
+        self.notification_messages.load(Ordering::Relaxed)
+    }
+
+    /// Get the number of active publishing timer groups.
+    ///
+    /// Subscriptions with the same publishing interval share a single timer,
+    /// so this is the number of distinct publishing intervals in use.
+    pub fn publishing_timer_group_count(&self) -> usize {
+        self.scheduler.lock().group_count()
+    }
+
+    /// Update the publishing timer groups of the session given by `session_id` after
+    /// its subscriptions changed, and tick it on the next tick. This must be called
+    /// while `cache` is locked, so that updates for a session are not reordered.
+    fn reschedule(&self, session_id: u32, cache: &SessionSubscriptions) {
+        let mut scheduler = self.scheduler.lock();
+        scheduler.set_session(session_id, cache.publishing_intervals(), Instant::now());
+        scheduler.tick_next(session_id);
+    }
+
+    /// Get the condition manager, keeping track of retained conditions.
+    pub fn conditions(&self) -> &ConditionManager {
+        &self.conditions
+    }
+
     /// Get the `SessionSubscriptions` object for a single session by its numeric ID.
     pub fn get_session_subscriptions(
         &self,
@@ -100,30 +160,91 @@ impl SubscriptionCache {
         inner.session_subscriptions.get(&session_id).cloned()
     }
 
+    /// Get the notification counters of the subscription with ID `subscription_id`,
+    /// and of each of its monitored items.
+    ///
+    /// This can be used to monitor whether clients keep up with the notifications
+    /// produced by their subscriptions. An increasing `dropped` count means monitored item
+    /// queues are overflowing, and the client is missing values.
+    pub fn subscription_metrics(&self, subscription_id: u32) -> Option<SubscriptionMetrics> {
+        let cache = ({
+            let lck = trace_read_lock!(self.inner);
+            let session_id = lck.subscription_to_session.get(&subscription_id)?;
+            lck.session_subscriptions.get(session_id).cloned()
+        })?;
+        let cache_lck = cache.lock();
+        cache_lck.get(subscription_id).map(|s| s.metrics())
+    }
+
+    /// Get the diagnostics of all subscriptions on the server, or only those owned by
+    /// the session with numeric ID `session_id`.
+    pub(crate) fn subscription_diagnostics(
+        &self,
+        session_id: Option<u32>,
+    ) -> Vec<SubscriptionDiagnosticsDataType> {
+        let caches: Vec<_> = {
+            let lck = trace_read_lock!(self.inner);
+            match session_id {
+                Some(id) => lck
+                    .session_subscriptions
+                    .get(&id)
+                    .cloned()
+                    .into_iter()
+                    .collect(),
+                None => lck.session_subscriptions.values().cloned().collect(),
+            }
+        };
+        caches
+            .iter()
+            .flat_map(|c| {
+                // Avoid holding the lock on the session while locking the subscriptions.
+                let session = c.lock().session().clone();
+                let session_id = trace_read_lock!(session).session_id().clone();
+                c.lock().diagnostics(&session_id)
+            })
+            .collect()
+    }
+
     /// This is the periodic subscription tick where we check for
     /// triggered subscriptions.
     ///
     pub(crate) async fn periodic_tick(&self, context: &ServerContext) {
-        // TODO: Look into replacing this with a smarter system, in theory it should be possible to
-        // always just sleep for the exact time until the next expired publish request, which could
-        // be more efficient, and would be more responsive.
         let mut to_delete = Vec::new();
         let mut items_to_delete = Vec::new();
-        {
+        let group_count = {
             let now = Utc::now();
             let now_instant = Instant::now();
+            // Only sessions with subscriptions in a due timer group, or with work
+            // pending, are ticked.
+            let (sessions, due) = self.scheduler.lock().due(now_instant);
             let lck = trace_read_lock!(self.inner);
-            for (session_id, sub) in lck.session_subscriptions.iter() {
+            for session_id in sessions {
+                let Some(sub) = lck.session_subscriptions.get(&session_id) else {
+                    self.scheduler
+                        .lock()
+                        .remove_session(session_id, now_instant);
+                    continue;
+                };
                 let mut sub_lck = sub.lock();
                 items_to_delete.push((
                     sub_lck.session().clone(),
-                    sub_lck.tick(&now, now_instant, TickReason::TickTimerFired),
+                    sub_lck.tick(&now, now_instant, TickReason::TickTimerFired, Some(&due)),
                 ));
+                let mut scheduler = self.scheduler.lock();
                 if sub_lck.is_ready_to_delete() {
-                    to_delete.push(*session_id);
+                    scheduler.remove_session(session_id, now_instant);
+                    to_delete.push(session_id);
+                } else {
+                    scheduler.set_session(session_id, sub_lck.publishing_intervals(), now_instant);
                 }
             }
-        }
+            self.scheduler.lock().group_count()
+        };
+        context
+            .info
+            .diagnostics
+            .set_publishing_interval_count(group_count as u32);
+        let any_expired = !to_delete.is_empty() || items_to_delete.iter().any(|i| !i.1.is_empty());
         if !to_delete.is_empty() {
             let mut lck = trace_write_lock!(self.inner);
             for id in to_delete {
@@ -134,11 +255,45 @@ impl SubscriptionCache {
                 .diagnostics
                 .set_current_subscription_count(lck.subscription_to_session.len() as u32);
         }
+        if any_expired {
+            self.update_monitored_item_count(&context.info);
+        }
         if !items_to_delete.is_empty() {
             Self::delete_expired_monitored_items(context, items_to_delete).await;
         }
     }
 
+    /// Halt all subscriptions on the server when it is shutting down.
+    ///
+    /// Each subscription is closed and sends a final `BadServerHalted` status change
+    /// to its client, and all monitored items are deleted. Any later attempt to create
+    /// a subscription fails with `BadServerHalted`.
+    pub(crate) async fn halt(&self, context: &ServerContext) {
+        let mut items_to_delete = Vec::new();
+        {
+            let now = Utc::now();
+            let now_instant = Instant::now();
+            let mut lck = trace_write_lock!(self.inner);
+            lck.halted = true;
+            for sub in lck.session_subscriptions.values() {
+                let mut sub_lck = sub.lock();
+                items_to_delete.push((sub_lck.session().clone(), sub_lck.halt(&now, now_instant)));
+            }
+            lck.subscription_to_session.clear();
+            lck.monitored_items.clear();
+            context.info.diagnostics.set_current_subscription_count(0);
+        }
+        self.update_monitored_item_count(&context.info);
+        Self::delete_expired_monitored_items(context, items_to_delete).await;
+    }
+
+    /// Return `true` if the session given by `session_id` has any subscriptions
+    /// that have not been closed and emptied yet.
+    pub(crate) fn has_subscriptions(&self, session_id: u32) -> bool {
+        self.get_session_subscriptions(session_id)
+            .is_some_and(|s| s.lock().has_subscriptions())
+    }
+
     async fn delete_expired_monitored_items(
         context: &ServerContext,
         items_to_delete: Vec<(Arc<RwLock<Session>>, Vec<MonitoredItemRef>)>,
@@ -165,6 +320,7 @@ impl SubscriptionCache {
                 subscriptions: context.subscriptions.clone(),
                 info: context.info.clone(),
                 type_tree_getter: context.type_tree_getter.clone(),
+                request_locale_ids: None,
             };
 
             for mgr in context.node_managers.iter() {
@@ -195,6 +351,16 @@ impl SubscriptionCache {
         cache_lck.get_monitored_item_count(subscription_id)
     }
 
+    pub(crate) fn monitored_item_counters(&self) -> &Arc<MonitoredItemCounters> {
+        &self.monitored_item_counters
+    }
+
+    /// Update the current monitored item count in the server diagnostics.
+    fn update_monitored_item_count(&self, info: &ServerInfo) {
+        info.diagnostics
+            .set_current_monitored_item_count(self.monitored_item_counters.total() as u32);
+    }
+
     pub(crate) fn create_subscription(
         &self,
         session_id: u32,
@@ -202,6 +368,9 @@ impl SubscriptionCache {
         context: &RequestContext,
     ) -> Result<CreateSubscriptionResponse, StatusCode> {
         let mut lck = trace_write_lock!(self.inner);
+        if lck.halted {
+            return Err(StatusCode::BadServerHalted);
+        }
         let cache = lck
             .session_subscriptions
             .entry(session_id)
@@ -211,11 +380,13 @@ impl SubscriptionCache {
                     Self::get_key(&context.session),
                     context.session.clone(),
                     context.info.type_tree_getter.get_type_tree_static(context),
+                    self.notification_messages.clone(),
                 )))
             })
             .clone();
         let mut cache_lck = cache.lock();
         let res = cache_lck.create_subscription(request, &context.info)?;
+        self.reschedule(session_id, &cache_lck);
         lck.subscription_to_session
             .insert(res.subscription_id, session_id);
         context
@@ -239,7 +410,9 @@ impl SubscriptionCache {
             return Err(StatusCode::BadNoSubscription);
         };
         let mut cache_lck = cache.lock();
-        cache_lck.modify_subscription(request, info)
+        let res = cache_lck.modify_subscription(request, info)?;
+        self.reschedule(session_id, &cache_lck);
+        Ok(res)
     }
 
     pub(crate) fn set_publishing_mode(
@@ -287,6 +460,10 @@ impl SubscriptionCache {
         };
 
         let mut cache_lck = cache.lock();
+        // Tick the session once the request expires, so that it times out.
+        self.scheduler
+            .lock()
+            .add_publish_deadline(session_id, request.deadline);
         cache_lck.enqueue_publish_request(now, now_instant, request);
         Ok(())
     }
@@ -396,44 +573,111 @@ impl SubscriptionCache {
 
     /// Notify listening clients to events. Without a custom node manager implementing
     /// event history, this is the only way to report events in the server.
+    ///
+    /// Each event is reported to monitored items on the given notifier, and on the
+    /// `Server` object. Use [`ServerHandle::notify_events_from`](crate::ServerHandle::notify_events_from)
+    /// to also report events to the notifiers above the source.
     pub fn notify_events<'a>(&self, items: impl Iterator<Item = (&'a dyn Event, &'a NodeId)>) {
+        self.notify_events_to(items.map(|(evt, notifier)| (evt, std::iter::once(notifier))));
+    }
+
+    /// Report a condition event for the condition given by `condition_id`.
+    ///
+    /// The event is reported like [`SubscriptionCache::notify_events`]. If its
+    /// `Retain` field is `true`, the condition is retained and replayed to clients
+    /// calling `ConditionRefresh`, until a condition event with `Retain` set to `false`
+    /// is reported for the same condition.
+    pub fn notify_condition(
+        &self,
+        condition_id: &NodeId,
+        event: impl Event + Send + Sync + 'static,
+        notifier: &NodeId,
+    ) {
+        self.notify_events([(&event as &dyn Event, notifier)].into_iter());
+        self.conditions
+            .update(condition_id, Box::new(event), notifier);
+    }
+
+    /// Replay retained conditions to the subscription given by `subscription_id`,
+    /// in response to a call to `ConditionRefresh` or `ConditionRefresh2`.
+    pub(crate) fn condition_refresh(
+        &self,
+        session_id: u32,
+        subscription_id: u32,
+        monitored_item_id: Option<u32>,
+    ) -> Result<(), StatusCode> {
+        let Some(cache) = self.get_session_subscriptions(session_id) else {
+            return Err(StatusCode::BadSubscriptionIdInvalid);
+        };
+        let refresh_event = |typ: ObjectTypeId| {
+            let mut evt = BaseEventType::new_now(typ, ByteString::from(Guid::new()), "");
+            evt.source_node = ObjectId::Server.into();
+            evt.source_name = "Server".into();
+            evt
+        };
+        let start = refresh_event(ObjectTypeId::RefreshStartEventType);
+        let end = refresh_event(ObjectTypeId::RefreshEndEventType);
+        let conditions = self.conditions.retained();
+        let mut cache_lck = cache.lock();
+        cache_lck.condition_refresh(
+            subscription_id,
+            monitored_item_id,
+            (&start, &end),
+            &conditions,
+        )
+    }
+
+    /// Notify listening clients to events, reporting each event to monitored items
+    /// on every node in the list of notifiers, and on the `Server` object.
+    pub(crate) fn notify_events_to<'a, 'b, I>(
+        &self,
+        items: impl Iterator<Item = (&'a dyn Event, I)>,
+    ) where
+        I: Iterator<Item = &'b NodeId>,
+    {
+        self.notify_events_to_sessions(items, |_| true);
+    }
+
+    /// Notify listening clients to events like [`SubscriptionCache::notify_events_to`],
+    /// but only report them to subscriptions in sessions where `session_filter`
+    /// returns `true` for the numeric session ID.
+    pub(crate) fn notify_events_to_sessions<'a, 'b, I>(
+        &self,
+        items: impl Iterator<Item = (&'a dyn Event, I)>,
+        session_filter: impl Fn(u32) -> bool,
+    ) where
+        I: Iterator<Item = &'b NodeId>,
+    {
         let lck = trace_read_lock!(self.inner);
         let mut by_subscription = HashMap::<u32, Vec<_>>::new();
-        for (evt, notifier) in items {
+        let server_id: NodeId = ObjectId::Server.into();
+        let mut add_items = |evt: &'a dyn Event, notifier: &NodeId| {
             let notifier_key = MonitoredItemKeyRef {
                 id: notifier,
                 attribute_id: AttributeId::EventNotifier,
             };
-            if let Some(items) = lck.monitored_items.get(&notifier_key) {
-                for (handle, item) in items {
-                    if !item.enabled {
-                        continue;
-                    }
-                    by_subscription
-                        .entry(handle.subscription_id)
-                        .or_default()
-                        .push((*handle, evt));
+            let Some(items) = lck.monitored_items.get(&notifier_key) else {
+                return;
+            };
+            for (handle, item) in items {
+                if !item.enabled {
+                    continue;
                 }
+                by_subscription
+                    .entry(handle.subscription_id)
+                    .or_default()
+                    .push((*handle, evt));
+            }
+        };
+        for (evt, notifiers) in items {
+            let mut notified_server = false;
+            for notifier in notifiers {
+                notified_server |= notifier == &server_id;
+                add_items(evt, notifier);
             }
             // The server gets all notifications.
-            let server_id: NodeId = ObjectId::Server.into();
-            if notifier != &server_id {
-                let server_key = MonitoredItemKeyRef {
-                    id: &server_id,
-                    attribute_id: AttributeId::EventNotifier,
-                };
-                let Some(items) = lck.monitored_items.get(&server_key) else {
-                    continue;
-                };
-                for (handle, item) in items {
-                    if !item.enabled {
-                        continue;
-                    }
-                    by_subscription
-                        .entry(handle.subscription_id)
-                        .or_default()
-                        .push((*handle, evt));
-                }
+            if !notified_server {
+                add_items(evt, &server_id);
             }
         }
 
@@ -441,6 +685,9 @@ impl SubscriptionCache {
             let Some(session_id) = lck.subscription_to_session.get(&sub_id) else {
                 continue;
             };
+            if !session_filter(*session_id) {
+                continue;
+            }
             let Some(cache) = lck.session_subscriptions.get(session_id) else {
                 continue;
             };
@@ -453,7 +700,24 @@ impl SubscriptionCache {
         &self,
         session_id: u32,
         subscription_id: u32,
-        requests: &[CreateMonitoredItem],
+        requests: &mut [CreateMonitoredItem],
+        info: &ServerInfo,
+    ) -> Result<Vec<MonitoredItemCreateResult>, StatusCode> {
+        let result = self.create_monitored_items_inner(session_id, subscription_id, requests);
+        if result.is_err() {
+            for item in requests.iter_mut() {
+                item.release_reservation();
+            }
+        }
+        self.update_monitored_item_count(info);
+        result
+    }
+
+    fn create_monitored_items_inner(
+        &self,
+        session_id: u32,
+        subscription_id: u32,
+        requests: &mut [CreateMonitoredItem],
     ) -> Result<Vec<MonitoredItemCreateResult>, StatusCode> {
         let mut lck = trace_write_lock!(self.inner);
         let Some(cache) = lck.session_subscriptions.get(&session_id).cloned() else {
@@ -487,6 +751,7 @@ impl SubscriptionCache {
         result
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn modify_monitored_items(
         &self,
         session_id: u32,
@@ -494,6 +759,7 @@ impl SubscriptionCache {
         info: &ServerInfo,
         timestamps_to_return: TimestampsToReturn,
         requests: Vec<MonitoredItemModifyRequest>,
+        data_types: &HashMap<u32, NodeId>,
         type_tree: &dyn TypeTree,
     ) -> Result<Vec<MonitoredItemUpdateRef>, StatusCode> {
         let Some(cache) = ({
@@ -509,10 +775,39 @@ impl SubscriptionCache {
             info,
             timestamps_to_return,
             requests,
+            data_types,
             type_tree,
         )
     }
 
+    /// Get the monitored node of each of the monitored items in `ids` that are
+    /// monitoring a value, on the subscription given by `subscription_id`.
+    pub(crate) fn get_monitored_value_nodes(
+        &self,
+        session_id: u32,
+        subscription_id: u32,
+        ids: &[u32],
+    ) -> HashMap<u32, NodeId> {
+        let Some(cache) = ({
+            let lck = trace_read_lock!(self.inner);
+            lck.session_subscriptions.get(&session_id).cloned()
+        }) else {
+            return HashMap::new();
+        };
+
+        let cache_lck = cache.lock();
+        let Some(sub) = cache_lck.get(subscription_id) else {
+            return HashMap::new();
+        };
+        ids.iter()
+            .filter_map(|id| {
+                let item = sub.get(id)?;
+                (item.item_to_monitor().attribute_id == AttributeId::Value)
+                    .then(|| (*id, item.item_to_monitor().node_id.clone()))
+            })
+            .collect()
+    }
+
     fn get_key(session: &RwLock<Session>) -> PersistentSessionKey {
         let lck = trace_read_lock!(session);
         PersistentSessionKey::new(
@@ -586,6 +881,18 @@ impl SubscriptionCache {
         session_id: u32,
         subscription_id: u32,
         items: &[u32],
+        info: &ServerInfo,
+    ) -> Result<Vec<(StatusCode, MonitoredItemRef)>, StatusCode> {
+        let result = self.delete_monitored_items_inner(session_id, subscription_id, items);
+        self.update_monitored_item_count(info);
+        result
+    }
+
+    fn delete_monitored_items_inner(
+        &self,
+        session_id: u32,
+        subscription_id: u32,
+        items: &[u32],
     ) -> Result<Vec<(StatusCode, MonitoredItemRef)>, StatusCode> {
         let mut lck = trace_write_lock!(self.inner);
         let Some(cache) = lck.session_subscriptions.get(&session_id).cloned() else {
@@ -615,6 +922,17 @@ impl SubscriptionCache {
         session_id: u32,
         ids: &[u32],
         info: &ServerInfo,
+    ) -> Result<Vec<(StatusCode, Vec<MonitoredItemRef>)>, StatusCode> {
+        let result = self.delete_subscriptions_inner(session_id, ids, info);
+        self.update_monitored_item_count(info);
+        result
+    }
+
+    fn delete_subscriptions_inner(
+        &self,
+        session_id: u32,
+        ids: &[u32],
+        info: &ServerInfo,
     ) -> Result<Vec<(StatusCode, Vec<MonitoredItemRef>)>, StatusCode> {
         let mut lck = trace_write_lock!(self.inner);
         let Some(cache) = lck.session_subscriptions.get(&session_id).cloned() else {
@@ -629,6 +947,7 @@ impl SubscriptionCache {
         info.diagnostics
             .set_current_subscription_count(lck.subscription_to_session.len() as u32);
         let result = cache_lck.delete_subscriptions(ids);
+        self.reschedule(session_id, &cache_lck);
 
         for (status, item_res) in &result {
             if !status.is_good() {
@@ -651,6 +970,38 @@ impl SubscriptionCache {
         Ok(result)
     }
 
+    /// Get the server and client handles of the monitored items in the subscription
+    /// given by `subscription_id`, in response to a call to `GetMonitoredItems`.
+    ///
+    /// Fails with `BadUserAccessDenied` if the subscription is not owned by the
+    /// session given by `session_id`.
+    pub(crate) fn get_monitored_items(
+        &self,
+        session_id: u32,
+        subscription_id: u32,
+    ) -> Result<(Vec<u32>, Vec<u32>), StatusCode> {
+        let cache = {
+            let lck = trace_read_lock!(self.inner);
+            match lck.subscription_to_session.get(&subscription_id) {
+                None => return Err(StatusCode::BadSubscriptionIdInvalid),
+                Some(owner) if *owner != session_id => return Err(StatusCode::BadUserAccessDenied),
+                Some(_) => lck
+                    .session_subscriptions
+                    .get(&session_id)
+                    .cloned()
+                    .ok_or(StatusCode::BadSubscriptionIdInvalid)?,
+            }
+        };
+
+        let cache_lck = cache.lock();
+        let sub = cache_lck
+            .get(subscription_id)
+            .ok_or(StatusCode::BadSubscriptionIdInvalid)?;
+        let mut items: Vec<_> = sub.items().map(|i| (i.id(), i.client_handle())).collect();
+        items.sort_unstable();
+        Ok(items.into_iter().unzip())
+    }
+
     pub(crate) fn get_session_subscription_ids(&self, session_id: u32) -> Vec<u32> {
         let Some(cache) = ({
             let lck = trace_read_lock!(self.inner);
@@ -695,6 +1046,7 @@ impl SubscriptionCache {
                         key.clone(),
                         context.session.clone(),
                         context.info.type_tree_getter.get_type_tree_static(context),
+                        self.notification_messages.clone(),
                     )))
                 })
                 .clone();
@@ -708,6 +1060,11 @@ impl SubscriptionCache {
                     res.status_code = StatusCode::Good;
                     res.available_sequence_numbers =
                         session_subs_lck.available_sequence_numbers(*sub_id);
+                    if req.send_initial_values {
+                        if let Some(sub) = session_subs_lck.get_mut(*sub_id) {
+                            sub.set_resend_data();
+                        }
+                    }
                     continue;
                 }
 
@@ -741,6 +1098,7 @@ impl SubscriptionCache {
                         res.status_code = e;
                         let _ = session_lck.insert(sub, notifs);
                     } else {
+                        self.reschedule(*current_owner_session_id, &session_lck);
                         if req.send_initial_values {
                             if let Some(sub) = session_subs_lck.get_mut(*sub_id) {
                                 sub.set_resend_data();
@@ -751,6 +1109,7 @@ impl SubscriptionCache {
                     }
                 }
             }
+            self.reschedule(context.session_id, &session_subs_lck);
         }
 
         TransferSubscriptionsResponse {
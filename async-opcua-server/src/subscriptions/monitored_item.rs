@@ -3,13 +3,14 @@ use std::collections::{BTreeSet, VecDeque};
 use opcua_nodes::{Event, ParsedEventFilter, TypeTree};
 use tracing::error;
 
-use super::MonitoredItemHandle;
+use super::{item_count::MonitoredItemReservation, MonitoredItemHandle};
 use crate::{info::ServerInfo, node_manager::ParsedReadValueId};
 use opcua_types::{
-    match_extension_object_owned, DataChangeFilter, DataValue, DateTime, EventFieldList,
-    EventFilter, EventFilterResult, ExtensionObject, MonitoredItemCreateRequest,
-    MonitoredItemModifyRequest, MonitoredItemNotification, MonitoringMode, NumericRange,
-    ParsedDataChangeFilter, StatusCode, TimestampsToReturn, Variant,
+    match_extension_object_owned, DataChangeFilter, DataChangeTrigger, DataTypeId, DataValue,
+    DateTime, Deadband, EventFieldList, EventFilter, EventFilterResult, ExtensionObject,
+    MonitoredItemCreateRequest, MonitoredItemModifyRequest, MonitoredItemNotification,
+    MonitoringMode, NodeId, NumericRange, ParsedDataChangeFilter, StatusCode, TimestampsToReturn,
+    Variant,
 };
 
 #[derive(Debug, Clone, PartialEq)]
@@ -75,6 +76,20 @@ impl FilterType {
     }
 }
 
+/// Check that a monitored item with the given data type can be used with a deadband in `filter`.
+/// Deadbands are only allowed on numeric data types. If the data type is not known
+/// the filter is allowed, and left to the node manager.
+fn validate_deadband(filter: &FilterType, is_numeric: Option<bool>) -> Result<(), StatusCode> {
+    let FilterType::DataChangeFilter(filter) = filter else {
+        return Ok(());
+    };
+    if matches!(filter.deadband, Deadband::None) || is_numeric != Some(false) {
+        Ok(())
+    } else {
+        Err(StatusCode::BadFilterNotAllowed)
+    }
+}
+
 #[derive(Debug)]
 /// Container for a request to create a single monitored item.
 pub struct CreateMonitoredItem {
@@ -92,6 +107,8 @@ pub struct CreateMonitoredItem {
     filter_res: Option<EventFilterResult>,
     timestamps_to_return: TimestampsToReturn,
     eu_range: Option<(f64, f64)>,
+    is_numeric: Option<bool>,
+    reservation: Option<MonitoredItemReservation>,
 }
 
 /// Takes the requested sampling interval value supplied by client and ensures it is within
@@ -179,6 +196,8 @@ impl CreateMonitoredItem {
             timestamps_to_return,
             filter_res,
             eu_range,
+            is_numeric: None,
+            reservation: None,
         }
     }
 
@@ -190,6 +209,18 @@ impl CreateMonitoredItem {
         }
     }
 
+    pub(crate) fn set_reservation(&mut self, reservation: MonitoredItemReservation) {
+        self.reservation = Some(reservation);
+    }
+
+    pub(crate) fn reservation_mut(&mut self) -> Option<&mut MonitoredItemReservation> {
+        self.reservation.as_mut()
+    }
+
+    pub(crate) fn release_reservation(&mut self) {
+        self.reservation = None;
+    }
+
     /// Set the initial value of the monitored item.
     pub fn set_initial_value(&mut self, value: DataValue) {
         self.initial_value = Some(value);
@@ -261,6 +292,35 @@ impl CreateMonitoredItem {
     pub(crate) fn filter_res(&self) -> Option<&EventFilterResult> {
         self.filter_res.as_ref()
     }
+
+    /// Reject the monitored item if it was created with a deadband filter,
+    /// but `data_type`, the data type of the monitored node, is not numeric.
+    /// This must be called before the item is passed to node managers.
+    pub(crate) fn validate_deadband(&mut self, data_type: &NodeId, type_tree: &dyn TypeTree) {
+        let is_numeric = type_tree.is_subtype_of(data_type, &DataTypeId::Number.into());
+        self.is_numeric = Some(is_numeric);
+        if self.status_code != StatusCode::BadNodeIdUnknown {
+            return;
+        }
+        if let Err(e) = validate_deadband(&self.filter, self.is_numeric) {
+            self.status_code = e;
+        }
+    }
+}
+
+/// Counters of notifications on a single monitored item, see [`MonitoredItem::metrics`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MonitoredItemMetrics {
+    /// Monitored item ID.
+    pub id: u32,
+    /// Number of notifications added to the queue.
+    pub queued: u64,
+    /// Number of notifications taken from the queue to be sent to the client.
+    pub delivered: u64,
+    /// Number of notifications discarded because the queue was full.
+    pub dropped: u64,
+    /// Number of notifications currently in the queue.
+    pub queue_depth: usize,
 }
 
 #[derive(Debug)]
@@ -283,10 +343,16 @@ pub struct MonitoredItem {
     last_data_value: Option<DataValue>,
     any_new_notification: bool,
     eu_range: Option<(f64, f64)>,
+    is_numeric: Option<bool>,
+    /// Capacity reserved for this monitored item, released when it is dropped.
+    _reservation: Option<MonitoredItemReservation>,
+    queued_count: u64,
+    delivered_count: u64,
+    dropped_count: u64,
 }
 
 impl MonitoredItem {
-    pub(super) fn new(request: &CreateMonitoredItem) -> Self {
+    pub(super) fn new(request: &mut CreateMonitoredItem) -> Self {
         let mut v = Self {
             id: request.id,
             item_to_monitor: request.item_to_monitor.clone(),
@@ -303,6 +369,11 @@ impl MonitoredItem {
             queue_overflow: false,
             any_new_notification: false,
             eu_range: request.eu_range,
+            is_numeric: request.is_numeric,
+            _reservation: request.reservation.take(),
+            queued_count: 0,
+            delivered_count: 0,
+            dropped_count: 0,
         };
         if let Some(val) = request.initial_value.as_ref() {
             v.notify_data_value(val.clone());
@@ -321,24 +392,33 @@ impl MonitoredItem {
     }
 
     /// Modifies the existing item with the values of the modify request. On success, the result
-    /// holds the filter result.
+    /// holds the filter result. `data_type` is the data type of the monitored node, if it
+    /// was read for this request.
     pub(super) fn modify(
         &mut self,
         info: &ServerInfo,
         timestamps_to_return: TimestampsToReturn,
         request: &MonitoredItemModifyRequest,
+        data_type: Option<&NodeId>,
         type_tree: &dyn TypeTree,
     ) -> (Option<EventFilterResult>, StatusCode) {
+        if let Some(data_type) = data_type {
+            self.is_numeric = Some(type_tree.is_subtype_of(data_type, &DataTypeId::Number.into()));
+        }
         self.timestamps_to_return = timestamps_to_return;
         let (filter_res, filter) = FilterType::from_filter(
             request.requested_parameters.filter.clone(),
             self.eu_range,
             type_tree,
         );
-        self.filter = match filter {
+        let filter = match filter {
             Ok(f) => f,
             Err(e) => return (filter_res, e),
         };
+        if let Err(e) = validate_deadband(&filter, self.is_numeric) {
+            return (filter_res, e);
+        }
+        self.filter = filter;
         self.sampling_interval =
             sanitize_sampling_interval(info, request.requested_parameters.sampling_interval);
         self.queue_size =
@@ -350,6 +430,7 @@ impl MonitoredItem {
         if self.notification_queue.len() > self.queue_size {
             // Discard old notifications
             let discard = self.notification_queue.len() - self.queue_size;
+            self.dropped_count += discard as u64;
             for _ in 0..discard {
                 if self.discard_oldest {
                     let _ = self.notification_queue.pop_back();
@@ -412,7 +493,9 @@ impl MonitoredItem {
                     && self.filter_by_sampling_interval(last_dv, &value)
             }
             (Some(last_dv), FilterType::None) => {
-                value.value != last_dv.value && self.filter_by_sampling_interval(last_dv, &value)
+                // Without a filter, the default trigger is on status or value.
+                value.has_changed(last_dv, DataChangeTrigger::StatusValue, &Deadband::None)
+                    && self.filter_by_sampling_interval(last_dv, &value)
             }
             (None, _) => true,
             _ => false,
@@ -473,8 +556,10 @@ impl MonitoredItem {
 
     fn enqueue_notification(&mut self, notification: impl Into<Notification>) {
         self.any_new_notification = true;
+        self.queued_count += 1;
         let overflow = self.notification_queue.len() == self.queue_size;
         if overflow {
+            self.dropped_count += 1;
             if self.discard_oldest {
                 self.notification_queue.pop_front();
             } else {
@@ -494,12 +579,15 @@ impl MonitoredItem {
     }
 
     pub(super) fn add_current_value_to_queue(&mut self) {
-        // Check if the last value is already enqueued
-        let last_value = self.notification_queue.front();
-        if let Some(Notification::MonitoredItemNotification(it)) = last_value {
-            if Some(&it.value) == self.last_data_value.as_ref() {
-                return;
-            }
+        // If a value is already queued, that value is sent as the current value.
+        // Only repeat the last value if nothing is waiting to be sent, see
+        // OPC-UA part 4 5.13.7.2, `sendInitialValues`.
+        if self
+            .notification_queue
+            .iter()
+            .any(|n| matches!(n, Notification::MonitoredItemNotification(_)))
+        {
+            return;
         }
 
         let Some(value) = self.last_data_value.as_ref() else {
@@ -529,7 +617,9 @@ impl MonitoredItem {
     }
 
     pub(super) fn pop_notification(&mut self) -> Option<Notification> {
-        self.notification_queue.pop_front()
+        let notification = self.notification_queue.pop_front()?;
+        self.delivered_count += 1;
+        Some(notification)
     }
 
     /// Adds or removes other monitored items which will be triggered when this monitored item changes
@@ -609,6 +699,17 @@ impl MonitoredItem {
     pub fn client_handle(&self) -> u32 {
         self.client_handle
     }
+
+    /// Get the notification counters of this monitored item.
+    pub fn metrics(&self) -> MonitoredItemMetrics {
+        MonitoredItemMetrics {
+            id: self.id,
+            queued: self.queued_count,
+            delivered: self.delivered_count,
+            dropped: self.dropped_count,
+            queue_depth: self.notification_queue.len(),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -617,9 +718,9 @@ pub(super) mod tests {
 
     use crate::{node_manager::ParsedReadValueId, subscriptions::monitored_item::Notification};
     use opcua_types::{
-        AttributeId, DataChangeFilter, DataChangeTrigger, DataValue, DateTime, Deadband,
-        DeadbandType, MonitoringMode, NodeId, ParsedDataChangeFilter, ReadValueId, StatusCode,
-        Variant,
+        AttributeId, ByteString, DataChangeFilter, DataChangeTrigger, DataValue, DateTime,
+        Deadband, DeadbandType, MonitoringMode, NodeId, ParsedDataChangeFilter, ReadValueId,
+        StatusCode, Variant,
     };
 
     use super::{FilterType, MonitoredItem};
@@ -649,6 +750,11 @@ pub(super) mod tests {
             last_data_value: None,
             any_new_notification: false,
             eu_range: None,
+            is_numeric: None,
+            _reservation: None,
+            queued_count: 0,
+            delivered_count: 0,
+            dropped_count: 0,
         };
 
         if let Some(val) = initial_value {
@@ -727,6 +833,36 @@ pub(super) mod tests {
         assert!(filter.is_changed(&v1, &v2));
     }
 
+    #[test]
+    fn data_change_ignores_sub_tick_timestamps() {
+        let filter = ParsedDataChangeFilter::parse(
+            DataChangeFilter {
+                trigger: DataChangeTrigger::StatusValueTimestamp,
+                deadband_type: DeadbandType::None as u32,
+                deadband_value: 0f64,
+            },
+            None,
+        )
+        .unwrap();
+
+        let mut v1 = DataValue::new_at(1i32, DateTime::ymd_hms_nano(2024, 1, 1, 0, 0, 0, 100));
+        v1.source_picoseconds = Some(10);
+        // Differs from v1 by less than a tick, and in picoseconds.
+        let mut v2 = DataValue::new_at(1i32, DateTime::ymd_hms_nano(2024, 1, 1, 0, 0, 0, 150));
+        v2.source_picoseconds = Some(20);
+        v2.server_picoseconds = Some(30);
+
+        assert_ne!(v1, v2);
+        assert!(v1.eq_ignoring_picoseconds(&v2));
+        assert!(!filter.is_changed(&v1, &v2));
+
+        // A full tick is a change.
+        v2.source_timestamp = Some(DateTime::ymd_hms_nano(2024, 1, 1, 0, 0, 0, 200));
+        v2.server_timestamp = v2.source_timestamp;
+        assert!(!v1.eq_ignoring_picoseconds(&v2));
+        assert!(filter.is_changed(&v1, &v2));
+    }
+
     #[test]
     fn data_change_deadband_abs() {
         let filter = DataChangeFilter {
@@ -771,6 +907,54 @@ pub(super) mod tests {
         assert!(filter.is_changed(&v1, &v2));
     }
 
+    #[test]
+    fn data_value_has_changed() {
+        let v1 = DataValue::new_at(10f64, DateTime::now());
+        let mut v2 = v1.clone();
+
+        // Status changes are reported for every trigger
+        v2.status = Some(StatusCode::UncertainLastUsableValue);
+        assert!(v2.has_changed(&v1, DataChangeTrigger::Status, &Deadband::None));
+        assert!(v2.has_changed(&v1, DataChangeTrigger::StatusValue, &Deadband::None));
+
+        // Value changes are filtered by the deadband
+        v2.status = v1.status;
+        v2.value = Some(Variant::Double(10.5f64));
+        assert!(!v2.has_changed(&v1, DataChangeTrigger::Status, &Deadband::None));
+        assert!(v2.has_changed(&v1, DataChangeTrigger::StatusValue, &Deadband::None));
+        assert!(!v2.has_changed(
+            &v1,
+            DataChangeTrigger::StatusValue,
+            &Deadband::Absolute(1.0)
+        ));
+
+        // Array values change if their length changes, regardless of deadband
+        let v1 = DataValue::value_only(vec![1f64, 2f64]);
+        let v2 = DataValue::value_only(vec![1f64, 2f64, 3f64]);
+        assert!(v2.has_changed(
+            &v1,
+            DataChangeTrigger::StatusValue,
+            &Deadband::Absolute(10.0)
+        ));
+
+        // Array elements are compared bitwise without a deadband
+        let v1 = DataValue::value_only(vec![0f64, f64::NAN]);
+        let v2 = DataValue::value_only(vec![0f64, f64::NAN]);
+        assert!(!v2.has_changed(&v1, DataChangeTrigger::StatusValue, &Deadband::None));
+        let v2 = DataValue::value_only(vec![-0f64, f64::NAN]);
+        assert!(v2.has_changed(&v1, DataChangeTrigger::StatusValue, &Deadband::None));
+
+        // Source timestamp changes are only reported for `StatusValueTimestamp`
+        let v1 = DataValue::new_at(10f64, DateTime::now());
+        let v2 = DataValue::new_at(10f64, v1.source_timestamp.unwrap() + Duration::seconds(1));
+        assert!(!v2.has_changed(&v1, DataChangeTrigger::StatusValue, &Deadband::None));
+        assert!(v2.has_changed(
+            &v1,
+            DataChangeTrigger::StatusValueTimestamp,
+            &Deadband::None
+        ));
+    }
+
     #[test]
     fn monitored_item_filter() {
         let start = Utc::now();
@@ -827,6 +1011,43 @@ pub(super) mod tests {
         assert_eq!(item.notification_queue.len(), 3);
     }
 
+    #[test]
+    fn monitored_item_default_filter_suppresses_duplicates() {
+        let start = Utc::now();
+        for value in [
+            Variant::Boolean(true),
+            Variant::from("foo"),
+            Variant::from(ByteString::from(vec![1u8, 2, 3])),
+            Variant::Double(f64::NAN),
+            Variant::from(vec![1i32, 2, 3]),
+            Variant::from(vec![1f64, f64::NAN]),
+        ] {
+            let mut item = new_monitored_item(
+                1,
+                ReadValueId {
+                    node_id: NodeId::null(),
+                    attribute_id: AttributeId::Value as u32,
+                    ..Default::default()
+                },
+                MonitoringMode::Reporting,
+                FilterType::None,
+                100.0,
+                true,
+                Some(DataValue::new_at(value.clone(), start.into())),
+            );
+            for i in 1..3 {
+                assert!(
+                    !item.notify_data_value(DataValue::new_at(
+                        value.clone(),
+                        (start + Duration::try_milliseconds(100 * i).unwrap()).into()
+                    )),
+                    "{value:?} was reported as changed"
+                );
+            }
+            assert_eq!(item.notification_queue.len(), 1);
+        }
+    }
+
     #[test]
     fn monitored_item_overflow() {
         let start = Utc::now();
@@ -876,4 +1097,39 @@ pub(super) mod tests {
             }
         }
     }
+
+    #[test]
+    fn add_current_value_to_queue() {
+        let start = Utc::now();
+        let mut item = new_monitored_item(
+            1,
+            ReadValueId {
+                node_id: NodeId::null(),
+                attribute_id: AttributeId::Value as u32,
+                ..Default::default()
+            },
+            MonitoringMode::Reporting,
+            FilterType::None,
+            100.0,
+            true,
+            Some(DataValue::new_at(0, start.into())),
+        );
+        assert!(item.notify_data_value(DataValue::new_at(
+            1,
+            (start + Duration::try_milliseconds(100).unwrap()).into(),
+        )));
+
+        // Queued values are sent as they are, nothing is added.
+        item.add_current_value_to_queue();
+        assert_eq!(item.notification_queue.len(), 2);
+
+        // With an empty queue, the last value is repeated.
+        item.notification_queue.clear();
+        item.add_current_value_to_queue();
+        assert_eq!(item.notification_queue.len(), 1);
+        let Some(Notification::MonitoredItemNotification(n)) = item.pop_notification() else {
+            panic!("Wrong notification type");
+        };
+        assert_eq!(n.value.value, Some(Variant::Int32(1)));
+    }
 }
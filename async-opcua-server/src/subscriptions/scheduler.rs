@@ -0,0 +1,196 @@
+use std::{
+    cmp::Reverse,
+    collections::BinaryHeap,
+    time::{Duration, Instant},
+};
+
+use hashbrown::{HashMap, HashSet};
+
+/// Timer shared by the subscriptions with the same publishing interval.
+#[derive(Debug)]
+struct TimerGroup {
+    /// The next time the group is due.
+    next: Instant,
+    /// Sessions with at least one subscription in this group.
+    sessions: HashSet<u32>,
+}
+
+/// Shared publishing timer for all subscriptions on the server.
+///
+/// Subscriptions are grouped by publishing interval, and each group has a single
+/// deadline. When the deadline of a group passes, only the sessions with subscriptions
+/// in the group are ticked, and the publish cycle of every subscription in the group
+/// runs on the same tick. This way the cost of the timer scales with the number of
+/// due subscriptions rather than the total number of subscriptions. Combine this with
+/// [`SubscriptionLimits::publishing_interval_resolution_ms`](crate::SubscriptionLimits::publishing_interval_resolution_ms)
+/// to keep the number of groups low.
+#[derive(Debug, Default)]
+pub(super) struct PublishScheduler {
+    /// Map from publishing interval to the timer group for that interval.
+    groups: HashMap<Duration, TimerGroup>,
+    /// Publishing intervals in use by each session.
+    sessions: HashMap<u32, HashSet<Duration>>,
+    /// Sessions that should be ticked on the next tick regardless of their groups.
+    pending: HashSet<u32>,
+    /// Deadlines of queued publish requests. Sessions are ticked once a deadline
+    /// passes, so that the request times out.
+    publish_deadlines: BinaryHeap<Reverse<(Instant, u32)>>,
+}
+
+impl PublishScheduler {
+    /// Get the sessions that should be ticked at `now`, and the publishing intervals whose
+    /// publish cycle is due. The deadline of each due group is moved to the next cycle.
+    ///
+    /// Cycles missed because the timer fell behind are skipped.
+    pub(super) fn due(&mut self, now: Instant) -> (HashSet<u32>, HashSet<Duration>) {
+        let mut sessions: HashSet<u32> = self.pending.drain().collect();
+        let mut due = HashSet::new();
+        for (interval, group) in self.groups.iter_mut() {
+            if group.next > now {
+                continue;
+            }
+            due.insert(*interval);
+            sessions.extend(group.sessions.iter().copied());
+            if interval.is_zero() {
+                group.next = now;
+            } else {
+                let missed = (now - group.next).as_nanos() / interval.as_nanos();
+                group.next += *interval * (missed as u32 + 1);
+            }
+        }
+        while let Some(Reverse((deadline, session_id))) = self.publish_deadlines.peek() {
+            if *deadline > now {
+                break;
+            }
+            sessions.insert(*session_id);
+            self.publish_deadlines.pop();
+        }
+        (sessions, due)
+    }
+
+    /// Set the publishing intervals used by the subscriptions of the session given
+    /// by `session_id`. New groups are first due one interval after `now`, groups
+    /// that are no longer used are removed.
+    pub(super) fn set_session(
+        &mut self,
+        session_id: u32,
+        intervals: impl IntoIterator<Item = Duration>,
+        now: Instant,
+    ) {
+        let intervals: HashSet<_> = intervals.into_iter().collect();
+        let old = self.sessions.remove(&session_id).unwrap_or_default();
+        for interval in old.difference(&intervals) {
+            if let Some(group) = self.groups.get_mut(interval) {
+                group.sessions.remove(&session_id);
+                if group.sessions.is_empty() {
+                    self.groups.remove(interval);
+                }
+            }
+        }
+        for interval in intervals.difference(&old) {
+            self.groups
+                .entry(*interval)
+                .or_insert_with(|| TimerGroup {
+                    next: now + *interval,
+                    sessions: HashSet::new(),
+                })
+                .sessions
+                .insert(session_id);
+        }
+        if !intervals.is_empty() {
+            self.sessions.insert(session_id, intervals);
+        }
+    }
+
+    /// Remove the session given by `session_id` from all timer groups.
+    pub(super) fn remove_session(&mut self, session_id: u32, now: Instant) {
+        self.set_session(session_id, [], now);
+        self.pending.remove(&session_id);
+    }
+
+    /// Tick the session given by `session_id` on the next tick, to start new subscriptions.
+    pub(super) fn tick_next(&mut self, session_id: u32) {
+        self.pending.insert(session_id);
+    }
+
+    /// Tick the session given by `session_id` once `deadline` has passed.
+    pub(super) fn add_publish_deadline(&mut self, session_id: u32, deadline: Instant) {
+        self.publish_deadlines.push(Reverse((deadline, session_id)));
+    }
+
+    /// Number of active timer groups.
+    pub(super) fn group_count(&self) -> usize {
+        self.groups.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use hashbrown::HashSet;
+
+    use super::PublishScheduler;
+
+    #[test]
+    fn publish_scheduler_groups() {
+        let start = Instant::now();
+        let fast = Duration::from_millis(100);
+        let slow = Duration::from_millis(250);
+        let ms = |ms| start + Duration::from_millis(ms);
+        let mut scheduler = PublishScheduler::default();
+        scheduler.set_session(1, [fast], start);
+        scheduler.set_session(2, [fast, slow], start);
+        scheduler.set_session(3, [slow], start);
+        assert_eq!(scheduler.group_count(), 2);
+
+        let (sessions, due) = scheduler.due(ms(50));
+        assert!(sessions.is_empty());
+        assert!(due.is_empty());
+        // Only the sessions in the due group are ticked.
+        assert_eq!(
+            scheduler.due(ms(100)),
+            (HashSet::from_iter([1, 2]), HashSet::from_iter([fast]))
+        );
+        // Already fired for this cycle.
+        assert!(scheduler.due(ms(150)).0.is_empty());
+        assert_eq!(
+            scheduler.due(ms(250)),
+            (
+                HashSet::from_iter([1, 2, 3]),
+                HashSet::from_iter([fast, slow])
+            )
+        );
+
+        // Missed cycles are skipped, the group stays on its original phase.
+        assert_eq!(scheduler.due(ms(720)).1, HashSet::from_iter([fast, slow]));
+        assert!(scheduler.due(ms(740)).1.is_empty());
+        assert_eq!(
+            scheduler.due(ms(750)),
+            (HashSet::from_iter([2, 3]), HashSet::from_iter([slow]))
+        );
+        assert_eq!(scheduler.due(ms(800)).1, HashSet::from_iter([fast]));
+
+        // Pending sessions and expired publish requests are ticked without a due group.
+        scheduler.tick_next(4);
+        scheduler.add_publish_deadline(3, ms(820));
+        scheduler.add_publish_deadline(1, ms(1000));
+        assert_eq!(
+            scheduler.due(ms(820)),
+            (HashSet::from_iter([3, 4]), HashSet::new())
+        );
+        assert!(scheduler.due(ms(830)).0.is_empty());
+
+        // Unused groups are removed.
+        scheduler.set_session(1, [slow], ms(830));
+        scheduler.remove_session(2, ms(830));
+        assert_eq!(scheduler.group_count(), 1);
+        // A new group is first due one interval after it is created.
+        scheduler.set_session(3, [slow, fast], ms(830));
+        assert!(scheduler.due(ms(900)).1.is_empty());
+        assert_eq!(
+            scheduler.due(ms(930)),
+            (HashSet::from_iter([3]), HashSet::from_iter([fast]))
+        );
+    }
+}
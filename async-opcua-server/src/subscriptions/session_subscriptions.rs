@@ -1,10 +1,14 @@
 use std::{
     collections::VecDeque,
-    sync::Arc,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
     time::{Duration, Instant},
 };
 
 use super::{
+    conditions::RetainedCondition,
     monitored_item::MonitoredItem,
     subscription::{MonitoredItemHandle, Subscription, TickReason, TickResult},
     CreateMonitoredItem, NonAckedPublish, PendingPublish, PersistentSessionKey,
@@ -23,9 +27,9 @@ use opcua_types::{
     AttributeId, CreateSubscriptionRequest, CreateSubscriptionResponse, DataValue, DateTime,
     DateTimeUtc, ExtensionObject, ModifySubscriptionRequest, ModifySubscriptionResponse,
     MonitoredItemCreateResult, MonitoredItemModifyRequest, MonitoredItemModifyResult,
-    MonitoringMode, NodeId, NotificationMessage, PublishRequest, PublishResponse, RepublishRequest,
-    RepublishResponse, ResponseHeader, ServiceFault, SetPublishingModeRequest,
-    SetPublishingModeResponse, StatusCode, TimestampsToReturn,
+    MonitoringMode, NodeId, NotificationMessage, ObjectId, PublishRequest, PublishResponse,
+    RepublishRequest, RepublishResponse, ResponseHeader, ServiceFault, SetPublishingModeRequest,
+    SetPublishingModeResponse, StatusCode, SubscriptionDiagnosticsDataType, TimestampsToReturn,
 };
 
 /// Subscriptions belonging to a single session. Note that they are technically _owned_ by
@@ -46,6 +50,11 @@ pub struct SessionSubscriptions {
     session: Arc<RwLock<Session>>,
     /// Static reference to the type-tree for the user owning this.
     type_tree_for_user: Arc<dyn TypeTreeForUserStatic>,
+    /// Server-wide count of produced notification messages.
+    notification_messages: Arc<AtomicU64>,
+    /// ID of the subscription that was last sent a publish response, used to
+    /// round-robin between subscriptions with the same priority.
+    last_published: u32,
 }
 
 impl SessionSubscriptions {
@@ -54,6 +63,7 @@ impl SessionSubscriptions {
         user_token: PersistentSessionKey,
         session: Arc<RwLock<Session>>,
         type_tree_for_user: Arc<dyn TypeTreeForUserStatic>,
+        notification_messages: Arc<AtomicU64>,
     ) -> Self {
         Self {
             user_token,
@@ -63,6 +73,8 @@ impl SessionSubscriptions {
             limits,
             session,
             type_tree_for_user,
+            notification_messages,
+            last_published: 0,
         }
     }
 
@@ -73,6 +85,11 @@ impl SessionSubscriptions {
             .max(1)
     }
 
+    /// Get the publishing intervals of the subscriptions in this session.
+    pub(super) fn publishing_intervals(&self) -> impl Iterator<Item = Duration> + '_ {
+        self.subscriptions.values().map(|s| s.publishing_interval())
+    }
+
     pub(super) fn is_ready_to_delete(&self) -> bool {
         self.subscriptions.is_empty() && self.publish_request_queue.is_empty()
     }
@@ -252,7 +269,7 @@ impl SessionSubscriptions {
     pub(super) fn create_monitored_items(
         &mut self,
         subscription_id: u32,
-        requests: &[CreateMonitoredItem],
+        requests: &mut [CreateMonitoredItem],
     ) -> Result<Vec<MonitoredItemCreateResult>, StatusCode> {
         let Some(sub) = self.subscriptions.get_mut(&subscription_id) else {
             return Err(StatusCode::BadSubscriptionIdInvalid);
@@ -275,6 +292,7 @@ impl SessionSubscriptions {
                 });
                 sub.insert(new_item.id(), new_item);
             } else {
+                item.release_reservation();
                 results.push(MonitoredItemCreateResult {
                     status_code: item.status_code(),
                     monitored_item_id: 0,
@@ -294,6 +312,7 @@ impl SessionSubscriptions {
         info: &ServerInfo,
         timestamps_to_return: TimestampsToReturn,
         requests: Vec<MonitoredItemModifyRequest>,
+        data_types: &HashMap<u32, NodeId>,
         type_tree: &dyn TypeTree,
     ) -> Result<Vec<MonitoredItemUpdateRef>, StatusCode> {
         let Some(sub) = self.subscriptions.get_mut(&subscription_id) else {
@@ -302,8 +321,13 @@ impl SessionSubscriptions {
         let mut results = Vec::with_capacity(requests.len());
         for request in requests {
             if let Some(item) = sub.get_mut(&request.monitored_item_id) {
-                let (filter_result, status) =
-                    item.modify(info, timestamps_to_return, &request, type_tree);
+                let (filter_result, status) = item.modify(
+                    info,
+                    timestamps_to_return,
+                    &request,
+                    data_types.get(&request.monitored_item_id),
+                    type_tree,
+                );
                 let filter_result = filter_result
                     .map(ExtensionObject::from_message)
                     .unwrap_or_else(ExtensionObject::null);
@@ -378,12 +402,16 @@ impl SessionSubscriptions {
         Ok(results)
     }
 
-    fn filter_links(links: Vec<u32>, sub: &Subscription) -> (Vec<u32>, Vec<StatusCode>) {
+    /// Get a per-link result for each link, and the list of links that are valid.
+    fn filter_links(
+        links: Vec<u32>,
+        is_valid: impl Fn(u32) -> bool,
+    ) -> (Vec<u32>, Vec<StatusCode>) {
         let mut to_apply = Vec::with_capacity(links.len());
         let mut results = Vec::with_capacity(links.len());
 
         for link in links {
-            if sub.contains_key(&link) {
+            if is_valid(link) {
                 to_apply.push(link);
                 results.push(StatusCode::Good);
             } else {
@@ -400,15 +428,24 @@ impl SessionSubscriptions {
         links_to_add: Vec<u32>,
         links_to_remove: Vec<u32>,
     ) -> Result<(Vec<StatusCode>, Vec<StatusCode>), StatusCode> {
+        if links_to_add.is_empty() && links_to_remove.is_empty() {
+            return Err(StatusCode::BadNothingToDo);
+        }
         let Some(sub) = self.subscriptions.get_mut(&subscription_id) else {
             return Err(StatusCode::BadSubscriptionIdInvalid);
         };
-        if !sub.contains_key(&triggering_item_id) {
+        let Some(item) = sub.get(&triggering_item_id) else {
             return Err(StatusCode::BadMonitoredItemIdInvalid);
-        }
+        };
 
-        let (to_add, add_results) = Self::filter_links(links_to_add, sub);
-        let (to_remove, remove_results) = Self::filter_links(links_to_remove, sub);
+        // Links are only added to other items in the same subscription, an item
+        // cannot trigger itself. Only existing links can be removed.
+        let (to_add, add_results) = Self::filter_links(links_to_add, |link| {
+            link != triggering_item_id && sub.contains_key(&link)
+        });
+        let (to_remove, remove_results) = Self::filter_links(links_to_remove, |link| {
+            item.triggered_items().contains(&link)
+        });
 
         let item = sub.get_mut(&triggering_item_id).unwrap();
 
@@ -493,6 +530,19 @@ impl SessionSubscriptions {
         requested_max_keep_alive_count: u32,
         requested_lifetime_count: u32,
     ) -> (f64, u32, u32) {
+        let resolution = info
+            .config
+            .limits
+            .subscriptions
+            .publishing_interval_resolution_ms;
+        let requested_publishing_interval = if resolution > 0.0 {
+            (requested_publishing_interval / resolution)
+                .round()
+                .max(1.0)
+                * resolution
+        } else {
+            requested_publishing_interval
+        };
         let revised_publishing_interval = f64::max(
             requested_publishing_interval,
             info.config.limits.subscriptions.min_publishing_interval_ms,
@@ -540,7 +590,7 @@ impl SessionSubscriptions {
     ) {
         if self.publish_request_queue.len() >= self.max_publish_requests() {
             // Tick to trigger publish, maybe remove a request to make space for new one
-            let _ = self.tick(now, now_instant, TickReason::ReceivePublishRequest);
+            let _ = self.tick(now, now_instant, TickReason::ReceivePublishRequest, None);
         }
 
         if self.publish_request_queue.len() >= self.max_publish_requests() {
@@ -559,14 +609,18 @@ impl SessionSubscriptions {
 
         request.ack_results = self.process_subscription_acks(&request.request);
         self.publish_request_queue.push_back(request);
-        self.tick(now, now_instant, TickReason::ReceivePublishRequest);
+        self.tick(now, now_instant, TickReason::ReceivePublishRequest, None);
     }
 
+    /// Tick the subscriptions in this session. If `due_intervals` is given, it contains
+    /// the publishing intervals whose shared timer fired, otherwise each subscription
+    /// checks whether its own publishing interval has elapsed.
     pub(crate) fn tick(
         &mut self,
         now: &DateTimeUtc,
         now_instant: Instant,
         tick_reason: TickReason,
+        due_intervals: Option<&HashSet<Duration>>,
     ) -> Vec<MonitoredItemRef> {
         let mut to_delete = Vec::new();
         if self.subscriptions.is_empty() {
@@ -582,13 +636,17 @@ impl SessionSubscriptions {
         self.remove_expired_publish_requests(now_instant);
 
         let subscription_ids = {
-            // Sort subscriptions by priority
+            // Sort subscriptions by priority, highest first. Subscriptions with equal
+            // priority are serviced in order of ID, starting after the last subscription
+            // that was sent a response.
+            let last = self.last_published;
             let mut subscription_priority: Vec<(u32, u8)> = self
                 .subscriptions
                 .values()
                 .map(|v| (v.id(), v.priority()))
                 .collect();
-            subscription_priority.sort_by(|s1, s2| s1.1.cmp(&s2.1));
+            subscription_priority
+                .sort_by_key(|&(id, priority)| (std::cmp::Reverse(priority), id <= last, id));
             subscription_priority.into_iter().map(|s| s.0)
         };
 
@@ -597,12 +655,20 @@ impl SessionSubscriptions {
 
         for sub_id in subscription_ids {
             let subscription = self.subscriptions.get_mut(&sub_id).unwrap();
-            let res = subscription.tick(
-                now,
-                now_instant,
-                tick_reason,
-                !self.publish_request_queue.is_empty(),
-            );
+            let publishing_req_queued = !self.publish_request_queue.is_empty();
+            let res = match due_intervals {
+                Some(due) => subscription.tick_scheduled(
+                    now,
+                    now_instant,
+                    due.contains(&subscription.publishing_interval()),
+                    publishing_req_queued,
+                ),
+                None => subscription.tick(now, now_instant, tick_reason, publishing_req_queued),
+            };
+            if let TickResult::Notifications(count) = res {
+                self.notification_messages
+                    .fetch_add(count as u64, Ordering::Relaxed);
+            }
             // Get notifications and publish request pairs while there are any of either left.
             while !self.publish_request_queue.is_empty() {
                 if let Some(notification_message) = subscription.take_notification() {
@@ -643,6 +709,7 @@ impl SessionSubscriptions {
             responses.into_iter().enumerate()
         {
             let is_last = idx == num_responses - 1;
+            self.last_published = subscription_id;
 
             let available_sequence_numbers = self.available_sequence_numbers(subscription_id);
 
@@ -676,6 +743,40 @@ impl SessionSubscriptions {
         to_delete
     }
 
+    /// Halt all subscriptions in this session, queueing a `BadServerHalted` status change
+    /// on each, and send it to any queued publish requests. Returns the monitored items
+    /// that were removed.
+    pub(super) fn halt(
+        &mut self,
+        now: &DateTimeUtc,
+        now_instant: Instant,
+    ) -> Vec<MonitoredItemRef> {
+        let mut to_delete = Vec::new();
+        for (sub_id, subscription) in self.subscriptions.iter_mut() {
+            subscription.halt(now);
+            to_delete.extend(subscription.drain().map(|item| {
+                MonitoredItemRef::new(
+                    MonitoredItemHandle {
+                        subscription_id: *sub_id,
+                        monitored_item_id: item.1.id(),
+                    },
+                    item.1.item_to_monitor().node_id.clone(),
+                    item.1.item_to_monitor().attribute_id,
+                )
+            }));
+        }
+        // Closed subscriptions do not produce new notifications, this just pairs
+        // the status changes with queued publish requests.
+        self.tick(now, now_instant, TickReason::ReceivePublishRequest, None);
+        to_delete
+    }
+
+    /// Return `true` if this session has any subscriptions left, including halted
+    /// subscriptions whose final notifications have not been sent yet.
+    pub(super) fn has_subscriptions(&self) -> bool {
+        !self.subscriptions.is_empty()
+    }
+
     fn find_notification_message(
         &self,
         subscription_id: u32,
@@ -778,6 +879,51 @@ impl SessionSubscriptions {
         }
     }
 
+    /// Replay retained conditions to the event monitored items of a subscription,
+    /// or only to `monitored_item_id` if it is given, between a `RefreshStartEvent`
+    /// and a `RefreshEndEvent`.
+    pub(super) fn condition_refresh(
+        &mut self,
+        subscription_id: u32,
+        monitored_item_id: Option<u32>,
+        refresh_events: (&dyn Event, &dyn Event),
+        conditions: &HashMap<NodeId, RetainedCondition>,
+    ) -> Result<(), StatusCode> {
+        let Some(sub) = self.subscriptions.get_mut(&subscription_id) else {
+            return Err(StatusCode::BadSubscriptionIdInvalid);
+        };
+        let items: Vec<_> = match monitored_item_id {
+            Some(id) => {
+                let Some(item) = sub.get(&id) else {
+                    return Err(StatusCode::BadMonitoredItemIdInvalid);
+                };
+                if item.item_to_monitor().attribute_id != AttributeId::EventNotifier {
+                    return Err(StatusCode::BadMonitoredItemIdInvalid);
+                }
+                vec![(id, item.item_to_monitor().node_id.clone())]
+            }
+            None => sub
+                .items()
+                .filter(|i| i.item_to_monitor().attribute_id == AttributeId::EventNotifier)
+                .map(|i| (i.id(), i.item_to_monitor().node_id.clone()))
+                .collect(),
+        };
+
+        let type_tree = self.type_tree_for_user.get_type_tree();
+        let server_id: NodeId = ObjectId::Server.into();
+        let (start, end) = refresh_events;
+        for (id, notifier) in items {
+            sub.notify_event(&id, start, type_tree.get());
+            for condition in conditions.values() {
+                if notifier == server_id || notifier == condition.notifier {
+                    sub.notify_event(&id, condition.event.as_ref(), type_tree.get());
+                }
+            }
+            sub.notify_event(&id, end, type_tree.get());
+        }
+        Ok(())
+    }
+
     pub(super) fn user_token(&self) -> &PersistentSessionKey {
         &self.user_token
     }
@@ -786,6 +932,16 @@ impl SessionSubscriptions {
         self.subscriptions.get(&subscription_id).map(|s| s.len())
     }
 
+    pub(super) fn diagnostics(&self, session_id: &NodeId) -> Vec<SubscriptionDiagnosticsDataType> {
+        let mut diagnostics: Vec<_> = self
+            .subscriptions
+            .values()
+            .map(|s| s.diagnostics(session_id.clone()))
+            .collect();
+        diagnostics.sort_by_key(|d| d.subscription_id);
+        diagnostics
+    }
+
     /// Get a reference to the session this subscription collection is owned by.
     pub fn session(&self) -> &Arc<RwLock<Session>> {
         &self.session
@@ -5,10 +5,13 @@ use std::{
 
 use opcua_core::handle::Handle;
 use opcua_nodes::{Event, TypeTree};
-use opcua_types::{DataValue, DateTime, DateTimeUtc, NotificationMessage, StatusCode};
+use opcua_types::{
+    DataValue, DateTime, DateTimeUtc, NodeId, NotificationMessage, StatusCode,
+    SubscriptionDiagnosticsDataType,
+};
 use tracing::{debug, trace, warn};
 
-use super::monitored_item::{MonitoredItem, Notification};
+use super::monitored_item::{MonitoredItem, MonitoredItemMetrics, Notification};
 
 #[derive(Debug, Copy, Clone, PartialEq)]
 /// Current internal state of the subscription.
@@ -60,6 +63,8 @@ pub(crate) enum UpdateStateAction {
 pub(super) enum TickResult {
     Expired,
     Enqueued,
+    /// One or more notification messages containing notifications were enqueued.
+    Notifications(usize),
     None,
 }
 
@@ -88,6 +93,23 @@ pub(crate) enum HandledState {
     Closed27 = 27,
 }
 
+/// Counters of notifications on a subscription, see
+/// [`SubscriptionCache::subscription_metrics`](super::SubscriptionCache::subscription_metrics).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SubscriptionMetrics {
+    /// Subscription ID.
+    pub subscription_id: u32,
+    /// Number of notifications added to monitored item queues.
+    pub queued: u64,
+    /// Number of notifications taken from monitored item queues to be sent to the client.
+    pub delivered: u64,
+    /// Number of notifications discarded because a monitored item queue was full.
+    /// If this keeps increasing, the queue sizes are too small for the rate of changes.
+    pub dropped: u64,
+    /// Counters for each current monitored item, ordered by ID.
+    pub monitored_items: Vec<MonitoredItemMetrics>,
+}
+
 #[derive(Debug)]
 /// A single subscription maintained by the server.
 pub struct Subscription {
@@ -121,12 +143,18 @@ pub struct Subscription {
     sequence_number: Handle,
     // The time that the subscription interval last fired
     last_time_publishing_interval_elapsed: Instant,
+    /// Due cycles of the shared publishing timer before this time are skipped, so that
+    /// a subscription joining an existing timer group does not publish early.
+    next_cycle_not_before: Option<Instant>,
     // Currently outstanding notifications to send
     notifications: VecDeque<NotificationMessage>,
     /// Maximum number of queued notifications.
     max_queued_notifications: usize,
     /// Maximum number of notifications per publish.
     max_notifications_per_publish: usize,
+    /// Notification counters of monitored items that have been deleted,
+    /// so that they are still included in the totals.
+    deleted_item_metrics: MonitoredItemMetrics,
 }
 
 #[derive(Debug, Copy, Clone, PartialEq)]
@@ -165,9 +193,11 @@ impl Subscription {
             // Counters for new items
             sequence_number: Handle::new(1),
             last_time_publishing_interval_elapsed: Instant::now(),
+            next_cycle_not_before: None,
             notifications: VecDeque::new(),
             max_queued_notifications,
             max_notifications_per_publish: max_notifications_per_publish as usize,
+            deleted_item_metrics: MonitoredItemMetrics::default(),
         }
     }
 
@@ -211,7 +241,12 @@ impl Subscription {
     }
 
     pub(super) fn remove(&mut self, id: &u32) -> Option<MonitoredItem> {
-        self.monitored_items.remove(id)
+        let item = self.monitored_items.remove(id)?;
+        let metrics = item.metrics();
+        self.deleted_item_metrics.queued += metrics.queued;
+        self.deleted_item_metrics.delivered += metrics.delivered;
+        self.deleted_item_metrics.dropped += metrics.dropped;
+        Some(item)
     }
 
     pub(super) fn insert(&mut self, id: u32, item: MonitoredItem) {
@@ -485,6 +520,46 @@ impl Subscription {
         if matches!(tick_reason, TickReason::TickTimerFired) && !publishing_interval_elapsed {
             return TickResult::None;
         }
+        self.run_publish_cycle(now, tick_reason, publishing_req_queued)
+    }
+
+    /// Tick the subscription from the shared publishing timer. `due` is whether
+    /// the timer group for the publishing interval of this subscription fired.
+    pub(super) fn tick_scheduled(
+        &mut self,
+        now: &DateTimeUtc,
+        now_instant: Instant,
+        due: bool,
+        publishing_req_queued: bool,
+    ) -> TickResult {
+        if self.state == SubscriptionState::Creating {
+            self.skip_cycles_until(now_instant);
+        } else if !due {
+            return TickResult::None;
+        } else if let Some(not_before) = self.next_cycle_not_before {
+            if now_instant < not_before {
+                return TickResult::None;
+            }
+            self.next_cycle_not_before = None;
+        }
+        self.last_time_publishing_interval_elapsed = now_instant;
+        self.run_publish_cycle(now, TickReason::TickTimerFired, publishing_req_queued)
+    }
+
+    /// Skip the cycles of the shared timer group that are due less than half a
+    /// publishing interval after `start`. The timer group is shared with other
+    /// subscriptions, so the first cycle runs on the group deadline closest to
+    /// one publishing interval after `start`.
+    fn skip_cycles_until(&mut self, start: Instant) {
+        self.next_cycle_not_before = Some(start + self.publishing_interval / 2);
+    }
+
+    fn run_publish_cycle(
+        &mut self,
+        now: &DateTimeUtc,
+        tick_reason: TickReason,
+        publishing_req_queued: bool,
+    ) -> TickResult {
         // First, get the actual state transition we're in.
         let transition = self.get_state_transition(
             tick_reason,
@@ -513,10 +588,11 @@ impl Subscription {
             UpdateStateAction::ReturnNotifications => {
                 let resend_data = std::mem::take(&mut self.resend_data);
                 let messages = self.tick_monitored_items(now, resend_data);
+                let num_messages = messages.len();
                 for msg in messages {
                     self.enqueue_notification(msg);
                 }
-                TickResult::Enqueued
+                TickResult::Notifications(num_messages)
             }
             UpdateStateAction::SubscriptionCreated => TickResult::None,
             UpdateStateAction::SubscriptionExpired => {
@@ -555,6 +631,18 @@ impl Subscription {
         self.state == SubscriptionState::Closed && self.notifications.is_empty()
     }
 
+    /// Close the subscription because the server is shutting down. A status change
+    /// with `BadServerHalted` is queued after any pending notifications.
+    pub(super) fn halt(&mut self, now: &DateTimeUtc) {
+        self.state = SubscriptionState::Closed;
+        let notification = NotificationMessage::status_change(
+            self.sequence_number.next(),
+            DateTime::from(*now),
+            StatusCode::BadServerHalted,
+        );
+        self.enqueue_notification(notification);
+    }
+
     fn handle_triggers(
         &mut self,
         now: &DateTimeUtc,
@@ -732,7 +820,10 @@ impl Subscription {
     }
 
     pub(super) fn set_publishing_interval(&mut self, publishing_interval: Duration) {
-        self.publishing_interval = publishing_interval;
+        if self.publishing_interval != publishing_interval {
+            self.publishing_interval = publishing_interval;
+            self.skip_cycles_until(Instant::now());
+        }
         self.reset_lifetime_counter();
     }
 
@@ -781,6 +872,51 @@ impl Subscription {
     pub fn state(&self) -> SubscriptionState {
         self.state
     }
+
+    /// Get the notification counters of this subscription and its monitored items.
+    /// The totals include monitored items that have since been deleted.
+    pub fn metrics(&self) -> SubscriptionMetrics {
+        let mut monitored_items: Vec<_> =
+            self.monitored_items.values().map(|i| i.metrics()).collect();
+        monitored_items.sort_by_key(|m| m.id);
+        let total = |f: fn(&MonitoredItemMetrics) -> u64| {
+            f(&self.deleted_item_metrics) + monitored_items.iter().map(f).sum::<u64>()
+        };
+        SubscriptionMetrics {
+            subscription_id: self.id,
+            queued: total(|m| m.queued),
+            delivered: total(|m| m.delivered),
+            dropped: total(|m| m.dropped),
+            monitored_items,
+        }
+    }
+
+    /// Get the diagnostics of this subscription, owned by the session with ID `session_id`.
+    /// Counters that are not tracked by the server are left at 0.
+    pub(super) fn diagnostics(&self, session_id: NodeId) -> SubscriptionDiagnosticsDataType {
+        let metrics = self.metrics();
+        SubscriptionDiagnosticsDataType {
+            session_id,
+            subscription_id: self.id,
+            priority: self.priority,
+            publishing_interval: self.publishing_interval.as_secs_f64() * 1000.0,
+            max_keep_alive_count: self.max_keep_alive_counter,
+            max_lifetime_count: self.max_lifetime_counter,
+            max_notifications_per_publish: self.max_notifications_per_publish as u32,
+            publishing_enabled: self.publishing_enabled,
+            notifications_count: metrics.delivered.try_into().unwrap_or(u32::MAX),
+            current_keep_alive_count: self.keep_alive_counter,
+            current_lifetime_count: self.lifetime_counter,
+            monitored_item_count: self.monitored_items.len() as u32,
+            disabled_monitored_item_count: self
+                .monitored_items
+                .values()
+                .filter(|i| !i.is_sampling())
+                .count() as u32,
+            monitoring_queue_overflow_count: metrics.dropped.try_into().unwrap_or(u32::MAX),
+            ..Default::default()
+        }
+    }
 }
 
 #[cfg(test)]
@@ -799,7 +935,7 @@ mod tests {
         ReadValueId, StatusChangeNotification, StatusCode, Variant,
     };
 
-    use super::{Subscription, TickReason};
+    use super::{Subscription, TickReason, TickResult};
 
     fn get_notifications(message: &NotificationMessage) -> Vec<Notification> {
         let mut res = Vec::new();
@@ -964,6 +1100,94 @@ mod tests {
         assert_eq!(status_change.status, StatusCode::BadTimeout);
     }
 
+    #[test]
+    fn max_notifications_per_publish() {
+        let mut sub = Subscription::new(1, true, Duration::from_millis(100), 100, 20, 1, 100, 2);
+        let start = Instant::now();
+        let start_dt = Utc::now();
+
+        sub.last_time_publishing_interval_elapsed = start;
+        sub.tick(&start_dt, start, TickReason::TickTimerFired, true);
+
+        for i in 0..5 {
+            sub.insert(
+                i + 1,
+                new_monitored_item(
+                    i + 1,
+                    ReadValueId {
+                        node_id: NodeId::null(),
+                        attribute_id: AttributeId::Value as u32,
+                        ..Default::default()
+                    },
+                    MonitoringMode::Reporting,
+                    FilterType::None,
+                    100.0,
+                    false,
+                    Some(DataValue::new_now(i as i32)),
+                ),
+            );
+        }
+
+        // 5 notifications with at most 2 per message should be split into 3 messages.
+        let (time, time_inst) = offset(start_dt, start, 100);
+        let res = sub.tick(&time, time_inst, TickReason::TickTimerFired, true);
+        assert!(matches!(res, TickResult::Notifications(3)));
+
+        let mut lens = Vec::new();
+        while let Some(notif) = sub.take_notification() {
+            assert!(sub.more_notifications() || lens.len() == 2);
+            lens.push(get_notifications(&notif).len());
+        }
+        lens.sort();
+        assert_eq!(lens, vec![1, 2, 2]);
+        assert!(!sub.more_notifications());
+    }
+
+    #[test]
+    fn scheduled_first_cycle() {
+        let mut sub = Subscription::new(1, true, Duration::from_millis(100), 100, 20, 1, 100, 1000);
+        let start = Instant::now();
+        let start_dt = Utc::now();
+
+        // The first tick starts the subscription, whether or not its group is due.
+        sub.tick_scheduled(&start_dt, start, false, true);
+        assert_eq!(sub.state, SubscriptionState::Normal);
+        sub.insert(
+            1,
+            new_monitored_item(
+                1,
+                ReadValueId {
+                    node_id: NodeId::null(),
+                    attribute_id: AttributeId::Value as u32,
+                    ..Default::default()
+                },
+                MonitoringMode::Reporting,
+                FilterType::None,
+                100.0,
+                false,
+                Some(DataValue::new_now(123)),
+            ),
+        );
+
+        // The existing timer group this subscription joined fires shortly after,
+        // which is too early for the first cycle.
+        let (time, time_inst) = offset(start_dt, start, 10);
+        sub.tick_scheduled(&time, time_inst, true, true);
+        assert!(sub.take_notification().is_none());
+
+        // The next cycle of the group is the closest to one publishing interval.
+        let (time, time_inst) = offset(start_dt, start, 110);
+        sub.tick_scheduled(&time, time_inst, true, true);
+        assert!(sub.take_notification().is_some());
+
+        // Changing the publishing interval moves the subscription to a different group.
+        sub.set_publishing_interval(Duration::from_millis(1000));
+        let (time, time_inst) = offset(start_dt, start, 200);
+        sub.tick_scheduled(&time, time_inst, true, true);
+        assert_eq!(sub.state, SubscriptionState::Normal);
+        assert!(sub.next_cycle_not_before.is_some());
+    }
+
     #[test]
     fn monitored_item_triggers() {
         let mut sub = Subscription::new(1, true, Duration::from_millis(100), 100, 20, 1, 100, 1000);
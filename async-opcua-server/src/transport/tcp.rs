@@ -6,6 +6,7 @@ use std::{
 use opcua_core::{
     comms::{
         buffer::SendBuffer,
+        buffer_pool::BufferPool,
         chunker::Chunker,
         message_chunk::{MessageChunk, MessageIsFinalType},
         message_chunk_info::ChunkInfo,
@@ -56,6 +57,7 @@ pub(crate) struct TransportConfig {
     pub max_message_size: usize,
     pub max_chunk_count: usize,
     pub hello_timeout: Duration,
+    pub buffer_pool: BufferPool,
 }
 
 #[derive(Debug)]
@@ -71,7 +73,7 @@ pub(crate) enum TransportPollResult {
     OutgoingMessageSent,
     IncomingChunk,
     IncomingMessage(Request),
-    Error(StatusCode),
+    Error(Error),
     RecoverableError(StatusCode, u32, u32),
     Closed,
 }
@@ -136,10 +138,12 @@ impl TcpConnector {
             self.config.max_chunk_count,
             true,
         );
+        buffer.set_buffer_pool(self.config.buffer_pool.clone());
 
         let endpoints = info.endpoints(&hello.endpoint_url, &None);
 
-        if !endpoints.is_some_and(|e| hello.is_endpoint_url_valid(&e)) {
+        let ignore_host = info.config.tcp_config.relax_endpoint_url_validation;
+        if !endpoints.is_some_and(|e| hello.is_endpoint_url_valid_for_host(&e, ignore_host)) {
             return Err(ErrorMessage::new(
                 StatusCode::BadTcpEndpointUrlInvalid,
                 "HELLO endpoint url is invalid",
@@ -214,7 +218,12 @@ impl Connector for TcpConnector {
             }
             r = self.connect_inner(info).instrument(tracing::info_span!("OPC-UA TCP handshake")) => {
                 match r {
-                    Ok(r) => return Ok(TcpTransport::new(self.read, self.write, r)),
+                    Ok(r) => {
+                        self.read
+                            .decoder_mut()
+                            .set_buffer_pool(self.config.buffer_pool.clone());
+                        return Ok(TcpTransport::new(self.read, self.write, r));
+                    }
                     Err(e) => e,
                 }
             }
@@ -258,6 +267,11 @@ impl TcpTransport {
         matches!(self.state, TransportState::Closing)
     }
 
+    /// Return `true` if there are outgoing messages that have not been written to the stream yet.
+    pub(crate) fn is_sending(&self) -> bool {
+        self.send_buffer.can_read() || self.send_buffer.should_encode_chunks()
+    }
+
     pub(crate) fn enqueue_error(&mut self, message: ErrorMessage) {
         self.send_buffer.write_error(message);
     }
@@ -301,7 +315,10 @@ impl TcpTransport {
         // write them to the send buffer before proceeding.
         if self.send_buffer.should_encode_chunks() {
             if let Err(e) = self.send_buffer.encode_next_chunk(channel) {
-                return TransportPollResult::Error(e);
+                return TransportPollResult::Error(Error::new(
+                    e,
+                    "Failed to encode outgoing chunk",
+                ));
             }
         }
 
@@ -350,13 +367,16 @@ impl TcpTransport {
                     if let Some((id, handle)) = e.full_context() {
                         TransportPollResult::RecoverableError(e.status(), id, handle)
                     } else {
-                        TransportPollResult::Error(e.status())
+                        TransportPollResult::Error(e)
                     }
                 }
             },
             Err(err) => {
                 error!("Error reading from stream {:?}", err);
-                TransportPollResult::Error(StatusCode::BadConnectionClosed)
+                TransportPollResult::Error(Error::new(
+                    StatusCode::BadConnectionClosed,
+                    format!("Error reading from stream: {err}"),
+                ))
             }
         }
     }
@@ -374,7 +394,10 @@ impl TcpTransport {
                     self.pending_chunks.clear();
                     Ok(None)
                 } else {
-                    let chunk = channel.verify_and_remove_security(&chunk.data)?;
+                    let pool = self.send_buffer.buffer_pool();
+                    let verified = channel.verify_and_remove_security(&chunk.data);
+                    pool.put(chunk.data);
+                    let chunk = verified?;
 
                     if self.pending_chunks.len() == self.send_buffer.max_chunk_count {
                         return Err(Error::decoding(format!(
@@ -396,7 +419,11 @@ impl TcpTransport {
                         &self.pending_chunks,
                     )?);
 
-                    let request = Chunker::decode(&self.pending_chunks, channel, None)
+                    let request = Chunker::decode(&self.pending_chunks, channel, None);
+                    for chunk in self.pending_chunks.drain(..) {
+                        self.send_buffer.buffer_pool().put(chunk.data);
+                    }
+                    let request = request
                         .map_err(|e| e.with_request_id(chunk_info.sequence_header.request_id))?;
                     Ok(Some(Request {
                         request_id: chunk_info.sequence_header.request_id,
@@ -57,7 +57,7 @@ impl Deadband {
             false
         } else {
             match self {
-                Deadband::None => v1 != v2,
+                Deadband::None => !values_identical(v1, v2),
                 Deadband::Absolute(deadband) => {
                     let (Some(v1), Some(v2)) = (v1.as_f64(), v2.as_f64()) else {
                         return true;
@@ -77,6 +77,26 @@ impl Deadband {
     }
 }
 
+/// Check whether two values are identical. Floating point values, including the
+/// elements of arrays, are compared by their bit pattern, so that a `NaN` value is not
+/// reported as changed on every sample, and `0.0` and `-0.0` are considered different.
+fn values_identical(v1: &Variant, v2: &Variant) -> bool {
+    match (v1, v2) {
+        (Variant::Float(v1), Variant::Float(v2)) => v1.to_bits() == v2.to_bits(),
+        (Variant::Double(v1), Variant::Double(v2)) => v1.to_bits() == v2.to_bits(),
+        (Variant::Variant(v1), Variant::Variant(v2)) => values_identical(v1, v2),
+        (Variant::Array(a1), Variant::Array(a2)) => {
+            a1.values.len() == a2.values.len()
+                && a1
+                    .values
+                    .iter()
+                    .zip(a2.values.iter())
+                    .all(|(v1, v2)| values_identical(v1, v2))
+        }
+        _ => v1 == v2,
+    }
+}
+
 #[derive(Debug, Clone)]
 /// Parsed variant of the data change filter that can be directly checked for data changes.
 pub struct ParsedDataChangeFilter {
@@ -86,27 +106,46 @@ pub struct ParsedDataChangeFilter {
     pub deadband: Deadband,
 }
 
-impl ParsedDataChangeFilter {
-    /// Check if this data change filter considers `v1` different from `v2`.
-    pub fn is_changed(&self, v1: &DataValue, v2: &DataValue) -> bool {
-        match self.trigger {
-            DataChangeTrigger::Status => v1.status != v2.status,
+impl DataValue {
+    /// Check if this value is considered changed from `previous` according to the
+    /// OPC UA data change rules, given a `trigger` and a `deadband`.
+    ///
+    /// A change in status is always reported. Depending on the trigger, a change in
+    /// value beyond the deadband, or a change in source timestamp, is also reported.
+    /// The deadband is applied to each element of array values, and a change in array
+    /// length is always a change. Without a deadband, values of any type are only
+    /// unchanged if they are identical, floating point values are compared bit for bit.
+    /// Source timestamps are compared at the 100 nanosecond resolution of the binary
+    /// encoding, so picoseconds are ignored.
+    ///
+    /// This is the check used by the server for data change filters. Monitored items
+    /// without a filter use `DataChangeTrigger::StatusValue` and `Deadband::None`.
+    pub fn has_changed(
+        &self,
+        previous: &DataValue,
+        trigger: DataChangeTrigger,
+        deadband: &Deadband,
+    ) -> bool {
+        match trigger {
+            DataChangeTrigger::Status => self.status != previous.status,
             DataChangeTrigger::StatusValue => {
-                v1.status != v2.status
-                    || self
-                        .deadband
-                        .is_changed_option(v1.value.as_ref(), v2.value.as_ref())
+                self.status != previous.status
+                    || deadband.is_changed_option(self.value.as_ref(), previous.value.as_ref())
             }
             DataChangeTrigger::StatusValueTimestamp => {
-                v1.status != v2.status
-                    || v1.source_timestamp != v2.source_timestamp
-                    || v1.source_picoseconds != v2.source_picoseconds
-                    || self
-                        .deadband
-                        .is_changed_option(v1.value.as_ref(), v2.value.as_ref())
+                self.status != previous.status
+                    || !self.source_timestamp_eq(previous)
+                    || deadband.is_changed_option(self.value.as_ref(), previous.value.as_ref())
             }
         }
     }
+}
+
+impl ParsedDataChangeFilter {
+    /// Check if this data change filter considers `v1` different from `v2`.
+    pub fn is_changed(&self, v1: &DataValue, v2: &DataValue) -> bool {
+        v1.has_changed(v2, self.trigger, &self.deadband)
+    }
 
     /// Parse from a raw data change filter and the EURange of the node, if present.
     pub fn parse(
@@ -131,8 +170,9 @@ impl ParsedDataChangeFilter {
                 if filter.deadband_value < 0.0 || filter.deadband_value > 100.0 {
                     return Err(StatusCode::BadDeadbandFilterInvalid);
                 }
+                // Percent deadband requires the node to have an EURange property.
                 let Some((low, high)) = eu_range else {
-                    return Err(StatusCode::BadDeadbandFilterInvalid);
+                    return Err(StatusCode::BadMonitoredItemFilterUnsupported);
                 };
                 if low >= high {
                     return Err(StatusCode::BadDeadbandFilterInvalid);
@@ -450,6 +450,15 @@ impl<'a> From<(Variant, &'a DateTime, &'a DateTime)> for DataValue {
 }
 */
 
+/// Compare two optional timestamps at the 100 nanosecond resolution of the binary encoding.
+fn timestamp_ticks_eq(a: Option<&DateTime>, b: Option<&DateTime>) -> bool {
+    match (a, b) {
+        (Some(a), Some(b)) => a.ticks() == b.ticks(),
+        (None, None) => true,
+        _ => false,
+    }
+}
+
 impl Default for DataValue {
     fn default() -> Self {
         Self::null()
@@ -633,6 +642,33 @@ impl DataValue {
         self.status().is_good()
     }
 
+    /// Compare two data values, ignoring the picoseconds fields and comparing
+    /// timestamps at the 100 nanosecond resolution of the binary encoding.
+    ///
+    /// Use this instead of `==` when checking whether a value has changed, since
+    /// timestamps that differ only below a tick are identical on the wire.
+    pub fn eq_ignoring_picoseconds(&self, other: &DataValue) -> bool {
+        self.value == other.value
+            && self.status == other.status
+            && timestamp_ticks_eq(
+                self.source_timestamp.as_ref(),
+                other.source_timestamp.as_ref(),
+            )
+            && timestamp_ticks_eq(
+                self.server_timestamp.as_ref(),
+                other.server_timestamp.as_ref(),
+            )
+    }
+
+    /// Check if the source timestamps of two data values are equal at tick resolution,
+    /// ignoring the source picoseconds.
+    pub fn source_timestamp_eq(&self, other: &DataValue) -> bool {
+        timestamp_ticks_eq(
+            self.source_timestamp.as_ref(),
+            other.source_timestamp.as_ref(),
+        )
+    }
+
     fn encoding_mask(&self) -> DataValueFlags {
         let mut encoding_mask = DataValueFlags::empty();
         if self.value.is_some() {
@@ -403,7 +403,8 @@ impl UaNullable for str {}
 ///    instead.
 pub trait BinaryEncodable {
     /// Returns the exact byte length of the structure as it would be if `encode` were called.
-    /// This may be called prior to writing to ensure the correct amount of space is available.
+    /// This may be called prior to writing to ensure the correct amount of space is available,
+    /// or to check the size of a message against negotiated limits without encoding it.
     #[allow(unused)]
     fn byte_len(&self, ctx: &crate::Context<'_>) -> usize;
     /// Encodes the instance to the write stream.
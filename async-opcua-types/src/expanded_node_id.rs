@@ -448,6 +448,17 @@ impl From<NodeId> for ExpandedNodeId {
     }
 }
 
+/// Escape a namespace URI for use in the `nsu=<uri>` part of a node ID string.
+/// The `%` and `;` characters have to be escaped out in the URI.
+pub(crate) fn escape_namespace_uri(uri: &str) -> String {
+    uri.replace('%', "%25").replace(';', "%3b")
+}
+
+/// Unescape a namespace URI from the `nsu=<uri>` part of a node ID string.
+pub(crate) fn unescape_namespace_uri(uri: &str) -> String {
+    uri.replace("%3b", ";").replace("%25", "%")
+}
+
 impl fmt::Display for ExpandedNodeId {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         // Formatted depending on the namespace uri being empty or not.
@@ -455,10 +466,7 @@ impl fmt::Display for ExpandedNodeId {
             // svr=<serverindex>;ns=<namespaceindex>;<type>=<value>
             write!(f, "svr={};{}", self.server_index, self.node_id)
         } else {
-            // The % and ; chars have to be escaped out in the uri
-            let namespace_uri = String::from(self.namespace_uri.as_ref())
-                .replace('%', "%25")
-                .replace(';', "%3b");
+            let namespace_uri = escape_namespace_uri(self.namespace_uri.as_ref());
             // svr=<serverindex>;nsu=<uri>;<type>=<value>
             write!(
                 f,
@@ -502,11 +510,7 @@ impl FromStr for ExpandedNodeId {
 
         // Check for namespace uri
         let namespace_uri = if let Some(nsu) = captures.name("nsu") {
-            // The % and ; chars need to be unescaped
-            let nsu = String::from(nsu.as_str())
-                .replace("%3b", ";")
-                .replace("%25", "%");
-            UAString::from(nsu)
+            UAString::from(unescape_namespace_uri(nsu.as_str()))
         } else {
             UAString::null()
         };
@@ -549,6 +553,29 @@ impl ExpandedNodeId {
         }
     }
 
+    /// Creates an expanded node id referencing a node on a different server.
+    /// `server_index` is the index of the server in the server array of the local server,
+    /// the namespace is given as a URI, since namespace indexes are local to each server.
+    pub fn new_remote(
+        server_index: u32,
+        namespace: &str,
+        value: impl Into<Identifier> + 'static,
+    ) -> Self {
+        Self::new_with_namespace(namespace, value).with_server_index(server_index)
+    }
+
+    /// Set the server index of this expanded node ID. 0 means the local server.
+    pub fn with_server_index(mut self, server_index: u32) -> Self {
+        self.server_index = server_index;
+        self
+    }
+
+    /// Return `true` if this expanded node ID references a node on the local server,
+    /// meaning that the server index is 0.
+    pub fn is_local(&self) -> bool {
+        self.server_index == 0
+    }
+
     /// Return a null ExpandedNodeId.
     pub fn null() -> ExpandedNodeId {
         Self::new(NodeId::null())
@@ -578,4 +605,22 @@ impl ExpandedNodeId {
             Some(Cow::Borrowed(&self.node_id))
         }
     }
+    /// Format the expanded node ID as a string, using the namespace URI from `namespaces`
+    /// instead of the namespace index, if the index is known.
+    ///
+    /// The result can be parsed back with `ExpandedNodeId::from_str`.
+    pub fn to_string_with_namespaces(&self, namespaces: &NamespaceMap) -> String {
+        if !self.namespace_uri.is_empty() || self.node_id.namespace == 0 {
+            return self.to_string();
+        }
+        match namespaces.get_namespace_uri(self.node_id.namespace) {
+            Some(uri) => format!(
+                "svr={};nsu={};{}",
+                self.server_index,
+                escape_namespace_uri(uri),
+                self.node_id.identifier
+            ),
+            None => self.to_string(),
+        }
+    }
 }
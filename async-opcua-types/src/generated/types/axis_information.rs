@@ -14,6 +14,7 @@ mod opcua {
 #[derive(Debug, Clone, PartialEq, Default)]
 pub struct AxisInformation {
     pub engineering_units: super::eu_information::EUInformation,
+    #[opcua(rename = "EURange")]
     pub eu_range: super::range::Range,
     pub title: opcua::types::localized_text::LocalizedText,
     pub axis_scale_type: super::enums::AxisScaleEnumeration,
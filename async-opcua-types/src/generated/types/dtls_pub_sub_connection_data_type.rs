@@ -15,6 +15,7 @@ mod opcua {
 pub struct DtlsPubSubConnectionDataType {
     pub client_cipher_suite: opcua::types::string::UAString,
     pub server_cipher_suites: Option<Vec<opcua::types::string::UAString>>,
+    #[opcua(rename = "ZeroRTT")]
     pub zero_rtt: bool,
     pub certificate_group_id: opcua::types::node_id::NodeId,
     pub verify_client_certificate: bool,
@@ -39,6 +39,40 @@ impl opcua::types::IntoVariant for AccessLevelExType {
         self.bits().into_variant()
     }
 }
+impl std::fmt::Display for AccessLevelExType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_empty() {
+            return f.write_str("0");
+        }
+        let flags: [(Self, &str); 13] = [
+            (Self::CurrentRead, "CurrentRead"),
+            (Self::CurrentWrite, "CurrentWrite"),
+            (Self::HistoryRead, "HistoryRead"),
+            (Self::HistoryWrite, "HistoryWrite"),
+            (Self::SemanticChange, "SemanticChange"),
+            (Self::StatusWrite, "StatusWrite"),
+            (Self::TimestampWrite, "TimestampWrite"),
+            (Self::NonatomicRead, "NonatomicRead"),
+            (Self::NonatomicWrite, "NonatomicWrite"),
+            (Self::WriteFullArrayOnly, "WriteFullArrayOnly"),
+            (Self::NoSubDataTypes, "NoSubDataTypes"),
+            (Self::NonVolatile, "NonVolatile"),
+            (Self::Constant, "Constant"),
+        ];
+        let mut first = true;
+        for (flag, name) in flags {
+            if flag.is_empty() || !self.contains(flag) {
+                continue;
+            }
+            if !first {
+                f.write_str(", ")?;
+            }
+            f.write_str(name)?;
+            first = false;
+        }
+        Ok(())
+    }
+}
 #[cfg(feature = "xml")]
 impl opcua::types::xml::XmlType for AccessLevelExType {
     const TAG: &'static str = "AccessLevelExType";
@@ -71,6 +105,34 @@ impl opcua::types::IntoVariant for AccessLevelType {
         self.bits().into_variant()
     }
 }
+impl std::fmt::Display for AccessLevelType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_empty() {
+            return f.write_str("0");
+        }
+        let flags: [(Self, &str); 7] = [
+            (Self::CurrentRead, "CurrentRead"),
+            (Self::CurrentWrite, "CurrentWrite"),
+            (Self::HistoryRead, "HistoryRead"),
+            (Self::HistoryWrite, "HistoryWrite"),
+            (Self::SemanticChange, "SemanticChange"),
+            (Self::StatusWrite, "StatusWrite"),
+            (Self::TimestampWrite, "TimestampWrite"),
+        ];
+        let mut first = true;
+        for (flag, name) in flags {
+            if flag.is_empty() || !self.contains(flag) {
+                continue;
+            }
+            if !first {
+                f.write_str(", ")?;
+            }
+            f.write_str(name)?;
+            first = false;
+        }
+        Ok(())
+    }
+}
 #[cfg(feature = "xml")]
 impl opcua::types::xml::XmlType for AccessLevelType {
     const TAG: &'static str = "AccessLevelType";
@@ -103,6 +165,31 @@ impl opcua::types::IntoVariant for AccessRestrictionType {
         self.bits().into_variant()
     }
 }
+impl std::fmt::Display for AccessRestrictionType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_empty() {
+            return f.write_str("0");
+        }
+        let flags: [(Self, &str); 4] = [
+            (Self::SigningRequired, "SigningRequired"),
+            (Self::EncryptionRequired, "EncryptionRequired"),
+            (Self::SessionRequired, "SessionRequired"),
+            (Self::ApplyRestrictionsToBrowse, "ApplyRestrictionsToBrowse"),
+        ];
+        let mut first = true;
+        for (flag, name) in flags {
+            if flag.is_empty() || !self.contains(flag) {
+                continue;
+            }
+            if !first {
+                f.write_str(", ")?;
+            }
+            f.write_str(name)?;
+            first = false;
+        }
+        Ok(())
+    }
+}
 #[cfg(feature = "xml")]
 impl opcua::types::xml::XmlType for AccessRestrictionType {
     const TAG: &'static str = "AccessRestrictionType";
@@ -117,6 +204,15 @@ pub enum ActionState {
     Executing = 1i32,
     Done = 2i32,
 }
+impl std::fmt::Display for ActionState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Idle => "Idle",
+            Self::Executing => "Executing",
+            Self::Done => "Done",
+        })
+    }
+}
 bitflags::bitflags! {
     #[derive(Debug, Copy, Clone, PartialEq)] #[doc =
     "https://reference.opcfoundation.org/v105/Core/docs/Part9/8.3"] pub struct AlarmMask
@@ -143,6 +239,30 @@ impl opcua::types::IntoVariant for AlarmMask {
         self.bits().into_variant()
     }
 }
+impl std::fmt::Display for AlarmMask {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_empty() {
+            return f.write_str("0");
+        }
+        let flags: [(Self, &str); 3] = [
+            (Self::Active, "Active"),
+            (Self::Unacknowledged, "Unacknowledged"),
+            (Self::Unconfirmed, "Unconfirmed"),
+        ];
+        let mut first = true;
+        for (flag, name) in flags {
+            if flag.is_empty() || !self.contains(flag) {
+                continue;
+            }
+            if !first {
+                f.write_str(", ")?;
+            }
+            f.write_str(name)?;
+            first = false;
+        }
+        Ok(())
+    }
+}
 #[cfg(feature = "xml")]
 impl opcua::types::xml::XmlType for AlarmMask {
     const TAG: &'static str = "AlarmMask";
@@ -158,6 +278,16 @@ pub enum ApplicationType {
     ClientAndServer = 2i32,
     DiscoveryServer = 3i32,
 }
+impl std::fmt::Display for ApplicationType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Server => "Server",
+            Self::Client => "Client",
+            Self::ClientAndServer => "ClientAndServer",
+            Self::DiscoveryServer => "DiscoveryServer",
+        })
+    }
+}
 bitflags::bitflags! {
     #[derive(Debug, Copy, Clone, PartialEq)] #[doc =
     "https://reference.opcfoundation.org/v105/Core/docs/Part3/8.60"] pub struct
@@ -194,6 +324,53 @@ impl opcua::types::IntoVariant for AttributeWriteMask {
         self.bits().into_variant()
     }
 }
+impl std::fmt::Display for AttributeWriteMask {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_empty() {
+            return f.write_str("0");
+        }
+        let flags: [(Self, &str); 26] = [
+            (Self::AccessLevel, "AccessLevel"),
+            (Self::ArrayDimensions, "ArrayDimensions"),
+            (Self::BrowseName, "BrowseName"),
+            (Self::ContainsNoLoops, "ContainsNoLoops"),
+            (Self::DataType, "DataType"),
+            (Self::Description, "Description"),
+            (Self::DisplayName, "DisplayName"),
+            (Self::EventNotifier, "EventNotifier"),
+            (Self::Executable, "Executable"),
+            (Self::Historizing, "Historizing"),
+            (Self::InverseName, "InverseName"),
+            (Self::IsAbstract, "IsAbstract"),
+            (Self::MinimumSamplingInterval, "MinimumSamplingInterval"),
+            (Self::NodeClass, "NodeClass"),
+            (Self::NodeId, "NodeId"),
+            (Self::Symmetric, "Symmetric"),
+            (Self::UserAccessLevel, "UserAccessLevel"),
+            (Self::UserExecutable, "UserExecutable"),
+            (Self::UserWriteMask, "UserWriteMask"),
+            (Self::ValueRank, "ValueRank"),
+            (Self::WriteMask, "WriteMask"),
+            (Self::ValueForVariableType, "ValueForVariableType"),
+            (Self::DataTypeDefinition, "DataTypeDefinition"),
+            (Self::RolePermissions, "RolePermissions"),
+            (Self::AccessRestrictions, "AccessRestrictions"),
+            (Self::AccessLevelEx, "AccessLevelEx"),
+        ];
+        let mut first = true;
+        for (flag, name) in flags {
+            if flag.is_empty() || !self.contains(flag) {
+                continue;
+            }
+            if !first {
+                f.write_str(", ")?;
+            }
+            f.write_str(name)?;
+            first = false;
+        }
+        Ok(())
+    }
+}
 #[cfg(feature = "xml")]
 impl opcua::types::xml::XmlType for AttributeWriteMask {
     const TAG: &'static str = "AttributeWriteMask";
@@ -208,6 +385,15 @@ pub enum AxisScaleEnumeration {
     Log = 1i32,
     Ln = 2i32,
 }
+impl std::fmt::Display for AxisScaleEnumeration {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Linear => "Linear",
+            Self::Log => "Log",
+            Self::Ln => "Ln",
+        })
+    }
+}
 #[opcua::types::ua_encodable]
 ///https://reference.opcfoundation.org/v105/Core/docs/Part14/6.4.2/#6.4.2.1
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -220,6 +406,17 @@ pub enum BrokerTransportQualityOfService {
     AtMostOnce = 3i32,
     ExactlyOnce = 4i32,
 }
+impl std::fmt::Display for BrokerTransportQualityOfService {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::NotSpecified => "NotSpecified",
+            Self::BestEffort => "BestEffort",
+            Self::AtLeastOnce => "AtLeastOnce",
+            Self::AtMostOnce => "AtMostOnce",
+            Self::ExactlyOnce => "ExactlyOnce",
+        })
+    }
+}
 #[opcua::types::ua_encodable]
 ///https://reference.opcfoundation.org/v105/Core/docs/Part4/7.5
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -231,6 +428,16 @@ pub enum BrowseDirection {
     Both = 2i32,
     Invalid = 3i32,
 }
+impl std::fmt::Display for BrowseDirection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Forward => "Forward",
+            Self::Inverse => "Inverse",
+            Self::Both => "Both",
+            Self::Invalid => "Invalid",
+        })
+    }
+}
 #[opcua::types::ua_encodable]
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 #[repr(i32)]
@@ -247,6 +454,22 @@ pub enum BrowseResultMask {
     ReferenceTypeInfo = 3i32,
     TargetInfo = 60i32,
 }
+impl std::fmt::Display for BrowseResultMask {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::None => "None",
+            Self::ReferenceTypeId => "ReferenceTypeId",
+            Self::IsForward => "IsForward",
+            Self::NodeClass => "NodeClass",
+            Self::BrowseName => "BrowseName",
+            Self::DisplayName => "DisplayName",
+            Self::TypeDefinition => "TypeDefinition",
+            Self::All => "All",
+            Self::ReferenceTypeInfo => "ReferenceTypeInfo",
+            Self::TargetInfo => "TargetInfo",
+        })
+    }
+}
 #[opcua::types::ua_encodable]
 ///https://reference.opcfoundation.org/v105/Core/docs/Part22/5.3.1/#5.3.1.9
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -267,6 +490,19 @@ pub enum ChassisIdSubtype {
     ///Represents a chassis identifier based on a locally defined value.
     Local = 7i32,
 }
+impl std::fmt::Display for ChassisIdSubtype {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::ChassisComponent => "ChassisComponent",
+            Self::InterfaceAlias => "InterfaceAlias",
+            Self::PortComponent => "PortComponent",
+            Self::MacAddress => "MacAddress",
+            Self::NetworkAddress => "NetworkAddress",
+            Self::InterfaceName => "InterfaceName",
+            Self::Local => "Local",
+        })
+    }
+}
 #[opcua::types::ua_encodable]
 ///https://reference.opcfoundation.org/v105/Core/docs/Part8/6.6.3
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -277,6 +513,15 @@ pub enum ConversionLimitEnum {
     Limited = 1i32,
     Unlimited = 2i32,
 }
+impl std::fmt::Display for ConversionLimitEnum {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::NoConversion => "NoConversion",
+            Self::Limited => "Limited",
+            Self::Unlimited => "Unlimited",
+        })
+    }
+}
 #[opcua::types::ua_encodable]
 ///https://reference.opcfoundation.org/v105/Core/docs/Part4/7.10
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -287,6 +532,15 @@ pub enum DataChangeTrigger {
     StatusValue = 1i32,
     StatusValueTimestamp = 2i32,
 }
+impl std::fmt::Display for DataChangeTrigger {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Status => "Status",
+            Self::StatusValue => "StatusValue",
+            Self::StatusValueTimestamp => "StatusValueTimestamp",
+        })
+    }
+}
 bitflags::bitflags! {
     #[derive(Debug, Copy, Clone, PartialEq)] #[doc =
     "https://reference.opcfoundation.org/v105/Core/docs/Part14/6.2.4/#6.2.4.2"] pub
@@ -315,6 +569,33 @@ impl opcua::types::IntoVariant for DataSetFieldContentMask {
         self.bits().into_variant()
     }
 }
+impl std::fmt::Display for DataSetFieldContentMask {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_empty() {
+            return f.write_str("0");
+        }
+        let flags: [(Self, &str); 6] = [
+            (Self::StatusCode, "StatusCode"),
+            (Self::SourceTimestamp, "SourceTimestamp"),
+            (Self::ServerTimestamp, "ServerTimestamp"),
+            (Self::SourcePicoSeconds, "SourcePicoSeconds"),
+            (Self::ServerPicoSeconds, "ServerPicoSeconds"),
+            (Self::RawData, "RawData"),
+        ];
+        let mut first = true;
+        for (flag, name) in flags {
+            if flag.is_empty() || !self.contains(flag) {
+                continue;
+            }
+            if !first {
+                f.write_str(", ")?;
+            }
+            f.write_str(name)?;
+            first = false;
+        }
+        Ok(())
+    }
+}
 #[cfg(feature = "xml")]
 impl opcua::types::xml::XmlType for DataSetFieldContentMask {
     const TAG: &'static str = "DataSetFieldContentMask";
@@ -345,6 +626,26 @@ impl opcua::types::IntoVariant for DataSetFieldFlags {
         self.bits().into_variant()
     }
 }
+impl std::fmt::Display for DataSetFieldFlags {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_empty() {
+            return f.write_str("0");
+        }
+        let flags: [(Self, &str); 1] = [(Self::PromotedField, "PromotedField")];
+        let mut first = true;
+        for (flag, name) in flags {
+            if flag.is_empty() || !self.contains(flag) {
+                continue;
+            }
+            if !first {
+                f.write_str(", ")?;
+            }
+            f.write_str(name)?;
+            first = false;
+        }
+        Ok(())
+    }
+}
 #[cfg(feature = "xml")]
 impl opcua::types::xml::XmlType for DataSetFieldFlags {
     const TAG: &'static str = "DataSetFieldFlags";
@@ -359,6 +660,15 @@ pub enum DataSetOrderingType {
     AscendingWriterId = 1i32,
     AscendingWriterIdSingle = 2i32,
 }
+impl std::fmt::Display for DataSetOrderingType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Undefined => "Undefined",
+            Self::AscendingWriterId => "AscendingWriterId",
+            Self::AscendingWriterIdSingle => "AscendingWriterIdSingle",
+        })
+    }
+}
 #[opcua::types::ua_encodable]
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 #[repr(i32)]
@@ -368,6 +678,15 @@ pub enum DeadbandType {
     Absolute = 1i32,
     Percent = 2i32,
 }
+impl std::fmt::Display for DeadbandType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::None => "None",
+            Self::Absolute => "Absolute",
+            Self::Percent => "Percent",
+        })
+    }
+}
 #[opcua::types::ua_encodable]
 ///https://reference.opcfoundation.org/v105/Core/docs/Part14/9.1.11/#9.1.11.4
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -380,6 +699,17 @@ pub enum DiagnosticsLevel {
     Log = 3i32,
     Debug = 4i32,
 }
+impl std::fmt::Display for DiagnosticsLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Basic => "Basic",
+            Self::Advanced => "Advanced",
+            Self::Info => "Info",
+            Self::Log => "Log",
+            Self::Debug => "Debug",
+        })
+    }
+}
 #[opcua::types::ua_encodable]
 ///https://reference.opcfoundation.org/v105/Core/docs/Part22/5.3.1/#5.3.1.1
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -393,6 +723,15 @@ pub enum Duplex {
     ///Link is currently disconnected or initializing.
     Unknown = 2i32,
 }
+impl std::fmt::Display for Duplex {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Full => "Full",
+            Self::Half => "Half",
+            Self::Unknown => "Unknown",
+        })
+    }
+}
 bitflags::bitflags! {
     #[derive(Debug, Copy, Clone, PartialEq)] #[doc =
     "https://reference.opcfoundation.org/v105/Core/docs/Part3/8.59"] pub struct
@@ -420,6 +759,30 @@ impl opcua::types::IntoVariant for EventNotifierType {
         self.bits().into_variant()
     }
 }
+impl std::fmt::Display for EventNotifierType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_empty() {
+            return f.write_str("0");
+        }
+        let flags: [(Self, &str); 3] = [
+            (Self::SubscribeToEvents, "SubscribeToEvents"),
+            (Self::HistoryRead, "HistoryRead"),
+            (Self::HistoryWrite, "HistoryWrite"),
+        ];
+        let mut first = true;
+        for (flag, name) in flags {
+            if flag.is_empty() || !self.contains(flag) {
+                continue;
+            }
+            if !first {
+                f.write_str(", ")?;
+            }
+            f.write_str(name)?;
+            first = false;
+        }
+        Ok(())
+    }
+}
 #[cfg(feature = "xml")]
 impl opcua::types::xml::XmlType for EventNotifierType {
     const TAG: &'static str = "EventNotifierType";
@@ -436,6 +799,17 @@ pub enum ExceptionDeviationFormat {
     PercentOfEURange = 3i32,
     Unknown = 4i32,
 }
+impl std::fmt::Display for ExceptionDeviationFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::AbsoluteValue => "AbsoluteValue",
+            Self::PercentOfValue => "PercentOfValue",
+            Self::PercentOfRange => "PercentOfRange",
+            Self::PercentOfEURange => "PercentOfEURange",
+            Self::Unknown => "Unknown",
+        })
+    }
+}
 #[opcua::types::ua_encodable]
 ///https://reference.opcfoundation.org/v105/Core/docs/Part4/7.7.3
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -461,6 +835,30 @@ pub enum FilterOperator {
     BitwiseAnd = 16i32,
     BitwiseOr = 17i32,
 }
+impl std::fmt::Display for FilterOperator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Equals => "Equals",
+            Self::IsNull => "IsNull",
+            Self::GreaterThan => "GreaterThan",
+            Self::LessThan => "LessThan",
+            Self::GreaterThanOrEqual => "GreaterThanOrEqual",
+            Self::LessThanOrEqual => "LessThanOrEqual",
+            Self::Like => "Like",
+            Self::Not => "Not",
+            Self::Between => "Between",
+            Self::InList => "InList",
+            Self::And => "And",
+            Self::Or => "Or",
+            Self::Cast => "Cast",
+            Self::InView => "InView",
+            Self::OfType => "OfType",
+            Self::RelatedTo => "RelatedTo",
+            Self::BitwiseAnd => "BitwiseAnd",
+            Self::BitwiseOr => "BitwiseOr",
+        })
+    }
+}
 #[opcua::types::ua_encodable]
 ///https://reference.opcfoundation.org/v105/Core/docs/Part11/6.7
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -475,6 +873,16 @@ pub enum HistoryUpdateType {
     ///Data was deleted.
     Delete = 4i32,
 }
+impl std::fmt::Display for HistoryUpdateType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Insert => "Insert",
+            Self::Replace => "Replace",
+            Self::Update => "Update",
+            Self::Delete => "Delete",
+        })
+    }
+}
 #[opcua::types::ua_encodable]
 ///https://reference.opcfoundation.org/v105/Core/docs/Part18/4.4.4
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -499,6 +907,21 @@ pub enum IdentityCriteriaType {
     ///The rule specifies any trusted application that has been authenticated with a trusted ApplicationInstance Certificate.
     TrustedApplication = 9i32,
 }
+impl std::fmt::Display for IdentityCriteriaType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::UserName => "UserName",
+            Self::Thumbprint => "Thumbprint",
+            Self::Role => "Role",
+            Self::GroupId => "GroupId",
+            Self::Anonymous => "Anonymous",
+            Self::AuthenticatedUser => "AuthenticatedUser",
+            Self::Application => "Application",
+            Self::X509Subject => "X509Subject",
+            Self::TrustedApplication => "TrustedApplication",
+        })
+    }
+}
 #[opcua::types::ua_encodable]
 ///https://reference.opcfoundation.org/v105/Core/docs/Part5/12.2.5/#12.2.5.1
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -510,6 +933,16 @@ pub enum IdType {
     Guid = 2i32,
     Opaque = 3i32,
 }
+impl std::fmt::Display for IdType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Numeric => "Numeric",
+            Self::String => "String",
+            Self::Guid => "Guid",
+            Self::Opaque => "Opaque",
+        })
+    }
+}
 #[opcua::types::ua_encodable]
 ///https://reference.opcfoundation.org/v105/Core/docs/Part22/5.3.1/#5.3.1.2
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -523,6 +956,15 @@ pub enum InterfaceAdminStatus {
     ///In some test mode.
     Testing = 2i32,
 }
+impl std::fmt::Display for InterfaceAdminStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Up => "Up",
+            Self::Down => "Down",
+            Self::Testing => "Testing",
+        })
+    }
+}
 #[opcua::types::ua_encodable]
 ///https://reference.opcfoundation.org/v105/Core/docs/Part22/5.3.1/#5.3.1.3
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -544,6 +986,19 @@ pub enum InterfaceOperStatus {
     ///Down due to state of lower-layer interface(s).
     LowerLayerDown = 6i32,
 }
+impl std::fmt::Display for InterfaceOperStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Up => "Up",
+            Self::Down => "Down",
+            Self::Testing => "Testing",
+            Self::Unknown => "Unknown",
+            Self::Dormant => "Dormant",
+            Self::NotPresent => "NotPresent",
+            Self::LowerLayerDown => "LowerLayerDown",
+        })
+    }
+}
 bitflags::bitflags! {
     #[derive(Debug, Copy, Clone, PartialEq)] #[doc =
     "https://reference.opcfoundation.org/v105/Core/docs/Part14/6.3.2/#6.3.2.3.1"] pub
@@ -574,6 +1029,39 @@ impl opcua::types::IntoVariant for JsonDataSetMessageContentMask {
         self.bits().into_variant()
     }
 }
+impl std::fmt::Display for JsonDataSetMessageContentMask {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_empty() {
+            return f.write_str("0");
+        }
+        let flags: [(Self, &str); 12] = [
+            (Self::DataSetWriterId, "DataSetWriterId"),
+            (Self::MetaDataVersion, "MetaDataVersion"),
+            (Self::SequenceNumber, "SequenceNumber"),
+            (Self::Timestamp, "Timestamp"),
+            (Self::Status, "Status"),
+            (Self::MessageType, "MessageType"),
+            (Self::DataSetWriterName, "DataSetWriterName"),
+            (Self::FieldEncoding1, "FieldEncoding1"),
+            (Self::PublisherId, "PublisherId"),
+            (Self::WriterGroupName, "WriterGroupName"),
+            (Self::MinorVersion, "MinorVersion"),
+            (Self::FieldEncoding2, "FieldEncoding2"),
+        ];
+        let mut first = true;
+        for (flag, name) in flags {
+            if flag.is_empty() || !self.contains(flag) {
+                continue;
+            }
+            if !first {
+                f.write_str(", ")?;
+            }
+            f.write_str(name)?;
+            first = false;
+        }
+        Ok(())
+    }
+}
 #[cfg(feature = "xml")]
 impl opcua::types::xml::XmlType for JsonDataSetMessageContentMask {
     const TAG: &'static str = "JsonDataSetMessageContentMask";
@@ -607,6 +1095,34 @@ impl opcua::types::IntoVariant for JsonNetworkMessageContentMask {
         self.bits().into_variant()
     }
 }
+impl std::fmt::Display for JsonNetworkMessageContentMask {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_empty() {
+            return f.write_str("0");
+        }
+        let flags: [(Self, &str); 7] = [
+            (Self::NetworkMessageHeader, "NetworkMessageHeader"),
+            (Self::DataSetMessageHeader, "DataSetMessageHeader"),
+            (Self::SingleDataSetMessage, "SingleDataSetMessage"),
+            (Self::PublisherId, "PublisherId"),
+            (Self::DataSetClassId, "DataSetClassId"),
+            (Self::ReplyTo, "ReplyTo"),
+            (Self::WriterGroupName, "WriterGroupName"),
+        ];
+        let mut first = true;
+        for (flag, name) in flags {
+            if flag.is_empty() || !self.contains(flag) {
+                continue;
+            }
+            if !first {
+                f.write_str(", ")?;
+            }
+            f.write_str(name)?;
+            first = false;
+        }
+        Ok(())
+    }
+}
 #[cfg(feature = "xml")]
 impl opcua::types::xml::XmlType for JsonNetworkMessageContentMask {
     const TAG: &'static str = "JsonNetworkMessageContentMask";
@@ -649,6 +1165,38 @@ impl opcua::types::IntoVariant for LldpSystemCapabilitiesMap {
         self.bits().into_variant()
     }
 }
+impl std::fmt::Display for LldpSystemCapabilitiesMap {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_empty() {
+            return f.write_str("0");
+        }
+        let flags: [(Self, &str); 11] = [
+            (Self::Other, "Other"),
+            (Self::Repeater, "Repeater"),
+            (Self::Bridge, "Bridge"),
+            (Self::WlanAccessPoint, "WlanAccessPoint"),
+            (Self::Router, "Router"),
+            (Self::Telephone, "Telephone"),
+            (Self::DocsisCableDevice, "DocsisCableDevice"),
+            (Self::StationOnly, "StationOnly"),
+            (Self::CvlanComponent, "CvlanComponent"),
+            (Self::SvlanComponent, "SvlanComponent"),
+            (Self::TwoPortMacRelay, "TwoPortMacRelay"),
+        ];
+        let mut first = true;
+        for (flag, name) in flags {
+            if flag.is_empty() || !self.contains(flag) {
+                continue;
+            }
+            if !first {
+                f.write_str(", ")?;
+            }
+            f.write_str(name)?;
+            first = false;
+        }
+        Ok(())
+    }
+}
 #[cfg(feature = "xml")]
 impl opcua::types::xml::XmlType for LldpSystemCapabilitiesMap {
     const TAG: &'static str = "LldpSystemCapabilitiesMap";
@@ -668,6 +1216,16 @@ pub enum ManAddrIfSubtype {
     ///Interface based on the system port number.
     SystemPortNumber = 3i32,
 }
+impl std::fmt::Display for ManAddrIfSubtype {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::None => "None",
+            Self::Unknown => "Unknown",
+            Self::PortRef => "PortRef",
+            Self::SystemPortNumber => "SystemPortNumber",
+        })
+    }
+}
 #[opcua::types::ua_encodable]
 ///https://reference.opcfoundation.org/v105/Core/docs/Part5/12.3.10
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -679,6 +1237,16 @@ pub enum MessageSecurityMode {
     Sign = 2i32,
     SignAndEncrypt = 3i32,
 }
+impl std::fmt::Display for MessageSecurityMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Invalid => "Invalid",
+            Self::None => "None",
+            Self::Sign => "Sign",
+            Self::SignAndEncrypt => "SignAndEncrypt",
+        })
+    }
+}
 #[opcua::types::ua_encodable]
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 #[repr(i32)]
@@ -689,6 +1257,17 @@ pub enum ModelChangeStructureVerbMask {
     ReferenceDeleted = 8i32,
     DataTypeChanged = 16i32,
 }
+impl std::fmt::Display for ModelChangeStructureVerbMask {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::NodeAdded => "NodeAdded",
+            Self::NodeDeleted => "NodeDeleted",
+            Self::ReferenceAdded => "ReferenceAdded",
+            Self::ReferenceDeleted => "ReferenceDeleted",
+            Self::DataTypeChanged => "DataTypeChanged",
+        })
+    }
+}
 #[opcua::types::ua_encodable]
 ///https://reference.opcfoundation.org/v105/Core/docs/Part4/7.23
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -699,6 +1278,15 @@ pub enum MonitoringMode {
     Sampling = 1i32,
     Reporting = 2i32,
 }
+impl std::fmt::Display for MonitoringMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Disabled => "Disabled",
+            Self::Sampling => "Sampling",
+            Self::Reporting => "Reporting",
+        })
+    }
+}
 #[opcua::types::ua_encodable]
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 #[repr(i32)]
@@ -710,6 +1298,15 @@ pub enum NamingRuleType {
     ///The modelling rule defines a constraint and the BrowseName is not used in an instance of the type.
     Constraint = 3i32,
 }
+impl std::fmt::Display for NamingRuleType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Mandatory => "Mandatory",
+            Self::Optional => "Optional",
+            Self::Constraint => "Constraint",
+        })
+    }
+}
 #[opcua::types::ua_encodable]
 ///https://reference.opcfoundation.org/v105/Core/docs/Part22/5.3.1/#5.3.1.4
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -727,6 +1324,17 @@ pub enum NegotiationStatus {
     ///No auto-negotiation is executed. The auto-negotiation function is either not supported on this interface or has not been enabled.
     NoNegotiation = 4i32,
 }
+impl std::fmt::Display for NegotiationStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::InProgress => "InProgress",
+            Self::Complete => "Complete",
+            Self::Failed => "Failed",
+            Self::Unknown => "Unknown",
+            Self::NoNegotiation => "NoNegotiation",
+        })
+    }
+}
 #[opcua::types::ua_encodable]
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 #[repr(i32)]
@@ -768,6 +1376,47 @@ pub enum NodeAttributesMask {
     ReferenceType = 26537060i32,
     View = 26501356i32,
 }
+impl std::fmt::Display for NodeAttributesMask {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::None => "None",
+            Self::AccessLevel => "AccessLevel",
+            Self::ArrayDimensions => "ArrayDimensions",
+            Self::BrowseName => "BrowseName",
+            Self::ContainsNoLoops => "ContainsNoLoops",
+            Self::DataType => "DataType",
+            Self::Description => "Description",
+            Self::DisplayName => "DisplayName",
+            Self::EventNotifier => "EventNotifier",
+            Self::Executable => "Executable",
+            Self::Historizing => "Historizing",
+            Self::InverseName => "InverseName",
+            Self::IsAbstract => "IsAbstract",
+            Self::MinimumSamplingInterval => "MinimumSamplingInterval",
+            Self::NodeClass => "NodeClass",
+            Self::NodeId => "NodeId",
+            Self::Symmetric => "Symmetric",
+            Self::UserAccessLevel => "UserAccessLevel",
+            Self::UserExecutable => "UserExecutable",
+            Self::UserWriteMask => "UserWriteMask",
+            Self::ValueRank => "ValueRank",
+            Self::WriteMask => "WriteMask",
+            Self::Value => "Value",
+            Self::DataTypeDefinition => "DataTypeDefinition",
+            Self::RolePermissions => "RolePermissions",
+            Self::AccessRestrictions => "AccessRestrictions",
+            Self::All => "All",
+            Self::BaseNode => "BaseNode",
+            Self::Object => "Object",
+            Self::ObjectType => "ObjectType",
+            Self::Variable => "Variable",
+            Self::VariableType => "VariableType",
+            Self::Method => "Method",
+            Self::ReferenceType => "ReferenceType",
+            Self::View => "View",
+        })
+    }
+}
 #[opcua::types::ua_encodable]
 ///https://reference.opcfoundation.org/v105/Core/docs/Part5/12.2.5/#12.2.5.2
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -793,6 +1442,21 @@ pub enum NodeClass {
     ///The Node is a View.
     View = 128i32,
 }
+impl std::fmt::Display for NodeClass {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Unspecified => "Unspecified",
+            Self::Object => "Object",
+            Self::Variable => "Variable",
+            Self::Method => "Method",
+            Self::ObjectType => "ObjectType",
+            Self::VariableType => "VariableType",
+            Self::ReferenceType => "ReferenceType",
+            Self::DataType => "DataType",
+            Self::View => "View",
+        })
+    }
+}
 #[opcua::types::ua_encodable]
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 #[repr(i32)]
@@ -802,6 +1466,16 @@ pub enum OpenFileMode {
     EraseExisting = 4i32,
     Append = 8i32,
 }
+impl std::fmt::Display for OpenFileMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Read => "Read",
+            Self::Write => "Write",
+            Self::EraseExisting => "EraseExisting",
+            Self::Append => "Append",
+        })
+    }
+}
 #[opcua::types::ua_encodable]
 ///https://reference.opcfoundation.org/v105/Core/docs/Part14/6.2.10/#6.2.10.2.4
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -812,6 +1486,15 @@ pub enum OverrideValueHandling {
     LastUsableValue = 1i32,
     OverrideValue = 2i32,
 }
+impl std::fmt::Display for OverrideValueHandling {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Disabled => "Disabled",
+            Self::LastUsableValue => "LastUsableValue",
+            Self::OverrideValue => "OverrideValue",
+        })
+    }
+}
 bitflags::bitflags! {
     #[derive(Debug, Copy, Clone, PartialEq)] #[doc =
     "https://reference.opcfoundation.org/v105/Core/docs/Part18/5.2.2"] pub struct
@@ -856,6 +1539,48 @@ impl opcua::types::IntoVariant for PasswordOptionsMask {
         self.bits().into_variant()
     }
 }
+impl std::fmt::Display for PasswordOptionsMask {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_empty() {
+            return f.write_str("0");
+        }
+        let flags: [(Self, &str); 9] = [
+            (
+                Self::SupportInitialPasswordChange,
+                "SupportInitialPasswordChange",
+            ),
+            (Self::SupportDisableUser, "SupportDisableUser"),
+            (
+                Self::SupportDisableDeleteForUser,
+                "SupportDisableDeleteForUser",
+            ),
+            (Self::SupportNoChangeForUser, "SupportNoChangeForUser"),
+            (Self::SupportDescriptionForUser, "SupportDescriptionForUser"),
+            (
+                Self::RequiresUpperCaseCharacters,
+                "RequiresUpperCaseCharacters",
+            ),
+            (
+                Self::RequiresLowerCaseCharacters,
+                "RequiresLowerCaseCharacters",
+            ),
+            (Self::RequiresDigitCharacters, "RequiresDigitCharacters"),
+            (Self::RequiresSpecialCharacters, "RequiresSpecialCharacters"),
+        ];
+        let mut first = true;
+        for (flag, name) in flags {
+            if flag.is_empty() || !self.contains(flag) {
+                continue;
+            }
+            if !first {
+                f.write_str(", ")?;
+            }
+            f.write_str(name)?;
+            first = false;
+        }
+        Ok(())
+    }
+}
 #[cfg(feature = "xml")]
 impl opcua::types::xml::XmlType for PasswordOptionsMask {
     const TAG: &'static str = "PasswordOptionsMask";
@@ -874,6 +1599,16 @@ pub enum PerformUpdateType {
     ///Data was deleted.
     Remove = 4i32,
 }
+impl std::fmt::Display for PerformUpdateType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Insert => "Insert",
+            Self::Replace => "Replace",
+            Self::Update => "Update",
+            Self::Remove => "Remove",
+        })
+    }
+}
 bitflags::bitflags! {
     #[derive(Debug, Copy, Clone, PartialEq)] #[doc =
     "https://reference.opcfoundation.org/v105/Core/docs/Part3/8.55"] pub struct
@@ -906,6 +1641,44 @@ impl opcua::types::IntoVariant for PermissionType {
         self.bits().into_variant()
     }
 }
+impl std::fmt::Display for PermissionType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_empty() {
+            return f.write_str("0");
+        }
+        let flags: [(Self, &str); 17] = [
+            (Self::Browse, "Browse"),
+            (Self::ReadRolePermissions, "ReadRolePermissions"),
+            (Self::WriteAttribute, "WriteAttribute"),
+            (Self::WriteRolePermissions, "WriteRolePermissions"),
+            (Self::WriteHistorizing, "WriteHistorizing"),
+            (Self::Read, "Read"),
+            (Self::Write, "Write"),
+            (Self::ReadHistory, "ReadHistory"),
+            (Self::InsertHistory, "InsertHistory"),
+            (Self::ModifyHistory, "ModifyHistory"),
+            (Self::DeleteHistory, "DeleteHistory"),
+            (Self::ReceiveEvents, "ReceiveEvents"),
+            (Self::Call, "Call"),
+            (Self::AddReference, "AddReference"),
+            (Self::RemoveReference, "RemoveReference"),
+            (Self::DeleteNode, "DeleteNode"),
+            (Self::AddNode, "AddNode"),
+        ];
+        let mut first = true;
+        for (flag, name) in flags {
+            if flag.is_empty() || !self.contains(flag) {
+                continue;
+            }
+            if !first {
+                f.write_str(", ")?;
+            }
+            f.write_str(name)?;
+            first = false;
+        }
+        Ok(())
+    }
+}
 #[cfg(feature = "xml")]
 impl opcua::types::xml::XmlType for PermissionType {
     const TAG: &'static str = "PermissionType";
@@ -930,6 +1703,19 @@ pub enum PortIdSubtype {
     ///Represents a port identifier based on a value locally assigned.
     Local = 7i32,
 }
+impl std::fmt::Display for PortIdSubtype {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::InterfaceAlias => "InterfaceAlias",
+            Self::PortComponent => "PortComponent",
+            Self::MacAddress => "MacAddress",
+            Self::NetworkAddress => "NetworkAddress",
+            Self::InterfaceName => "InterfaceName",
+            Self::AgentCircuitId => "AgentCircuitId",
+            Self::Local => "Local",
+        })
+    }
+}
 bitflags::bitflags! {
     #[derive(Debug, Copy, Clone, PartialEq)] #[doc =
     "https://reference.opcfoundation.org/v105/Core/docs/Part14/9.1.3/#9.1.3.7.2"] pub
@@ -961,6 +1747,40 @@ impl opcua::types::IntoVariant for PubSubConfigurationRefMask {
         self.bits().into_variant()
     }
 }
+impl std::fmt::Display for PubSubConfigurationRefMask {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_empty() {
+            return f.write_str("0");
+        }
+        let flags: [(Self, &str); 13] = [
+            (Self::ElementAdd, "ElementAdd"),
+            (Self::ElementMatch, "ElementMatch"),
+            (Self::ElementModify, "ElementModify"),
+            (Self::ElementRemove, "ElementRemove"),
+            (Self::ReferenceWriter, "ReferenceWriter"),
+            (Self::ReferenceReader, "ReferenceReader"),
+            (Self::ReferenceWriterGroup, "ReferenceWriterGroup"),
+            (Self::ReferenceReaderGroup, "ReferenceReaderGroup"),
+            (Self::ReferenceConnection, "ReferenceConnection"),
+            (Self::ReferencePubDataset, "ReferencePubDataset"),
+            (Self::ReferenceSubDataset, "ReferenceSubDataset"),
+            (Self::ReferenceSecurityGroup, "ReferenceSecurityGroup"),
+            (Self::ReferencePushTarget, "ReferencePushTarget"),
+        ];
+        let mut first = true;
+        for (flag, name) in flags {
+            if flag.is_empty() || !self.contains(flag) {
+                continue;
+            }
+            if !first {
+                f.write_str(", ")?;
+            }
+            f.write_str(name)?;
+            first = false;
+        }
+        Ok(())
+    }
+}
 #[cfg(feature = "xml")]
 impl opcua::types::xml::XmlType for PubSubConfigurationRefMask {
     const TAG: &'static str = "PubSubConfigurationRefMask";
@@ -974,6 +1794,14 @@ pub enum PubSubDiagnosticsCounterClassification {
     Information = 0i32,
     Error = 1i32,
 }
+impl std::fmt::Display for PubSubDiagnosticsCounterClassification {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Information => "Information",
+            Self::Error => "Error",
+        })
+    }
+}
 #[opcua::types::ua_encodable]
 ///https://reference.opcfoundation.org/v105/Core/docs/Part14/6.2.1
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -986,6 +1814,17 @@ pub enum PubSubState {
     Error = 3i32,
     PreOperational = 4i32,
 }
+impl std::fmt::Display for PubSubState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Disabled => "Disabled",
+            Self::Paused => "Paused",
+            Self::Operational => "Operational",
+            Self::Error => "Error",
+            Self::PreOperational => "PreOperational",
+        })
+    }
+}
 #[opcua::types::ua_encodable]
 ///https://reference.opcfoundation.org/v105/Core/docs/Part5/12.5
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -999,6 +1838,18 @@ pub enum RedundancySupport {
     Transparent = 4i32,
     HotAndMirrored = 5i32,
 }
+impl std::fmt::Display for RedundancySupport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::None => "None",
+            Self::Cold => "Cold",
+            Self::Warm => "Warm",
+            Self::Hot => "Hot",
+            Self::Transparent => "Transparent",
+            Self::HotAndMirrored => "HotAndMirrored",
+        })
+    }
+}
 #[opcua::types::ua_encodable]
 ///https://reference.opcfoundation.org/v105/Core/docs/Part5/12.44
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -1010,6 +1861,16 @@ pub enum RedundantServerMode {
     BackupReady = 2i32,
     BackupNotReady = 3i32,
 }
+impl std::fmt::Display for RedundantServerMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::PrimaryWithBackup => "PrimaryWithBackup",
+            Self::PrimaryOnly => "PrimaryOnly",
+            Self::BackupReady => "BackupReady",
+            Self::BackupNotReady => "BackupNotReady",
+        })
+    }
+}
 #[opcua::types::ua_encodable]
 ///https://reference.opcfoundation.org/v105/Core/docs/Part5/12.3.12
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -1019,6 +1880,14 @@ pub enum SecurityTokenRequestType {
     Issue = 0i32,
     Renew = 1i32,
 }
+impl std::fmt::Display for SecurityTokenRequestType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Issue => "Issue",
+            Self::Renew => "Renew",
+        })
+    }
+}
 #[opcua::types::ua_encodable]
 ///https://reference.opcfoundation.org/v105/Core/docs/Part5/12.6
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -1034,6 +1903,20 @@ pub enum ServerState {
     CommunicationFault = 6i32,
     Unknown = 7i32,
 }
+impl std::fmt::Display for ServerState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Running => "Running",
+            Self::Failed => "Failed",
+            Self::NoConfiguration => "NoConfiguration",
+            Self::Suspended => "Suspended",
+            Self::Shutdown => "Shutdown",
+            Self::Test => "Test",
+            Self::CommunicationFault => "CommunicationFault",
+            Self::Unknown => "Unknown",
+        })
+    }
+}
 #[opcua::types::ua_encodable]
 ///https://reference.opcfoundation.org/v105/Core/docs/Part11/6.5.8
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -1043,6 +1926,14 @@ pub enum SortOrderType {
     Ascending = 0i32,
     Descending = 1i32,
 }
+impl std::fmt::Display for SortOrderType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Ascending => "Ascending",
+            Self::Descending => "Descending",
+        })
+    }
+}
 #[opcua::types::ua_encodable]
 ///https://reference.opcfoundation.org/v105/Core/docs/Part5/12.2.5/#12.2.5.3
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -1055,6 +1946,17 @@ pub enum StructureType {
     StructureWithSubtypedValues = 3i32,
     UnionWithSubtypedValues = 4i32,
 }
+impl std::fmt::Display for StructureType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Structure => "Structure",
+            Self::StructureWithOptionalFields => "StructureWithOptionalFields",
+            Self::Union => "Union",
+            Self::StructureWithSubtypedValues => "StructureWithSubtypedValues",
+            Self::UnionWithSubtypedValues => "UnionWithSubtypedValues",
+        })
+    }
+}
 #[opcua::types::ua_encodable]
 ///https://reference.opcfoundation.org/v105/Core/docs/Part4/7.40
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -1067,6 +1969,17 @@ pub enum TimestampsToReturn {
     Neither = 3i32,
     Invalid = 4i32,
 }
+impl std::fmt::Display for TimestampsToReturn {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Source => "Source",
+            Self::Server => "Server",
+            Self::Both => "Both",
+            Self::Neither => "Neither",
+            Self::Invalid => "Invalid",
+        })
+    }
+}
 #[opcua::types::ua_encodable]
 ///https://reference.opcfoundation.org/v105/Core/docs/Part12/7.8.2/#7.8.2.7
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -1086,6 +1999,18 @@ pub enum TrustListMasks {
     ///All fields are provided.
     All = 15i32,
 }
+impl std::fmt::Display for TrustListMasks {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::None => "None",
+            Self::TrustedCertificates => "TrustedCertificates",
+            Self::TrustedCrls => "TrustedCrls",
+            Self::IssuerCertificates => "IssuerCertificates",
+            Self::IssuerCrls => "IssuerCrls",
+            Self::All => "All",
+        })
+    }
+}
 bitflags::bitflags! {
     #[derive(Debug, Copy, Clone, PartialEq)] #[doc =
     "https://reference.opcfoundation.org/v105/Core/docs/Part12/7.8.2/#7.8.2.8"] pub
@@ -1125,6 +2050,52 @@ impl opcua::types::IntoVariant for TrustListValidationOptions {
         self.bits().into_variant()
     }
 }
+impl std::fmt::Display for TrustListValidationOptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_empty() {
+            return f.write_str("0");
+        }
+        let flags: [(Self, &str); 7] = [
+            (
+                Self::SuppressCertificateExpired,
+                "SuppressCertificateExpired",
+            ),
+            (Self::SuppressHostNameInvalid, "SuppressHostNameInvalid"),
+            (
+                Self::SuppressRevocationStatusUnknown,
+                "SuppressRevocationStatusUnknown",
+            ),
+            (
+                Self::SuppressIssuerCertificateExpired,
+                "SuppressIssuerCertificateExpired",
+            ),
+            (
+                Self::SuppressIssuerRevocationStatusUnknown,
+                "SuppressIssuerRevocationStatusUnknown",
+            ),
+            (
+                Self::CheckRevocationStatusOnline,
+                "CheckRevocationStatusOnline",
+            ),
+            (
+                Self::CheckRevocationStatusOffline,
+                "CheckRevocationStatusOffline",
+            ),
+        ];
+        let mut first = true;
+        for (flag, name) in flags {
+            if flag.is_empty() || !self.contains(flag) {
+                continue;
+            }
+            if !first {
+                f.write_str(", ")?;
+            }
+            f.write_str(name)?;
+            first = false;
+        }
+        Ok(())
+    }
+}
 #[cfg(feature = "xml")]
 impl opcua::types::xml::XmlType for TrustListValidationOptions {
     const TAG: &'static str = "TrustListValidationOptions";
@@ -1188,6 +2159,38 @@ pub enum TsnFailureCode {
     ///Enhanced feature cannot be supported without a CNC
     FeatureNotSupported = 25i32,
 }
+impl std::fmt::Display for TsnFailureCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::NoFailure => "NoFailure",
+            Self::InsufficientBandwidth => "InsufficientBandwidth",
+            Self::InsufficientResources => "InsufficientResources",
+            Self::InsufficientTrafficClassBandwidth => "InsufficientTrafficClassBandwidth",
+            Self::StreamIdInUse => "StreamIdInUse",
+            Self::StreamDestinationAddressInUse => "StreamDestinationAddressInUse",
+            Self::StreamPreemptedByHigherRank => "StreamPreemptedByHigherRank",
+            Self::LatencyHasChanged => "LatencyHasChanged",
+            Self::EgressPortNotAvbCapable => "EgressPortNotAvbCapable",
+            Self::UseDifferentDestinationAddress => "UseDifferentDestinationAddress",
+            Self::OutOfMsrpResources => "OutOfMsrpResources",
+            Self::OutOfMmrpResources => "OutOfMmrpResources",
+            Self::CannotStoreDestinationAddress => "CannotStoreDestinationAddress",
+            Self::PriorityIsNotAnSrcClass => "PriorityIsNotAnSrcClass",
+            Self::MaxFrameSizeTooLarge => "MaxFrameSizeTooLarge",
+            Self::MaxFanInPortsLimitReached => "MaxFanInPortsLimitReached",
+            Self::FirstValueChangedForStreamId => "FirstValueChangedForStreamId",
+            Self::VlanBlockedOnEgress => "VlanBlockedOnEgress",
+            Self::VlanTaggingDisabledOnEgress => "VlanTaggingDisabledOnEgress",
+            Self::SrClassPriorityMismatch => "SrClassPriorityMismatch",
+            Self::FeatureNotPropagated => "FeatureNotPropagated",
+            Self::MaxLatencyExceeded => "MaxLatencyExceeded",
+            Self::BridgeDoesNotProvideNetworkId => "BridgeDoesNotProvideNetworkId",
+            Self::StreamTransformNotSupported => "StreamTransformNotSupported",
+            Self::StreamIdTypeNotSupported => "StreamIdTypeNotSupported",
+            Self::FeatureNotSupported => "FeatureNotSupported",
+        })
+    }
+}
 #[opcua::types::ua_encodable]
 ///https://reference.opcfoundation.org/v105/Core/docs/Part22/5.3.1/#5.3.1.8
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -1203,6 +2206,16 @@ pub enum TsnListenerStatus {
     ///Listener failed.
     Failed = 3i32,
 }
+impl std::fmt::Display for TsnListenerStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::None => "None",
+            Self::Ready => "Ready",
+            Self::PartialFailed => "PartialFailed",
+            Self::Failed => "Failed",
+        })
+    }
+}
 #[opcua::types::ua_encodable]
 ///https://reference.opcfoundation.org/v105/Core/docs/Part22/5.3.1/#5.3.1.6
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -1220,6 +2233,17 @@ pub enum TsnStreamState {
     ///The related TSN Stream object is in an error state.
     Error = 4i32,
 }
+impl std::fmt::Display for TsnStreamState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Disabled => "Disabled",
+            Self::Configuring => "Configuring",
+            Self::Ready => "Ready",
+            Self::Operational => "Operational",
+            Self::Error => "Error",
+        })
+    }
+}
 #[opcua::types::ua_encodable]
 ///https://reference.opcfoundation.org/v105/Core/docs/Part22/5.3.1/#5.3.1.7
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -1233,6 +2257,15 @@ pub enum TsnTalkerStatus {
     ///Talker failed.
     Failed = 2i32,
 }
+impl std::fmt::Display for TsnTalkerStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::None => "None",
+            Self::Ready => "Ready",
+            Self::Failed => "Failed",
+        })
+    }
+}
 bitflags::bitflags! {
     #[derive(Debug, Copy, Clone, PartialEq)] #[doc =
     "https://reference.opcfoundation.org/v105/Core/docs/Part14/6.3.1/#6.3.1.3.2"] pub
@@ -1261,6 +2294,33 @@ impl opcua::types::IntoVariant for UadpDataSetMessageContentMask {
         self.bits().into_variant()
     }
 }
+impl std::fmt::Display for UadpDataSetMessageContentMask {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_empty() {
+            return f.write_str("0");
+        }
+        let flags: [(Self, &str); 6] = [
+            (Self::Timestamp, "Timestamp"),
+            (Self::PicoSeconds, "PicoSeconds"),
+            (Self::Status, "Status"),
+            (Self::MajorVersion, "MajorVersion"),
+            (Self::MinorVersion, "MinorVersion"),
+            (Self::SequenceNumber, "SequenceNumber"),
+        ];
+        let mut first = true;
+        for (flag, name) in flags {
+            if flag.is_empty() || !self.contains(flag) {
+                continue;
+            }
+            if !first {
+                f.write_str(", ")?;
+            }
+            f.write_str(name)?;
+            first = false;
+        }
+        Ok(())
+    }
+}
 #[cfg(feature = "xml")]
 impl opcua::types::xml::XmlType for UadpDataSetMessageContentMask {
     const TAG: &'static str = "UadpDataSetMessageContentMask";
@@ -1295,6 +2355,38 @@ impl opcua::types::IntoVariant for UadpNetworkMessageContentMask {
         self.bits().into_variant()
     }
 }
+impl std::fmt::Display for UadpNetworkMessageContentMask {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_empty() {
+            return f.write_str("0");
+        }
+        let flags: [(Self, &str); 11] = [
+            (Self::PublisherId, "PublisherId"),
+            (Self::GroupHeader, "GroupHeader"),
+            (Self::WriterGroupId, "WriterGroupId"),
+            (Self::GroupVersion, "GroupVersion"),
+            (Self::NetworkMessageNumber, "NetworkMessageNumber"),
+            (Self::SequenceNumber, "SequenceNumber"),
+            (Self::PayloadHeader, "PayloadHeader"),
+            (Self::Timestamp, "Timestamp"),
+            (Self::PicoSeconds, "PicoSeconds"),
+            (Self::DataSetClassId, "DataSetClassId"),
+            (Self::PromotedFields, "PromotedFields"),
+        ];
+        let mut first = true;
+        for (flag, name) in flags {
+            if flag.is_empty() || !self.contains(flag) {
+                continue;
+            }
+            if !first {
+                f.write_str(", ")?;
+            }
+            f.write_str(name)?;
+            first = false;
+        }
+        Ok(())
+    }
+}
 #[cfg(feature = "xml")]
 impl opcua::types::xml::XmlType for UadpNetworkMessageContentMask {
     const TAG: &'static str = "UadpNetworkMessageContentMask";
@@ -1329,6 +2421,31 @@ impl opcua::types::IntoVariant for UserConfigurationMask {
         self.bits().into_variant()
     }
 }
+impl std::fmt::Display for UserConfigurationMask {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_empty() {
+            return f.write_str("0");
+        }
+        let flags: [(Self, &str); 4] = [
+            (Self::NoDelete, "NoDelete"),
+            (Self::Disabled, "Disabled"),
+            (Self::NoChangeByUser, "NoChangeByUser"),
+            (Self::MustChangePassword, "MustChangePassword"),
+        ];
+        let mut first = true;
+        for (flag, name) in flags {
+            if flag.is_empty() || !self.contains(flag) {
+                continue;
+            }
+            if !first {
+                f.write_str(", ")?;
+            }
+            f.write_str(name)?;
+            first = false;
+        }
+        Ok(())
+    }
+}
 #[cfg(feature = "xml")]
 impl opcua::types::xml::XmlType for UserConfigurationMask {
     const TAG: &'static str = "UserConfigurationMask";
@@ -1344,3 +2461,13 @@ pub enum UserTokenType {
     Certificate = 2i32,
     IssuedToken = 3i32,
 }
+impl std::fmt::Display for UserTokenType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Anonymous => "Anonymous",
+            Self::UserName => "UserName",
+            Self::Certificate => "Certificate",
+            Self::IssuedToken => "IssuedToken",
+        })
+    }
+}
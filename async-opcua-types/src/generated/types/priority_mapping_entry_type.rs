@@ -15,7 +15,9 @@ mod opcua {
 pub struct PriorityMappingEntryType {
     pub mapping_uri: opcua::types::string::UAString,
     pub priority_label: opcua::types::string::UAString,
+    #[opcua(rename = "PriorityValue_PCP")]
     pub priority_value_pcp: u8,
+    #[opcua(rename = "PriorityValue_DSCP")]
     pub priority_value_dscp: u32,
 }
 impl opcua::types::MessageInfo for PriorityMappingEntryType {
@@ -1,5 +1,3 @@
-use std::{self, fmt};
-
 use tracing::error;
 
 use crate::{
@@ -289,18 +287,7 @@ impl<'a>
 const MESSAGE_SECURITY_MODE_NONE: &str = "None";
 const MESSAGE_SECURITY_MODE_SIGN: &str = "Sign";
 const MESSAGE_SECURITY_MODE_SIGN_AND_ENCRYPT: &str = "SignAndEncrypt";
-
-impl fmt::Display for MessageSecurityMode {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let name = match self {
-            MessageSecurityMode::None => MESSAGE_SECURITY_MODE_NONE,
-            MessageSecurityMode::Sign => MESSAGE_SECURITY_MODE_SIGN,
-            MessageSecurityMode::SignAndEncrypt => MESSAGE_SECURITY_MODE_SIGN_AND_ENCRYPT,
-            _ => "",
-        };
-        write!(f, "{name}")
-    }
-}
+const MESSAGE_SECURITY_MODE_INVALID: &str = "Invalid";
 
 impl From<MessageSecurityMode> for String {
     fn from(security_mode: MessageSecurityMode) -> Self {
@@ -314,6 +301,7 @@ impl<'a> From<&'a str> for MessageSecurityMode {
             MESSAGE_SECURITY_MODE_NONE => MessageSecurityMode::None,
             MESSAGE_SECURITY_MODE_SIGN => MessageSecurityMode::Sign,
             MESSAGE_SECURITY_MODE_SIGN_AND_ENCRYPT => MessageSecurityMode::SignAndEncrypt,
+            MESSAGE_SECURITY_MODE_INVALID => MessageSecurityMode::Invalid,
             _ => {
                 error!("Specified security mode \"{}\" is not recognized", str);
                 MessageSecurityMode::Invalid
@@ -25,6 +25,14 @@ pub mod profiles {
     /// Security policy for username and password tokens.
     pub const SECURITY_USER_TOKEN_POLICY_USERPASS: &str =
         "http://opcfoundation.org/UA-Profile/ Security/UserToken-Server/UserNamePassword";
+    /// Core 2017 server facet.
+    pub const SERVER_FACET_CORE_2017: &str =
+        "http://opcfoundation.org/UA-Profile/Server/Core2017Facet";
+    /// Standard data change subscription 2017 server facet.
+    pub const SERVER_FACET_STANDARD_DATA_CHANGE_SUBSCRIPTION_2017: &str =
+        "http://opcfoundation.org/UA-Profile/Server/StandardDataChangeSubscription2017";
+    /// Method server facet.
+    pub const SERVER_FACET_METHODS: &str = "http://opcfoundation.org/UA-Profile/Server/Methods";
 }
 
 /// Contains a few constants and defaults used in decoding and encoding.
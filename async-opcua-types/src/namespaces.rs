@@ -63,6 +63,19 @@ impl NamespaceMap {
         max + 1
     }
 
+    /// Add all namespaces in `other` to this map, in the order of their
+    /// index in `other`. Namespaces that are already present keep their index.
+    ///
+    /// Returns a map from the index of each namespace in `other` to its index in `self`.
+    pub fn merge(&mut self, other: &NamespaceMap) -> HashMap<u16, u16> {
+        let mut namespaces: Vec<_> = other.known_namespaces.iter().collect();
+        namespaces.sort_by_key(|(_, idx)| **idx);
+        namespaces
+            .into_iter()
+            .map(|(uri, idx)| (*idx, self.add_namespace(uri)))
+            .collect()
+    }
+
     /// Return the inner namespace map.
     pub fn known_namespaces(&self) -> &HashMap<String, u16> {
         &self.known_namespaces
@@ -73,6 +86,14 @@ impl NamespaceMap {
         self.known_namespaces.get(ns).copied()
     }
 
+    /// Get the namespace URI with the given index.
+    pub fn get_namespace_uri(&self, index: u16) -> Option<&str> {
+        self.known_namespaces
+            .iter()
+            .find(|(_, idx)| **idx == index)
+            .map(|(uri, _)| uri.as_str())
+    }
+
     /// Try to resolve an expanded node ID to a NodeId.
     pub fn resolve_node_id<'b>(
         &self,
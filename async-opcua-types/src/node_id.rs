@@ -19,12 +19,13 @@ use std::{
 use crate::{
     byte_string::ByteString,
     encoding::{BinaryDecodable, BinaryEncodable, EncodingResult},
+    expanded_node_id::{escape_namespace_uri, unescape_namespace_uri},
     guid::Guid,
     read_u16, read_u32, read_u8,
     status_code::StatusCode,
     string::*,
-    write_u16, write_u32, write_u8, DataTypeId, Error, MethodId, ObjectId, ObjectTypeId,
-    ReferenceTypeId, UaNullable, VariableId, VariableTypeId,
+    write_u16, write_u32, write_u8, DataTypeId, Error, MethodId, NamespaceMap, ObjectId,
+    ObjectTypeId, ReferenceTypeId, UaNullable, VariableId, VariableTypeId,
 };
 
 /// The kind of identifier, numeric, string, guid or byte
@@ -529,6 +530,58 @@ impl FromStr for NodeId {
     }
 }
 
+impl NodeId {
+    /// Parse a node ID from a string, resolving namespace URIs against `namespaces`.
+    ///
+    /// In addition to the `ns=<namespaceindex>;<type>=<value>` form accepted by
+    /// `NodeId::from_str`, this accepts `nsu=<uri>;<type>=<value>`, where the `%` and `;`
+    /// characters in the URI are escaped as `%25` and `%3b`. Returns `BadNodeIdUnknown` if
+    /// the namespace URI is not in `namespaces`.
+    pub fn from_str_with_namespaces(
+        s: &str,
+        namespaces: &NamespaceMap,
+    ) -> Result<NodeId, StatusCode> {
+        use regex::Regex;
+
+        static RE: LazyLock<Regex> = LazyLock::new(|| {
+            Regex::new(r"^(ns=(?P<ns>[0-9]+);|nsu=(?P<nsu>[^;]+);)?(?P<t>[isgb]=.+)$").unwrap()
+        });
+
+        let captures = RE.captures(s).ok_or(StatusCode::BadNodeIdInvalid)?;
+
+        let namespace = if let Some(ns) = captures.name("ns") {
+            ns.as_str()
+                .parse::<u16>()
+                .map_err(|_| StatusCode::BadNodeIdInvalid)?
+        } else if let Some(nsu) = captures.name("nsu") {
+            namespaces
+                .get_index(&unescape_namespace_uri(nsu.as_str()))
+                .ok_or(StatusCode::BadNodeIdUnknown)?
+        } else {
+            0
+        };
+
+        let t = captures.name("t").unwrap();
+        Identifier::from_str(t.as_str())
+            .map(|t| NodeId::new(namespace, t))
+            .map_err(|_| StatusCode::BadNodeIdInvalid)
+    }
+
+    /// Format the node ID as a string, using the `nsu=<uri>;<type>=<value>` form with
+    /// the namespace URI from `namespaces` if the namespace index is non-zero and known.
+    ///
+    /// The result can be parsed back with [`NodeId::from_str_with_namespaces`].
+    pub fn to_string_with_namespaces(&self, namespaces: &NamespaceMap) -> String {
+        if self.namespace == 0 {
+            return self.to_string();
+        }
+        match namespaces.get_namespace_uri(self.namespace) {
+            Some(uri) => format!("nsu={};{}", escape_namespace_uri(uri), self.identifier),
+            None => self.to_string(),
+        }
+    }
+}
+
 impl<'a> From<&'a str> for NodeId {
     fn from(value: &'a str) -> Self {
         (0u16, value).into()
@@ -16,10 +16,16 @@ use crate::{
     encoding::{BinaryDecodable, BinaryEncodable, EncodingResult},
     extension_object::ExtensionObject,
     node_id::NodeId,
+    qualified_name::QualifiedName,
     string::UAString,
-    Error,
+    variant::Variant,
+    AdditionalParametersType, Error, KeyValuePair,
 };
 
+/// Name of the parameter in the additional header of a request used to set the preferred
+/// locale IDs for that request only.
+pub const LOCALE_IDS_PARAMETER: &str = "LocaleIds";
+
 #[allow(unused)]
 mod opcua {
     pub(super) use crate as types;
@@ -202,4 +208,49 @@ impl RequestHeader {
     pub fn dummy() -> RequestHeader {
         RequestHeader::new(&NodeId::null(), &DateTime::now(), 1)
     }
+
+    /// Set the preferred locale IDs for this request only, overriding the locale IDs
+    /// set when the session was activated.
+    ///
+    /// This is sent as a parameter in the additional header. Any other additional
+    /// parameters already set on the header are kept.
+    pub fn set_locale_ids(&mut self, locale_ids: Vec<UAString>) {
+        let mut parameters = self
+            .additional_header
+            .inner_as::<AdditionalParametersType>()
+            .and_then(|p| p.parameters.clone())
+            .unwrap_or_default();
+        parameters.retain(|p| p.key.name.as_ref() != LOCALE_IDS_PARAMETER);
+        parameters.push(KeyValuePair {
+            key: QualifiedName::new(0, LOCALE_IDS_PARAMETER),
+            value: locale_ids.into(),
+        });
+        self.additional_header = ExtensionObject::from_message(AdditionalParametersType {
+            parameters: Some(parameters),
+        });
+    }
+
+    /// Get the preferred locale IDs for this request, if they are set in the additional header.
+    pub fn locale_ids(&self) -> Option<Vec<UAString>> {
+        let params = self
+            .additional_header
+            .inner_as::<AdditionalParametersType>()?;
+        let param =
+            params.parameters.as_ref()?.iter().find(|p| {
+                p.key.namespace_index == 0 && p.key.name.as_ref() == LOCALE_IDS_PARAMETER
+            })?;
+        match &param.value {
+            Variant::Array(arr) => Some(
+                arr.values
+                    .iter()
+                    .filter_map(|v| match v {
+                        Variant::String(s) => Some(s.clone()),
+                        _ => None,
+                    })
+                    .collect(),
+            ),
+            Variant::String(s) => Some(vec![s.clone()]),
+            _ => None,
+        }
+    }
 }
@@ -287,6 +287,9 @@ fn expanded_node_id() {
     node_id.namespace_uri = UAString::from("test2");
     node_id.server_index = 50330;
     serialize_test(node_id);
+
+    serialize_test(ExpandedNodeId::new_remote(3, "urn:remote", "Tag1"));
+    serialize_test(ExpandedNodeId::new(NodeId::new(2, 15)).with_server_index(1));
 }
 
 #[test]
@@ -658,6 +661,40 @@ fn test_custom_union_nullable() {
     serialize_test(st);
 }
 
+#[test]
+fn test_custom_union_generated() {
+    mod opcua {
+        pub(super) use crate as types;
+    }
+
+    // Same shape as unions produced by the code generator.
+    #[derive(Debug, PartialEq, Clone, Default, BinaryDecodable, BinaryEncodable)]
+    enum MyUnion {
+        #[default]
+        Null,
+        Var1(i32),
+        #[opcua(rename = "var2")]
+        Var2(Option<Vec<EUInformation>>),
+    }
+
+    let ctx_f = ContextOwned::default();
+    let ctx = ctx_f.context();
+    let st = MyUnion::default();
+    assert_eq!(st.byte_len(&ctx), 4);
+    serialize_and_compare(st, &[0, 0, 0, 0]);
+
+    serialize_and_compare(MyUnion::Var1(5), &[1, 0, 0, 0, 5, 0, 0, 0]);
+
+    let st = MyUnion::Var2(Some(vec![EUInformation {
+        namespace_uri: "test".into(),
+        unit_id: 123,
+        display_name: "test".into(),
+        description: "desc".into(),
+    }]));
+    serialize_test(st);
+    serialize_test(MyUnion::Var2(None));
+}
+
 #[test]
 fn test_xml_in_binary() {
     // Bit tricky to test since we don't support encoding extension objects as XML.
@@ -690,3 +727,20 @@ fn test_xml_in_binary() {
     let decoded = ExtensionObject::decode(&mut stream, &ctx).unwrap();
     assert_eq!(decoded.inner_as::<EUInformation>().unwrap(), &rf);
 }
+
+#[test]
+fn request_header_locale_ids() {
+    let mut header = crate::RequestHeader::dummy();
+    assert_eq!(header.locale_ids(), None);
+
+    header.set_locale_ids(vec!["fr-FR".into(), "en".into()]);
+    // Setting the locale IDs again replaces the existing parameter.
+    header.set_locale_ids(vec!["fr".into()]);
+    let header = serialize_test_and_return(header);
+    assert_eq!(header.locale_ids(), Some(vec![UAString::from("fr")]));
+    let params = header
+        .additional_header
+        .inner_as::<crate::AdditionalParametersType>()
+        .unwrap();
+    assert_eq!(params.parameters.as_ref().unwrap().len(), 1);
+}
@@ -1002,3 +1002,49 @@ fn test_binary_in_json() {
         obj_3.inner_as().unwrap()
     );
 }
+
+#[test]
+fn generated_struct_json_matches_binary() {
+    use crate::{AxisInformation, AxisScaleEnumeration, BinaryDecodable, EUInformation, Range};
+
+    let with_steps = AxisInformation {
+        engineering_units: EUInformation {
+            namespace_uri: "http://www.opcfoundation.org/UA/units/un/cefact".into(),
+            unit_id: 4408652,
+            display_name: LocalizedText::new("en", "°C"),
+            description: LocalizedText::new("en", "degree Celsius"),
+        },
+        eu_range: Range {
+            low: -10.5,
+            high: 50.25,
+        },
+        title: LocalizedText::new("en", "Temperature"),
+        axis_scale_type: AxisScaleEnumeration::Log,
+        axis_steps: Some(vec![-10.0, 0.0, 25.5, 50.0]),
+    };
+    let without_steps = AxisInformation {
+        axis_steps: None,
+        ..with_steps.clone()
+    };
+
+    // The schema name of `eu_range` is not the pascal case of the field name.
+    let v = to_value(&with_steps).unwrap();
+    assert_eq!(v["EURange"], json!({"Low": -10.5, "High": 50.25}));
+    assert!(v.get("EuRange").is_none());
+
+    let ctx_r = ctx();
+    let ctx = ctx_r.context();
+    for st in [with_steps, without_steps] {
+        let mut buf = Vec::with_capacity(st.byte_len(&ctx));
+        let mut cursor = Cursor::new(&mut buf);
+        crate::BinaryEncodable::encode(&st, &mut cursor, &ctx).unwrap();
+        cursor.seek(std::io::SeekFrom::Start(0)).unwrap();
+        let from_binary = <AxisInformation as BinaryDecodable>::decode(&mut cursor, &ctx).unwrap();
+
+        let v = to_value(&st).unwrap();
+        let from_json: AxisInformation = from_value(v).unwrap();
+
+        assert_eq!(from_binary, from_json);
+        assert_eq!(st, from_json);
+    }
+}
@@ -2,6 +2,7 @@ mod date_time;
 mod encoding;
 #[cfg(feature = "json")]
 mod json;
+mod namespaces;
 mod node_id;
 mod variant;
 #[cfg(feature = "xml")]
@@ -95,3 +96,29 @@ where
         assert_eq!(actual[i], expected[i])
     }
 }
+
+#[test]
+fn generated_enum_display() {
+    use crate::{AccessLevelExType, MessageSecurityMode, NodeClass};
+
+    assert_eq!(
+        format!("{}", MessageSecurityMode::SignAndEncrypt),
+        "SignAndEncrypt"
+    );
+    assert_eq!(format!("{}", MessageSecurityMode::Invalid), "Invalid");
+    // Security modes round trip through their names.
+    for mode in [
+        MessageSecurityMode::Invalid,
+        MessageSecurityMode::None,
+        MessageSecurityMode::SignAndEncrypt,
+    ] {
+        assert_eq!(MessageSecurityMode::from(mode.to_string().as_str()), mode);
+    }
+    assert_eq!(format!("{}", NodeClass::ObjectType), "ObjectType");
+
+    // Bitfields list the flags that are set.
+    let flags = AccessLevelExType::CurrentRead | AccessLevelExType::HistoryRead;
+    assert_eq!(format!("{flags}"), "CurrentRead, HistoryRead");
+    // The schema has no name for an empty AccessLevelExType.
+    assert_eq!(format!("{}", AccessLevelExType::empty()), "0");
+}
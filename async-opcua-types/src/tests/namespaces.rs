@@ -0,0 +1,128 @@
+use std::io::{Cursor, Read};
+
+use hashbrown::HashMap;
+
+use crate::{
+    BinaryDecodable, BinaryEncodable, Context, ContextOwned, DecodingOptions, DynEncodable,
+    EUInformation, EncodingResult, LocalizedText, NamespaceMap, NodeId, Range, TypeLoader,
+    TypeLoaderCollection,
+};
+
+fn namespaces(uris: &[&str]) -> NamespaceMap {
+    let mut map = NamespaceMap::new();
+    for uri in uris {
+        map.add_namespace(uri);
+    }
+    map
+}
+
+#[test]
+fn namespace_map_merge() {
+    let mut map = namespaces(&["urn:a", "urn:b"]);
+    let other = namespaces(&["urn:c", "urn:b", "urn:a"]);
+
+    let remap = map.merge(&other);
+    assert_eq!(remap, HashMap::from_iter([(0, 0), (1, 3), (2, 2), (3, 1)]));
+    assert_eq!(map.get_index("urn:a"), Some(1));
+    assert_eq!(map.get_index("urn:b"), Some(2));
+    assert_eq!(map.get_index("urn:c"), Some(3));
+    assert_eq!(map.known_namespaces().len(), 4);
+}
+
+/// Type loader for a single type with encoding ID `i=1` in the namespace
+/// `uri`, using its own namespace array.
+struct LocalLoader {
+    namespaces: NamespaceMap,
+    uri: &'static str,
+    decode: fn(&mut dyn Read, &Context<'_>) -> EncodingResult<Box<dyn DynEncodable>>,
+}
+
+impl TypeLoader for LocalLoader {
+    #[cfg(feature = "xml")]
+    fn load_from_xml(
+        &self,
+        _node_id: &NodeId,
+        _stream: &mut crate::xml::XmlStreamReader<&mut dyn Read>,
+        _ctx: &Context<'_>,
+    ) -> Option<EncodingResult<Box<dyn DynEncodable>>> {
+        None
+    }
+
+    #[cfg(feature = "json")]
+    fn load_from_json(
+        &self,
+        _node_id: &NodeId,
+        _stream: &mut crate::json::JsonStreamReader<&mut dyn Read>,
+        _ctx: &Context<'_>,
+    ) -> Option<EncodingResult<Box<dyn DynEncodable>>> {
+        None
+    }
+
+    fn load_from_binary(
+        &self,
+        node_id: &NodeId,
+        stream: &mut dyn Read,
+        ctx: &Context<'_>,
+    ) -> Option<EncodingResult<Box<dyn DynEncodable>>> {
+        let idx = self.namespaces.get_index(self.uri)?;
+        if *node_id != NodeId::new(idx, 1) {
+            return None;
+        }
+        Some((self.decode)(stream, ctx))
+    }
+
+    fn local_namespaces(&self) -> Option<&NamespaceMap> {
+        Some(&self.namespaces)
+    }
+}
+
+#[test]
+fn type_loader_local_namespaces() {
+    let mut loaders = TypeLoaderCollection::new_empty();
+    // The two loaders use different namespace orderings, and both differ from the server.
+    loaders.add_type_loader(LocalLoader {
+        namespaces: namespaces(&["urn:a", "urn:b"]),
+        uri: "urn:a",
+        decode: |s, c| Ok(Box::new(Range::decode(s, c)?)),
+    });
+    loaders.add_type_loader(LocalLoader {
+        namespaces: namespaces(&["urn:b", "urn:a"]),
+        uri: "urn:b",
+        decode: |s, c| Ok(Box::new(EUInformation::decode(s, c)?)),
+    });
+    let ctx_owned = ContextOwned::new(
+        namespaces(&["urn:other", "urn:b", "urn:a"]),
+        loaders,
+        DecodingOptions::test(),
+    );
+    let ctx = ctx_owned.context();
+
+    let range = Range {
+        low: 1.0,
+        high: 2.0,
+    };
+    let mut stream = Cursor::new(range.encode_to_vec(&ctx));
+    let obj = ctx
+        .load_from_binary(&NodeId::new(3, 1), &mut stream)
+        .unwrap();
+    assert_eq!(obj.inner_as::<Range>(), Some(&range));
+
+    let info = EUInformation {
+        display_name: LocalizedText::from("meter"),
+        ..Default::default()
+    };
+    let mut stream = Cursor::new(info.encode_to_vec(&ctx));
+    let obj = ctx
+        .load_from_binary(&NodeId::new(2, 1), &mut stream)
+        .unwrap();
+    assert_eq!(obj.inner_as::<EUInformation>(), Some(&info));
+
+    // Namespaces unknown to the server, or to the type loaders, are not handled.
+    let mut stream = Cursor::new(range.encode_to_vec(&ctx));
+    assert!(ctx
+        .load_from_binary(&NodeId::new(1, 1), &mut stream)
+        .is_err());
+    assert!(ctx
+        .load_from_binary(&NodeId::new(4, 1), &mut stream)
+        .is_err());
+}
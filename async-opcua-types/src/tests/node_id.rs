@@ -139,8 +139,68 @@ fn expanded_node_id() {
         server_index: 33,
     };
     assert_eq!(format!("{node_id}"), "svr=33;ns=1;s=Hello World");
+    assert!(!node_id.is_local());
     assert_eq!(
         ExpandedNodeId::from_str("svr=33;ns=1;s=Hello World").unwrap(),
         node_id
     );
+
+    // Remote node IDs are given with a namespace URI
+    let node_id = ExpandedNodeId::new_remote(2, "urn:remote", "Tag1");
+    assert_eq!(node_id.server_index, 2);
+    assert!(!node_id.is_local());
+    assert_eq!(format!("{node_id}"), "svr=2;nsu=urn:remote;s=Tag1");
+    assert_eq!(
+        ExpandedNodeId::from_str("svr=2;nsu=urn:remote;s=Tag1").unwrap(),
+        node_id
+    );
+    assert!(node_id.with_server_index(0).is_local());
+}
+
+#[test]
+fn node_id_with_namespace_uri() {
+    let mut namespaces = NamespaceMap::new();
+    let ns = namespaces.add_namespace("urn:my;namespace%");
+
+    for identifier in [
+        Identifier::Numeric(123),
+        Identifier::String("Hello World".into()),
+        Identifier::Guid(Guid::from_str("995a9546-cd91-4393-b1c8-a83851f88d6a").unwrap()),
+        Identifier::ByteString(ByteString::from(vec![1u8, 2, 3, 4])),
+    ] {
+        let node_id = NodeId::new(ns, identifier.clone());
+        let s = node_id.to_string_with_namespaces(&namespaces);
+        assert_eq!(s, format!("nsu=urn:my%3bnamespace%25;{identifier}"));
+        assert_eq!(
+            NodeId::from_str_with_namespaces(&s, &namespaces).unwrap(),
+            node_id
+        );
+        // The index form is still accepted.
+        assert_eq!(
+            NodeId::from_str_with_namespaces(&node_id.to_string(), &namespaces).unwrap(),
+            node_id
+        );
+
+        let expanded = ExpandedNodeId::from(node_id.clone());
+        let s = expanded.to_string_with_namespaces(&namespaces);
+        assert_eq!(s, format!("svr=0;nsu=urn:my%3bnamespace%25;{identifier}"));
+        let parsed = ExpandedNodeId::from_str(&s).unwrap();
+        assert_eq!(parsed.namespace_uri.as_ref(), "urn:my;namespace%");
+        assert_eq!(parsed.try_resolve(&namespaces).unwrap().as_ref(), &node_id);
+    }
+
+    // Namespace 0 and unknown namespaces use the index form.
+    let node_id = NodeId::new(0, 15);
+    assert_eq!(node_id.to_string_with_namespaces(&namespaces), "i=15");
+    let node_id = NodeId::new(5, 15);
+    assert_eq!(node_id.to_string_with_namespaces(&namespaces), "ns=5;i=15");
+
+    assert_eq!(
+        NodeId::from_str_with_namespaces("nsu=urn:unknown;i=15", &namespaces),
+        Err(StatusCode::BadNodeIdUnknown)
+    );
+    assert_eq!(
+        NodeId::from_str_with_namespaces("nsu=;i=15", &namespaces),
+        Err(StatusCode::BadNodeIdInvalid)
+    );
 }
@@ -4,7 +4,7 @@ use crate::{
     numeric_range::NumericRange,
     status_code::StatusCode,
     variant::{Variant, VariantTypeId},
-    ByteString, DataTypeId, DataValue, DateTime, DiagnosticInfo, ExpandedNodeId, Guid,
+    Array, ByteString, DataTypeId, DataValue, DateTime, DiagnosticInfo, ExpandedNodeId, Guid,
     LocalizedText, NodeId, QualifiedName, TryFromVariant, UAString, VariantScalarTypeId,
 };
 
@@ -326,6 +326,209 @@ fn index_of_string() {
     assert_eq!(r, StatusCode::BadIndexRangeNoData);
 }
 
+fn matrix(rows: u32, cols: u32) -> Variant {
+    let values: Vec<Variant> = (0..rows * cols).map(|v| Variant::from(v as i32)).collect();
+    Variant::from(Array::new_multi(VariantScalarTypeId::Int32, values, vec![rows, cols]).unwrap())
+}
+
+fn int_array(v: &Variant) -> (Vec<i32>, Option<Vec<u32>>) {
+    let Variant::Array(array) = v else {
+        panic!("Expected array, got {v:?}");
+    };
+    let values = array
+        .values
+        .iter()
+        .map(|v| match v {
+            Variant::Int32(v) => *v,
+            _ => panic!("Expected Int32"),
+        })
+        .collect();
+    (values, array.dimensions.clone())
+}
+
+#[test]
+fn range_slice_matrix() {
+    // 3x4 matrix with values 0..12, row by row.
+    let v = matrix(3, 4);
+
+    let r = v.range_slice(&"1:2,0:1".parse().unwrap()).unwrap();
+    assert_eq!(int_array(&r), (vec![4, 5, 8, 9], Some(vec![2, 2])));
+
+    let r = v.range_slice(&"2,3".parse().unwrap()).unwrap();
+    assert_eq!(int_array(&r), (vec![11], Some(vec![1, 1])));
+
+    // Ranges past the end of a dimension are clamped.
+    let r = v.range_slice(&"0:5,2:10".parse().unwrap()).unwrap();
+    assert_eq!(int_array(&r), (vec![2, 3, 6, 7, 10, 11], Some(vec![3, 2])));
+
+    assert_eq!(
+        v.range_slice(&"3,0".parse().unwrap()).unwrap_err(),
+        StatusCode::BadIndexRangeNoData
+    );
+    // Every dimension must be specified.
+    assert_eq!(
+        v.range_slice(&NumericRange::Range(0, 1)).unwrap_err(),
+        StatusCode::BadIndexRangeInvalid
+    );
+    assert_eq!(
+        v.range_slice(&"0,0,0".parse().unwrap()).unwrap_err(),
+        StatusCode::BadIndexRangeInvalid
+    );
+
+    // One-dimensional arrays only accept a single range.
+    let v = Variant::from(vec![1, 2, 3]);
+    assert_eq!(
+        v.range_slice(&"0,1".parse().unwrap()).unwrap_err(),
+        StatusCode::BadIndexRangeInvalid
+    );
+    assert_eq!(
+        Variant::from(5).range_slice(&NumericRange::Index(0)),
+        Err(StatusCode::BadIndexRangeDataMismatch)
+    );
+    // Ranges must not end before they start.
+    assert_eq!(
+        v.range_slice(&NumericRange::Range(2, 1)),
+        Err(StatusCode::BadIndexRangeInvalid)
+    );
+    assert_eq!(
+        Variant::from("abc").range_slice(&NumericRange::Range(2, 1)),
+        Err(StatusCode::BadIndexRangeInvalid)
+    );
+}
+
+#[test]
+fn range_set_array() {
+    let mut v = Variant::from(vec![0, 0, 0, 0, 0]);
+    v.range_set(&NumericRange::Range(1, 3), &Variant::from(vec![1, 2, 3]))
+        .unwrap();
+    assert_eq!(int_array(&v).0, vec![0, 1, 2, 3, 0]);
+
+    v.range_set(&NumericRange::Index(4), &Variant::from(vec![9]))
+        .unwrap();
+    assert_eq!(int_array(&v).0, vec![0, 1, 2, 3, 9]);
+
+    assert_eq!(
+        v.range_set(&NumericRange::Index(5), &Variant::from(vec![9])),
+        Err(StatusCode::BadIndexRangeNoData)
+    );
+    assert_eq!(
+        v.range_set(&NumericRange::Index(0), &Variant::from(vec![1.0f64])),
+        Err(StatusCode::BadIndexRangeDataMismatch)
+    );
+    assert_eq!(
+        v.range_set(&NumericRange::Index(0), &Variant::from(vec![0i32; 0])),
+        Err(StatusCode::BadIndexRangeDataMismatch)
+    );
+    // The value must cover the whole range.
+    assert_eq!(
+        v.range_set(&NumericRange::Range(0, 2), &Variant::from(vec![5, 5])),
+        Err(StatusCode::BadIndexRangeDataMismatch)
+    );
+    assert_eq!(
+        v.range_set(&NumericRange::Range(3, 2), &Variant::from(vec![5, 5])),
+        Err(StatusCode::BadIndexRangeInvalid)
+    );
+    assert_eq!(int_array(&v).0, vec![0, 1, 2, 3, 9]);
+}
+
+#[test]
+fn range_string_array() {
+    let mut v = Variant::from(vec![
+        "abcd".to_owned(),
+        "efgh".to_owned(),
+        "ijkl".to_owned(),
+    ]);
+
+    // The last range selects a substring of each element.
+    let r = v.range_slice(&"1:2,1:2".parse().unwrap()).unwrap();
+    assert_eq!(r, Variant::from(vec!["fg".to_owned(), "jk".to_owned()]));
+    let r = v.range_slice(&"0,3".parse().unwrap()).unwrap();
+    assert_eq!(r, Variant::from(vec!["d".to_owned()]));
+    assert_eq!(
+        v.range_slice(&"0,4".parse().unwrap()),
+        Err(StatusCode::BadIndexRangeNoData)
+    );
+
+    v.range_set(
+        &"0:1,1:2".parse().unwrap(),
+        &Variant::from(vec!["XY".to_owned(), "ZW".to_owned()]),
+    )
+    .unwrap();
+    assert_eq!(
+        v,
+        Variant::from(vec![
+            "aXYd".to_owned(),
+            "eZWh".to_owned(),
+            "ijkl".to_owned()
+        ])
+    );
+    // Each element of the value must cover the substring.
+    assert_eq!(
+        v.range_set(
+            &"2,0:1".parse().unwrap(),
+            &Variant::from(vec!["X".to_owned()])
+        ),
+        Err(StatusCode::BadIndexRangeDataMismatch)
+    );
+
+    let v = Variant::from(vec![
+        ByteString::from(vec![1u8, 2, 3]),
+        ByteString::from(vec![4u8, 5, 6]),
+    ]);
+    let r = v.range_slice(&"0:1,2".parse().unwrap()).unwrap();
+    assert_eq!(
+        r,
+        Variant::from(vec![
+            ByteString::from(vec![3u8]),
+            ByteString::from(vec![6u8])
+        ])
+    );
+}
+
+#[test]
+fn range_set_matrix() {
+    let mut v = matrix(3, 4);
+    let block = Variant::from(
+        Array::new_multi(
+            VariantScalarTypeId::Int32,
+            vec![
+                Variant::from(-1),
+                Variant::from(-2),
+                Variant::from(-3),
+                Variant::from(-4),
+            ],
+            vec![2, 2],
+        )
+        .unwrap(),
+    );
+    v.range_set(&"1:2,2:3".parse().unwrap(), &block).unwrap();
+    assert_eq!(
+        int_array(&v),
+        (
+            vec![0, 1, 2, 3, 4, 5, -1, -2, 8, 9, -3, -4],
+            Some(vec![3, 4])
+        )
+    );
+
+    // A flat array with the same number of elements as the block is also accepted.
+    v.range_set(&"0,0:2".parse().unwrap(), &Variant::from(vec![7, 7, 7]))
+        .unwrap();
+    assert_eq!(int_array(&v).0[0..4], [7, 7, 7, 3]);
+
+    assert_eq!(
+        v.range_set(&"0,0:2".parse().unwrap(), &Variant::from(vec![7, 7])),
+        Err(StatusCode::BadIndexRangeDataMismatch)
+    );
+    assert_eq!(
+        v.range_set(&NumericRange::Index(0), &Variant::from(vec![7])),
+        Err(StatusCode::BadIndexRangeInvalid)
+    );
+    assert_eq!(
+        Variant::from(5).range_set(&NumericRange::Index(0), &Variant::from(vec![7])),
+        Err(StatusCode::BadWriteNotSupported)
+    );
+}
+
 fn ensure_conversion_fails<'a>(v: &Variant, convert_to: Vec<impl Into<VariantTypeId<'a>>>) {
     convert_to.into_iter().for_each(|vt| {
         let t: VariantTypeId = vt.into();
@@ -1631,6 +1834,90 @@ fn variant_cast_expanded_node_id() {
     );
 }
 
+#[test]
+fn variant_convert_narrowing_out_of_range() {
+    // Implicit conversions to a smaller type fail rather than wrapping.
+    assert_eq!(
+        Variant::from(200u8).convert(VariantScalarTypeId::SByte),
+        Variant::Empty
+    );
+    assert_eq!(
+        Variant::from(40000u16).convert(VariantScalarTypeId::Int16),
+        Variant::Empty
+    );
+    assert_eq!(
+        Variant::from(u32::MAX).convert(VariantScalarTypeId::Int32),
+        Variant::Empty
+    );
+    assert_eq!(
+        Variant::from(u64::MAX).convert(VariantScalarTypeId::Int64),
+        Variant::Empty
+    );
+}
+
+#[test]
+fn variant_convert_to_common_type() {
+    // The operand with lower precedence is converted.
+    assert_eq!(
+        Variant::from(5u8).convert_to_common_type(&Variant::from(2.5f64)),
+        (Variant::Double(5.0), Variant::Double(2.5))
+    );
+    assert_eq!(
+        Variant::from(2.5f64).convert_to_common_type(&Variant::from(5u8)),
+        (Variant::Double(2.5), Variant::Double(5.0))
+    );
+    assert_eq!(
+        Variant::from("12").convert_to_common_type(&Variant::from(5i32)),
+        (Variant::Int32(12), Variant::Int32(5))
+    );
+    assert_eq!(
+        Variant::from(5i32).convert_to_common_type(&Variant::from(Guid::null())),
+        (Variant::Int32(5), Variant::Empty)
+    );
+}
+
+#[test]
+fn variant_partial_cmp_numeric() {
+    use std::cmp::Ordering;
+
+    fn cmp(lhs: impl Into<Variant>, rhs: impl Into<Variant>) -> Option<Ordering> {
+        lhs.into().partial_cmp_numeric(&rhs.into())
+    }
+
+    // Byte vs Double
+    assert_eq!(cmp(5u8, 5.0f64), Some(Ordering::Equal));
+    assert_eq!(cmp(5u8, 5.5f64), Some(Ordering::Less));
+    assert_eq!(cmp(5.5f64, 5u8), Some(Ordering::Greater));
+    // Int32 vs Double, Float vs Double
+    assert_eq!(cmp(-3i32, -3.5f64), Some(Ordering::Greater));
+    assert_eq!(cmp(1.5f32, 1.5f64), Some(Ordering::Equal));
+    assert_eq!(cmp(0.0f64, -0.0f64), Some(Ordering::Equal));
+    // Signed vs unsigned
+    assert_eq!(cmp(10i64, 20u64), Some(Ordering::Less));
+    assert_eq!(cmp(-1i16, 1u16), Some(Ordering::Less));
+    assert_eq!(cmp(10u32, 10i64), Some(Ordering::Equal));
+    // Boolean converts to 0 or 1.
+    assert_eq!(cmp(true, 1i32), Some(Ordering::Equal));
+    assert_eq!(cmp(false, 0.5f64), Some(Ordering::Less));
+    // Strings are converted to numbers if they can be parsed.
+    assert_eq!(cmp("12", 12i32), Some(Ordering::Equal));
+    assert_eq!(cmp(13i32, "12"), Some(Ordering::Greater));
+    assert_eq!(cmp("1.5", 2.0f64), Some(Ordering::Less));
+
+    // Comparisons without a valid conversion are unknown.
+    assert_eq!(cmp("abc", 12i32), None);
+    assert_eq!(cmp(12i32, "abc"), None);
+    assert_eq!(cmp(200u8, -1i8), None);
+    assert_eq!(cmp(u64::MAX, 1i64), None);
+    assert_eq!(cmp(-1i32, 1u64), None);
+    assert_eq!(cmp(1i32, Guid::null()), None);
+    assert_eq!(cmp(1i32, DateTime::now()), None);
+    assert_eq!(cmp(1i32, Variant::Empty), None);
+    assert_eq!(cmp("abc", "abc"), None);
+    assert_eq!(cmp(f64::NAN, 1i32), None);
+    assert_eq!(cmp(f64::NAN, f64::NAN), None);
+}
+
 #[test]
 fn variant_bytestring_to_bytearray() {
     let v = ByteString::from(&[0x1, 0x2, 0x3, 0x4]);
@@ -205,6 +205,10 @@ where
     fn priority(&self) -> TypeLoaderPriority {
         TypeLoaderPriority::Generated
     }
+
+    fn namespace_uri(&self) -> Option<&str> {
+        Some(Self::namespace())
+    }
 }
 
 /// Owned variant of [Context], this is stored by clients and servers, which
@@ -275,6 +279,11 @@ impl ContextOwned {
         &mut self.options
     }
 
+    /// Get a reference to the type loaders.
+    pub fn loaders(&self) -> &TypeLoaderCollection {
+        &self.loaders
+    }
+
     /// Get a mutable reference to the type loaders.
     pub fn loaders_mut(&mut self) -> &mut TypeLoaderCollection {
         &mut self.loaders
@@ -440,6 +449,23 @@ pub trait TypeLoader: Send + Sync {
     fn priority(&self) -> TypeLoaderPriority {
         TypeLoaderPriority::Generated
     }
+
+    /// Get the URI of the namespace this type loader handles, if it is
+    /// limited to a single namespace.
+    fn namespace_uri(&self) -> Option<&str> {
+        None
+    }
+
+    /// Get the namespace map used for the node IDs this type loader handles, if it
+    /// uses fixed namespace indices instead of looking up its namespaces in the context.
+    ///
+    /// If this returns `Some`, the namespace index of each node ID passed to the
+    /// type loader is translated from the index in the context, which is usually the
+    /// namespace array of the server, to the index of the same namespace in this map.
+    /// Node IDs in namespaces missing from this map are not passed to the type loader.
+    fn local_namespaces(&self) -> Option<&NamespaceMap> {
+        None
+    }
 }
 
 impl<'a> Context<'a> {
@@ -468,7 +494,10 @@ impl<'a> Context<'a> {
         stream: &mut crate::json::JsonStreamReader<&mut dyn Read>,
     ) -> crate::EncodingResult<crate::ExtensionObject> {
         for loader in self.loaders {
-            if let Some(r) = loader.load_from_json(node_id, stream, self) {
+            let Some(node_id) = self.node_id_for_loader(&**loader, node_id) else {
+                continue;
+            };
+            if let Some(r) = loader.load_from_json(&node_id, stream, self) {
                 return Ok(crate::ExtensionObject { body: Some(r?) });
             }
         }
@@ -485,7 +514,10 @@ impl<'a> Context<'a> {
         stream: &mut dyn Read,
     ) -> crate::EncodingResult<crate::ExtensionObject> {
         for loader in self.loaders {
-            if let Some(r) = loader.load_from_binary(node_id, stream, self) {
+            let Some(node_id) = self.node_id_for_loader(&**loader, node_id) else {
+                continue;
+            };
+            if let Some(r) = loader.load_from_binary(&node_id, stream, self) {
                 return Ok(crate::ExtensionObject { body: Some(r?) });
             }
         }
@@ -503,7 +535,10 @@ impl<'a> Context<'a> {
         stream: &mut crate::xml::XmlStreamReader<&mut dyn std::io::Read>,
     ) -> crate::EncodingResult<crate::ExtensionObject> {
         for loader in self.loaders {
-            if let Some(r) = loader.load_from_xml(node_id, stream, self) {
+            let Some(node_id) = self.node_id_for_loader(&**loader, node_id) else {
+                continue;
+            };
+            if let Some(r) = loader.load_from_xml(&node_id, stream, self) {
                 return Ok(crate::ExtensionObject { body: Some(r?) });
             }
         }
@@ -512,6 +547,32 @@ impl<'a> Context<'a> {
         )))
     }
 
+    /// Translate the namespace index of `node_id` to the index used by `loader`,
+    /// if it has its own namespace map. Returns `None` if the loader does not know
+    /// the namespace.
+    fn node_id_for_loader<'b>(
+        &self,
+        loader: &dyn TypeLoader,
+        node_id: &'b NodeId,
+    ) -> Option<Cow<'b, NodeId>> {
+        let Some(local) = loader.local_namespaces() else {
+            return Some(Cow::Borrowed(node_id));
+        };
+        if node_id.namespace == 0 {
+            return Some(Cow::Borrowed(node_id));
+        }
+        let uri = self.namespaces.get_namespace_uri(node_id.namespace)?;
+        let namespace = local.get_index(uri)?;
+        if namespace == node_id.namespace {
+            Some(Cow::Borrowed(node_id))
+        } else {
+            Some(Cow::Owned(NodeId {
+                namespace,
+                identifier: node_id.identifier.clone(),
+            }))
+        }
+    }
+
     /// Get the decoding options.
     pub fn options(&self) -> &DecodingOptions {
         &self.options
@@ -9,6 +9,7 @@ mod from;
 mod into;
 #[cfg(feature = "json")]
 mod json;
+mod range;
 mod type_id;
 #[cfg(feature = "xml")]
 mod xml;
@@ -22,6 +23,7 @@ pub use into::IntoVariant;
 pub use type_id::*;
 
 use std::{
+    cmp::Ordering,
     convert::TryFrom,
     fmt,
     io::{Read, Write},
@@ -930,7 +932,9 @@ impl Variant {
                 VariantScalarTypeId::Int16 => (v as i16).into(),
                 VariantScalarTypeId::Int32 => (v as i32).into(),
                 VariantScalarTypeId::Int64 => (v as i64).into(),
-                VariantScalarTypeId::SByte => (v as i8).into(),
+                VariantScalarTypeId::SByte => {
+                    i8::try_from(v).map(Variant::from).unwrap_or(Variant::Empty)
+                }
                 VariantScalarTypeId::UInt16 => (v as u16).into(),
                 VariantScalarTypeId::UInt32 => (v as u32).into(),
                 VariantScalarTypeId::UInt64 => (v as u64).into(),
@@ -1126,7 +1130,9 @@ impl Variant {
                 match target_type {
                     VariantScalarTypeId::Double => (v as f64).into(),
                     VariantScalarTypeId::Float => (v as f32).into(),
-                    VariantScalarTypeId::Int16 => (v as i16).into(),
+                    VariantScalarTypeId::Int16 => i16::try_from(v)
+                        .map(Variant::from)
+                        .unwrap_or(Variant::Empty),
                     VariantScalarTypeId::Int32 => (v as i32).into(),
                     VariantScalarTypeId::Int64 => (v as i64).into(),
                     VariantScalarTypeId::StatusCode => {
@@ -1141,7 +1147,9 @@ impl Variant {
             Variant::UInt32(v) => match target_type {
                 VariantScalarTypeId::Double => (v as f64).into(),
                 VariantScalarTypeId::Float => (v as f32).into(),
-                VariantScalarTypeId::Int32 => (v as i32).into(),
+                VariantScalarTypeId::Int32 => i32::try_from(v)
+                    .map(Variant::from)
+                    .unwrap_or(Variant::Empty),
                 VariantScalarTypeId::Int64 => (v as i64).into(),
                 VariantScalarTypeId::UInt64 => (v as u64).into(),
                 _ => Variant::Empty,
@@ -1149,7 +1157,9 @@ impl Variant {
             Variant::UInt64(v) => match target_type {
                 VariantScalarTypeId::Double => (v as f64).into(),
                 VariantScalarTypeId::Float => (v as f32).into(),
-                VariantScalarTypeId::Int64 => (v as i64).into(),
+                VariantScalarTypeId::Int64 => i64::try_from(v)
+                    .map(Variant::from)
+                    .unwrap_or(Variant::Empty),
                 _ => Variant::Empty,
             },
             Variant::Array(ref s) => {
@@ -1220,6 +1230,43 @@ impl Variant {
         }
     }
 
+    /// Convert two variants to a common type using the implicit conversion rules from
+    /// OPC UA Part 4, 7.7.4.3. The variant whose type has lower precedence is converted to
+    /// the type of the other. If no implicit conversion exists, or the value does not fit
+    /// in the target type, the converted variant is `Variant::Empty`.
+    pub fn convert_to_common_type(&self, other: &Variant) -> (Variant, Variant) {
+        let self_type = self.type_id();
+        let other_type = other.type_id();
+        match self_type.precedence().cmp(&other_type.precedence()) {
+            Ordering::Less => (self.clone(), other.convert(self_type)),
+            Ordering::Equal => (self.clone(), other.clone()),
+            Ordering::Greater => (self.convert(other_type), other.clone()),
+        }
+    }
+
+    /// Compare two variants after converting them to a common type with
+    /// [`Variant::convert_to_common_type`], as done by the comparison operators
+    /// in content filters.
+    ///
+    /// Returns `None` if the variants cannot be converted to a common type, if the common
+    /// type is not numeric or boolean, or if either value is `NaN`.
+    pub fn partial_cmp_numeric(&self, other: &Variant) -> Option<Ordering> {
+        match self.convert_to_common_type(other) {
+            (Variant::SByte(lhs), Variant::SByte(rhs)) => Some(lhs.cmp(&rhs)),
+            (Variant::Byte(lhs), Variant::Byte(rhs)) => Some(lhs.cmp(&rhs)),
+            (Variant::Int16(lhs), Variant::Int16(rhs)) => Some(lhs.cmp(&rhs)),
+            (Variant::Int32(lhs), Variant::Int32(rhs)) => Some(lhs.cmp(&rhs)),
+            (Variant::Int64(lhs), Variant::Int64(rhs)) => Some(lhs.cmp(&rhs)),
+            (Variant::UInt16(lhs), Variant::UInt16(rhs)) => Some(lhs.cmp(&rhs)),
+            (Variant::UInt32(lhs), Variant::UInt32(rhs)) => Some(lhs.cmp(&rhs)),
+            (Variant::UInt64(lhs), Variant::UInt64(rhs)) => Some(lhs.cmp(&rhs)),
+            (Variant::Double(lhs), Variant::Double(rhs)) => lhs.partial_cmp(&rhs),
+            (Variant::Float(lhs), Variant::Float(rhs)) => lhs.partial_cmp(&rhs),
+            (Variant::Boolean(lhs), Variant::Boolean(rhs)) => Some(lhs.cmp(&rhs)),
+            _ => None,
+        }
+    }
+
     /// Get the type ID of this variant. This can be useful to
     /// work with the variant abstractly, and check if the variant is
     /// of the expected type and dimensions.
@@ -1450,67 +1497,13 @@ impl Variant {
         }
     }
     /// Set a range of values in this variant using a different variant.
+    /// See [`Variant::range_set`].
     pub fn set_range_of(
         &mut self,
         range: &NumericRange,
         other: &Variant,
     ) -> Result<(), StatusCode> {
-        // TODO: This doesn't seem complete.
-        // Types need to be the same
-        if self.data_type() != other.data_type() {
-            return Err(StatusCode::BadIndexRangeDataMismatch);
-        }
-
-        let other_array = if let Variant::Array(other) = other {
-            other
-        } else {
-            return Err(StatusCode::BadIndexRangeNoData);
-        };
-        let other_values = &other_array.values;
-
-        // Check value is same type as our array
-        match self {
-            Variant::Array(ref mut array) => {
-                let values = &mut array.values;
-                match range {
-                    NumericRange::None => Err(StatusCode::BadIndexRangeNoData),
-                    NumericRange::Index(idx) => {
-                        let idx = (*idx) as usize;
-                        if idx >= values.len() || other_values.is_empty() {
-                            Err(StatusCode::BadIndexRangeNoData)
-                        } else {
-                            values[idx] = other_values[0].clone();
-                            Ok(())
-                        }
-                    }
-                    NumericRange::Range(min, max) => {
-                        let (min, max) = ((*min) as usize, (*max) as usize);
-                        if min >= values.len() {
-                            Err(StatusCode::BadIndexRangeNoData)
-                        } else {
-                            // Possibly this could splice or something but it's trying to copy elements
-                            // until either the source or destination array is finished.
-                            let mut idx = min;
-                            while idx < values.len() && idx <= max && idx - min < other_values.len()
-                            {
-                                values[idx] = other_values[idx - min].clone();
-                                idx += 1;
-                            }
-                            Ok(())
-                        }
-                    }
-                    NumericRange::MultipleRanges(_ranges) => {
-                        // Not yet supported
-                        error!("Multiple ranges not supported");
-                        Err(StatusCode::BadIndexRangeNoData)
-                    }
-                }
-            }
-            _ => {
-                error!("Writing a range is not supported when the recipient is not an array");
-                Err(StatusCode::BadWriteNotSupported)
-            }
-        }
+        self.range_set(range, other)
     }
 
     /// This function gets a range of values from the variant if it is an array,
@@ -1523,79 +1516,9 @@ impl Variant {
     }
 
     /// This function gets a range of values from the variant if it is an array, or returns a clone
-    /// of the variant itself.
+    /// of the variant itself. See [`Variant::range_slice`].
     pub fn range_of(&self, range: &NumericRange) -> Result<Variant, StatusCode> {
-        match range {
-            NumericRange::None => Ok(self.clone()),
-            NumericRange::Index(idx) => {
-                let idx = (*idx) as usize;
-                match self {
-                    Variant::String(_) | Variant::ByteString(_) => self.substring(idx, idx),
-                    Variant::Array(array) => {
-                        // Get value at the index (or not)
-                        let values = &array.values;
-                        if let Some(v) = values.get(idx) {
-                            let values = vec![v.clone()];
-                            Ok(Variant::from((array.value_type, values)))
-                        } else {
-                            Err(StatusCode::BadIndexRangeNoData)
-                        }
-                    }
-                    _ => Err(StatusCode::BadIndexRangeDataMismatch),
-                }
-            }
-            NumericRange::Range(min, max) => {
-                let (min, max) = ((*min) as usize, (*max) as usize);
-                match self {
-                    Variant::String(_) | Variant::ByteString(_) => self.substring(min, max),
-                    Variant::Array(array) => {
-                        let values = &array.values;
-                        if min >= values.len() {
-                            // Min must be in range
-                            Err(StatusCode::BadIndexRangeNoData)
-                        } else {
-                            let max = if max >= values.len() {
-                                values.len() - 1
-                            } else {
-                                max
-                            };
-                            let values = &values[min..=max];
-                            let values: Vec<Variant> = values.to_vec();
-                            Ok(Variant::from((array.value_type, values)))
-                        }
-                    }
-                    _ => Err(StatusCode::BadIndexRangeDataMismatch),
-                }
-            }
-            NumericRange::MultipleRanges(ranges) => {
-                let mut res = Vec::new();
-                for range in ranges {
-                    let v = self.range_of(range)?;
-                    match v {
-                        Variant::Array(a) => {
-                            res.extend(a.values.into_iter());
-                        }
-                        r => res.push(r),
-                    }
-                }
-                let type_id = if !res.is_empty() {
-                    let VariantTypeId::Scalar(s) = res[0].type_id() else {
-                        return Err(StatusCode::BadIndexRangeNoData);
-                    };
-                    s
-                } else {
-                    match self.type_id() {
-                        VariantTypeId::Array(s, _) => s,
-                        VariantTypeId::Scalar(s) => s,
-                        VariantTypeId::Empty => return Ok(Variant::Empty),
-                    }
-                };
-
-                Ok(Self::Array(Box::new(
-                    Array::new(type_id, res).map_err(|_| StatusCode::BadInvalidArgument)?,
-                )))
-            }
-        }
+        self.range_slice(range)
     }
 
     /// Try to cast this variant to the type `T`.
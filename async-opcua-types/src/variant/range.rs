@@ -0,0 +1,261 @@
+//! Reading and writing parts of array values using a [`NumericRange`].
+
+use crate::{Array, ByteString, NumericRange, StatusCode, UAString, VariantScalarTypeId};
+
+use super::Variant;
+
+/// Bounds selected by a range in a single dimension of an array.
+#[derive(Debug, Clone, Copy)]
+struct DimensionBounds {
+    /// Index of the first selected element.
+    start: usize,
+    /// Number of selected elements, clamped to the end of the dimension.
+    len: usize,
+}
+
+/// Get the first and last index selected by a range in a single dimension.
+fn range_limits(range: &NumericRange) -> Result<(usize, usize), StatusCode> {
+    match range {
+        NumericRange::Index(idx) => Ok((*idx as usize, *idx as usize)),
+        NumericRange::Range(min, max) if min <= max => Ok((*min as usize, *max as usize)),
+        // `None` is only valid for the whole value, and nested ranges are never valid.
+        NumericRange::Range(..) | NumericRange::None | NumericRange::MultipleRanges(_) => {
+            Err(StatusCode::BadIndexRangeInvalid)
+        }
+    }
+}
+
+impl DimensionBounds {
+    fn new(range: &NumericRange, dimension: usize) -> Result<Self, StatusCode> {
+        let (min, max) = range_limits(range)?;
+        if min >= dimension {
+            return Err(StatusCode::BadIndexRangeNoData);
+        }
+        Ok(Self {
+            start: min,
+            len: max.min(dimension - 1) - min + 1,
+        })
+    }
+}
+
+/// Get the dimensions of `array`. Arrays without explicit dimensions are one-dimensional.
+fn array_dimensions(array: &Array) -> Result<Vec<usize>, StatusCode> {
+    let dims: Vec<_> = match &array.dimensions {
+        Some(dims) if !dims.is_empty() => dims.iter().map(|d| *d as usize).collect(),
+        _ => vec![array.values.len()],
+    };
+    if dims.iter().product::<usize>() != array.values.len() {
+        return Err(StatusCode::BadIndexRangeNoData);
+    }
+    Ok(dims)
+}
+
+/// Get the range for each dimension of `range` in `array`, and the range within each
+/// element if the array contains strings and `range` has one more range than the
+/// array has dimensions.
+fn dimension_ranges<'a>(
+    range: &'a NumericRange,
+    array: &Array,
+    dims: &[usize],
+) -> (&'a [NumericRange], Option<&'a NumericRange>) {
+    let ranges = match range {
+        NumericRange::MultipleRanges(ranges) => ranges,
+        r => std::slice::from_ref(r),
+    };
+    let is_string = matches!(
+        array.value_type,
+        VariantScalarTypeId::String | VariantScalarTypeId::ByteString
+    );
+    match ranges.split_last() {
+        Some((last, rest)) if is_string && rest.len() == dims.len() => (rest, Some(last)),
+        _ => (ranges, None),
+    }
+}
+
+/// Get the bounds of `ranges` in an array with dimensions `dims`.
+fn range_bounds(
+    ranges: &[NumericRange],
+    dims: &[usize],
+) -> Result<Vec<DimensionBounds>, StatusCode> {
+    // The range must specify every dimension of the array.
+    if ranges.len() != dims.len() {
+        return Err(StatusCode::BadIndexRangeInvalid);
+    }
+    ranges
+        .iter()
+        .zip(dims)
+        .map(|(r, d)| DimensionBounds::new(r, *d))
+        .collect()
+}
+
+/// Get the offset of each element in a block with size `extents` in an array with
+/// dimensions `dims`, starting at the element at `start`, in the order the
+/// elements are stored. Higher rank dimensions are stored first.
+fn block_offsets(dims: &[usize], start: &[usize], extents: &[usize]) -> Vec<usize> {
+    let mut strides = vec![1; dims.len()];
+    for i in (0..dims.len().saturating_sub(1)).rev() {
+        strides[i] = strides[i + 1] * dims[i + 1];
+    }
+    let mut offsets = vec![0];
+    for ((start, extent), stride) in start.iter().zip(extents).zip(strides) {
+        offsets = offsets
+            .into_iter()
+            .flat_map(|o| (0..*extent).map(move |k| o + (start + k) * stride))
+            .collect();
+    }
+    offsets
+}
+
+impl Variant {
+    /// Get the bytes of a `String` or `ByteString` value.
+    fn string_bytes(&self) -> Option<&[u8]> {
+        match self {
+            Variant::String(v) => v.value().as_ref().map(|v| v.as_bytes()),
+            Variant::ByteString(v) => v.value.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// Overwrite the part of this `String` or `ByteString` selected by `range`
+    /// with the start of `value`.
+    fn set_substring(&mut self, range: &NumericRange, value: &Variant) -> Result<(), StatusCode> {
+        let (Some(target), Some(source)) = (self.string_bytes(), value.string_bytes()) else {
+            return Err(StatusCode::BadIndexRangeNoData);
+        };
+        let bounds = DimensionBounds::new(range, target.len())?;
+        if source.len() < bounds.len {
+            return Err(StatusCode::BadIndexRangeDataMismatch);
+        }
+        let mut bytes = target.to_vec();
+        bytes[bounds.start..bounds.start + bounds.len].copy_from_slice(&source[..bounds.len]);
+        *self = match self {
+            Variant::String(_) => String::from_utf8(bytes)
+                .map(|v| Variant::from(UAString::from(v)))
+                .map_err(|_| StatusCode::BadIndexRangeDataMismatch)?,
+            _ => Variant::from(ByteString::from(bytes)),
+        };
+        Ok(())
+    }
+
+    /// Get the part of this value selected by `range`.
+    ///
+    /// For arrays, `range` must contain one range for each dimension of the array,
+    /// separated by `,`, so `1:2,0:1` selects a 2x2 block of a matrix. Ranges that extend past
+    /// the end of a dimension are clamped to the end. The result is an array with the dimensions
+    /// of the selected block. For `String` and `ByteString` values, a single range selects
+    /// a substring. For arrays of `String` or `ByteString`, `range` may contain one more
+    /// range than the array has dimensions, which selects a substring of each element.
+    ///
+    /// Returns `BadIndexRangeInvalid` if the number of ranges does not match
+    /// the number of dimensions or a range ends before it starts, `BadIndexRangeNoData`
+    /// if the range starts outside of the value, and `BadIndexRangeDataMismatch` if the value cannot be indexed.
+    pub fn range_slice(&self, range: &NumericRange) -> Result<Variant, StatusCode> {
+        let array = match self {
+            Variant::Array(array) => array,
+            Variant::String(_) | Variant::ByteString(_) => {
+                if range.is_none() {
+                    return Ok(self.clone());
+                }
+                let (min, max) = range_limits(range)?;
+                return self.substring(min, max);
+            }
+            _ if range.is_none() => return Ok(self.clone()),
+            _ => return Err(StatusCode::BadIndexRangeDataMismatch),
+        };
+        if range.is_none() {
+            return Ok(self.clone());
+        }
+
+        let dims = array_dimensions(array)?;
+        let (ranges, element_range) = dimension_ranges(range, array, &dims);
+        let bounds = range_bounds(ranges, &dims)?;
+        let start: Vec<_> = bounds.iter().map(|b| b.start).collect();
+        let extents: Vec<_> = bounds.iter().map(|b| b.len).collect();
+        let values = block_offsets(&dims, &start, &extents)
+            .into_iter()
+            .map(|o| match element_range {
+                Some(r) => array.values[o].range_slice(r),
+                None => Ok(array.values[o].clone()),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let array = if dims.len() == 1 {
+            Array::new(array.value_type, values)
+        } else {
+            let extents: Vec<_> = extents.into_iter().map(|e| e as u32).collect();
+            Array::new_multi(array.value_type, values, extents)
+        };
+        array
+            .map(Variant::from)
+            .map_err(|_| StatusCode::BadUnexpectedError)
+    }
+
+    /// Overwrite the part of this array selected by `range` with the elements of `value`.
+    ///
+    /// `range` selects a block of the array in the same way as [`Variant::range_slice`].
+    /// `value` must be an array of the same type, with the same number of dimensions
+    /// as this array. A one-dimensional `value` is also accepted if it has exactly as many
+    /// elements as the selected block. `value` must cover the selected block, only the
+    /// elements inside the block are written. If `range` selects a substring of each element
+    /// of a `String` or `ByteString` array, that substring is overwritten with the start of
+    /// the corresponding element of `value`.
+    ///
+    /// Returns `BadIndexRangeInvalid` if the number of ranges does not match
+    /// the number of dimensions or a range ends before it starts, `BadIndexRangeNoData`
+    /// if the range starts outside of the array, `BadIndexRangeDataMismatch` if `value`
+    /// does not match the array or is smaller than the selected block,
+    /// and `BadWriteNotSupported` if this is not an array.
+    pub fn range_set(&mut self, range: &NumericRange, value: &Variant) -> Result<(), StatusCode> {
+        // Types need to be the same
+        if self.data_type() != value.data_type() {
+            return Err(StatusCode::BadIndexRangeDataMismatch);
+        }
+        let Variant::Array(source) = value else {
+            return Err(StatusCode::BadIndexRangeNoData);
+        };
+        let Variant::Array(array) = self else {
+            return Err(StatusCode::BadWriteNotSupported);
+        };
+
+        let dims = array_dimensions(array)?;
+        let (ranges, element_range) = dimension_ranges(range, array, &dims);
+        let bounds = range_bounds(ranges, &dims)?;
+        let mut source_dims = array_dimensions(source)?;
+        if source_dims.len() != dims.len() {
+            if source.dimensions.is_none()
+                && source.values.len() == bounds.iter().map(|b| b.len).product::<usize>()
+            {
+                source_dims = bounds.iter().map(|b| b.len).collect();
+            } else {
+                return Err(StatusCode::BadIndexRangeDataMismatch);
+            }
+        }
+
+        if bounds.iter().zip(&source_dims).any(|(b, d)| *d < b.len) {
+            return Err(StatusCode::BadIndexRangeDataMismatch);
+        }
+
+        let start: Vec<_> = bounds.iter().map(|b| b.start).collect();
+        let extents: Vec<_> = bounds.iter().map(|b| b.len).collect();
+        let source_values = &source.values;
+        let targets = block_offsets(&dims, &start, &extents);
+        let sources = block_offsets(&source_dims, &vec![0; dims.len()], &extents);
+        if let Some(element_range) = element_range {
+            // Check every element before writing any of them.
+            let mut values = Vec::with_capacity(targets.len());
+            for (target, source) in targets.iter().zip(sources) {
+                let mut value = array.values[*target].clone();
+                value.set_substring(element_range, &source_values[source])?;
+                values.push(value);
+            }
+            for (target, value) in targets.into_iter().zip(values) {
+                array.values[target] = value;
+            }
+        } else {
+            for (target, source) in targets.into_iter().zip(sources) {
+                array.values[target] = source_values[source].clone();
+            }
+        }
+        Ok(())
+    }
+}
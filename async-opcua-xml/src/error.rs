@@ -14,6 +14,9 @@ pub enum XmlErrorInner {
     #[error("Failed to load XML: {0}")]
     /// XML parsing error.
     Xml(#[from] roxmltree::Error),
+    #[error("Failed to read XML: {0}")]
+    /// Error reading an XML stream.
+    Read(#[from] quick_xml::Error),
     #[error("Expected child: {0}")]
     /// Required field was missing.
     MissingField(String),
@@ -62,6 +65,15 @@ impl From<roxmltree::Error> for XmlError {
     }
 }
 
+impl From<quick_xml::Error> for XmlError {
+    fn from(value: quick_xml::Error) -> Self {
+        Self {
+            span: 0..0,
+            error: XmlErrorInner::Read(value),
+        }
+    }
+}
+
 impl XmlError {
     /// Create an error for a node with a missing field with name `name`.
     pub fn missing_field(node: &Node<'_, '_>, name: &str) -> Self {
@@ -7,7 +7,8 @@
 //! - A subset of the XMLSchema schema in [schema::xml_schema].
 //! - XML schema for OPC-UA BSD files in [schema::opc_binary_schema]
 //! - XML schema for OPC-UA types defined in XSD files in [schema::opc_ua_types]
-//! - XML schema for NodeSet2 files in [schema::ua_node_set]
+//! - XML schema for NodeSet2 files in [schema::ua_node_set], which can also be read
+//!   incrementally with [NodeSet2Reader].
 //!
 //! XML parsing is done with the `roxmltree` crate.
 
@@ -25,6 +26,7 @@ pub use quick_xml::events;
 pub use error::{XmlError, XmlErrorInner};
 pub use schema::opc_binary_schema::load_bsd_file;
 pub use schema::ua_node_set::load_nodeset2_file;
+pub use schema::ua_node_set_stream::NodeSet2Reader;
 pub use schema::xml_schema::load_xsd_schema;
 
 pub use schema::opc_ua_types::XmlElement;
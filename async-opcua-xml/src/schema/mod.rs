@@ -3,4 +3,5 @@
 pub mod opc_binary_schema;
 pub mod opc_ua_types;
 pub mod ua_node_set;
+pub mod ua_node_set_stream;
 pub mod xml_schema;
@@ -0,0 +1,296 @@
+//! Streaming reader for NodeSet2 files.
+
+use std::io::BufRead;
+
+use quick_xml::{events::Event, Reader, Writer};
+use roxmltree::{Document, Node};
+
+use crate::{error::XmlErrorInner, ext::value_from_attr_opt, XmlError, XmlLoad};
+
+use super::ua_node_set::{UANode, UANodeSet};
+
+/// Reader for NodeSet2 files that parses one node at a time.
+///
+/// [`load_nodeset2_file`](super::ua_node_set::load_nodeset2_file) parses the whole document
+/// into a DOM before converting it, which for large node sets means holding both the
+/// document and the full list of nodes in memory. This reader instead reads the XML
+/// incrementally, and only parses a single node element at a time.
+///
+/// The header of the node set, meaning the namespace and server URIs, models and aliases,
+/// is read when the reader is created and is available from [`NodeSet2Reader::header`].
+/// The NodeSet2 schema requires these to come before the nodes. The nodes themselves
+/// are returned by iterating over the reader.
+pub struct NodeSet2Reader<R> {
+    reader: Reader<R>,
+    buffer: Vec<u8>,
+    header: UANodeSet,
+    /// Start tag of the root element. Each node is parsed inside a copy of it,
+    /// so that namespaces declared on the root element are available.
+    root_start: Vec<u8>,
+    root_end: Vec<u8>,
+    next: Option<UANode>,
+    done: bool,
+}
+
+/// A top level element in the node set, serialized back to XML.
+struct Fragment {
+    name: String,
+    /// Position of the element in the original document.
+    position: usize,
+    content: Vec<u8>,
+}
+
+impl<R: BufRead> NodeSet2Reader<R> {
+    /// Create a new reader, reading the header of the node set from `reader`.
+    ///
+    /// Returns an error if the document is not valid XML, or does not contain
+    /// a `UANodeSet` element.
+    pub fn new(reader: R) -> Result<Self, XmlError> {
+        let mut reader = Self {
+            reader: Reader::from_reader(reader),
+            buffer: Vec::new(),
+            header: UANodeSet::default(),
+            root_start: Vec::new(),
+            root_end: Vec::new(),
+            next: None,
+            done: false,
+        };
+        reader.read_root()?;
+        reader.read_header()?;
+        Ok(reader)
+    }
+
+    /// Get the header of the node set. The list of nodes is always empty.
+    pub fn header(&self) -> &UANodeSet {
+        &self.header
+    }
+
+    fn read_root(&mut self) -> Result<(), XmlError> {
+        loop {
+            self.buffer.clear();
+            let (start, is_empty) = match self.reader.read_event_into(&mut self.buffer)? {
+                Event::Start(s) => (s, false),
+                Event::Empty(s) => (s, true),
+                Event::Eof => return Err(missing_field("UANodeSet")),
+                _ => continue,
+            };
+            if start.local_name().as_ref() != b"UANodeSet" {
+                return Err(missing_field("UANodeSet"));
+            }
+            self.root_start = [b"<", &*start, b">"].concat();
+            self.root_end = [b"</", start.name().as_ref(), b">"].concat();
+            self.done = is_empty;
+            break;
+        }
+
+        let root = [self.root_start.as_slice(), &self.root_end].concat();
+        let document = parse_document(&root)?;
+        self.header.last_modified = value_from_attr_opt(&document.root_element(), "LastModified")?;
+        Ok(())
+    }
+
+    fn read_header(&mut self) -> Result<(), XmlError> {
+        let mut header = std::mem::take(&mut self.header);
+        while let Some(fragment) = self.next_fragment()? {
+            let node = self.parse_fragment(&fragment, |node| {
+                match node.tag_name().name() {
+                    "NamespaceUris" => header.namespace_uris = Some(XmlLoad::load(node)?),
+                    "ServerUris" => header.server_uris = Some(XmlLoad::load(node)?),
+                    "Models" => header.models = Some(XmlLoad::load(node)?),
+                    "Aliases" => header.aliases = Some(XmlLoad::load(node)?),
+                    _ => return UANode::from_node(node),
+                }
+                Ok(None)
+            })?;
+            if node.is_some() {
+                self.next = node;
+                break;
+            }
+        }
+        self.header = header;
+        Ok(())
+    }
+
+    fn next_node(&mut self) -> Result<Option<UANode>, XmlError> {
+        if let Some(node) = self.next.take() {
+            return Ok(Some(node));
+        }
+        while let Some(fragment) = self.next_fragment()? {
+            if let Some(node) = self.parse_fragment(&fragment, UANode::from_node)? {
+                return Ok(Some(node));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Read the next child element of the root element, if any.
+    fn next_fragment(&mut self) -> Result<Option<Fragment>, XmlError> {
+        if self.done {
+            return Ok(None);
+        }
+        let mut writer = Writer::new(Vec::new());
+        let mut depth = 0u32;
+        let mut name = String::new();
+        let mut position = 0;
+        loop {
+            let event_position = self.reader.buffer_position() as usize;
+            self.buffer.clear();
+            let event = self.reader.read_event_into(&mut self.buffer)?;
+            match &event {
+                Event::Start(s) | Event::Empty(s) if depth == 0 => {
+                    name = String::from_utf8_lossy(s.local_name().as_ref()).into_owned();
+                    position = event_position;
+                }
+                Event::End(_) if depth == 0 => {
+                    self.done = true;
+                    return Ok(None);
+                }
+                Event::Eof => {
+                    self.done = true;
+                    return Err(XmlError {
+                        span: event_position..event_position,
+                        error: XmlErrorInner::MissingContent,
+                    });
+                }
+                // Whitespace and comments between elements.
+                _ if depth == 0 => continue,
+                _ => (),
+            }
+            match &event {
+                Event::Start(_) => depth += 1,
+                Event::End(_) => depth -= 1,
+                _ => (),
+            }
+            writer
+                .write_event(event)
+                .map_err(|e| XmlError::from(quick_xml::Error::from(e)))?;
+            if depth == 0 {
+                return Ok(Some(Fragment {
+                    name,
+                    position,
+                    content: writer.into_inner(),
+                }));
+            }
+        }
+    }
+
+    fn parse_fragment<T>(
+        &self,
+        fragment: &Fragment,
+        parse: impl FnOnce(&Node<'_, '_>) -> Result<T, XmlError>,
+    ) -> Result<T, XmlError> {
+        let content = [
+            self.root_start.as_slice(),
+            &fragment.content,
+            &self.root_end,
+        ]
+        .concat();
+        // Report errors relative to the original document.
+        let offset = |mut e: XmlError| {
+            let start = self.root_start.len();
+            e.span = (e.span.start.saturating_sub(start) + fragment.position)
+                ..(e.span.end.saturating_sub(start) + fragment.position);
+            e
+        };
+        let document = parse_document(&content).map_err(offset)?;
+        let node = document
+            .root_element()
+            .first_element_child()
+            .ok_or_else(|| missing_field(&fragment.name))?;
+        parse(&node).map_err(offset)
+    }
+}
+
+impl<R: BufRead> Iterator for NodeSet2Reader<R> {
+    type Item = Result<UANode, XmlError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let r = self.next_node().transpose();
+        if matches!(r, Some(Err(_))) {
+            // The position in the stream is undefined after an error.
+            self.done = true;
+            self.next = None;
+        }
+        r
+    }
+}
+
+fn parse_document(content: &[u8]) -> Result<Document<'_>, XmlError> {
+    let text = std::str::from_utf8(content).map_err(|e| XmlError {
+        span: e.valid_up_to()..e.valid_up_to(),
+        error: XmlErrorInner::Other(e.to_string()),
+    })?;
+    Ok(Document::parse(text)?)
+}
+
+fn missing_field(name: &str) -> XmlError {
+    XmlError {
+        span: 0..0,
+        error: XmlErrorInner::MissingField(name.to_owned()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{load_nodeset2_file, schema::ua_node_set::UANode};
+
+    use super::NodeSet2Reader;
+
+    const NODESET: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<!-- Test node set -->
+<UANodeSet xmlns="http://opcfoundation.org/UA/2011/03/UANodeSet.xsd" xmlns:uax="http://opcfoundation.org/UA/2008/02/Types.xsd" LastModified="2023-12-15T00:00:00Z">
+  <NamespaceUris>
+    <Uri>http://test.com</Uri>
+  </NamespaceUris>
+  <Aliases>
+    <Alias Alias="Int32">i=6</Alias>
+  </Aliases>
+  <Extensions><Extension><Foo /></Extension></Extensions>
+  <UAObject NodeId="ns=1;i=1" BrowseName="1:Root">
+    <DisplayName>Root</DisplayName>
+    <References>
+      <Reference ReferenceType="i=47" IsForward="false">i=85</Reference>
+    </References>
+  </UAObject>
+  <!-- Comment between nodes -->
+  <UAVariable NodeId="ns=1;i=2" BrowseName="1:Var" DataType="Int32">
+    <DisplayName>Var &amp; value</DisplayName>
+    <References />
+    <Value><uax:Int32>5</uax:Int32></Value>
+  </UAVariable>
+  <UAMethod NodeId="ns=1;i=3" BrowseName="1:Method" />
+</UANodeSet>"#;
+
+    #[test]
+    fn read_nodeset_stream() {
+        let reader = NodeSet2Reader::new(NODESET.as_bytes()).unwrap();
+        let header = reader.header();
+        assert_eq!(
+            header.namespace_uris.as_ref().unwrap().uris,
+            vec!["http://test.com".to_owned()]
+        );
+        assert_eq!(header.aliases.as_ref().unwrap().aliases.len(), 1);
+        assert!(header.last_modified.is_some());
+
+        let nodes = reader.collect::<Result<Vec<_>, _>>().unwrap();
+        let loaded = load_nodeset2_file(NODESET).unwrap().node_set.unwrap();
+        assert_eq!(nodes.len(), 3);
+        assert_eq!(format!("{nodes:?}"), format!("{:?}", loaded.nodes));
+        let UANode::Variable(var) = &nodes[1] else {
+            panic!("Expected variable");
+        };
+        assert_eq!(var.base.base.display_names[0].text, "Var & value");
+    }
+
+    #[test]
+    fn read_nodeset_stream_invalid() {
+        assert!(NodeSet2Reader::new("<Foo />".as_bytes()).is_err());
+
+        let truncated = &NODESET[..NODESET.find("<UAMethod").unwrap()];
+        let mut reader = NodeSet2Reader::new(truncated.as_bytes()).unwrap();
+        assert!(reader.next().unwrap().is_ok());
+        assert!(reader.next().unwrap().is_ok());
+        assert!(reader.next().unwrap().is_err());
+        assert!(reader.next().is_none());
+    }
+}
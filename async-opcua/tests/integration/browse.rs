@@ -1,16 +1,23 @@
-use super::utils::setup;
+use std::time::Duration;
+
+use super::utils::{
+    paged_node_manager, setup, setup_with, test_server, PagedNodeManager, TestNodeManager, Tester,
+};
 use opcua::{
     nodes::TypeTree,
     server::address_space::{ObjectBuilder, ReferenceDirection, VariableBuilder},
     types::{
-        BrowseDescription, BrowseDirection, BrowsePath, BrowseResultMask, ByteString, DataTypeId,
-        NodeClass, NodeClassMask, NodeId, ObjectId, ObjectTypeId, ReferenceTypeId, RelativePath,
-        RelativePathElement, StatusCode, VariableTypeId,
+        BrowseDescription, BrowseDirection, BrowsePath, BrowseResult, BrowseResultMask, ByteString,
+        DataTypeId, ExpandedNodeId, NodeClass, NodeClassMask, NodeId, ObjectId, ObjectTypeId,
+        ReferenceTypeId, RelativePath, RelativePathElement, StatusCode, VariableTypeId,
     },
 };
-use opcua_client::browser::BrowseFilter;
+use opcua_client::{browser::BrowseFilter, ResolvedTarget};
 use opcua_nodes::DefaultTypeTree;
-use opcua_types::{AttributeId, ReadValueId, TimestampsToReturn, VariableId, Variant};
+use opcua_types::{
+    AttributeId, ContentFilter, ContentFilterBuilder, NodeTypeDescription, Operand,
+    QueryDataDescription, ReadValueId, TimestampsToReturn, VariableId, Variant,
+};
 
 fn hierarchical_desc(node_id: NodeId) -> BrowseDescription {
     BrowseDescription {
@@ -349,6 +356,219 @@ async fn browse_release_continuation_point() {
     assert!(refs.is_empty());
 }
 
+#[tokio::test]
+async fn browse_continuation_point_timeout() {
+    let server = test_server().browse_continuation_point_timeout_ms(200);
+    let mut tester = Tester::new(server, false).await;
+    let nm = tester
+        .handle
+        .node_managers()
+        .get_of_type::<TestNodeManager>()
+        .unwrap();
+    let (session, lp) = tester.connect_default().await.unwrap();
+    lp.spawn();
+    tokio::time::timeout(Duration::from_secs(2), session.wait_for_connection())
+        .await
+        .unwrap();
+
+    let root_id = nm.inner().next_node_id();
+    nm.inner().add_node(
+        nm.address_space(),
+        tester.handle.type_tree(),
+        ObjectBuilder::new(&root_id, "TestObj1", "TestObj1")
+            .build()
+            .into(),
+        &ObjectId::ObjectsFolder.into(),
+        &ReferenceTypeId::Organizes.into(),
+        Some(&ObjectTypeId::FolderType.into()),
+        Vec::new(),
+    );
+    for i in 0..200 {
+        let id = nm.inner().next_node_id();
+        nm.inner().add_node(
+            nm.address_space(),
+            tester.handle.type_tree(),
+            VariableBuilder::new(&id, format!("Var{i}"), format!("Var{i}"))
+                .data_type(DataTypeId::Int32)
+                .build()
+                .into(),
+            &root_id,
+            &ReferenceTypeId::HasComponent.into(),
+            Some(&VariableTypeId::BaseDataVariableType.into()),
+            Vec::new(),
+        );
+    }
+
+    // Continuation points can be used before they expire.
+    let desc = hierarchical_desc(root_id);
+    let r = session
+        .browse(std::slice::from_ref(&desc), 100, None)
+        .await
+        .unwrap();
+    let cp = r[0].continuation_point.clone();
+    assert!(!cp.is_null());
+    let r = session.browse_next(false, &[cp]).await.unwrap();
+    assert_eq!(StatusCode::Good, r[0].status_code);
+    assert_eq!(100, r[0].references.as_ref().unwrap().len());
+
+    // After the timeout they are invalid.
+    let r = session.browse(&[desc], 100, None).await.unwrap();
+    let cp = r[0].continuation_point.clone();
+    assert!(!cp.is_null());
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    let r = session.browse_next(false, &[cp]).await.unwrap();
+    assert_eq!(StatusCode::BadContinuationPointInvalid, r[0].status_code);
+}
+
+#[tokio::test]
+async fn browse_cursor() {
+    let (tester, _nm, session) = setup_with(
+        test_server()
+            .with_node_manager(paged_node_manager())
+            .browse_continuation_point_timeout_ms(300),
+    )
+    .await;
+    let nm = tester
+        .handle
+        .node_managers()
+        .get_of_type::<PagedNodeManager>()
+        .unwrap();
+    let ids: Vec<_> = (0..25)
+        .map(|i| nm.add_variable(&format!("Var{i}")))
+        .collect();
+    let ref_ids = |r: &BrowseResult| -> Vec<NodeId> {
+        r.references
+            .iter()
+            .flatten()
+            .map(|r| r.node_id.node_id.clone())
+            .collect()
+    };
+
+    let desc = hierarchical_desc(nm.root_id());
+    let r = session
+        .browse(std::slice::from_ref(&desc), 10, None)
+        .await
+        .unwrap();
+    assert_eq!(ref_ids(&r[0]), ids[0..10]);
+    assert_eq!(nm.live_continuation_points(), 1);
+
+    // Removing a variable that was already returned does not shift the cursor,
+    // since it resumes after the last returned node.
+    nm.remove_variable(&ids[3]);
+    let r = session
+        .browse_next(false, &[r[0].continuation_point.clone()])
+        .await
+        .unwrap();
+    assert_eq!(StatusCode::Good, r[0].status_code);
+    assert_eq!(ref_ids(&r[0]), ids[10..20]);
+    let cp = r[0].continuation_point.clone();
+    assert!(!cp.is_null());
+    assert_eq!(nm.live_continuation_points(), 1);
+
+    // Unused continuation points are released after the timeout,
+    // without the client using them again.
+    tokio::time::timeout(Duration::from_secs(3), async {
+        while nm.live_continuation_points() > 0 {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+    })
+    .await
+    .unwrap();
+    let r = session.browse_next(false, &[cp]).await.unwrap();
+    assert_eq!(StatusCode::BadContinuationPointInvalid, r[0].status_code);
+}
+
+#[tokio::test]
+async fn browse_max_continuation_points() {
+    let server = test_server().max_browse_continuation_points(2);
+    let mut tester = Tester::new(server, false).await;
+    let nm = tester
+        .handle
+        .node_managers()
+        .get_of_type::<TestNodeManager>()
+        .unwrap();
+    let (session, lp) = tester.connect_default().await.unwrap();
+    lp.spawn();
+    tokio::time::timeout(Duration::from_secs(2), session.wait_for_connection())
+        .await
+        .unwrap();
+
+    let root_id = nm.inner().next_node_id();
+    nm.inner().add_node(
+        nm.address_space(),
+        tester.handle.type_tree(),
+        ObjectBuilder::new(&root_id, "TestObj1", "TestObj1")
+            .build()
+            .into(),
+        &ObjectId::ObjectsFolder.into(),
+        &ReferenceTypeId::Organizes.into(),
+        Some(&ObjectTypeId::FolderType.into()),
+        Vec::new(),
+    );
+    for i in 0..20 {
+        let id = nm.inner().next_node_id();
+        nm.inner().add_node(
+            nm.address_space(),
+            tester.handle.type_tree(),
+            VariableBuilder::new(&id, format!("Var{i}"), format!("Var{i}"))
+                .data_type(DataTypeId::Int32)
+                .build()
+                .into(),
+            &root_id,
+            &ReferenceTypeId::HasComponent.into(),
+            Some(&VariableTypeId::BaseDataVariableType.into()),
+            Vec::new(),
+        );
+    }
+
+    // The limit is exposed in the server capabilities.
+    let r = session
+        .read(
+            &[ReadValueId::new_value(
+                VariableId::Server_ServerCapabilities_MaxBrowseContinuationPoints.into(),
+            )],
+            TimestampsToReturn::Neither,
+            0.0,
+        )
+        .await
+        .unwrap();
+    assert_eq!(r[0].value, Some(Variant::UInt16(2)));
+
+    // Only two of the nodes get a continuation point, the last one is rejected.
+    // Nodes are not necessarily processed in order.
+    let desc = hierarchical_desc(root_id);
+    let r = session
+        .browse(&[desc.clone(), desc.clone(), desc.clone()], 5, None)
+        .await
+        .unwrap();
+    assert_eq!(3, r.len());
+    let (ok, rejected): (Vec<_>, Vec<_>) = r.into_iter().partition(|it| it.status_code.is_good());
+    assert_eq!(2, ok.len());
+    for it in &ok {
+        assert!(!it.continuation_point.is_null());
+    }
+    assert_eq!(StatusCode::BadNoContinuationPoints, rejected[0].status_code);
+    assert!(rejected[0].continuation_point.is_null());
+    assert!(rejected[0].references.is_none());
+
+    // Continuing a browse reuses the slot of the consumed continuation point.
+    let r2 = session
+        .browse_next(false, &[ok[0].continuation_point.clone()])
+        .await
+        .unwrap();
+    assert_eq!(StatusCode::Good, r2[0].status_code);
+    assert!(!r2[0].continuation_point.is_null());
+
+    // Releasing a continuation point makes room for a new one.
+    session
+        .browse_next(true, &[ok[1].continuation_point.clone()])
+        .await
+        .unwrap();
+    let r = session.browse(&[desc], 5, None).await.unwrap();
+    assert_eq!(StatusCode::Good, r[0].status_code);
+    assert!(!r[0].continuation_point.is_null());
+}
+
 #[tokio::test]
 async fn browse_limits() {
     let (tester, _nm, session) = setup().await;
@@ -662,3 +882,246 @@ async fn test_recursive_browser_multi_hit() {
     // Note: This value is expected to change with new versions of the standard.
     assert_eq!(rs.len(), 2247);
 }
+
+#[tokio::test]
+async fn browse_recursive() {
+    let (_tester, _nm, session) = setup().await;
+
+    // A depth of 0 returns only the direct references.
+    let direct = session
+        .browse(&[hierarchical_desc(ObjectId::Server.into())], 1000, None)
+        .await
+        .unwrap();
+    let direct = direct[0].references.clone().unwrap_or_default();
+    let res = session
+        .browse_recursive(ObjectId::Server, ReferenceTypeId::HierarchicalReferences, 0)
+        .await
+        .unwrap();
+    assert_eq!(res.len(), direct.len());
+    assert!(res
+        .iter()
+        .any(|r| r.node_id.node_id == VariableId::Server_NamespaceArray));
+    assert!(!res
+        .iter()
+        .any(|r| r.node_id.node_id == VariableId::Server_ServerStatus_State));
+
+    // A depth of 1 also returns the children of the direct references.
+    let res = session
+        .browse_recursive(ObjectId::Server, ReferenceTypeId::HierarchicalReferences, 1)
+        .await
+        .unwrap();
+    assert!(res.len() > direct.len());
+    assert!(res
+        .iter()
+        .any(|r| r.node_id.node_id == VariableId::Server_ServerStatus_State));
+
+    // Nodes reached through multiple paths are only returned once.
+    let res = session
+        .browse_recursive(ObjectId::TypesFolder, ReferenceTypeId::References, 3)
+        .await
+        .unwrap();
+    let mut ids: Vec<_> = res.iter().map(|r| &r.node_id).collect();
+    let len = ids.len();
+    ids.sort_by_key(|n| n.to_string());
+    ids.dedup();
+    assert_eq!(len, ids.len());
+}
+
+#[tokio::test]
+async fn browse_both_directions() {
+    let (tester, nm, session) = setup().await;
+    let var_id = nm.inner().next_node_id();
+    let prop_id = nm.inner().next_node_id();
+    nm.inner().add_node(
+        nm.address_space(),
+        tester.handle.type_tree(),
+        VariableBuilder::new(&var_id, "TestVar1", "TestVar1")
+            .data_type(DataTypeId::Int32)
+            .value(0i32)
+            .build()
+            .into(),
+        &ObjectId::ObjectsFolder.into(),
+        &ReferenceTypeId::Organizes.into(),
+        Some(&VariableTypeId::BaseDataVariableType.into()),
+        Vec::new(),
+    );
+    nm.inner().add_node(
+        nm.address_space(),
+        tester.handle.type_tree(),
+        VariableBuilder::new(&prop_id, "TestProp1", "TestProp1")
+            .data_type(DataTypeId::String)
+            .value("foo")
+            .build()
+            .into(),
+        &var_id,
+        &ReferenceTypeId::HasProperty.into(),
+        Some(&VariableTypeId::PropertyType.into()),
+        Vec::new(),
+    );
+
+    let browse = |direction: BrowseDirection| {
+        let session = session.clone();
+        let var_id = var_id.clone();
+        async move {
+            let mut desc = hierarchical_desc(var_id);
+            desc.browse_direction = direction;
+            desc.reference_type_id = NodeId::null();
+            let r = session.browse(&[desc], 1000, None).await.unwrap();
+            let mut refs: Vec<_> = r[0]
+                .references
+                .clone()
+                .unwrap_or_default()
+                .into_iter()
+                .map(|r| (r.node_id.node_id, r.reference_type_id, r.is_forward))
+                .collect();
+            refs.sort_by_key(|r| r.0.to_string());
+            refs
+        }
+    };
+
+    let property = (prop_id.clone(), ReferenceTypeId::HasProperty.into(), true);
+    let type_definition = (
+        VariableTypeId::BaseDataVariableType.into(),
+        ReferenceTypeId::HasTypeDefinition.into(),
+        true,
+    );
+    let parent = (
+        ObjectId::ObjectsFolder.into(),
+        ReferenceTypeId::Organizes.into(),
+        false,
+    );
+
+    let mut expected = vec![property.clone(), type_definition.clone(), parent.clone()];
+    expected.sort_by_key(|r: &(NodeId, NodeId, bool)| r.0.to_string());
+    assert_eq!(browse(BrowseDirection::Both).await, expected);
+
+    let mut expected = vec![property, type_definition];
+    expected.sort_by_key(|r: &(NodeId, NodeId, bool)| r.0.to_string());
+    assert_eq!(browse(BrowseDirection::Forward).await, expected);
+
+    assert_eq!(browse(BrowseDirection::Inverse).await, vec![parent]);
+}
+
+#[tokio::test]
+async fn resolve_expanded_node_id() {
+    let (tester, _nm, session) = setup().await;
+
+    let servers = session.read_server_array().await.unwrap();
+    assert_eq!(
+        servers,
+        vec![tester.handle.info().application_uri.as_ref().to_owned()]
+    );
+
+    // Local node, namespace given by index.
+    let id = ExpandedNodeId::new(NodeId::from(ObjectId::ObjectsFolder));
+    assert_eq!(
+        session.resolve_expanded_node_id(&id).await.unwrap(),
+        ResolvedTarget::Local(ObjectId::ObjectsFolder.into())
+    );
+
+    // Local node, namespace given by URI.
+    let id = ExpandedNodeId::new_with_namespace("urn:rustopcuatestserver", 15);
+    let idx = session
+        .get_namespace_index("urn:rustopcuatestserver")
+        .await
+        .unwrap();
+    assert_eq!(
+        session.resolve_expanded_node_id(&id).await.unwrap(),
+        ResolvedTarget::Local(NodeId::new(idx, 15))
+    );
+
+    // Unknown namespace.
+    let id = ExpandedNodeId::new_with_namespace("urn:unknown", 15);
+    let err = session.resolve_expanded_node_id(&id).await.unwrap_err();
+    assert_eq!(err.status(), StatusCode::BadNodeIdUnknown);
+
+    // Server index 0 is the server itself, anything else must be in the server array.
+    let id = ExpandedNodeId::new_remote(1, "urn:remote", "Tag1");
+    let err = session.resolve_expanded_node_id(&id).await.unwrap_err();
+    assert_eq!(err.status(), StatusCode::BadServerIndexInvalid);
+}
+
+#[tokio::test]
+async fn query_instances_of_type() {
+    let (tester, nm, session) = setup().await;
+
+    for i in 0..3 {
+        let id = nm.inner().next_node_id();
+        nm.inner().add_node(
+            nm.address_space(),
+            tester.handle.type_tree(),
+            ObjectBuilder::new(&id, format!("QueryObj{i}"), format!("QueryObj{i}"))
+                .build()
+                .into(),
+            &ObjectId::ObjectsFolder.into(),
+            &ReferenceTypeId::Organizes.into(),
+            Some(&ObjectTypeId::ServerType.into()),
+            Vec::new(),
+        );
+    }
+
+    let node_type = NodeTypeDescription {
+        type_definition_node: ObjectTypeId::ServerType.into(),
+        include_sub_types: false,
+        data_to_return: Some(vec![QueryDataDescription {
+            relative_path: RelativePath { elements: None },
+            attribute_id: AttributeId::BrowseName as u32,
+            index_range: Default::default(),
+        }]),
+    };
+
+    // Fetch all instances of the type, two at a time.
+    let r = session
+        .query_first(
+            std::slice::from_ref(&node_type),
+            ContentFilter::default(),
+            2,
+            None,
+        )
+        .await
+        .unwrap();
+    let mut data_sets = r.query_data_sets.unwrap_or_default();
+    assert_eq!(data_sets.len(), 2);
+    assert!(!r.continuation_point.is_null());
+
+    let r = session
+        .query_next(false, r.continuation_point)
+        .await
+        .unwrap();
+    data_sets.extend(r.query_data_sets.unwrap_or_default());
+    assert!(r.revised_continuation_point.is_null());
+
+    let mut names: Vec<_> = data_sets
+        .iter()
+        .map(|d| {
+            assert_eq!(d.type_definition_node, ObjectTypeId::ServerType.into());
+            let Some(Variant::QualifiedName(name)) = d.values.as_ref().unwrap().first() else {
+                panic!("Wrong result type");
+            };
+            name.name.to_string()
+        })
+        .collect();
+    names.sort();
+    assert_eq!(names, ["QueryObj0", "QueryObj1", "QueryObj2", "Server"]);
+
+    // Filter on a property of the type, only the server has a ServerCapabilities object.
+    let filter = ContentFilterBuilder::new()
+        .eq(
+            Operand::simple_attribute(
+                ObjectTypeId::ServerType,
+                "ServerCapabilities",
+                AttributeId::NodeId,
+                Default::default(),
+            ),
+            Operand::literal(ObjectId::Server_ServerCapabilities),
+        )
+        .build();
+    let r = session
+        .query_first(&[node_type], filter, 0, None)
+        .await
+        .unwrap();
+    let data_sets = r.query_data_sets.unwrap_or_default();
+    assert_eq!(data_sets.len(), 1);
+    assert_eq!(data_sets[0].node_id, ObjectId::Server.into());
+    assert!(r.continuation_point.is_null());
+}
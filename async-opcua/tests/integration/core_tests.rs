@@ -1,5 +1,8 @@
 use std::{
-    sync::{atomic::Ordering, Arc},
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    },
     time::Duration,
 };
 
@@ -8,30 +11,39 @@ use async_trait::async_trait;
 use bytes::BytesMut;
 use log::debug;
 use opcua::{
-    client::IdentityToken,
-    core::comms::tcp_codec::{Message, TcpCodec},
+    client::{Client, ConnectBestError, ConnectionState, IdentityToken},
+    core::comms::{
+        tcp_codec::{Message, TcpCodec},
+        tcp_types::{HelloMessage, ReverseHelloMessage},
+    },
     core::config::Config,
-    crypto::SecurityPolicy,
+    crypto::{CertificateList, CertificateStore, InMemoryCertificateStore, SecurityPolicy},
     types::{
         ApplicationType, DecodingOptions, MessageSecurityMode, NodeId, ReadValueId, StatusCode,
         TimestampsToReturn, VariableId, Variant,
     },
 };
-use opcua_client::IssuedTokenWrapper;
+use opcua_client::{
+    services::{Publish, Read},
+    DataChangeCallback, DefaultRetryPolicy, ExponentialBackoff, IssuedTokenSource,
+    IssuedTokenWrapper, UARequest,
+};
 use opcua_server::{
     authenticator::{issued_token_security_policy, AuthManager, UserToken},
     ServerEndpoint,
 };
-use opcua_types::{ByteString, Error, UAString, UserTokenPolicy, UserTokenType};
+use opcua_types::{
+    ByteString, Error, SimpleBinaryEncodable, UAString, UserTokenPolicy, UserTokenType,
+};
 use tokio::{
-    io::AsyncReadExt,
+    io::{AsyncReadExt, AsyncWriteExt},
     net::{TcpListener, TcpStream},
 };
 use tokio_util::codec::Decoder;
 
 use crate::utils::{
-    client_user_token, client_x509_token, copy_shared_certs, default_server, test_server, Tester,
-    CLIENT_USERPASS_ID, TEST_COUNTER,
+    client_user_token, client_x509_token, copy_shared_certs, default_client, default_server,
+    test_server, Tester, CLIENT_USERPASS_ID, TEST_COUNTER,
 };
 
 #[tokio::test]
@@ -133,6 +145,100 @@ async fn connect_none() {
     .await;
 }
 
+/// Tracing subscriber recording the messages of events on the `connection_trace` target.
+struct ConnectionTraceCapture(Arc<opcua::sync::Mutex<Vec<String>>>);
+
+struct MessageVisitor<'a>(&'a mut String);
+
+impl tracing::field::Visit for MessageVisitor<'_> {
+    fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            *self.0 = format!("{value:?}");
+        }
+    }
+}
+
+impl tracing::Subscriber for ConnectionTraceCapture {
+    fn register_callsite(
+        &self,
+        _metadata: &'static tracing::Metadata<'static>,
+    ) -> tracing::subscriber::Interest {
+        tracing::subscriber::Interest::sometimes()
+    }
+
+    fn enabled(&self, metadata: &tracing::Metadata<'_>) -> bool {
+        metadata.target() == "connection_trace"
+    }
+
+    fn new_span(&self, _span: &tracing::span::Attributes<'_>) -> tracing::span::Id {
+        tracing::span::Id::from_u64(1)
+    }
+
+    fn record(&self, _span: &tracing::span::Id, _values: &tracing::span::Record<'_>) {}
+
+    fn record_follows_from(&self, _span: &tracing::span::Id, _follows: &tracing::span::Id) {}
+
+    fn event(&self, event: &tracing::Event<'_>) {
+        let mut message = String::new();
+        event.record(&mut MessageVisitor(&mut message));
+        self.0.lock().push(message);
+    }
+
+    fn enter(&self, _span: &tracing::span::Id) {}
+
+    fn exit(&self, _span: &tracing::span::Id) {}
+}
+
+#[tokio::test]
+async fn connection_trace() {
+    // The test runtime is single threaded, so this captures events from the server as well.
+    let captured = Arc::new(opcua::sync::Mutex::new(Vec::new()));
+    let _guard = tracing::subscriber::set_default(ConnectionTraceCapture(captured.clone()));
+
+    let mut tester = Tester::new_default_server(false).await;
+    let (session, handle) = tester
+        .connect(
+            SecurityPolicy::Basic256Sha256,
+            MessageSecurityMode::SignAndEncrypt,
+            IdentityToken::Anonymous,
+        )
+        .await
+        .unwrap();
+    let _h = handle.spawn();
+
+    tokio::time::timeout(Duration::from_secs(20), session.wait_for_connection())
+        .await
+        .unwrap();
+    assert!(captured.lock().is_empty());
+
+    let session_id = session.server_session_id();
+    let read = || async {
+        session
+            .read(
+                &[ReadValueId::from(<VariableId as Into<NodeId>>::into(
+                    VariableId::Server_ServiceLevel,
+                ))],
+                TimestampsToReturn::Both,
+                0.0,
+            )
+            .await
+            .unwrap();
+    };
+    assert!(tester.handle.set_connection_trace(&session_id, true));
+    read().await;
+    // Both the request and the response are traced.
+    let traced = std::mem::take(&mut *captured.lock());
+    assert!(traced.len() >= 2, "{traced:?}");
+
+    assert!(tester.handle.set_connection_trace(&session_id, false));
+    read().await;
+    assert!(captured.lock().is_empty());
+
+    assert!(!tester
+        .handle
+        .set_connection_trace(&NodeId::new(1, 123456), true));
+}
+
 #[tokio::test]
 async fn connect_basic128rsa15_sign() {
     conn_test(
@@ -213,6 +319,107 @@ async fn connect_aes256sha256rsapss_sign_and_encrypt() {
     .await;
 }
 
+#[tokio::test]
+async fn connect_with_in_memory_certificate_store() {
+    let tester = Tester::new_default_server(false).await;
+
+    // The client PKI is kept entirely in memory, so the client never touches the disk.
+    let config = default_client(tester.test_id, false).config();
+    let certificate_store = CertificateStore::new_with_backend(InMemoryCertificateStore::new());
+    let (cert, pkey) =
+        certificate_store.read_or_create_own_cert(false, Some(config.application_description()));
+    assert!(cert.is_some() && pkey.is_some());
+    let mut client = Client::new_with_certificate_store(config, certificate_store);
+
+    let (session, handle) = client
+        .connect_to_matching_endpoint(
+            (
+                &tester.endpoint() as &str,
+                SecurityPolicy::Basic256Sha256.to_str(),
+                MessageSecurityMode::SignAndEncrypt,
+            ),
+            IdentityToken::Anonymous,
+        )
+        .await
+        .unwrap();
+    let _h = handle.spawn();
+
+    tokio::time::timeout(Duration::from_secs(20), session.wait_for_connection())
+        .await
+        .unwrap();
+
+    session
+        .read(
+            &[ReadValueId::from(<VariableId as Into<NodeId>>::into(
+                VariableId::Server_ServiceLevel,
+            ))],
+            TimestampsToReturn::Both,
+            0.0,
+        )
+        .await
+        .unwrap();
+
+    // The server certificate was trusted in memory.
+    let trusted = client
+        .certificate_store()
+        .read()
+        .backend()
+        .read_certs(CertificateList::Trusted);
+    assert_eq!(trusted.len(), 1);
+}
+
+#[tokio::test]
+async fn connect_best_endpoint() {
+    let mut tester = Tester::new_default_server(false).await;
+    let endpoint = tester.endpoint();
+
+    let (session, lp) = tester
+        .client
+        .connect_best(&endpoint, IdentityToken::Anonymous, true)
+        .await
+        .unwrap();
+    lp.spawn();
+    tokio::time::timeout(Duration::from_secs(20), session.wait_for_connection())
+        .await
+        .unwrap();
+    assert_eq!(
+        session.endpoint_info().endpoint.security_mode,
+        MessageSecurityMode::SignAndEncrypt
+    );
+
+    // No endpoint on the server accepts issued tokens.
+    let err = tester
+        .client
+        .connect_best(
+            &endpoint,
+            IdentityToken::IssuedToken(IssuedTokenWrapper::new_source(ByteString::from(
+                "token".as_bytes(),
+            ))),
+            false,
+        )
+        .await
+        .err()
+        .unwrap();
+    // The error says why each endpoint was rejected.
+    let ConnectBestError::NoSuitableEndpoint(reason) = err else {
+        panic!("Unexpected error {err}");
+    };
+    assert!(
+        reason.contains("issued identity tokens are not supported"),
+        "{reason}"
+    );
+    assert!(!reason.contains("security mode"), "{reason}");
+
+    // Discovery errors are reported separately.
+    let err = tester
+        .client
+        .connect_best("opc.tcp://localhost:1", IdentityToken::Anonymous, true)
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(err, ConnectBestError::Discovery(_)));
+}
+
 #[tokio::test]
 async fn connect_basic128rsa15_with_username_password() {
     conn_test(
@@ -248,6 +455,59 @@ async fn connect_basic128rsa_15_with_invalid_token() {
     assert_eq!(res, StatusCode::BadIdentityTokenRejected);
 }
 
+/// Connect directly to an endpoint the server does not offer, by taking an
+/// offered endpoint and replacing its security policy and mode.
+async fn rejected_conn_test(
+    removed: impl Fn(&ServerEndpoint) -> bool,
+    policy: SecurityPolicy,
+    mode: MessageSecurityMode,
+) -> StatusCode {
+    let mut server = test_server();
+    server.config_mut().endpoints.retain(|_, e| !removed(e));
+    let mut tester = Tester::new(server, true).await;
+    let mut endpoint = tester
+        .client
+        .get_server_endpoints_from_url(tester.endpoint())
+        .await
+        .unwrap()
+        .into_iter()
+        .find(|e| e.security_mode == MessageSecurityMode::SignAndEncrypt)
+        .unwrap();
+    endpoint.security_policy_uri = policy.to_uri().into();
+    endpoint.security_mode = mode;
+
+    let (_, handle) = tester
+        .client
+        .connect_to_endpoint_directly(endpoint, IdentityToken::Anonymous)
+        .unwrap();
+    handle.spawn().await.unwrap()
+}
+
+#[tokio::test]
+async fn connect_unoffered_security_policy() {
+    let res = rejected_conn_test(
+        |e| e.security_policy() == SecurityPolicy::Aes256Sha256RsaPss,
+        SecurityPolicy::Aes256Sha256RsaPss,
+        MessageSecurityMode::SignAndEncrypt,
+    )
+    .await;
+    assert_eq!(res, StatusCode::BadSecurityPolicyRejected);
+}
+
+#[tokio::test]
+async fn connect_unoffered_security_mode() {
+    let res = rejected_conn_test(
+        |e| {
+            e.security_policy() == SecurityPolicy::Basic256Sha256
+                && e.message_security_mode() == MessageSecurityMode::Sign
+        },
+        SecurityPolicy::Basic256Sha256,
+        MessageSecurityMode::Sign,
+    )
+    .await;
+    assert_eq!(res, StatusCode::BadSecurityModeRejected);
+}
+
 #[tokio::test]
 async fn find_servers() {
     let tester = Tester::new_default_server(true).await;
@@ -267,6 +527,47 @@ async fn find_servers() {
     assert_eq!(s.product_uri.as_ref(), "urn:integration_server Testkit");
 }
 
+#[tokio::test]
+async fn find_servers_filter() {
+    let tester = Tester::new_default_server(true).await;
+    let servers = tester
+        .client
+        .find_servers(
+            tester.endpoint(),
+            None,
+            Some(vec!["urn:integration_server".into()]),
+        )
+        .await
+        .unwrap();
+    assert_eq!(servers.len(), 1);
+
+    let servers = tester
+        .client
+        .find_servers(tester.endpoint(), None, Some(vec!["urn:other".into()]))
+        .await
+        .unwrap();
+    assert!(servers.is_empty());
+
+    let err = tester
+        .client
+        .find_servers("http://localhost:4840", None, None)
+        .await
+        .unwrap_err();
+    assert_eq!(err, StatusCode::BadTcpEndpointUrlInvalid);
+}
+
+#[tokio::test]
+async fn find_servers_on_network() {
+    let tester = Tester::new_default_server(true).await;
+    // The server is not a discovery server with the multicast extension.
+    let err = tester
+        .client
+        .find_servers_on_network(tester.endpoint(), 0, 0, None)
+        .await
+        .unwrap_err();
+    assert_eq!(err, StatusCode::BadServiceUnsupported);
+}
+
 #[tokio::test]
 async fn discovery_test() {
     let tester = Tester::new_default_server(true).await;
@@ -480,3 +781,576 @@ async fn issued_token_test() {
         .await
         .unwrap();
 }
+
+/// Token source returning a new token each time it is called.
+#[derive(Default)]
+struct RotatingTokenSource(AtomicU32);
+
+#[async_trait]
+impl IssuedTokenSource for RotatingTokenSource {
+    async fn get_issued_token(&self) -> Result<ByteString, Error> {
+        let n = self.0.fetch_add(1, Ordering::Relaxed) + 1;
+        Ok(ByteString::from(format!("token-{n}").as_bytes()))
+    }
+}
+
+/// Authenticator accepting any issued token, recording the tokens it receives.
+#[derive(Default)]
+struct RecordingTokenAuthenticator(std::sync::Mutex<Vec<String>>);
+
+#[async_trait]
+impl AuthManager for RecordingTokenAuthenticator {
+    fn user_token_policies(&self, endpoint: &ServerEndpoint) -> Vec<UserTokenPolicy> {
+        IssuedTokenAuthenticator.user_token_policies(endpoint)
+    }
+
+    async fn authenticate_issued_identity_token(
+        &self,
+        _endpoint: &ServerEndpoint,
+        token: &ByteString,
+    ) -> Result<UserToken, Error> {
+        let token_str =
+            String::from_utf8(token.value.clone().unwrap_or_default()).map_err(Error::decoding)?;
+        self.0.lock().unwrap().push(token_str);
+        Ok(UserToken("issued".into()))
+    }
+}
+
+#[tokio::test]
+async fn issued_token_refreshed_on_reactivation() {
+    let authenticator = Arc::new(RecordingTokenAuthenticator::default());
+    let server = test_server()
+        .add_endpoint(
+            "issued_token",
+            (
+                "/issued_token",
+                SecurityPolicy::Aes128Sha256RsaOaep,
+                MessageSecurityMode::SignAndEncrypt,
+                &[] as &[&str],
+            ),
+        )
+        .relax_endpoint_url_validation(true)
+        .with_authenticator(authenticator.clone());
+    let tester = Tester::new(server, false).await;
+
+    // Connect through a proxy, so that the connection can be dropped without
+    // closing the session.
+    let proxy = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let proxy_addr = proxy.local_addr().unwrap();
+    let server_addr = tester.addr;
+    let connections = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let connections_ref = connections.clone();
+    tokio::spawn(async move {
+        loop {
+            let (mut inbound, _) = proxy.accept().await.unwrap();
+            let handle = tokio::spawn(async move {
+                let mut outbound = TcpStream::connect(server_addr).await.unwrap();
+                let _ = tokio::io::copy_bidirectional(&mut inbound, &mut outbound).await;
+            });
+            connections_ref.lock().unwrap().push(handle);
+        }
+    });
+
+    let mut endpoint = tester
+        .client
+        .get_server_endpoints_from_url(tester.endpoint())
+        .await
+        .unwrap()
+        .into_iter()
+        .find(|e| e.endpoint_url.as_ref().ends_with("/issued_token"))
+        .unwrap();
+    endpoint.endpoint_url = format!("opc.tcp://{proxy_addr}/issued_token").into();
+
+    let (session, lp) = tester
+        .client
+        .session_builder()
+        .connect_to_endpoint_directly(endpoint)
+        .unwrap()
+        .user_identity_token(IdentityToken::new_issued_token(
+            RotatingTokenSource::default(),
+        ))
+        .build(tester.client.certificate_store().clone());
+    lp.spawn();
+    tokio::time::timeout(Duration::from_secs(5), session.wait_for_connection())
+        .await
+        .unwrap();
+    let session_id = session.server_session_id();
+    assert_eq!(*authenticator.0.lock().unwrap(), vec!["token-1"]);
+
+    // Drop the connection, the client reconnects and reactivates the session
+    // with a new token.
+    for handle in connections.lock().unwrap().drain(..) {
+        handle.abort();
+    }
+    tokio::time::timeout(Duration::from_secs(10), async {
+        while authenticator.0.lock().unwrap().len() < 2 {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+    })
+    .await
+    .unwrap();
+    tokio::time::timeout(Duration::from_secs(5), session.wait_for_connection())
+        .await
+        .unwrap();
+
+    assert_eq!(*authenticator.0.lock().unwrap(), vec!["token-1", "token-2"]);
+    assert_eq!(session.server_session_id(), session_id);
+    session
+        .read(
+            &[ReadValueId::from(<VariableId as Into<NodeId>>::into(
+                VariableId::Server_ServiceLevel,
+            ))],
+            TimestampsToReturn::Both,
+            0.0,
+        )
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn reverse_connect() {
+    let tester = Tester::new_default_server(false).await;
+    let endpoint = tester
+        .client
+        .get_server_endpoints_from_url(tester.endpoint())
+        .await
+        .unwrap()
+        .into_iter()
+        .find(|e| e.security_mode == MessageSecurityMode::None)
+        .unwrap();
+    let server_uri = endpoint.server.application_uri.to_string();
+
+    // Pick a free port for the client to listen on.
+    let reverse_addr = TcpListener::bind("127.0.0.1:0")
+        .await
+        .unwrap()
+        .local_addr()
+        .unwrap();
+
+    let (session, event_loop) = tester
+        .client
+        .session_builder()
+        .connect_to_endpoint_directly(endpoint)
+        .unwrap()
+        .reverse_connect(reverse_addr)
+        .build(tester.client.certificate_store().clone());
+    let handle = event_loop.spawn();
+
+    async fn connect_reverse(addr: std::net::SocketAddr) -> TcpStream {
+        loop {
+            if let Ok(stream) = TcpStream::connect(addr).await {
+                return stream;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+    }
+
+    // A reverse hello for the wrong endpoint is rejected.
+    let mut stream = connect_reverse(reverse_addr).await;
+    let rhe = ReverseHelloMessage::new(&server_uri, &format!("{}wrong", tester.endpoint()));
+    stream
+        .write_all(&SimpleBinaryEncodable::encode_to_vec(&rhe))
+        .await
+        .unwrap();
+    let mut bytes = BytesMut::with_capacity(1024);
+    stream.read_buf(&mut bytes).await.unwrap();
+    let msg = TcpCodec::new(DecodingOptions::default())
+        .decode(&mut bytes)
+        .unwrap();
+    let Some(Message::Error(msg)) = msg else {
+        panic!("Expected error got {msg:?}");
+    };
+    assert_eq!(msg.error, StatusCode::BadTcpEndpointUrlInvalid);
+
+    // A valid reverse hello is accepted, forward the connection to the real server.
+    let mut stream = connect_reverse(reverse_addr).await;
+    let rhe = ReverseHelloMessage::new(&server_uri, &tester.endpoint());
+    stream
+        .write_all(&SimpleBinaryEncodable::encode_to_vec(&rhe))
+        .await
+        .unwrap();
+    let mut server = TcpStream::connect(tester.addr).await.unwrap();
+    tokio::task::spawn(async move {
+        let _ = tokio::io::copy_bidirectional(&mut stream, &mut server).await;
+    });
+
+    tokio::time::timeout(Duration::from_secs(20), session.wait_for_connection())
+        .await
+        .unwrap();
+
+    let read = session
+        .read(
+            &[ReadValueId::new_value(
+                VariableId::Server_ServerStatus_State.into(),
+            )],
+            TimestampsToReturn::Neither,
+            0.0,
+        )
+        .await
+        .unwrap();
+    assert_eq!(read[0].value, Some(Variant::Int32(0)));
+
+    session.disconnect().await.unwrap();
+    handle.await.unwrap();
+}
+
+#[tokio::test]
+async fn channel_pool() {
+    let tester = Tester::new_default_server(false).await;
+    let endpoints = tester
+        .client
+        .get_server_endpoints_from_url(tester.endpoint())
+        .await
+        .unwrap();
+    let pool = tester
+        .client
+        .session_builder()
+        .with_endpoints(endpoints)
+        .connect_to_matching_endpoint((
+            &tester.endpoint() as &str,
+            SecurityPolicy::Basic256Sha256.to_str(),
+            MessageSecurityMode::SignAndEncrypt,
+        ))
+        .unwrap()
+        .build_channel_pool(tester.client.certificate_store().clone());
+
+    let (s1, evt_loop_1) = pool.session_builder().build();
+    let (s2, evt_loop_2) = pool
+        .session_builder()
+        .user_identity_token(client_user_token())
+        .build();
+    let h1 = evt_loop_1.spawn();
+    let h2 = evt_loop_2.spawn();
+
+    for session in [&s1, &s2] {
+        tokio::time::timeout(Duration::from_secs(20), session.wait_for_connection())
+            .await
+            .unwrap();
+    }
+    assert!(pool.is_connected());
+    assert_ne!(s1.server_session_id(), s2.server_session_id());
+    assert_eq!(
+        s1.channel().secure_channel_id(),
+        s2.channel().secure_channel_id()
+    );
+    assert_eq!(
+        s1.channel().secure_channel_id(),
+        pool.channel().secure_channel_id()
+    );
+
+    let read_state = |session: Arc<opcua::client::Session>| async move {
+        session
+            .read(
+                &[ReadValueId::new_value(
+                    VariableId::Server_ServerStatus_State.into(),
+                )],
+                TimestampsToReturn::Neither,
+                0.0,
+            )
+            .await
+            .unwrap()
+    };
+    for session in [&s1, &s2] {
+        let read = read_state(session.clone()).await;
+        assert_eq!(read[0].value, Some(Variant::Int32(0)));
+    }
+
+    // Closing one session leaves the shared channel open for the other.
+    s1.disconnect().await.unwrap();
+    h1.await.unwrap();
+    assert!(pool.is_connected());
+    let read = read_state(s2.clone()).await;
+    assert_eq!(read[0].value, Some(Variant::Int32(0)));
+
+    s2.disconnect().await.unwrap();
+    h2.await.unwrap();
+    pool.close().await;
+    assert!(!pool.is_connected());
+}
+
+#[tokio::test]
+async fn secure_channel_events() {
+    use futures::StreamExt;
+    use opcua_client::{SecureChannelEvent, SessionPollResult};
+
+    let mut tester = Tester::new_default_server(false).await;
+    let (session, evt_loop) = tester.connect_default().await.unwrap();
+    let stream = evt_loop.enter();
+    tokio::pin!(stream);
+
+    let evt = tokio::time::timeout(Duration::from_secs(20), async {
+        loop {
+            if let SessionPollResult::SecureChannel(evt) = stream.next().await.unwrap().unwrap() {
+                break evt;
+            }
+        }
+    })
+    .await
+    .unwrap();
+
+    let SecureChannelEvent::Opened { previous, current } = evt else {
+        panic!("Expected opened event, got {evt:?}");
+    };
+    assert!(previous.is_none());
+    assert_eq!(current.channel_id, session.channel().secure_channel_id());
+    assert_ne!(current.token_id, 0);
+}
+
+#[tokio::test]
+async fn connection_state_callback() {
+    let mut tester = Tester::new_default_server(false).await;
+    let (session, lp) = tester.connect_default().await.unwrap();
+
+    let states = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let states_ref = states.clone();
+    session.on_connection_status_change(move |s| states_ref.lock().unwrap().push(s));
+
+    let handle = lp.spawn();
+    tokio::time::timeout(Duration::from_secs(2), session.wait_for_connection())
+        .await
+        .unwrap();
+    assert_eq!(
+        *states.lock().unwrap(),
+        vec![ConnectionState::Connecting, ConnectionState::Connected]
+    );
+
+    session.disconnect().await.unwrap();
+    handle.await.unwrap();
+    assert_eq!(
+        states.lock().unwrap().last(),
+        Some(&ConnectionState::Closed)
+    );
+}
+
+#[tokio::test]
+async fn dropped_event_loop_cancels_requests() {
+    let mut tester = Tester::new_default_server(false).await;
+    let (session, lp) = tester.connect_default().await.unwrap();
+    let handle = lp.spawn();
+    tokio::time::timeout(Duration::from_secs(2), session.wait_for_connection())
+        .await
+        .unwrap();
+
+    // With a long publishing interval, the server holds on to publish requests.
+    session
+        .create_subscription(
+            Duration::from_secs(30),
+            10,
+            5,
+            0,
+            0,
+            true,
+            DataChangeCallback::new(|_, _| {}),
+        )
+        .await
+        .unwrap();
+    let session_ref = session.clone();
+    let request = tokio::spawn(async move {
+        Publish::new(&session_ref)
+            .timeout(Duration::from_secs(60))
+            .send(session_ref.channel())
+            .await
+    });
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert!(!request.is_finished());
+
+    // Dropping the event loop fails the pending request, instead of waiting for the timeout.
+    handle.abort();
+    let _ = handle.await;
+    let r = tokio::time::timeout(Duration::from_secs(2), request)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(r.unwrap_err(), StatusCode::BadSessionClosed);
+
+    // New requests fail immediately, and are not retried.
+    let r = tokio::time::timeout(
+        Duration::from_secs(2),
+        session.send_with_retry(
+            Read::new(&session).node(ReadValueId::new_value(
+                VariableId::Server_ServiceLevel.into(),
+            )),
+            DefaultRetryPolicy::new(ExponentialBackoff::new(
+                Duration::from_secs(30),
+                Some(10),
+                Duration::from_secs(1),
+            )),
+        ),
+    )
+    .await
+    .unwrap();
+    assert_eq!(r.unwrap_err(), StatusCode::BadSessionClosed);
+}
+
+#[tokio::test]
+async fn hello_endpoint_url_validation() {
+    async fn send_hello(addr: std::net::SocketAddr, endpoint_url: &str) -> Option<Message> {
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        let hello = HelloMessage::new(endpoint_url, 65535, 65535, 0, 0);
+        stream
+            .write_all(&SimpleBinaryEncodable::encode_to_vec(&hello))
+            .await
+            .unwrap();
+        let mut bytes = BytesMut::with_capacity(1024);
+        stream.read_buf(&mut bytes).await.unwrap();
+        TcpCodec::new(DecodingOptions::default())
+            .decode(&mut bytes)
+            .unwrap()
+    }
+
+    // The host in the endpoint url does not match the server.
+    let tester = Tester::new_default_server(false).await;
+    let wrong_host = format!("opc.tcp://wrong-host:{}/", tester.addr.port());
+    let msg = send_hello(tester.addr, &wrong_host).await;
+    let Some(Message::Error(msg)) = msg else {
+        panic!("Expected error got {msg:?}");
+    };
+    assert_eq!(msg.error, StatusCode::BadTcpEndpointUrlInvalid);
+
+    let msg = send_hello(tester.addr, &tester.endpoint()).await;
+    assert!(matches!(msg, Some(Message::Acknowledge(_))), "{msg:?}");
+
+    // With relaxed validation, only the path is checked.
+    let tester = Tester::new(test_server().relax_endpoint_url_validation(true), false).await;
+    let msg = send_hello(tester.addr, &wrong_host).await;
+    assert!(matches!(msg, Some(Message::Acknowledge(_))), "{msg:?}");
+}
+
+fn rejected_requests_count(tester: &Tester) -> u32 {
+    let value = tester
+        .handle
+        .info()
+        .diagnostics
+        .get(VariableId::Server_ServerDiagnostics_ServerDiagnosticsSummary_RejectedRequestsCount)
+        .unwrap();
+    match value.value {
+        Some(Variant::UInt32(v)) => v,
+        v => panic!("Expected rejected requests count, got {v:?}"),
+    }
+}
+
+#[tokio::test]
+async fn connection_rate_limit() {
+    let tester = Tester::new(
+        test_server()
+            .diagnostics_enabled(true)
+            .max_connections_per_ip(3)
+            .rate_limit_interval_ms(60_000),
+        false,
+    )
+    .await;
+
+    let mut accepted = 0;
+    let mut streams = Vec::new();
+    for _ in 0..10 {
+        let mut stream = TcpStream::connect(tester.addr).await.unwrap();
+        let hello = HelloMessage::new(&tester.endpoint(), 65535, 65535, 0, 0);
+        // The server may already have closed the socket.
+        let _ = stream
+            .write_all(&SimpleBinaryEncodable::encode_to_vec(&hello))
+            .await;
+        let mut bytes = BytesMut::with_capacity(1024);
+        let msg = match stream.read_buf(&mut bytes).await {
+            Ok(_) => TcpCodec::new(DecodingOptions::default())
+                .decode(&mut bytes)
+                .unwrap(),
+            Err(_) => None,
+        };
+        if matches!(msg, Some(Message::Acknowledge(_))) {
+            accepted += 1;
+        } else {
+            assert!(msg.is_none(), "{msg:?}");
+        }
+        streams.push(stream);
+    }
+    assert_eq!(accepted, 3);
+    assert_eq!(rejected_requests_count(&tester), 7);
+}
+
+#[tokio::test]
+async fn request_rate_limit() {
+    let mut tester = Tester::new(
+        test_server()
+            .diagnostics_enabled(true)
+            .max_requests_per_session(20)
+            .rate_limit_interval_ms(60_000),
+        false,
+    )
+    .await;
+    let (session, lp) = tester.connect_default().await.unwrap();
+    lp.spawn();
+    tokio::time::timeout(Duration::from_secs(2), session.wait_for_connection())
+        .await
+        .unwrap();
+
+    let sub_id = session
+        .create_subscription(
+            Duration::from_millis(100),
+            100,
+            1,
+            0,
+            0,
+            true,
+            DataChangeCallback::new(|_, _| {}),
+        )
+        .await
+        .unwrap();
+
+    let rejected_before = rejected_requests_count(&tester);
+    let mut succeeded = 0;
+    let mut throttled = 0;
+    for _ in 0..50 {
+        let res = session
+            .read(
+                &[ReadValueId::new_value(
+                    VariableId::Server_ServiceLevel.into(),
+                )],
+                TimestampsToReturn::Neither,
+                0.0,
+            )
+            .await;
+        match res {
+            Ok(_) => succeeded += 1,
+            Err(StatusCode::BadTooManyOperations) => throttled += 1,
+            Err(e) => panic!("Unexpected error {e}"),
+        }
+    }
+    // Some requests may have been sent while connecting.
+    assert!(succeeded <= 20, "{succeeded}");
+    assert_eq!(succeeded + throttled, 50);
+    assert_eq!(
+        rejected_requests_count(&tester) - rejected_before,
+        throttled
+    );
+
+    // Publish and Republish are not limited, so subscriptions keep working.
+    tokio::time::timeout(
+        Duration::from_secs(5),
+        Publish::new(&session).send(session.channel()),
+    )
+    .await
+    .unwrap()
+    .unwrap();
+    let res = session.republish(sub_id, 1).await;
+    assert!(
+        !matches!(res, Err(StatusCode::BadTooManyOperations)),
+        "{res:?}"
+    );
+
+    // Other sessions are not affected.
+    let (session2, lp) = tester.connect_default().await.unwrap();
+    lp.spawn();
+    tokio::time::timeout(Duration::from_secs(2), session2.wait_for_connection())
+        .await
+        .unwrap();
+    session2
+        .read(
+            &[ReadValueId::new_value(
+                VariableId::Server_ServiceLevel.into(),
+            )],
+            TimestampsToReturn::Neither,
+            0.0,
+        )
+        .await
+        .unwrap();
+}
@@ -1,15 +1,19 @@
-use std::sync::Arc;
+use std::{
+    sync::{Arc, LazyLock},
+    time::Duration,
+};
 
 use opcua_client::custom_types::DataTypeTreeBuilder;
 use opcua_nodes::{AccessLevel, DataTypeBuilder, VariableBuilder};
 use opcua_types::{
     custom::{DynamicStructure, DynamicTypeLoader},
-    DataTypeDefinition, DataTypeId, EUInformation, IntoVariant, LocalizedText, NodeId, ObjectId,
-    ReadValueId, ReferenceTypeId, StructureDefinition, StructureField, StructureType,
-    TimestampsToReturn, TypeLoader, VariableTypeId, Variant,
+    DataTypeDefinition, DataTypeId, EUInformation, IntoVariant, LocalizedText, MessageSecurityMode,
+    NodeId, ObjectId, ReadValueId, ReferenceTypeId, StaticTypeLoader, StructureDefinition,
+    StructureField, StructureType, TimestampsToReturn, TypeLoader, TypeLoaderInstance, VariableId,
+    VariableTypeId, Variant,
 };
 
-use crate::utils::setup;
+use crate::utils::{setup, test_server};
 
 use super::utils::{TestNodeManager, Tester};
 
@@ -154,3 +158,114 @@ async fn test_data_type_tree_builder() {
     );
     assert!(v.get_field(2).is_none());
 }
+
+struct TestServerTypeLoader;
+
+static TEST_SERVER_TYPES: LazyLock<TypeLoaderInstance> = LazyLock::new(TypeLoaderInstance::new);
+
+impl StaticTypeLoader for TestServerTypeLoader {
+    fn instance() -> &'static TypeLoaderInstance {
+        &TEST_SERVER_TYPES
+    }
+
+    fn namespace() -> &'static str {
+        "urn:rustopcuatestserver"
+    }
+}
+
+#[tokio::test]
+async fn type_loader_namespaces_fetched_on_connect() {
+    let tester = Tester::new(test_server(), false).await;
+    let endpoints = tester
+        .client
+        .get_server_endpoints_from_url(tester.endpoint())
+        .await
+        .unwrap();
+    let (session, event_loop) = tester
+        .client
+        .session_builder()
+        .with_endpoints(endpoints)
+        .connect_to_matching_endpoint((
+            &tester.endpoint() as &str,
+            "http://opcfoundation.org/UA/SecurityPolicy#None",
+            MessageSecurityMode::None,
+        ))
+        .unwrap()
+        .type_loader(Arc::new(TestServerTypeLoader))
+        .build(tester.client.certificate_store().clone());
+    event_loop.spawn();
+    tokio::time::timeout(Duration::from_secs(2), session.wait_for_connection())
+        .await
+        .unwrap();
+
+    // The namespace array is read before the session is reported as connected.
+    assert_eq!(
+        session.get_namespace_index_from_cache(TestServerTypeLoader::namespace()),
+        Some(2)
+    );
+}
+
+#[tokio::test]
+async fn ensure_type_loaders() {
+    let (_tester, _nm, session) = setup().await;
+
+    // Without any type loaders in the builder, namespaces are not fetched automatically.
+    assert_eq!(
+        session.get_namespace_index_from_cache(TestServerTypeLoader::namespace()),
+        None
+    );
+
+    session.add_type_loader(Arc::new(TestServerTypeLoader));
+    let map = session.ensure_type_loaders().await.unwrap();
+    assert_eq!(map.get_index(TestServerTypeLoader::namespace()), Some(2));
+    assert_eq!(
+        session.get_namespace_index_from_cache(TestServerTypeLoader::namespace()),
+        Some(2)
+    );
+}
+
+#[tokio::test]
+async fn cached_namespaces() {
+    let (_tester, _nm, session) = setup().await;
+
+    let map = session.namespaces().await.unwrap();
+    assert_eq!(
+        map.get_namespace_uri(0),
+        Some("http://opcfoundation.org/UA/")
+    );
+    assert_eq!(map.get_index(TestServerTypeLoader::namespace()), Some(2));
+    assert_eq!(
+        session.get_namespace_index_from_cache(TestServerTypeLoader::namespace()),
+        Some(2)
+    );
+
+    // The second call is served from the cache.
+    let cached = session.namespaces().await.unwrap();
+    assert!(Arc::ptr_eq(&map, &cached));
+
+    // A value with the same source timestamp does not replace the cached map.
+    let value = session
+        .read(
+            &[ReadValueId::from(<NodeId>::from(
+                VariableId::Server_NamespaceArray,
+            ))],
+            TimestampsToReturn::Source,
+            0.0,
+        )
+        .await
+        .unwrap()
+        .remove(0);
+    let refreshed = session.update_namespaces(&value).unwrap();
+    let unchanged = session.update_namespaces(&value).unwrap();
+    assert!(Arc::ptr_eq(&refreshed, &unchanged));
+    assert_eq!(
+        refreshed.get_index(TestServerTypeLoader::namespace()),
+        Some(2)
+    );
+
+    let refreshed = session.refresh_namespaces().await.unwrap();
+    assert_eq!(
+        refreshed.get_index(TestServerTypeLoader::namespace()),
+        Some(2)
+    );
+}
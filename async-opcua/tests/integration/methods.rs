@@ -5,12 +5,13 @@ use std::{
 
 use crate::utils::ChannelNotifications;
 
-use super::utils::setup;
+use super::utils::{setup, setup_with, test_server};
 use opcua::{
+    client::MethodCallError,
     server::address_space::MethodBuilder,
     types::{
-        AttributeId, CallMethodRequest, DataTypeId, NodeId, ObjectId, StatusCode, Variant,
-        VariantTypeId,
+        AttributeId, ByteString, CallMethodRequest, DataTypeId, NodeId, ObjectId, StatusCode,
+        Variant, VariantTypeId,
     },
 };
 use opcua_types::{
@@ -130,6 +131,148 @@ async fn call_args() {
     assert_eq!(r.status_code, StatusCode::BadInvalidArgument);
 }
 
+#[tokio::test]
+async fn call_typed() {
+    let (_tester, nm, session) = setup().await;
+
+    let id = nm.inner().next_node_id();
+    let input_id = nm.inner().next_node_id();
+    let output_id = nm.inner().next_node_id();
+    {
+        let mut sp = nm.address_space().write();
+        MethodBuilder::new(&id, "MethodAdd", "MethodAdd")
+            .executable(true)
+            .user_executable(true)
+            .component_of(ObjectId::ObjectsFolder)
+            .input_args(
+                &mut *sp,
+                &input_id,
+                &[
+                    ("Lhs", DataTypeId::Int64).into(),
+                    ("Rhs", DataTypeId::Int64).into(),
+                ],
+            )
+            .output_args(
+                &mut *sp,
+                &output_id,
+                &[("Result", DataTypeId::Int64).into()],
+            )
+            .insert(&mut *sp);
+    }
+
+    nm.inner().add_method_cb(id.clone(), |args| {
+        let (Some(Variant::Int64(lhs)), Some(Variant::Int64(rhs))) = (args.first(), args.get(1))
+        else {
+            return Err(StatusCode::BadInvalidArgument);
+        };
+
+        Ok(vec![Variant::Int64(lhs + rhs)])
+    });
+
+    let r = session
+        .call_typed(ObjectId::ObjectsFolder, id.clone(), (3i64, 2i64))
+        .await
+        .unwrap();
+    assert_eq!(r, vec![Variant::Int64(5)]);
+
+    let r = session
+        .call_typed_validated(
+            ObjectId::ObjectsFolder,
+            id.clone(),
+            vec![Variant::Int64(4), Variant::Int64(2)],
+        )
+        .await
+        .unwrap();
+    assert_eq!(r, vec![Variant::Int64(6)]);
+
+    // Wrong argument type is only caught by the server.
+    let e = session
+        .call_typed(ObjectId::ObjectsFolder, id.clone(), ("foo", 2i64))
+        .await
+        .unwrap_err();
+    assert_eq!(e.status(), StatusCode::BadInvalidArgument);
+
+    // Validated calls check the arguments before calling the method.
+    let e = session
+        .call_typed_validated(ObjectId::ObjectsFolder, id.clone(), ("foo", 2i64))
+        .await
+        .unwrap_err();
+    assert_eq!(
+        e,
+        MethodCallError::ArgumentTypeMismatch {
+            index: 0,
+            expected: DataTypeId::Int64.into(),
+            actual: Some(DataTypeId::String.into()),
+        }
+    );
+
+    let e = session
+        .call_typed_validated(ObjectId::ObjectsFolder, id.clone(), (1i64,))
+        .await
+        .unwrap_err();
+    assert_eq!(
+        e,
+        MethodCallError::ArgumentCountMismatch {
+            expected: 2,
+            actual: 1
+        }
+    );
+    assert_eq!(e.status(), StatusCode::BadArgumentsMissing);
+
+    let e = session
+        .call_typed_validated(ObjectId::ObjectsFolder, NodeId::new(2, "missing"), ())
+        .await
+        .unwrap_err();
+    assert_eq!(e.status(), StatusCode::BadMethodInvalid);
+}
+
+#[tokio::test]
+async fn call_large_arguments() {
+    let (_tester, nm, session) = setup().await;
+
+    let id = nm.inner().next_node_id();
+    let input_id = nm.inner().next_node_id();
+    let output_id = nm.inner().next_node_id();
+    {
+        let mut sp = nm.address_space().write();
+        MethodBuilder::new(&id, "Echo", "Echo")
+            .executable(true)
+            .user_executable(true)
+            .component_of(ObjectId::ObjectsFolder)
+            .input_args(
+                &mut *sp,
+                &input_id,
+                &[("Value", DataTypeId::BaseDataType).into()],
+            )
+            .output_args(
+                &mut *sp,
+                &output_id,
+                &[("Value", DataTypeId::BaseDataType).into()],
+            )
+            .insert(&mut *sp);
+    }
+
+    nm.inner().add_method_cb(id.clone(), |args| {
+        Ok(args.first().cloned().into_iter().collect())
+    });
+
+    // The request and the response are both split into many chunks.
+    let values: Vec<i64> = (0..100_000).collect();
+    let r = session
+        .call_typed(ObjectId::ObjectsFolder, id.clone(), (values.clone(),))
+        .await
+        .unwrap();
+    assert_eq!(r, vec![Variant::from(values)]);
+
+    // A single argument larger than the negotiated limits is rejected before it is sent.
+    let blob = ByteString::from(vec![0u8; 5 * 1024 * 1024]);
+    let e = session
+        .call_typed(ObjectId::ObjectsFolder, id.clone(), (blob,))
+        .await
+        .unwrap_err();
+    assert_eq!(e.status(), StatusCode::BadRequestTooLarge);
+}
+
 #[tokio::test]
 async fn call_fail() {
     let (_tester, nm, session) = setup().await;
@@ -230,6 +373,115 @@ async fn call_fail() {
     assert_eq!(r.status_code, StatusCode::BadTooManyArguments);
 }
 
+#[tokio::test]
+async fn call_validates_arguments() {
+    let (_tester, nm, session) = setup().await;
+    let called = Arc::new(AtomicU64::new(0));
+
+    let id = nm.inner().next_node_id();
+    let input_id = nm.inner().next_node_id();
+    {
+        let mut sp = nm.address_space().write();
+        MethodBuilder::new(&id, "Wait", "Wait")
+            .executable(true)
+            .user_executable(true)
+            .component_of(ObjectId::ObjectsFolder)
+            .input_args(
+                &mut *sp,
+                &input_id,
+                &[
+                    ("Count", DataTypeId::Int64).into(),
+                    ("Timeout", DataTypeId::Duration).into(),
+                ],
+            )
+            .insert(&mut *sp);
+    }
+
+    let called_ref = called.clone();
+    nm.inner().add_method_cb(id.clone(), move |_| {
+        called_ref.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        Ok(Vec::new())
+    });
+
+    let call = |args: Vec<Variant>| CallMethodRequest {
+        object_id: ObjectId::ObjectsFolder.into(),
+        method_id: id.clone(),
+        input_arguments: Some(args),
+    };
+
+    let r = session
+        .call_one(call(vec![Variant::Int64(1)]))
+        .await
+        .unwrap();
+    assert_eq!(r.status_code, StatusCode::BadArgumentsMissing);
+
+    let r = session
+        .call_one(call(vec![Variant::Int64(1), "soon".into()]))
+        .await
+        .unwrap();
+    assert_eq!(r.status_code, StatusCode::BadInvalidArgument);
+    assert_eq!(
+        r.input_argument_results,
+        Some(vec![StatusCode::Good, StatusCode::BadTypeMismatch])
+    );
+
+    // Arrays are rejected for scalar arguments.
+    let r = session
+        .call_one(call(vec![vec![1i64, 2].into(), Variant::Double(1.0)]))
+        .await
+        .unwrap();
+    assert_eq!(r.status_code, StatusCode::BadInvalidArgument);
+    assert_eq!(
+        r.input_argument_results,
+        Some(vec![StatusCode::BadTypeMismatch, StatusCode::Good])
+    );
+    assert_eq!(called.load(std::sync::atomic::Ordering::Relaxed), 0);
+
+    // `Duration` is a subtype of `Double`, so a plain `Double` is accepted.
+    let r = session
+        .call_one(call(vec![Variant::Int64(1), Variant::Double(1.0)]))
+        .await
+        .unwrap();
+    assert_eq!(r.status_code, StatusCode::Good);
+    assert_eq!(called.load(std::sync::atomic::Ordering::Relaxed), 1);
+}
+
+#[tokio::test]
+async fn call_skips_argument_validation() {
+    let (_tester, nm, session) = setup_with(test_server().validate_method_arguments(false)).await;
+    let called = Arc::new(AtomicU64::new(0));
+
+    let id = nm.inner().next_node_id();
+    let input_id = nm.inner().next_node_id();
+    {
+        let mut sp = nm.address_space().write();
+        MethodBuilder::new(&id, "Wait", "Wait")
+            .executable(true)
+            .user_executable(true)
+            .component_of(ObjectId::ObjectsFolder)
+            .input_args(&mut *sp, &input_id, &[("Count", DataTypeId::Int64).into()])
+            .insert(&mut *sp);
+    }
+
+    let called_ref = called.clone();
+    nm.inner().add_method_cb(id.clone(), move |_| {
+        called_ref.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        Ok(Vec::new())
+    });
+
+    // The node manager is responsible for the arguments, so the call goes through.
+    let r = session
+        .call_one(CallMethodRequest {
+            object_id: ObjectId::ObjectsFolder.into(),
+            method_id: id.clone(),
+            input_arguments: Some(vec!["soon".into()]),
+        })
+        .await
+        .unwrap();
+    assert_eq!(r.status_code, StatusCode::Good);
+    assert_eq!(called.load(std::sync::atomic::Ordering::Relaxed), 1);
+}
+
 #[tokio::test]
 async fn call_limits() {
     let (tester, _nm, session) = setup().await;
@@ -304,3 +556,74 @@ async fn call_get_monitored_items() {
     assert_eq!(handles.len(), 1);
     assert_eq!(15, handles[0]);
 }
+
+#[tokio::test]
+async fn call_get_monitored_items_handles() {
+    let (mut tester, _nm, session) = setup().await;
+
+    let (notifs, _data, _) = ChannelNotifications::new();
+    let sub_id = session
+        .create_subscription(Duration::from_millis(100), 100, 20, 1000, 0, true, notifs)
+        .await
+        .unwrap();
+
+    let items = [
+        VariableId::Server_ServerStatus_State,
+        VariableId::Server_ServerStatus_CurrentTime,
+        VariableId::Server_ServiceLevel,
+    ]
+    .into_iter()
+    .enumerate()
+    .map(|(idx, id)| MonitoredItemCreateRequest {
+        item_to_monitor: ReadValueId {
+            node_id: id.into(),
+            attribute_id: AttributeId::Value as u32,
+            ..Default::default()
+        },
+        monitoring_mode: opcua::types::MonitoringMode::Reporting,
+        requested_parameters: MonitoringParameters {
+            sampling_interval: 0.0,
+            queue_size: 10,
+            discard_oldest: true,
+            client_handle: 100 + idx as u32,
+            ..Default::default()
+        },
+    })
+    .collect();
+    let created = session
+        .create_monitored_items(sub_id, TimestampsToReturn::Both, items)
+        .await
+        .unwrap();
+
+    let (ids, handles) = session.call_get_monitored_items(sub_id).await.unwrap();
+    let mut returned: Vec<_> = ids.into_iter().zip(handles).collect();
+    returned.sort_unstable();
+    let mut expected: Vec<_> = created
+        .iter()
+        .map(|r| {
+            (
+                r.result.monitored_item_id,
+                r.requested_parameters.client_handle,
+            )
+        })
+        .collect();
+    expected.sort_unstable();
+    assert_eq!(returned, expected);
+    assert_eq!(returned.len(), 3);
+
+    // An unknown subscription.
+    let e = session
+        .call_get_monitored_items(sub_id + 1000)
+        .await
+        .unwrap_err();
+    assert_eq!(e, StatusCode::BadSubscriptionIdInvalid);
+
+    // The subscription is owned by a different session.
+    let (session2, lp) = tester.connect_default().await.unwrap();
+    lp.spawn();
+    tokio::time::timeout(Duration::from_secs(2), session2.wait_for_connection())
+        .await
+        .unwrap();
+    let e = session2.call_get_monitored_items(sub_id).await.unwrap_err();
+    assert_eq!(e, StatusCode::BadUserAccessDenied);
+}
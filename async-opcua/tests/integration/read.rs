@@ -1,23 +1,34 @@
 use std::{sync::atomic::Ordering, time::Duration};
 
-use crate::utils::{client_user_token, default_server, Tester};
+use crate::utils::{
+    client_user_token, default_server, test_node_manager_with_value_cache, test_server, Tester,
+};
 
-use super::utils::{array_value, read_value_id, read_value_ids, setup};
+use super::utils::{
+    array_value, read_value_id, read_value_ids, setup, setup_with, unchecked_node_manager,
+    TestNodeManager, UncheckedNodeManager,
+};
 use chrono::TimeDelta;
 use opcua::{
-    client::HistoryReadAction,
+    client::{DataChangeCallback, HistoryReadAction},
     server::address_space::{
         AccessLevel, DataTypeBuilder, EventNotifier, MethodBuilder, ObjectBuilder,
         ObjectTypeBuilder, ReferenceTypeBuilder, VariableBuilder, VariableTypeBuilder, ViewBuilder,
     },
+    server::node_manager::NodeManagerHealth,
     types::{
-        AttributeId, DataTypeId, DataValue, DateTime, HistoryData, HistoryReadValueId, NodeClass,
-        NodeId, ObjectId, ObjectTypeId, QualifiedName, ReadRawModifiedDetails, ReadValueId,
-        ReferenceTypeId, StatusCode, TimestampsToReturn, VariableId, VariableTypeId, Variant,
-        WriteMask,
+        profiles, Array, AttributeId, BrowseDescription, BrowseDirection, BrowseResultMask,
+        DataTypeId, DataValue, DateTime, EUInformation, HistoryData, HistoryReadValueId,
+        LocalizedText, MonitoredItemCreateRequest, MonitoringMode, MonitoringParameters, NodeClass,
+        NodeClassMask, NodeId, ObjectId, ObjectTypeId, QualifiedName, Range,
+        ReadRawModifiedDetails, ReadValueId, ReferenceTypeId, SessionDiagnosticsDataType,
+        StatusCode, TimestampsToReturn, VariableId, VariableTypeId, Variant, VariantScalarTypeId,
+        WriteMask, WriteValue,
     },
 };
-use opcua_client::{services::Read, DefaultRetryPolicy, ExponentialBackoff};
+use opcua_client::{
+    services::Read, DefaultRetryPolicy, ExponentialBackoff, OperationLimit, UARequest,
+};
 
 #[tokio::test]
 async fn read() {
@@ -40,6 +51,82 @@ async fn read() {
     assert_eq!(&Variant::Byte(123), r[0].value.as_ref().unwrap())
 }
 
+#[tokio::test]
+async fn read_access_level() {
+    // By default, the server checks access levels for node managers that do not do so themselves.
+    let (tester, _nm, session) =
+        setup_with(test_server().with_node_manager(unchecked_node_manager())).await;
+    let nm = tester
+        .handle
+        .node_managers()
+        .get_of_type::<UncheckedNodeManager>()
+        .unwrap();
+
+    let write_only = nm.add_variable(
+        "WriteOnly",
+        1,
+        AccessLevel::CURRENT_WRITE,
+        AccessLevel::CURRENT_WRITE,
+    );
+    let user_write_only = nm.add_variable(
+        "UserWriteOnly",
+        1,
+        AccessLevel::CURRENT_READ | AccessLevel::CURRENT_WRITE,
+        AccessLevel::CURRENT_WRITE,
+    );
+    let unreadable = nm.add_variable_with_unreadable_access_levels("Unreadable", 1);
+
+    let r = session
+        .read(
+            &[
+                read_value_id(AttributeId::Value, &write_only),
+                read_value_id(AttributeId::Value, &user_write_only),
+                // Other attributes are not affected by the access level.
+                read_value_id(AttributeId::AccessLevel, &write_only),
+                // Values of variables with unknown access levels are not readable.
+                read_value_id(AttributeId::Value, &unreadable),
+            ],
+            TimestampsToReturn::Both,
+            0.0,
+        )
+        .await
+        .unwrap();
+    assert_eq!(r[0].status, Some(StatusCode::BadNotReadable));
+    assert_eq!(r[1].status, Some(StatusCode::BadUserAccessDenied));
+    assert_eq!(
+        r[2].value,
+        Some(Variant::Byte(AccessLevel::CURRENT_WRITE.bits()))
+    );
+    assert_eq!(r[3].status, Some(StatusCode::BadUserAccessDenied));
+}
+
+#[tokio::test]
+async fn read_access_level_unhealthy_node_manager() {
+    let (tester, _nm, session) =
+        setup_with(test_server().with_node_manager(unchecked_node_manager())).await;
+    let nm = tester
+        .handle
+        .node_managers()
+        .get_of_type::<UncheckedNodeManager>()
+        .unwrap();
+    let unreadable = nm.add_variable_with_unreadable_access_levels("Unreadable", 1);
+    nm.set_health(NodeManagerHealth::Unhealthy(
+        StatusCode::BadServerNotConnected,
+    ));
+
+    // The access levels of nodes owned by an unhealthy node manager are not read,
+    // so the read fails with the status reported by the node manager.
+    let r = session
+        .read(
+            &[read_value_id(AttributeId::Value, &unreadable)],
+            TimestampsToReturn::Both,
+            0.0,
+        )
+        .await
+        .unwrap();
+    assert_eq!(r[0].status, Some(StatusCode::BadServerNotConnected));
+}
+
 #[tokio::test]
 async fn read_variable() {
     let (tester, nm, session) = setup().await;
@@ -122,6 +209,278 @@ async fn read_variable() {
     assert_eq!(r[11].value, Some(Variant::NodeId(Box::new(id))));
 }
 
+#[tokio::test]
+async fn read_index_range() {
+    let (tester, nm, session) = setup().await;
+
+    let array_id = nm.inner().next_node_id();
+    let matrix_id = nm.inner().next_node_id();
+    let matrix = Array::new_multi(
+        VariantScalarTypeId::Int32,
+        (0..9).map(Variant::Int32).collect::<Vec<_>>(),
+        vec![3, 3],
+    )
+    .unwrap();
+    for (id, value, dims) in [
+        (
+            &array_id,
+            Variant::from((0..6).collect::<Vec<i32>>()),
+            vec![6],
+        ),
+        (&matrix_id, Variant::from(matrix), vec![3, 3]),
+    ] {
+        nm.inner().add_node(
+            nm.address_space(),
+            tester.handle.type_tree(),
+            VariableBuilder::new(id, "TestVar", "TestVar")
+                .value(value)
+                .value_rank(dims.len() as i32)
+                .array_dimensions(&dims)
+                .data_type(DataTypeId::Int32)
+                .access_level(AccessLevel::CURRENT_READ)
+                .user_access_level(AccessLevel::CURRENT_READ)
+                .build()
+                .into(),
+            &ObjectId::ObjectsFolder.into(),
+            &ReferenceTypeId::Organizes.into(),
+            Some(&VariableTypeId::BaseDataVariableType.into()),
+            Vec::new(),
+        );
+    }
+
+    let range_read = |id: &NodeId, range: &str| ReadValueId {
+        node_id: id.clone(),
+        attribute_id: AttributeId::Value as u32,
+        index_range: range.parse().unwrap(),
+        ..Default::default()
+    };
+    let r = session
+        .read(
+            &[
+                range_read(&array_id, "2:4"),
+                range_read(&array_id, "4:10"),
+                range_read(&array_id, "6"),
+                range_read(&array_id, "0,1"),
+                range_read(&matrix_id, "0:1,1:2"),
+                range_read(&matrix_id, "2,0"),
+                range_read(&matrix_id, "1"),
+                range_read(&matrix_id, "3,0:1"),
+            ],
+            TimestampsToReturn::Both,
+            0.0,
+        )
+        .await
+        .unwrap();
+
+    let ints = |v: &[i32]| v.iter().map(|v| Variant::Int32(*v)).collect::<Vec<_>>();
+    let dims = |v: &DataValue| match v.value.as_ref().unwrap() {
+        Variant::Array(a) => a.dimensions.clone(),
+        _ => panic!("Expected array"),
+    };
+    assert_eq!(array_value(&r[0]), &ints(&[2, 3, 4]));
+    assert_eq!(array_value(&r[1]), &ints(&[4, 5]));
+    assert_eq!(r[2].status, Some(StatusCode::BadIndexRangeNoData));
+    assert_eq!(r[3].status, Some(StatusCode::BadIndexRangeInvalid));
+    assert_eq!(array_value(&r[4]), &ints(&[1, 2, 4, 5]));
+    assert_eq!(dims(&r[4]), Some(vec![2, 2]));
+    assert_eq!(array_value(&r[5]), &ints(&[6]));
+    assert_eq!(dims(&r[5]), Some(vec![1, 1]));
+    assert_eq!(r[6].status, Some(StatusCode::BadIndexRangeInvalid));
+    assert_eq!(r[7].status, Some(StatusCode::BadIndexRangeNoData));
+}
+
+#[tokio::test]
+async fn registered_node_handles() {
+    let (mut tester, nm, session) = setup().await;
+
+    let id = nm.inner().next_node_id();
+    nm.inner().add_node(
+        nm.address_space(),
+        tester.handle.type_tree(),
+        VariableBuilder::new(&id, "TestVar1", "TestVar1")
+            .value(123)
+            .data_type(DataTypeId::Int32)
+            .access_level(AccessLevel::CURRENT_READ | AccessLevel::CURRENT_WRITE)
+            .user_access_level(AccessLevel::CURRENT_READ | AccessLevel::CURRENT_WRITE)
+            .build()
+            .into(),
+        &ObjectId::ObjectsFolder.into(),
+        &ReferenceTypeId::Organizes.into(),
+        Some(&VariableTypeId::BaseDataVariableType.into()),
+        Vec::new(),
+    );
+
+    // Nodes owned by node managers without registration support are returned unchanged.
+    let registered = session
+        .register_nodes(&[id.clone(), VariableId::Server_ServiceLevel.into()])
+        .await
+        .unwrap();
+    assert_eq!(registered.len(), 2);
+    assert_ne!(registered[0], id);
+    assert_eq!(registered[0].namespace, id.namespace);
+    assert_eq!(registered[1], VariableId::Server_ServiceLevel);
+    assert_eq!(
+        nm.inner().call_info().lock().register_nodes,
+        vec![id.clone()]
+    );
+    let handle = registered[0].clone();
+
+    // The handle is resolved by the server in every service, node managers only
+    // see the original node ID.
+    let r = session
+        .write(&[WriteValue {
+            node_id: handle.clone(),
+            attribute_id: AttributeId::Value as u32,
+            value: DataValue::new_now(456),
+            ..Default::default()
+        }])
+        .await
+        .unwrap();
+    assert_eq!(r[0], StatusCode::Good);
+
+    let r = session
+        .read(
+            &[read_value_id(AttributeId::Value, handle.clone())],
+            TimestampsToReturn::Both,
+            0.0,
+        )
+        .await
+        .unwrap();
+    assert_eq!(r[0].value, Some(Variant::Int32(456)));
+    assert_eq!(nm.inner().call_info().lock().read_values, vec![id.clone()]);
+
+    let r = session
+        .browse(
+            &[BrowseDescription {
+                node_id: handle.clone(),
+                browse_direction: BrowseDirection::Inverse,
+                reference_type_id: ReferenceTypeId::Organizes.into(),
+                include_subtypes: true,
+                node_class_mask: 0,
+                result_mask: BrowseResultMask::All as u32,
+            }],
+            0,
+            None,
+        )
+        .await
+        .unwrap();
+    let refs = r[0].references.clone().unwrap_or_default();
+    assert_eq!(refs.len(), 1);
+    assert_eq!(refs[0].node_id.node_id, ObjectId::ObjectsFolder);
+
+    let sub_id = session
+        .create_subscription(
+            Duration::from_millis(100),
+            100,
+            20,
+            1000,
+            0,
+            true,
+            DataChangeCallback::new(|_, _| {}),
+        )
+        .await
+        .unwrap();
+    let r = session
+        .create_monitored_items(
+            sub_id,
+            TimestampsToReturn::Both,
+            vec![MonitoredItemCreateRequest {
+                item_to_monitor: read_value_id(AttributeId::Value, handle.clone()),
+                monitoring_mode: MonitoringMode::Reporting,
+                requested_parameters: MonitoringParameters {
+                    sampling_interval: 0.0,
+                    queue_size: 10,
+                    discard_oldest: true,
+                    ..Default::default()
+                },
+            }],
+        )
+        .await
+        .unwrap();
+    assert_eq!(r[0].result.status_code, StatusCode::Good);
+    assert_eq!(
+        nm.inner().call_info().lock().value_monitored_items,
+        vec![id.clone()]
+    );
+
+    // Handles are scoped to the session that registered them.
+    let (other, lp) = tester.connect_default().await.unwrap();
+    lp.spawn();
+    tokio::time::timeout(Duration::from_secs(2), other.wait_for_connection())
+        .await
+        .unwrap();
+    let r = other
+        .read(
+            &[read_value_id(AttributeId::Value, handle.clone())],
+            TimestampsToReturn::Both,
+            0.0,
+        )
+        .await
+        .unwrap();
+    assert_eq!(r[0].status, Some(StatusCode::BadNodeIdUnknown));
+
+    session.unregister_nodes(&registered[..1]).await.unwrap();
+    assert_eq!(
+        nm.inner().call_info().lock().unregister_nodes,
+        vec![id.clone()]
+    );
+
+    // The handle is no longer valid once unregistered.
+    let r = session
+        .read(
+            &[read_value_id(AttributeId::Value, handle)],
+            TimestampsToReturn::Both,
+            0.0,
+        )
+        .await
+        .unwrap();
+    assert_eq!(r[0].status, Some(StatusCode::BadNodeIdUnknown));
+}
+
+#[tokio::test]
+async fn registered_node_handle_limit() {
+    let mut server = test_server();
+    server
+        .limits_mut()
+        .operational
+        .max_registered_nodes_per_session = 2;
+    let (tester, nm, session) = setup_with(server).await;
+
+    let ids: Vec<_> = (0..3)
+        .map(|i| {
+            let id = nm.inner().next_node_id();
+            nm.inner().add_node(
+                nm.address_space(),
+                tester.handle.type_tree(),
+                VariableBuilder::new(&id, format!("TestVar{i}"), format!("TestVar{i}"))
+                    .value(123)
+                    .data_type(DataTypeId::Int32)
+                    .access_level(AccessLevel::CURRENT_READ)
+                    .user_access_level(AccessLevel::CURRENT_READ)
+                    .build()
+                    .into(),
+                &ObjectId::ObjectsFolder.into(),
+                &ReferenceTypeId::Organizes.into(),
+                Some(&VariableTypeId::BaseDataVariableType.into()),
+                Vec::new(),
+            );
+            id
+        })
+        .collect();
+
+    let registered = session.register_nodes(&ids[..2]).await.unwrap();
+    assert_eq!(registered.len(), 2);
+
+    // The session holds as many handles as it may, so further registrations fail.
+    let e = session.register_nodes(&ids[2..]).await.unwrap_err();
+    assert_eq!(e, StatusCode::BadTooManyOperations);
+
+    // Unregistering a handle makes room for another.
+    session.unregister_nodes(&registered[..1]).await.unwrap();
+    let r = session.register_nodes(&ids[2..]).await.unwrap();
+    assert_ne!(r[0], ids[2]);
+}
+
 #[tokio::test]
 async fn read_object() {
     let (tester, nm, session) = setup().await;
@@ -744,6 +1103,208 @@ async fn read_limits() {
         .unwrap();
 }
 
+#[tokio::test]
+async fn read_all() {
+    let (tester, nm, session) = setup().await;
+
+    let read_limit = tester
+        .handle
+        .info()
+        .config
+        .limits
+        .operational
+        .max_nodes_per_read;
+
+    let ids: Vec<_> = (0..10).map(|_| nm.inner().next_node_id()).collect();
+    for (i, id) in ids.iter().enumerate() {
+        nm.inner().add_node(
+            nm.address_space(),
+            tester.handle.type_tree(),
+            VariableBuilder::new(id, format!("Var{i}"), format!("Var{i}"))
+                .value(i as i32)
+                .data_type(DataTypeId::Int32)
+                .build()
+                .into(),
+            &ObjectId::ObjectsFolder.into(),
+            &ReferenceTypeId::Organizes.into(),
+            Some(&VariableTypeId::BaseDataVariableType.into()),
+            Vec::new(),
+        );
+    }
+
+    // Too many for a single read, but read_all splits it into batches.
+    let count = read_limit * 2 + 5;
+    let mut ops: Vec<_> = (0..count)
+        .map(|i| read_value_id(AttributeId::Value, ids[i % ids.len()].clone()))
+        .collect();
+    ops.push(read_value_id(AttributeId::Value, NodeId::new(2, "missing")));
+    let r = session.read_all(ops, TimestampsToReturn::Both, 0.0).await;
+    assert_eq!(r.len(), count + 1);
+    for (i, v) in r.iter().take(count).enumerate() {
+        assert_eq!(v.value, Some(Variant::Int32((i % ids.len()) as i32)));
+    }
+    assert_eq!(r[count].status, Some(StatusCode::BadNodeIdUnknown));
+
+    // Nothing to read.
+    let r = session
+        .read_all(Vec::new(), TimestampsToReturn::Both, 0.0)
+        .await;
+    assert!(r.is_empty());
+}
+
+#[tokio::test]
+async fn read_operation_limits() {
+    let (tester, _nm, session) = setup().await;
+    let config = &tester.handle.info().config.limits.operational;
+    let expected = |v: usize| match v {
+        0 => OperationLimit::Unlimited,
+        v => OperationLimit::Limit(v),
+    };
+
+    let limits = session.operation_limits().await.unwrap();
+    assert_eq!(
+        limits.max_nodes_per_read,
+        expected(config.max_nodes_per_read)
+    );
+    assert!(!limits.max_nodes_per_read.is_default());
+    assert_eq!(
+        limits.max_nodes_per_history_read_data,
+        expected(config.max_nodes_per_history_read_data)
+    );
+    assert_eq!(
+        limits.max_nodes_per_history_read_events,
+        expected(config.max_nodes_per_history_read_events)
+    );
+    assert_eq!(
+        limits.max_nodes_per_write,
+        expected(config.max_nodes_per_write)
+    );
+    assert_eq!(
+        limits.max_nodes_per_history_update_data,
+        expected(config.max_nodes_per_history_update)
+    );
+    assert_eq!(
+        limits.max_nodes_per_history_update_events,
+        expected(config.max_nodes_per_history_update)
+    );
+    assert_eq!(
+        limits.max_nodes_per_method_call,
+        expected(config.max_nodes_per_method_call)
+    );
+    assert_eq!(
+        limits.max_nodes_per_browse,
+        expected(config.max_nodes_per_browse)
+    );
+    assert_eq!(
+        limits.max_nodes_per_register_nodes,
+        expected(config.max_nodes_per_register_nodes)
+    );
+    assert_eq!(
+        limits.max_nodes_per_translate_browse_paths_to_node_ids,
+        expected(config.max_nodes_per_translate_browse_paths_to_node_ids)
+    );
+    assert_eq!(
+        limits.max_nodes_per_node_management,
+        expected(config.max_nodes_per_node_management)
+    );
+    assert_eq!(
+        limits.max_monitored_items_per_call,
+        expected(config.max_monitored_items_per_call)
+    );
+
+    // The limits are cached on the session.
+    assert_eq!(session.operation_limits().await.unwrap(), limits);
+}
+
+#[tokio::test]
+async fn read_analog_item_properties() {
+    let (tester, nm, session) = setup().await;
+
+    let id = nm.inner().next_node_id();
+    nm.inner().add_node(
+        nm.address_space(),
+        tester.handle.type_tree(),
+        VariableBuilder::new(&id, "Analog", "Analog")
+            .value(10.0f64)
+            .data_type(DataTypeId::Double)
+            .access_level(AccessLevel::CURRENT_READ)
+            .user_access_level(AccessLevel::CURRENT_READ)
+            .build()
+            .into(),
+        &ObjectId::ObjectsFolder.into(),
+        &ReferenceTypeId::Organizes.into(),
+        Some(&VariableTypeId::AnalogItemType.into()),
+        Vec::new(),
+    );
+    let range = Range {
+        low: 5.0,
+        high: 15.0,
+    };
+    let units = EUInformation {
+        namespace_uri: "http://www.opcfoundation.org/UA/units/un/cefact".into(),
+        unit_id: 4408652,
+        display_name: "°C".into(),
+        description: "degree Celsius".into(),
+    };
+    let range_id = nm.inner().next_node_id();
+    let units_id = nm.inner().next_node_id();
+    for (prop_id, name, value, data_type) in [
+        (
+            &range_id,
+            "EURange",
+            Variant::from(range.clone()),
+            DataTypeId::Range,
+        ),
+        (
+            &units_id,
+            "EngineeringUnits",
+            Variant::from(units.clone()),
+            DataTypeId::EUInformation,
+        ),
+    ] {
+        nm.inner().add_node(
+            nm.address_space(),
+            tester.handle.type_tree(),
+            VariableBuilder::new(prop_id, name, name)
+                .value(value)
+                .data_type(data_type)
+                .access_level(AccessLevel::CURRENT_READ)
+                .user_access_level(AccessLevel::CURRENT_READ)
+                .build()
+                .into(),
+            &id,
+            &ReferenceTypeId::HasProperty.into(),
+            Some(&VariableTypeId::PropertyType.into()),
+            Vec::new(),
+        );
+    }
+
+    assert_eq!(session.read_eu_range(&id).await.unwrap(), range);
+    assert_eq!(session.read_eu_information(&id).await.unwrap(), units);
+
+    // A variable that isn't an analog item.
+    let plain_id = nm.inner().next_node_id();
+    nm.inner().add_node(
+        nm.address_space(),
+        tester.handle.type_tree(),
+        VariableBuilder::new(&plain_id, "Plain", "Plain")
+            .value(10.0f64)
+            .data_type(DataTypeId::Double)
+            .access_level(AccessLevel::CURRENT_READ)
+            .user_access_level(AccessLevel::CURRENT_READ)
+            .build()
+            .into(),
+        &ObjectId::ObjectsFolder.into(),
+        &ReferenceTypeId::Organizes.into(),
+        Some(&VariableTypeId::BaseDataVariableType.into()),
+        Vec::new(),
+    );
+    let err = session.read_eu_range(&plain_id).await.unwrap_err();
+    assert_eq!(err.status(), StatusCode::BadNoMatch);
+    let err = session.read_eu_information(&plain_id).await.unwrap_err();
+    assert_eq!(err.status(), StatusCode::BadNoMatch);
+}
+
 #[tokio::test]
 async fn history_read_raw() {
     let (tester, nm, session) = setup().await;
@@ -1077,6 +1638,114 @@ async fn history_read_fail() {
     assert_eq!(r[0].status_code, StatusCode::BadNodeIdUnknown);
 }
 
+#[tokio::test]
+async fn read_request_locale_ids() {
+    let (tester, nm, session) = setup().await;
+
+    let id = nm.inner().next_node_id();
+    nm.inner().add_node(
+        nm.address_space(),
+        tester.handle.type_tree(),
+        VariableBuilder::new(&id, "TestVar1", "TestVar1")
+            .value(LocalizedText::new("en", "Hello"))
+            .data_type(DataTypeId::LocalizedText)
+            .access_level(AccessLevel::CURRENT_READ)
+            .user_access_level(AccessLevel::CURRENT_READ)
+            .build()
+            .into(),
+        &ObjectId::ObjectsFolder.into(),
+        &ReferenceTypeId::Organizes.into(),
+        Some(&VariableTypeId::BaseDataVariableType.into()),
+        Vec::new(),
+    );
+    nm.inner().add_translations(
+        id.clone(),
+        vec![
+            LocalizedText::new("en", "Hello"),
+            LocalizedText::new("fr", "Bonjour"),
+            LocalizedText::new("de", "Hallo"),
+        ],
+    );
+
+    // Without any preferred locales we get the first translation.
+    let r = session
+        .read(
+            &[ReadValueId::new_value(id.clone())],
+            TimestampsToReturn::Both,
+            0.0,
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        r[0].value,
+        Some(Variant::from(LocalizedText::new("en", "Hello")))
+    );
+
+    // Override the locale for a single request.
+    for (locale, expected) in [("fr-FR", "Bonjour"), ("de", "Hallo")] {
+        let r = Read::new(&session)
+            .node(ReadValueId::new_value(id.clone()))
+            .request_locale_ids(vec![locale.into()])
+            .send(session.channel())
+            .await
+            .unwrap();
+        assert_eq!(
+            r.results.unwrap()[0].value,
+            Some(Variant::from(LocalizedText::new(&locale[..2], expected)))
+        );
+    }
+}
+
+#[tokio::test]
+async fn read_unhealthy_node_manager() {
+    let (tester, nm, session) = setup().await;
+
+    let id = nm.inner().next_node_id();
+    nm.inner().add_node(
+        nm.address_space(),
+        tester.handle.type_tree(),
+        VariableBuilder::new(&id, "TestVar1", "TestVar1")
+            .value(1)
+            .data_type(DataTypeId::Int32)
+            .access_level(AccessLevel::CURRENT_READ)
+            .user_access_level(AccessLevel::CURRENT_READ)
+            .build()
+            .into(),
+        &ObjectId::ObjectsFolder.into(),
+        &ReferenceTypeId::Organizes.into(),
+        Some(&VariableTypeId::BaseDataVariableType.into()),
+        Vec::new(),
+    );
+    *nm.inner().issues().health.lock() =
+        NodeManagerHealth::Unhealthy(StatusCode::UncertainNoCommunicationLastUsableValue);
+
+    // Nodes owned by the unhealthy node manager fail, other nodes are unaffected.
+    let r = session
+        .read(
+            &[
+                ReadValueId::new_value(id.clone()),
+                read_value_id(AttributeId::Value, VariableId::Server_ServiceLevel),
+            ],
+            TimestampsToReturn::Both,
+            0.0,
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        r[0].status,
+        Some(StatusCode::UncertainNoCommunicationLastUsableValue)
+    );
+    assert_eq!(r[0].value, None);
+    assert!(r[1].value.is_some());
+
+    *nm.inner().issues().health.lock() = NodeManagerHealth::Healthy;
+    let r = session
+        .read(&[ReadValueId::new_value(id)], TimestampsToReturn::Both, 0.0)
+        .await
+        .unwrap();
+    assert_eq!(r[0].value, Some(Variant::Int32(1)));
+}
+
 #[tokio::test]
 async fn read_retry() {
     let (tester, nm, session) = setup().await;
@@ -1196,3 +1865,389 @@ async fn test_diagnostics() {
     assert_eq!(diagnostics[2].value, Some(Variant::UInt32(1)));
     assert_eq!(diagnostics[3].value, Some(Variant::UInt32(0)));
 }
+
+fn session_diagnostics_array(value: &DataValue) -> Vec<SessionDiagnosticsDataType> {
+    let Some(Variant::Array(arr)) = &value.value else {
+        panic!("Expected array, got {value:?}");
+    };
+    arr.values
+        .iter()
+        .map(|v| {
+            let Variant::ExtensionObject(o) = v else {
+                panic!("Expected extension object, got {v:?}");
+            };
+            o.inner_as::<SessionDiagnosticsDataType>().unwrap().clone()
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn test_session_diagnostics() {
+    let server = default_server().diagnostics_enabled(true);
+    let mut tester = Tester::new(server, false).await;
+    let (admin, lp) = tester
+        .connect(
+            opcua_crypto::SecurityPolicy::Aes128Sha256RsaOaep,
+            opcua_types::MessageSecurityMode::SignAndEncrypt,
+            client_user_token(),
+        )
+        .await
+        .unwrap();
+    lp.spawn();
+    tokio::time::timeout(Duration::from_secs(2), admin.wait_for_connection())
+        .await
+        .unwrap();
+    let (anonymous, lp) = tester.connect_default().await.unwrap();
+    lp.spawn();
+    tokio::time::timeout(Duration::from_secs(2), anonymous.wait_for_connection())
+        .await
+        .unwrap();
+
+    let array_id: NodeId =
+        VariableId::Server_ServerDiagnostics_SessionsDiagnosticsSummary_SessionDiagnosticsArray
+            .into();
+
+    // The admin user can see every session.
+    let r = admin
+        .read(
+            &[ReadValueId::new_value(array_id.clone())],
+            TimestampsToReturn::Both,
+            0.0,
+        )
+        .await
+        .unwrap();
+    let sessions = session_diagnostics_array(&r[0]);
+    assert_eq!(sessions.len(), 2);
+
+    // The anonymous user can only see its own session.
+    let r = anonymous
+        .read(
+            &[ReadValueId::new_value(array_id)],
+            TimestampsToReturn::Both,
+            0.0,
+        )
+        .await
+        .unwrap();
+    let sessions = session_diagnostics_array(&r[0]);
+    assert_eq!(sessions.len(), 1);
+    assert_eq!(sessions[0].session_id, anonymous.server_session_id());
+    assert!(sessions[0].total_request_count.total_count > 0);
+
+    // Browse the per-session diagnostics nodes.
+    let desc = BrowseDescription {
+        node_id: ObjectId::Server_ServerDiagnostics_SessionsDiagnosticsSummary.into(),
+        browse_direction: BrowseDirection::Forward,
+        reference_type_id: ReferenceTypeId::HasComponent.into(),
+        include_subtypes: true,
+        node_class_mask: NodeClassMask::OBJECT.bits(),
+        result_mask: BrowseResultMask::All as u32,
+    };
+    let r = admin
+        .browse(std::slice::from_ref(&desc), 0, None)
+        .await
+        .unwrap();
+    let admin_refs = r[0].references.clone().unwrap_or_default();
+    assert_eq!(admin_refs.len(), 2);
+    let r = anonymous.browse(&[desc], 0, None).await.unwrap();
+    let refs = r[0].references.clone().unwrap_or_default();
+    assert_eq!(refs.len(), 1);
+    let own_session = refs[0].node_id.node_id.clone();
+
+    let r = anonymous
+        .browse(
+            &[BrowseDescription {
+                node_id: own_session.clone(),
+                browse_direction: BrowseDirection::Forward,
+                reference_type_id: ReferenceTypeId::HasComponent.into(),
+                include_subtypes: true,
+                node_class_mask: NodeClassMask::VARIABLE.bits(),
+                result_mask: BrowseResultMask::All as u32,
+            }],
+            0,
+            None,
+        )
+        .await
+        .unwrap();
+    let refs = r[0].references.clone().unwrap_or_default();
+    assert_eq!(refs.len(), 2);
+    let diag_node = refs
+        .iter()
+        .find(|r| r.browse_name.name.as_ref() == "SessionDiagnostics")
+        .unwrap()
+        .node_id
+        .node_id
+        .clone();
+
+    let r = anonymous
+        .read(
+            &[ReadValueId::new_value(diag_node)],
+            TimestampsToReturn::Both,
+            0.0,
+        )
+        .await
+        .unwrap();
+    let Some(Variant::ExtensionObject(o)) = &r[0].value else {
+        panic!("Expected extension object, got {:?}", r[0]);
+    };
+    let diag = o.inner_as::<SessionDiagnosticsDataType>().unwrap();
+    assert_eq!(diag.session_id, anonymous.server_session_id());
+
+    // The anonymous user cannot read the diagnostics of the other session.
+    let other_session = admin_refs
+        .iter()
+        .find(|r| r.node_id.node_id != own_session)
+        .unwrap()
+        .node_id
+        .node_id
+        .clone();
+    let r = anonymous
+        .read(
+            &[read_value_id(AttributeId::DisplayName, other_session)],
+            TimestampsToReturn::Both,
+            0.0,
+        )
+        .await
+        .unwrap();
+    assert_eq!(r[0].status, Some(StatusCode::BadNodeIdUnknown));
+}
+
+#[tokio::test]
+async fn read_server_auditing() {
+    let (_tester, _nm, session) = setup().await;
+    assert!(!session.read_server_auditing().await.unwrap());
+
+    let server = test_server().auditing_enabled(true);
+    let mut tester = Tester::new(server, false).await;
+    let (session, lp) = tester.connect_default().await.unwrap();
+    lp.spawn();
+    tokio::time::timeout(Duration::from_secs(2), session.wait_for_connection())
+        .await
+        .unwrap();
+
+    assert!(!session.server_auditing());
+    assert!(session.read_server_auditing().await.unwrap());
+    assert!(session.server_auditing());
+}
+
+#[tokio::test]
+async fn test_session_diagnostics_require_permission() {
+    let server = default_server()
+        .diagnostics_enabled(true)
+        .diagnostics_require_permission(true);
+    let mut tester = Tester::new(server, false).await;
+    let (admin, lp) = tester
+        .connect(
+            opcua_crypto::SecurityPolicy::Aes128Sha256RsaOaep,
+            opcua_types::MessageSecurityMode::SignAndEncrypt,
+            client_user_token(),
+        )
+        .await
+        .unwrap();
+    lp.spawn();
+    tokio::time::timeout(Duration::from_secs(2), admin.wait_for_connection())
+        .await
+        .unwrap();
+    let (anonymous, lp) = tester.connect_default().await.unwrap();
+    lp.spawn();
+    tokio::time::timeout(Duration::from_secs(2), anonymous.wait_for_connection())
+        .await
+        .unwrap();
+
+    let array_id: NodeId =
+        VariableId::Server_ServerDiagnostics_SessionsDiagnosticsSummary_SessionDiagnosticsArray
+            .into();
+
+    // The admin user can still see every session.
+    let r = admin
+        .read(
+            &[ReadValueId::new_value(array_id.clone())],
+            TimestampsToReturn::Both,
+            0.0,
+        )
+        .await
+        .unwrap();
+    assert_eq!(session_diagnostics_array(&r[0]).len(), 2);
+
+    // The anonymous user cannot see any sessions, not even its own.
+    let r = anonymous
+        .read(
+            &[ReadValueId::new_value(array_id)],
+            TimestampsToReturn::Both,
+            0.0,
+        )
+        .await
+        .unwrap();
+    assert_eq!(r[0].status, Some(StatusCode::BadUserAccessDenied));
+
+    let desc = BrowseDescription {
+        node_id: ObjectId::Server_ServerDiagnostics_SessionsDiagnosticsSummary.into(),
+        browse_direction: BrowseDirection::Forward,
+        reference_type_id: ReferenceTypeId::HasComponent.into(),
+        include_subtypes: true,
+        node_class_mask: NodeClassMask::OBJECT.bits(),
+        result_mask: BrowseResultMask::All as u32,
+    };
+    let r = admin
+        .browse(std::slice::from_ref(&desc), 0, None)
+        .await
+        .unwrap();
+    let admin_refs = r[0].references.clone().unwrap_or_default();
+    assert_eq!(admin_refs.len(), 2);
+    let r = anonymous
+        .browse(std::slice::from_ref(&desc), 0, None)
+        .await
+        .unwrap();
+    assert!(r[0].references.clone().unwrap_or_default().is_empty());
+
+    // Reading the session nodes directly is rejected.
+    let to_read: Vec<_> = admin_refs
+        .iter()
+        .map(|r| read_value_id(AttributeId::BrowseName, r.node_id.node_id.clone()))
+        .collect();
+    let r = anonymous
+        .read(&to_read, TimestampsToReturn::Both, 0.0)
+        .await
+        .unwrap();
+    for v in r {
+        assert_eq!(v.status, Some(StatusCode::BadUserAccessDenied));
+    }
+    let r = admin
+        .read(&to_read, TimestampsToReturn::Both, 0.0)
+        .await
+        .unwrap();
+    for v in r {
+        assert_eq!(v.status, Some(StatusCode::Good));
+    }
+}
+
+#[tokio::test]
+async fn read_server_profiles() {
+    let (_tester, _nm, session) = setup().await;
+
+    let r = session
+        .read(
+            &[ReadValueId::new_value(
+                VariableId::Server_ServerCapabilities_ServerProfileArray.into(),
+            )],
+            TimestampsToReturn::Neither,
+            0.0,
+        )
+        .await
+        .unwrap();
+    let Some(Variant::Array(arr)) = &r[0].value else {
+        panic!("Expected array, got {:?}", r[0].value);
+    };
+    assert!(arr
+        .values
+        .contains(&Variant::from(profiles::SERVER_FACET_CORE_2017)));
+}
+
+#[tokio::test]
+async fn read_max_age_value_cache() {
+    let server = default_server()
+        .with_node_manager(test_node_manager_with_value_cache(Duration::from_secs(60)));
+    let mut tester = Tester::new(server, false).await;
+    let nm = tester
+        .handle
+        .node_managers()
+        .get_of_type::<TestNodeManager>()
+        .unwrap();
+    let (session, lp) = tester.connect_default().await.unwrap();
+    lp.spawn();
+    tokio::time::timeout(Duration::from_secs(2), session.wait_for_connection())
+        .await
+        .unwrap();
+
+    let id = nm.inner().next_node_id();
+    nm.inner().add_node(
+        nm.address_space(),
+        tester.handle.type_tree(),
+        VariableBuilder::new(&id, "TestVar1", "TestVar1")
+            .value(1)
+            .data_type(DataTypeId::Int32)
+            .access_level(AccessLevel::CURRENT_READ)
+            .user_access_level(AccessLevel::CURRENT_READ)
+            .build()
+            .into(),
+        &ObjectId::ObjectsFolder.into(),
+        &ReferenceTypeId::Organizes.into(),
+        Some(&VariableTypeId::BaseDataVariableType.into()),
+        Vec::new(),
+    );
+
+    let read_count = || {
+        nm.inner()
+            .call_info()
+            .lock()
+            .read_values
+            .iter()
+            .filter(|n| **n == id)
+            .count()
+    };
+    let read = |max_age: f64| {
+        let session = session.clone();
+        let id = id.clone();
+        async move {
+            let r = session
+                .read(
+                    &[read_value_id(AttributeId::Value, id)],
+                    TimestampsToReturn::Both,
+                    max_age,
+                )
+                .await
+                .unwrap();
+            r[0].value.clone()
+        }
+    };
+
+    // The first read populates the cache, the second is answered from it.
+    assert_eq!(read(0.0).await, Some(Variant::Int32(1)));
+    assert_eq!(read_count(), 1);
+    assert_eq!(read(10_000.0).await, Some(Variant::Int32(1)));
+    assert_eq!(read_count(), 1);
+
+    // A max age of zero always reads the current value.
+    assert_eq!(read(0.0).await, Some(Variant::Int32(1)));
+    assert_eq!(read_count(), 2);
+
+    // Setting the value removes it from the cache.
+    nm.set_value(
+        tester.handle.subscriptions(),
+        &id,
+        None,
+        DataValue::new_now(2),
+    )
+    .unwrap();
+    assert_eq!(read(10_000.0).await, Some(Variant::Int32(2)));
+    assert_eq!(read_count(), 3);
+
+    // A cached value older than the max age is not used.
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert_eq!(read(10.0).await, Some(Variant::Int32(2)));
+    assert_eq!(read_count(), 4);
+
+    // Values are cached per user, another user does not get the cached value.
+    let user_session = tester
+        .connect_and_wait(
+            opcua_crypto::SecurityPolicy::Aes128Sha256RsaOaep,
+            opcua_types::MessageSecurityMode::SignAndEncrypt,
+            client_user_token(),
+        )
+        .await
+        .unwrap();
+    let user_read = || async {
+        let r = user_session
+            .read(
+                &[read_value_id(AttributeId::Value, id.clone())],
+                TimestampsToReturn::Both,
+                10_000.0,
+            )
+            .await
+            .unwrap();
+        r[0].value.clone()
+    };
+    assert_eq!(user_read().await, Some(Variant::Int32(2)));
+    assert_eq!(read_count(), 5);
+    assert_eq!(user_read().await, Some(Variant::Int32(2)));
+    assert_eq!(read_count(), 5);
+}
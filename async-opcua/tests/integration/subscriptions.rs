@@ -1,32 +1,56 @@
-use std::{collections::HashMap, time::Duration};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{
+        atomic::{AtomicI32, AtomicU32, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
 
-use crate::utils::{test_server, ChannelNotifications, TestNodeManager, Tester};
+use crate::utils::{
+    client_user_token, default_server, test_node_manager, test_server, ChannelNotifications,
+    TestNodeManager, Tester, CLIENT_USERPASS_ID,
+};
 
-use super::utils::setup;
+use super::utils::{read_value_id, setup};
+use async_trait::async_trait;
 use chrono::DateTime;
+use futures::StreamExt;
 use opcua::{
-    server::address_space::{AccessLevel, VariableBuilder},
+    server::{
+        address_space::{AccessLevel, EventNotifier, ObjectBuilder, VariableBuilder},
+        authenticator::{AuthManager, DefaultAuthenticator, UserToken},
+        diagnostics::{DiagnosticsNodeManager, NamespaceMetadata},
+        node_manager::{
+            memory::{polling_node_manager, PollingNodeManager, PollingSource},
+            NodeManager,
+        },
+        ConditionMethod, ServerEndpoint,
+    },
     types::{
         AttributeId, DataTypeId, DataValue, MonitoredItemCreateRequest, MonitoredItemModifyRequest,
         MonitoringMode, MonitoringParameters, NodeId, ObjectId, ReadValueId, ReferenceTypeId,
-        StatusCode, TimestampsToReturn, VariableTypeId, Variant,
+        StatusCode, TimestampsToReturn, VariableId, VariableTypeId, Variant,
     },
 };
 use opcua_client::{
     services::{
         CreateMonitoredItems, CreateSubscription, Publish, Republish, TransferSubscriptions,
     },
-    IdentityToken, Subscription, UARequest,
+    DataChangeCallback, IdentityToken, OnSubscriptionNotification, Session, StreamBackpressure,
+    Subscription, SubscriptionCallbacks, SubscriptionNotification, SubscriptionStateSnapshot,
+    SubscriptionStream, UARequest,
 };
 use opcua_core_namespace::events::{
     AuditHistoryBulkInsertEventType, AuditSecurityEventType, ProgressEventType,
 };
 use opcua_crypto::{random, SecurityPolicy};
-use opcua_nodes::Event;
+use opcua_nodes::{Event, EventBuilder};
 use opcua_types::{
-    ContentFilterBuilder, DataChangeFilter, DataChangeTrigger, DeadbandType, EventFilter,
-    ExtensionObject, LiteralOperand, MessageSecurityMode, ObjectTypeId, Operand, Range,
-    SimpleAttributeOperand,
+    BrowseDescription, BrowseDirection, BrowseResultMask, ContentFilterBuilder, DataChangeFilter,
+    DataChangeTrigger, DeadbandType, Error, EventFilter, ExtensionObject, LiteralOperand,
+    LocalizedText, MessageSecurityMode, MethodId, ObjectTypeId, Operand, Range,
+    SimpleAttributeOperand, SubscriptionDiagnosticsDataType, UserTokenPolicy,
 };
 use tokio::{sync::mpsc::UnboundedReceiver, time::timeout};
 
@@ -121,6 +145,198 @@ async fn simple_subscriptions() {
     session.delete_subscription(sub_id).await.unwrap();
 }
 
+#[tokio::test]
+async fn unchanged_value_not_reported() {
+    let (tester, nm, session) = setup().await;
+
+    let id = nm.inner().next_node_id();
+    nm.inner().add_node(
+        nm.address_space(),
+        tester.handle.type_tree(),
+        VariableBuilder::new(&id, "TestVar1", "TestVar1")
+            .value(false)
+            .data_type(DataTypeId::Boolean)
+            .access_level(AccessLevel::CURRENT_READ)
+            .user_access_level(AccessLevel::CURRENT_READ)
+            .build()
+            .into(),
+        &ObjectId::ObjectsFolder.into(),
+        &ReferenceTypeId::Organizes.into(),
+        Some(&VariableTypeId::BaseDataVariableType.into()),
+        Vec::new(),
+    );
+
+    let (notifs, mut data, _) = ChannelNotifications::new();
+    let sub_id = session
+        .create_subscription(Duration::from_millis(100), 100, 20, 1000, 0, true, notifs)
+        .await
+        .unwrap();
+
+    // No filter, so the default `StatusValue` trigger is used.
+    let res = session
+        .create_monitored_items(
+            sub_id,
+            TimestampsToReturn::Both,
+            vec![MonitoredItemCreateRequest {
+                item_to_monitor: read_value_id(AttributeId::Value, id.clone()),
+                monitoring_mode: MonitoringMode::Reporting,
+                requested_parameters: MonitoringParameters {
+                    sampling_interval: 0.0,
+                    queue_size: 10,
+                    discard_oldest: true,
+                    ..Default::default()
+                },
+            }],
+        )
+        .await
+        .unwrap();
+    assert_eq!(res[0].result.status_code, StatusCode::Good);
+
+    let (_, v) = timeout(Duration::from_millis(500), data.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(v.value, Some(Variant::Boolean(false)));
+
+    // Set the same value twice, only the first is a change.
+    for _ in 0..2 {
+        nm.set_value(
+            tester.handle.subscriptions(),
+            &id,
+            None,
+            DataValue::new_now(true),
+        )
+        .unwrap();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+
+    let (_, v) = timeout(Duration::from_millis(500), data.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(v.value, Some(Variant::Boolean(true)));
+    timeout(Duration::from_millis(300), data.recv())
+        .await
+        .unwrap_err();
+
+    session.delete_subscription(sub_id).await.unwrap();
+}
+
+/// Records the subscription each data value was received on, in order.
+struct OrderedNotifications {
+    tag: &'static str,
+    received: Arc<Mutex<Vec<&'static str>>>,
+}
+
+impl OnSubscriptionNotification for OrderedNotifications {
+    fn on_data_value(&mut self, _notification: DataValue, _item: &opcua::client::MonitoredItem) {
+        self.received.lock().unwrap().push(self.tag);
+    }
+}
+
+#[tokio::test]
+async fn subscription_priority() {
+    let (tester, nm, session) = setup().await;
+
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let mut subs = Vec::new();
+    // The low priority subscription is created first, so it has the lowest ID.
+    for (tag, priority) in [("low", 1), ("high", 200)] {
+        let id = nm.inner().next_node_id();
+        nm.inner().add_node(
+            nm.address_space(),
+            tester.handle.type_tree(),
+            VariableBuilder::new(&id, tag, tag)
+                .value(0)
+                .data_type(DataTypeId::Int32)
+                .access_level(AccessLevel::CURRENT_READ)
+                .user_access_level(AccessLevel::CURRENT_READ)
+                .build()
+                .into(),
+            &ObjectId::ObjectsFolder.into(),
+            &ReferenceTypeId::Organizes.into(),
+            Some(&VariableTypeId::BaseDataVariableType.into()),
+            Vec::new(),
+        );
+
+        // One notification per publish response, so each subscription produces
+        // several messages.
+        let sub_id = session
+            .create_subscription(
+                Duration::from_millis(100),
+                100,
+                20,
+                1,
+                priority,
+                true,
+                OrderedNotifications {
+                    tag,
+                    received: received.clone(),
+                },
+            )
+            .await
+            .unwrap();
+        let res = session
+            .create_monitored_items(
+                sub_id,
+                TimestampsToReturn::Both,
+                vec![MonitoredItemCreateRequest {
+                    item_to_monitor: read_value_id(AttributeId::Value, id.clone()),
+                    monitoring_mode: MonitoringMode::Reporting,
+                    requested_parameters: MonitoringParameters {
+                        sampling_interval: 0.0,
+                        queue_size: 10,
+                        discard_oldest: true,
+                        ..Default::default()
+                    },
+                }],
+            )
+            .await
+            .unwrap();
+        assert_eq!(res[0].result.status_code, StatusCode::Good);
+        subs.push((sub_id, id));
+    }
+
+    // Wait for the initial values.
+    timeout(Duration::from_secs(2), async {
+        while received.lock().unwrap().len() < 2 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .unwrap();
+    received.lock().unwrap().clear();
+
+    // Queue notifications on both subscriptions while publishing is disabled,
+    // then enable both at once, so they have notifications ready on the same tick.
+    // The values have no source timestamp so they aren't filtered by sampling interval.
+    let sub_ids: Vec<_> = subs.iter().map(|s| s.0).collect();
+    session.set_publishing_mode(&sub_ids, false).await.unwrap();
+    for i in 1..=5 {
+        for (_, id) in &subs {
+            nm.set_value(
+                tester.handle.subscriptions(),
+                id,
+                None,
+                DataValue::value_only(i),
+            )
+            .unwrap();
+        }
+    }
+    session.set_publishing_mode(&sub_ids, true).await.unwrap();
+
+    timeout(Duration::from_secs(5), async {
+        while received.lock().unwrap().len() < 10 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .unwrap();
+
+    let received = received.lock().unwrap().clone();
+    assert_eq!(received, [["high"; 5], ["low"; 5]].concat());
+}
+
 async fn recv_n<T>(recv: &mut UnboundedReceiver<T>, n: usize) -> Vec<T> {
     let mut res = Vec::with_capacity(n);
     for _ in 0..n {
@@ -191,12 +407,18 @@ async fn many_subscriptions() {
     }
 
     // Should get 1000 notifications, note that since the max notifications per publish is 100,
-    // this should require 10 publish requests. No current way to measure that, unfortunately.
-    // TODO: Once we have proper server metrics, check those here.
+    // this should require at least 10 notification messages.
     let its = tokio::time::timeout(Duration::from_millis(800), recv_n(&mut data, 1000))
         .await
         .unwrap();
     assert_eq!(1000, its.len());
+    assert!(tester.handle.subscriptions().notification_message_count() >= 10);
+    // Each notification message needs a publish round-trip.
+    let stats = session.publish_stats();
+    assert!(stats.responses_received >= 10);
+    assert!(stats.requests_sent >= stats.responses_received);
+    assert_eq!(stats.min_publish_requests, 2);
+    assert!(!stats.publish_starved);
     for (_id, v) in its {
         let val = match v.value {
             Some(Variant::Int32(v)) => v,
@@ -369,6 +591,159 @@ async fn modify_subscription() {
     session.delete_subscription(sub_id).await.unwrap();
 }
 
+#[tokio::test]
+async fn subscription_metrics() {
+    let server = test_server().diagnostics_enabled(true);
+    let mut tester = Tester::new(server, false).await;
+    let nm = tester
+        .handle
+        .node_managers()
+        .get_of_type::<TestNodeManager>()
+        .unwrap();
+    let (session, lp) = tester.connect_default().await.unwrap();
+    lp.spawn();
+    tokio::time::timeout(Duration::from_secs(2), session.wait_for_connection())
+        .await
+        .unwrap();
+
+    let id = nm.inner().next_node_id();
+    nm.inner().add_node(
+        nm.address_space(),
+        tester.handle.type_tree(),
+        VariableBuilder::new(&id, "TestVar1", "TestVar1")
+            .value(-1)
+            .data_type(DataTypeId::Int32)
+            .access_level(AccessLevel::CURRENT_READ)
+            .user_access_level(AccessLevel::CURRENT_READ)
+            .build()
+            .into(),
+        &ObjectId::ObjectsFolder.into(),
+        &ReferenceTypeId::Organizes.into(),
+        Some(&VariableTypeId::BaseDataVariableType.into()),
+        Vec::new(),
+    );
+
+    let (notifs, mut data, _) = ChannelNotifications::new();
+    let sub_id = session
+        .create_subscription(Duration::from_millis(100), 100, 20, 1000, 0, true, notifs)
+        .await
+        .unwrap();
+
+    // Create a monitored item with a queue size of 1.
+    let res = session
+        .create_monitored_items(
+            sub_id,
+            TimestampsToReturn::Both,
+            vec![MonitoredItemCreateRequest {
+                item_to_monitor: ReadValueId {
+                    node_id: id.clone(),
+                    attribute_id: AttributeId::Value as u32,
+                    ..Default::default()
+                },
+                monitoring_mode: MonitoringMode::Reporting,
+                requested_parameters: MonitoringParameters {
+                    sampling_interval: 0.0,
+                    queue_size: 1,
+                    discard_oldest: true,
+                    ..Default::default()
+                },
+            }],
+        )
+        .await
+        .unwrap();
+    assert_eq!(res[0].result.status_code, StatusCode::Good);
+    let monitored_item_id = res[0].result.monitored_item_id;
+
+    // Wait for the initial value.
+    timeout(Duration::from_millis(500), data.recv())
+        .await
+        .unwrap()
+        .unwrap();
+
+    // Set several values before the next publish, overflowing the queue.
+    // Source timestamps are spread out so that none are filtered by the sampling interval.
+    let now = opcua::types::DateTime::now();
+    for i in 1..=5 {
+        nm.set_value(
+            tester.handle.subscriptions(),
+            &id,
+            None,
+            DataValue {
+                value: Some(i.into()),
+                source_timestamp: Some(now + chrono::TimeDelta::try_seconds(i.into()).unwrap()),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    }
+    let (_, v) = timeout(Duration::from_millis(500), data.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(v.value, Some(Variant::Int32(5)));
+    assert!(v.status().overflow());
+
+    let metrics = tester
+        .handle
+        .subscriptions()
+        .subscription_metrics(sub_id)
+        .unwrap();
+    assert_eq!(metrics.subscription_id, sub_id);
+    assert_eq!(metrics.queued, 6);
+    assert_eq!(metrics.dropped, 4);
+    assert_eq!(metrics.delivered, 2);
+    assert_eq!(metrics.monitored_items.len(), 1);
+    let item = &metrics.monitored_items[0];
+    assert_eq!(item.id, monitored_item_id);
+    assert_eq!(item.dropped, 4);
+    assert_eq!(item.queue_depth, 0);
+
+    // The counters are also available in the subscription diagnostics.
+    let r = session
+        .read(
+            &[ReadValueId::new_value(
+                VariableId::Server_ServerDiagnostics_SubscriptionDiagnosticsArray.into(),
+            )],
+            TimestampsToReturn::Both,
+            0.0,
+        )
+        .await
+        .unwrap();
+    let Some(Variant::Array(arr)) = &r[0].value else {
+        panic!("Expected array, got {:?}", r[0]);
+    };
+    assert_eq!(arr.values.len(), 1);
+    let Variant::ExtensionObject(o) = &arr.values[0] else {
+        panic!("Expected extension object");
+    };
+    let diagnostics = o.inner_as::<SubscriptionDiagnosticsDataType>().unwrap();
+    assert_eq!(diagnostics.session_id, session.server_session_id());
+    assert_eq!(diagnostics.subscription_id, sub_id);
+    assert_eq!(diagnostics.monitored_item_count, 1);
+    assert_eq!(diagnostics.notifications_count, 2);
+    assert_eq!(diagnostics.monitoring_queue_overflow_count, 4);
+
+    // Deleted monitored items are still included in the totals.
+    session
+        .delete_monitored_items(sub_id, &[monitored_item_id])
+        .await
+        .unwrap();
+    let metrics = tester
+        .handle
+        .subscriptions()
+        .subscription_metrics(sub_id)
+        .unwrap();
+    assert!(metrics.monitored_items.is_empty());
+    assert_eq!(metrics.dropped, 4);
+
+    session.delete_subscription(sub_id).await.unwrap();
+    assert!(tester
+        .handle
+        .subscriptions()
+        .subscription_metrics(sub_id)
+        .is_none());
+}
+
 #[tokio::test]
 async fn subscription_limits() {
     let (tester, _nm, session) = setup().await;
@@ -454,6 +829,15 @@ async fn subscription_limits() {
 
 #[tokio::test]
 async fn transfer_subscriptions() {
+    transfer_subscriptions_test(true).await;
+}
+
+#[tokio::test]
+async fn transfer_subscriptions_without_initial_values() {
+    transfer_subscriptions_test(false).await;
+}
+
+async fn transfer_subscriptions_test(send_initial_values: bool) {
     let server = test_server();
     let mut tester = Tester::new(server, false).await;
     let nm = tester
@@ -592,38 +976,78 @@ async fn transfer_subscriptions() {
     // Call transfer subscriptions.
     let r = TransferSubscriptions::new(&session)
         .subscription(sub_id)
-        .send_initial_values(true)
+        .send_initial_values(send_initial_values)
         .send(session.channel())
         .await
         .unwrap();
     assert_eq!(r.results.unwrap()[0].status_code, StatusCode::Good);
     session.trigger_publish_now();
 
-    // Expect a value
+    if send_initial_values {
+        // Expect the current value to be sent again.
+        let (r, v) = timeout(Duration::from_millis(500), data.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(r.node_id, id);
+        let val = match v.value {
+            Some(Variant::Int32(v)) => v,
+            _ => panic!("Expected integer value"),
+        };
+        assert_eq!(-1, val);
+    } else {
+        // The value has already been sent, so nothing is sent until it changes.
+        timeout(Duration::from_millis(300), data.recv())
+            .await
+            .unwrap_err();
+    }
+
+    // Future changes are delivered either way.
+    nm.set_value(
+        tester.handle.subscriptions(),
+        &id,
+        None,
+        DataValue::new_now(2),
+    )
+    .unwrap();
     let (r, v) = timeout(Duration::from_millis(500), data.recv())
         .await
         .unwrap()
         .unwrap();
     assert_eq!(r.node_id, id);
-    let val = match v.value {
-        Some(Variant::Int32(v)) => v,
-        _ => panic!("Expected integer value"),
-    };
-    assert_eq!(-1, val);
+    assert_eq!(v.value, Some(Variant::Int32(2)));
 }
 
 #[tokio::test]
-async fn test_data_change_filters() {
-    let (tester, nm, session) = setup().await;
+async fn export_import_subscription_state() {
+    let server = test_server();
+    let mut tester = Tester::new(server, false).await;
+    let nm = tester
+        .handle
+        .node_managers()
+        .get_of_type::<TestNodeManager>()
+        .unwrap();
+    // Need to use an encrypted connection, or transfer won't work.
+    let (session, lp) = tester
+        .connect(
+            SecurityPolicy::Aes256Sha256RsaPss,
+            MessageSecurityMode::SignAndEncrypt,
+            IdentityToken::Anonymous,
+        )
+        .await
+        .unwrap();
+    lp.spawn();
+    tokio::time::timeout(Duration::from_secs(2), session.wait_for_connection())
+        .await
+        .unwrap();
 
     let id = nm.inner().next_node_id();
-    // Add a pair of nodes
     nm.inner().add_node(
         nm.address_space(),
         tester.handle.type_tree(),
         VariableBuilder::new(&id, "TestVar1", "TestVar1")
-            .value(0.0f64)
-            .data_type(DataTypeId::Double)
+            .value(-1)
+            .data_type(DataTypeId::Int32)
             .access_level(AccessLevel::CURRENT_READ)
             .user_access_level(AccessLevel::CURRENT_READ)
             .build()
@@ -633,21 +1057,255 @@ async fn test_data_change_filters() {
         Some(&VariableTypeId::BaseDataVariableType.into()),
         Vec::new(),
     );
-    let id2 = nm.inner().next_node_id();
-    nm.inner().add_node(
-        nm.address_space(),
-        tester.handle.type_tree(),
-        VariableBuilder::new(&id2, "TestVar2", "TestVar2")
-            .value(6.0f64)
-            .data_type(DataTypeId::Double)
-            .access_level(AccessLevel::CURRENT_READ)
-            .user_access_level(AccessLevel::CURRENT_READ)
-            .build()
-            .into(),
-        &ObjectId::ObjectsFolder.into(),
-        &ReferenceTypeId::Organizes.into(),
-        Some(&VariableTypeId::BaseDataVariableType.into()),
-        Vec::new(),
+
+    let (notifs, mut data, _) = ChannelNotifications::new();
+    let sub_id = session
+        .create_subscription(Duration::from_millis(100), 100, 20, 1000, 0, true, notifs)
+        .await
+        .unwrap();
+    let res = session
+        .create_monitored_items(
+            sub_id,
+            TimestampsToReturn::Both,
+            vec![MonitoredItemCreateRequest {
+                item_to_monitor: read_value_id(AttributeId::Value, id.clone()),
+                monitoring_mode: MonitoringMode::Reporting,
+                requested_parameters: MonitoringParameters {
+                    sampling_interval: 0.0,
+                    queue_size: 10,
+                    discard_oldest: true,
+                    filter: ExtensionObject::from_message(DataChangeFilter {
+                        trigger: DataChangeTrigger::StatusValue,
+                        deadband_type: DeadbandType::None as u32,
+                        deadband_value: 0.0,
+                    }),
+                    ..Default::default()
+                },
+            }],
+        )
+        .await
+        .unwrap();
+    assert_eq!(res[0].result.status_code, StatusCode::Good);
+
+    let (_, v) = timeout(Duration::from_millis(500), data.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(v.value, Some(Variant::Int32(-1)));
+
+    // Store the subscription state and close the session, keeping the subscriptions.
+    let snapshot = session.export_subscription_state();
+    assert_eq!(snapshot.subscription_ids(), vec![sub_id]);
+    let stored = serde_json::to_string(&snapshot).unwrap();
+    session
+        .disconnect_without_delete_subscriptions()
+        .await
+        .unwrap();
+
+    // Restore the state on a new session and take over the subscription.
+    let (session, lp) = tester
+        .connect(
+            SecurityPolicy::Aes256Sha256RsaPss,
+            MessageSecurityMode::SignAndEncrypt,
+            IdentityToken::Anonymous,
+        )
+        .await
+        .unwrap();
+    lp.spawn();
+    tokio::time::timeout(Duration::from_secs(2), session.wait_for_connection())
+        .await
+        .unwrap();
+
+    let restored: SubscriptionStateSnapshot = serde_json::from_str(&stored).unwrap();
+    assert_eq!(restored, snapshot);
+    let (notifs, mut data, _) = ChannelNotifications::new();
+    let mut notifs = Some(notifs);
+    let ids = session
+        .import_subscription_state(restored.clone(), |_| notifs.take().unwrap())
+        .unwrap();
+    assert_eq!(ids, vec![sub_id]);
+    assert_eq!(session.export_subscription_state(), snapshot);
+    {
+        let state = session.subscription_state().lock();
+        let item = state
+            .get(sub_id)
+            .unwrap()
+            .monitored_items()
+            .values()
+            .next()
+            .unwrap()
+            .clone();
+        assert_eq!(item.item_to_monitor().node_id, id);
+        assert_eq!(
+            item.decoded_filter::<DataChangeFilter>().unwrap().trigger,
+            DataChangeTrigger::StatusValue
+        );
+    }
+
+    // Importing the same subscriptions again fails.
+    let err = session
+        .import_subscription_state(restored, |_| DataChangeCallback::new(|_, _| {}))
+        .unwrap_err();
+    assert_eq!(err.status(), StatusCode::BadSubscriptionIdInvalid);
+
+    let r = session
+        .transfer_subscriptions(&[sub_id], false)
+        .await
+        .unwrap();
+    assert_eq!(r[0].status_code, StatusCode::Good);
+
+    // Notifications on the existing monitored item are delivered to the restored subscription.
+    nm.set_value(
+        tester.handle.subscriptions(),
+        &id,
+        None,
+        DataValue::new_now(2),
+    )
+    .unwrap();
+    let (r, v) = timeout(Duration::from_millis(500), data.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(r.node_id, id);
+    assert_eq!(v.value, Some(Variant::Int32(2)));
+}
+
+#[tokio::test]
+async fn transfer_subscriptions_same_session() {
+    let server = test_server();
+    let mut tester = Tester::new(server, false).await;
+    let nm = tester
+        .handle
+        .node_managers()
+        .get_of_type::<TestNodeManager>()
+        .unwrap();
+    let (session, lp) = tester
+        .connect(
+            SecurityPolicy::Aes256Sha256RsaPss,
+            MessageSecurityMode::SignAndEncrypt,
+            IdentityToken::Anonymous,
+        )
+        .await
+        .unwrap();
+    lp.spawn();
+    tokio::time::timeout(Duration::from_secs(2), session.wait_for_connection())
+        .await
+        .unwrap();
+
+    let id = nm.inner().next_node_id();
+    nm.inner().add_node(
+        nm.address_space(),
+        tester.handle.type_tree(),
+        VariableBuilder::new(&id, "TestVar1", "TestVar1")
+            .value(-1)
+            .data_type(DataTypeId::Int32)
+            .access_level(AccessLevel::CURRENT_READ)
+            .user_access_level(AccessLevel::CURRENT_READ)
+            .build()
+            .into(),
+        &ObjectId::ObjectsFolder.into(),
+        &ReferenceTypeId::Organizes.into(),
+        Some(&VariableTypeId::BaseDataVariableType.into()),
+        Vec::new(),
+    );
+
+    let (notifs, mut data, _) = ChannelNotifications::new();
+    let sub_id = session
+        .create_subscription(Duration::from_millis(100), 100, 20, 1000, 0, true, notifs)
+        .await
+        .unwrap();
+    session
+        .create_monitored_items(
+            sub_id,
+            TimestampsToReturn::Both,
+            vec![MonitoredItemCreateRequest {
+                item_to_monitor: ReadValueId {
+                    node_id: id.clone(),
+                    attribute_id: AttributeId::Value as u32,
+                    ..Default::default()
+                },
+                monitoring_mode: opcua::types::MonitoringMode::Reporting,
+                requested_parameters: MonitoringParameters {
+                    sampling_interval: 0.0,
+                    queue_size: 10,
+                    discard_oldest: true,
+                    ..Default::default()
+                },
+            }],
+        )
+        .await
+        .unwrap();
+    let (_, v) = timeout(Duration::from_millis(500), data.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(v.value, Some(Variant::Int32(-1)));
+
+    // Transferring to the owning session without initial values sends nothing.
+    let r = TransferSubscriptions::new(&session)
+        .subscription(sub_id)
+        .send_initial_values(false)
+        .send(session.channel())
+        .await
+        .unwrap();
+    assert_eq!(r.results.unwrap()[0].status_code, StatusCode::Good);
+    session.trigger_publish_now();
+    timeout(Duration::from_millis(300), data.recv())
+        .await
+        .unwrap_err();
+
+    // With initial values, the current value is sent again.
+    let r = TransferSubscriptions::new(&session)
+        .subscription(sub_id)
+        .send_initial_values(true)
+        .send(session.channel())
+        .await
+        .unwrap();
+    assert_eq!(r.results.unwrap()[0].status_code, StatusCode::Good);
+    session.trigger_publish_now();
+    let (_, v) = timeout(Duration::from_millis(500), data.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(v.value, Some(Variant::Int32(-1)));
+}
+
+#[tokio::test]
+async fn test_data_change_filters() {
+    let (tester, nm, session) = setup().await;
+
+    let id = nm.inner().next_node_id();
+    // Add a pair of nodes
+    nm.inner().add_node(
+        nm.address_space(),
+        tester.handle.type_tree(),
+        VariableBuilder::new(&id, "TestVar1", "TestVar1")
+            .value(0.0f64)
+            .data_type(DataTypeId::Double)
+            .access_level(AccessLevel::CURRENT_READ)
+            .user_access_level(AccessLevel::CURRENT_READ)
+            .build()
+            .into(),
+        &ObjectId::ObjectsFolder.into(),
+        &ReferenceTypeId::Organizes.into(),
+        Some(&VariableTypeId::BaseDataVariableType.into()),
+        Vec::new(),
+    );
+    let id2 = nm.inner().next_node_id();
+    nm.inner().add_node(
+        nm.address_space(),
+        tester.handle.type_tree(),
+        VariableBuilder::new(&id2, "TestVar2", "TestVar2")
+            .value(6.0f64)
+            .data_type(DataTypeId::Double)
+            .access_level(AccessLevel::CURRENT_READ)
+            .user_access_level(AccessLevel::CURRENT_READ)
+            .build()
+            .into(),
+        &ObjectId::ObjectsFolder.into(),
+        &ReferenceTypeId::Organizes.into(),
+        Some(&VariableTypeId::BaseDataVariableType.into()),
+        Vec::new(),
     );
     let prop_id = nm.inner().next_node_id();
     // And an EURange property
@@ -804,16 +1462,34 @@ async fn test_data_change_filters() {
 }
 
 #[tokio::test]
-async fn test_manual_republish() {
+async fn test_data_change_filters_rejected() {
     let (tester, nm, session) = setup().await;
 
+    // A numeric node without an EURange property
     let id = nm.inner().next_node_id();
     nm.inner().add_node(
         nm.address_space(),
         tester.handle.type_tree(),
         VariableBuilder::new(&id, "TestVar1", "TestVar1")
-            .value(-1)
-            .data_type(DataTypeId::Int32)
+            .value(0.0f64)
+            .data_type(DataTypeId::Double)
+            .access_level(AccessLevel::CURRENT_READ)
+            .user_access_level(AccessLevel::CURRENT_READ)
+            .build()
+            .into(),
+        &ObjectId::ObjectsFolder.into(),
+        &ReferenceTypeId::Organizes.into(),
+        Some(&VariableTypeId::BaseDataVariableType.into()),
+        Vec::new(),
+    );
+    // A non-numeric node
+    let id2 = nm.inner().next_node_id();
+    nm.inner().add_node(
+        nm.address_space(),
+        tester.handle.type_tree(),
+        VariableBuilder::new(&id2, "TestVar2", "TestVar2")
+            .value("foo")
+            .data_type(DataTypeId::String)
             .access_level(AccessLevel::CURRENT_READ)
             .user_access_level(AccessLevel::CURRENT_READ)
             .build()
@@ -824,27 +1500,274 @@ async fn test_manual_republish() {
         Vec::new(),
     );
 
-    // Create a subscription
-    let res = CreateSubscription::new(&session)
-        .publishing_interval(Duration::from_millis(100))
-        .max_lifetime_count(100)
-        .max_keep_alive_count(20)
-        .max_notifications_per_publish(1000)
-        .priority(0)
-        .publishing_enabled(true)
-        .send(session.channel())
+    // A non-numeric node without a value, deadbands are checked against the DataType.
+    let id3 = nm.inner().next_node_id();
+    nm.inner().add_node(
+        nm.address_space(),
+        tester.handle.type_tree(),
+        VariableBuilder::new(&id3, "TestVar3", "TestVar3")
+            .data_type(DataTypeId::String)
+            .access_level(AccessLevel::CURRENT_READ)
+            .user_access_level(AccessLevel::CURRENT_READ)
+            .build()
+            .into(),
+        &ObjectId::ObjectsFolder.into(),
+        &ReferenceTypeId::Organizes.into(),
+        Some(&VariableTypeId::BaseDataVariableType.into()),
+        Vec::new(),
+    );
+
+    let (notifs, _data, _) = ChannelNotifications::new();
+    let sub_id = session
+        .create_subscription(Duration::from_millis(100), 100, 20, 1000, 0, true, notifs)
         .await
         .unwrap();
-    let sub_id = res.subscription_id;
 
-    let res = CreateMonitoredItems::new(sub_id, &session)
-        .item(MonitoredItemCreateRequest {
-            item_to_monitor: ReadValueId {
-                node_id: id.clone(),
-                attribute_id: AttributeId::Value as u32,
-                ..Default::default()
-            },
-            monitoring_mode: opcua::types::MonitoringMode::Reporting,
+    let item = |node_id: &NodeId, deadband_type: DeadbandType| MonitoredItemCreateRequest {
+        item_to_monitor: ReadValueId {
+            node_id: node_id.clone(),
+            attribute_id: AttributeId::Value as u32,
+            ..Default::default()
+        },
+        monitoring_mode: MonitoringMode::Reporting,
+        requested_parameters: MonitoringParameters {
+            sampling_interval: 0.0,
+            queue_size: 10,
+            discard_oldest: true,
+            filter: ExtensionObject::from_message(DataChangeFilter {
+                trigger: DataChangeTrigger::StatusValue,
+                deadband_type: deadband_type as u32,
+                deadband_value: 10.0,
+            }),
+            ..Default::default()
+        },
+    };
+
+    let res = session
+        .create_monitored_items(
+            sub_id,
+            TimestampsToReturn::Both,
+            vec![
+                item(&id, DeadbandType::Percent),
+                item(&id2, DeadbandType::Absolute),
+                item(&id, DeadbandType::Absolute),
+                item(&id3, DeadbandType::Absolute),
+                item(&id2, DeadbandType::None),
+            ],
+        )
+        .await
+        .unwrap();
+    assert_eq!(res.len(), 5);
+    // Percent deadband requires an EURange property.
+    assert_eq!(
+        res[0].result.status_code,
+        StatusCode::BadMonitoredItemFilterUnsupported
+    );
+    // Deadbands are only allowed on numeric values.
+    assert_eq!(res[1].result.status_code, StatusCode::BadFilterNotAllowed);
+    assert_eq!(res[2].result.status_code, StatusCode::Good);
+    assert_eq!(res[3].result.status_code, StatusCode::BadFilterNotAllowed);
+    assert_eq!(res[4].result.status_code, StatusCode::Good);
+    // Rejected items are never passed to the node manager.
+    assert_eq!(
+        nm.inner().call_info().lock().value_monitored_items,
+        vec![id.clone(), id2.clone()]
+    );
+
+    // Adding a deadband to an existing item on a non-numeric node is also rejected.
+    let res = session
+        .modify_monitored_items(
+            sub_id,
+            TimestampsToReturn::Both,
+            &[MonitoredItemModifyRequest {
+                monitored_item_id: res[4].result.monitored_item_id,
+                requested_parameters: item(&id2, DeadbandType::Absolute).requested_parameters,
+            }],
+        )
+        .await
+        .unwrap();
+    assert_eq!(res[0].status_code, StatusCode::BadFilterNotAllowed);
+}
+
+#[tokio::test]
+async fn test_data_change_triggers() {
+    let (tester, nm, session) = setup().await;
+
+    let t0 = opcua::types::DateTime::now();
+    let at = |secs: i64| t0 + chrono::Duration::seconds(secs);
+
+    let triggers = [
+        DataChangeTrigger::Status,
+        DataChangeTrigger::StatusValue,
+        DataChangeTrigger::StatusValueTimestamp,
+    ];
+    let mut ids = Vec::new();
+    for trigger in triggers {
+        let id = nm.inner().next_node_id();
+        nm.inner().add_node(
+            nm.address_space(),
+            tester.handle.type_tree(),
+            VariableBuilder::new(&id, format!("{trigger:?}"), format!("{trigger:?}"))
+                .value(0.0f64)
+                .data_type(DataTypeId::Double)
+                .access_level(AccessLevel::CURRENT_READ)
+                .user_access_level(AccessLevel::CURRENT_READ)
+                .build()
+                .into(),
+            &ObjectId::ObjectsFolder.into(),
+            &ReferenceTypeId::Organizes.into(),
+            Some(&VariableTypeId::BaseDataVariableType.into()),
+            Vec::new(),
+        );
+        nm.set_value(
+            tester.handle.subscriptions(),
+            &id,
+            None,
+            DataValue::new_at(0.0, t0),
+        )
+        .unwrap();
+        ids.push(id);
+    }
+
+    let (notifs, mut data, _) = ChannelNotifications::new();
+    let sub_id = session
+        .create_subscription(Duration::from_millis(100), 100, 20, 1000, 0, true, notifs)
+        .await
+        .unwrap();
+
+    let res = session
+        .create_monitored_items(
+            sub_id,
+            TimestampsToReturn::Both,
+            ids.iter()
+                .zip(triggers)
+                .map(|(id, trigger)| MonitoredItemCreateRequest {
+                    item_to_monitor: ReadValueId {
+                        node_id: id.clone(),
+                        attribute_id: AttributeId::Value as u32,
+                        ..Default::default()
+                    },
+                    monitoring_mode: MonitoringMode::Reporting,
+                    requested_parameters: MonitoringParameters {
+                        sampling_interval: 0.0,
+                        queue_size: 10,
+                        discard_oldest: true,
+                        filter: ExtensionObject::from_message(DataChangeFilter {
+                            trigger,
+                            deadband_type: DeadbandType::None as u32,
+                            deadband_value: 0.0,
+                        }),
+                        ..Default::default()
+                    },
+                })
+                .collect(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(res.len(), 3);
+    for r in &res {
+        assert_eq!(r.result.status_code, StatusCode::Good);
+    }
+
+    // Collect the nodes that report a change until the notifications stop.
+    async fn changed_nodes(
+        data: &mut UnboundedReceiver<(ReadValueId, DataValue)>,
+    ) -> HashSet<NodeId> {
+        let mut changed = HashSet::new();
+        while let Ok(Some((r, _))) = timeout(Duration::from_millis(500), data.recv()).await {
+            changed.insert(r.node_id);
+        }
+        changed
+    }
+    let update = |value: DataValue| {
+        for id in &ids {
+            nm.set_value(tester.handle.subscriptions(), id, None, value.clone())
+                .unwrap();
+        }
+    };
+
+    // All items report the initial value.
+    assert_eq!(changed_nodes(&mut data).await.len(), 3);
+
+    // A change in value is ignored by the status-only trigger.
+    update(DataValue::new_at(1.0, at(1)));
+    assert_eq!(
+        changed_nodes(&mut data).await,
+        HashSet::from_iter(ids[1..].iter().cloned())
+    );
+
+    // A change in source timestamp is only reported by `StatusValueTimestamp`.
+    update(DataValue::new_at(1.0, at(2)));
+    assert_eq!(
+        changed_nodes(&mut data).await,
+        HashSet::from_iter(ids[2..].iter().cloned())
+    );
+
+    // A change in status is reported by every trigger.
+    update(DataValue::new_at_status(
+        1.0,
+        at(3),
+        StatusCode::UncertainLastUsableValue,
+    ));
+    assert_eq!(
+        changed_nodes(&mut data).await,
+        HashSet::from_iter(ids.iter().cloned())
+    );
+
+    // Once the status is stable, value changes are again ignored by the status-only trigger.
+    update(DataValue::new_at_status(
+        2.0,
+        at(4),
+        StatusCode::UncertainLastUsableValue,
+    ));
+    assert_eq!(
+        changed_nodes(&mut data).await,
+        HashSet::from_iter(ids[1..].iter().cloned())
+    );
+}
+
+#[tokio::test]
+async fn test_manual_republish() {
+    let (tester, nm, session) = setup().await;
+
+    let id = nm.inner().next_node_id();
+    nm.inner().add_node(
+        nm.address_space(),
+        tester.handle.type_tree(),
+        VariableBuilder::new(&id, "TestVar1", "TestVar1")
+            .value(-1)
+            .data_type(DataTypeId::Int32)
+            .access_level(AccessLevel::CURRENT_READ)
+            .user_access_level(AccessLevel::CURRENT_READ)
+            .build()
+            .into(),
+        &ObjectId::ObjectsFolder.into(),
+        &ReferenceTypeId::Organizes.into(),
+        Some(&VariableTypeId::BaseDataVariableType.into()),
+        Vec::new(),
+    );
+
+    // Create a subscription
+    let res = CreateSubscription::new(&session)
+        .publishing_interval(Duration::from_millis(100))
+        .max_lifetime_count(100)
+        .max_keep_alive_count(20)
+        .max_notifications_per_publish(1000)
+        .priority(0)
+        .publishing_enabled(true)
+        .send(session.channel())
+        .await
+        .unwrap();
+    let sub_id = res.subscription_id;
+
+    let res = CreateMonitoredItems::new(sub_id, &session)
+        .item(MonitoredItemCreateRequest {
+            item_to_monitor: ReadValueId {
+                node_id: id.clone(),
+                attribute_id: AttributeId::Value as u32,
+                ..Default::default()
+            },
+            monitoring_mode: opcua::types::MonitoringMode::Reporting,
             requested_parameters: MonitoringParameters {
                 sampling_interval: 0.0,
                 queue_size: 10,
@@ -1008,4 +1931,1537 @@ async fn test_event_subscriptions() {
     );
 }
 
-// TODO: Add more detailed high level tests on subscriptions.
+#[tokio::test]
+async fn test_event_subscriptions_on_notifier() {
+    let (tester, nm, session) = setup().await;
+
+    // An area with a device raising events, and an unrelated area.
+    let area = nm.inner().next_node_id();
+    nm.inner().add_node(
+        nm.address_space(),
+        tester.handle.type_tree(),
+        ObjectBuilder::new(&area, "Area", "Area")
+            .event_notifier(EventNotifier::SUBSCRIBE_TO_EVENTS)
+            .build()
+            .into(),
+        &ObjectId::ObjectsFolder.into(),
+        &ReferenceTypeId::Organizes.into(),
+        Some(&ObjectTypeId::FolderType.into()),
+        Vec::new(),
+    );
+    let device = nm.inner().next_node_id();
+    nm.inner().add_node(
+        nm.address_space(),
+        tester.handle.type_tree(),
+        ObjectBuilder::new(&device, "Device", "Device")
+            .event_notifier(EventNotifier::SUBSCRIBE_TO_EVENTS)
+            .build()
+            .into(),
+        &area,
+        &ReferenceTypeId::HasNotifier.into(),
+        Some(&ObjectTypeId::BaseObjectType.into()),
+        Vec::new(),
+    );
+    let other = nm.inner().next_node_id();
+    nm.inner().add_node(
+        nm.address_space(),
+        tester.handle.type_tree(),
+        ObjectBuilder::new(&other, "OtherArea", "OtherArea")
+            .event_notifier(EventNotifier::SUBSCRIBE_TO_EVENTS)
+            .build()
+            .into(),
+        &ObjectId::ObjectsFolder.into(),
+        &ReferenceTypeId::Organizes.into(),
+        Some(&ObjectTypeId::FolderType.into()),
+        Vec::new(),
+    );
+
+    let (notifs, _, mut events) = ChannelNotifications::new();
+    let sub_id = session
+        .create_subscription(Duration::from_millis(100), 100, 20, 1000, 0, true, notifs)
+        .await
+        .unwrap();
+
+    let item = |node_id: &NodeId| MonitoredItemCreateRequest {
+        item_to_monitor: ReadValueId {
+            node_id: node_id.clone(),
+            attribute_id: AttributeId::EventNotifier as u32,
+            ..Default::default()
+        },
+        monitoring_mode: MonitoringMode::Reporting,
+        requested_parameters: MonitoringParameters {
+            sampling_interval: 0.0,
+            queue_size: 10,
+            discard_oldest: true,
+            filter: ExtensionObject::new(EventFilter {
+                select_clauses: Some(vec![SimpleAttributeOperand::new_value(
+                    ObjectTypeId::BaseEventType,
+                    "Message",
+                )]),
+                where_clause: Default::default(),
+            }),
+            ..Default::default()
+        },
+    };
+    let res = session
+        .create_monitored_items(
+            sub_id,
+            TimestampsToReturn::Both,
+            vec![item(&area), item(&other)],
+        )
+        .await
+        .unwrap();
+    assert_eq!(res[0].result.status_code, StatusCode::Good);
+    assert_eq!(res[1].result.status_code, StatusCode::Good);
+
+    let mut evt = ProgressEventType::new_event_now(
+        ProgressEventType::event_type_id(),
+        random::byte_string(6),
+        "Device event",
+        tester.handle.type_tree().read().namespaces(),
+    );
+    evt.base.source_node = device.clone();
+    tester.handle.notify_events_from(&device, &evt);
+
+    // The event is reported on the area above the device, but not the unrelated area.
+    let (r, v) = timeout(Duration::from_millis(500), events.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(r.node_id, area);
+    assert_eq!(v.unwrap(), vec![Variant::from(evt.base.message.clone())]);
+    assert!(timeout(Duration::from_millis(300), events.recv())
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn test_event_propagation_through_hierarchy() {
+    let (tester, nm, session) = setup().await;
+
+    // Area -HasNotifier-> Device -HasEventSource-> Sensor
+    let area = nm.inner().next_node_id();
+    nm.inner().add_node(
+        nm.address_space(),
+        tester.handle.type_tree(),
+        ObjectBuilder::new(&area, "Area", "Area")
+            .event_notifier(EventNotifier::SUBSCRIBE_TO_EVENTS)
+            .build()
+            .into(),
+        &ObjectId::ObjectsFolder.into(),
+        &ReferenceTypeId::Organizes.into(),
+        Some(&ObjectTypeId::FolderType.into()),
+        Vec::new(),
+    );
+    let device = nm.inner().next_node_id();
+    nm.inner().add_node(
+        nm.address_space(),
+        tester.handle.type_tree(),
+        ObjectBuilder::new(&device, "Device", "Device")
+            .event_notifier(EventNotifier::SUBSCRIBE_TO_EVENTS)
+            .build()
+            .into(),
+        &area,
+        &ReferenceTypeId::HasNotifier.into(),
+        Some(&ObjectTypeId::BaseObjectType.into()),
+        Vec::new(),
+    );
+    let sensor = nm.inner().next_node_id();
+    nm.inner().add_node(
+        nm.address_space(),
+        tester.handle.type_tree(),
+        ObjectBuilder::new(&sensor, "Sensor", "Sensor")
+            .build()
+            .into(),
+        &device,
+        &ReferenceTypeId::HasEventSource.into(),
+        Some(&ObjectTypeId::BaseObjectType.into()),
+        Vec::new(),
+    );
+
+    let (notifs, _, mut events) = ChannelNotifications::new();
+    let sub_id = session
+        .create_subscription(Duration::from_millis(100), 100, 20, 1000, 0, true, notifs)
+        .await
+        .unwrap();
+    let res = session
+        .create_monitored_items(
+            sub_id,
+            TimestampsToReturn::Both,
+            vec![MonitoredItemCreateRequest {
+                item_to_monitor: ReadValueId {
+                    node_id: area.clone(),
+                    attribute_id: AttributeId::EventNotifier as u32,
+                    ..Default::default()
+                },
+                monitoring_mode: MonitoringMode::Reporting,
+                requested_parameters: MonitoringParameters {
+                    sampling_interval: 0.0,
+                    queue_size: 10,
+                    discard_oldest: true,
+                    filter: ExtensionObject::new(EventFilter {
+                        select_clauses: Some(vec![SimpleAttributeOperand::new_value(
+                            ObjectTypeId::BaseEventType,
+                            "SourceNode",
+                        )]),
+                        where_clause: Default::default(),
+                    }),
+                    ..Default::default()
+                },
+            }],
+        )
+        .await
+        .unwrap();
+    assert_eq!(res[0].result.status_code, StatusCode::Good);
+
+    let mut evt = ProgressEventType::new_event_now(
+        ProgressEventType::event_type_id(),
+        random::byte_string(6),
+        "Sensor event",
+        tester.handle.type_tree().read().namespaces(),
+    );
+    evt.base.source_node = sensor.clone();
+    tester.handle.notify_events_from(&sensor, &evt);
+
+    let (r, v) = timeout(Duration::from_millis(500), events.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(r.node_id, area);
+    assert_eq!(v.unwrap(), vec![Variant::from(sensor)]);
+}
+
+// TODO: Add more detailed high level tests on subscriptions.
+
+#[tokio::test]
+async fn node_manager_monitored_item_limit() {
+    let server = default_server().with_node_manager_max_monitored_items(test_node_manager(), 3);
+    let mut tester = Tester::new(server, false).await;
+    let nm = tester
+        .handle
+        .node_managers()
+        .get_of_type::<TestNodeManager>()
+        .unwrap();
+    let (session, lp) = tester.connect_default().await.unwrap();
+    lp.spawn();
+    tokio::time::timeout(Duration::from_secs(2), session.wait_for_connection())
+        .await
+        .unwrap();
+
+    let ids: Vec<_> = (0..5)
+        .map(|i| {
+            let id = nm.inner().next_node_id();
+            nm.inner().add_node(
+                nm.address_space(),
+                tester.handle.type_tree(),
+                VariableBuilder::new(&id, format!("TestVar{i}"), format!("TestVar{i}"))
+                    .value(i)
+                    .data_type(DataTypeId::Int32)
+                    .access_level(AccessLevel::CURRENT_READ)
+                    .user_access_level(AccessLevel::CURRENT_READ)
+                    .build()
+                    .into(),
+                &ObjectId::ObjectsFolder.into(),
+                &ReferenceTypeId::Organizes.into(),
+                Some(&VariableTypeId::BaseDataVariableType.into()),
+                Vec::new(),
+            );
+            id
+        })
+        .collect();
+
+    let item = |id: &NodeId| MonitoredItemCreateRequest {
+        item_to_monitor: ReadValueId {
+            node_id: id.clone(),
+            attribute_id: AttributeId::Value as u32,
+            ..Default::default()
+        },
+        monitoring_mode: MonitoringMode::Reporting,
+        requested_parameters: MonitoringParameters {
+            sampling_interval: 100.0,
+            queue_size: 10,
+            discard_oldest: true,
+            ..Default::default()
+        },
+    };
+
+    let (notifs, _data, _) = ChannelNotifications::new();
+    let sub_id = session
+        .create_subscription(Duration::from_secs(1), 100, 20, 1000, 0, true, notifs)
+        .await
+        .unwrap();
+
+    // Create more items than the node manager allows, plus one item in the
+    // core namespace, which is not limited.
+    let mut to_create: Vec<_> = ids.iter().map(item).collect();
+    to_create.push(item(&VariableId::Server_ServerStatus_CurrentTime.into()));
+    let res = session
+        .create_monitored_items(sub_id, TimestampsToReturn::Both, to_create)
+        .await
+        .unwrap();
+    assert_eq!(res.len(), 6);
+    for r in &res[..3] {
+        assert_eq!(r.result.status_code, StatusCode::Good);
+    }
+    for r in &res[3..5] {
+        assert_eq!(r.result.status_code, StatusCode::BadTooManyMonitoredItems);
+    }
+    assert_eq!(res[5].result.status_code, StatusCode::Good);
+    let first_item_id = res[0].result.monitored_item_id;
+
+    // The limit is visible through the diagnostics node manager.
+    let diagnostics = tester
+        .handle
+        .node_managers()
+        .get_of_type::<DiagnosticsNodeManager>()
+        .unwrap();
+    let limits = diagnostics.monitored_item_limits();
+    assert_eq!(limits.len(), 1);
+    assert_eq!(limits[0].node_manager, nm.name());
+    assert_eq!(limits[0].current, 3);
+    assert_eq!(limits[0].max, 3);
+
+    // The limit applies across subscriptions.
+    let (notifs, _data, _) = ChannelNotifications::new();
+    let sub_id_2 = session
+        .create_subscription(Duration::from_secs(1), 100, 20, 1000, 0, true, notifs)
+        .await
+        .unwrap();
+    let res = session
+        .create_monitored_items(sub_id_2, TimestampsToReturn::Both, vec![item(&ids[3])])
+        .await
+        .unwrap();
+    assert_eq!(
+        res[0].result.status_code,
+        StatusCode::BadTooManyMonitoredItems
+    );
+
+    // Deleting an item frees up capacity.
+    session
+        .delete_monitored_items(sub_id, &[first_item_id])
+        .await
+        .unwrap();
+    let res = session
+        .create_monitored_items(sub_id_2, TimestampsToReturn::Both, vec![item(&ids[3])])
+        .await
+        .unwrap();
+    assert_eq!(res[0].result.status_code, StatusCode::Good);
+    assert_eq!(diagnostics.monitored_item_limits()[0].current, 3);
+}
+
+#[tokio::test]
+async fn server_monitored_item_limit() {
+    let server = test_server().max_total_monitored_items(3);
+    let mut tester = Tester::new(server, false).await;
+
+    let item = |id: VariableId| MonitoredItemCreateRequest {
+        item_to_monitor: ReadValueId {
+            node_id: id.into(),
+            attribute_id: AttributeId::Value as u32,
+            ..Default::default()
+        },
+        monitoring_mode: MonitoringMode::Reporting,
+        requested_parameters: MonitoringParameters {
+            sampling_interval: 100.0,
+            queue_size: 10,
+            discard_oldest: true,
+            ..Default::default()
+        },
+    };
+
+    let mut sessions = Vec::new();
+    for _ in 0..2 {
+        let (session, lp) = tester.connect_default().await.unwrap();
+        lp.spawn();
+        tokio::time::timeout(Duration::from_secs(2), session.wait_for_connection())
+            .await
+            .unwrap();
+        let (notifs, _data, _) = ChannelNotifications::new();
+        let sub_id = session
+            .create_subscription(Duration::from_secs(1), 100, 20, 1000, 0, true, notifs)
+            .await
+            .unwrap();
+        sessions.push((session, sub_id));
+    }
+
+    // Each session stays well below the per-subscription limit,
+    // but together they exceed the server-wide limit.
+    let (session, sub_id) = &sessions[0];
+    let res = session
+        .create_monitored_items(
+            *sub_id,
+            TimestampsToReturn::Both,
+            vec![
+                item(VariableId::Server_ServerStatus_CurrentTime),
+                item(VariableId::Server_ServerStatus_State),
+            ],
+        )
+        .await
+        .unwrap();
+    assert!(res.iter().all(|r| r.result.status_code == StatusCode::Good));
+    let first_item_id = res[0].result.monitored_item_id;
+
+    let (session_2, sub_id_2) = &sessions[1];
+    let res = session_2
+        .create_monitored_items(
+            *sub_id_2,
+            TimestampsToReturn::Both,
+            vec![
+                item(VariableId::Server_ServerStatus_CurrentTime),
+                item(VariableId::Server_ServerStatus_State),
+            ],
+        )
+        .await
+        .unwrap();
+    assert_eq!(res[0].result.status_code, StatusCode::Good);
+    assert_eq!(
+        res[1].result.status_code,
+        StatusCode::BadTooManyMonitoredItems
+    );
+
+    // Deleting an item in one session frees up capacity for the other.
+    session
+        .delete_monitored_items(*sub_id, &[first_item_id])
+        .await
+        .unwrap();
+    let res = session_2
+        .create_monitored_items(
+            *sub_id_2,
+            TimestampsToReturn::Both,
+            vec![item(VariableId::Server_ServerStatus_State)],
+        )
+        .await
+        .unwrap();
+    assert_eq!(res[0].result.status_code, StatusCode::Good);
+}
+
+#[tokio::test]
+async fn server_monitored_item_limit_concurrent() {
+    let server = test_server().max_total_monitored_items(10);
+    let mut tester = Tester::new(server, false).await;
+
+    let item = |id: VariableId| MonitoredItemCreateRequest {
+        item_to_monitor: ReadValueId {
+            node_id: id.into(),
+            attribute_id: AttributeId::Value as u32,
+            ..Default::default()
+        },
+        monitoring_mode: MonitoringMode::Reporting,
+        requested_parameters: MonitoringParameters {
+            sampling_interval: 100.0,
+            queue_size: 10,
+            discard_oldest: true,
+            ..Default::default()
+        },
+    };
+
+    let mut sessions = Vec::new();
+    for _ in 0..4 {
+        let (session, lp) = tester.connect_default().await.unwrap();
+        lp.spawn();
+        tokio::time::timeout(Duration::from_secs(2), session.wait_for_connection())
+            .await
+            .unwrap();
+        let (notifs, _data, _) = ChannelNotifications::new();
+        let sub_id = session
+            .create_subscription(Duration::from_secs(1), 100, 20, 1000, 0, true, notifs)
+            .await
+            .unwrap();
+        sessions.push((session, sub_id));
+    }
+
+    // Requests from all sessions at once must not exceed the server-wide limit together.
+    let results = futures::future::join_all(sessions.iter().map(|(session, sub_id)| {
+        session.create_monitored_items(
+            *sub_id,
+            TimestampsToReturn::Both,
+            (0..5)
+                .map(|_| item(VariableId::Server_ServerStatus_CurrentTime))
+                .collect(),
+        )
+    }))
+    .await;
+    let statuses: Vec<_> = results
+        .into_iter()
+        .flat_map(|r| r.unwrap())
+        .map(|r| r.result.status_code)
+        .collect();
+    assert_eq!(statuses.len(), 20);
+    assert_eq!(
+        statuses.iter().filter(|s| **s == StatusCode::Good).count(),
+        10
+    );
+    assert!(statuses
+        .iter()
+        .all(|s| *s == StatusCode::Good || *s == StatusCode::BadTooManyMonitoredItems));
+}
+
+#[tokio::test]
+async fn subscription_stream() {
+    let (tester, nm, session) = setup().await;
+
+    let id = nm.inner().next_node_id();
+    nm.inner().add_node(
+        nm.address_space(),
+        tester.handle.type_tree(),
+        VariableBuilder::new(&id, "TestVar1", "TestVar1")
+            .value(-1)
+            .data_type(DataTypeId::Int32)
+            .access_level(AccessLevel::CURRENT_READ)
+            .user_access_level(AccessLevel::CURRENT_READ)
+            .build()
+            .into(),
+        &ObjectId::ObjectsFolder.into(),
+        &ReferenceTypeId::Organizes.into(),
+        Some(&VariableTypeId::BaseDataVariableType.into()),
+        Vec::new(),
+    );
+
+    let (sub_id, mut stream) = session
+        .subscribe_stream(
+            Duration::from_millis(100),
+            100,
+            20,
+            1000,
+            0,
+            true,
+            StreamBackpressure::DropOldest(10),
+        )
+        .await
+        .unwrap();
+
+    session
+        .create_monitored_items(
+            sub_id,
+            TimestampsToReturn::Both,
+            vec![MonitoredItemCreateRequest {
+                item_to_monitor: ReadValueId {
+                    node_id: id.clone(),
+                    attribute_id: AttributeId::Value as u32,
+                    ..Default::default()
+                },
+                monitoring_mode: MonitoringMode::Reporting,
+                requested_parameters: MonitoringParameters {
+                    client_handle: 5,
+                    sampling_interval: 0.0,
+                    queue_size: 10,
+                    discard_oldest: true,
+                    ..Default::default()
+                },
+            }],
+        )
+        .await
+        .unwrap();
+
+    async fn next_value(stream: &mut SubscriptionStream, id: &NodeId) -> i32 {
+        let n = timeout(Duration::from_millis(500), stream.next())
+            .await
+            .unwrap()
+            .unwrap();
+        let SubscriptionNotification::DataChange {
+            client_handle,
+            node_id,
+            value,
+            ..
+        } = n
+        else {
+            panic!("Expected data change, got {n:?}");
+        };
+        assert_eq!(client_handle, 5);
+        assert_eq!(&node_id, id);
+        match value.value {
+            Some(Variant::Int32(v)) => v,
+            _ => panic!("Expected integer value"),
+        }
+    }
+
+    assert_eq!(next_value(&mut stream, &id).await, -1);
+
+    nm.set_value(
+        tester.handle.subscriptions(),
+        &id,
+        None,
+        DataValue::new_now(1),
+    )
+    .unwrap();
+    assert_eq!(next_value(&mut stream, &id).await, 1);
+
+    // Deleting the subscription ends the stream.
+    session.delete_subscription(sub_id).await.unwrap();
+    let end = timeout(Duration::from_millis(500), stream.next())
+        .await
+        .unwrap();
+    assert!(end.is_none());
+}
+
+/// Subscribe to `AuditActivateSessionEventType` events on the `Server` object.
+async fn subscribe_activate_session_audit_events(
+    session: &Session,
+) -> UnboundedReceiver<(ReadValueId, Option<Vec<Variant>>)> {
+    let (notifs, _, events) = ChannelNotifications::new();
+    let sub_id = session
+        .create_subscription(Duration::from_millis(100), 100, 20, 1000, 0, true, notifs)
+        .await
+        .unwrap();
+
+    session
+        .create_monitored_items(
+            sub_id,
+            TimestampsToReturn::Both,
+            vec![MonitoredItemCreateRequest {
+                item_to_monitor: ReadValueId {
+                    node_id: ObjectId::Server.into(),
+                    attribute_id: AttributeId::EventNotifier as u32,
+                    ..Default::default()
+                },
+                monitoring_mode: MonitoringMode::Reporting,
+                requested_parameters: MonitoringParameters {
+                    sampling_interval: 0.0,
+                    queue_size: 10,
+                    discard_oldest: true,
+                    filter: ExtensionObject::new(EventFilter {
+                        select_clauses: Some(vec![
+                            SimpleAttributeOperand::new_value(
+                                ObjectTypeId::BaseEventType,
+                                "EventType",
+                            ),
+                            SimpleAttributeOperand::new_value(
+                                ObjectTypeId::AuditEventType,
+                                "Status",
+                            ),
+                            SimpleAttributeOperand::new_value(
+                                ObjectTypeId::AuditEventType,
+                                "ClientUserId",
+                            ),
+                            SimpleAttributeOperand::new_value(
+                                ObjectTypeId::AuditSecurityEventType,
+                                "StatusCodeId",
+                            ),
+                        ]),
+                        where_clause: ContentFilterBuilder::new()
+                            .of_type(LiteralOperand::from(
+                                ObjectTypeId::AuditActivateSessionEventType,
+                            ))
+                            .build(),
+                    }),
+                    ..Default::default()
+                },
+            }],
+        )
+        .await
+        .unwrap();
+    events
+}
+
+#[tokio::test]
+async fn audit_event_failed_activation() {
+    let server = default_server().audit_events(true);
+    let mut tester = Tester::new(server, true).await;
+    // Anonymous users are not allowed to read diagnostics.
+    let (anonymous, lp) = tester.connect_default().await.unwrap();
+    lp.spawn();
+    tokio::time::timeout(Duration::from_secs(2), anonymous.wait_for_connection())
+        .await
+        .unwrap();
+    let mut anonymous_events = subscribe_activate_session_audit_events(&anonymous).await;
+
+    // Audit events are only reported to users allowed to read diagnostics.
+    let (session, lp) = tester
+        .connect(
+            SecurityPolicy::Aes256Sha256RsaPss,
+            MessageSecurityMode::SignAndEncrypt,
+            client_user_token(),
+        )
+        .await
+        .unwrap();
+    lp.spawn();
+    tokio::time::timeout(Duration::from_secs(2), session.wait_for_connection())
+        .await
+        .unwrap();
+    let mut events = subscribe_activate_session_audit_events(&session).await;
+
+    // Try to connect with an invalid password.
+    let (_, handle) = tester
+        .connect(
+            SecurityPolicy::Basic128Rsa15,
+            MessageSecurityMode::SignAndEncrypt,
+            IdentityToken::UserName(CLIENT_USERPASS_ID.to_owned(), "invalid".into()),
+        )
+        .await
+        .unwrap();
+    let res = handle.spawn().await.unwrap();
+    assert_eq!(res, StatusCode::BadIdentityTokenRejected);
+
+    let (r, v) = timeout(Duration::from_millis(1000), events.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(r.node_id, NodeId::from(ObjectId::Server));
+    let evt = v.unwrap();
+    assert_eq!(4, evt.len());
+    assert_eq!(
+        Variant::from(NodeId::from(ObjectTypeId::AuditActivateSessionEventType)),
+        evt[0]
+    );
+    assert_eq!(Variant::from(false), evt[1]);
+    assert_eq!(Variant::from(CLIENT_USERPASS_ID), evt[2]);
+    let Variant::StatusCode(status) = evt[3] else {
+        panic!("Expected status code, got {:?}", evt[3]);
+    };
+    assert!(status.is_bad());
+
+    // The anonymous session receives no audit events.
+    assert!(timeout(Duration::from_millis(500), anonymous_events.recv())
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn publishing_interval_resolution() {
+    let mut server = default_server().diagnostics_enabled(true);
+    server
+        .limits_mut()
+        .subscriptions
+        .publishing_interval_resolution_ms = 500.0;
+    let mut tester = Tester::new(server, false).await;
+    let (session, lp) = tester
+        .connect(
+            SecurityPolicy::Aes256Sha256RsaPss,
+            MessageSecurityMode::SignAndEncrypt,
+            client_user_token(),
+        )
+        .await
+        .unwrap();
+    lp.spawn();
+    tokio::time::timeout(Duration::from_secs(2), session.wait_for_connection())
+        .await
+        .unwrap();
+
+    // Requested intervals are rounded to the nearest multiple of the resolution.
+    for (requested, revised) in [(480, 500.0), (520, 500.0), (1100, 1000.0), (10, 500.0)] {
+        let res = CreateSubscription::new(&session)
+            .publishing_interval(Duration::from_millis(requested))
+            .max_lifetime_count(100)
+            .max_keep_alive_count(20)
+            .publishing_enabled(true)
+            .send(session.channel())
+            .await
+            .unwrap();
+        assert_eq!(res.revised_publishing_interval, revised);
+    }
+
+    // The diagnostics count the distinct publishing intervals in use.
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    let r = session
+        .read(
+            &[ReadValueId::new_value(
+                VariableId::Server_ServerDiagnostics_ServerDiagnosticsSummary_PublishingIntervalCount
+                    .into(),
+            )],
+            TimestampsToReturn::Both,
+            0.0,
+        )
+        .await
+        .unwrap();
+    assert_eq!(r[0].value, Some(Variant::UInt32(2)));
+}
+
+#[tokio::test]
+async fn monitored_item_count_diagnostics() {
+    let server = default_server().diagnostics_enabled(true);
+    let mut tester = Tester::new(server, false).await;
+    let (session, lp) = tester
+        .connect(
+            SecurityPolicy::Aes256Sha256RsaPss,
+            MessageSecurityMode::SignAndEncrypt,
+            client_user_token(),
+        )
+        .await
+        .unwrap();
+    lp.spawn();
+    tokio::time::timeout(Duration::from_secs(2), session.wait_for_connection())
+        .await
+        .unwrap();
+
+    // The count is exposed as a component of the ServerDiagnostics object.
+    let r = session
+        .browse(
+            &[BrowseDescription {
+                node_id: ObjectId::Server_ServerDiagnostics.into(),
+                browse_direction: BrowseDirection::Forward,
+                reference_type_id: ReferenceTypeId::HasComponent.into(),
+                include_subtypes: true,
+                node_class_mask: 0,
+                result_mask: BrowseResultMask::All as u32,
+            }],
+            1000,
+            None,
+        )
+        .await
+        .unwrap();
+    let count_id = r[0]
+        .references
+        .iter()
+        .flatten()
+        .find(|r| r.browse_name.name.as_ref() == "CurrentMonitoredItemCount")
+        .unwrap()
+        .node_id
+        .node_id
+        .clone();
+    async fn read_count(session: &opcua_client::Session, id: &NodeId) -> Option<Variant> {
+        let r = session
+            .read(
+                &[ReadValueId::new_value(id.clone())],
+                TimestampsToReturn::Both,
+                0.0,
+            )
+            .await
+            .unwrap();
+        r[0].value.clone()
+    }
+    assert_eq!(
+        read_count(&session, &count_id).await,
+        Some(Variant::UInt32(0))
+    );
+
+    let (notifs, _data, _) = ChannelNotifications::new();
+    let sub_id = session
+        .create_subscription(Duration::from_millis(100), 100, 20, 1000, 0, true, notifs)
+        .await
+        .unwrap();
+    let res = session
+        .create_monitored_items(
+            sub_id,
+            TimestampsToReturn::Both,
+            [
+                VariableId::Server_ServerStatus_CurrentTime.into(),
+                VariableId::Server_ServerStatus_State.into(),
+                count_id.clone(),
+            ]
+            .into_iter()
+            .map(|id: NodeId| MonitoredItemCreateRequest {
+                item_to_monitor: ReadValueId::new_value(id),
+                monitoring_mode: MonitoringMode::Reporting,
+                requested_parameters: MonitoringParameters {
+                    sampling_interval: 100.0,
+                    queue_size: 10,
+                    discard_oldest: true,
+                    ..Default::default()
+                },
+            })
+            .collect(),
+        )
+        .await
+        .unwrap();
+    for r in &res {
+        assert_eq!(r.result.status_code, StatusCode::Good);
+    }
+
+    let summary = tester.handle.info().summary();
+    assert_eq!(summary.current_monitored_item_count(), 3);
+    assert_eq!(
+        read_count(&session, &count_id).await,
+        Some(Variant::UInt32(3))
+    );
+
+    session
+        .delete_monitored_items(sub_id, &[res[0].result.monitored_item_id])
+        .await
+        .unwrap();
+    assert_eq!(summary.current_monitored_item_count(), 2);
+
+    // Deleting the subscription removes the remaining items.
+    session.delete_subscription(sub_id).await.unwrap();
+    assert_eq!(summary.current_monitored_item_count(), 0);
+    assert_eq!(
+        read_count(&session, &count_id).await,
+        Some(Variant::UInt32(0))
+    );
+}
+
+#[tokio::test]
+async fn set_triggering() {
+    let (tester, nm, session) = setup().await;
+
+    let mut ids = Vec::new();
+    for name in ["Trigger", "Triggered1", "Triggered2"] {
+        let id = nm.inner().next_node_id();
+        nm.inner().add_node(
+            nm.address_space(),
+            tester.handle.type_tree(),
+            VariableBuilder::new(&id, name, name)
+                .value(0)
+                .data_type(DataTypeId::Int32)
+                .access_level(AccessLevel::CURRENT_READ)
+                .user_access_level(AccessLevel::CURRENT_READ)
+                .build()
+                .into(),
+            &ObjectId::ObjectsFolder.into(),
+            &ReferenceTypeId::Organizes.into(),
+            Some(&VariableTypeId::BaseDataVariableType.into()),
+            Vec::new(),
+        );
+        ids.push(id);
+    }
+
+    let (notifs, mut data, _) = ChannelNotifications::new();
+    let sub_id = session
+        .create_subscription(Duration::from_millis(100), 100, 20, 1000, 0, true, notifs)
+        .await
+        .unwrap();
+
+    // One reporting item, and two sampling items with a queue size of one.
+    let res = session
+        .create_monitored_items(
+            sub_id,
+            TimestampsToReturn::Both,
+            ids.iter()
+                .enumerate()
+                .map(|(idx, id)| MonitoredItemCreateRequest {
+                    item_to_monitor: ReadValueId {
+                        node_id: id.clone(),
+                        attribute_id: AttributeId::Value as u32,
+                        ..Default::default()
+                    },
+                    monitoring_mode: if idx == 0 {
+                        MonitoringMode::Reporting
+                    } else {
+                        MonitoringMode::Sampling
+                    },
+                    requested_parameters: MonitoringParameters {
+                        sampling_interval: 0.0,
+                        queue_size: 1,
+                        discard_oldest: true,
+                        ..Default::default()
+                    },
+                })
+                .collect(),
+        )
+        .await
+        .unwrap();
+    let item_ids: Vec<_> = res.iter().map(|r| r.result.monitored_item_id).collect();
+
+    // Only the reporting item sends its initial value.
+    let (r, _) = timeout(Duration::from_millis(500), data.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(r.node_id, ids[0]);
+
+    // An item cannot trigger itself, and links must be to items in the same subscription.
+    let (add_res, remove_res) = session
+        .set_triggering(
+            sub_id,
+            item_ids[0],
+            &[item_ids[1], item_ids[0], item_ids[2], 12345],
+            &[item_ids[2], 12345],
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        add_res.unwrap(),
+        vec![
+            StatusCode::Good,
+            StatusCode::BadMonitoredItemIdInvalid,
+            StatusCode::Good,
+            StatusCode::BadMonitoredItemIdInvalid
+        ]
+    );
+    // Links that do not exist yet cannot be removed.
+    assert_eq!(
+        remove_res.unwrap(),
+        vec![
+            StatusCode::BadMonitoredItemIdInvalid,
+            StatusCode::BadMonitoredItemIdInvalid
+        ]
+    );
+    assert_eq!(
+        session
+            .set_triggering(sub_id, item_ids[0], &[], &[])
+            .await
+            .unwrap_err(),
+        StatusCode::BadNothingToDo
+    );
+
+    // Changes to the sampling items are not reported on their own.
+    for (id, value) in ids[1..].iter().zip([1, 2]) {
+        nm.set_value(
+            tester.handle.subscriptions(),
+            id,
+            None,
+            DataValue::new_now(value),
+        )
+        .unwrap();
+    }
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    assert!(data.try_recv().is_err());
+
+    // A change to the triggering item delivers the queued values of both triggered items.
+    nm.set_value(
+        tester.handle.subscriptions(),
+        &ids[0],
+        None,
+        DataValue::new_now(3),
+    )
+    .unwrap();
+    let values: HashMap<_, _> = timeout(Duration::from_millis(500), recv_n(&mut data, 3))
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|(r, v)| (r.node_id, v.value.unwrap()))
+        .collect();
+    assert_eq!(values.get(&ids[0]), Some(&Variant::Int32(3)));
+    assert_eq!(values.get(&ids[1]), Some(&Variant::Int32(1)));
+    assert_eq!(values.get(&ids[2]), Some(&Variant::Int32(2)));
+
+    // Existing links can be removed.
+    let (_, remove_res) = session
+        .set_triggering(sub_id, item_ids[0], &[], &[item_ids[2]])
+        .await
+        .unwrap();
+    assert_eq!(remove_res.unwrap(), vec![StatusCode::Good]);
+}
+
+#[derive(Default)]
+struct CountingSource {
+    value: AtomicI32,
+    samples: AtomicU32,
+}
+
+#[async_trait]
+impl PollingSource for CountingSource {
+    async fn sample(&self, nodes: &[NodeId]) -> Vec<DataValue> {
+        self.samples.fetch_add(1, Ordering::Relaxed);
+        let value = self.value.load(Ordering::Relaxed);
+        nodes.iter().map(|_| DataValue::new_now(value)).collect()
+    }
+}
+
+#[tokio::test]
+async fn polling_node_manager_values() {
+    let server = default_server().with_node_manager(polling_node_manager(
+        NamespaceMetadata {
+            namespace_uri: "urn:polling".to_owned(),
+            ..Default::default()
+        },
+        "polling",
+        CountingSource::default(),
+    ));
+    let mut tester = Tester::new(server, false).await;
+    let nm = tester
+        .handle
+        .node_managers()
+        .get_of_type::<PollingNodeManager<CountingSource>>()
+        .unwrap();
+    let ns = *nm.namespaces().keys().next().unwrap();
+    let id = NodeId::new(ns, "Polled");
+    nm.address_space().write().add_variables(
+        vec![VariableBuilder::new(&id, "Polled", "Polled")
+            .value(0)
+            .data_type(DataTypeId::Int32)
+            .access_level(AccessLevel::CURRENT_READ)
+            .user_access_level(AccessLevel::CURRENT_READ)
+            .build()],
+        &ObjectId::ObjectsFolder.into(),
+    );
+    nm.inner().add_polled_node(id.clone());
+    let source = nm.inner().source();
+    source.value.store(1, Ordering::Relaxed);
+
+    let (session, lp) = tester.connect_default().await.unwrap();
+    lp.spawn();
+    tokio::time::timeout(Duration::from_secs(2), session.wait_for_connection())
+        .await
+        .unwrap();
+
+    let read = |max_age: f64| {
+        let session = session.clone();
+        let id = id.clone();
+        async move {
+            let r = session
+                .read(
+                    &[read_value_id(AttributeId::Value, id)],
+                    TimestampsToReturn::Both,
+                    max_age,
+                )
+                .await
+                .unwrap();
+            r.into_iter().next().unwrap().value
+        }
+    };
+
+    // Reads sample the source, unless the last sample is recent enough.
+    assert_eq!(read(0.0).await, Some(Variant::Int32(1)));
+    assert_eq!(source.samples.load(Ordering::Relaxed), 1);
+    source.value.store(2, Ordering::Relaxed);
+    assert_eq!(read(60_000.0).await, Some(Variant::Int32(1)));
+    assert_eq!(source.samples.load(Ordering::Relaxed), 1);
+    assert_eq!(read(0.0).await, Some(Variant::Int32(2)));
+    assert_eq!(source.samples.load(Ordering::Relaxed), 2);
+
+    // Sampled values are returned with the requested timestamps.
+    let r = session
+        .read(
+            &[read_value_id(AttributeId::Value, id.clone())],
+            TimestampsToReturn::Source,
+            60_000.0,
+        )
+        .await
+        .unwrap();
+    assert_eq!(r[0].value, Some(Variant::Int32(2)));
+    assert!(r[0].source_timestamp.is_some());
+    assert!(r[0].server_timestamp.is_none());
+
+    let (notifs, mut data, _) = ChannelNotifications::new();
+    let sub_id = session
+        .create_subscription(Duration::from_millis(100), 100, 20, 1000, 0, true, notifs)
+        .await
+        .unwrap();
+    let res = session
+        .create_monitored_items(
+            sub_id,
+            TimestampsToReturn::Both,
+            vec![MonitoredItemCreateRequest {
+                item_to_monitor: ReadValueId {
+                    node_id: id.clone(),
+                    attribute_id: AttributeId::Value as u32,
+                    ..Default::default()
+                },
+                monitoring_mode: MonitoringMode::Reporting,
+                requested_parameters: MonitoringParameters {
+                    sampling_interval: 50.0,
+                    queue_size: 10,
+                    discard_oldest: true,
+                    ..Default::default()
+                },
+            }],
+        )
+        .await
+        .unwrap();
+    assert_eq!(res[0].result.status_code, StatusCode::Good);
+
+    let (_, v) = timeout(Duration::from_millis(500), data.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(v.value, Some(Variant::Int32(2)));
+
+    // The source is polled in the background, and changes are reported.
+    source.value.store(3, Ordering::Relaxed);
+    let (_, v) = timeout(Duration::from_millis(500), data.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(v.value, Some(Variant::Int32(3)));
+
+    // Samples without changes are not reported.
+    let samples = source.samples.load(Ordering::Relaxed);
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    assert!(source.samples.load(Ordering::Relaxed) > samples);
+    assert!(data.try_recv().is_err());
+
+    // Once the monitored item is deleted, the source is no longer polled.
+    session.delete_subscription(sub_id).await.unwrap();
+    let samples = source.samples.load(Ordering::Relaxed);
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert_eq!(source.samples.load(Ordering::Relaxed), samples);
+}
+
+#[tokio::test]
+async fn condition_refresh_and_acknowledge() {
+    let (tester, _nm, session) = setup().await;
+
+    let acknowledged = Arc::new(Mutex::new(Vec::new()));
+    let acknowledged_ref = acknowledged.clone();
+    tester
+        .handle
+        .subscriptions()
+        .conditions()
+        .set_method_callback(move |_, method, condition_id, comment| {
+            acknowledged_ref
+                .lock()
+                .unwrap()
+                .push((method, condition_id.clone(), comment.clone()));
+            StatusCode::Good
+        });
+
+    let (notifs, _, mut events) = ChannelNotifications::new();
+    let sub_id = session
+        .create_subscription(Duration::from_millis(100), 100, 20, 1000, 0, true, notifs)
+        .await
+        .unwrap();
+    session
+        .create_monitored_items(
+            sub_id,
+            TimestampsToReturn::Both,
+            vec![MonitoredItemCreateRequest {
+                item_to_monitor: ReadValueId {
+                    node_id: ObjectId::Server.into(),
+                    attribute_id: AttributeId::EventNotifier as u32,
+                    ..Default::default()
+                },
+                monitoring_mode: MonitoringMode::Reporting,
+                requested_parameters: MonitoringParameters {
+                    sampling_interval: 0.0,
+                    queue_size: 10,
+                    discard_oldest: true,
+                    filter: ExtensionObject::new(EventFilter {
+                        select_clauses: Some(vec![
+                            SimpleAttributeOperand::new_value(
+                                ObjectTypeId::BaseEventType,
+                                "EventType",
+                            ),
+                            SimpleAttributeOperand::new_value(
+                                ObjectTypeId::BaseEventType,
+                                "EventId",
+                            ),
+                        ]),
+                        where_clause: Default::default(),
+                    }),
+                    ..Default::default()
+                },
+            }],
+        )
+        .await
+        .unwrap();
+
+    let condition_id = NodeId::new(1, "MyCondition");
+    let server_id: NodeId = ObjectId::Server.into();
+    let condition = |retain: bool| {
+        let type_tree = tester.handle.type_tree().read();
+        EventBuilder::new(ObjectTypeId::AcknowledgeableConditionType, &*type_tree)
+            .unwrap()
+            .field("SourceNode", server_id.clone())
+            .unwrap()
+            .field("Message", LocalizedText::from("Too hot"))
+            .unwrap()
+            .field("Retain", retain)
+            .unwrap()
+            .build()
+    };
+    let event_type = |evt: &Option<Vec<Variant>>| evt.as_ref().unwrap()[0].clone();
+
+    tester
+        .handle
+        .subscriptions()
+        .notify_condition(&condition_id, condition(true), &server_id);
+    let (_, evt) = timeout(Duration::from_millis(500), events.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        event_type(&evt),
+        Variant::from(NodeId::from(ObjectTypeId::AcknowledgeableConditionType))
+    );
+    let event_id = evt.unwrap()[1].clone();
+    assert!(tester
+        .handle
+        .subscriptions()
+        .conditions()
+        .is_retained(&condition_id));
+
+    // A refresh replays the retained condition between the refresh start and end events.
+    let refresh = || {
+        session.call_one((
+            NodeId::from(ObjectTypeId::ConditionType),
+            NodeId::from(MethodId::ConditionType_ConditionRefresh),
+            Some(vec![Variant::from(sub_id)]),
+        ))
+    };
+    assert_eq!(refresh().await.unwrap().status_code, StatusCode::Good);
+    let evts = timeout(Duration::from_millis(500), recv_n(&mut events, 3))
+        .await
+        .unwrap();
+    let types: Vec<_> = evts.iter().map(|(_, e)| event_type(e)).collect();
+    assert_eq!(
+        types,
+        vec![
+            Variant::from(NodeId::from(ObjectTypeId::RefreshStartEventType)),
+            Variant::from(NodeId::from(ObjectTypeId::AcknowledgeableConditionType)),
+            Variant::from(NodeId::from(ObjectTypeId::RefreshEndEventType)),
+        ]
+    );
+    assert_eq!(evts[1].1.as_ref().unwrap()[1], event_id);
+
+    let r = session
+        .call_one((
+            NodeId::from(ObjectTypeId::ConditionType),
+            NodeId::from(MethodId::ConditionType_ConditionRefresh2),
+            Some(vec![Variant::from(sub_id), Variant::from(12345u32)]),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(r.status_code, StatusCode::BadMonitoredItemIdInvalid);
+
+    // Acknowledge is passed to the callback, if the event ID matches the last event.
+    let acknowledge = |event_id: Variant| {
+        session.call_one((
+            condition_id.clone(),
+            NodeId::from(MethodId::AcknowledgeableConditionType_Acknowledge),
+            Some(vec![event_id, LocalizedText::from("Noted").into()]),
+        ))
+    };
+    let r = acknowledge(random::byte_string(16).into()).await.unwrap();
+    assert_eq!(r.status_code, StatusCode::BadEventIdUnknown);
+    assert!(acknowledged.lock().unwrap().is_empty());
+    let r = acknowledge(event_id).await.unwrap();
+    assert_eq!(r.status_code, StatusCode::Good);
+    assert_eq!(
+        acknowledged.lock().unwrap().as_slice(),
+        &[(
+            ConditionMethod::Acknowledge,
+            condition_id.clone(),
+            LocalizedText::from("Noted")
+        )]
+    );
+
+    // Once the condition is no longer retained, it is not part of a refresh.
+    tester
+        .handle
+        .subscriptions()
+        .notify_condition(&condition_id, condition(false), &server_id);
+    timeout(Duration::from_millis(500), events.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(refresh().await.unwrap().status_code, StatusCode::Good);
+    let evts = timeout(Duration::from_millis(500), recv_n(&mut events, 2))
+        .await
+        .unwrap();
+    let types: Vec<_> = evts.iter().map(|(_, e)| event_type(e)).collect();
+    assert_eq!(
+        types,
+        vec![
+            Variant::from(NodeId::from(ObjectTypeId::RefreshStartEventType)),
+            Variant::from(NodeId::from(ObjectTypeId::RefreshEndEventType)),
+        ]
+    );
+}
+
+/// Authenticator allowing anonymous users, who may not acknowledge conditions.
+struct NoAcknowledgeAuthenticator(DefaultAuthenticator);
+
+#[async_trait]
+impl AuthManager for NoAcknowledgeAuthenticator {
+    async fn authenticate_anonymous_token(&self, endpoint: &ServerEndpoint) -> Result<(), Error> {
+        self.0.authenticate_anonymous_token(endpoint).await
+    }
+
+    fn user_token_policies(&self, endpoint: &ServerEndpoint) -> Vec<UserTokenPolicy> {
+        self.0.user_token_policies(endpoint)
+    }
+
+    fn is_user_executable(&self, _token: &UserToken, method_id: &NodeId) -> bool {
+        method_id != &NodeId::from(MethodId::AcknowledgeableConditionType_Acknowledge)
+    }
+}
+
+#[tokio::test]
+async fn condition_acknowledge_requires_user_executable() {
+    let server = test_server().with_authenticator(Arc::new(NoAcknowledgeAuthenticator(
+        DefaultAuthenticator::new(Default::default()),
+    )));
+    let mut tester = Tester::new(server, false).await;
+    let (session, lp) = tester.connect_default().await.unwrap();
+    lp.spawn();
+    timeout(Duration::from_secs(2), session.wait_for_connection())
+        .await
+        .unwrap();
+
+    let called = Arc::new(AtomicU32::new(0));
+    let called_ref = called.clone();
+    tester
+        .handle
+        .subscriptions()
+        .conditions()
+        .set_method_callback(move |_, _, _, _| {
+            called_ref.fetch_add(1, Ordering::Relaxed);
+            StatusCode::Good
+        });
+
+    let condition_id = NodeId::new(1, "MyCondition");
+    let server_id: NodeId = ObjectId::Server.into();
+    let condition = {
+        let type_tree = tester.handle.type_tree().read();
+        EventBuilder::new(ObjectTypeId::AcknowledgeableConditionType, &*type_tree)
+            .unwrap()
+            .field("SourceNode", server_id.clone())
+            .unwrap()
+            .field("Retain", true)
+            .unwrap()
+            .build()
+    };
+    tester
+        .handle
+        .subscriptions()
+        .notify_condition(&condition_id, condition, &server_id);
+
+    // The user may not execute Acknowledge, so the condition manager is never invoked.
+    let r = session
+        .call_one((
+            condition_id,
+            NodeId::from(MethodId::AcknowledgeableConditionType_Acknowledge),
+            Some(vec![
+                random::byte_string(16).into(),
+                LocalizedText::from("Noted").into(),
+            ]),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(r.status_code, StatusCode::BadUserAccessDenied);
+    assert_eq!(called.load(Ordering::Relaxed), 0);
+}
+
+#[tokio::test]
+async fn graceful_shutdown() {
+    let (mut tester, _nm, session) = setup().await;
+
+    let (status_send, mut status_recv) = tokio::sync::mpsc::unbounded_channel();
+    let (data_send, mut data_recv) = tokio::sync::mpsc::unbounded_channel();
+    let sub_id = session
+        .create_subscription(
+            Duration::from_millis(100),
+            100,
+            20,
+            1000,
+            0,
+            true,
+            SubscriptionCallbacks::new(
+                move |s| {
+                    let _ = status_send.send(s.status);
+                },
+                move |v, _| {
+                    let _ = data_send.send(v);
+                },
+                |_, _| {},
+            ),
+        )
+        .await
+        .unwrap();
+    session
+        .create_monitored_items(
+            sub_id,
+            TimestampsToReturn::Both,
+            vec![MonitoredItemCreateRequest {
+                item_to_monitor: ReadValueId::new_value(
+                    VariableId::Server_ServerStatus_State.into(),
+                ),
+                monitoring_mode: MonitoringMode::Reporting,
+                requested_parameters: MonitoringParameters {
+                    sampling_interval: 0.0,
+                    queue_size: 10,
+                    ..Default::default()
+                },
+            }],
+        )
+        .await
+        .unwrap();
+    let v = timeout(Duration::from_millis(500), data_recv.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(v.value, Some(Variant::Int32(0)));
+
+    tester.handle.shutdown(Duration::from_secs(10));
+
+    // The subscription is closed with a final status change.
+    let status = timeout(Duration::from_secs(2), status_recv.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(status, StatusCode::BadServerHalted);
+
+    // The server finishes draining well before the timeout.
+    timeout(Duration::from_secs(5), &mut tester.server_task)
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+
+    // New connections are no longer accepted.
+    assert!(tokio::net::TcpStream::connect(tester.addr).await.is_err());
+}
+
+#[tokio::test]
+async fn shutdown_rejects_new_subscriptions() {
+    let (mut tester, nm, session) = setup().await;
+
+    let (status_send, mut status_recv) = tokio::sync::mpsc::unbounded_channel();
+    session
+        .create_subscription(
+            Duration::from_millis(100),
+            100,
+            20,
+            1000,
+            0,
+            true,
+            SubscriptionCallbacks::new(
+                move |s| {
+                    let _ = status_send.send(s.status);
+                },
+                |_, _| {},
+                |_, _| {},
+            ),
+        )
+        .await
+        .unwrap();
+
+    // A slow read keeps the connection open while the server is draining.
+    let id = nm.inner().next_node_id();
+    nm.inner().add_node(
+        nm.address_space(),
+        tester.handle.type_tree(),
+        VariableBuilder::new(&id, "TestVar1", "TestVar1")
+            .value(0)
+            .data_type(DataTypeId::Int32)
+            .access_level(AccessLevel::CURRENT_READ)
+            .user_access_level(AccessLevel::CURRENT_READ)
+            .build()
+            .into(),
+        &ObjectId::ObjectsFolder.into(),
+        &ReferenceTypeId::Organizes.into(),
+        Some(&VariableTypeId::BaseDataVariableType.into()),
+        Vec::new(),
+    );
+    *nm.inner().issues().read_delay.lock() = Duration::from_secs(1);
+    let read = tokio::spawn({
+        let session = session.clone();
+        async move {
+            session
+                .read(&[ReadValueId::new_value(id)], TimestampsToReturn::Both, 0.0)
+                .await
+        }
+    });
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    tester.handle.shutdown(Duration::from_secs(10));
+    let status = timeout(Duration::from_secs(2), status_recv.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(status, StatusCode::BadServerHalted);
+
+    // The server is draining, so no new subscriptions are created.
+    let r = session
+        .create_subscription(
+            Duration::from_millis(100),
+            100,
+            20,
+            1000,
+            0,
+            true,
+            SubscriptionCallbacks::new(|_| {}, |_, _| {}, |_, _| {}),
+        )
+        .await;
+    assert_eq!(r.unwrap_err(), StatusCode::BadServerHalted);
+
+    // The read in flight still completes.
+    let r = read.await.unwrap().unwrap();
+    assert_eq!(r[0].value, Some(Variant::Int32(0)));
+
+    timeout(Duration::from_secs(5), &mut tester.server_task)
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+}
@@ -1,21 +1,28 @@
+use std::time::Duration;
+
 use chrono::TimeDelta;
 use opcua::{
-    client::{HistoryReadAction, HistoryUpdateAction, Session},
+    client::{HistoryReadAction, HistoryUpdateAction, IdentityToken, Session},
+    crypto::SecurityPolicy,
     server::address_space::{
         AccessLevel, DataTypeBuilder, EventNotifier, MethodBuilder, NodeType, ObjectBuilder,
         ObjectTypeBuilder, ReferenceTypeBuilder, VariableBuilder, VariableTypeBuilder, ViewBuilder,
     },
     types::{
-        AttributeId, ByteString, DataTypeId, DataValue, DateTime, HistoryData, HistoryReadValueId,
-        LocalizedText, NodeId, ObjectId, ObjectTypeId, QualifiedName, ReadRawModifiedDetails,
-        ReferenceTypeId, StatusCode, TimestampsToReturn, UpdateDataDetails, VariableTypeId,
-        Variant, WriteMask, WriteValue,
+        Array, AttributeId, ByteString, DataTypeId, DataValue, DateTime, HistoryData,
+        HistoryReadValueId, LocalizedText, MessageSecurityMode, NodeId, ObjectId, ObjectTypeId,
+        QualifiedName, ReadRawModifiedDetails, ReferenceTypeId, StatusCode, TimestampsToReturn,
+        UpdateDataDetails, VariableId, VariableTypeId, Variant, VariantScalarTypeId, WriteMask,
+        WriteValue,
     },
 };
 use opcua_types::NumericRange;
-// Write is not implemented in the core library itself, only in the test node manager,
+// Write is mostly not implemented in the core library itself, only in the test node manager,
 // we still test here to test write functionality in the address space.
-use super::utils::{array_value, read_value_id, setup};
+use super::utils::{
+    array_value, client_user_token, default_server, read_value_id, setup, setup_with, test_server,
+    unchecked_node_manager, TestNodeManager, Tester, UncheckedNodeManager,
+};
 
 fn write_value(
     attribute_id: AttributeId,
@@ -537,6 +544,93 @@ async fn write_invalid() {
     assert_eq!(r[3], StatusCode::BadUserAccessDenied);
 }
 
+#[tokio::test]
+async fn write_access_level() {
+    // By default, the server checks access levels for node managers that do not do so themselves.
+    let (tester, _nm, session) =
+        setup_with(test_server().with_node_manager(unchecked_node_manager())).await;
+    let nm = tester
+        .handle
+        .node_managers()
+        .get_of_type::<UncheckedNodeManager>()
+        .unwrap();
+
+    let read_only = nm.add_variable(
+        "ReadOnly",
+        1,
+        AccessLevel::CURRENT_READ,
+        AccessLevel::CURRENT_READ,
+    );
+    let user_read_only = nm.add_variable(
+        "UserReadOnly",
+        1,
+        AccessLevel::CURRENT_READ | AccessLevel::CURRENT_WRITE,
+        AccessLevel::CURRENT_READ,
+    );
+    let writable = nm.add_variable(
+        "Writable",
+        1,
+        AccessLevel::CURRENT_READ | AccessLevel::CURRENT_WRITE,
+        AccessLevel::CURRENT_READ | AccessLevel::CURRENT_WRITE,
+    );
+
+    let r = session
+        .write(&[
+            write_value(AttributeId::Value, 2, &read_only),
+            write_value(AttributeId::Value, 2, &user_read_only),
+            write_value(AttributeId::Value, 2, &writable),
+        ])
+        .await
+        .unwrap();
+    assert_eq!(
+        r,
+        vec![
+            StatusCode::BadNotWritable,
+            StatusCode::BadUserAccessDenied,
+            StatusCode::Good
+        ]
+    );
+}
+
+#[tokio::test]
+async fn write_access_level_in_same_request() {
+    let (tester, _nm, session) =
+        setup_with(test_server().with_node_manager(unchecked_node_manager())).await;
+    let nm = tester
+        .handle
+        .node_managers()
+        .get_of_type::<UncheckedNodeManager>()
+        .unwrap();
+
+    let user_access_level = AccessLevel::CURRENT_READ | AccessLevel::CURRENT_WRITE;
+    let id = nm.add_variable("Var", 1, AccessLevel::CURRENT_READ, user_access_level);
+    let other = nm.add_variable("Other", 1, AccessLevel::CURRENT_READ, user_access_level);
+
+    // Writes to the value are checked against the access level at that point
+    // in the request, so only a successful write to the access level allows later writes.
+    let writable = AccessLevel::CURRENT_READ | AccessLevel::CURRENT_WRITE;
+    let r = session
+        .write(&[
+            write_value(AttributeId::Value, 2, &id),
+            write_value(AttributeId::AccessLevel, writable.bits(), &id),
+            write_value(AttributeId::Value, 3, &id),
+            write_value(AttributeId::AccessLevel, "invalid", &other),
+            write_value(AttributeId::Value, 3, &other),
+        ])
+        .await
+        .unwrap();
+    assert_eq!(
+        r,
+        vec![
+            StatusCode::BadNotWritable,
+            StatusCode::Good,
+            StatusCode::Good,
+            StatusCode::BadTypeMismatch,
+            StatusCode::BadNotWritable,
+        ]
+    );
+}
+
 #[tokio::test]
 async fn write_limits() {
     let (tester, _nm, session) = setup().await;
@@ -594,7 +688,7 @@ async fn write_bytestring_to_byte_array() {
 
     let bytes = ByteString::from(vec![0x1u8, 0x2u8, 0x3u8, 0x4u8]);
     let mut write = write_value(AttributeId::Value, bytes, &id);
-    write.index_range = NumericRange::Range(0, 4);
+    write.index_range = NumericRange::Range(0, 3);
     let r = session.write(&[write]).await.unwrap();
     assert_eq!(StatusCode::Good, r[0]);
 
@@ -706,6 +800,71 @@ async fn write_index_range() {
     assert_eq!(val.value.unwrap(), bytes.into());
 }
 
+#[tokio::test]
+async fn write_index_range_matrix() {
+    let (tester, nm, session) = setup().await;
+
+    let id = nm.inner().next_node_id();
+    let matrix = Array::new_multi(
+        VariantScalarTypeId::Int32,
+        vec![Variant::Int32(0); 9],
+        vec![3, 3],
+    )
+    .unwrap();
+    nm.inner().add_node(
+        nm.address_space(),
+        tester.handle.type_tree(),
+        VariableBuilder::new(&id, "TestVar", "TestVar")
+            .value(matrix)
+            .data_type(DataTypeId::Int32)
+            .value_rank(2)
+            .array_dimensions(&[3, 3])
+            .access_level(AccessLevel::CURRENT_WRITE)
+            .user_access_level(AccessLevel::CURRENT_WRITE)
+            .build()
+            .into(),
+        &ObjectId::ObjectsFolder.into(),
+        &ReferenceTypeId::Organizes.into(),
+        Some(&VariableTypeId::BaseDataVariableType.into()),
+        Vec::new(),
+    );
+
+    let block = Array::new_multi(
+        VariantScalarTypeId::Int32,
+        (1..=4).map(Variant::Int32).collect::<Vec<_>>(),
+        vec![2, 2],
+    )
+    .unwrap();
+    let mut write = write_value(AttributeId::Value, block, &id);
+    write.index_range = "1:2,0:1".parse().unwrap();
+    let mut invalid_write = write.clone();
+    invalid_write.index_range = NumericRange::Range(0, 1);
+
+    let r = session.write(&[write, invalid_write]).await.unwrap();
+    assert_eq!(r[0], StatusCode::Good);
+    assert_eq!(r[1], StatusCode::BadIndexRangeInvalid);
+
+    let sp = nm.address_space().read();
+    let NodeType::Variable(v) = sp.find(&id).unwrap() else {
+        panic!("");
+    };
+    let val = v.value(
+        TimestampsToReturn::Both,
+        &NumericRange::None,
+        &Default::default(),
+        0.0,
+    );
+    let expected: Vec<_> = [0, 0, 0, 1, 2, 0, 3, 4, 0]
+        .into_iter()
+        .map(Variant::Int32)
+        .collect();
+    assert_eq!(array_value(&val), &expected);
+    let Some(Variant::Array(arr)) = &val.value else {
+        panic!("Expected array");
+    };
+    assert_eq!(arr.dimensions, Some(vec![3, 3]));
+}
+
 #[tokio::test]
 async fn history_update_insert() {
     let (tester, nm, session) = setup().await;
@@ -877,3 +1036,190 @@ async fn history_update_fail() {
 
     assert_eq!(r[0].status_code, StatusCode::BadNodeIdUnknown);
 }
+
+#[tokio::test]
+async fn write_batched() {
+    // Only allow two nodes per write, so that a single request for all values fails.
+    let mut server = test_server();
+    server.limits_mut().operational.max_nodes_per_write = 2;
+    let mut tester = Tester::new(server, false).await;
+    let nm = tester
+        .handle
+        .node_managers()
+        .get_of_type::<TestNodeManager>()
+        .unwrap();
+    let (session, lp) = tester.connect_default().await.unwrap();
+    lp.spawn();
+    tokio::time::timeout(Duration::from_secs(2), session.wait_for_connection())
+        .await
+        .unwrap();
+
+    let ids: Vec<_> = (0..5)
+        .map(|i| {
+            let id = nm.inner().next_node_id();
+            nm.inner().add_node(
+                nm.address_space(),
+                tester.handle.type_tree(),
+                VariableBuilder::new(&id, format!("TestVar{i}"), format!("TestVar{i}"))
+                    .value(ByteString::null())
+                    .data_type(DataTypeId::ByteString)
+                    .access_level(AccessLevel::CURRENT_READ | AccessLevel::CURRENT_WRITE)
+                    .user_access_level(AccessLevel::CURRENT_READ | AccessLevel::CURRENT_WRITE)
+                    .build()
+                    .into(),
+                &ObjectId::ObjectsFolder.into(),
+                &ReferenceTypeId::Organizes.into(),
+                Some(&VariableTypeId::BaseDataVariableType.into()),
+                Vec::new(),
+            );
+            id
+        })
+        .collect();
+
+    let values: Vec<_> = ids
+        .iter()
+        .enumerate()
+        .map(|(i, id)| {
+            write_value(
+                AttributeId::Value,
+                ByteString::from(vec![i as u8; 1000]),
+                id,
+            )
+        })
+        .collect();
+
+    let e = session.write(&values).await.unwrap_err();
+    assert_eq!(e, StatusCode::BadTooManyOperations);
+
+    // Each value is a bit over 1000 bytes, so this fits at most two values per request.
+    let r = session.write_batched(&values, 2500).await.unwrap();
+    assert_eq!(r, vec![StatusCode::Good; 5]);
+
+    let r = session
+        .read(
+            &ids.iter()
+                .map(|id| read_value_id(AttributeId::Value, id))
+                .collect::<Vec<_>>(),
+            TimestampsToReturn::Both,
+            0.0,
+        )
+        .await
+        .unwrap();
+    for (read, write) in r.into_iter().zip(&values) {
+        assert_eq!(read.value, write.value.value);
+    }
+}
+
+async fn read_diagnostics(session: &Session) -> Vec<DataValue> {
+    session
+        .read(
+            &[
+                read_value_id(
+                    AttributeId::Value,
+                    VariableId::Server_ServerDiagnostics_EnabledFlag,
+                ),
+                read_value_id(
+                    AttributeId::Value,
+                    VariableId::Server_ServerDiagnostics_ServerDiagnosticsSummary_CumulatedSessionCount,
+                ),
+                read_value_id(
+                    AttributeId::Value,
+                    VariableId::Server_ServerDiagnostics_ServerDiagnosticsSummary_CurrentSessionCount,
+                ),
+                read_value_id(
+                    AttributeId::Value,
+                    VariableId::Server_ServerDiagnostics_SessionsDiagnosticsSummary_SessionDiagnosticsArray,
+                ),
+            ],
+            TimestampsToReturn::Both,
+            0.0,
+        )
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn write_diagnostics_enabled_flag() {
+    let mut tester = Tester::new(default_server(), false).await;
+    let (session, lp) = tester
+        .connect(
+            SecurityPolicy::Aes128Sha256RsaOaep,
+            MessageSecurityMode::SignAndEncrypt,
+            client_user_token(),
+        )
+        .await
+        .unwrap();
+    lp.spawn();
+    tokio::time::timeout(Duration::from_secs(2), session.wait_for_connection())
+        .await
+        .unwrap();
+
+    let enabled_flag = VariableId::Server_ServerDiagnostics_EnabledFlag;
+
+    let r = read_diagnostics(&session).await;
+    assert_eq!(r[0].value, Some(Variant::Boolean(false)));
+
+    // Anonymous users may not toggle diagnostics.
+    let anonymous = tester
+        .connect_and_wait(
+            SecurityPolicy::None,
+            MessageSecurityMode::None,
+            IdentityToken::Anonymous,
+        )
+        .await
+        .unwrap();
+    let r = anonymous
+        .write(&[write_value(AttributeId::Value, true, enabled_flag)])
+        .await
+        .unwrap();
+    assert_eq!(r, vec![StatusCode::BadUserAccessDenied]);
+
+    let r = session
+        .write(&[
+            write_value(AttributeId::Value, 1i32, enabled_flag),
+            write_value(AttributeId::Value, true, VariableId::Server_ServiceLevel),
+        ])
+        .await
+        .unwrap();
+    assert_eq!(
+        r,
+        vec![StatusCode::BadTypeMismatch, StatusCode::BadNotWritable]
+    );
+
+    // Enable diagnostics. Existing sessions are included right away.
+    let r = session
+        .write(&[write_value(AttributeId::Value, true, enabled_flag)])
+        .await
+        .unwrap();
+    assert_eq!(r, vec![StatusCode::Good]);
+    let r = read_diagnostics(&session).await;
+    assert_eq!(r[1].value, Some(Variant::UInt32(0)));
+    assert_eq!(r[2].value, Some(Variant::UInt32(2)));
+    let Some(Variant::Array(sessions)) = &r[3].value else {
+        panic!("Expected session diagnostics array, got {:?}", r[3]);
+    };
+    assert_eq!(sessions.values.len(), 2);
+
+    // Only sessions created from now on are counted in the cumulated count.
+    let _other = tester
+        .connect_and_wait(
+            SecurityPolicy::None,
+            MessageSecurityMode::None,
+            IdentityToken::Anonymous,
+        )
+        .await
+        .unwrap();
+    let r = read_diagnostics(&session).await;
+    assert_eq!(r[0].value, Some(Variant::Boolean(true)));
+    assert_eq!(r[1].value, Some(Variant::UInt32(1)));
+    assert_eq!(r[2].value, Some(Variant::UInt32(3)));
+
+    // And disable them again.
+    let r = session
+        .write(&[write_value(AttributeId::Value, false, enabled_flag)])
+        .await
+        .unwrap();
+    assert_eq!(r, vec![StatusCode::Good]);
+    let r = read_diagnostics(&session).await;
+    assert_eq!(r[0].value, Some(Variant::Boolean(false)));
+}
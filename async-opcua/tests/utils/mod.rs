@@ -1,5 +1,7 @@
 mod node_manager;
+mod paged_node_manager;
 mod tester;
+mod unchecked_node_manager;
 
 pub const CLIENT_USERPASS_ID: &str = "sample1";
 pub const CLIENT_X509_ID: &str = "x509";
@@ -7,8 +9,10 @@ pub const CLIENT_X509_ID: &str = "x509";
 pub use node_manager::*;
 use opcua::types::{AttributeId, DataValue, NodeId, ReadValueId, Variant};
 use opcua_client::OnSubscriptionNotification;
+pub use paged_node_manager::*;
 pub use tester::*;
 use tokio::sync::mpsc::UnboundedReceiver;
+pub use unchecked_node_manager::*;
 
 #[allow(unused)]
 pub fn read_value_id(attribute: AttributeId, id: impl Into<NodeId>) -> ReadValueId {
@@ -1,6 +1,7 @@
 use std::{
     collections::HashMap,
     sync::atomic::{AtomicU32, Ordering},
+    time::Duration,
 };
 
 use async_trait::async_trait;
@@ -14,16 +15,16 @@ use opcua::{
             memory::{InMemoryNodeManager, InMemoryNodeManagerBuilder, InMemoryNodeManagerImpl},
             AddNodeItem, AddReferenceItem, DeleteNodeItem, DeleteReferenceItem, HistoryNode,
             HistoryUpdateNode, MethodCall, MonitoredItemRef, MonitoredItemUpdateRef,
-            NodeManagerBuilder, NodeManagersRef, ParsedReadValueId, RequestContext, ServerContext,
-            WriteNode,
+            NodeManagerBuilder, NodeManagerHealth, NodeManagersRef, ParsedReadValueId,
+            RegisterNodeItem, RequestContext, ServerContext, WriteNode,
         },
         ContinuationPoint, CreateMonitoredItem,
     },
     sync::{Mutex, RwLock},
     types::{
-        AttributeId, DataValue, DateTime, ExpandedNodeId, MonitoringMode, NodeClass, NodeId,
-        PerformUpdateType, ReadRawModifiedDetails, ReferenceTypeId, StatusCode, TimestampsToReturn,
-        Variant,
+        AttributeId, DataValue, DateTime, ExpandedNodeId, LocalizedText, MonitoringMode, NodeClass,
+        NodeId, PerformUpdateType, ReadRawModifiedDetails, ReferenceTypeId, StatusCode,
+        TimestampsToReturn, Variant,
     },
 };
 use opcua_core::{trace_read_lock, trace_write_lock};
@@ -52,6 +53,7 @@ pub struct TestNodeManagerImpl {
     history_data: RwLock<HashMap<NodeId, HistoryData>>,
     call_info: Mutex<CallInfo>,
     method_cbs: Mutex<HashMap<NodeId, Box<MethodCb>>>,
+    translations: RwLock<HashMap<NodeId, Vec<LocalizedText>>>,
     node_id_generator: AtomicU32,
     namespace_index: u16,
     node_managers: NodeManagersRef,
@@ -61,6 +63,8 @@ pub struct TestNodeManagerImpl {
 #[derive(Default)]
 pub struct IssueEmulation {
     pub fatal_read: AtomicU32,
+    pub health: Mutex<NodeManagerHealth>,
+    pub read_delay: Mutex<Duration>,
 }
 
 /// Information about calls made to the node manager impl, for verifying in tests.
@@ -88,6 +92,11 @@ pub fn test_node_manager() -> impl NodeManagerBuilder {
     InMemoryNodeManagerBuilder::new(make_test_node_manager_impl)
 }
 
+#[allow(unused)]
+pub fn test_node_manager_with_value_cache(ttl: Duration) -> impl NodeManagerBuilder {
+    InMemoryNodeManagerBuilder::new(make_test_node_manager_impl).with_value_cache(ttl)
+}
+
 fn make_test_node_manager_impl(
     context: ServerContext,
     address_space: &mut AddressSpace,
@@ -113,6 +122,10 @@ impl InMemoryNodeManagerImpl for TestNodeManagerImpl {
         "test"
     }
 
+    fn health(&self) -> NodeManagerHealth {
+        *self.issues.health.lock()
+    }
+
     fn handle_new_node(&self, parent_id: &ExpandedNodeId) -> bool {
         // Let this node manager handle all new nodes without a specified node id.
         parent_id.server_index == 0
@@ -159,6 +172,10 @@ impl InMemoryNodeManagerImpl for TestNodeManagerImpl {
         {
             panic!("Something went wrong! (Error emulation)");
         }
+        let delay = *self.issues.read_delay.lock();
+        if !delay.is_zero() {
+            tokio::time::sleep(delay).await;
+        }
         {
             let mut call_info = self.call_info.lock();
             for node in nodes.iter() {
@@ -167,9 +184,21 @@ impl InMemoryNodeManagerImpl for TestNodeManagerImpl {
         }
 
         let address_space = address_space.read();
+        let translations = trace_read_lock!(self.translations);
         nodes
             .iter()
-            .map(|n| address_space.read(context, n, max_age, timestamps_to_return))
+            .map(|n| {
+                let mut value = address_space.read(context, n, max_age, timestamps_to_return);
+                if !value.status.is_some_and(|s| s.is_bad()) {
+                    if let Some(text) = translations
+                        .get(&n.node_id)
+                        .and_then(|t| context.localize(t))
+                    {
+                        value.value = Some(text.into());
+                    }
+                }
+                value
+            })
             .collect()
     }
 
@@ -250,6 +279,20 @@ impl InMemoryNodeManagerImpl for TestNodeManagerImpl {
         }
     }
 
+    async fn register_nodes(
+        &self,
+        _context: &RequestContext,
+        _address_space: &RwLock<AddressSpace>,
+        nodes: &mut [&mut RegisterNodeItem],
+    ) -> Result<(), StatusCode> {
+        let mut call_info = self.call_info.lock();
+        for node in nodes {
+            call_info.register_nodes.push(node.node_id().clone());
+            node.set_registered_handle();
+        }
+        Ok(())
+    }
+
     async fn unregister_nodes(
         &self,
         _context: &RequestContext,
@@ -715,6 +758,7 @@ impl TestNodeManagerImpl {
             history_data: Default::default(),
             call_info: Default::default(),
             method_cbs: Default::default(),
+            translations: Default::default(),
             node_id_generator: AtomicU32::new(1),
             namespace_index,
             node_managers,
@@ -726,6 +770,19 @@ impl TestNodeManagerImpl {
         &self.issues
     }
 
+    #[allow(unused)]
+    pub fn call_info(&self) -> &Mutex<CallInfo> {
+        &self.call_info
+    }
+
+    /// Set translations of the value of a node, the value is read in the
+    /// preferred locale of the request.
+    #[allow(unused)]
+    pub fn add_translations(&self, node_id: NodeId, texts: Vec<LocalizedText>) {
+        let mut translations = trace_write_lock!(self.translations);
+        translations.insert(node_id, texts);
+    }
+
     #[allow(unused)]
     pub fn add_method_cb(
         &self,
@@ -0,0 +1,144 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use opcua::{
+    server::{
+        diagnostics::NamespaceMetadata,
+        node_manager::{
+            AddReferenceResult, BrowseCursor, BrowseNode, NodeManager, NodeManagerBuilder,
+            NodeMetadata, RequestContext, ServerContext,
+        },
+    },
+    sync::Mutex,
+    types::{
+        LocalizedText, NodeClass, NodeId, QualifiedName, ReferenceTypeId, StatusCode,
+        VariableTypeId,
+    },
+};
+use opcua_nodes::DefaultTypeTree;
+
+const PAGED_NAMESPACE: &str = "urn:rustopcua:paged";
+
+/// Continuation point holding a cursor, and a handle used to count
+/// the continuation points the server keeps alive.
+struct PagedContinuationPoint {
+    cursor: BrowseCursor,
+    _live: Arc<()>,
+}
+
+/// Node manager with a single folder organizing a list of variables, which pages
+/// browse results for the folder using a `BrowseCursor`.
+pub struct PagedNodeManager {
+    namespace_index: u16,
+    variables: Mutex<Vec<NodeId>>,
+    live: Arc<()>,
+}
+
+#[allow(unused)]
+pub fn paged_node_manager() -> impl NodeManagerBuilder {
+    |context: ServerContext| PagedNodeManager {
+        namespace_index: context
+            .type_tree
+            .write()
+            .namespaces_mut()
+            .add_namespace(PAGED_NAMESPACE),
+        variables: Default::default(),
+        live: Default::default(),
+    }
+}
+
+#[allow(unused)]
+impl PagedNodeManager {
+    /// Node ID of the folder organizing the variables.
+    pub fn root_id(&self) -> NodeId {
+        NodeId::new(self.namespace_index, "Root")
+    }
+
+    /// Add a variable at the end of the folder, returning its node ID.
+    pub fn add_variable(&self, name: &str) -> NodeId {
+        let id = NodeId::new(self.namespace_index, name.to_owned());
+        self.variables.lock().push(id.clone());
+        id
+    }
+
+    /// Remove a variable from the folder.
+    pub fn remove_variable(&self, id: &NodeId) {
+        self.variables.lock().retain(|v| v != id);
+    }
+
+    /// Number of continuation points created by this node manager that
+    /// the server has not released yet.
+    pub fn live_continuation_points(&self) -> usize {
+        Arc::strong_count(&self.live) - 1
+    }
+
+    fn metadata(&self, id: &NodeId) -> NodeMetadata {
+        let name = id.to_string();
+        NodeMetadata {
+            node_id: id.clone().into(),
+            type_definition: VariableTypeId::BaseDataVariableType.into(),
+            browse_name: QualifiedName::new(self.namespace_index, name.as_str()),
+            display_name: LocalizedText::new("", &name),
+            node_class: NodeClass::Variable,
+        }
+    }
+
+    fn browse_root(&self, node: &mut BrowseNode, type_tree: &DefaultTypeTree) {
+        let mut cp = node
+            .take_continuation_point::<PagedContinuationPoint>()
+            .map(|c| *c)
+            .unwrap_or_else(|| PagedContinuationPoint {
+                cursor: BrowseCursor::new(),
+                _live: self.live.clone(),
+            });
+        let variables = self.variables.lock();
+        for id in cp.cursor.resume(variables.iter(), |id| id) {
+            if let AddReferenceResult::Full(_) = node.add(
+                type_tree,
+                self.metadata(id)
+                    .into_ref_desc(true, ReferenceTypeId::Organizes),
+            ) {
+                node.set_next_continuation_point(Box::new(cp));
+                return;
+            }
+            cp.cursor.advance(id);
+        }
+    }
+}
+
+#[async_trait]
+impl NodeManager for PagedNodeManager {
+    fn owns_node(&self, id: &NodeId) -> bool {
+        id.namespace == self.namespace_index
+    }
+
+    fn name(&self) -> &str {
+        "paged"
+    }
+
+    fn namespaces_for_user(&self, _context: &RequestContext) -> Vec<NamespaceMetadata> {
+        vec![NamespaceMetadata {
+            namespace_uri: PAGED_NAMESPACE.to_owned(),
+            namespace_index: self.namespace_index,
+            ..Default::default()
+        }]
+    }
+
+    async fn init(&self, _type_tree: &mut DefaultTypeTree, _context: ServerContext) {}
+
+    async fn browse(
+        &self,
+        context: &RequestContext,
+        nodes_to_browse: &mut [BrowseNode],
+    ) -> Result<(), StatusCode> {
+        let type_tree = context.type_tree.read();
+        let root_id = self.root_id();
+        for node in nodes_to_browse {
+            if node.node_id() == &root_id {
+                self.browse_root(node, &type_tree);
+                node.set_status(StatusCode::Good);
+            }
+        }
+        Ok(())
+    }
+}
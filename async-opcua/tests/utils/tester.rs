@@ -18,7 +18,7 @@ use opcua::{
 use opcua_core::config::Config;
 use opcua_crypto::CertificateStore;
 use opcua_types::ApplicationDescription;
-use tokio::net::TcpListener;
+use tokio::{net::TcpListener, task::JoinHandle};
 use tokio_util::sync::{CancellationToken, DropGuard};
 
 use super::{test_node_manager, TestNodeManager, CLIENT_USERPASS_ID, CLIENT_X509_ID};
@@ -29,6 +29,7 @@ pub struct Tester {
     _guard: DropGuard,
     pub addr: SocketAddr,
     pub test_id: u16,
+    pub server_task: JoinHandle<Result<(), String>>,
 }
 
 pub static TEST_COUNTER: AtomicU16 = AtomicU16::new(0);
@@ -50,7 +51,12 @@ pub fn hostname() -> String {
 
 #[allow(unused)]
 pub async fn setup() -> (Tester, Arc<TestNodeManager>, Arc<Session>) {
-    let server = test_server();
+    setup_with(test_server()).await
+}
+
+/// Start `server`, which must contain a test node manager, and connect a session to it.
+#[allow(unused)]
+pub async fn setup_with(server: ServerBuilder) -> (Tester, Arc<TestNodeManager>, Arc<Session>) {
     let mut tester = Tester::new(server, false).await;
     let nm = tester
         .handle
@@ -95,7 +101,8 @@ pub fn default_server() -> ServerBuilder {
                 CLIENT_USERPASS_ID,
                 &format!("{CLIENT_USERPASS_ID}_password"),
             )
-            .read_diagnostics(true),
+            .read_diagnostics(true)
+            .write_diagnostics(true),
         )
         .add_user_token(
             CLIENT_X509_ID,
@@ -308,7 +315,7 @@ impl Tester {
         let (server, handle) = server.build().unwrap();
         let token = CancellationToken::new();
 
-        tokio::task::spawn(server.run_with(listener));
+        let server_task = tokio::task::spawn(server.run_with(listener));
 
         let client = default_client(test_id, quick_timeout).client().unwrap();
 
@@ -318,6 +325,7 @@ impl Tester {
             client,
             addr,
             test_id,
+            server_task,
         }
     }
 
@@ -337,7 +345,7 @@ impl Tester {
 
         let (server, handle) = server.build().unwrap();
 
-        tokio::task::spawn(server.run_with(listener));
+        let server_task = tokio::task::spawn(server.run_with(listener));
 
         let client = default_client(test_id, quick_timeout).client().unwrap();
 
@@ -347,6 +355,7 @@ impl Tester {
             client,
             addr,
             test_id,
+            server_task,
         }
     }
 
@@ -369,7 +378,7 @@ impl Tester {
 
         let (server, handle) = server.build().unwrap();
 
-        tokio::task::spawn(server.run_with(listener));
+        let server_task = tokio::task::spawn(server.run_with(listener));
 
         let client = client.client().unwrap();
 
@@ -379,6 +388,7 @@ impl Tester {
             client,
             addr,
             test_id,
+            server_task,
         }
     }
 
@@ -0,0 +1,184 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use opcua::{
+    server::{
+        address_space::AccessLevel,
+        diagnostics::NamespaceMetadata,
+        node_manager::{
+            NodeManager, NodeManagerBuilder, NodeManagerHealth, ReadNode, RequestContext,
+            ServerContext, WriteNode,
+        },
+    },
+    sync::Mutex,
+    types::{AttributeId, DataValue, NodeId, StatusCode, TimestampsToReturn, Variant},
+};
+use opcua_nodes::DefaultTypeTree;
+
+const UNCHECKED_NAMESPACE: &str = "urn:rustopcua:unchecked";
+
+struct UncheckedVariable {
+    value: Variant,
+    /// Access levels of the variable, or `None` if they cannot be read.
+    access_levels: Option<(AccessLevel, AccessLevel)>,
+}
+
+/// Node manager with a flat set of variables, which reads and writes them without
+/// checking their access levels, leaving that to the server.
+pub struct UncheckedNodeManager {
+    namespace_index: u16,
+    variables: Mutex<HashMap<NodeId, UncheckedVariable>>,
+    health: Mutex<NodeManagerHealth>,
+}
+
+#[allow(unused)]
+pub fn unchecked_node_manager() -> impl NodeManagerBuilder {
+    |context: ServerContext| UncheckedNodeManager {
+        namespace_index: context
+            .type_tree
+            .write()
+            .namespaces_mut()
+            .add_namespace(UNCHECKED_NAMESPACE),
+        variables: Default::default(),
+        health: Default::default(),
+    }
+}
+
+impl UncheckedNodeManager {
+    /// Add a variable with the given access levels, returning its node ID.
+    #[allow(unused)]
+    pub fn add_variable(
+        &self,
+        name: &str,
+        value: impl Into<Variant>,
+        access_level: AccessLevel,
+        user_access_level: AccessLevel,
+    ) -> NodeId {
+        self.insert_variable(name, value, Some((access_level, user_access_level)))
+    }
+
+    /// Add a variable whose access levels cannot be read, returning its node ID.
+    #[allow(unused)]
+    pub fn add_variable_with_unreadable_access_levels(
+        &self,
+        name: &str,
+        value: impl Into<Variant>,
+    ) -> NodeId {
+        self.insert_variable(name, value, None)
+    }
+
+    /// Set the health reported by the node manager.
+    #[allow(unused)]
+    pub fn set_health(&self, health: NodeManagerHealth) {
+        *self.health.lock() = health;
+    }
+
+    fn insert_variable(
+        &self,
+        name: &str,
+        value: impl Into<Variant>,
+        access_levels: Option<(AccessLevel, AccessLevel)>,
+    ) -> NodeId {
+        let id = NodeId::new(self.namespace_index, name.to_owned());
+        self.variables.lock().insert(
+            id.clone(),
+            UncheckedVariable {
+                value: value.into(),
+                access_levels,
+            },
+        );
+        id
+    }
+}
+
+#[async_trait]
+impl NodeManager for UncheckedNodeManager {
+    fn owns_node(&self, id: &NodeId) -> bool {
+        id.namespace == self.namespace_index
+    }
+
+    fn name(&self) -> &str {
+        "unchecked"
+    }
+
+    fn health(&self) -> NodeManagerHealth {
+        *self.health.lock()
+    }
+
+    fn namespaces_for_user(&self, _context: &RequestContext) -> Vec<NamespaceMetadata> {
+        vec![NamespaceMetadata {
+            namespace_uri: UNCHECKED_NAMESPACE.to_owned(),
+            namespace_index: self.namespace_index,
+            ..Default::default()
+        }]
+    }
+
+    async fn init(&self, _type_tree: &mut DefaultTypeTree, _context: ServerContext) {}
+
+    async fn read(
+        &self,
+        _context: &RequestContext,
+        _max_age: f64,
+        _timestamps_to_return: TimestampsToReturn,
+        nodes_to_read: &mut [&mut ReadNode],
+    ) -> Result<(), StatusCode> {
+        let variables = self.variables.lock();
+        for node in nodes_to_read {
+            let Some(variable) = variables.get(&node.node().node_id) else {
+                node.set_error(StatusCode::BadNodeIdUnknown);
+                continue;
+            };
+            let value: Variant = match (node.node().attribute_id, variable.access_levels) {
+                (AttributeId::Value, _) => variable.value.clone(),
+                (AttributeId::AccessLevel | AttributeId::UserAccessLevel, None) => {
+                    node.set_error(StatusCode::BadNotReadable);
+                    continue;
+                }
+                (AttributeId::AccessLevel, Some((access_level, _))) => access_level.bits().into(),
+                (AttributeId::UserAccessLevel, Some((_, user_access_level))) => {
+                    user_access_level.bits().into()
+                }
+                _ => {
+                    node.set_error(StatusCode::BadAttributeIdInvalid);
+                    continue;
+                }
+            };
+            node.set_result(DataValue::new_now(value));
+        }
+        Ok(())
+    }
+
+    async fn write(
+        &self,
+        _context: &RequestContext,
+        nodes_to_write: &mut [&mut WriteNode],
+    ) -> Result<(), StatusCode> {
+        let mut variables = self.variables.lock();
+        for node in nodes_to_write {
+            let Some(variable) = variables.get_mut(&node.value().node_id) else {
+                node.set_status(StatusCode::BadNodeIdUnknown);
+                continue;
+            };
+            let value = node.value().value.value.clone().unwrap_or_default();
+            let status = match (node.value().attribute_id, value) {
+                (AttributeId::Value, value) => {
+                    variable.value = value;
+                    StatusCode::Good
+                }
+                (AttributeId::AccessLevel, Variant::Byte(b)) => {
+                    let access_level = AccessLevel::from_bits_truncate(b);
+                    let user_access_level = variable
+                        .access_levels
+                        .map(|(_, user_access_level)| user_access_level)
+                        .unwrap_or(access_level);
+                    variable.access_levels = Some((access_level, user_access_level));
+                    StatusCode::Good
+                }
+                (AttributeId::AccessLevel, _) => StatusCode::BadTypeMismatch,
+                _ => StatusCode::BadNotWritable,
+            };
+            node.set_status(status);
+        }
+        Ok(())
+    }
+}
@@ -17,6 +17,15 @@ pub enum IMTagSelectorEnumeration {
     LOCATION = 1i32,
     BOTH = 2i32,
 }
+impl std::fmt::Display for IMTagSelectorEnumeration {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::FUNCTION => "FUNCTION",
+            Self::LOCATION => "LOCATION",
+            Self::BOTH => "BOTH",
+        })
+    }
+}
 #[opcua::types::ua_encodable]
 ///https://reference.opcfoundation.org/v104/PROFINET/v101/docs/6.3.3/#6.3.3.3.2
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -34,6 +43,17 @@ pub enum PnARStateEnumeration {
     ///The AR connection to the device is not established because the Name of Station of the device exists multiple times
     UNCONNECTED_ERR_DUPLICATE_NOS = 4i32,
 }
+impl std::fmt::Display for PnARStateEnumeration {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::CONNECTED => "CONNECTED",
+            Self::UNCONNECTED => "UNCONNECTED",
+            Self::UNCONNECTED_ERR_DEVICE_NOT_FOUND => "UNCONNECTED_ERR_DEVICE_NOT_FOUND",
+            Self::UNCONNECTED_ERR_DUPLICATE_IP => "UNCONNECTED_ERR_DUPLICATE_IP",
+            Self::UNCONNECTED_ERR_DUPLICATE_NOS => "UNCONNECTED_ERR_DUPLICATE_NOS",
+        })
+    }
+}
 #[opcua::types::ua_encodable]
 ///https://reference.opcfoundation.org/v104/PROFINET/v101/docs/6.3.3/#6.3.3.3.3
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -48,6 +68,16 @@ pub enum PnARTypeEnumeration {
     ///The SR AR is a special form of the IOCARSingle indicating system redundancy or dynamic reconfiguration usage
     IOCARSR = 32i32,
 }
+impl std::fmt::Display for PnARTypeEnumeration {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::IOCARSingle => "IOCARSingle",
+            Self::IOSAR => "IOSAR",
+            Self::IOCARSingleUsingRT_CLASS_3 => "IOCARSingleUsingRT_CLASS_3",
+            Self::IOCARSR => "IOCARSR",
+        })
+    }
+}
 #[opcua::types::ua_encodable]
 ///https://reference.opcfoundation.org/v104/PROFINET/v101/docs/6.3.3/#6.3.3.3.14
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -61,6 +91,15 @@ pub enum PnAssetChangeEnumeration {
     ///Asset has been changed
     CHANGED = 2i32,
 }
+impl std::fmt::Display for PnAssetChangeEnumeration {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::INSERTED => "INSERTED",
+            Self::REMOVED => "REMOVED",
+            Self::CHANGED => "CHANGED",
+        })
+    }
+}
 #[opcua::types::ua_encodable]
 ///https://reference.opcfoundation.org/v104/PROFINET/v101/docs/6.3.3/#6.3.3.3.13
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -76,6 +115,16 @@ pub enum PnAssetTypeEnumeration {
     ///Asset
     ASSET = 3i32,
 }
+impl std::fmt::Display for PnAssetTypeEnumeration {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::DEVICE => "DEVICE",
+            Self::MODULE => "MODULE",
+            Self::SUBMODULE => "SUBMODULE",
+            Self::ASSET => "ASSET",
+        })
+    }
+}
 #[opcua::types::ua_encodable]
 ///https://reference.opcfoundation.org/v104/PROFINET/v101/docs/6.3.3/#6.3.3.3.9
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -91,6 +140,14 @@ pub enum PnChannelAccumulativeEnumeration {
     */
     ACCUMULATIVE = 256i32,
 }
+impl std::fmt::Display for PnChannelAccumulativeEnumeration {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::SINGLE => "SINGLE",
+            Self::ACCUMULATIVE => "ACCUMULATIVE",
+        })
+    }
+}
 #[opcua::types::ua_encodable]
 ///https://reference.opcfoundation.org/v104/PROFINET/v101/docs/6.3.3/#6.3.3.3.12
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -106,6 +163,16 @@ pub enum PnChannelDirectionEnumeration {
     ///Input/Output
     BIDIRECTIONAL_CHANNEL = 24576i32,
 }
+impl std::fmt::Display for PnChannelDirectionEnumeration {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::MANUFACTURER_SPECIFIC => "MANUFACTURER_SPECIFIC",
+            Self::INPUT_CHANNEL => "INPUT_CHANNEL",
+            Self::OUTPUT_CHANNEL => "OUTPUT_CHANNEL",
+            Self::BIDIRECTIONAL_CHANNEL => "BIDIRECTIONAL_CHANNEL",
+        })
+    }
+}
 #[opcua::types::ua_encodable]
 ///https://reference.opcfoundation.org/v104/PROFINET/v101/docs/6.3.3/#6.3.3.3.10
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -121,6 +188,16 @@ pub enum PnChannelMaintenanceEnumeration {
     ///Use QualifiedChannelQualifier variable
     USE_QUALIFIED_CHANNEL_QUALIFIER = 1536i32,
 }
+impl std::fmt::Display for PnChannelMaintenanceEnumeration {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::FAULT => "FAULT",
+            Self::MAINTENANCE_REQUIRED => "MAINTENANCE_REQUIRED",
+            Self::MAINTENANCE_DEMANDED => "MAINTENANCE_DEMANDED",
+            Self::USE_QUALIFIED_CHANNEL_QUALIFIER => "USE_QUALIFIED_CHANNEL_QUALIFIER",
+        })
+    }
+}
 #[opcua::types::ua_encodable]
 ///https://reference.opcfoundation.org/v104/PROFINET/v101/docs/6.3.3/#6.3.3.3.11
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -142,6 +219,16 @@ pub enum PnChannelSpecifierEnumeration {
     */
     DISAPPEARS_OTHER_REMAIN = 6144i32,
 }
+impl std::fmt::Display for PnChannelSpecifierEnumeration {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::ALL_DISAPPEARS => "ALL_DISAPPEARS",
+            Self::APPEARS => "APPEARS",
+            Self::DISAPPEARS => "DISAPPEARS",
+            Self::DISAPPEARS_OTHER_REMAIN => "DISAPPEARS_OTHER_REMAIN",
+        })
+    }
+}
 #[opcua::types::ua_encodable]
 ///https://reference.opcfoundation.org/v104/PROFINET/v101/docs/6.3.3/#6.3.3.3.8
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -174,6 +261,20 @@ pub enum PnChannelTypeEnumeration {
     #[opcua(rename = "64BIT")]
     __64BIT = 7i32,
 }
+impl std::fmt::Display for PnChannelTypeEnumeration {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::UNSPECIFIC => "UNSPECIFIC",
+            Self::__1BIT => "1BIT",
+            Self::__2BIT => "2BIT",
+            Self::__4BIT => "4BIT",
+            Self::__8BIT => "8BIT",
+            Self::__16BIT => "16BIT",
+            Self::__32BIT => "32BIT",
+            Self::__64BIT => "64BIT",
+        })
+    }
+}
 #[opcua::types::ua_encodable]
 ///https://reference.opcfoundation.org/v104/PROFINET/v101/docs/6.3.3/#6.3.3.3.1
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -189,6 +290,16 @@ pub enum PnDeviceStateEnumeration {
     ///The device is a docking device and currently online.
     ONLINE_DOCKING = 3i32,
 }
+impl std::fmt::Display for PnDeviceStateEnumeration {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::OFFLINE => "OFFLINE",
+            Self::OFFLINE_DOCKING => "OFFLINE_DOCKING",
+            Self::ONLINE => "ONLINE",
+            Self::ONLINE_DOCKING => "ONLINE_DOCKING",
+        })
+    }
+}
 #[opcua::types::ua_encodable]
 ///https://reference.opcfoundation.org/v104/PROFINET/v101/docs/6.3.3/#6.3.3.3.15
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -209,6 +320,19 @@ pub enum PnLinkStateEnumeration {
     ///Down due to lower layer
     LOWER_LAYER_DOWN = 7i32,
 }
+impl std::fmt::Display for PnLinkStateEnumeration {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::UP => "UP",
+            Self::DOWN => "DOWN",
+            Self::TESTING => "TESTING",
+            Self::UNKNOWN => "UNKNOWN",
+            Self::DORMANT => "DORMANT",
+            Self::NOT_PRESENT => "NOT_PRESENT",
+            Self::LOWER_LAYER_DOWN => "LOWER_LAYER_DOWN",
+        })
+    }
+}
 #[opcua::types::ua_encodable]
 ///https://reference.opcfoundation.org/v104/PROFINET/v101/docs/6.3.3/#6.3.3.3.4
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -226,6 +350,17 @@ pub enum PnModuleStateEnumeration {
     ///Default state
     OK = 4i32,
 }
+impl std::fmt::Display for PnModuleStateEnumeration {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::NO_MODULE => "NO_MODULE",
+            Self::WRONG_MODULE => "WRONG_MODULE",
+            Self::PROPER_MODULE => "PROPER_MODULE",
+            Self::SUBSTITUTE => "SUBSTITUTE",
+            Self::OK => "OK",
+        })
+    }
+}
 #[opcua::types::ua_encodable]
 ///https://reference.opcfoundation.org/v104/PROFINET/v101/docs/6.3.3/#6.3.3.3.16
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -244,6 +379,19 @@ pub enum PnPortStateEnumeration {
     FORWARDING = 5i32,
     BROKEN = 6i32,
 }
+impl std::fmt::Display for PnPortStateEnumeration {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::UNKNOWN => "UNKNOWN",
+            Self::DISABLED_DISCARDING => "DISABLED_DISCARDING",
+            Self::BLOCKING => "BLOCKING",
+            Self::LISTENING => "LISTENING",
+            Self::LEARNING => "LEARNING",
+            Self::FORWARDING => "FORWARDING",
+            Self::BROKEN => "BROKEN",
+        })
+    }
+}
 #[opcua::types::ua_encodable]
 ///https://reference.opcfoundation.org/v104/PROFINET/v101/docs/6.3.3/#6.3.3.3.5
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -254,6 +402,14 @@ pub enum PnSubmoduleAddInfoEnumeration {
     ///This Submodule is not available for takeover by IOSAR.
     TAKEOVER_NOT_ALLOWED = 1i32,
 }
+impl std::fmt::Display for PnSubmoduleAddInfoEnumeration {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::NO_ADD_INFO => "NO_ADD_INFO",
+            Self::TAKEOVER_NOT_ALLOWED => "TAKEOVER_NOT_ALLOWED",
+        })
+    }
+}
 #[opcua::types::ua_encodable]
 ///https://reference.opcfoundation.org/v104/PROFINET/v101/docs/6.3.3/#6.3.3.3.6
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -271,6 +427,17 @@ pub enum PnSubmoduleARInfoEnumeration {
     ///This AR is not owner of the submodule. It is owned by another IOSAR
     LOCKED_BY_IO_SUPERVISOR = 512i32,
 }
+impl std::fmt::Display for PnSubmoduleARInfoEnumeration {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::OWN => "OWN",
+            Self::APPLICATION_READY_PENDING => "APPLICATION_READY_PENDING",
+            Self::SUPERORDINATED_LOCKED => "SUPERORDINATED_LOCKED",
+            Self::LOCKED_BY_IO_CONTROLLER => "LOCKED_BY_IO_CONTROLLER",
+            Self::LOCKED_BY_IO_SUPERVISOR => "LOCKED_BY_IO_SUPERVISOR",
+        })
+    }
+}
 #[opcua::types::ua_encodable]
 ///https://reference.opcfoundation.org/v104/PROFINET/v101/docs/6.3.3/#6.3.3.3.7
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -286,3 +453,13 @@ pub enum PnSubmoduleIdentInfoEnumeration {
     ///NoSubmodule (NO)
     NO_SUBMODULE = 6144i32,
 }
+impl std::fmt::Display for PnSubmoduleIdentInfoEnumeration {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::OK => "OK",
+            Self::SUBSTITUTE => "SUBSTITUTE",
+            Self::WRONG => "WRONG",
+            Self::NO_SUBMODULE => "NO_SUBMODULE",
+        })
+    }
+}
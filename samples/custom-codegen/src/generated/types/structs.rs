@@ -11,6 +11,7 @@
 ///https://reference.opcfoundation.org/v104/PROFINET/v101/docs/6.3.3/#6.3.3.1.1
 #[derive(Debug, Clone, PartialEq, Default)]
 pub struct PnDeviceDiagnosisDataType {
+    #[opcua(rename = "API")]
     pub api: u32,
     pub slot: u16,
     pub subslot: u16,
@@ -54,6 +55,108 @@ impl opcua::types::ExpandedMessageInfo for PnDeviceDiagnosisDataType {
         opcua::types::ExpandedNodeId::from((id, "http://opcfoundation.org/UA/PROFINET/"))
     }
 }
+impl PnDeviceDiagnosisDataType {
+    ///Create a builder for `PnDeviceDiagnosisDataType`.
+    pub fn builder() -> PnDeviceDiagnosisDataTypeBuilder {
+        PnDeviceDiagnosisDataTypeBuilder::default()
+    }
+}
+///Builder for [`PnDeviceDiagnosisDataType`]. Fields that are not set are given their default value.
+#[derive(Debug, Clone, Default)]
+pub struct PnDeviceDiagnosisDataTypeBuilder {
+    inner: PnDeviceDiagnosisDataType,
+}
+impl PnDeviceDiagnosisDataTypeBuilder {
+    ///Set the `api` field.
+    pub fn api(mut self, value: u32) -> Self {
+        self.inner.api = value;
+        self
+    }
+    ///Set the `slot` field.
+    pub fn slot(mut self, value: u16) -> Self {
+        self.inner.slot = value;
+        self
+    }
+    ///Set the `subslot` field.
+    pub fn subslot(mut self, value: u16) -> Self {
+        self.inner.subslot = value;
+        self
+    }
+    ///Set the `channel_number` field.
+    pub fn channel_number(mut self, value: u16) -> Self {
+        self.inner.channel_number = value;
+        self
+    }
+    ///Set the `__type` field.
+    pub fn r#type(mut self, value: super::enums::PnChannelTypeEnumeration) -> Self {
+        self.inner.__type = value;
+        self
+    }
+    ///Set the `accumulative` field.
+    pub fn accumulative(mut self, value: super::enums::PnChannelAccumulativeEnumeration) -> Self {
+        self.inner.accumulative = value;
+        self
+    }
+    ///Set the `maintenance` field.
+    pub fn maintenance(mut self, value: super::enums::PnChannelMaintenanceEnumeration) -> Self {
+        self.inner.maintenance = value;
+        self
+    }
+    ///Set the `specifier` field.
+    pub fn specifier(mut self, value: super::enums::PnChannelSpecifierEnumeration) -> Self {
+        self.inner.specifier = value;
+        self
+    }
+    ///Set the `direction` field.
+    pub fn direction(mut self, value: super::enums::PnChannelDirectionEnumeration) -> Self {
+        self.inner.direction = value;
+        self
+    }
+    ///Set the `user_structure_identifier` field.
+    pub fn user_structure_identifier(mut self, value: u16) -> Self {
+        self.inner.user_structure_identifier = value;
+        self
+    }
+    ///Set the `channel_error_type` field.
+    pub fn channel_error_type(mut self, value: u16) -> Self {
+        self.inner.channel_error_type = value;
+        self
+    }
+    ///Set the `ext_channel_error_type` field.
+    pub fn ext_channel_error_type(mut self, value: u16) -> Self {
+        self.inner.ext_channel_error_type = value;
+        self
+    }
+    ///Set the `ext_channel_add_value` field.
+    pub fn ext_channel_add_value(mut self, value: u32) -> Self {
+        self.inner.ext_channel_add_value = value;
+        self
+    }
+    ///Set the `qualified_channel_qualifier` field.
+    pub fn qualified_channel_qualifier(mut self, value: u32) -> Self {
+        self.inner.qualified_channel_qualifier = value;
+        self
+    }
+    ///Set the `manufacturer_data` field.
+    pub fn manufacturer_data(mut self, value: opcua::types::byte_string::ByteString) -> Self {
+        self.inner.manufacturer_data = value;
+        self
+    }
+    ///Set the `message` field.
+    pub fn message(mut self, value: opcua::types::localized_text::LocalizedText) -> Self {
+        self.inner.message = value;
+        self
+    }
+    ///Set the `help_text` field.
+    pub fn help_text(mut self, value: opcua::types::localized_text::LocalizedText) -> Self {
+        self.inner.help_text = value;
+        self
+    }
+    ///Build the value.
+    pub fn build(self) -> PnDeviceDiagnosisDataType {
+        self.inner
+    }
+}
 #[opcua::types::ua_encodable]
 ///https://reference.opcfoundation.org/v104/PROFINET/v101/docs/6.3.3/#6.3.3.2.1
 #[derive(Debug, Clone, PartialEq, Default)]
@@ -82,6 +185,33 @@ impl opcua::types::ExpandedMessageInfo for PnDeviceRoleOptionSet {
         opcua::types::ExpandedNodeId::from((id, "http://opcfoundation.org/UA/PROFINET/"))
     }
 }
+impl PnDeviceRoleOptionSet {
+    ///Create a builder for `PnDeviceRoleOptionSet`.
+    pub fn builder() -> PnDeviceRoleOptionSetBuilder {
+        PnDeviceRoleOptionSetBuilder::default()
+    }
+}
+///Builder for [`PnDeviceRoleOptionSet`]. Fields that are not set are given their default value.
+#[derive(Debug, Clone, Default)]
+pub struct PnDeviceRoleOptionSetBuilder {
+    inner: PnDeviceRoleOptionSet,
+}
+impl PnDeviceRoleOptionSetBuilder {
+    ///Set the `value` field.
+    pub fn value(mut self, value: opcua::types::byte_string::ByteString) -> Self {
+        self.inner.value = value;
+        self
+    }
+    ///Set the `valid_bits` field.
+    pub fn valid_bits(mut self, value: opcua::types::byte_string::ByteString) -> Self {
+        self.inner.valid_bits = value;
+        self
+    }
+    ///Build the value.
+    pub fn build(self) -> PnDeviceRoleOptionSet {
+        self.inner
+    }
+}
 #[opcua::types::ua_encodable]
 ///https://reference.opcfoundation.org/v104/PROFINET/v101/docs/6.3.3/#6.3.3.1.2
 #[derive(Debug, Clone, PartialEq, Default)]
@@ -117,3 +247,50 @@ impl opcua::types::ExpandedMessageInfo for PnIM5DataType {
         opcua::types::ExpandedNodeId::from((id, "http://opcfoundation.org/UA/PROFINET/"))
     }
 }
+impl PnIM5DataType {
+    ///Create a builder for `PnIM5DataType`.
+    pub fn builder() -> PnIM5DataTypeBuilder {
+        PnIM5DataTypeBuilder::default()
+    }
+}
+///Builder for [`PnIM5DataType`]. Fields that are not set are given their default value.
+#[derive(Debug, Clone, Default)]
+pub struct PnIM5DataTypeBuilder {
+    inner: PnIM5DataType,
+}
+impl PnIM5DataTypeBuilder {
+    ///Set the `annotation` field.
+    pub fn annotation(mut self, value: opcua::types::string::UAString) -> Self {
+        self.inner.annotation = value;
+        self
+    }
+    ///Set the `order_id` field.
+    pub fn order_id(mut self, value: opcua::types::string::UAString) -> Self {
+        self.inner.order_id = value;
+        self
+    }
+    ///Set the `vendor_id` field.
+    pub fn vendor_id(mut self, value: u16) -> Self {
+        self.inner.vendor_id = value;
+        self
+    }
+    ///Set the `serial_number` field.
+    pub fn serial_number(mut self, value: opcua::types::string::UAString) -> Self {
+        self.inner.serial_number = value;
+        self
+    }
+    ///Set the `hardware_revision` field.
+    pub fn hardware_revision(mut self, value: opcua::types::string::UAString) -> Self {
+        self.inner.hardware_revision = value;
+        self
+    }
+    ///Set the `software_revision` field.
+    pub fn software_revision(mut self, value: opcua::types::string::UAString) -> Self {
+        self.inner.software_revision = value;
+        self
+    }
+    ///Build the value.
+    pub fn build(self) -> PnIM5DataType {
+        self.inner
+    }
+}
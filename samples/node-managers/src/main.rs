@@ -36,10 +36,14 @@ async fn main() {
     let mut sim = Simulation::new();
 
     // Add some initial nodes. We can add more later.
+    // The sine and cosine tags keep the last 100 values (about 10 seconds)
+    // in memory, which can be read using the HistoryRead service.
     sim.add_tag("sine", "Sine 1", "Sine wave", SineValue::default())
         .add_metadata("meta 1", "value 1")
-        .add_metadata("stuff", "things");
-    sim.add_tag("cos", "Cos 1", "Cosine wave", CosValue::default());
+        .add_metadata("stuff", "things")
+        .historize(100);
+    sim.add_tag("cos", "Cos 1", "Cosine wave", CosValue::default())
+        .historize(100);
     sim.add_tag("time", "Time 1", "Linear time", JustLinearTime::default())
         .add_metadata("Hello", "there");
 
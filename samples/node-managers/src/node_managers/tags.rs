@@ -2,22 +2,24 @@ use std::{collections::VecDeque, sync::Arc};
 
 use async_trait::async_trait;
 use opcua::{
-    nodes::{AccessLevel, DefaultTypeTree, ReferenceDirection},
+    nodes::{AccessLevel, DefaultTypeTree, ReferenceDirection, TypeTree},
     server::{
         diagnostics::NamespaceMetadata,
         node_manager::{
             as_opaque_node_id, from_opaque_node_id, impl_translate_browse_paths_using_browse,
-            AddReferenceResult, BrowseNode, BrowsePathItem, ExternalReference,
-            ExternalReferenceRequest, NodeManager, NodeManagerBuilder, NodeMetadata,
-            ParsedReadValueId, ReadNode, RequestContext, ServerContext,
+            AddReferenceResult, BrowseCursor, BrowseNode, BrowsePathItem, ExternalReference,
+            ExternalReferenceRequest, HistoryNode, NodeManager, NodeManagerBuilder, NodeMetadata,
+            ParsedReadValueId, ParsedWriteValue, ReadNode, RequestContext, ServerContext,
+            WriteNode,
         },
-        CreateMonitoredItem,
+        ContinuationPoint, CreateMonitoredItem,
     },
     sync::RwLock,
     types::{
-        AccessLevelExType, DataTypeId, DataValue, IdType, Identifier, LocalizedText, NodeClass,
-        NodeId, QualifiedName, ReferenceDescription, ReferenceTypeId, StatusCode,
-        TimestampsToReturn, VariableTypeId, Variant, WriteMask,
+        AccessLevelExType, AttributeId, DataTypeId, DataValue, DateTime, HistoryData, IdType,
+        Identifier, LocalizedText, NodeClass, NodeId, QualifiedName, ReadRawModifiedDetails,
+        ReferenceDescription, ReferenceTypeId, StatusCode, TimestampsToReturn, VariableTypeId,
+        Variant, WriteMask,
     },
 };
 use serde::{Deserialize, Serialize};
@@ -170,6 +172,47 @@ impl NodeManager for TagNodeManager {
         Ok(())
     }
 
+    async fn write(
+        &self,
+        context: &RequestContext,
+        nodes_to_write: &mut [&mut WriteNode],
+    ) -> Result<(), StatusCode> {
+        // Write sets attribute values for nodes owned by this node manager.
+        // Here we only allow writing the value of tags, everything else is read-only.
+        let mut written = Vec::new();
+        {
+            let mut sim = self.sim.write();
+            let type_tree = context.type_tree.read();
+
+            for node in nodes_to_write {
+                match self.write_node(&mut sim, &type_tree, node.value()) {
+                    Ok(()) => {
+                        node.set_status(StatusCode::Good);
+                        written.push(node.value().node_id.clone());
+                    }
+                    Err(e) => node.set_status(e),
+                }
+            }
+        }
+
+        // Notify any subscriptions on the written nodes. Make sure to do this
+        // after releasing the write lock on the simulation, since sampling the value
+        // requires a read lock.
+        let sim = self.sim.read();
+        context.subscriptions.maybe_notify(
+            written.iter().map(|n| (n, AttributeId::Value)),
+            |id, _, _, _| {
+                let Identifier::String(s) = &id.identifier else {
+                    return None;
+                };
+                let tag = sim.get_tag_meta(s.as_ref())?;
+                Some(DataValue::new_at(tag.get_value(), tag.modified_time))
+            },
+        );
+
+        Ok(())
+    }
+
     async fn translate_browse_paths_to_node_ids(
         &self,
         context: &RequestContext,
@@ -208,6 +251,36 @@ impl NodeManager for TagNodeManager {
 
         Ok(())
     }
+
+    async fn history_read_raw_modified(
+        &self,
+        _context: &RequestContext,
+        details: &ReadRawModifiedDetails,
+        nodes: &mut [&mut HistoryNode],
+        _timestamps_to_return: TimestampsToReturn,
+    ) -> Result<(), StatusCode> {
+        // History read is split into one method per type of history details.
+        // We only keep raw values for some tags, so this is the only one we implement,
+        // and there are never any modified values.
+        if details.is_read_modified {
+            return Err(StatusCode::BadHistoryOperationUnsupported);
+        }
+
+        let sim = self.sim.read();
+        for node in nodes {
+            match self.history_read_node(&sim, details, node) {
+                Ok(values) => {
+                    node.set_result(HistoryData {
+                        data_values: Some(values),
+                    });
+                    node.set_status(StatusCode::Good);
+                }
+                Err(e) => node.set_status(e),
+            }
+        }
+
+        Ok(())
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -224,12 +297,21 @@ enum ParsedNodeId {
 // In custom node managers we need to handle browse continuation. In this
 // case we're going to be lazy and simply keep a queue of yet-to-be-submitted nodes.
 // If the potential number of nodes is very high this may not be a good idea, in which
-// case you need some smarter cursoring scheme.
+// case you need some smarter cursoring scheme, like the `BrowseCursor` used when browsing
+// the root node.
 #[derive(Default)]
 struct BrowseContinuationPoint {
     nodes: VecDeque<ReferenceDescription>,
 }
 
+// History continuation points work the same way as browse continuation points,
+// the server stores them and gives them back to us on the next request.
+// Our history buffer drops old values as new ones arrive, so we store the timestamp
+// of the next value to return, rather than an index into the buffer.
+struct HistoryContinuationPoint {
+    next: DateTime,
+}
+
 impl TagNodeManager {
     fn parse_node_id(&self, id: &NodeId) -> Option<ParsedNodeId> {
         if id.namespace != self.namespace.namespace_index {
@@ -290,7 +372,14 @@ impl TagNodeManager {
         // Browse is unique in that it gets nodes that the active node manager does _not_ own.
         // We can use this to return all our tags, by handling browse for the root node.
 
-        let mut cp = BrowseContinuationPoint::default();
+        // There may be a very large number of tags, so instead of storing the remaining
+        // references in the continuation point, we store a cursor with the last tag we
+        // returned. Tags are sorted, so when the client calls BrowseNext we can seek
+        // directly to the next one, even if tags were added or removed in the meantime.
+        let mut cursor = node_to_browse
+            .take_continuation_point::<BrowseCursor>()
+            .map(|c| *c)
+            .unwrap_or_default();
 
         // In this case, we only need to care about references that _we_ own, so we don't need to
         // return inverse references, or type definition references for the root node.
@@ -298,21 +387,24 @@ impl TagNodeManager {
             && node_to_browse.allows_reference_type(&ReferenceTypeId::Organizes.into(), type_tree)
             && node_to_browse.allows_node_class(NodeClass::Variable)
         {
-            for tag in sim.iter_tag_meta() {
+            let last_tag = match cursor.last_node_id().map(|id| &id.identifier) {
+                Some(Identifier::String(s)) => Some(s.as_ref()),
+                _ => None,
+            };
+            for tag in sim.iter_tag_meta_after(last_tag) {
                 let meta = self.get_node_metadata_tag(&tag);
+                let node_id = meta.node_id.node_id.clone();
 
-                if let AddReferenceResult::Full(c) = node_to_browse.add(
+                if let AddReferenceResult::Full(_) = node_to_browse.add(
                     type_tree,
                     meta.into_ref_desc(true, ReferenceTypeId::Organizes),
                 ) {
-                    cp.nodes.push_back(c);
+                    node_to_browse.set_next_continuation_point(Box::new(cursor));
+                    return;
                 }
+                cursor.advance(&node_id);
             }
         }
-
-        if !cp.nodes.is_empty() {
-            node_to_browse.set_next_continuation_point(Box::new(cp));
-        }
     }
 
     fn browse_node(
@@ -409,7 +501,7 @@ impl TagNodeManager {
                 let Some(tag) = sim.get_tag_meta(&tag_meta_id.tag) else {
                     return Err(StatusCode::BadNodeIdUnknown);
                 };
-                if !tag.metadata.contains_key(&tag_meta_id.tag) {
+                if !tag.metadata.contains_key(&tag_meta_id.meta) {
                     return Err(StatusCode::BadNodeIdUnknown);
                 }
 
@@ -427,7 +519,7 @@ impl TagNodeManager {
 
                     if let AddReferenceResult::Full(c) = node_to_browse.add(
                         type_tree,
-                        meta.into_ref_desc(true, ReferenceTypeId::HasProperty),
+                        meta.into_ref_desc(false, ReferenceTypeId::HasProperty),
                     ) {
                         cp.nodes.push_back(c);
                     }
@@ -455,6 +547,112 @@ impl TagNodeManager {
         Ok(())
     }
 
+    fn history_read_node(
+        &self,
+        sim: &Simulation,
+        details: &ReadRawModifiedDetails,
+        node: &mut HistoryNode,
+    ) -> Result<Vec<DataValue>, StatusCode> {
+        let Some(id) = self.parse_node_id(node.node_id()) else {
+            return Err(StatusCode::BadNodeIdUnknown);
+        };
+        let ParsedNodeId::Tag(t) = id else {
+            return Err(StatusCode::BadHistoryOperationUnsupported);
+        };
+        let Some(tag) = sim.get_tag_meta(&t) else {
+            return Err(StatusCode::BadNodeIdUnknown);
+        };
+        let Some(history) = tag.history else {
+            return Err(StatusCode::BadHistoryOperationUnsupported);
+        };
+
+        // Values are returned in reverse order if the start time is after the end time,
+        // or if only the end time is given.
+        let start = (!details.start_time.is_null()).then_some(details.start_time);
+        let end = (!details.end_time.is_null()).then_some(details.end_time);
+        let is_forward = match (start, end) {
+            (Some(start), Some(end)) => start <= end,
+            (None, Some(_)) => false,
+            _ => true,
+        };
+
+        // If this is a continuation of an earlier read, resume from where it left off.
+        let start = match node.continuation_point() {
+            Some(cp) => {
+                let Some(cp) = cp.get::<HistoryContinuationPoint>() else {
+                    return Err(StatusCode::BadContinuationPointInvalid);
+                };
+                Some(cp.next)
+            }
+            None => start,
+        };
+
+        let (values, next) =
+            read_raw_history(history, start, end, is_forward, details.num_values_per_node);
+        if let Some(next) = next {
+            node.set_next_continuation_point(Some(ContinuationPoint::new(Box::new(
+                HistoryContinuationPoint { next },
+            ))));
+        }
+
+        Ok(values)
+    }
+
+    fn write_node(
+        &self,
+        sim: &mut Simulation,
+        type_tree: &DefaultTypeTree,
+        node: &ParsedWriteValue,
+    ) -> Result<(), StatusCode> {
+        let Some(id) = self.parse_node_id(&node.node_id) else {
+            return Err(StatusCode::BadNodeIdUnknown);
+        };
+
+        let tag = match id {
+            ParsedNodeId::Tag(t) => t,
+            // Metadata is defined by the underlying system, clients can't change it.
+            ParsedNodeId::Meta(tag_meta_id) => {
+                let Some(tag) = sim.get_tag_meta(&tag_meta_id.tag) else {
+                    return Err(StatusCode::BadNodeIdUnknown);
+                };
+                if !tag.metadata.contains_key(&tag_meta_id.meta) {
+                    return Err(StatusCode::BadNodeIdUnknown);
+                }
+                return Err(StatusCode::BadNotWritable);
+            }
+        };
+
+        let Some(meta) = sim.get_tag_meta(&tag) else {
+            return Err(StatusCode::BadNodeIdUnknown);
+        };
+        if node.attribute_id != AttributeId::Value {
+            return Err(StatusCode::BadNotWritable);
+        }
+        // Tags are scalar, so writing to an index range makes no sense.
+        if node.index_range.has_range() {
+            return Err(StatusCode::BadIndexRangeInvalid);
+        }
+
+        // Check that the value matches the data type of the tag. We allow writing
+        // subtypes of the tag data type, just like the in-memory node managers.
+        let Some(value) = node.value.value.clone() else {
+            return Err(StatusCode::BadTypeMismatch);
+        };
+        let Some(value_type) = value.data_type().and_then(|t| {
+            t.try_resolve(type_tree.namespaces())
+                .map(|t| t.into_owned())
+        }) else {
+            return Err(StatusCode::BadTypeMismatch);
+        };
+        if value.is_array() || !type_tree.is_subtype_of(&value_type, &meta.value.data_type().into())
+        {
+            return Err(StatusCode::BadTypeMismatch);
+        }
+
+        sim.write_tag_value(&tag, value);
+        Ok(())
+    }
+
     fn read_node(
         &self,
         sim: &Simulation,
@@ -488,25 +686,34 @@ impl TagNodeManager {
                     }
                     opcua::types::AttributeId::WriteMask => WriteMask::empty().bits().into(),
                     opcua::types::AttributeId::UserWriteMask => WriteMask::empty().bits().into(),
-                    opcua::types::AttributeId::Value => tag.value.get_value(),
+                    opcua::types::AttributeId::Value => tag.get_value(),
                     opcua::types::AttributeId::DataType => {
                         NodeId::from(tag.value.data_type()).into()
                     }
                     opcua::types::AttributeId::ValueRank => (-1i32).into(),
-                    opcua::types::AttributeId::AccessLevel => {
-                        AccessLevel::CURRENT_READ.bits().into()
-                    }
-                    opcua::types::AttributeId::UserAccessLevel => {
-                        AccessLevel::CURRENT_READ.bits().into()
+                    // Tags can be written, see `write_node`, and some keep history,
+                    // see `history_read_node`.
+                    opcua::types::AttributeId::AccessLevel
+                    | opcua::types::AttributeId::UserAccessLevel => {
+                        let mut level = AccessLevel::CURRENT_READ | AccessLevel::CURRENT_WRITE;
+                        if tag.history.is_some() {
+                            level |= AccessLevel::HISTORY_READ;
+                        }
+                        level.bits().into()
                     }
                     opcua::types::AttributeId::MinimumSamplingInterval => 0f64.into(),
-                    opcua::types::AttributeId::Historizing => false.into(),
+                    opcua::types::AttributeId::Historizing => tag.history.is_some().into(),
                     opcua::types::AttributeId::AccessLevelEx => {
                         // TODO: The type here is wrong, bug in the codegen?
                         // Looks like we generate bitfields as i32, even if they inherit from UInt32.
                         // I think it's because BSD files don't distinguish between the two.
                         // Fixable now that we use NodeSet2 files.
-                        (AccessLevelExType::CurrentRead.bits() as u32).into()
+                        let mut level =
+                            AccessLevelExType::CurrentRead | AccessLevelExType::CurrentWrite;
+                        if tag.history.is_some() {
+                            level |= AccessLevelExType::HistoryRead;
+                        }
+                        (level.bits() as u32).into()
                     }
                     _ => return Err(StatusCode::BadAttributeIdInvalid),
                 };
@@ -556,3 +763,131 @@ impl TagNodeManager {
         }
     }
 }
+
+// Read the raw values in `history` from `start` to `end`, in reverse order if `is_forward`
+// is false. The start time is included in the range, the end time is not. At most
+// `max_values` values are returned, or all of them if it is 0, along with the timestamp
+// of the next value in the range, if there are more.
+fn read_raw_history(
+    history: &VecDeque<DataValue>,
+    start: Option<DateTime>,
+    end: Option<DateTime>,
+    is_forward: bool,
+    max_values: u32,
+) -> (Vec<DataValue>, Option<DateTime>) {
+    let in_range = |v: &&DataValue| {
+        let ts = v.source_timestamp.unwrap_or_default();
+        let after_start = match start {
+            Some(start) if is_forward => ts >= start,
+            Some(start) => ts <= start,
+            None => true,
+        };
+        let before_end = match end {
+            Some(end) if is_forward => ts < end,
+            Some(end) => ts > end,
+            None => true,
+        };
+        after_start && before_end
+    };
+
+    // Read one more value than requested, to know whether there are more.
+    let limit = match max_values {
+        0 => usize::MAX,
+        n => n as usize + 1,
+    };
+    let mut values: Vec<_> = if is_forward {
+        history
+            .iter()
+            .filter(in_range)
+            .take(limit)
+            .cloned()
+            .collect()
+    } else {
+        history
+            .iter()
+            .rev()
+            .filter(in_range)
+            .take(limit)
+            .cloned()
+            .collect()
+    };
+
+    let next = if values.len() == limit {
+        values.pop().and_then(|v| v.source_timestamp)
+    } else {
+        None
+    };
+    (values, next)
+}
+
+#[cfg(test)]
+mod tests {
+    use opcua::types::{DataValue, DateTime, Variant};
+
+    use super::read_raw_history;
+    use crate::sim::{gen::SineValue, Simulation, TagRef};
+
+    fn at(second: u16) -> DateTime {
+        DateTime::ymd_hms(2024, 1, 1, 0, 0, second)
+    }
+
+    // A simulation with a sine tag that keeps 5 values, ticked once per second from
+    // second 0 to 7, so that the history holds the values from second 3 to 7.
+    fn simulation() -> Simulation {
+        let mut sim = Simulation::new();
+        sim.add_tag("sine", "Sine 1", "Sine wave", SineValue::default())
+            .historize(5);
+        for tick in 0..8 {
+            sim.tick_at(tick, at(tick as u16));
+        }
+        sim
+    }
+
+    fn seconds(values: &[DataValue]) -> Vec<DateTime> {
+        values.iter().map(|v| v.source_timestamp.unwrap()).collect()
+    }
+
+    #[test]
+    fn sine_history_time_range() {
+        let sim = simulation();
+        let history = sim.get_tag_meta("sine").unwrap().history.unwrap();
+
+        // Values older than the buffer are dropped.
+        let (values, next) = read_raw_history(history, Some(at(0)), None, true, 0);
+        assert_eq!(seconds(&values), [3, 4, 5, 6, 7].map(at));
+        assert_eq!(next, None);
+        assert_eq!(
+            values[0].value,
+            Some(Variant::Double((3.0f64 / 50.0).sin()))
+        );
+
+        // The start time is included, the end time is not.
+        let (values, _) = read_raw_history(history, Some(at(4)), Some(at(6)), true, 0);
+        assert_eq!(seconds(&values), [4, 5].map(at));
+
+        // Reading backward starts at the start time, which is the later of the two.
+        let (values, _) = read_raw_history(history, Some(at(6)), Some(at(3)), false, 0);
+        assert_eq!(seconds(&values), [6, 5, 4].map(at));
+    }
+
+    #[test]
+    fn sine_history_paged() {
+        let sim = simulation();
+        let history = sim.get_tag_meta("sine").unwrap().history.unwrap();
+
+        // Each page returns the timestamp of the next value to read.
+        let (values, next) = read_raw_history(history, Some(at(3)), Some(at(7)), true, 2);
+        assert_eq!(seconds(&values), [3, 4].map(at));
+        assert_eq!(next, Some(at(5)));
+        let (values, next) = read_raw_history(history, next, Some(at(7)), true, 2);
+        assert_eq!(seconds(&values), [5, 6].map(at));
+        assert_eq!(next, None);
+
+        let (values, next) = read_raw_history(history, Some(at(7)), None, false, 3);
+        assert_eq!(seconds(&values), [7, 6, 5].map(at));
+        assert_eq!(next, Some(at(4)));
+        let (values, next) = read_raw_history(history, next, None, false, 3);
+        assert_eq!(seconds(&values), [4, 3].map(at));
+        assert_eq!(next, None);
+    }
+}
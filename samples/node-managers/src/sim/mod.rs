@@ -4,9 +4,12 @@
 
 pub mod gen;
 
-use std::collections::HashMap;
+use std::{
+    collections::{BTreeMap, HashMap, VecDeque},
+    ops::Bound,
+};
 
-use opcua::types::{DataTypeId, DateTime, Variant};
+use opcua::types::{DataTypeId, DataValue, DateTime, Variant};
 
 pub trait Generator {
     fn tick(&mut self, time: u64);
@@ -22,10 +25,42 @@ struct Tag {
     description: String,
     metadata: HashMap<String, String>,
     modified_time: DateTime,
+    // Value written by a client. This overrides the generated value,
+    // like a setpoint in a real system.
+    written_value: Option<Variant>,
+    // Recent values of the tag, if it is historized.
+    history: Option<History>,
+}
+
+// A short rolling buffer of historical values. A real system would
+// keep its history in some external store, this is just for demonstration.
+struct History {
+    values: VecDeque<DataValue>,
+    capacity: usize,
+}
+
+impl History {
+    fn push(&mut self, value: DataValue) {
+        if self.values.len() >= self.capacity {
+            self.values.pop_front();
+        }
+        self.values.push_back(value);
+    }
+}
+
+impl Tag {
+    fn value(&self) -> Variant {
+        self.written_value
+            .clone()
+            .unwrap_or_else(|| self.value.get_value())
+    }
 }
 
 pub trait TagRef {
     fn add_metadata(self, key: impl Into<String>, value: impl Into<String>) -> Self;
+
+    /// Keep the last `capacity` values of the tag in memory.
+    fn historize(self, capacity: usize) -> Self;
 }
 
 impl TagRef for &mut Tag {
@@ -33,6 +68,14 @@ impl TagRef for &mut Tag {
         self.metadata.insert(key.into(), value.into());
         self
     }
+
+    fn historize(self, capacity: usize) -> Self {
+        self.history = Some(History {
+            values: VecDeque::new(),
+            capacity: capacity.max(1),
+        });
+        self
+    }
 }
 
 pub struct TagMeta<'a> {
@@ -41,11 +84,23 @@ pub struct TagMeta<'a> {
     pub tag: &'a str,
     pub metadata: &'a HashMap<String, String>,
     pub value: &'a dyn Generator,
+    pub written_value: Option<&'a Variant>,
     pub modified_time: DateTime,
+    /// Historical values, in chronological order, if the tag is historized.
+    pub history: Option<&'a VecDeque<DataValue>>,
+}
+
+impl TagMeta<'_> {
+    pub fn get_value(&self) -> Variant {
+        self.written_value
+            .cloned()
+            .unwrap_or_else(|| self.value.get_value())
+    }
 }
 
 pub struct Simulation {
-    tags: HashMap<String, Tag>,
+    // Sorted, so that tags can be browsed in a stable order.
+    tags: BTreeMap<String, Tag>,
     last_tick: u64,
     last_tick_timestamp: DateTime,
 }
@@ -53,7 +108,7 @@ pub struct Simulation {
 impl Simulation {
     pub fn new() -> Self {
         Self {
-            tags: HashMap::new(),
+            tags: BTreeMap::new(),
             last_tick: 0,
             last_tick_timestamp: DateTime::now(),
         }
@@ -68,21 +123,46 @@ impl Simulation {
     }
 
     pub fn tick(&mut self, time: u64) {
+        self.tick_at(time, DateTime::now());
+    }
+
+    /// Tick the simulation, recording history with the given timestamp.
+    pub fn tick_at(&mut self, time: u64, timestamp: DateTime) {
         self.last_tick = time;
-        self.last_tick_timestamp = DateTime::now();
+        self.last_tick_timestamp = timestamp;
         for tag in self.tags.values_mut() {
             tag.value.tick(time);
+            let value = tag.value();
+            if let Some(history) = &mut tag.history {
+                history.push(DataValue::new_at(value, self.last_tick_timestamp));
+            }
         }
     }
 
     pub fn iter_tag_meta(&self) -> impl Iterator<Item = TagMeta<'_>> + '_ {
-        self.tags.iter().map(|(k, v)| TagMeta {
+        self.iter_tag_meta_after(None)
+    }
+
+    // Iterate over the tags sorted after `after`, or all tags if it is `None`.
+    pub fn iter_tag_meta_after<'a>(
+        &'a self,
+        after: Option<&str>,
+    ) -> impl Iterator<Item = TagMeta<'a>> + 'a {
+        let range = match after {
+            Some(tag) => self
+                .tags
+                .range::<str, _>((Bound::Excluded(tag), Bound::Unbounded)),
+            None => self.tags.range::<str, _>(..),
+        };
+        range.map(|(k, v)| TagMeta {
             name: &v.name,
             description: &v.description,
             tag: k.as_str(),
             metadata: &v.metadata,
             value: &*v.value,
+            written_value: v.written_value.as_ref(),
             modified_time: v.modified_time,
+            history: v.history.as_ref().map(|h| &h.values),
         })
     }
 
@@ -93,7 +173,9 @@ impl Simulation {
             tag: id,
             metadata: &v.metadata,
             value: &*v.value,
+            written_value: v.written_value.as_ref(),
             modified_time: v.modified_time,
+            history: v.history.as_ref().map(|h| &h.values),
         })
     }
 
@@ -113,6 +195,8 @@ impl Simulation {
                 description: description.into(),
                 metadata: HashMap::new(),
                 modified_time: DateTime::now(),
+                written_value: None,
+                history: None,
             },
         );
         let t = self.tags.get_mut(&id).unwrap();
@@ -130,6 +214,17 @@ impl Simulation {
     }
 
     pub fn get_tag_value(&self, tag: &str) -> Option<Variant> {
-        self.tags.get(tag).map(|t| t.value.get_value())
+        self.tags.get(tag).map(|t| t.value())
+    }
+
+    /// Write a value to the tag, overriding the generated value.
+    /// Returns `false` if the tag does not exist.
+    pub fn write_tag_value(&mut self, tag: &str, value: Variant) -> bool {
+        let Some(t) = self.tags.get_mut(tag) else {
+            return false;
+        };
+        t.written_value = Some(value);
+        t.modified_time = DateTime::now();
+        true
     }
 }
@@ -68,7 +68,8 @@ async fn main() {
             warn!("Failed to register CTRL-C handler: {e}");
             return;
         }
-        handle_c.cancel();
+        // Let clients finish ongoing requests and notify them that the server is stopping.
+        handle_c.shutdown(Duration::from_secs(5));
     });
 
     // Run the server. This does not ordinarily exit so you must Ctrl+C to terminate